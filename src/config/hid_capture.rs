@@ -0,0 +1,411 @@
+//! Module for building capability map skeletons from hid-recorder captures.
+//! New devices can be enabled by recording their HID reports with
+//! hid-recorder while pressing each input in turn. The fields that change in
+//! the capture are identified so they can be labeled with the capability they
+//! should emit, and a capability map skeleton is generated from the labels.
+
+use std::{collections::HashSet, error::Error, fmt::Display};
+
+use crate::input::capability::{Capability, Gamepad, Mouse};
+
+/// Fields whose byte takes more distinct values than this are treated as an
+/// axis or trigger instead of a set of buttons
+const AXIS_MIN_VALUES: usize = 8;
+
+/// Fields that change in at least this fraction of reports are treated as a
+/// counter or timestamp and ignored
+const COUNTER_MIN_CHANGE_RATIO: f64 = 0.9;
+
+/// Minimum number of reports before fields can be treated as counters
+const COUNTER_MIN_REPORTS: usize = 10;
+
+/// A single input report from a hid-recorder capture
+#[derive(Debug, Clone, PartialEq)]
+pub struct HidReport {
+    /// Time of the report in seconds since the recording started
+    pub time: f64,
+    /// Report data, including the report id if the device uses report ids
+    pub data: Vec<u8>,
+}
+
+/// A device recording in the format written by hid-recorder. Only the first
+/// device of a recording is read.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HidCapture {
+    pub name: String,
+    pub vendor_id: u16,
+    pub product_id: u16,
+    /// Whether the report descriptor defines report ids, in which case the
+    /// first byte of each report is its report id
+    pub uses_report_ids: bool,
+    pub reports: Vec<HidReport>,
+}
+
+impl HidCapture {
+    /// Load a [HidCapture] from the given hid-recorder file
+    pub fn from_file(path: &str) -> Result<HidCapture, Box<dyn Error>> {
+        let content = std::fs::read_to_string(path)?;
+        HidCapture::parse(content.as_str())
+    }
+
+    /// Parse a [HidCapture] from the given hid-recorder output. E.g.
+    /// "N: My Gamepad", "I: 3 045e 028e", "R: 2 85 01" and
+    /// "E: 000001.250000 3 01 00 80"
+    pub fn parse(content: &str) -> Result<HidCapture, Box<dyn Error>> {
+        let mut capture = HidCapture::default();
+        let mut device = None;
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim();
+            let Some((kind, value)) = line.split_once(':') else {
+                continue;
+            };
+            let mut values = value.split_whitespace();
+            let invalid = || format!("Invalid capture line {}: {line}", i + 1);
+
+            // Skip any devices after the first one
+            if kind == "D" {
+                let index = values.next().ok_or_else(invalid)?.to_string();
+                match device.as_ref() {
+                    None => device = Some(index),
+                    Some(first) if *first != index => break,
+                    Some(_) => (),
+                }
+                continue;
+            }
+
+            match kind {
+                "N" => capture.name = value.trim().to_string(),
+                "I" => {
+                    let ids: Vec<&str> = values.collect();
+                    let [_, vendor_id, product_id] = ids.as_slice() else {
+                        return Err(invalid().into());
+                    };
+                    capture.vendor_id =
+                        u16::from_str_radix(vendor_id, 16).map_err(|_| invalid())?;
+                    capture.product_id =
+                        u16::from_str_radix(product_id, 16).map_err(|_| invalid())?;
+                }
+                "R" => {
+                    let descriptor = parse_bytes(values.skip(1)).ok_or_else(invalid)?;
+                    capture.uses_report_ids = has_report_ids(&descriptor);
+                }
+                "E" => {
+                    let time = values.next().ok_or_else(invalid)?;
+                    let time: f64 = time.parse().map_err(|_| invalid())?;
+                    let data = parse_bytes(values.skip(1)).ok_or_else(invalid)?;
+                    capture.reports.push(HidReport { time, data });
+                }
+                _ => (),
+            }
+        }
+
+        if capture.reports.is_empty() {
+            return Err("Capture does not contain any input reports".into());
+        }
+        Ok(capture)
+    }
+
+    /// Returns the fields that changed during the capture in the order in
+    /// which they first changed
+    pub fn fields(&self) -> Vec<CaptureField> {
+        // Reports with different report ids have different layouts
+        let mut report_ids: Vec<Option<u8>> = Vec::new();
+        for report in self.reports.iter() {
+            let report_id = self.report_id(report);
+            if !report_ids.contains(&report_id) {
+                report_ids.push(report_id);
+            }
+        }
+
+        let mut fields = Vec::new();
+        for report_id in report_ids {
+            let reports: Vec<&HidReport> = self
+                .reports
+                .iter()
+                .filter(|report| self.report_id(report) == report_id)
+                .collect();
+            fields.extend(find_fields(report_id, reports.as_slice()));
+        }
+        fields.sort_by(|a, b| a.first_change.total_cmp(&b.first_change));
+        fields
+    }
+
+    /// Returns the report id of the given report
+    fn report_id(&self, report: &HidReport) -> Option<u8> {
+        if !self.uses_report_ids {
+            return None;
+        }
+        report.data.first().copied()
+    }
+}
+
+/// The type of a changing field in a report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    /// A single bit that is set and cleared, e.g. a button
+    Bit(u8),
+    /// A whole byte that takes many values, e.g. an axis or a trigger
+    Byte,
+}
+
+/// A field of a report that changed during a capture
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaptureField {
+    pub report_id: Option<u8>,
+    /// Offset of the byte in the report, including the report id
+    pub byte: usize,
+    pub kind: FieldKind,
+    /// Number of times a bit left its initial value, or the number of
+    /// distinct values of a byte
+    pub changes: usize,
+    /// Time in seconds the field first changed
+    pub first_change: f64,
+}
+
+impl CaptureField {
+    /// Returns a description of how the field changed. E.g. "pressed 3
+    /// times, first at 1.25s"
+    pub fn describe(&self) -> String {
+        match self.kind {
+            FieldKind::Bit(_) => format!(
+                "pressed {} time(s), first at {:.2}s",
+                self.changes, self.first_change
+            ),
+            FieldKind::Byte => format!(
+                "{} distinct values, first changed at {:.2}s (axis or trigger)",
+                self.changes, self.first_change
+            ),
+        }
+    }
+}
+
+impl Display for CaptureField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(report_id) = self.report_id {
+            write!(f, "report 0x{report_id:02x}, ")?;
+        }
+        write!(f, "byte {}", self.byte)?;
+        if let FieldKind::Bit(bit) = self.kind {
+            write!(f, ", bit {bit}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the fields that changed in the given reports with the same layout
+fn find_fields(report_id: Option<u8>, reports: &[&HidReport]) -> Vec<CaptureField> {
+    let Some(baseline) = reports.first() else {
+        return vec![];
+    };
+    let len = reports
+        .iter()
+        .map(|report| report.data.len())
+        .max()
+        .unwrap_or_default();
+    let start = if report_id.is_some() { 1 } else { 0 };
+
+    let mut fields = Vec::new();
+    for byte in start..len {
+        let values: Vec<(f64, u8)> = reports
+            .iter()
+            .map(|report| (report.time, report.data.get(byte).copied().unwrap_or(0)))
+            .collect();
+        let initial = values[0].1;
+        let Some(first_change) = values.iter().find(|(_, v)| *v != initial).map(|(t, _)| *t) else {
+            continue;
+        };
+
+        // Skip counters and timestamps that change with nearly every report
+        let change_count = values.windows(2).filter(|w| w[0].1 != w[1].1).count();
+        if values.len() >= COUNTER_MIN_REPORTS
+            && change_count as f64 >= (values.len() - 1) as f64 * COUNTER_MIN_CHANGE_RATIO
+        {
+            log::debug!("Skipping byte {byte} that changes with every report");
+            continue;
+        }
+
+        let distinct: HashSet<u8> = values.iter().map(|(_, v)| *v).collect();
+        if distinct.len() > AXIS_MIN_VALUES {
+            fields.push(CaptureField {
+                report_id,
+                byte,
+                kind: FieldKind::Byte,
+                changes: distinct.len(),
+                first_change,
+            });
+            continue;
+        }
+
+        for bit in 0..8 {
+            let mask = 1 << bit;
+            let initial_bit = baseline.data.get(byte).copied().unwrap_or(0) & mask;
+            let mut presses = 0;
+            let mut first_press = None;
+            let mut pressed = false;
+            for (time, value) in values.iter() {
+                let is_pressed = value & mask != initial_bit;
+                if is_pressed && !pressed {
+                    presses += 1;
+                    first_press.get_or_insert(*time);
+                }
+                pressed = is_pressed;
+            }
+            let Some(first_change) = first_press else {
+                continue;
+            };
+            fields.push(CaptureField {
+                report_id,
+                byte,
+                kind: FieldKind::Bit(bit),
+                changes: presses,
+                first_change,
+            });
+        }
+    }
+
+    fields
+}
+
+/// Parse the given hex bytes. E.g. ["05", "01"]
+fn parse_bytes<'a>(values: impl Iterator<Item = &'a str>) -> Option<Vec<u8>> {
+    values
+        .map(|value| u8::from_str_radix(value, 16).ok())
+        .collect()
+}
+
+/// Returns true if the given report descriptor defines any report ids
+pub fn has_report_ids(descriptor: &[u8]) -> bool {
+    const LONG_ITEM: u8 = 0xfe;
+    const REPORT_ID: u8 = 0x84;
+    let mut i = 0;
+    while i < descriptor.len() {
+        let prefix = descriptor[i];
+        if prefix == LONG_ITEM {
+            let size = descriptor.get(i + 1).copied().unwrap_or(0) as usize;
+            i += 3 + size;
+            continue;
+        }
+        if prefix & 0xfc == REPORT_ID {
+            return true;
+        }
+        let size = match prefix & 0x03 {
+            3 => 4,
+            size => size as usize,
+        };
+        i += 1 + size;
+    }
+    false
+}
+
+/// Returns the capability config for the given capability as YAML lines, or
+/// None if the capability cannot be used in a capability map
+pub fn capability_yaml(capability: &Capability) -> Option<Vec<String>> {
+    let lines = match capability {
+        Capability::Gamepad(gamepad) => match gamepad {
+            Gamepad::Button(button) => vec!["gamepad:".into(), format!("  button: {button}")],
+            Gamepad::Axis(axis) => vec![
+                "gamepad:".into(),
+                "  axis:".into(),
+                format!("    name: {axis}"),
+            ],
+            Gamepad::Trigger(trigger) => vec![
+                "gamepad:".into(),
+                "  trigger:".into(),
+                format!("    name: {trigger}"),
+            ],
+            Gamepad::Dial(dial) => vec!["gamepad:".into(), format!("  dial: {dial}")],
+            _ => return None,
+        },
+        Capability::Keyboard(key) => vec![format!("keyboard: {key}")],
+        Capability::Mouse(Mouse::Button(button)) => {
+            vec!["mouse:".into(), format!("  button: {button}")]
+        }
+        Capability::DBus(action) => vec![format!("dbus: {action}")],
+        Capability::Switch(switch) => vec![format!("switch: {switch}")],
+        _ => return None,
+    };
+    Some(lines)
+}
+
+/// Returns a version 2 capability map skeleton with a mapping for each of
+/// the given labeled fields. Each mapping maps the labeled capability to
+/// itself and notes the field it was identified in, so the target events can
+/// be edited to remap the inputs.
+pub fn capability_map_skeleton(
+    capture: &HidCapture,
+    id: &str,
+    fields: &[(CaptureField, Capability)],
+) -> Result<String, Box<dyn Error>> {
+    let name = serde_json::to_string(&capture.name)?;
+    let id = serde_json::to_string(id)?;
+    let mut lines = vec![
+        "# yaml-language-server: $schema=https://raw.githubusercontent.com/ShadowBlip/InputPlumber/main/rootfs/usr/share/inputplumber/schema/capability_map_v2.json".to_string(),
+        format!(
+            "# Generated from a hid-recorder capture of {} ({:04x}:{:04x}).",
+            capture.name, capture.vendor_id, capture.product_id
+        ),
+        "# Each mapping notes the report field its input was identified in. The".into(),
+        "# source device must emit the source capability for that field.".into(),
+        "".into(),
+        "# Schema version number".into(),
+        "version: 2".into(),
+        "".into(),
+        "# The type of configuration schema".into(),
+        "kind: CapabilityMap".into(),
+        "".into(),
+        "# Name for the device event map".into(),
+        format!("name: {name}"),
+        "".into(),
+        "# Unique identifier of the capability mapping".into(),
+        format!("id: {id}"),
+        "".into(),
+        "# List of mapped events that are activated by a specific set of activation keys."
+            .into(),
+    ];
+
+    if fields.is_empty() {
+        lines.push("mapping: []".into());
+    } else {
+        lines.push("mapping:".into());
+    }
+    for (field, capability) in fields {
+        let Some(config) = capability_yaml(capability) else {
+            return Err(format!("{capability} cannot be used in a capability map").into());
+        };
+        let name = capability.to_string();
+        let name = name.rsplit(':').next().unwrap_or(name.as_str());
+        lines.push(format!("  # {field}"));
+        lines.push(format!("  - name: {name}"));
+        lines.push("    source_events:".into());
+        for (i, line) in config.iter().enumerate() {
+            let prefix = if i == 0 { "      - " } else { "        " };
+            lines.push(format!("{prefix}{line}"));
+        }
+        lines.push("    target_event:".into());
+        for line in config.iter() {
+            lines.push(format!("      {line}"));
+        }
+    }
+
+    lines.push("".into());
+    lines.push("# List of events to filter from the source devices".into());
+    lines.push("filtered_events: []".into());
+    lines.push("".into());
+
+    Ok(lines.join("\n"))
+}
+
+/// Returns a capability map id derived from the given device name. E.g.
+/// "My Gamepad 2" becomes "my_gamepad_2"
+pub fn capability_map_id(name: &str) -> String {
+    let id: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let id: Vec<&str> = id.split('_').filter(|part| !part.is_empty()).collect();
+    if id.is_empty() {
+        return "device".to_string();
+    }
+    id.join("_")
+}
@@ -0,0 +1,125 @@
+use std::str::FromStr;
+
+use crate::{config::CapabilityMap, input::capability::Capability};
+
+use super::hid_capture::{
+    capability_map_id, capability_map_skeleton, has_report_ids, CaptureField, FieldKind, HidCapture,
+};
+
+const CAPTURE: &str = "# Test Gamepad
+D: 0
+R: 6 05 01 09 05 85 01
+N: Test Gamepad
+P: usb-0000:00:14.0-1/input0
+I: 3 045e 028e
+D: 0
+E: 000000.000000 3 01 00 80
+E: 000000.500000 3 01 01 80
+E: 000000.600000 3 01 00 80
+E: 000001.000000 3 01 04 80
+E: 000001.100000 3 01 00 80
+E: 000001.500000 3 01 01 80
+E: 000001.600000 3 01 00 80
+D: 1
+E: 000002.000000 3 01 ff 80
+";
+
+#[test]
+fn test_parse_capture() {
+    let capture = HidCapture::parse(CAPTURE).unwrap();
+    assert_eq!(capture.name, "Test Gamepad");
+    assert_eq!(capture.vendor_id, 0x045e);
+    assert_eq!(capture.product_id, 0x028e);
+    assert!(capture.uses_report_ids);
+    // Reports of other devices are ignored
+    assert_eq!(capture.reports.len(), 7);
+    assert_eq!(capture.reports[1].time, 0.5);
+    assert_eq!(capture.reports[1].data, vec![0x01, 0x01, 0x80]);
+
+    assert!(HidCapture::parse("N: Empty").is_err());
+    assert!(HidCapture::parse("E: 000000.000000 1 zz").is_err());
+}
+
+#[test]
+fn test_capture_fields() {
+    let capture = HidCapture::parse(CAPTURE).unwrap();
+    let fields = capture.fields();
+    assert_eq!(
+        fields,
+        vec![
+            CaptureField {
+                report_id: Some(1),
+                byte: 1,
+                kind: FieldKind::Bit(0),
+                changes: 2,
+                first_change: 0.5,
+            },
+            CaptureField {
+                report_id: Some(1),
+                byte: 1,
+                kind: FieldKind::Bit(2),
+                changes: 1,
+                first_change: 1.0,
+            },
+        ]
+    );
+    assert_eq!(fields[0].to_string(), "report 0x01, byte 1, bit 0");
+}
+
+#[test]
+fn test_capture_axis_field() {
+    let mut content = String::from("N: Stick\n");
+    let values = [0x80, 0x90, 0xa0, 0xb0, 0xc0, 0xd0, 0xe0, 0xf0, 0xff, 0x80];
+    for (i, value) in values.iter().enumerate() {
+        // Each value is held for two reports like a moving stick
+        for time in [i * 2, i * 2 + 1] {
+            content.push_str(format!("E: {time}.0 2 00 {value:02x}\n").as_str());
+        }
+    }
+    let capture = HidCapture::parse(content.as_str()).unwrap();
+    assert!(!capture.uses_report_ids);
+    let fields = capture.fields();
+    assert_eq!(fields.len(), 1);
+    assert_eq!(fields[0].byte, 1);
+    assert_eq!(fields[0].kind, FieldKind::Byte);
+}
+
+#[test]
+fn test_report_ids() {
+    assert!(has_report_ids(&[0x05, 0x01, 0x85, 0x01]));
+    assert!(!has_report_ids(&[0x05, 0x01, 0x09, 0x85]));
+}
+
+#[test]
+fn test_capability_map_skeleton() {
+    let capture = HidCapture::parse(CAPTURE).unwrap();
+    let fields = capture.fields();
+    let labeled = vec![
+        (
+            fields[0].clone(),
+            Capability::from_str("Gamepad:Button:South").unwrap(),
+        ),
+        (
+            fields[1].clone(),
+            Capability::from_str("Gamepad:Axis:LeftStick").unwrap(),
+        ),
+    ];
+    let id = capability_map_id(capture.name.as_str());
+    assert_eq!(id, "test_gamepad");
+
+    let skeleton = capability_map_skeleton(&capture, id.as_str(), &labeled).unwrap();
+    let map = CapabilityMap::_from_yaml(skeleton).unwrap();
+    assert_eq!(map.version, 2);
+    assert_eq!(map.name, "Test Gamepad");
+    assert_eq!(map.mapping.len(), 2);
+    assert_eq!(map.mapping[0].name, "South");
+    for (mapping, (_, capability)) in map.mapping.iter().zip(labeled.iter()) {
+        let source = Capability::from(mapping.source_events[0].clone());
+        let target = Capability::from(mapping.target_event.clone());
+        assert_eq!(&source, capability);
+        assert_eq!(&target, capability);
+    }
+
+    let skeleton = capability_map_skeleton(&capture, "empty", &[]).unwrap();
+    assert!(CapabilityMap::_from_yaml(skeleton).is_ok());
+}
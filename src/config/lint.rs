@@ -0,0 +1,216 @@
+//! Module for detecting ambiguous [CompositeDeviceConfig] definitions. Source
+//! devices can only be managed by a single composite device, so if multiple
+//! configs match the same device, only the first matching config will be used.
+
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use ::procfs::CpuInfo;
+use glob_match::glob_match;
+
+use crate::dmi::data::DMIData;
+
+use super::{load_device_config_files, CompositeDeviceConfig, Evdev, Hidraw, SourceDevice};
+
+/// Describes two configs that have source device matchers for the same device
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigOverlap {
+    /// Description of the device both configs match. E.g. "hidraw 28de:1205"
+    pub device: String,
+    /// Name of the config that will be used for the device
+    pub winner: String,
+    /// Path to the config that will be used for the device
+    pub winner_path: PathBuf,
+    /// Name of the config that will never be used for the device
+    pub loser: String,
+    /// Path to the config that will never be used for the device
+    pub loser_path: PathBuf,
+    /// Reason why the winning config is chosen
+    pub reason: String,
+}
+
+impl Display for ConfigOverlap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "'{}' ({}) and '{}' ({}) both match {}; '{}' wins because {}",
+            self.winner,
+            self.winner_path.display(),
+            self.loser,
+            self.loser_path.display(),
+            self.device,
+            self.winner,
+            self.reason
+        )
+    }
+}
+
+/// Loads all device configs that match the given system data and returns
+/// any configs whose source device matchers overlap.
+pub fn lint_device_configs(data: &DMIData, cpu_info: &CpuInfo) -> Vec<ConfigOverlap> {
+    let configs: Vec<(PathBuf, CompositeDeviceConfig)> = load_device_config_files()
+        .into_iter()
+        .filter(|(_, config)| config.has_valid_matches(data, cpu_info))
+        .collect();
+    find_overlapping_configs(&configs)
+}
+
+/// Returns all pairs of configs whose source device matchers overlap. The given
+/// configs should already be filtered to ones that match the current platform
+/// and be in the order that they are considered when matching devices.
+pub fn find_overlapping_configs(
+    configs: &[(PathBuf, CompositeDeviceConfig)],
+) -> Vec<ConfigOverlap> {
+    let mut overlaps = Vec::new();
+
+    for (i, (path_a, config_a)) in configs.iter().enumerate() {
+        for (path_b, config_b) in configs.iter().skip(i + 1) {
+            let Some(device) = find_overlapping_device(config_a, config_b) else {
+                continue;
+            };
+
+            // Configs without 'auto_manage' are skipped unless management of
+            // all devices was enabled, so a config that has it will win.
+            // Otherwise the first matching config in load order is used.
+            let auto_a = is_auto_managed(config_a);
+            let auto_b = is_auto_managed(config_b);
+            let (winner, winner_path, loser, loser_path, reason) = if auto_b && !auto_a {
+                let reason = format!(
+                    "'{}' does not have 'auto_manage' enabled and is skipped unless all devices are managed",
+                    config_a.name
+                );
+                (config_b, path_b, config_a, path_a, reason)
+            } else {
+                let reason = load_order_reason(path_a, path_b);
+                (config_a, path_a, config_b, path_b, reason)
+            };
+
+            overlaps.push(ConfigOverlap {
+                device,
+                winner: winner.name.clone(),
+                winner_path: winner_path.clone(),
+                loser: loser.name.clone(),
+                loser_path: loser_path.clone(),
+                reason,
+            });
+        }
+    }
+
+    overlaps
+}
+
+/// Returns why the config at the first path is loaded before the config at
+/// the second path. Config directories are checked in order, and the files in
+/// each directory are loaded in order of their file name.
+fn load_order_reason(first: &Path, second: &Path) -> String {
+    let first_dir = first.parent().unwrap_or(first);
+    let second_dir = second.parent().unwrap_or(second);
+    if first_dir == second_dir {
+        let first_name = first.file_name().unwrap_or_default().to_string_lossy();
+        let second_name = second.file_name().unwrap_or_default().to_string_lossy();
+        return format!("'{first_name}' sorts before '{second_name}' and is loaded first");
+    }
+    format!(
+        "{} is checked before {}",
+        first_dir.display(),
+        second_dir.display()
+    )
+}
+
+/// Returns true if the given config has the 'auto_manage' option enabled
+fn is_auto_managed(config: &CompositeDeviceConfig) -> bool {
+    config
+        .options
+        .as_ref()
+        .and_then(|options| options.auto_manage)
+        .unwrap_or(false)
+}
+
+/// Returns a description of the first device matched by source devices in
+/// both of the given configs.
+fn find_overlapping_device(
+    config_a: &CompositeDeviceConfig,
+    config_b: &CompositeDeviceConfig,
+) -> Option<String> {
+    for source_a in config_a.source_devices.iter() {
+        for source_b in config_b.source_devices.iter() {
+            if let Some(device) = sources_overlap(source_a, source_b) {
+                return Some(device);
+            }
+        }
+    }
+    None
+}
+
+/// Returns a description of the device if both source device configs can
+/// match the same vendor and product id.
+fn sources_overlap(source_a: &SourceDevice, source_b: &SourceDevice) -> Option<String> {
+    // Ignored devices are never managed, so they can't be mis-matched
+    if source_a.ignore.unwrap_or(false) || source_b.ignore.unwrap_or(false) {
+        return None;
+    }
+    if let (Some(a), Some(b)) = (source_a.hidraw.as_ref(), source_b.hidraw.as_ref()) {
+        if let Some(device) = hidraw_overlap(a, b) {
+            return Some(device);
+        }
+    }
+    if let (Some(a), Some(b)) = (source_a.evdev.as_ref(), source_b.evdev.as_ref()) {
+        if let Some(device) = evdev_overlap(a, b) {
+            return Some(device);
+        }
+    }
+    None
+}
+
+/// Returns a description of the device if both hidraw configs match the same
+/// vendor and product id.
+fn hidraw_overlap(a: &Hidraw, b: &Hidraw) -> Option<String> {
+    let (Some(vid_a), Some(pid_a)) = (a.vendor_id, a.product_id) else {
+        return None;
+    };
+    let (Some(vid_b), Some(pid_b)) = (b.vendor_id, b.product_id) else {
+        return None;
+    };
+    if vid_a != vid_b || pid_a != pid_b {
+        return None;
+    }
+
+    // Different interfaces of the same device do not overlap
+    if let (Some(iface_a), Some(iface_b)) = (a.interface_num, b.interface_num) {
+        if iface_a != iface_b {
+            return None;
+        }
+    }
+
+    Some(format!("hidraw {vid_a:04x}:{pid_a:04x}"))
+}
+
+/// Returns a description of the device if both evdev configs match the same
+/// vendor and product id.
+fn evdev_overlap(a: &Evdev, b: &Evdev) -> Option<String> {
+    let (Some(vid_a), Some(pid_a)) = (a.vendor_id.as_ref(), a.product_id.as_ref()) else {
+        return None;
+    };
+    let (Some(vid_b), Some(pid_b)) = (b.vendor_id.as_ref(), b.product_id.as_ref()) else {
+        return None;
+    };
+    if !globs_overlap(vid_a, vid_b) || !globs_overlap(pid_a, pid_b) {
+        return None;
+    }
+
+    // Devices with names that can never match do not overlap
+    if let (Some(name_a), Some(name_b)) = (a.name.as_ref(), b.name.as_ref()) {
+        if !globs_overlap(name_a, name_b) {
+            return None;
+        }
+    }
+
+    Some(format!("evdev {vid_a}:{pid_a}"))
+}
+
+/// Returns true if either glob pattern matches the other
+fn globs_overlap(a: &str, b: &str) -> bool {
+    glob_match(a, b) || glob_match(b, a)
+}
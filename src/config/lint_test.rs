@@ -0,0 +1,163 @@
+use std::path::PathBuf;
+
+use crate::config::CompositeDeviceConfig;
+
+use super::lint::find_overlapping_configs;
+
+fn device_config(name: &str, auto_manage: bool, sources: &str) -> CompositeDeviceConfig {
+    let yaml = format!(
+        "version: 1
+kind: CompositeDevice
+name: {name}
+matches: []
+source_devices:
+{sources}
+options:
+  auto_manage: {auto_manage}
+"
+    );
+    serde_yaml::from_str(&yaml).unwrap()
+}
+
+const HIDRAW_DECK: &str = "  - group: gamepad
+    hidraw:
+      vendor_id: 0x28de
+      product_id: 0x1205
+      interface_num: 2";
+
+const EVDEV_XBOX: &str = "  - group: gamepad
+    evdev:
+      name: Microsoft X-Box 360 pad
+      vendor_id: '045e'
+      product_id: '028e'";
+
+#[test]
+fn test_overlapping_load_order() {
+    let configs = vec![
+        (
+            PathBuf::from("/usr/share/inputplumber/devices/50-a.yaml"),
+            device_config("A", true, HIDRAW_DECK),
+        ),
+        (
+            PathBuf::from("/usr/share/inputplumber/devices/60-b.yaml"),
+            device_config("B", true, HIDRAW_DECK),
+        ),
+        (
+            PathBuf::from("/usr/share/inputplumber/devices/70-c.yaml"),
+            device_config("C", true, EVDEV_XBOX),
+        ),
+    ];
+    let overlaps = find_overlapping_configs(&configs);
+    assert_eq!(overlaps.len(), 1);
+    let overlap = &overlaps[0];
+    assert_eq!(overlap.device, "hidraw 28de:1205");
+    assert_eq!(overlap.winner, "A");
+    assert_eq!(overlap.loser, "B");
+    assert_eq!(
+        overlap.reason,
+        "'50-a.yaml' sorts before '60-b.yaml' and is loaded first"
+    );
+
+    // Configs in different directories are loaded in directory order
+    let configs = vec![
+        (
+            PathBuf::from("/etc/inputplumber/devices.d/50-a.yaml"),
+            device_config("A", true, EVDEV_XBOX),
+        ),
+        (
+            PathBuf::from("/usr/share/inputplumber/devices/50-a.yaml"),
+            device_config("B", true, EVDEV_XBOX),
+        ),
+    ];
+    let overlaps = find_overlapping_configs(&configs);
+    assert_eq!(overlaps.len(), 1);
+    assert_eq!(overlaps[0].device, "evdev 045e:028e");
+    assert_eq!(overlaps[0].winner, "A");
+    assert_eq!(
+        overlaps[0].reason,
+        "/etc/inputplumber/devices.d is checked before /usr/share/inputplumber/devices"
+    );
+}
+
+#[test]
+fn test_overlapping_auto_manage() {
+    let configs = vec![
+        (
+            PathBuf::from("/usr/share/inputplumber/devices/50-a.yaml"),
+            device_config("A", false, HIDRAW_DECK),
+        ),
+        (
+            PathBuf::from("/usr/share/inputplumber/devices/60-b.yaml"),
+            device_config("B", true, HIDRAW_DECK),
+        ),
+    ];
+    let overlaps = find_overlapping_configs(&configs);
+    assert_eq!(overlaps.len(), 1);
+    assert_eq!(overlaps[0].winner, "B");
+    assert_eq!(overlaps[0].loser, "A");
+    assert!(overlaps[0].reason.contains("'auto_manage'"));
+}
+
+#[test]
+fn test_non_overlapping_configs() {
+    let other_interface = "  - group: gamepad
+    hidraw:
+      vendor_id: 0x28de
+      product_id: 0x1205
+      interface_num: 1";
+    let ignored = "  - group: gamepad
+    ignore: true
+    hidraw:
+      vendor_id: 0x28de
+      product_id: 0x1205";
+    let other_name = "  - group: gamepad
+    evdev:
+      name: Xbox Wireless Controller
+      vendor_id: '045e'
+      product_id: '028e'";
+    let wildcard = "  - group: gamepad
+    evdev:
+      vendor_id: '045e'
+      product_id: '*'";
+
+    // Different interfaces, ignored devices, and names that can never match
+    // do not overlap.
+    let configs = vec![
+        (
+            PathBuf::from("/usr/share/inputplumber/devices/50-a.yaml"),
+            device_config("A", true, HIDRAW_DECK),
+        ),
+        (
+            PathBuf::from("/usr/share/inputplumber/devices/60-b.yaml"),
+            device_config("B", true, other_interface),
+        ),
+        (
+            PathBuf::from("/usr/share/inputplumber/devices/70-c.yaml"),
+            device_config("C", true, ignored),
+        ),
+        (
+            PathBuf::from("/usr/share/inputplumber/devices/80-d.yaml"),
+            device_config("D", true, EVDEV_XBOX),
+        ),
+        (
+            PathBuf::from("/usr/share/inputplumber/devices/90-e.yaml"),
+            device_config("E", true, other_name),
+        ),
+    ];
+    assert!(find_overlapping_configs(&configs).is_empty());
+
+    // Glob patterns that can match the same device do overlap
+    let configs = vec![
+        (
+            PathBuf::from("/usr/share/inputplumber/devices/50-a.yaml"),
+            device_config("A", true, wildcard),
+        ),
+        (
+            PathBuf::from("/usr/share/inputplumber/devices/60-b.yaml"),
+            device_config("B", true, EVDEV_XBOX),
+        ),
+    ];
+    let overlaps = find_overlapping_configs(&configs);
+    assert_eq!(overlaps.len(), 1);
+    assert_eq!(overlaps[0].device, "evdev 045e:*");
+}
@@ -1,14 +1,21 @@
+pub mod hid_capture;
+#[cfg(test)]
+pub mod hid_capture_test;
+pub mod lint;
+#[cfg(test)]
+pub mod lint_test;
 pub mod path;
 
-use std::io;
+use std::{collections::HashMap, fs, io, path::PathBuf};
 
 use ::procfs::CpuInfo;
 use glob_match::glob_match;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
+    config::path::get_devices_paths,
     dmi::data::DMIData,
     input::event::{native::NativeEvent, value::InputValue},
     udev::device::UdevDevice,
@@ -21,6 +28,55 @@ pub enum LoadError {
     IoError(#[from] io::Error),
     #[error("Unable to deserialize: {0}")]
     DeserializeError(#[from] serde_yaml::Error),
+    #[error("Invalid config: {0}")]
+    InvalidConfig(String),
+}
+
+/// Looks in all default locations for [CompositeDeviceConfig] definitions and
+/// loads them. Each config is returned along with the path it was loaded from,
+/// in the order in which configs are considered when matching devices.
+pub fn load_device_config_files() -> Vec<(PathBuf, CompositeDeviceConfig)> {
+    let mut devices = Vec::new();
+    let paths = get_devices_paths();
+
+    // Look for composite device profiles in all known locations
+    for path in paths.iter() {
+        log::trace!("Checking {path:?} for composite device configs");
+        let files = fs::read_dir(path);
+        if files.is_err() {
+            log::debug!("Failed to load directory {path:?}: {}", files.unwrap_err());
+            continue;
+        }
+        let mut files: Vec<_> = files.unwrap().map(|r| r.unwrap()).collect();
+        files.sort_by_key(|dir| dir.file_name());
+
+        // Look at each file in the directory and try to load them
+        for file in files {
+            let filename = file.file_name();
+            let filename = filename.as_os_str().to_str().unwrap();
+
+            // Skip any non-yaml files
+            if !filename.ends_with(".yaml") {
+                continue;
+            }
+
+            // Try to load the composite device profile
+            log::trace!("Found file: {}", file.path().display());
+            let device = CompositeDeviceConfig::from_yaml_file(file.path().display().to_string());
+            if device.is_err() {
+                log::warn!(
+                    "Failed to parse composite device config '{}': {}",
+                    file.path().display(),
+                    device.unwrap_err()
+                );
+                continue;
+            }
+            let device = device.unwrap();
+            devices.push((file.path(), device));
+        }
+    }
+
+    devices
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -30,7 +86,13 @@ pub struct DeviceProfile {
     pub kind: String, //useful?
     pub name: String, //useful?
     pub target_devices: Option<Vec<String>>,
+    /// Name of a target loadout defined in the global config to use as the
+    /// target devices. Ignored if `target_devices` is set.
+    pub target_loadout: Option<String>,
     pub description: Option<String>,
+    /// How input from the physical touchscreen is emitted. One of
+    /// "passthrough", "mouse", or "block". Defaults to "passthrough".
+    pub touchscreen: Option<String>,
     pub mapping: Vec<ProfileMapping>,
 }
 
@@ -55,6 +117,22 @@ pub struct ProfileMapping {
     pub name: String,
     pub source_event: CapabilityConfig,
     pub target_events: Vec<CapabilityConfig>,
+    /// Delay in milliseconds between each target event when this mapping emits
+    /// a chord. Overrides the chord delay of the composite device.
+    pub chord_delay_ms: Option<u64>,
+    /// Kind of press of the source button that triggers this mapping. Can be
+    /// "short", "long" or "double". If any mapping of a source button has a
+    /// press kind, its mappings without one are treated as "short".
+    pub press: Option<String>,
+    /// Time in milliseconds the source button must be held to trigger a
+    /// "long" press mapping.
+    pub hold_ms: Option<u64>,
+    /// Maximum time in milliseconds between two presses of the source button
+    /// to trigger a "double" press mapping.
+    pub double_press_ms: Option<u64>,
+    /// Interval in milliseconds at which the target buttons are repeatedly
+    /// pressed and released while the source button is held.
+    pub auto_fire_ms: Option<u64>,
 }
 
 impl ProfileMapping {
@@ -170,6 +248,7 @@ impl CapabilityMap {
     /// Load a [CapabilityMap] from the given YAML string
     pub fn _from_yaml(content: String) -> Result<CapabilityMap, LoadError> {
         let device: CapabilityMap = serde_yaml::from_str(content.as_str())?;
+        device.validate()?;
         Ok(device)
     }
 
@@ -177,8 +256,24 @@ impl CapabilityMap {
     pub fn from_yaml_file(path: String) -> Result<CapabilityMap, LoadError> {
         let file = std::fs::File::open(path)?;
         let device: CapabilityMap = serde_yaml::from_reader(file)?;
+        device.validate()?;
         Ok(device)
     }
+
+    /// Returns an error if the map uses features that are not supported by
+    /// its schema version. Mapping conditions require version 2.
+    fn validate(&self) -> Result<(), LoadError> {
+        if self.version >= 2 {
+            return Ok(());
+        }
+        if let Some(mapping) = self.mapping.iter().find(|m| m.conditions.is_some()) {
+            return Err(LoadError::InvalidConfig(format!(
+                "Mapping '{}' in capability map '{}' uses conditions which require version 2",
+                mapping.name, self.id
+            )));
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -187,17 +282,54 @@ pub struct CapabilityMapping {
     pub name: String,
     pub source_events: Vec<CapabilityConfig>,
     pub target_event: CapabilityConfig,
+    pub chord: Option<ChordOptions>,
+    /// Conditions that must all be met for the mapping to be translated.
+    /// Source events of mappings whose conditions are not met are emitted
+    /// untranslated.
+    pub conditions: Option<Vec<MappingCondition>>,
 }
 
+/// A condition that must be met for a capability mapping to be translated
 #[derive(Debug, Deserialize, Clone)]
 #[serde(rename_all = "snake_case")]
+pub struct MappingCondition {
+    /// Only translate the mapping while the given capability is held
+    pub held: Option<CapabilityConfig>,
+    /// Only translate the mapping while the given capability is not held
+    pub not_held: Option<CapabilityConfig>,
+    /// Only translate the mapping while the composite device is in the given
+    /// intercept mode. E.g. "None", "Pass", "Always", "GamepadOnly"
+    pub intercept_mode: Option<String>,
+}
+
+/// Timing options used to decide whether multiple source events should be
+/// treated as a chord.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct ChordOptions {
+    /// Maximum time in milliseconds between the first and last source event
+    /// press for them to be treated as a chord.
+    pub max_interval_ms: Option<u64>,
+    /// If true, the source events of a chord that was not completed within
+    /// 'max_interval_ms' will be emitted as-is.
+    pub emit_partial: Option<bool>,
+    /// If true, source events must be pressed in the order they are defined.
+    pub ordered: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(rename_all = "snake_case")]
 pub struct CapabilityConfig {
     pub gamepad: Option<GamepadCapability>,
     pub keyboard: Option<String>,
+    /// XKB keysym (e.g. "z" or "eacute") that is translated into the keys that
+    /// produce it with the active keyboard layout
+    pub keysym: Option<String>,
     pub mouse: Option<MouseCapability>,
     pub dbus: Option<String>,
     pub touchpad: Option<TouchpadCapability>,
     pub touchscreen: Option<TouchCapability>,
+    pub switch: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -207,6 +339,7 @@ pub struct GamepadCapability {
     pub button: Option<String>,
     pub trigger: Option<TriggerCapability>,
     pub gyro: Option<GyroCapability>,
+    pub dial: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -238,6 +371,7 @@ pub struct GyroCapability {
 pub struct MouseCapability {
     pub button: Option<String>,
     pub motion: Option<MouseMotionCapability>,
+    pub wheel: Option<MouseWheelCapability>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -245,6 +379,29 @@ pub struct MouseCapability {
 pub struct MouseMotionCapability {
     pub direction: Option<String>,
     pub speed_pps: Option<u64>,
+    pub bounds: Option<MouseBoundsCapability>,
+}
+
+/// Box to constrain translated mouse motion to, with its width and height
+/// given as a fraction (0.0-1.0) of the screen size. The box is centered on
+/// the pointer position when motion is activated.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct MouseBoundsCapability {
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Defines how input translated into mouse wheel buttons is turned into
+/// discrete wheel detents.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct MouseWheelCapability {
+    /// Dial rotation required to scroll one detent, or how far a joystick
+    /// axis must be moved to start scrolling
+    pub threshold: Option<f64>,
+    /// Number of detents per second to scroll while the input is held
+    pub repeat_rate: Option<f64>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -266,6 +423,19 @@ pub struct TouchCapability {
 pub struct TouchMotionCapability {
     pub region: Option<String>,
     pub speed_pps: Option<u64>,
+    pub warp: Option<TouchWarpCapability>,
+}
+
+/// Absolute position to warp a touch to. Values are normalized between 0.0
+/// and 1.0 where (0.0, 0.0) is the top-left corner of the touch device.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct TouchWarpCapability {
+    pub x: f64,
+    pub y: f64,
+    /// Press the touch while the source event is pressed. If false, only the
+    /// position of the touch is updated. Defaults to false.
+    pub press: Option<bool>,
 }
 
 /// Defines available options for loading a [CompositeDeviceConfig]
@@ -276,6 +446,252 @@ pub struct CompositeDeviceConfigOptions {
     /// If this is false, InputPlumber will not try to manage the device unless
     /// an external service enables management of all devices.
     pub auto_manage: Option<bool>,
+    /// Defines how Steam Deck trackpad haptic commands from the "deck" target
+    /// device should be converted into rumble for source devices that do not
+    /// have trackpad haptics.
+    pub haptics_to_rumble: Option<HapticsToRumble>,
+    /// If true, translated input events will be mirrored to DBus target
+    /// devices that can handle them in addition to all other capable target
+    /// devices, providing a parallel event stream for overlays without
+    /// intercepting input.
+    pub clone_output: Option<bool>,
+    /// Delay in milliseconds between each event when emitting a chord of
+    /// multiple target events. Defaults to 80.
+    pub chord_delay_ms: Option<u64>,
+    /// List of application identifiers (glob patterns) for which force
+    /// feedback is muted while the application is active.
+    pub ff_mute_apps: Option<Vec<String>>,
+    /// Options for detecting and compensating for stick drift
+    pub drift_detection: Option<DriftDetection>,
+    /// If true, a plain keyboard and mouse target device are created before
+    /// source devices are grabbed. Keyboard and mouse events that no other
+    /// target device can handle are passed through to these devices, so
+    /// basic input keeps working while target devices are started or changed.
+    pub passthrough_fallback: Option<bool>,
+    /// Number of worker threads used to translate source events with the
+    /// device profile outside of the composite device loop. The order of
+    /// events from each source device is preserved, and events queued while
+    /// a worker is busy are translated as one batch. Defaults to 0, which
+    /// translates events one at a time in the composite device loop.
+    pub translation_workers: Option<usize>,
+    /// If true, each source device is translated by its own dedicated worker
+    /// thread instead of a fixed number of 'translation_workers'. Useful for
+    /// composite devices with many high-rate source devices.
+    pub translation_worker_per_source: Option<bool>,
+    /// Commands or DBus calls to execute when the system suspends or resumes
+    pub power_hooks: Option<PowerHooks>,
+    /// If true, matching USB source devices are de-authorized when plugged in
+    /// and only authorized after InputPlumber has hidden their input devices.
+    /// This prevents input events from reaching other processes before the
+    /// device is grabbed. Requires 'auto_manage' and USB vendor and product
+    /// ids in the source device configs.
+    pub usb_authorization: Option<bool>,
+    /// If true, any input devices created by the kernel driver for the same
+    /// HID device as a source device (e.g. the motion sensor or touchpad
+    /// nodes created by hid-playstation) that are not part of this config are
+    /// hidden and not consumed by any other composite device.
+    pub hide_sibling_devices: Option<bool>,
+    /// Number of minutes without any input after which target devices stop
+    /// polling for output events until the next input event. Applies to all
+    /// target devices that do not define their own 'idle_timeout_minutes'.
+    pub target_idle_timeout_minutes: Option<u64>,
+    /// If true, target devices are re-created when the active user session
+    /// of their seat changes (e.g. fast user switching), so applications of
+    /// the previous session lose access to them. Defaults to true.
+    pub recreate_targets_on_session_change: Option<bool>,
+    /// Options for detecting when the device is connected to a dock. While
+    /// docked, the composite device emits the 'Switch:Docked' capability, which
+    /// device profiles can map or use as a mapping condition.
+    pub dock_detection: Option<DockDetection>,
+    /// Map of capability names to the names they are exposed as over DBus
+    /// (e.g. "Gamepad:Button:QuickAccess": "Gamepad:Button:Quick"). This
+    /// keeps DBus clients working that depend on older capability names.
+    pub capability_aliases: Option<HashMap<String, String>>,
+    /// Captures a state snapshot of target devices when events take too long
+    /// to reach them. Applies to all target devices that do not define their
+    /// own 'latency_watchdog'.
+    pub latency_watchdog: Option<LatencyWatchdog>,
+    /// Device profile and target devices to switch to when the global input
+    /// mode is changed to "game" or "desktop" over DBus
+    pub input_modes: Option<InputModes>,
+    /// Policy for remapping the power and volume buttons with device profiles.
+    /// These keys are passed through unchanged unless remapping them is
+    /// explicitly allowed.
+    pub system_keys: Option<SystemKeys>,
+    /// Number of milliseconds a source device may be stuck reading input
+    /// before it is restarted. Defaults to 5000. Set to 0 to disable the
+    /// watchdog.
+    pub source_watchdog_timeout_ms: Option<u64>,
+}
+
+/// Configurations the composite device switches between when the global
+/// input mode changes
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct InputModes {
+    /// Configuration to use in game mode (e.g. a fullscreen game is running)
+    pub game: Option<InputModeConfig>,
+    /// Configuration to use in desktop mode (e.g. keyboard and mouse control
+    /// of the desktop)
+    pub desktop: Option<InputModeConfig>,
+}
+
+/// Device profile and target devices of a single input mode
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct InputModeConfig {
+    /// Absolute path to the device profile to load
+    pub profile: String,
+    /// Target devices to use (e.g. ["xb360"])
+    pub target_devices: Vec<String>,
+}
+
+/// Defines when to capture a state snapshot of a target device because
+/// events take too long to travel from source devices to the target device
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct LatencyWatchdog {
+    /// Latency in milliseconds above which events are considered late.
+    /// Defaults to 50.
+    pub threshold_ms: Option<u64>,
+    /// Number of seconds the latency must stay above the threshold before a
+    /// snapshot is captured. Defaults to 5.
+    pub duration_secs: Option<u64>,
+    /// Directory to write snapshots to in addition to the journal
+    pub dump_path: Option<String>,
+}
+
+/// Defines how to detect whether a device is connected to a dock
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct DockDetection {
+    /// USB devices (e.g. the hub of a dock) whose presence means the device
+    /// is docked
+    pub usb: Option<Vec<DockUsbDevice>>,
+    /// If true, the device is considered docked while an external display is
+    /// connected over DisplayPort (e.g. USB-C DisplayPort alt-mode)
+    pub displayport: Option<bool>,
+    /// Target devices to use while docked. The previous target devices are
+    /// restored when undocked.
+    pub target_devices: Option<Vec<String>>,
+}
+
+/// Identifies a USB device that is part of a dock
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct DockUsbDevice {
+    pub vendor_id: u16,
+    pub product_id: u16,
+}
+
+/// Defines how trackpad haptic commands are converted into generic rumble
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct HapticsToRumble {
+    /// If false, haptic commands will not be converted into rumble. Defaults to true.
+    pub enabled: Option<bool>,
+    /// Curve used to scale the haptic intensity. Can be one of "linear",
+    /// "quadratic", "cubic", or "sqrt". Defaults to "linear".
+    pub curve: Option<String>,
+    /// Multiplier applied to the resulting rumble intensity. Defaults to 1.0.
+    pub scale: Option<f64>,
+    /// Rumble intensity (0.0 - 1.0) of haptic "tick" commands before the
+    /// curve is applied. Defaults to 0.5.
+    pub tick_strength: Option<f64>,
+    /// Rumble intensity (0.0 - 1.0) of haptic "click" commands before the
+    /// curve is applied. Defaults to 1.0.
+    pub click_strength: Option<f64>,
+    /// Multiplier applied to how long the rumble plays for each haptic
+    /// command. Defaults to 1.0.
+    pub duration_scale: Option<f64>,
+}
+
+impl HapticsToRumble {
+    /// Returns a copy of these options where any unset option uses the value
+    /// from the given fallback options.
+    pub fn or(&self, fallback: Option<&HapticsToRumble>) -> HapticsToRumble {
+        let Some(fallback) = fallback else {
+            return self.clone();
+        };
+        HapticsToRumble {
+            enabled: self.enabled.or(fallback.enabled),
+            curve: self.curve.clone().or(fallback.curve.clone()),
+            scale: self.scale.or(fallback.scale),
+            tick_strength: self.tick_strength.or(fallback.tick_strength),
+            click_strength: self.click_strength.or(fallback.click_strength),
+            duration_scale: self.duration_scale.or(fallback.duration_scale),
+        }
+    }
+}
+
+/// Defines how stick drift is detected and compensated for
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct DriftDetection {
+    /// If true, the resting position of sticks is observed to detect drift.
+    /// Defaults to false.
+    pub enabled: Option<bool>,
+    /// If true, suggested drift corrections are applied automatically.
+    /// Defaults to false.
+    pub auto_apply: Option<bool>,
+    /// Maximum stick center offset (0.0 - 1.0) that can be corrected.
+    /// Defaults to 0.1.
+    pub max_center_offset: Option<f64>,
+    /// Maximum deadzone (0.0 - 1.0) that can be applied. Defaults to 0.2.
+    pub max_deadzone: Option<f64>,
+}
+
+/// Defines which system keys (power and volume buttons) device profiles are
+/// allowed to remap
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct SystemKeys {
+    /// System key capabilities that device profiles may remap. Can be
+    /// "Keyboard:KeyPower", "Keyboard:KeyVolumeUp" or "Keyboard:KeyVolumeDown".
+    pub allow_remap: Option<Vec<String>>,
+    /// Time in milliseconds a remapped power key must be held before the
+    /// power key is passed through anyway, so a long press can still power
+    /// off the system. A value of 0 disables the passthrough. Defaults to 3000.
+    pub power_long_press_ms: Option<u64>,
+}
+
+/// Defines actions to execute when the composite device is suspended or
+/// resumed (e.g. to toggle controller power-saving modes)
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct PowerHooks {
+    /// Actions to execute, in order, before the system suspends
+    pub suspend: Option<Vec<HookAction>>,
+    /// Actions to execute, in order, after the system resumes
+    pub resume: Option<Vec<HookAction>>,
+    /// Maximum time in milliseconds to wait for each action. Defaults to 5000.
+    pub timeout_ms: Option<u64>,
+}
+
+/// A single hook action. Either a command or a DBus call should be defined.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct HookAction {
+    /// Program and arguments to execute. E.g. ["/usr/bin/ryzenadj", "--power-saving"]
+    pub command: Option<Vec<String>>,
+    /// DBus method to call on the system bus
+    pub dbus: Option<DBusMethodCall>,
+}
+
+/// Defines a DBus method call on the system bus
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct DBusMethodCall {
+    /// Bus name of the service. E.g. "org.freedesktop.UPower"
+    pub destination: String,
+    /// Object path. E.g. "/org/freedesktop/UPower"
+    pub path: String,
+    /// Interface of the method. E.g. "org.freedesktop.UPower"
+    pub interface: String,
+    /// Name of the method to call
+    pub method: String,
+    /// Optional string arguments to pass to the method
+    pub args: Option<Vec<String>>,
 }
 
 /// Defines a platform match for loading a [CompositeDeviceConfig]
@@ -308,9 +724,119 @@ pub struct SourceDevice {
     pub hidraw: Option<Hidraw>,
     pub iio: Option<IIO>,
     pub udev: Option<Udev>,
+    pub external: Option<External>,
     pub unique: Option<bool>,
     pub blocked: Option<bool>,
     pub ignore: Option<bool>,
+    pub quirks: Option<SourceQuirks>,
+    /// Invert the x value of all axis and IMU events. Only applies to evdev
+    /// and iio source devices.
+    pub invert_x: Option<bool>,
+    /// Invert the y value of all axis and IMU events. Only applies to evdev
+    /// and iio source devices.
+    pub invert_y: Option<bool>,
+    /// Swap the x and y values of all axis and IMU events. Swapping is done
+    /// before inversion. Only applies to evdev and iio source devices.
+    pub swap_axes: Option<bool>,
+    /// Write input events from this source device directly to target devices
+    /// from the source device thread for minimal latency. This only applies
+    /// to capabilities that are not remapped by the device profile or
+    /// capability map and is disabled automatically while input is
+    /// intercepted or input statistics are collected.
+    pub raw_passthrough: Option<bool>,
+}
+
+/// Device-specific behaviors to apply to input events from a source device
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct SourceQuirks {
+    /// Apply the built-in quirks known for the device. Quirks defined here
+    /// replace built-in quirks of the same kind. Defaults to true.
+    pub builtin: Option<bool>,
+    /// Ignore all input events for the given number of milliseconds after the
+    /// source device is opened.
+    pub ignore_initial_events_ms: Option<u64>,
+    /// List of axes whose values should be inverted
+    pub invert_axis: Option<Vec<InvertAxis>>,
+    /// List of key capabilities (e.g. "Keyboard:KeyF13") that are reported as
+    /// a latched Fn-lock state instead of a momentary key press.
+    pub fake_fn_lock: Option<Vec<String>>,
+    /// List of buttons that should be released automatically after a delay
+    /// because the device does not reliably report their release.
+    pub auto_release: Option<Vec<AutoRelease>>,
+    /// List of buttons whose release-press bounces should be ignored, e.g.
+    /// because a worn switch double-clicks.
+    pub debounce: Option<Vec<Debounce>>,
+    /// Reject touchpad contacts that are likely to be a resting palm or thumb
+    pub palm_rejection: Option<PalmRejection>,
+    /// List of capabilities the device reports its paddles as, which should
+    /// be translated to paddle capabilities
+    pub paddles: Option<Vec<PaddleMapping>>,
+}
+
+/// Defines which touchpad contacts are rejected as accidental touches
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct PalmRejection {
+    /// Minimum pressure (0.0-1.0) a contact must reach before it is accepted
+    pub min_pressure: Option<f64>,
+    /// Contacts that start within these zones along the edges of the
+    /// touchpad are rejected until they are lifted
+    pub edge_exclusion: Option<EdgeExclusion>,
+}
+
+/// Size of the zones along each edge of a touchpad as a fraction (0.0-1.0) of
+/// the touchpad width or height
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct EdgeExclusion {
+    pub left: Option<f64>,
+    pub right: Option<f64>,
+    pub top: Option<f64>,
+    pub bottom: Option<f64>,
+}
+
+/// Defines a button that is released automatically after a delay
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct AutoRelease {
+    /// Button capability to release (e.g. "Gamepad:Button:Guide")
+    pub capability: String,
+    /// Time in milliseconds after the button is pressed to release it
+    pub delay_ms: u64,
+}
+
+/// Defines a capability that a source device reports a paddle as
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct PaddleMapping {
+    /// Capability the paddle is reported as (e.g. "Keyboard:KeyF13")
+    pub capability: String,
+    /// Paddle to translate the capability to (e.g. "RightPaddle1")
+    pub paddle: String,
+}
+
+/// Defines a button whose release-press bounces are ignored
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct Debounce {
+    /// Button capability to debounce (e.g. "Gamepad:Button:South")
+    pub capability: String,
+    /// A press within this many milliseconds after a release is ignored
+    /// together with the release
+    pub window_ms: u64,
+}
+
+/// Defines an axis that should be inverted
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct InvertAxis {
+    /// Axis capability to invert (e.g. "Gamepad:Axis:LeftStick")
+    pub capability: String,
+    /// If true, invert the x value of the axis
+    pub x: Option<bool>,
+    /// If true, invert the y value of the axis
+    pub y: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, Clone, PartialEq)]
@@ -333,6 +859,16 @@ pub struct Hidraw {
     pub name: Option<String>,
 }
 
+/// Matches a source device provided by an external driver process that has
+/// registered over the external driver socket.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct External {
+    pub name: Option<String>,
+    pub vendor_id: Option<u16>,
+    pub product_id: Option<u16>,
+}
+
 #[derive(Debug, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct Udev {
@@ -383,11 +919,244 @@ pub struct CompositeDeviceConfig {
     pub maximum_sources: Option<i32>,
     pub capability_map_id: Option<String>,
     pub source_devices: Vec<SourceDevice>,
-    pub target_devices: Option<Vec<String>>,
+    pub target_devices: Option<Vec<TargetDeviceConfig>>,
     pub options: Option<CompositeDeviceConfigOptions>,
 }
 
+/// Target device to emulate for a [CompositeDeviceConfig]. This can either be
+/// a target device type (e.g. "xb360") or an object with the target device
+/// type and overrides of the identity the device presents to the system.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum TargetDeviceConfig {
+    Type(String),
+    Device(TargetDeviceOptions),
+}
+
+impl TargetDeviceConfig {
+    /// Returns the target device type. E.g. "xb360"
+    pub fn kind(&self) -> &str {
+        match self {
+            TargetDeviceConfig::Type(kind) => kind.as_str(),
+            TargetDeviceConfig::Device(options) => options.kind.as_str(),
+        }
+    }
+
+    /// Returns the identity overrides of the target device, if any
+    pub fn options(&self) -> Option<&TargetDeviceOptions> {
+        match self {
+            TargetDeviceConfig::Type(_) => None,
+            TargetDeviceConfig::Device(options) => Some(options),
+        }
+    }
+}
+
+/// Overrides of the identity a target device presents to the system
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct TargetDeviceOptions {
+    /// Target device type. E.g. "xb360"
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub name: Option<String>,
+    pub vendor_id: Option<u16>,
+    pub product_id: Option<u16>,
+    /// Unique identifier such as a serial number or MAC address. Only applied
+    /// to target devices that support it.
+    pub uniq: Option<String>,
+    /// Orientation of the touchscreen. Can be "normal", "upside-down", "left"
+    /// or "right". Only applied to touchscreen target devices.
+    pub orientation: Option<String>,
+    /// Width of the touchscreen panel. Only applied to touchscreen target
+    /// devices.
+    pub width: Option<u16>,
+    /// Height of the touchscreen panel. Only applied to touchscreen target
+    /// devices.
+    pub height: Option<u16>,
+    /// Number of minutes without any input after which the target device
+    /// stops polling for output events until the next input event.
+    pub idle_timeout_minutes: Option<u64>,
+    /// Acceleration curve applied to translated mouse motion. Can be "flat",
+    /// "quadratic" or "cubic". Only applied to mouse target devices.
+    pub pointer_acceleration: Option<String>,
+    /// Time in milliseconds over which changes in translated mouse motion
+    /// speed are smoothed. Only applied to mouse target devices.
+    pub pointer_smoothing_ms: Option<u32>,
+    /// Backend used to emit input, either "uinput" (default) or "wayland".
+    /// Only applied to keyboard and mouse target devices.
+    pub backend: Option<String>,
+    /// Wayland display to connect to when using the "wayland" backend, as a
+    /// socket name or absolute path. Defaults to WAYLAND_DISPLAY.
+    pub wayland_display: Option<String>,
+    /// Address of the remote InputPlumber to send input to, e.g.
+    /// "tcp://192.168.1.10:31337" or "vsock://2:31337". Only applied to
+    /// network target devices.
+    pub address: Option<String>,
+    /// Captures a state snapshot of the target device when events take too
+    /// long to reach it.
+    pub latency_watchdog: Option<LatencyWatchdog>,
+}
+
+impl TargetDeviceOptions {
+    /// Returns options for the given target device type without any
+    /// overrides
+    pub fn new(kind: &str) -> Self {
+        Self {
+            kind: kind.to_string(),
+            name: None,
+            vendor_id: None,
+            product_id: None,
+            uniq: None,
+            orientation: None,
+            width: None,
+            height: None,
+            idle_timeout_minutes: None,
+            pointer_acceleration: None,
+            pointer_smoothing_ms: None,
+            backend: None,
+            wayland_display: None,
+            address: None,
+            latency_watchdog: None,
+        }
+    }
+}
+
 impl CompositeDeviceConfig {
+    /// Returns the USB vendor and product ids of all source devices that
+    /// define both an exact vendor and product id.
+    pub fn get_usb_ids(&self) -> Vec<(u16, u16)> {
+        let mut ids = Vec::new();
+        for config in self.source_devices.iter() {
+            if let Some(hidraw) = config.hidraw.as_ref() {
+                if let (Some(vid), Some(pid)) = (hidraw.vendor_id, hidraw.product_id) {
+                    ids.push((vid, pid));
+                }
+            }
+            if let Some(evdev) = config.evdev.as_ref() {
+                let vid = evdev
+                    .vendor_id
+                    .as_ref()
+                    .and_then(|id| u16::from_str_radix(id, 16).ok());
+                let pid = evdev
+                    .product_id
+                    .as_ref()
+                    .and_then(|id| u16::from_str_radix(id, 16).ok());
+                if let (Some(vid), Some(pid)) = (vid, pid) {
+                    ids.push((vid, pid));
+                }
+            }
+        }
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+
+    /// Returns the identity overrides configured for the given target device
+    /// type, if any
+    pub fn get_target_device_options(&self, kind: &str) -> Option<&TargetDeviceOptions> {
+        self.target_devices
+            .as_ref()?
+            .iter()
+            .filter(|target| target.kind() == kind)
+            .find_map(|target| target.options())
+    }
+
+    /// Returns the options used to create the given target device type,
+    /// including the idle timeout and latency watchdog of the composite
+    /// device if the target device does not define its own.
+    pub fn get_target_device_create_options(&self, kind: &str) -> Option<TargetDeviceOptions> {
+        let mut options = self.get_target_device_options(kind).cloned();
+        let idle_timeout = self
+            .options
+            .as_ref()
+            .and_then(|options| options.target_idle_timeout_minutes);
+        if let Some(minutes) = idle_timeout {
+            let options = options.get_or_insert_with(|| TargetDeviceOptions::new(kind));
+            options.idle_timeout_minutes.get_or_insert(minutes);
+        }
+        let latency_watchdog = self
+            .options
+            .as_ref()
+            .and_then(|options| options.latency_watchdog.as_ref());
+        if let Some(watchdog) = latency_watchdog {
+            let options = options.get_or_insert_with(|| TargetDeviceOptions::new(kind));
+            options
+                .latency_watchdog
+                .get_or_insert_with(|| watchdog.clone());
+        }
+        options
+    }
+
+    /// Create a [CompositeDeviceConfig] at runtime that combines the source
+    /// devices at the given device paths (e.g. "/dev/input/event3",
+    /// "/dev/hidraw0") into a single composite device.
+    pub fn from_source_paths(
+        name: String,
+        paths: &[String],
+    ) -> Result<CompositeDeviceConfig, LoadError> {
+        if paths.is_empty() {
+            return Err(LoadError::InvalidConfig(
+                "At least one source device path is required".to_string(),
+            ));
+        }
+        let mut source_devices = Vec::with_capacity(paths.len());
+        for path in paths {
+            let group = if path.starts_with("/dev/input/") || path.starts_with("/dev/hidraw") {
+                "gamepad"
+            } else if path.starts_with("/dev/iio:") {
+                "imu"
+            } else {
+                return Err(LoadError::InvalidConfig(format!(
+                    "Unsupported source device path: {path}"
+                )));
+            };
+            let udev = Udev {
+                attributes: None,
+                dev_node: Some(path.clone()),
+                dev_path: None,
+                driver: None,
+                properties: None,
+                subsystem: None,
+                sys_name: None,
+                sys_path: None,
+            };
+            source_devices.push(SourceDevice {
+                group: group.to_string(),
+                evdev: None,
+                hidraw: None,
+                iio: None,
+                udev: Some(udev),
+                external: None,
+                unique: None,
+                blocked: None,
+                ignore: None,
+                quirks: None,
+                invert_x: None,
+                invert_y: None,
+                swap_axes: None,
+                raw_passthrough: None,
+            });
+        }
+
+        let target_devices = ["xb360", "mouse", "keyboard"]
+            .into_iter()
+            .map(|kind| TargetDeviceConfig::Type(kind.to_string()))
+            .collect();
+
+        Ok(CompositeDeviceConfig {
+            version: 1,
+            kind: "CompositeDevice".to_string(),
+            name,
+            matches: Vec::new(),
+            single_source: None,
+            maximum_sources: Some(paths.len() as i32),
+            capability_map_id: None,
+            source_devices,
+            target_devices: Some(target_devices),
+            options: None,
+        })
+    }
+
     /// Load a [CompositeDevice] from the given YAML string
     pub fn _from_yaml(content: String) -> Result<CompositeDeviceConfig, LoadError> {
         let device: CompositeDeviceConfig = serde_yaml::from_str(content.as_str())?;
@@ -460,6 +1229,15 @@ impl CompositeDeviceConfig {
                     }
                 }
             }
+            "external" => {
+                for config in self.source_devices.iter() {
+                    if let Some(external_config) = config.external.as_ref() {
+                        if self.has_matching_external(udevice, external_config) {
+                            return Some(config.clone());
+                        }
+                    }
+                }
+            }
             _ => (),
         };
         None
@@ -641,6 +1419,37 @@ impl CompositeDeviceConfig {
         true
     }
 
+    /// Returns true if a given external device matches the given external config
+    pub fn has_matching_external(&self, device: &UdevDevice, external_config: &External) -> bool {
+        log::trace!(
+            "Checking external config: {:?} against {:?}",
+            external_config,
+            device
+        );
+
+        if let Some(name) = external_config.name.as_ref() {
+            let dname = device.name();
+            log::trace!("Checking name: {name} against {dname}");
+            if !glob_match(name.as_str(), dname.as_str()) {
+                return false;
+            }
+        }
+
+        if let Some(vendor_id) = external_config.vendor_id {
+            if vendor_id != device.id_vendor() {
+                return false;
+            }
+        }
+
+        if let Some(product_id) = external_config.product_id {
+            if product_id != device.id_product() {
+                return false;
+            }
+        }
+
+        true
+    }
+
     /// Returns true if a given evdev device is within a list of evdev configs.
     pub fn has_matching_evdev(&self, device: &UdevDevice, evdev_config: &Evdev) -> bool {
         //TODO: Check if the evdev has no proterties defined, that would always match.
@@ -796,3 +1605,87 @@ impl CompositeDeviceConfig {
         Some(matches)
     }
 }
+
+/// Service-wide configuration of InputPlumber
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct GlobalConfig {
+    pub version: u32,
+    pub kind: String,
+    /// Udev tags, properties, and permissions applied to target devices
+    pub target_permissions: Option<Vec<TargetPermissions>>,
+    /// Addresses to listen on for input from remote devices, such as
+    /// "network" target devices on other machines or in virtual machines.
+    /// E.g. "tcp://0.0.0.0:31337" or "vsock://any:31337"
+    pub network_listen: Option<Vec<String>>,
+    /// Clear the logind idle hint of the active session while input is
+    /// written to target devices, so the session is not considered idle
+    /// while playing with a controller. Defaults to true.
+    pub report_user_activity: Option<bool>,
+    /// Named sets of target devices that can be selected by name, e.g.
+    /// {"deck-like": ["deck", "touchscreen", "keyboard"]}
+    pub target_loadouts: Option<HashMap<String, Vec<String>>>,
+    /// Command run to make uinput available if it is missing at startup.
+    /// An empty value disables the hook. Defaults to "modprobe uinput".
+    pub uinput_hook: Option<String>,
+    /// Maximum number of seconds to wait between attempts to recover
+    /// uinput. Defaults to 60.
+    pub uinput_max_backoff: Option<u64>,
+}
+
+impl GlobalConfig {
+    /// Load the [GlobalConfig] from the given YAML file
+    pub fn from_yaml_file(path: String) -> Result<GlobalConfig, LoadError> {
+        let file = std::fs::File::open(path)?;
+        let config: GlobalConfig = serde_yaml::from_reader(file)?;
+        Ok(config)
+    }
+
+    /// Returns the target devices of the target loadout with the given name
+    pub fn target_loadout(&self, name: &str) -> Option<Vec<String>> {
+        self.target_loadouts.as_ref()?.get(name).cloned()
+    }
+
+    /// Load the [GlobalConfig] from the default location. Returns an empty
+    /// config if the file does not exist.
+    pub fn load() -> GlobalConfig {
+        let path = path::get_global_config_path();
+        if !path.exists() {
+            return GlobalConfig::default();
+        }
+        let path = path.to_string_lossy().to_string();
+        match GlobalConfig::from_yaml_file(path.clone()) {
+            Ok(config) => config,
+            Err(e) => {
+                log::error!("Failed to load global config {path}: {e}");
+                GlobalConfig::default()
+            }
+        }
+    }
+}
+
+/// Udev tags, properties, and permissions applied to the input devices of
+/// target devices, e.g. to make them accessible to the gaming session on
+/// distributions where virtual devices are not.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct TargetPermissions {
+    /// Types of target devices to apply the permissions to (e.g. "xb360").
+    /// Applied to all target devices if not set.
+    pub target_types: Option<Vec<String>>,
+    /// Add the 'uaccess' tag so logind grants the active session access
+    pub uaccess: Option<bool>,
+    /// Assign the target devices to the given seat (e.g. "seat0")
+    pub seat: Option<String>,
+    /// Group that owns the device nodes (e.g. "input")
+    pub group: Option<String>,
+    /// Permissions of the device nodes (e.g. "0660")
+    pub mode: Option<String>,
+    /// Additional udev tags to add
+    pub tags: Option<Vec<String>>,
+    /// Udev properties to set (e.g. {"ID_INPUT_JOYSTICK": "1"})
+    pub properties: Option<HashMap<String, String>>,
+    /// Check that the tags and properties were applied after the target
+    /// device is created. Defaults to true.
+    pub verify: Option<bool>,
+}
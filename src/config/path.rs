@@ -30,6 +30,31 @@ pub fn get_profiles_path() -> PathBuf {
     base_path.join("profiles")
 }
 
+/// Returns the directory for persisted device calibrations
+/// (e.g. "/var/lib/inputplumber/calibration")
+pub fn get_calibration_path() -> PathBuf {
+    PathBuf::from("/var/lib/inputplumber/calibration")
+}
+
+/// Returns the directory for calibration overlays of individual controllers
+/// that are applied on any composite device the controller is part of
+/// (e.g. "/etc/inputplumber/capability_maps.d/calibration")
+pub fn get_controller_calibration_path() -> PathBuf {
+    PathBuf::from("/etc/inputplumber/capability_maps.d/calibration")
+}
+
+/// Returns the directory for persisted device settings
+/// (e.g. "/var/lib/inputplumber/settings")
+pub fn get_settings_path() -> PathBuf {
+    PathBuf::from("/var/lib/inputplumber/settings")
+}
+
+/// Returns the path to the service-wide configuration
+/// (e.g. "/etc/inputplumber/inputplumber.yaml")
+pub fn get_global_config_path() -> PathBuf {
+    PathBuf::from("/etc/inputplumber/inputplumber.yaml")
+}
+
 /// Returns a list of directories in preference order to find device configurations.
 /// E.g. ["/etc/inputplumber/devices.d", "/usr/share/inputplumber/devices"]
 pub fn get_devices_paths() -> Vec<PathBuf> {
@@ -0,0 +1,83 @@
+//! Aliases for capability names exposed over DBus
+use std::collections::HashMap;
+
+use crate::{
+    config::CompositeDeviceConfig,
+    input::capability::{Capability, Gamepad, Mouse},
+};
+
+/// Returns the DBus representation of the given capability. E.g.
+/// "Gamepad:Button:South"
+pub fn capability_string(cap: Capability) -> String {
+    match cap {
+        Capability::Gamepad(gamepad) => match gamepad {
+            Gamepad::Button(button) => format!("Gamepad:Button:{}", button),
+            Gamepad::Axis(axis) => format!("Gamepad:Axis:{}", axis),
+            Gamepad::Trigger(trigger) => format!("Gamepad:Trigger:{}", trigger),
+            Gamepad::Accelerometer => "Gamepad:Accelerometer".to_string(),
+            Gamepad::Gyro => "Gamepad:Gyro".to_string(),
+            Gamepad::Dial(dial) => format!("Gamepad:Dial:{}", dial),
+        },
+        Capability::Mouse(mouse) => match mouse {
+            Mouse::Motion => "Mouse:Motion".to_string(),
+            Mouse::Button(button) => format!("Mouse:Button:{}", button),
+        },
+        Capability::Keyboard(key) => format!("Keyboard:{}", key),
+        Capability::Switch(switch) => format!("Switch:{}", switch),
+        _ => cap.to_string(),
+    }
+}
+
+/// The [CapabilityAliases] table renames capabilities when they are exposed
+/// over DBus, so clients that depend on older capability names keep working
+/// when capabilities are renamed. Aliases are also accepted in place of the
+/// capability names in DBus method calls.
+#[derive(Debug, Clone, Default)]
+pub struct CapabilityAliases {
+    /// Map of capability names to their aliases
+    aliases: HashMap<String, String>,
+    /// Map of aliases to their capability names
+    names: HashMap<String, String>,
+}
+
+impl CapabilityAliases {
+    /// Create a new alias table from the given map of capability names to
+    /// their aliases
+    pub fn new(aliases: HashMap<String, String>) -> Self {
+        let names = aliases
+            .iter()
+            .map(|(name, alias)| (alias.clone(), name.clone()))
+            .collect();
+        Self { aliases, names }
+    }
+
+    /// Create a new alias table from the aliases defined in the given
+    /// composite device config
+    pub fn from_config(config: &CompositeDeviceConfig) -> Self {
+        let aliases = config
+            .options
+            .as_ref()
+            .and_then(|options| options.capability_aliases.clone())
+            .unwrap_or_default();
+        Self::new(aliases)
+    }
+
+    /// Returns the DBus representation of the given capability, using its
+    /// alias if one is defined
+    pub fn to_dbus(&self, cap: Capability) -> String {
+        let name = capability_string(cap);
+        match self.aliases.get(&name) {
+            Some(alias) => alias.clone(),
+            None => name,
+        }
+    }
+
+    /// Returns the capability name of the given capability string received
+    /// over DBus. Strings that are not an alias are returned unchanged.
+    pub fn resolve(&self, name: &str) -> String {
+        match self.names.get(name) {
+            Some(name) => name.clone(),
+            None => name.to_string(),
+        }
+    }
+}
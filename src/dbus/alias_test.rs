@@ -0,0 +1,29 @@
+use std::{collections::HashMap, str::FromStr};
+
+use crate::{
+    dbus::alias::CapabilityAliases,
+    input::capability::{Capability, Gamepad, GamepadButton},
+};
+
+#[test]
+fn test_capability_aliases() {
+    let aliases = HashMap::from([(
+        "Gamepad:Button:QuickAccess".to_string(),
+        "Gamepad:Button:Quick".to_string(),
+    )]);
+    let aliases = CapabilityAliases::new(aliases);
+
+    // Aliased capabilities use their alias
+    let cap = Capability::Gamepad(Gamepad::Button(GamepadButton::QuickAccess));
+    assert_eq!(aliases.to_dbus(cap.clone()), "Gamepad:Button:Quick");
+    let name = aliases.resolve("Gamepad:Button:Quick");
+    assert_eq!(Capability::from_str(name.as_str()), Ok(cap));
+
+    // Other capabilities keep their name
+    let cap = Capability::Gamepad(Gamepad::Button(GamepadButton::South));
+    assert_eq!(aliases.to_dbus(cap), "Gamepad:Button:South");
+    assert_eq!(
+        aliases.resolve("Gamepad:Button:QuickAccess"),
+        "Gamepad:Button:QuickAccess"
+    );
+}
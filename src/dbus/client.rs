@@ -0,0 +1,44 @@
+//! # D-Bus interface proxies for: `org.shadowblip.InputPlumber`
+//!
+//! Used by command line commands to talk to the running InputPlumber service.
+use zbus::proxy;
+
+#[proxy(
+    interface = "org.shadowblip.InputManager",
+    default_service = "org.shadowblip.InputPlumber",
+    default_path = "/org/shadowblip/InputPlumber/Manager"
+)]
+trait InputManager {
+    /// GetCompositeDevice method
+    fn get_composite_device(&self, id: &str) -> zbus::Result<String>;
+}
+
+#[proxy(
+    interface = "org.shadowblip.Input.CompositeDevice",
+    default_service = "org.shadowblip.InputPlumber"
+)]
+trait CompositeDevice {
+    /// LoadProfilePath method
+    fn load_profile_path(&self, path: &str) -> zbus::Result<()>;
+
+    /// Stop method
+    fn stop(&self) -> zbus::Result<()>;
+
+    /// InterceptMode property
+    #[zbus(property)]
+    fn intercept_mode(&self) -> zbus::Result<u32>;
+    #[zbus(property)]
+    fn set_intercept_mode(&self, value: u32) -> zbus::Result<()>;
+
+    /// Name property
+    #[zbus(property)]
+    fn name(&self) -> zbus::Result<String>;
+
+    /// ProfileName property
+    #[zbus(property)]
+    fn profile_name(&self) -> zbus::Result<String>;
+
+    /// Uuid property
+    #[zbus(property)]
+    fn uuid(&self) -> zbus::Result<String>;
+}
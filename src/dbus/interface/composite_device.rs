@@ -1,27 +1,149 @@
-use std::str::FromStr;
+use std::{collections::HashMap, str::FromStr, time::Duration};
 
 use zbus::{
     fdo,
+    object_server::SignalContext,
     zvariant::{self, Value},
 };
 use zbus_macros::interface;
 
-use crate::input::{
-    capability::{Capability, Gamepad, Mouse},
-    composite_device::{client::CompositeDeviceClient, InterceptMode},
-    event::{native::NativeEvent, value::InputValue},
+use crate::{
+    dbus::alias::CapabilityAliases,
+    input::{
+        capability::Capability,
+        composite_device::{
+            client::{ClientError, CompositeDeviceClient},
+            drift::DriftCorrection,
+            snapshot::DeviceSnapshot,
+            touchscreen::TouchscreenMode,
+            InterceptMode,
+        },
+        event::{
+            native::{NativeEvent, ScheduledNativeEvent},
+            value::InputValue,
+        },
+    },
 };
 
+/// Version of the intercept modes exposed by the InterceptModes property
+const INTERCEPT_MODES_VERSION: u32 = 1;
+
+/// Flag for SetInterceptModeChecked to skip capability validation
+const INTERCEPT_FLAG_FORCE: u32 = 1;
+
 /// The [CompositeDeviceInterface] provides a DBus interface that can be exposed for managing
 /// a [CompositeDevice]. It works by sending command messages to a channel that the
 /// [CompositeDevice] is listening on.
 pub struct CompositeDeviceInterface {
     composite_device: CompositeDeviceClient,
+    aliases: CapabilityAliases,
 }
 
 impl CompositeDeviceInterface {
-    pub fn new(composite_device: CompositeDeviceClient) -> CompositeDeviceInterface {
-        CompositeDeviceInterface { composite_device }
+    pub fn new(
+        composite_device: CompositeDeviceClient,
+        aliases: CapabilityAliases,
+    ) -> CompositeDeviceInterface {
+        CompositeDeviceInterface {
+            composite_device,
+            aliases,
+        }
+    }
+
+    /// Emit the InterceptActivated signal with the given capabilities that
+    /// triggered intercept mode.
+    pub async fn emit_intercept_activated(
+        ctxt: &SignalContext<'_>,
+        capabilities: Vec<Capability>,
+        aliases: &CapabilityAliases,
+    ) -> zbus::Result<()> {
+        let capability_strings = capabilities
+            .into_iter()
+            .map(|cap| aliases.to_dbus(cap))
+            .collect();
+        Self::intercept_activated(ctxt, capability_strings).await
+    }
+
+    /// Convert the given capabilities into capability strings for DBus
+    fn capability_strings<I>(&self, capabilities: I) -> Vec<String>
+    where
+        I: IntoIterator<Item = Capability>,
+    {
+        capabilities
+            .into_iter()
+            .map(|cap| self.aliases.to_dbus(cap))
+            .collect()
+    }
+
+    /// Create a [NativeEvent] from the given capability string and DBus value
+    fn parse_event(&self, event: String, value: zvariant::Value) -> fdo::Result<NativeEvent> {
+        let event = self.aliases.resolve(event.as_str());
+        let cap = Capability::from_str(event.as_str()).map_err(|_| {
+            fdo::Error::Failed(format!(
+                "Failed to parse event string {event} into capability."
+            ))
+        })?;
+
+        let val = match value {
+            zvariant::Value::Bool(v) => InputValue::Bool(v),
+            zvariant::Value::F64(v) => InputValue::Float(v),
+            zvariant::Value::Array(v) => match v.len() {
+                2 => {
+                    let x_val = v.first().unwrap();
+                    let y_val: &Value = v.get(1).unwrap().unwrap();
+                    let x = f64::try_from(x_val).map_err(|_| {
+                        fdo::Error::Failed("Failed to parse x value into float.".to_string())
+                    })?;
+                    let y = f64::try_from(y_val).map_err(|_| {
+                        fdo::Error::Failed("Failed to parse y value into float.".to_string())
+                    })?;
+                    InputValue::Vector2 {
+                        x: Some(x),
+                        y: Some(y),
+                    }
+                }
+                3 => {
+                    let x_val = v.first().unwrap();
+                    let y_val: &Value = v.get(1).unwrap().unwrap();
+                    let z_val: &Value = v.get(2).unwrap().unwrap();
+                    let x = f64::try_from(x_val).map_err(|_| {
+                        fdo::Error::Failed("Failed to parse x value into float.".to_string())
+                    })?;
+                    let y = f64::try_from(y_val).map_err(|_| {
+                        fdo::Error::Failed("Failed to parse y value into float.".to_string())
+                    })?;
+                    let z = f64::try_from(z_val).map_err(|_| {
+                        fdo::Error::Failed("Failed to parse z value into float.".to_string())
+                    })?;
+                    InputValue::Vector3 {
+                        x: Some(x),
+                        y: Some(y),
+                        z: Some(z),
+                    }
+                }
+                _ => InputValue::None,
+            },
+            _ => InputValue::None,
+        };
+
+        Ok(NativeEvent::new(cap, val))
+    }
+
+    /// Emit the ProfileReverted signal with the name of the restored profile
+    pub async fn emit_profile_reverted(
+        ctxt: &SignalContext<'_>,
+        profile_name: String,
+    ) -> zbus::Result<()> {
+        Self::profile_reverted(ctxt, profile_name).await
+    }
+
+    /// Emit the SourceDeviceRestarted signal with the id of the restarted
+    /// source device
+    pub async fn emit_source_device_restarted(
+        ctxt: &SignalContext<'_>,
+        device_id: String,
+    ) -> zbus::Result<()> {
+        Self::source_device_restarted(ctxt, device_id).await
     }
 }
 
@@ -36,6 +158,16 @@ impl CompositeDeviceInterface {
             .map_err(|e| fdo::Error::Failed(e.to_string()))
     }
 
+    /// Stable identifier of the composite device that does not change between
+    /// boots, unlike its DBus path
+    #[zbus(property)]
+    async fn uuid(&self) -> fdo::Result<String> {
+        self.composite_device
+            .get_uuid()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
     /// Name of the currently loaded profile
     #[zbus(property)]
     async fn profile_name(&self) -> fdo::Result<String> {
@@ -45,6 +177,19 @@ impl CompositeDeviceInterface {
             .map_err(|e| fdo::Error::Failed(e.to_string()))
     }
 
+    /// Where the currently loaded profile was loaded from. One of "file",
+    /// "embedded" if the default profile file is missing and the default
+    /// profile compiled into InputPlumber is used, or "yaml" if it was loaded
+    /// with LoadProfileFromYaml.
+    #[zbus(property)]
+    async fn profile_source(&self) -> fdo::Result<String> {
+        self.composite_device
+            .get_profile_source()
+            .await
+            .map(|source| source.as_str().to_string())
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
     /// Stop the composite device and all target devices
     async fn stop(&self) -> fdo::Result<()> {
         self.composite_device
@@ -53,6 +198,177 @@ impl CompositeDeviceInterface {
             .map_err(|e| fdo::Error::Failed(e.to_string()))
     }
 
+    /// Start calibrating the sticks and IMU of the device. The device should
+    /// be at rest when calibration starts, then each stick should be rotated
+    /// through its full range of motion before calling FinishCalibration.
+    async fn start_calibration(&self) -> fdo::Result<()> {
+        self.composite_device
+            .start_calibration()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Finish the calibration in progress, then persist and apply the results.
+    /// The results are also saved for each calibrated controller that reports
+    /// a unique identifier, and are applied whenever that controller is part
+    /// of any composite device.
+    async fn finish_calibration(&self) -> fdo::Result<()> {
+        self.composite_device
+            .finish_calibration()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Stick drift severity of the worst stick from 0.0 (no drift) to 1.0
+    /// (severe drift). Drift is only measured if drift detection is enabled
+    /// in the device config.
+    #[zbus(property)]
+    async fn drift_severity(&self) -> fdo::Result<f64> {
+        self.composite_device
+            .get_drift_severity()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Suggested stick drift corrections as a map of stick names to their
+    /// center x, center y, and deadzone. E.g. {"left_stick": (0.02, -0.01, 0.05)}
+    #[zbus(property)]
+    async fn drift_suggestions(&self) -> fdo::Result<HashMap<String, (f64, f64, f64)>> {
+        let suggestions = self
+            .composite_device
+            .get_drift_suggestions()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        let to_tuple = |c: DriftCorrection| (c.center_x, c.center_y, c.deadzone);
+        let mut values = HashMap::new();
+        if let Some(correction) = suggestions.left_stick {
+            values.insert("left_stick".to_string(), to_tuple(correction));
+        }
+        if let Some(correction) = suggestions.right_stick {
+            values.insert("right_stick".to_string(), to_tuple(correction));
+        }
+        Ok(values)
+    }
+
+    /// Whether or not suggested stick drift corrections are applied
+    /// automatically
+    #[zbus(property)]
+    async fn drift_auto_apply(&self) -> fdo::Result<bool> {
+        self.composite_device
+            .get_drift_auto_apply()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    #[zbus(property)]
+    async fn set_drift_auto_apply(&self, auto_apply: bool) -> zbus::Result<()> {
+        self.composite_device
+            .set_drift_auto_apply(auto_apply)
+            .await
+            .map_err(|err| zbus::Error::Failure(err.to_string()))?;
+        Ok(())
+    }
+
+    /// Apply and persist the currently suggested stick drift corrections
+    async fn apply_drift_correction(&self) -> fdo::Result<()> {
+        self.composite_device
+            .apply_drift_correction()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Remove all stick drift corrections
+    async fn clear_drift_correction(&self) -> fdo::Result<()> {
+        self.composite_device
+            .clear_drift_correction()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Number of events that were dropped before reaching a target device as
+    /// a map of drop reasons to a map of capabilities to counts. Drop reasons
+    /// include "not_implemented", "filtered", "duplicate", "intercepted",
+    /// "blocked_source", "inhibited", "no_target", and "touchscreen_blocked".
+    /// E.g.
+    /// {"no_target": {"Gamepad:Button:QuickAccess": 2}}
+    #[zbus(property)]
+    async fn dropped_events(&self) -> fdo::Result<HashMap<String, HashMap<String, u64>>> {
+        let counts = self
+            .composite_device
+            .get_dropped_events()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        let counts = counts
+            .into_iter()
+            .map(|(reason, caps)| {
+                let caps = caps
+                    .into_iter()
+                    .map(|(cap, count)| (self.aliases.to_dbus(cap), count))
+                    .collect();
+                (reason.as_str().to_string(), caps)
+            })
+            .collect();
+        Ok(counts)
+    }
+
+    /// Reset the counts of dropped events
+    async fn reset_dropped_events(&self) -> fdo::Result<()> {
+        self.composite_device
+            .reset_dropped_events()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Whether or not usage statistics of source inputs are collected. While
+    /// enabled, events of source devices with 'raw_passthrough' are processed
+    /// by the composite device instead of bypassing it, so they are counted.
+    #[zbus(property)]
+    async fn input_statistics_enabled(&self) -> fdo::Result<bool> {
+        self.composite_device
+            .get_input_statistics_enabled()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    #[zbus(property)]
+    async fn set_input_statistics_enabled(&self, enabled: bool) -> zbus::Result<()> {
+        self.composite_device
+            .set_input_statistics_enabled(enabled)
+            .await
+            .map_err(|err| zbus::Error::Failure(err.to_string()))?;
+        Ok(())
+    }
+
+    /// Usage statistics of the source inputs that were used while collection
+    /// was enabled, as a map of capabilities to the number of presses and a
+    /// histogram of axis magnitudes. Each of the 10 histogram buckets covers
+    /// an equal range of the magnitude between 0.0 and 1.0. E.g.
+    /// {"Gamepad:Button:South": (12, [0, 0, 0, 0, 0, 0, 0, 0, 0, 0])}
+    #[zbus(property)]
+    async fn input_statistics(&self) -> fdo::Result<HashMap<String, (u64, Vec<u64>)>> {
+        let statistics = self
+            .composite_device
+            .get_input_statistics()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        let statistics = statistics
+            .into_iter()
+            .map(|(cap, stats)| {
+                let histogram = stats.histogram.to_vec();
+                (self.aliases.to_dbus(cap), (stats.presses, histogram))
+            })
+            .collect();
+        Ok(statistics)
+    }
+
+    /// Reset the usage statistics of source inputs
+    async fn reset_input_statistics(&self) -> fdo::Result<()> {
+        self.composite_device
+            .reset_input_statistics()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
     /// Load the device profile from the given path
     async fn load_profile_path(&self, path: String) -> fdo::Result<()> {
         self.composite_device
@@ -69,6 +385,29 @@ impl CompositeDeviceInterface {
             .map_err(|e| fdo::Error::Failed(e.to_string()))
     }
 
+    /// Temporarily load the device profile from the given path. If the profile
+    /// is not confirmed with ConfirmProfile within the given number of
+    /// seconds, the previously loaded profile is restored.
+    async fn load_profile_temporary(&self, path: String, timeout_secs: u32) -> fdo::Result<()> {
+        if timeout_secs == 0 {
+            return Err(fdo::Error::InvalidArgs(
+                "Timeout must be greater than zero".to_string(),
+            ));
+        }
+        self.composite_device
+            .load_profile_temporary(path, timeout_secs as u64)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Confirm the profile loaded with LoadProfileTemporary so it is kept
+    async fn confirm_profile(&self) -> fdo::Result<()> {
+        self.composite_device
+            .confirm_profile()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
     /// Set the target input device types the composite device should emulate,
     /// such as ["gamepad", "mouse", "keyboard"]. This method will stop all
     /// current virtual devices for the composite device and create and attach
@@ -80,57 +419,92 @@ impl CompositeDeviceInterface {
             .map_err(|e| fdo::Error::Failed(e.to_string()))
     }
 
-    /// Directly write to the composite device's target devices with the given event
-    fn send_event(&self, event: String, value: zvariant::Value) -> fdo::Result<()> {
-        let cap = Capability::from_str(event.as_str()).map_err(|_| {
-            fdo::Error::Failed(format!(
-                "Failed to parse event string {event} into capability."
-            ))
-        })?;
+    /// Set the target devices of the target loadout with the given name, as
+    /// defined in the global config (e.g. "deck-like"). This replaces the
+    /// current target devices like SetTargetDevices.
+    async fn set_target_loadout(&self, name: String) -> fdo::Result<()> {
+        self.composite_device
+            .set_target_loadout(name)
+            .await
+            .map_err(|e| match e {
+                ClientError::ServiceError(e) => fdo::Error::InvalidArgs(e.to_string()),
+                e => fdo::Error::Failed(e.to_string()),
+            })
+    }
 
-        let val = match value {
-            zvariant::Value::Bool(v) => InputValue::Bool(v),
-            zvariant::Value::F64(v) => InputValue::Float(v),
-            zvariant::Value::Array(v) => match v.len() {
-                2 => {
-                    let x_val = v.first().unwrap();
-                    let y_val: &Value = v.get(1).unwrap().unwrap();
-                    let x = f64::try_from(x_val).map_err(|_| {
-                        fdo::Error::Failed("Failed to parse x value into float.".to_string())
-                    })?;
-                    let y = f64::try_from(y_val).map_err(|_| {
-                        fdo::Error::Failed("Failed to parse y value into float.".to_string())
-                    })?;
-                    InputValue::Vector2 {
-                        x: Some(x),
-                        y: Some(y),
-                    }
-                }
-                3 => {
-                    let x_val = v.first().unwrap();
-                    let y_val: &Value = v.get(1).unwrap().unwrap();
-                    let z_val: &Value = v.get(2).unwrap().unwrap();
-                    let x = f64::try_from(x_val).map_err(|_| {
-                        fdo::Error::Failed("Failed to parse x value into float.".to_string())
-                    })?;
-                    let y = f64::try_from(y_val).map_err(|_| {
-                        fdo::Error::Failed("Failed to parse y value into float.".to_string())
-                    })?;
-                    let z = f64::try_from(z_val).map_err(|_| {
-                        fdo::Error::Failed("Failed to parse z value into float.".to_string())
-                    })?;
-                    InputValue::Vector3 {
-                        x: Some(x),
-                        y: Some(y),
-                        z: Some(z),
-                    }
-                }
-                _ => InputValue::None,
-            },
-            _ => InputValue::None,
-        };
+    /// Atomically load the device profile at the given path and switch to the
+    /// given target device types, such as ["gamepad", "mouse", "keyboard"].
+    /// The given target device types replace any defined in the profile.
+    /// If any step fails, the previous profile and target devices are kept.
+    async fn apply_configuration(
+        &self,
+        profile_path: String,
+        target_device_types: Vec<String>,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> fdo::Result<()> {
+        self.composite_device
+            .apply_configuration(profile_path.clone(), target_device_types.clone())
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        Self::configuration_changed(&ctxt, profile_path, target_device_types).await?;
+        Ok(())
+    }
 
-        let event = NativeEvent::new(cap, val);
+    /// Export a JSON snapshot of the runtime state of the composite device,
+    /// including the loaded profile, intercept mode, target devices, and
+    /// blocked source devices
+    async fn export_snapshot(&self) -> fdo::Result<String> {
+        let snapshot = self
+            .composite_device
+            .get_snapshot()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        snapshot.to_json().map_err(fdo::Error::Failed)
+    }
+
+    /// Restore the runtime state of the composite device from a JSON snapshot
+    /// created with ExportSnapshot
+    async fn restore_snapshot(&self, snapshot: String) -> fdo::Result<()> {
+        let snapshot =
+            DeviceSnapshot::from_json(snapshot.as_str()).map_err(fdo::Error::InvalidArgs)?;
+        self.composite_device
+            .restore_snapshot(snapshot)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Emitted when a configuration was applied using ApplyConfiguration
+    #[zbus(signal)]
+    async fn configuration_changed(
+        ctxt: &SignalContext<'_>,
+        profile_path: String,
+        target_device_types: Vec<String>,
+    ) -> zbus::Result<()>;
+
+    /// Emitted when a profile loaded with LoadProfileTemporary was not
+    /// confirmed in time, with the name of the restored profile
+    #[zbus(signal)]
+    async fn profile_reverted(ctxt: &SignalContext<'_>, profile_name: String) -> zbus::Result<()>;
+
+    /// Emitted when a source device was restarted because it was stuck
+    /// reading input, with the id of the source device (e.g. "hidraw://hidraw0")
+    #[zbus(signal)]
+    async fn source_device_restarted(
+        ctxt: &SignalContext<'_>,
+        device_id: String,
+    ) -> zbus::Result<()>;
+
+    /// Emitted when the intercept activation chord triggers intercept mode,
+    /// with the list of capabilities that triggered it
+    #[zbus(signal)]
+    async fn intercept_activated(
+        ctxt: &SignalContext<'_>,
+        capabilities: Vec<String>,
+    ) -> zbus::Result<()>;
+
+    /// Directly write to the composite device's target devices with the given event
+    fn send_event(&self, event: String, value: zvariant::Value) -> fdo::Result<()> {
+        let event = self.parse_event(event, value)?;
 
         self.composite_device
             .blocking_write_send_event(event)
@@ -139,8 +513,45 @@ impl CompositeDeviceInterface {
         Ok(())
     }
 
+    /// Write the given event to the composite device's target devices after
+    /// the given delay in milliseconds. If the repeat interval is greater than
+    /// zero, the event is repeated at that interval until it was written
+    /// 'count' times, or until it is cancelled if 'count' is zero. Returns the
+    /// id of the scheduled event.
+    async fn schedule_event(
+        &self,
+        event: String,
+        value: zvariant::Value<'_>,
+        delay_ms: u64,
+        repeat_interval_ms: u64,
+        count: u32,
+    ) -> fdo::Result<u64> {
+        let event = self.parse_event(event, value)?;
+        let mut event = ScheduledNativeEvent::new(event, Duration::from_millis(delay_ms));
+        if repeat_interval_ms > 0 {
+            event = event.with_repeat(Duration::from_millis(repeat_interval_ms), count);
+        }
+        self.composite_device
+            .schedule_event(event)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Cancel the scheduled event with the given id, including any remaining
+    /// repetitions
+    async fn cancel_scheduled_event(&self, id: u64) -> fdo::Result<()> {
+        self.composite_device
+            .cancel_scheduled_event(id)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
     /// Directly write to the composite device's target devices with the given button event list
-    async fn send_button_chord(&self, mut events: Vec<String>) -> fdo::Result<()> {
+    async fn send_button_chord(&self, events: Vec<String>) -> fdo::Result<()> {
+        let mut events: Vec<String> = events
+            .iter()
+            .map(|event| self.aliases.resolve(event))
+            .collect();
         // Store built native events to send in a command to the CompositeDevice
         let mut chord: Vec<NativeEvent> = Vec::new();
 
@@ -193,6 +604,7 @@ impl CompositeDeviceInterface {
 
         // Iterate in the given order for press events
         for event_str in activation_events {
+            let event_str = self.aliases.resolve(event_str.as_str());
             // Validate the event is valid and create a NativeEvent
             if event_str.contains("Button") || event_str.starts_with("Keyboard") {
                 let cap = Capability::from_str(event_str.as_str()).map_err(|_| {
@@ -207,6 +619,7 @@ impl CompositeDeviceInterface {
                 )));
             };
         }
+        let target_event = self.aliases.resolve(target_event.as_str());
         let mut target_cap: Capability = Capability::None;
         if target_event.contains("Button") || target_event.starts_with("Keyboard") {
             let cap = Capability::from_str(target_event.as_str()).map_err(|_| {
@@ -234,27 +647,7 @@ impl CompositeDeviceInterface {
             .await
             .map_err(|e| fdo::Error::Failed(e.to_string()))?;
 
-        let mut capability_strings = Vec::new();
-        for cap in capabilities {
-            let str = match cap {
-                Capability::Gamepad(gamepad) => match gamepad {
-                    Gamepad::Button(button) => format!("Gamepad:Button:{}", button),
-                    Gamepad::Axis(axis) => format!("Gamepad:Axis:{}", axis),
-                    Gamepad::Trigger(trigger) => format!("Gamepad:Trigger:{}", trigger),
-                    Gamepad::Accelerometer => "Gamepad:Accelerometer".to_string(),
-                    Gamepad::Gyro => "Gamepad:Gyro".to_string(),
-                },
-                Capability::Mouse(mouse) => match mouse {
-                    Mouse::Motion => "Mouse:Motion".to_string(),
-                    Mouse::Button(button) => format!("Mouse:Button:{}", button),
-                },
-                Capability::Keyboard(key) => format!("Keyboard:{}", key),
-                _ => cap.to_string(),
-            };
-            capability_strings.push(str);
-        }
-
-        Ok(capability_strings)
+        Ok(self.capability_strings(capabilities))
     }
 
     /// List of capabilities that all target devices implement
@@ -266,27 +659,20 @@ impl CompositeDeviceInterface {
             .await
             .map_err(|e| fdo::Error::Failed(e.to_string()))?;
 
-        let mut capability_strings = Vec::new();
-        for cap in capabilities {
-            let str = match cap {
-                Capability::Gamepad(gamepad) => match gamepad {
-                    Gamepad::Button(button) => format!("Gamepad:Button:{}", button),
-                    Gamepad::Axis(axis) => format!("Gamepad:Axis:{}", axis),
-                    Gamepad::Trigger(trigger) => format!("Gamepad:Trigger:{}", trigger),
-                    Gamepad::Accelerometer => "Gamepad:Accelerometer".to_string(),
-                    Gamepad::Gyro => "Gamepad:Gyro".to_string(),
-                },
-                Capability::Mouse(mouse) => match mouse {
-                    Mouse::Motion => "Mouse:Motion".to_string(),
-                    Mouse::Button(button) => format!("Mouse:Button:{}", button),
-                },
-                Capability::Keyboard(key) => format!("Keyboard:{}", key),
-                _ => cap.to_string(),
-            };
-            capability_strings.push(str);
-        }
+        Ok(self.capability_strings(capabilities))
+    }
 
-        Ok(capability_strings)
+    /// List of capabilities that can reach target devices after applying the
+    /// current device profile
+    #[zbus(property)]
+    async fn translated_capabilities(&self) -> fdo::Result<Vec<String>> {
+        let capabilities = self
+            .composite_device
+            .get_translated_capabilities()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        Ok(self.capability_strings(capabilities))
     }
 
     /// List of source devices that this composite device is processing inputs for
@@ -301,6 +687,43 @@ impl CompositeDeviceInterface {
         Ok(paths)
     }
 
+    /// Ids of source devices that are blocked from passing their input events
+    /// to target devices. E.g. ["evdev://event0"]
+    #[zbus(property)]
+    async fn blocked_source_devices(&self) -> fdo::Result<Vec<String>> {
+        let ids = self
+            .composite_device
+            .get_blocked_source_devices()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        Ok(ids)
+    }
+
+    /// Block the source device with the given id (e.g. "evdev://event0") from
+    /// passing its input events to target devices until it is unblocked
+    async fn block_source_device(&self, device_id: String) -> fdo::Result<()> {
+        self.composite_device
+            .set_source_device_blocked(device_id, true)
+            .await
+            .map_err(|e| match e {
+                ClientError::ServiceError(e) => fdo::Error::InvalidArgs(e.to_string()),
+                e => fdo::Error::Failed(e.to_string()),
+            })
+    }
+
+    /// Unblock the source device with the given id (e.g. "evdev://event0") so
+    /// its input events are passed to target devices again
+    async fn unblock_source_device(&self, device_id: String) -> fdo::Result<()> {
+        self.composite_device
+            .set_source_device_blocked(device_id, false)
+            .await
+            .map_err(|e| match e {
+                ClientError::ServiceError(e) => fdo::Error::InvalidArgs(e.to_string()),
+                e => fdo::Error::Failed(e.to_string()),
+            })
+    }
+
     /// The intercept mode of the composite device.
     #[zbus(property)]
     async fn intercept_mode(&self) -> fdo::Result<u32> {
@@ -310,23 +733,15 @@ impl CompositeDeviceInterface {
             .await
             .map_err(|e| fdo::Error::Failed(e.to_string()))?;
 
-        match mode {
-            InterceptMode::None => Ok(0),
-            InterceptMode::Pass => Ok(1),
-            InterceptMode::Always => Ok(2),
-            InterceptMode::GamepadOnly => Ok(3),
-        }
+        Ok(mode.into())
     }
 
+    /// Set the intercept mode of the composite device. Invalid modes are
+    /// treated as 'None' for compatibility. Use SetInterceptModeChecked to
+    /// validate the mode.
     #[zbus(property)]
     async fn set_intercept_mode(&self, mode: u32) -> zbus::Result<()> {
-        let mode = match mode {
-            0 => InterceptMode::None,
-            1 => InterceptMode::Pass,
-            2 => InterceptMode::Always,
-            3 => InterceptMode::GamepadOnly,
-            _ => InterceptMode::None,
-        };
+        let mode = InterceptMode::try_from(mode).unwrap_or(InterceptMode::None);
         self.composite_device
             .set_intercept_mode(mode)
             .await
@@ -334,6 +749,71 @@ impl CompositeDeviceInterface {
         Ok(())
     }
 
+    /// Available intercept modes and their numeric values that can be used
+    /// with the InterceptMode property, e.g. {"None": 0, "Pass": 1}
+    #[zbus(property)]
+    async fn intercept_modes(&self) -> fdo::Result<HashMap<String, u32>> {
+        let modes = InterceptMode::ALL
+            .iter()
+            .map(|mode| (mode.as_str().to_string(), (*mode).into()))
+            .collect();
+        Ok(modes)
+    }
+
+    /// Version of the InterceptModes enumeration. This is incremented whenever
+    /// intercept modes are added or changed.
+    #[zbus(property)]
+    async fn intercept_modes_version(&self) -> fdo::Result<u32> {
+        Ok(INTERCEPT_MODES_VERSION)
+    }
+
+    /// Set the intercept mode of the composite device after validating that
+    /// the mode is supported by the device. Returns an InvalidArgs error for
+    /// unknown modes or flags and a NotSupported error if the mode cannot be
+    /// used with the device's capabilities. Supported flags:
+    /// 1 - Force: set the mode without validating capability support
+    async fn set_intercept_mode_checked(&self, mode: u32, flags: u32) -> fdo::Result<()> {
+        let mode = InterceptMode::try_from(mode).map_err(fdo::Error::InvalidArgs)?;
+        if flags & !INTERCEPT_FLAG_FORCE != 0 {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "Invalid intercept mode flags: {flags}"
+            )));
+        }
+        let force = flags & INTERCEPT_FLAG_FORCE != 0;
+        self.composite_device
+            .set_intercept_mode_checked(mode, force)
+            .await
+            .map_err(|e| match e {
+                ClientError::ServiceError(e) => fdo::Error::NotSupported(e.to_string()),
+                e => fdo::Error::Failed(e.to_string()),
+            })?;
+        Ok(())
+    }
+
+    /// How input from the physical touchscreen is emitted to target devices.
+    /// One of "passthrough", "mouse", or "block". Touches are always sent
+    /// over DBus while the intercept mode is 'Always'.
+    #[zbus(property)]
+    async fn touchscreen_mode(&self) -> fdo::Result<String> {
+        let mode = self
+            .composite_device
+            .get_touchscreen_mode()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        Ok(mode.as_str().to_string())
+    }
+
+    #[zbus(property)]
+    async fn set_touchscreen_mode(&self, mode: String) -> zbus::Result<()> {
+        let mode = TouchscreenMode::from_str(mode.as_str()).map_err(zbus::Error::Failure)?;
+        self.composite_device
+            .set_touchscreen_mode(mode)
+            .await
+            .map_err(|err| zbus::Error::Failure(err.to_string()))?;
+        Ok(())
+    }
+
     /// Target devices that this [CompositeDevice] is managing
     #[zbus(property)]
     async fn target_devices(&self) -> fdo::Result<Vec<String>> {
@@ -0,0 +1,247 @@
+use std::time::Duration;
+
+use zbus::fdo;
+use zbus_macros::interface;
+
+use crate::{
+    config::HapticsToRumble,
+    input::{
+        composite_device::client::CompositeDeviceClient,
+        output_event::{HapticCurve, DEFAULT_HAPTIC_CLICK_STRENGTH, DEFAULT_HAPTIC_TICK_STRENGTH},
+    },
+};
+
+/// Maximum duration in milliseconds of a test rumble
+const MAX_TEST_RUMBLE_MS: u32 = 10000;
+
+/// The [ForceFeedbackInterface] provides a DBus interface that can be exposed
+/// for managing force feedback output of a [CompositeDevice].
+pub struct ForceFeedbackInterface {
+    composite_device: CompositeDeviceClient,
+}
+
+impl ForceFeedbackInterface {
+    pub fn new(composite_device: CompositeDeviceClient) -> ForceFeedbackInterface {
+        ForceFeedbackInterface { composite_device }
+    }
+
+    /// Returns the current options used to convert haptics into rumble
+    async fn get_haptics_to_rumble(&self) -> fdo::Result<HapticsToRumble> {
+        self.composite_device
+            .get_haptics_to_rumble()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Update the options used to convert haptics into rumble with the given
+    /// function
+    async fn update_haptics_to_rumble(
+        &self,
+        update: impl FnOnce(&mut HapticsToRumble),
+    ) -> zbus::Result<()> {
+        let mut options = self
+            .composite_device
+            .get_haptics_to_rumble()
+            .await
+            .map_err(|err| zbus::Error::Failure(err.to_string()))?;
+        update(&mut options);
+        self.composite_device
+            .set_haptics_to_rumble(options)
+            .await
+            .map_err(|err| zbus::Error::Failure(err.to_string()))?;
+        Ok(())
+    }
+}
+
+#[interface(name = "org.shadowblip.Output.ForceFeedback")]
+impl ForceFeedbackInterface {
+    /// Force feedback output gain in percent (0-100). This is applied on top
+    /// of any gain set by games.
+    #[zbus(property)]
+    async fn gain(&self) -> fdo::Result<u32> {
+        self.composite_device
+            .get_ff_gain()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    #[zbus(property)]
+    async fn set_gain(&self, gain: u32) -> zbus::Result<()> {
+        if gain > 100 {
+            return Err(zbus::Error::Failure(
+                "Gain must be between 0 and 100".to_string(),
+            ));
+        }
+        self.composite_device
+            .set_ff_gain(gain)
+            .await
+            .map_err(|err| zbus::Error::Failure(err.to_string()))?;
+        Ok(())
+    }
+
+    /// List of application identifiers (glob patterns) for which force
+    /// feedback is muted while the application is active. The active
+    /// application is set with the ActiveApplication property of the manager.
+    #[zbus(property)]
+    async fn mute_apps(&self) -> fdo::Result<Vec<String>> {
+        self.composite_device
+            .get_ff_mute_apps()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    #[zbus(property)]
+    async fn set_mute_apps(&self, apps: Vec<String>) -> zbus::Result<()> {
+        self.composite_device
+            .set_ff_mute_apps(apps)
+            .await
+            .map_err(|err| zbus::Error::Failure(err.to_string()))?;
+        Ok(())
+    }
+
+    /// Whether force feedback is currently muted because the active
+    /// application is in the list of applications to mute
+    #[zbus(property)]
+    async fn muted(&self) -> fdo::Result<bool> {
+        self.composite_device
+            .get_ff_muted()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Rumble all source devices capable of force feedback at the given
+    /// intensity (0.0 - 1.0) for the given duration in milliseconds. Returns
+    /// the number of source devices the rumble was sent to.
+    async fn test_rumble(&self, intensity: f64, duration_ms: u32) -> fdo::Result<u32> {
+        if !(0.0..=1.0).contains(&intensity) {
+            return Err(fdo::Error::InvalidArgs(
+                "Intensity must be between 0.0 and 1.0".to_string(),
+            ));
+        }
+        if duration_ms > MAX_TEST_RUMBLE_MS {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "Duration must be at most {MAX_TEST_RUMBLE_MS}ms"
+            )));
+        }
+        let duration = Duration::from_millis(duration_ms as u64);
+        let count = self
+            .composite_device
+            .test_rumble(intensity, duration)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        Ok(count as u32)
+    }
+
+    /// Whether trackpad haptic commands are converted into rumble for source
+    /// devices without trackpad haptics
+    #[zbus(property)]
+    async fn haptics_to_rumble_enabled(&self) -> fdo::Result<bool> {
+        let options = self.get_haptics_to_rumble().await?;
+        Ok(options.enabled.unwrap_or(true))
+    }
+
+    #[zbus(property)]
+    async fn set_haptics_to_rumble_enabled(&self, enabled: bool) -> zbus::Result<()> {
+        self.update_haptics_to_rumble(|options| options.enabled = Some(enabled))
+            .await
+    }
+
+    /// Curve used to scale the haptic intensity when converting haptics into
+    /// rumble. One of "linear", "quadratic", "cubic", or "sqrt".
+    #[zbus(property)]
+    async fn haptics_to_rumble_curve(&self) -> fdo::Result<String> {
+        let options = self.get_haptics_to_rumble().await?;
+        Ok(options.curve.unwrap_or_else(|| "linear".to_string()))
+    }
+
+    #[zbus(property)]
+    async fn set_haptics_to_rumble_curve(&self, curve: String) -> zbus::Result<()> {
+        if !HapticCurve::NAMES.contains(&curve.as_str()) {
+            return Err(zbus::Error::Failure(format!(
+                "Curve must be one of: {}",
+                HapticCurve::NAMES.join(", ")
+            )));
+        }
+        self.update_haptics_to_rumble(|options| options.curve = Some(curve))
+            .await
+    }
+
+    /// Multiplier applied to the rumble intensity when converting haptics
+    /// into rumble
+    #[zbus(property)]
+    async fn haptics_to_rumble_scale(&self) -> fdo::Result<f64> {
+        let options = self.get_haptics_to_rumble().await?;
+        Ok(options.scale.unwrap_or(1.0))
+    }
+
+    #[zbus(property)]
+    async fn set_haptics_to_rumble_scale(&self, scale: f64) -> zbus::Result<()> {
+        if !scale.is_finite() || scale < 0.0 {
+            return Err(zbus::Error::Failure(
+                "Scale must be a positive number".to_string(),
+            ));
+        }
+        self.update_haptics_to_rumble(|options| options.scale = Some(scale))
+            .await
+    }
+
+    /// Rumble intensity (0.0 - 1.0) of haptic "tick" commands before the
+    /// curve is applied
+    #[zbus(property)]
+    async fn haptics_to_rumble_tick_strength(&self) -> fdo::Result<f64> {
+        let options = self.get_haptics_to_rumble().await?;
+        Ok(options
+            .tick_strength
+            .unwrap_or(DEFAULT_HAPTIC_TICK_STRENGTH))
+    }
+
+    #[zbus(property)]
+    async fn set_haptics_to_rumble_tick_strength(&self, strength: f64) -> zbus::Result<()> {
+        if !(0.0..=1.0).contains(&strength) {
+            return Err(zbus::Error::Failure(
+                "Strength must be between 0.0 and 1.0".to_string(),
+            ));
+        }
+        self.update_haptics_to_rumble(|options| options.tick_strength = Some(strength))
+            .await
+    }
+
+    /// Rumble intensity (0.0 - 1.0) of haptic "click" commands before the
+    /// curve is applied
+    #[zbus(property)]
+    async fn haptics_to_rumble_click_strength(&self) -> fdo::Result<f64> {
+        let options = self.get_haptics_to_rumble().await?;
+        Ok(options
+            .click_strength
+            .unwrap_or(DEFAULT_HAPTIC_CLICK_STRENGTH))
+    }
+
+    #[zbus(property)]
+    async fn set_haptics_to_rumble_click_strength(&self, strength: f64) -> zbus::Result<()> {
+        if !(0.0..=1.0).contains(&strength) {
+            return Err(zbus::Error::Failure(
+                "Strength must be between 0.0 and 1.0".to_string(),
+            ));
+        }
+        self.update_haptics_to_rumble(|options| options.click_strength = Some(strength))
+            .await
+    }
+
+    /// Multiplier applied to how long the rumble plays for each haptic command
+    #[zbus(property)]
+    async fn haptics_to_rumble_duration_scale(&self) -> fdo::Result<f64> {
+        let options = self.get_haptics_to_rumble().await?;
+        Ok(options.duration_scale.unwrap_or(1.0))
+    }
+
+    #[zbus(property)]
+    async fn set_haptics_to_rumble_duration_scale(&self, scale: f64) -> zbus::Result<()> {
+        if !scale.is_finite() || scale < 0.0 {
+            return Err(zbus::Error::Failure(
+                "Scale must be a positive number".to_string(),
+            ));
+        }
+        self.update_haptics_to_rumble(|options| options.duration_scale = Some(scale))
+            .await
+    }
+}
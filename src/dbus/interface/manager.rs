@@ -1,12 +1,18 @@
-use std::time::Duration;
+use std::{str::FromStr, time::Duration};
 
 use tokio::sync::mpsc;
-use zbus::fdo;
+use zbus::{fdo, object_server::SignalContext};
 use zbus_macros::interface;
 
 use crate::{
     config::CompositeDeviceConfig,
-    input::{manager::ManagerCommand, target::TargetDeviceTypeId},
+    input::{
+        composite_device::input_mode::InputMode,
+        latency,
+        manager::{ManagerCommand, ManagerError},
+        target::TargetDeviceTypeId,
+    },
+    logging,
 };
 
 /// The [ManagerInterface] provides a DBus interface that can be exposed for managing
@@ -66,6 +72,138 @@ impl ManagerInterface {
         Ok(())
     }
 
+    /// Identifier of the application currently in focus (e.g. a Steam app id
+    /// or a desktop file name). This is set by a session integration that
+    /// tracks the focused application and is used to apply per-application
+    /// behavior, like muting force feedback. An empty string means no
+    /// application is active.
+    #[zbus(property)]
+    async fn active_application(&self) -> fdo::Result<String> {
+        let (sender, mut receiver) = mpsc::channel(1);
+        self.tx
+            .send_timeout(
+                ManagerCommand::GetActiveApp { sender },
+                Duration::from_millis(500),
+            )
+            .await
+            .map_err(|err| fdo::Error::Failed(err.to_string()))?;
+
+        // Read the response from the manager
+        let Some(response) = receiver.recv().await else {
+            return Err(fdo::Error::Failed("No response from manager".to_string()));
+        };
+        Ok(response.unwrap_or_default())
+    }
+    #[zbus(property)]
+    async fn set_active_application(&self, value: String) -> zbus::Result<()> {
+        let app = if value.is_empty() { None } else { Some(value) };
+        self.tx
+            .send_timeout(
+                ManagerCommand::SetActiveApp(app),
+                Duration::from_millis(500),
+            )
+            .await
+            .map_err(|err| zbus::Error::Failure(err.to_string()))?;
+        Ok(())
+    }
+
+    /// Global input mode that all composite devices were switched to with
+    /// [ManagerInterface::set_input_mode]. Either "game", "desktop", or an
+    /// empty string if no mode was set.
+    #[zbus(property)]
+    async fn input_mode(&self) -> fdo::Result<String> {
+        let (sender, mut receiver) = mpsc::channel(1);
+        self.tx
+            .send_timeout(
+                ManagerCommand::GetInputMode { sender },
+                Duration::from_millis(500),
+            )
+            .await
+            .map_err(|err| fdo::Error::Failed(err.to_string()))?;
+
+        // Read the response from the manager
+        let Some(response) = receiver.recv().await else {
+            return Err(fdo::Error::Failed("No response from manager".to_string()));
+        };
+        Ok(response.map(|mode| mode.to_string()).unwrap_or_default())
+    }
+
+    /// Switch all composite devices to the device profile and target devices
+    /// their config defines for the given input mode ("game" or "desktop").
+    /// Composite devices without a configuration for the mode are unchanged.
+    async fn set_input_mode(
+        &self,
+        mode: String,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> fdo::Result<()> {
+        let mode = InputMode::from_str(mode.as_str()).map_err(fdo::Error::InvalidArgs)?;
+        self.tx
+            .send_timeout(
+                ManagerCommand::SetInputMode(mode),
+                Duration::from_millis(500),
+            )
+            .await
+            .map_err(|err| fdo::Error::Failed(err.to_string()))?;
+        self.input_mode_changed(&ctxt).await?;
+        Ok(())
+    }
+
+    /// Whether or not target devices that use uinput are currently unavailable
+    /// (e.g. the uinput module is not loaded yet). While degraded, InputPlumber
+    /// keeps trying to recover and will manage devices again once it succeeds.
+    #[zbus(property)]
+    async fn target_support_degraded(&self) -> fdo::Result<bool> {
+        let (sender, mut receiver) = mpsc::channel(1);
+        self.tx
+            .send_timeout(
+                ManagerCommand::GetTargetSupportDegraded { sender },
+                Duration::from_millis(500),
+            )
+            .await
+            .map_err(|err| fdo::Error::Failed(err.to_string()))?;
+
+        // Read the response from the manager
+        let Some(response) = receiver.recv().await else {
+            return Err(fdo::Error::Failed("No response from manager".to_string()));
+        };
+        Ok(response)
+    }
+
+    /// Current log level directives. E.g. "info,inputplumber::input::composite_device=trace"
+    #[zbus(property)]
+    fn log_level(&self) -> fdo::Result<String> {
+        Ok(logging::get_filter())
+    }
+
+    /// Set the log level at runtime using a comma separated list of directives
+    /// in the form of 'level' or 'module=level'. Modules of InputPlumber may
+    /// omit the crate name. E.g. "info,input::composite_device=trace"
+    async fn set_log_level(
+        &self,
+        directives: String,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> fdo::Result<()> {
+        logging::set_filter(directives.as_str()).map_err(fdo::Error::InvalidArgs)?;
+        self.log_level_changed(&ctxt).await?;
+        Ok(())
+    }
+
+    /// Whether low latency mode is enabled. In low latency mode all devices
+    /// poll at a higher rate, events are not batched, and device threads use
+    /// realtime scheduling where permitted, at the cost of higher power usage.
+    #[zbus(property)]
+    fn low_latency_mode(&self) -> fdo::Result<bool> {
+        Ok(latency::is_enabled())
+    }
+
+    /// Enable or disable low latency mode. Defaults are restored when it is
+    /// disabled.
+    #[zbus(property)]
+    fn set_low_latency_mode(&self, value: bool) -> zbus::Result<()> {
+        latency::set_enabled(value);
+        Ok(())
+    }
+
     /// Returns a list of supported target device names. E.g. ["InputPlumber Mouse", "Microsoft
     /// XBox 360 Gamepad"]
     #[zbus(property)]
@@ -96,13 +234,76 @@ impl ManagerInterface {
         Ok("".to_string())
     }
 
+    /// Create a composite device that combines the source devices at the
+    /// given device paths (e.g. ["/dev/input/event3", "/dev/hidraw0"]) without
+    /// a composite device configuration file. Returns the DBus path of the
+    /// created composite device.
+    async fn create_composite_device_from_sources(
+        &self,
+        sources: Vec<String>,
+        name: String,
+    ) -> fdo::Result<String> {
+        let (sender, mut receiver) = mpsc::channel(1);
+        self.tx
+            .send_timeout(
+                ManagerCommand::CreateCompositeDeviceFromSources {
+                    sources,
+                    name,
+                    sender,
+                },
+                Duration::from_millis(500),
+            )
+            .await
+            .map_err(|err| fdo::Error::Failed(err.to_string()))?;
+
+        // Read the response from the manager
+        let Some(response) = receiver.recv().await else {
+            return Err(fdo::Error::Failed("No response from manager".to_string()));
+        };
+        let device_path = match response {
+            Ok(path) => path,
+            Err(e) => {
+                let err = format!("Failed to create composite device: {e:?}");
+                return Err(fdo::Error::Failed(err));
+            }
+        };
+
+        Ok(device_path)
+    }
+
+    /// Returns the DBus path of the composite device with the given stable
+    /// identifier (UUID). The DBus path of a composite device is also accepted.
+    async fn get_composite_device(&self, id: String) -> fdo::Result<String> {
+        let (sender, mut receiver) = mpsc::channel(1);
+        self.tx
+            .send_timeout(
+                ManagerCommand::GetCompositeDevicePath {
+                    id: id.clone(),
+                    sender,
+                },
+                Duration::from_millis(500),
+            )
+            .await
+            .map_err(|err| fdo::Error::Failed(err.to_string()))?;
+
+        // Read the response from the manager
+        let Some(response) = receiver.recv().await else {
+            return Err(fdo::Error::Failed("No response from manager".to_string()));
+        };
+        response.ok_or_else(|| fdo::Error::Failed(format!("No composite device found: {id}")))
+    }
+
     /// Create a target device of the given type. Returns the DBus path to
     /// the created target device.
     async fn create_target_device(&self, kind: String) -> fdo::Result<String> {
         let (sender, mut receiver) = mpsc::channel(1);
         self.tx
             .send_timeout(
-                ManagerCommand::CreateTargetDevice { kind, sender },
+                ManagerCommand::CreateTargetDevice {
+                    kind,
+                    options: None,
+                    sender,
+                },
                 Duration::from_millis(500),
             )
             .await
@@ -114,6 +315,10 @@ impl ManagerInterface {
         };
         let device_path = match response {
             Ok(path) => path,
+            Err(ManagerError::TargetDevicePreflightFailed(report)) => {
+                let err = format!("Failed to create target device: {report}");
+                return Err(fdo::Error::Failed(err));
+            }
             Err(e) => {
                 let err = format!("Failed to create target device: {e:?}");
                 return Err(fdo::Error::Failed(err));
@@ -166,6 +371,26 @@ impl ManagerInterface {
         Ok(())
     }
 
+    /// Temporarily block all composite devices from emitting events to target
+    /// devices. Input state is still tracked while inhibited. This can be used
+    /// by lock screens to ensure no input reaches applications underneath.
+    async fn inhibit(&self) -> fdo::Result<()> {
+        self.tx
+            .send_timeout(ManagerCommand::SetInhibited(true), Duration::from_secs(5))
+            .await
+            .map_err(|err| fdo::Error::Failed(err.to_string()))?;
+        Ok(())
+    }
+
+    /// Allow all composite devices to emit events to target devices again
+    async fn uninhibit(&self) -> fdo::Result<()> {
+        self.tx
+            .send_timeout(ManagerCommand::SetInhibited(false), Duration::from_secs(5))
+            .await
+            .map_err(|err| fdo::Error::Failed(err.to_string()))?;
+        Ok(())
+    }
+
     /// Used to prepare InputPlumber for system suspend
     async fn hook_sleep(&self) -> fdo::Result<()> {
         let (sender, mut receiver) = mpsc::channel(1);
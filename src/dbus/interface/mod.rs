@@ -1,4 +1,6 @@
 pub mod composite_device;
+pub mod force_feedback;
 pub mod manager;
+pub mod performance;
 pub mod source;
 pub mod target;
@@ -0,0 +1,94 @@
+use std::sync::Arc;
+
+use zbus::{fdo, object_server::SignalContext};
+use zbus_macros::interface;
+
+use crate::input::target::performance::TargetPerformance;
+
+/// The [PerformanceInterface] provides a DBus interface that exposes
+/// performance counters of a target device. A queue depth close to the
+/// queue capacity means the target device is not keeping up with events.
+pub struct PerformanceInterface {
+    performance: Arc<TargetPerformance>,
+}
+
+impl PerformanceInterface {
+    pub fn new(performance: Arc<TargetPerformance>) -> PerformanceInterface {
+        PerformanceInterface { performance }
+    }
+}
+
+#[interface(name = "org.shadowblip.Performance")]
+impl PerformanceInterface {
+    /// Total number of events written to the device
+    #[zbus(property)]
+    async fn events_written(&self) -> fdo::Result<u64> {
+        Ok(self.performance.events_written())
+    }
+
+    /// Time in microseconds it took to write the last event
+    #[zbus(property)]
+    async fn write_latency_us(&self) -> fdo::Result<u64> {
+        Ok(self.performance.write_latency_last_us())
+    }
+
+    /// Average time in microseconds it took to write an event
+    #[zbus(property)]
+    async fn write_latency_avg_us(&self) -> fdo::Result<u64> {
+        Ok(self.performance.write_latency_avg_us())
+    }
+
+    /// Longest time in microseconds it took to write a single event
+    #[zbus(property)]
+    async fn write_latency_max_us(&self) -> fdo::Result<u64> {
+        Ok(self.performance.write_latency_max_us())
+    }
+
+    /// Time in microseconds between reading the last event from its source
+    /// device and it reaching the device
+    #[zbus(property)]
+    async fn event_latency_us(&self) -> fdo::Result<u64> {
+        Ok(self.performance.event_latency_last_us())
+    }
+
+    /// Longest time in microseconds between reading an event from its
+    /// source device and it reaching the device
+    #[zbus(property)]
+    async fn event_latency_max_us(&self) -> fdo::Result<u64> {
+        Ok(self.performance.event_latency_max_us())
+    }
+
+    /// Number of commands waiting to be processed by the device
+    #[zbus(property)]
+    async fn queue_depth(&self) -> fdo::Result<u32> {
+        Ok(self.performance.queue_depth() as u32)
+    }
+
+    /// Largest number of commands that were waiting to be processed
+    #[zbus(property)]
+    async fn queue_depth_max(&self) -> fdo::Result<u32> {
+        Ok(self.performance.queue_depth_max() as u32)
+    }
+
+    /// Number of commands the queue can hold before events are delayed
+    #[zbus(property)]
+    async fn queue_capacity(&self) -> fdo::Result<u32> {
+        Ok(self.performance.queue_capacity() as u32)
+    }
+
+    /// Reset all performance counters
+    async fn reset_counters(&self) -> fdo::Result<()> {
+        self.performance.reset();
+        Ok(())
+    }
+
+    /// Emitted when the event latency of the device stayed above the
+    /// configured threshold. Includes the latency in microseconds and a JSON
+    /// snapshot of the device state.
+    #[zbus(signal)]
+    pub async fn high_latency(
+        ctxt: &SignalContext<'_>,
+        latency_us: u64,
+        snapshot: String,
+    ) -> zbus::Result<()>;
+}
@@ -0,0 +1,67 @@
+use std::error::Error;
+
+use zbus::{fdo, Connection};
+use zbus_macros::interface;
+
+use crate::{
+    drivers::firmware::{query_firmware_info, FirmwareInfo},
+    input::source::hidraw::get_dbus_path,
+    udev::device::UdevDevice,
+};
+
+/// DBusInterface exposing firmware and hardware information of a known
+/// controller
+pub struct SourceFirmwareInterface {
+    info: FirmwareInfo,
+}
+
+impl SourceFirmwareInterface {
+    pub fn new(info: FirmwareInfo) -> SourceFirmwareInterface {
+        SourceFirmwareInterface { info }
+    }
+
+    /// Queries the firmware information of the given device and creates a
+    /// new instance of the source firmware interface on DBus if the device
+    /// is a known controller.
+    pub async fn listen_on_dbus(
+        conn: Connection,
+        sys_name: String,
+        device: UdevDevice,
+    ) -> Result<(), Box<dyn Error>> {
+        // Querying the device uses blocking feature report requests
+        let info = tokio::task::spawn_blocking(move || {
+            query_firmware_info(&device).map_err(|e| e.to_string())
+        })
+        .await??;
+        let Some(info) = info else {
+            return Ok(());
+        };
+        log::debug!("Got firmware info for {sys_name}: {info:?}");
+
+        let path = get_dbus_path(sys_name);
+        let iface = SourceFirmwareInterface::new(info);
+        conn.object_server().at(path, iface).await?;
+        Ok(())
+    }
+}
+
+#[interface(name = "org.shadowblip.Input.Source.Firmware")]
+impl SourceFirmwareInterface {
+    /// Version of the controller firmware
+    #[zbus(property)]
+    async fn firmware_version(&self) -> fdo::Result<String> {
+        Ok(self.info.firmware_version.clone())
+    }
+
+    /// Serial number of the controller
+    #[zbus(property)]
+    async fn serial_number(&self) -> fdo::Result<String> {
+        Ok(self.info.serial_number.clone())
+    }
+
+    /// Hardware (board) revision of the controller
+    #[zbus(property)]
+    async fn board_revision(&self) -> fdo::Result<String> {
+        Ok(self.info.board_revision.clone())
+    }
+}
@@ -1,9 +1,16 @@
-use std::error::Error;
+use std::{error::Error, ffi::CString};
 
-use zbus::{fdo, Connection};
+use zbus::{fdo, message::Header, Connection};
 use zbus_macros::interface;
 
-use crate::{input::source::hidraw::get_dbus_path, udev::device::UdevDevice};
+use crate::{
+    dbus::polkit::{check_authorization, ACTION_GET_FEATURE_REPORT, ACTION_SEND_FEATURE_REPORT},
+    input::source::hidraw::get_dbus_path,
+    udev::{audio, device::UdevDevice},
+};
+
+/// Maximum size in bytes of a HID report
+const MAX_REPORT_SIZE: u16 = 4096;
 
 /// DBusInterface exposing information about a HIDRaw device
 pub struct SourceHIDRawInterface {
@@ -87,4 +94,128 @@ impl SourceHIDRawInterface {
     async fn sysfs_path(&self) -> fdo::Result<String> {
         Ok(self.device.devpath())
     }
+
+    /// List of ALSA sound cards that are part of the same USB device, such as
+    /// the speaker and headset jack of a DualSense controller. Each entry is
+    /// the card number (e.g. 1 for "hw:1") and the card identifier.
+    #[zbus(property)]
+    async fn sound_cards(&self) -> fdo::Result<Vec<(u32, String)>> {
+        let cards = audio::find_sound_cards(self.device.syspath().as_str())
+            .map_err(|e| fdo::Error::IOError(e.to_string()))?;
+        let cards = cards
+            .into_iter()
+            .map(|card| (card.number, card.id))
+            .collect();
+        Ok(cards)
+    }
+
+    /// Read the feature report with the given report id from the device. The
+    /// length is the size of the report in bytes including the report id.
+    /// Returns the bytes read, starting with the report id. Requires the
+    /// caller to be authorized for the 'get-feature-report' polkit action.
+    async fn get_feature_report(
+        &self,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] header: Header<'_>,
+        report_id: u8,
+        length: u16,
+    ) -> fdo::Result<Vec<u8>> {
+        if length == 0 || length > MAX_REPORT_SIZE {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "Report length must be between 1 and {MAX_REPORT_SIZE}"
+            )));
+        }
+        check_authorization(conn, &header, ACTION_GET_FEATURE_REPORT).await?;
+
+        // Reading the report uses a blocking feature report request
+        let path = self.device.devnode();
+        log::debug!("Reading feature report {report_id} from {path}");
+        let result = tokio::task::spawn_blocking(move || {
+            read_feature_report(path, report_id, length as usize).map_err(|e| e.to_string())
+        })
+        .await;
+        match result {
+            Ok(Ok(report)) => Ok(report),
+            Ok(Err(e)) => Err(fdo::Error::IOError(e)),
+            Err(e) => Err(fdo::Error::Failed(e.to_string())),
+        }
+    }
+
+    /// Send the given feature report to the device and read back the
+    /// response. The report must start with the report id. If the response
+    /// length is greater than 0, the feature report with the same report id
+    /// is read after sending and returned, starting with the report id.
+    /// Otherwise an empty response is returned. Requires the caller to be
+    /// authorized for the 'send-feature-report' polkit action.
+    async fn send_feature_report(
+        &self,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] header: Header<'_>,
+        report: Vec<u8>,
+        response_length: u16,
+    ) -> fdo::Result<Vec<u8>> {
+        if report.is_empty() || report.len() > MAX_REPORT_SIZE as usize {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "Report length must be between 1 and {MAX_REPORT_SIZE}"
+            )));
+        }
+        if response_length > MAX_REPORT_SIZE {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "Response length must be at most {MAX_REPORT_SIZE}"
+            )));
+        }
+        check_authorization(conn, &header, ACTION_SEND_FEATURE_REPORT).await?;
+
+        // Sending the report uses blocking feature report requests
+        let path = self.device.devnode();
+        log::debug!("Sending feature report {} to {path}", report[0]);
+        let result = tokio::task::spawn_blocking(move || {
+            send_feature_report(path, report, response_length as usize).map_err(|e| e.to_string())
+        })
+        .await;
+        match result {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(e)) => Err(fdo::Error::IOError(e)),
+            Err(e) => Err(fdo::Error::Failed(e.to_string())),
+        }
+    }
+}
+
+/// Read the feature report with the given report id and length from the
+/// hidraw device at the given path
+fn read_feature_report(
+    path: String,
+    report_id: u8,
+    length: usize,
+) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    let c_path = CString::new(path)?;
+    let api = hidapi::HidApi::new()?;
+    let device = api.open_path(&c_path)?;
+    let mut buf = vec![0; length];
+    buf[0] = report_id;
+    let bytes_read = device.get_feature_report(&mut buf)?;
+    buf.truncate(bytes_read);
+    Ok(buf)
+}
+
+/// Send the given feature report to the hidraw device at the given path and
+/// read back the feature report with the same report id if the given response
+/// length is greater than 0.
+fn send_feature_report(
+    path: String,
+    report: Vec<u8>,
+    response_length: usize,
+) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    let c_path = CString::new(path)?;
+    let api = hidapi::HidApi::new()?;
+    let device = api.open_path(&c_path)?;
+    device.send_feature_report(&report)?;
+    if response_length == 0 {
+        return Ok(vec![]);
+    }
+    let mut buf = vec![0; response_length];
+    buf[0] = report[0];
+    let bytes_read = device.get_feature_report(&mut buf)?;
+    buf.truncate(bytes_read);
+    Ok(buf)
 }
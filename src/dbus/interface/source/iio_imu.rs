@@ -1,10 +1,13 @@
 use std::error::Error;
 
-use crate::udev::device::{AttributeGetter, AttributeSetter, UdevDevice};
+use crate::udev::device::{AttributeGetter, UdevDevice};
 use zbus::{fdo, Connection};
 use zbus_macros::interface;
 
-use crate::input::source::iio::get_dbus_path;
+use crate::input::source::iio::{
+    get_dbus_path,
+    settings::{self, ImuSetting},
+};
 
 /// DBusInterface exposing information about a HIDRaw device
 pub struct SourceIioImuInterface {
@@ -109,30 +112,14 @@ impl SourceIioImuInterface {
 
     #[zbus(property)]
     async fn set_accel_sample_rate(&self, sample_rate: f64) -> zbus::Result<()> {
-        let Ok(mut dev) = self.device.get_device() else {
-            return Ok(());
-        };
-        match dev.set_attribute_on_tree(
-            "in_accel_sampling_frequency",
-            sample_rate.to_string().as_str(),
-        ) {
-            Ok(result) => Ok(result),
-            Err(e) => Err(zbus::Error::Failure(e.to_string())),
-        }
+        settings::set(&self.device, ImuSetting::AccelSampleRate, sample_rate)
+            .map_err(|e| zbus::Error::Failure(e.to_string()))
     }
 
     #[zbus(property)]
     async fn set_angvel_sample_rate(&self, sample_rate: f64) -> zbus::Result<()> {
-        let Ok(mut dev) = self.device.get_device() else {
-            return Ok(());
-        };
-        match dev.set_attribute_on_tree(
-            "in_anglvel_sampling_frequency",
-            sample_rate.to_string().as_str(),
-        ) {
-            Ok(result) => Ok(result),
-            Err(e) => Err(zbus::Error::Failure(e.to_string())),
-        }
+        settings::set(&self.device, ImuSetting::AngvelSampleRate, sample_rate)
+            .map_err(|e| zbus::Error::Failure(e.to_string()))
     }
 
     #[zbus(property)]
@@ -189,23 +176,13 @@ impl SourceIioImuInterface {
 
     #[zbus(property)]
     async fn set_accel_scale(&self, scale: f64) -> zbus::Result<()> {
-        let Ok(mut dev) = self.device.get_device() else {
-            return Ok(());
-        };
-        match dev.set_attribute_on_tree("in_accel_scale", scale.to_string().as_str()) {
-            Ok(result) => Ok(result),
-            Err(e) => Err(zbus::Error::Failure(e.to_string())),
-        }
+        settings::set(&self.device, ImuSetting::AccelScale, scale)
+            .map_err(|e| zbus::Error::Failure(e.to_string()))
     }
 
     #[zbus(property)]
     async fn set_angvel_scale(&self, scale: f64) -> zbus::Result<()> {
-        let Ok(mut dev) = self.device.get_device() else {
-            return Ok(());
-        };
-        match dev.set_attribute_on_tree("in_anglvel_scale", scale.to_string().as_str()) {
-            Ok(result) => Ok(result),
-            Err(e) => Err(zbus::Error::Failure(e.to_string())),
-        }
+        settings::set(&self.device, ImuSetting::AngvelScale, scale)
+            .map_err(|e| zbus::Error::Failure(e.to_string()))
     }
 }
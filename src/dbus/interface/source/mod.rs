@@ -1,4 +1,6 @@
 pub mod evdev;
+pub mod firmware;
 pub mod hidraw;
 pub mod iio_imu;
+pub mod msi_claw;
 pub mod udev;
@@ -0,0 +1,83 @@
+use std::error::Error;
+
+use zbus::{fdo, Connection};
+use zbus_macros::interface;
+
+use crate::{
+    drivers::msi_claw::{
+        driver::{is_supported, set_requested_mode, Driver},
+        hid_report::GamepadMode,
+    },
+    input::source::hidraw::get_dbus_path,
+    udev::device::UdevDevice,
+};
+
+/// DBusInterface exposing the hardware mode of an MSI Claw controller
+pub struct SourceMsiClawInterface {
+    device: UdevDevice,
+}
+
+impl SourceMsiClawInterface {
+    pub fn new(device: UdevDevice) -> SourceMsiClawInterface {
+        SourceMsiClawInterface { device }
+    }
+
+    /// Creates a new instance of the source MSI Claw interface on DBus if the
+    /// given device is the vendor interface of an MSI Claw controller.
+    pub async fn listen_on_dbus(
+        conn: Connection,
+        sys_name: String,
+        device: UdevDevice,
+    ) -> Result<(), Box<dyn Error>> {
+        let vid = device.id_vendor();
+        let pid = device.id_product();
+        if !is_supported(vid, pid, device.interface_number()) {
+            return Ok(());
+        }
+
+        let path = get_dbus_path(sys_name);
+        let iface = SourceMsiClawInterface::new(device);
+        conn.object_server().at(path, iface).await?;
+        Ok(())
+    }
+}
+
+#[interface(name = "org.shadowblip.Input.Source.MsiClaw")]
+impl SourceMsiClawInterface {
+    /// Current hardware mode of the controller, such as "xinput"
+    #[zbus(property)]
+    async fn mode(&self) -> fdo::Result<String> {
+        let mode = GamepadMode::from_pid(self.device.id_product()).unwrap_or_default();
+        Ok(mode.to_string())
+    }
+
+    /// Hardware modes the controller can be switched to
+    #[zbus(property)]
+    async fn modes(&self) -> fdo::Result<Vec<String>> {
+        let modes = GamepadMode::SELECTABLE
+            .iter()
+            .map(|mode| mode.to_string())
+            .collect();
+        Ok(modes)
+    }
+
+    /// Switch the controller to the given hardware mode, one of "xinput",
+    /// "dinput" or "keyboard". The controller reconnects in the new mode,
+    /// and stays in the new mode until the daemon restarts.
+    async fn set_mode(&self, mode: String) -> fdo::Result<()> {
+        let Some(mode) = GamepadMode::from_name(mode.as_str()) else {
+            return Err(fdo::Error::InvalidArgs(format!("Invalid mode: {mode}")));
+        };
+        set_requested_mode(mode);
+
+        // Writing to the device is blocking
+        let device = self.device.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut driver = Driver::new(device)?;
+            driver.set_mode(mode)
+        })
+        .await
+        .map_err(|e| fdo::Error::Failed(e.to_string()))?
+        .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+}
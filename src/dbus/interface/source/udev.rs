@@ -3,7 +3,7 @@ use std::{collections::HashMap, error::Error};
 use zbus::{fdo, Connection};
 use zbus_macros::interface;
 
-use crate::udev::device::UdevDevice;
+use crate::{input::source::quirks, udev::device::UdevDevice};
 
 /// The [SourceUdevDeviceInterface] provides a DBus interface to expose udev
 /// information over dbus
@@ -110,4 +110,16 @@ impl SourceUdevDeviceInterface {
     async fn properties(&self) -> fdo::Result<HashMap<String, String>> {
         Ok(self.device.get_properties())
     }
+
+    /// Returns the number of release-press bounces that were suppressed for
+    /// each debounced capability of the device. E.g.
+    /// {"Gamepad:Button:South": 3}
+    #[zbus(property)]
+    async fn suppressed_bounces(&self) -> fdo::Result<HashMap<String, u64>> {
+        let bounces = quirks::suppressed_bounces(self.device.sysname().as_str())
+            .into_iter()
+            .map(|(cap, count)| (cap.to_string(), count))
+            .collect();
+        Ok(bounces)
+    }
 }
@@ -0,0 +1,117 @@
+use std::{
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use zbus::{fdo, object_server::SignalContext};
+use zbus_macros::interface;
+
+use crate::input::target::dbus::{AccelUnits, GyroUnits, ImuSettings};
+
+/// The [TargetDBusInterface] provides a DBus interface that can be exposed for managing
+/// a [DBusDevice]. It works by sending command messages to a channel that the
+/// [DBusDevice] is listening on.
+pub struct TargetDBusInterface {
+    imu_settings: Arc<Mutex<ImuSettings>>,
+}
+
+impl TargetDBusInterface {
+    pub fn new(imu_settings: Arc<Mutex<ImuSettings>>) -> TargetDBusInterface {
+        TargetDBusInterface { imu_settings }
+    }
+}
+
+#[interface(name = "org.shadowblip.Input.DBusDevice")]
+impl TargetDBusInterface {
+    /// Name of the DBus device
+    #[zbus(property)]
+    async fn name(&self) -> fdo::Result<String> {
+        Ok("DBusDevice".into())
+    }
+
+    /// Units that gyro values are emitted in. Can be "deg/s" or "rad/s".
+    #[zbus(property)]
+    async fn gyro_units(&self) -> fdo::Result<String> {
+        let settings = self.imu_settings.lock().unwrap();
+        Ok(settings.gyro_units.as_str().to_string())
+    }
+
+    #[zbus(property)]
+    async fn set_gyro_units(&self, units: String) -> zbus::Result<()> {
+        let units = GyroUnits::from_str(units.as_str()).map_err(zbus::Error::Failure)?;
+        self.imu_settings.lock().unwrap().gyro_units = units;
+        Ok(())
+    }
+
+    /// Maximum absolute value of emitted gyro values in the current gyro
+    /// units. A value of 0.0 means the values are unlimited.
+    #[zbus(property)]
+    async fn gyro_range(&self) -> fdo::Result<f64> {
+        Ok(self.imu_settings.lock().unwrap().gyro_range)
+    }
+
+    #[zbus(property)]
+    async fn set_gyro_range(&self, range: f64) -> zbus::Result<()> {
+        self.imu_settings.lock().unwrap().gyro_range = range.max(0.0);
+        Ok(())
+    }
+
+    /// Units that accelerometer values are emitted in. Can be "m/s^2" or "g".
+    #[zbus(property)]
+    async fn accel_units(&self) -> fdo::Result<String> {
+        let settings = self.imu_settings.lock().unwrap();
+        Ok(settings.accel_units.as_str().to_string())
+    }
+
+    #[zbus(property)]
+    async fn set_accel_units(&self, units: String) -> zbus::Result<()> {
+        let units = AccelUnits::from_str(units.as_str()).map_err(zbus::Error::Failure)?;
+        self.imu_settings.lock().unwrap().accel_units = units;
+        Ok(())
+    }
+
+    /// Maximum absolute value of emitted accelerometer values in the current
+    /// accelerometer units. A value of 0.0 means the values are unlimited.
+    #[zbus(property)]
+    async fn accel_range(&self) -> fdo::Result<f64> {
+        Ok(self.imu_settings.lock().unwrap().accel_range)
+    }
+
+    #[zbus(property)]
+    async fn set_accel_range(&self, range: f64) -> zbus::Result<()> {
+        self.imu_settings.lock().unwrap().accel_range = range.max(0.0);
+        Ok(())
+    }
+
+    /// Emitted when an input event occurs
+    #[zbus(signal)]
+    pub async fn input_event(
+        ctxt: &SignalContext<'_>,
+        event: String,
+        value: f64,
+    ) -> zbus::Result<()>;
+
+    /// Emitted when a touch event occurs.
+    #[zbus(signal)]
+    pub async fn touch_event(
+        ctxt: &SignalContext<'_>,
+        event: String,
+        index: u32,
+        is_touching: bool,
+        pressure: f64,
+        x: f64,
+        y: f64,
+    ) -> zbus::Result<()>;
+
+    /// Emitted when an IMU event occurs. The event is either "gyro" or "accel"
+    /// and the values are in the units defined by the GyroUnits and AccelUnits
+    /// properties.
+    #[zbus(signal)]
+    pub async fn imu_event(
+        ctxt: &SignalContext<'_>,
+        event: String,
+        x: f64,
+        y: f64,
+        z: f64,
+    ) -> zbus::Result<()>;
+}
@@ -0,0 +1,64 @@
+use std::sync::{Arc, Mutex};
+
+use zbus::fdo;
+use zbus_macros::interface;
+
+use crate::input::target::debug::EventLog;
+
+/// The [TargetDebugInterface] provides a DBus interface to query the events
+/// recorded by a [DebugDevice].
+pub struct TargetDebugInterface {
+    events: Arc<Mutex<EventLog>>,
+}
+
+impl TargetDebugInterface {
+    pub fn new(events: Arc<Mutex<EventLog>>) -> TargetDebugInterface {
+        TargetDebugInterface { events }
+    }
+}
+
+#[interface(name = "org.shadowblip.Input.DebugDevice")]
+impl TargetDebugInterface {
+    /// Name of the debug device
+    #[zbus(property)]
+    async fn name(&self) -> fdo::Result<String> {
+        Ok("DebugDevice".into())
+    }
+
+    /// Maximum number of events that are kept. The oldest events are dropped
+    /// once this is reached.
+    #[zbus(property)]
+    async fn buffer_size(&self) -> fdo::Result<u32> {
+        Ok(self.events.lock().unwrap().capacity() as u32)
+    }
+
+    #[zbus(property)]
+    async fn set_buffer_size(&self, size: u32) -> zbus::Result<()> {
+        self.events.lock().unwrap().set_capacity(size as usize);
+        Ok(())
+    }
+
+    /// Number of events currently recorded
+    #[zbus(property)]
+    async fn event_count(&self) -> fdo::Result<u32> {
+        Ok(self.events.lock().unwrap().len() as u32)
+    }
+
+    /// Returns up to the given number of the most recently received events,
+    /// oldest first. Each event is a tuple of the time it was received in
+    /// milliseconds since the UNIX epoch, its capability, and its value.
+    async fn get_events(&self, count: u32) -> fdo::Result<Vec<(u64, String, String)>> {
+        let events = self.events.lock().unwrap().latest(count as usize);
+        let events = events
+            .into_iter()
+            .map(|event| (event.timestamp_ms, event.capability, event.value))
+            .collect();
+        Ok(events)
+    }
+
+    /// Remove all recorded events
+    async fn clear(&self) -> fdo::Result<()> {
+        self.events.lock().unwrap().clear();
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+use std::str::FromStr;
+
+use zbus::fdo;
+use zbus_macros::interface;
+
+use crate::input::{
+    capability::{Capability, Keyboard},
+    event::{native::NativeEvent, value::InputValue},
+    target::client::TargetDeviceClient,
+};
+
+/// The [TargetKeyboardInterface] provides a DBus interface that can be exposed
+/// for managing a [KeyboardDevice]. It works by sending command messages to a
+/// channel that the [KeyboardDevice] is listening on.
+pub struct TargetKeyboardInterface {
+    target_device: TargetDeviceClient,
+}
+
+impl TargetKeyboardInterface {
+    pub fn new(target_device: TargetDeviceClient) -> TargetKeyboardInterface {
+        TargetKeyboardInterface { target_device }
+    }
+}
+
+#[interface(name = "org.shadowblip.Input.Keyboard")]
+impl TargetKeyboardInterface {
+    /// Name of the keyboard device
+    #[zbus(property)]
+    async fn name(&self) -> fdo::Result<String> {
+        Ok("Keyboard".into())
+    }
+
+    /// Send the given key to the virtual keyboard (e.g. "KeyA")
+    async fn send_key(&self, key: String, value: bool) -> fdo::Result<()> {
+        let Ok(key) = Keyboard::from_str(key.as_str()) else {
+            return Err(fdo::Error::InvalidArgs(format!("Invalid key: {key}")));
+        };
+        let event = NativeEvent::new(Capability::Keyboard(key), InputValue::Bool(value));
+        self.target_device
+            .write_event(event)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,7 @@
+pub mod dbus;
+pub mod debug;
+pub mod gamepad;
+pub mod keyboard;
+pub mod mouse;
+pub mod pointer;
+pub mod touchscreen;
@@ -0,0 +1,46 @@
+use zbus::fdo;
+use zbus_macros::interface;
+
+use crate::input::{
+    capability::{Capability, Mouse},
+    event::{native::NativeEvent, value::InputValue},
+    target::client::TargetDeviceClient,
+};
+
+/// The [TargetMouseInterface] provides a DBus interface that can be exposed
+/// for managing a [MouseDevice]. It works by sending command messages to a
+/// channel that the [MouseDevice] is listening on.
+pub struct TargetMouseInterface {
+    target_device: TargetDeviceClient,
+}
+
+impl TargetMouseInterface {
+    pub fn new(target_device: TargetDeviceClient) -> TargetMouseInterface {
+        TargetMouseInterface { target_device }
+    }
+}
+
+#[interface(name = "org.shadowblip.Input.Mouse")]
+impl TargetMouseInterface {
+    /// Name of the mouse device
+    #[zbus(property)]
+    async fn name(&self) -> fdo::Result<String> {
+        Ok("Mouse".into())
+    }
+
+    /// Move the virtual mouse by the given amount relative to the current
+    /// cursor position.
+    async fn move_cursor(&self, x: i32, y: i32) -> fdo::Result<()> {
+        let value = InputValue::Vector2 {
+            x: Some(x as f64),
+            y: Some(y as f64),
+        };
+        let event = NativeEvent::new(Capability::Mouse(Mouse::Motion), value);
+        self.target_device
+            .write_event(event)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+use std::{
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use zbus::fdo;
+use zbus_macros::interface;
+
+use crate::input::target::mouse::{AccelerationProfile, PointerSettings};
+
+/// The [TargetPointerInterface] provides a DBus interface to change how
+/// translated mouse motion (e.g. from sticks or gyro) is accelerated and
+/// smoothed by a [MouseDevice]. Changes are applied on the next poll.
+pub struct TargetPointerInterface {
+    settings: Arc<Mutex<PointerSettings>>,
+}
+
+impl TargetPointerInterface {
+    pub fn new(settings: Arc<Mutex<PointerSettings>>) -> TargetPointerInterface {
+        TargetPointerInterface { settings }
+    }
+}
+
+#[interface(name = "org.shadowblip.Input.Pointer")]
+impl TargetPointerInterface {
+    /// Acceleration curve applied to translated mouse motion. Can be "flat",
+    /// "quadratic" or "cubic".
+    #[zbus(property)]
+    async fn acceleration_profile(&self) -> fdo::Result<String> {
+        let settings = self.settings.lock().unwrap();
+        Ok(settings.acceleration.as_str().to_string())
+    }
+
+    #[zbus(property)]
+    async fn set_acceleration_profile(&self, profile: String) -> zbus::Result<()> {
+        let profile =
+            AccelerationProfile::from_str(profile.as_str()).map_err(zbus::Error::Failure)?;
+        self.settings.lock().unwrap().acceleration = profile;
+        Ok(())
+    }
+
+    /// List of all supported acceleration profiles
+    #[zbus(property)]
+    async fn acceleration_profiles(&self) -> fdo::Result<Vec<String>> {
+        let profiles = AccelerationProfile::ALL
+            .iter()
+            .map(|profile| profile.as_str().to_string())
+            .collect();
+        Ok(profiles)
+    }
+
+    /// Time in milliseconds over which changes in mouse speed are smoothed.
+    /// A value of 0 disables smoothing.
+    #[zbus(property)]
+    async fn smoothing_ms(&self) -> fdo::Result<u32> {
+        Ok(self.settings.lock().unwrap().smoothing_ms)
+    }
+
+    #[zbus(property)]
+    async fn set_smoothing_ms(&self, smoothing_ms: u32) -> zbus::Result<()> {
+        self.settings.lock().unwrap().smoothing_ms = smoothing_ms;
+        Ok(())
+    }
+}
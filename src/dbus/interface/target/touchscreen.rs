@@ -0,0 +1,82 @@
+use std::{
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use zbus::fdo;
+use zbus_macros::interface;
+
+use crate::input::target::touchscreen::{TouchscreenOrientation, TouchscreenSettings};
+
+/// The [TargetTouchscreenInterface] provides a DBus interface that can be
+/// exposed for managing a [TouchscreenDevice]. Changes to the orientation and
+/// resolution are applied to the device before the next touch event is
+/// emitted.
+pub struct TargetTouchscreenInterface {
+    settings: Arc<Mutex<TouchscreenSettings>>,
+}
+
+impl TargetTouchscreenInterface {
+    pub fn new(settings: Arc<Mutex<TouchscreenSettings>>) -> TargetTouchscreenInterface {
+        TargetTouchscreenInterface { settings }
+    }
+}
+
+#[interface(name = "org.shadowblip.Input.Touchscreen")]
+impl TargetTouchscreenInterface {
+    /// Name of the touchscreen device
+    #[zbus(property)]
+    async fn name(&self) -> fdo::Result<String> {
+        Ok("Touchscreen".into())
+    }
+
+    /// Orientation of the touchscreen. Can be "normal", "upside-down", "left"
+    /// or "right".
+    #[zbus(property)]
+    async fn orientation(&self) -> fdo::Result<String> {
+        let settings = self.settings.lock().unwrap();
+        Ok(settings.orientation.as_str().to_string())
+    }
+
+    #[zbus(property)]
+    async fn set_orientation(&self, orientation: String) -> zbus::Result<()> {
+        let orientation =
+            TouchscreenOrientation::from_str(orientation.as_str()).map_err(zbus::Error::Failure)?;
+        self.settings.lock().unwrap().orientation = orientation;
+        Ok(())
+    }
+
+    /// Width of the touchscreen panel before rotation is applied
+    #[zbus(property)]
+    async fn width(&self) -> fdo::Result<u32> {
+        Ok(self.settings.lock().unwrap().width as u32)
+    }
+
+    #[zbus(property)]
+    async fn set_width(&self, width: u32) -> zbus::Result<()> {
+        self.settings.lock().unwrap().width = to_size(width)?;
+        Ok(())
+    }
+
+    /// Height of the touchscreen panel before rotation is applied
+    #[zbus(property)]
+    async fn height(&self) -> fdo::Result<u32> {
+        Ok(self.settings.lock().unwrap().height as u32)
+    }
+
+    #[zbus(property)]
+    async fn set_height(&self, height: u32) -> zbus::Result<()> {
+        self.settings.lock().unwrap().height = to_size(height)?;
+        Ok(())
+    }
+}
+
+/// Validate the given touchscreen size
+fn to_size(value: u32) -> zbus::Result<u16> {
+    match u16::try_from(value) {
+        Ok(size) if size > 0 => Ok(size),
+        _ => Err(zbus::Error::Failure(format!(
+            "Invalid touchscreen size: {value}"
+        ))),
+    }
+}
@@ -0,0 +1,43 @@
+//! # D-Bus interface proxies for: `org.freedesktop.login1`
+//!
+//! Used to track the active session of each seat so target devices can be
+//! assigned to the seat of the active session, and to report user activity
+//! from target devices to the active session.
+use zbus::{proxy, zvariant::OwnedObjectPath};
+
+#[proxy(
+    interface = "org.freedesktop.login1.Manager",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1"
+)]
+trait LoginManager {
+    /// ListSeats method
+    fn list_seats(&self) -> zbus::Result<Vec<(String, OwnedObjectPath)>>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.login1.Seat",
+    default_service = "org.freedesktop.login1"
+)]
+trait Seat {
+    /// ActiveSession property
+    #[zbus(property)]
+    fn active_session(&self) -> zbus::Result<(String, OwnedObjectPath)>;
+
+    /// Id property
+    #[zbus(property)]
+    fn id(&self) -> zbus::Result<String>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.login1.Session",
+    default_service = "org.freedesktop.login1"
+)]
+trait Session {
+    /// SetIdleHint method
+    fn set_idle_hint(&self, idle: bool) -> zbus::Result<()>;
+
+    /// IdleHint property
+    #[zbus(property)]
+    fn idle_hint(&self) -> zbus::Result<bool>;
+}
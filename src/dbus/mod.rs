@@ -1 +1,7 @@
+pub mod alias;
+#[cfg(test)]
+pub mod alias_test;
+pub mod client;
 pub mod interface;
+pub mod logind;
+pub mod polkit;
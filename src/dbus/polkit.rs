@@ -0,0 +1,69 @@
+//! # D-Bus interface proxy for: `org.freedesktop.PolicyKit1.Authority`
+//!
+//! Used to check whether the caller of a privileged InputPlumber method is
+//! authorized to perform the action.
+use std::collections::HashMap;
+
+use zbus::{fdo, message::Header, proxy, zvariant::Value, Connection};
+
+/// Polkit action required to read feature reports from hidraw source devices
+pub const ACTION_GET_FEATURE_REPORT: &str = "org.shadowblip.InputPlumber.get-feature-report";
+/// Polkit action required to send feature reports to hidraw source devices
+pub const ACTION_SEND_FEATURE_REPORT: &str = "org.shadowblip.InputPlumber.send-feature-report";
+
+/// Allow polkit to prompt the user for authentication if required
+const CHECK_AUTHORIZATION_FLAGS_ALLOW_USER_INTERACTION: u32 = 1;
+
+#[proxy(
+    interface = "org.freedesktop.PolicyKit1.Authority",
+    default_service = "org.freedesktop.PolicyKit1",
+    default_path = "/org/freedesktop/PolicyKit1/Authority"
+)]
+trait Authority {
+    /// CheckAuthorization method
+    #[allow(clippy::type_complexity)]
+    fn check_authorization(
+        &self,
+        subject: &(&str, HashMap<&str, Value<'_>>),
+        action_id: &str,
+        details: HashMap<&str, &str>,
+        flags: u32,
+        cancellation_id: &str,
+    ) -> zbus::Result<(bool, bool, HashMap<String, String>)>;
+}
+
+/// Check whether the sender of the message with the given header is
+/// authorized to perform the given polkit action. Returns an
+/// [fdo::Error::AccessDenied] error if the sender is not authorized.
+pub async fn check_authorization(
+    conn: &Connection,
+    header: &Header<'_>,
+    action_id: &str,
+) -> fdo::Result<()> {
+    let Some(sender) = header.sender() else {
+        return Err(fdo::Error::AccessDenied(
+            "Unable to determine the sender of the message".to_string(),
+        ));
+    };
+
+    let authority = AuthorityProxy::new(conn).await?;
+    let mut subject_details = HashMap::new();
+    subject_details.insert("name", Value::from(sender.as_str()));
+    let subject = ("system-bus-name", subject_details);
+    let (is_authorized, _is_challenge, _details) = authority
+        .check_authorization(
+            &subject,
+            action_id,
+            HashMap::new(),
+            CHECK_AUTHORIZATION_FLAGS_ALLOW_USER_INTERACTION,
+            "",
+        )
+        .await?;
+    if !is_authorized {
+        return Err(fdo::Error::AccessDenied(format!(
+            "Sender {sender} is not authorized to perform {action_id}"
+        )));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use std::{error::Error, ffi::CString};
+
+use hidapi::HidDevice;
+use packed_struct::PackedStruct;
+
+use crate::udev::device::UdevDevice;
+
+use super::{
+    event::{BinaryInput, ButtonEvent, Event},
+    hid_report::PackedInputDataReport,
+};
+
+// Report ID
+pub const REPORT_ID: u8 = 0x04;
+
+// Input report size
+const PACKET_SIZE: usize = 32;
+
+// HID buffer read timeout
+const HID_TIMEOUT: i32 = 10;
+
+pub const VID: u16 = 0x2DC8;
+pub const PIDS: [u16; 2] = [0x3106, 0x3109];
+
+/// USB interface of the dongle that implements the vendor protocol
+pub const VENDOR_INTERFACE: i32 = 1;
+
+pub struct Driver {
+    /// HIDRAW device instance
+    device: HidDevice,
+    /// State for the device
+    state: Option<PackedInputDataReport>,
+}
+
+impl Driver {
+    pub fn new(udevice: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let path = udevice.devnode();
+
+        let cs_path = CString::new(path.clone())?;
+        let api = hidapi::HidApi::new()?;
+        let device = api.open_path(&cs_path)?;
+
+        let info = device.get_device_info()?;
+        if info.vendor_id() != VID
+            || !PIDS.contains(&info.product_id())
+            || info.interface_number() != VENDOR_INTERFACE
+        {
+            return Err(
+                format!("Device '{path}' is not an 8BitDo Ultimate vendor interface").into(),
+            );
+        }
+
+        Ok(Self {
+            device,
+            state: None,
+        })
+    }
+
+    /// Poll the device and read input reports
+    pub fn poll(&mut self) -> Result<Vec<Event>, Box<dyn Error + Send + Sync>> {
+        // Read data from the device into a buffer
+        let mut buf = [0; PACKET_SIZE];
+        let bytes_read = self.device.read_timeout(&mut buf[..], HID_TIMEOUT)?;
+        if bytes_read == 0 {
+            return Ok(vec![]);
+        }
+
+        // The vendor interface also carries configuration replies which are
+        // not input reports.
+        let report_id = buf[0];
+        if report_id != REPORT_ID {
+            log::trace!("Ignoring vendor report_id {report_id}");
+            return Ok(vec![]);
+        }
+
+        let input_report = PackedInputDataReport::unpack(&buf)?;
+
+        // Update the state
+        let old_state = self.update_state(input_report);
+
+        // Translate the state into a stream of input events
+        let events = self.translate_events(old_state);
+
+        Ok(events)
+    }
+
+    /// Update input state
+    fn update_state(
+        &mut self,
+        input_report: PackedInputDataReport,
+    ) -> Option<PackedInputDataReport> {
+        let old_state = self.state;
+        self.state = Some(input_report);
+        old_state
+    }
+
+    /// Translate the state into individual events
+    fn translate_events(&mut self, old_state: Option<PackedInputDataReport>) -> Vec<Event> {
+        let mut events = Vec::new();
+        let Some(state) = self.state else {
+            return events;
+        };
+
+        // Translate state changes into events if they have changed
+        let old_state = old_state.unwrap_or_default();
+
+        if state.left_paddle != old_state.left_paddle {
+            events.push(Event::Button(ButtonEvent::LeftPaddle(BinaryInput {
+                pressed: state.left_paddle,
+            })));
+        }
+        if state.right_paddle != old_state.right_paddle {
+            events.push(Event::Button(ButtonEvent::RightPaddle(BinaryInput {
+                pressed: state.right_paddle,
+            })));
+        }
+        if state.profile != old_state.profile {
+            events.push(Event::Button(ButtonEvent::Profile(BinaryInput {
+                pressed: state.profile,
+            })));
+        }
+        if state.profile_slot != old_state.profile_slot {
+            log::debug!("Switched to profile slot {}", state.profile_slot);
+        }
+
+        log::trace!("Got events: {events:?}");
+
+        events
+    }
+}
@@ -0,0 +1,22 @@
+/// Events that can be emitted by the controller
+#[derive(Clone, Debug)]
+pub enum Event {
+    Button(ButtonEvent),
+}
+
+/// [BinaryInput] contains either pressed or unpressed
+#[derive(Clone, Debug)]
+pub struct BinaryInput {
+    pub pressed: bool,
+}
+
+/// Button events represend binary inputs
+#[derive(Clone, Debug)]
+pub enum ButtonEvent {
+    /// Left back paddle (L4)
+    LeftPaddle(BinaryInput),
+    /// Right back paddle (R4)
+    RightPaddle(BinaryInput),
+    /// Profile switch button
+    Profile(BinaryInput),
+}
@@ -0,0 +1,38 @@
+use packed_struct::prelude::*;
+
+use super::driver::REPORT_ID;
+
+/// 8BitDo Ultimate vendor input report. This report is sent on the vendor
+/// interface of the 2.4GHz dongle alongside the regular XInput reports and
+/// contains the state of the buttons that XInput cannot represent.
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering = "msb0", size_bytes = "32")]
+pub struct PackedInputDataReport {
+    // byte 0
+    #[packed_field(bytes = "0")]
+    pub report_id: u8, // Report ID (always 0x04)
+
+    // byte 1
+    #[packed_field(bits = "13")]
+    pub profile: bool, // Profile switch button
+    #[packed_field(bits = "14")]
+    pub right_paddle: bool, // R4
+    #[packed_field(bits = "15")]
+    pub left_paddle: bool, // L4
+
+    // byte 2
+    #[packed_field(bytes = "2")]
+    pub profile_slot: u8, // Currently active profile slot
+}
+
+impl Default for PackedInputDataReport {
+    fn default() -> Self {
+        Self {
+            report_id: REPORT_ID,
+            profile: false,
+            right_paddle: false,
+            left_paddle: false,
+            profile_slot: 0,
+        }
+    }
+}
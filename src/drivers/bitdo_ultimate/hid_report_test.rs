@@ -0,0 +1,22 @@
+use std::error::Error;
+
+use packed_struct::PackedStructSlice;
+
+use super::hid_report::PackedInputDataReport;
+
+#[tokio::test]
+async fn test_bitdo_ultimate() -> Result<(), Box<dyn Error>> {
+    let report = PackedInputDataReport::unpack_from_slice(&DATA_LEFT_PADDLE_PROFILE).unwrap();
+    println!("{report:?}");
+    assert!(report.left_paddle, "left paddle should be pressed");
+    assert!(!report.right_paddle, "right paddle should not be pressed");
+    assert!(report.profile, "profile button should be pressed");
+    assert_eq!(report.profile_slot, 2);
+
+    Ok(())
+}
+
+const DATA_LEFT_PADDLE_PROFILE: [u8; 32] = [
+    0x04, 0x05, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
@@ -11,6 +11,7 @@ use crate::drivers::dualsense::{
     event::{BinaryInput, ButtonEvent, TriggerEvent, TriggerInput},
     hid_report::Direction,
 };
+use crate::drivers::firmware::FirmwareInfo;
 
 use super::{
     event::{AccelerometerEvent, AccelerometerInput, AxisEvent, AxisInput, Event, TouchAxisInput},
@@ -33,6 +34,11 @@ pub const PIDS: [u16; 2] = [DS5_EDGE_PID, DS5_PID];
 pub const FEATURE_REPORT_PAIRING_INFO: u8 = 0x09;
 pub const FEATURE_REPORT_FIRMWARE_INFO: u8 = 0x20;
 pub const FEATURE_REPORT_CALIBRATION: u8 = 0x05;
+pub const FEATURE_REPORT_TEST_COMMAND: u8 = 0x80;
+pub const FEATURE_REPORT_TEST_RESULT: u8 = 0x81;
+pub const FEATURE_REPORT_PAIRING_INFO_SIZE: usize = 20;
+pub const FEATURE_REPORT_FIRMWARE_INFO_SIZE: usize = 64;
+pub const FEATURE_REPORT_TEST_RESULT_SIZE: usize = 64;
 
 pub const INPUT_REPORT_USB: u8 = 0x01;
 pub const INPUT_REPORT_USB_SIZE: usize = 64;
@@ -162,6 +168,22 @@ impl Driver {
         self.write(state)
     }
 
+    /// Read the firmware version, hardware version, and MAC address of the
+    /// controller using the firmware info and pairing info feature reports.
+    pub fn get_firmware_info(&self) -> Result<FirmwareInfo, Box<dyn Error + Send + Sync>> {
+        let mut buf = [0; FEATURE_REPORT_FIRMWARE_INFO_SIZE];
+        buf[0] = FEATURE_REPORT_FIRMWARE_INFO;
+        let bytes_read = self.device.get_feature_report(&mut buf)?;
+        let mut info = parse_firmware_info_report(&buf[..bytes_read])?;
+
+        let mut buf = [0; FEATURE_REPORT_PAIRING_INFO_SIZE];
+        buf[0] = FEATURE_REPORT_PAIRING_INFO;
+        let bytes_read = self.device.get_feature_report(&mut buf)?;
+        info.serial_number = parse_pairing_info_report(&buf[..bytes_read])?;
+
+        Ok(info)
+    }
+
     /// Unpacks the buffer into a [PackedInputDataReport] structure and updates
     /// the internal gamepad state
     fn handle_input_report(
@@ -215,6 +237,17 @@ impl Driver {
         let state = report.state();
 
         let Some(old_report) = old_state else {
+            // Report a headset that was already plugged in on the first report
+            if state.plugged_headphones {
+                events.push(Event::Button(ButtonEvent::HeadphoneInsert(BinaryInput {
+                    pressed: true,
+                })));
+            }
+            if state.plugged_mic {
+                events.push(Event::Button(ButtonEvent::MicrophoneInsert(BinaryInput {
+                    pressed: true,
+                })));
+            }
             return events;
         };
         let old_state = old_report.state();
@@ -357,6 +390,18 @@ impl Driver {
                 pressed: state.mute,
             })));
         }
+
+        // Audio jack events
+        if state.plugged_headphones != old_state.plugged_headphones {
+            events.push(Event::Button(ButtonEvent::HeadphoneInsert(BinaryInput {
+                pressed: state.plugged_headphones,
+            })));
+        }
+        if state.plugged_mic != old_state.plugged_mic {
+            events.push(Event::Button(ButtonEvent::MicrophoneInsert(BinaryInput {
+                pressed: state.plugged_mic,
+            })));
+        }
         if state.touchpad != old_state.touchpad {
             events.push(Event::Button(ButtonEvent::PadPress(BinaryInput {
                 pressed: state.touchpad,
@@ -397,29 +442,36 @@ impl Driver {
         if state.touch_data != old_state.touch_data {
             // Timestamp changes indicate that touches are happening
             self.last_touch = Instant::now();
-            let finger_data_0 = state.touch_data.touch_finger_data[0];
-            let old_finger_data_0 = old_state.touch_data.touch_finger_data[0];
-            if finger_data_0 != old_finger_data_0 {
-                let is_touching = finger_data_0.context != 128; // Set to 128 when not touching
-                self.touch_state[0] = is_touching;
+            for index in 0..self.touch_state.len() {
+                let finger_data = state.touch_data.touch_finger_data[index];
+                let old_finger_data = old_state.touch_data.touch_finger_data[index];
+                if finger_data == old_finger_data {
+                    continue;
+                }
+                let is_touching = finger_data.is_touching();
+
+                // If a finger was lifted and a new one placed between reports,
+                // the contact id changes. Lift the old touch first so the new
+                // touch gets its own tracking id.
+                if is_touching
+                    && self.touch_state[index]
+                    && finger_data.touch_id() != old_finger_data.touch_id()
+                {
+                    events.push(Event::Axis(AxisEvent::Pad(TouchAxisInput {
+                        index: index as u8,
+                        is_touching: false,
+                        x: old_finger_data.get_x(),
+                        y: old_finger_data.get_y(),
+                    })));
+                }
+
+                self.touch_state[index] = is_touching;
                 events.push(Event::Axis(AxisEvent::Pad(TouchAxisInput {
-                    index: 0,
-                    is_touching,
-                    x: finger_data_0.get_x(),
-                    y: finger_data_0.get_y(),
-                })))
-            }
-            let finger_data_1 = state.touch_data.touch_finger_data[1];
-            let old_finger_data_1 = old_state.touch_data.touch_finger_data[1];
-            if finger_data_1 != old_finger_data_1 {
-                let is_touching = finger_data_1.context != 128; // Set to 128 when not touching
-                self.touch_state[1] = is_touching;
-                events.push(Event::Axis(AxisEvent::Pad(TouchAxisInput {
-                    index: 1,
+                    index: index as u8,
                     is_touching,
-                    x: finger_data_1.get_x(),
-                    y: finger_data_1.get_y(),
-                })))
+                    x: finger_data.get_x(),
+                    y: finger_data.get_y(),
+                })));
             }
         } else if (self.touch_state[0] || self.touch_state[1])
             && (self.last_touch.elapsed() > Duration::from_millis(200))
@@ -464,3 +516,37 @@ impl Driver {
         events
     }
 }
+
+/// Parse the firmware info feature report into the hardware and firmware
+/// version of the controller.
+// Source: https://github.com/torvalds/linux/blob/master/drivers/hid/hid-playstation.c
+pub fn parse_firmware_info_report(
+    buf: &[u8],
+) -> Result<FirmwareInfo, Box<dyn Error + Send + Sync>> {
+    if buf.len() < 0x20 || buf[0] != FEATURE_REPORT_FIRMWARE_INFO {
+        return Err("Invalid firmware info report".into());
+    }
+    let hw_version = u32::from_le_bytes([buf[0x18], buf[0x19], buf[0x1a], buf[0x1b]]);
+    let fw_version = u32::from_le_bytes([buf[0x1c], buf[0x1d], buf[0x1e], buf[0x1f]]);
+
+    Ok(FirmwareInfo {
+        firmware_version: format!("0x{fw_version:08x}"),
+        serial_number: "".to_string(),
+        board_revision: format!("0x{hw_version:08x}"),
+    })
+}
+
+/// Parse the pairing info feature report into the MAC address of the
+/// controller. E.g. "a0:ab:51:c1:d2:e3"
+pub fn parse_pairing_info_report(buf: &[u8]) -> Result<String, Box<dyn Error + Send + Sync>> {
+    if buf.len() < 7 || buf[0] != FEATURE_REPORT_PAIRING_INFO {
+        return Err("Invalid pairing info report".into());
+    }
+    // The MAC address is stored in little endian byte order
+    let mac: Vec<String> = buf[1..7]
+        .iter()
+        .rev()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    Ok(mac.join(":"))
+}
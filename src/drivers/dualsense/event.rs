@@ -24,6 +24,8 @@ pub enum ButtonEvent {
     Options(BinaryInput),
     Guide(BinaryInput),
     Mute(BinaryInput),
+    HeadphoneInsert(BinaryInput),
+    MicrophoneInsert(BinaryInput),
     DPadDown(BinaryInput),
     DPadUp(BinaryInput),
     DPadLeft(BinaryInput),
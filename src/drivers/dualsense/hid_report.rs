@@ -129,7 +129,8 @@ pub enum PowerState {
 #[packed_struct(bit_numbering = "msb0", size_bytes = "4")]
 pub struct TouchFingerData {
     // byte 0
-    // Set to 127 when touching and 128 when not.
+    // Bit 7 is set when not touching. Bits 0-6 are the id of the contact,
+    // which changes with every new touch.
     #[packed_field(bytes = "0")]
     pub context: u8,
     // byte 1
@@ -159,7 +160,12 @@ impl Default for TouchFingerData {
 
 impl TouchFingerData {
     pub fn is_touching(&self) -> bool {
-        self.context != 128
+        self.context & 0x80 == 0
+    }
+
+    /// Returns the id of the contact. A new id is assigned to every touch.
+    pub fn touch_id(&self) -> u8 {
+        self.context & 0x7F
     }
 
     pub fn get_x(&self) -> u16 {
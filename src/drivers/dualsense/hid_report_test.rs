@@ -2,7 +2,7 @@ use std::error::Error;
 
 use packed_struct::PackedStructSlice;
 
-use crate::drivers::dualsense::hid_report::InputState;
+use crate::drivers::dualsense::hid_report::{InputState, TouchFingerData};
 
 #[tokio::test]
 async fn test_ds_hid() -> Result<(), Box<dyn Error>> {
@@ -23,3 +23,19 @@ async fn test_ds_hid() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[test]
+fn test_ds_touch_context() {
+    let mut finger = TouchFingerData::default();
+    assert!(!finger.is_touching());
+
+    // A touch with contact id 5
+    finger.context = 0x05;
+    assert!(finger.is_touching());
+    assert_eq!(finger.touch_id(), 5);
+
+    // Lifted touches keep their contact id
+    finger.context = 0x85;
+    assert!(!finger.is_touching());
+    assert_eq!(finger.touch_id(), 5);
+}
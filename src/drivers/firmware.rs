@@ -0,0 +1,61 @@
+//! Firmware and hardware information of known controllers
+use std::error::Error;
+
+use crate::{
+    drivers::{dualsense, lego, legos, steam_deck},
+    udev::device::UdevDevice,
+};
+
+/// Firmware and hardware information reported by a controller. Fields that
+/// the controller does not report are empty.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FirmwareInfo {
+    pub firmware_version: String,
+    pub serial_number: String,
+    pub board_revision: String,
+}
+
+/// Query the firmware and hardware information of the given hidraw device.
+/// Returns [None] if the device is not a known controller.
+pub fn query_firmware_info(
+    device: &UdevDevice,
+) -> Result<Option<FirmwareInfo>, Box<dyn Error + Send + Sync>> {
+    let vid = device.id_vendor();
+    let pid = device.id_product();
+    let path = device.devnode();
+
+    // Sony DualSense
+    if vid == dualsense::driver::DS5_VID && dualsense::driver::PIDS.contains(&pid) {
+        let driver = dualsense::driver::Driver::new(path)?;
+        return Ok(Some(driver.get_firmware_info()?));
+    }
+
+    // Steam Deck
+    if vid == steam_deck::driver::VID && pid == steam_deck::driver::PID {
+        let driver = steam_deck::driver::Driver::new(path)?;
+        return Ok(Some(driver.get_firmware_info()?));
+    }
+
+    // Legion Go controllers do not have a known feature report with this
+    // information, so use the USB device descriptor instead.
+    let legion_go_pids = [
+        lego::driver_dinput_combined::PID,
+        lego::driver_dinput_split::PID,
+        lego::driver_fps_mode::PID,
+        lego::driver_xinput::PID,
+    ];
+    let is_legion_go = vid == lego::driver_xinput::VID && legion_go_pids.contains(&pid);
+    let is_legion_go_s = vid == legos::driver::VID && pid == legos::driver::PID;
+    if is_legion_go || is_legion_go_s {
+        let info = FirmwareInfo {
+            firmware_version: device
+                .get_attribute_from_tree("bcdDevice")
+                .unwrap_or_default(),
+            serial_number: device.serial_number(),
+            board_revision: "".to_string(),
+        };
+        return Ok(Some(info));
+    }
+
+    Ok(None)
+}
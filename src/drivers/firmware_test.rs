@@ -0,0 +1,50 @@
+use crate::drivers::{dualsense, steam_deck};
+
+#[test]
+fn test_dualsense_firmware_info() {
+    let mut buf = [0u8; 64];
+    buf[0] = dualsense::driver::FEATURE_REPORT_FIRMWARE_INFO;
+    buf[1..20].copy_from_slice(b"Jun 19 202314:47:34");
+    buf[0x18..0x1c].copy_from_slice(&[0x08, 0x02, 0x00, 0x01]);
+    buf[0x1c..0x20].copy_from_slice(&[0x36, 0x00, 0x00, 0x01]);
+    let info = dualsense::driver::parse_firmware_info_report(&buf).unwrap();
+    assert_eq!(info.firmware_version, "0x01000036");
+    assert_eq!(info.board_revision, "0x01000208");
+
+    let buf = [
+        dualsense::driver::FEATURE_REPORT_PAIRING_INFO,
+        0xe3,
+        0xd2,
+        0xc1,
+        0x51,
+        0xab,
+        0xa0,
+        0x08,
+    ];
+    let mac = dualsense::driver::parse_pairing_info_report(&buf).unwrap();
+    assert_eq!(mac, "a0:ab:51:c1:d2:e3");
+
+    assert!(dualsense::driver::parse_firmware_info_report(&[0x05, 0x00]).is_err());
+}
+
+#[test]
+fn test_steam_deck_firmware_info() {
+    // Attribute reply captured from a real device
+    let buf = [
+        0x83, 0x2d, 0x01, 0x05, 0x12, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x0a, 0x2b, 0x12,
+        0xa9, 0x62, 0x04, 0xad, 0xf1, 0xe4, 0x65, 0x09, 0x2e, 0x00, 0x00, 0x00, 0x0b, 0xa0, 0x0f,
+        0x00, 0x00, 0x0d, 0x00, 0x00, 0x00, 0x00, 0x0c, 0x00, 0x00, 0x00, 0x00, 0x0e, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+    ];
+    let attributes = steam_deck::driver::parse_attributes_report(&buf).unwrap();
+    assert_eq!(attributes.get(&0x01), Some(&0x1205));
+    assert_eq!(attributes.get(&0x04), Some(&0x65e4f1ad));
+    assert_eq!(attributes.get(&0x09), Some(&46));
+    assert_eq!(attributes.len(), 9);
+
+    let mut buf = vec![0xae, 0x15, 0x01];
+    buf.extend_from_slice(b"FVAA12345678");
+    buf.resize(64, 0);
+    let serial = steam_deck::driver::parse_serial_report(&buf).unwrap();
+    assert_eq!(serial, "FVAA12345678");
+}
@@ -0,0 +1,445 @@
+//! Parser for HID report descriptors. A report descriptor describes the layout
+//! of every report a HID device can send or receive. Parsing it allows devices
+//! without a bespoke driver to be read from and written to generically.
+//!
+//! See the "Device Class Definition for HID" specification, section 6.2.2.
+use std::{collections::HashMap, error::Error, fmt::Display};
+
+/// Usage pages used by the generic driver
+pub const USAGE_PAGE_GENERIC_DESKTOP: u16 = 0x01;
+pub const USAGE_PAGE_SIMULATION: u16 = 0x02;
+pub const USAGE_PAGE_LED: u16 = 0x08;
+pub const USAGE_PAGE_BUTTON: u16 = 0x09;
+pub const USAGE_PAGE_PID: u16 = 0x0F;
+
+/// Generic desktop usages
+pub const USAGE_JOYSTICK: u16 = 0x04;
+pub const USAGE_GAMEPAD: u16 = 0x05;
+pub const USAGE_X: u16 = 0x30;
+pub const USAGE_Y: u16 = 0x31;
+pub const USAGE_Z: u16 = 0x32;
+pub const USAGE_RX: u16 = 0x33;
+pub const USAGE_RY: u16 = 0x34;
+pub const USAGE_RZ: u16 = 0x35;
+pub const USAGE_HAT_SWITCH: u16 = 0x39;
+
+/// Simulation control usages
+pub const USAGE_ACCELERATOR: u16 = 0xC4;
+pub const USAGE_BRAKE: u16 = 0xC5;
+
+/// Physical interface device usages
+pub const USAGE_MAGNITUDE: u16 = 0x70;
+
+// Item types
+const ITEM_TYPE_MAIN: u8 = 0;
+const ITEM_TYPE_GLOBAL: u8 = 1;
+const ITEM_TYPE_LOCAL: u8 = 2;
+
+// Main item tags
+const MAIN_INPUT: u8 = 0x8;
+const MAIN_OUTPUT: u8 = 0x9;
+const MAIN_COLLECTION: u8 = 0xA;
+const MAIN_FEATURE: u8 = 0xB;
+const MAIN_END_COLLECTION: u8 = 0xC;
+
+// Global item tags
+const GLOBAL_USAGE_PAGE: u8 = 0x0;
+const GLOBAL_LOGICAL_MIN: u8 = 0x1;
+const GLOBAL_LOGICAL_MAX: u8 = 0x2;
+const GLOBAL_REPORT_SIZE: u8 = 0x7;
+const GLOBAL_REPORT_ID: u8 = 0x8;
+const GLOBAL_REPORT_COUNT: u8 = 0x9;
+const GLOBAL_PUSH: u8 = 0xA;
+const GLOBAL_POP: u8 = 0xB;
+
+// Local item tags
+const LOCAL_USAGE: u8 = 0x0;
+const LOCAL_USAGE_MIN: u8 = 0x1;
+const LOCAL_USAGE_MAX: u8 = 0x2;
+
+// Collection types
+const COLLECTION_APPLICATION: u32 = 0x01;
+
+// Prefix of long items, which are reserved and skipped
+const LONG_ITEM_PREFIX: u8 = 0xFE;
+
+/// Possible errors when parsing a report descriptor
+#[derive(Debug, Clone, PartialEq)]
+pub enum DescriptorError {
+    /// The descriptor ended in the middle of an item
+    UnexpectedEnd(usize),
+    /// A pop item was found without a matching push
+    InvalidPop(usize),
+    /// An end collection item was found without an open collection
+    InvalidEndCollection(usize),
+}
+
+impl Display for DescriptorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DescriptorError::UnexpectedEnd(offset) => {
+                write!(f, "Unexpected end of descriptor at byte {offset}")
+            }
+            DescriptorError::InvalidPop(offset) => {
+                write!(f, "Pop item without push at byte {offset}")
+            }
+            DescriptorError::InvalidEndCollection(offset) => {
+                write!(f, "End collection without collection at byte {offset}")
+            }
+        }
+    }
+}
+
+impl Error for DescriptorError {}
+
+/// Type of report a [ReportField] belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReportType {
+    Input,
+    Output,
+    Feature,
+}
+
+/// A single value in a HID report
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportField {
+    /// Type of report this field is in
+    pub report_type: ReportType,
+    /// Report id of the report this field is in. This is 0 if the device
+    /// does not use report ids.
+    pub report_id: u8,
+    /// Usage page of the field (e.g. Generic Desktop, Button)
+    pub usage_page: u16,
+    /// Usage of the field within its usage page (e.g. X, Button 1)
+    pub usage: u16,
+    /// Offset of the field in bits from the start of the report data, not
+    /// including the report id byte.
+    pub bit_offset: usize,
+    /// Size of the field in bits
+    pub bit_size: usize,
+    /// Minimum value the field can report
+    pub logical_min: i32,
+    /// Maximum value the field can report
+    pub logical_max: i32,
+    /// True if the field holds a value. False if the field is an array that
+    /// holds the indexes of currently active usages.
+    pub is_variable: bool,
+    /// True if the field is padding or otherwise constant
+    pub is_constant: bool,
+}
+
+impl ReportField {
+    /// Extracts the raw value of the field from the given report data. The
+    /// report data should not include the report id byte.
+    pub fn extract(&self, data: &[u8]) -> Option<i32> {
+        if self.bit_size == 0 || self.bit_size > 32 {
+            return None;
+        }
+        let end = self.bit_offset + self.bit_size;
+        if end > data.len() * 8 {
+            return None;
+        }
+
+        let mut value: u32 = 0;
+        for i in 0..self.bit_size {
+            let bit = self.bit_offset + i;
+            if data[bit / 8] & (1 << (bit % 8)) != 0 {
+                value |= 1 << i;
+            }
+        }
+
+        // Sign extend the value if the field can report negative values
+        if self.logical_min < 0 && self.bit_size < 32 && value & (1 << (self.bit_size - 1)) != 0 {
+            value |= u32::MAX << self.bit_size;
+        }
+
+        Some(value as i32)
+    }
+
+    /// Writes the given raw value of the field into the given report data.
+    /// The report data should not include the report id byte.
+    pub fn insert(&self, data: &mut [u8], value: i32) {
+        if self.bit_size == 0 || self.bit_size > 32 {
+            return;
+        }
+        let end = self.bit_offset + self.bit_size;
+        if end > data.len() * 8 {
+            return;
+        }
+
+        let value = value as u32;
+        for i in 0..self.bit_size {
+            let bit = self.bit_offset + i;
+            let mask = 1 << (bit % 8);
+            if value & (1 << i) != 0 {
+                data[bit / 8] |= mask;
+            } else {
+                data[bit / 8] &= !mask;
+            }
+        }
+    }
+
+    /// Returns the given raw value normalized between 0.0 and 1.0 based on
+    /// the logical range of the field.
+    pub fn normalize(&self, value: i32) -> f64 {
+        let range = self.logical_max as f64 - self.logical_min as f64;
+        if range <= 0.0 {
+            return 0.0;
+        }
+        let value = value.clamp(self.logical_min, self.logical_max);
+        (value as f64 - self.logical_min as f64) / range
+    }
+
+    /// Returns a raw value for the given value between 0.0 and 1.0 based on
+    /// the logical range of the field.
+    pub fn denormalize(&self, value: f64) -> i32 {
+        let range = self.logical_max as f64 - self.logical_min as f64;
+        let value = value.clamp(0.0, 1.0) * range + self.logical_min as f64;
+        value.round() as i32
+    }
+}
+
+/// A parsed HID report descriptor
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReportDescriptor {
+    /// All fields defined in the descriptor
+    pub fields: Vec<ReportField>,
+    /// Usage page and usage of every top-level application collection
+    pub applications: Vec<(u16, u16)>,
+    /// Size of each report in bytes, not including the report id byte
+    report_sizes: HashMap<(ReportType, u8), usize>,
+}
+
+/// Global item state that can be pushed and popped
+#[derive(Debug, Clone, Default)]
+struct GlobalState {
+    usage_page: u16,
+    logical_min: i32,
+    logical_max: i32,
+    report_size: usize,
+    report_id: u8,
+    report_count: usize,
+}
+
+/// Local item state that is reset after every main item
+#[derive(Debug, Clone, Default)]
+struct LocalState {
+    /// List of usages as (usage page, usage) pairs
+    usages: Vec<(u16, u16)>,
+    usage_min: Option<(u16, u16)>,
+    usage_max: Option<(u16, u16)>,
+}
+
+impl LocalState {
+    /// Returns the usage for the value at the given index in a main item
+    fn usage_at(&self, index: usize) -> Option<(u16, u16)> {
+        if let (Some(min), Some(max)) = (self.usage_min, self.usage_max) {
+            let usage = (min.1 as usize + index).min(max.1 as usize) as u16;
+            return Some((min.0, usage));
+        }
+        if self.usages.is_empty() {
+            return None;
+        }
+        let index = index.min(self.usages.len() - 1);
+        Some(self.usages[index])
+    }
+}
+
+impl ReportDescriptor {
+    /// Parse the given raw report descriptor
+    pub fn parse(data: &[u8]) -> Result<Self, DescriptorError> {
+        let mut descriptor = ReportDescriptor::default();
+        let mut global = GlobalState::default();
+        let mut global_stack: Vec<GlobalState> = Vec::new();
+        let mut local = LocalState::default();
+        let mut collection_depth = 0;
+        let mut offsets: HashMap<(ReportType, u8), usize> = HashMap::new();
+
+        let mut i = 0;
+        while i < data.len() {
+            let prefix = data[i];
+
+            // Long items are reserved for future use and can be skipped
+            if prefix == LONG_ITEM_PREFIX {
+                let Some(size) = data.get(i + 1) else {
+                    return Err(DescriptorError::UnexpectedEnd(i));
+                };
+                i += 3 + *size as usize;
+                continue;
+            }
+
+            let size = match prefix & 0x03 {
+                3 => 4,
+                size => size as usize,
+            };
+            let item_type = (prefix >> 2) & 0x03;
+            let tag = (prefix >> 4) & 0x0F;
+            if i + 1 + size > data.len() {
+                return Err(DescriptorError::UnexpectedEnd(i));
+            }
+            let item_data = &data[i + 1..i + 1 + size];
+            let value = unsigned_value(item_data);
+
+            match item_type {
+                ITEM_TYPE_MAIN => {
+                    match tag {
+                        MAIN_INPUT | MAIN_OUTPUT | MAIN_FEATURE => {
+                            let report_type = match tag {
+                                MAIN_INPUT => ReportType::Input,
+                                MAIN_OUTPUT => ReportType::Output,
+                                _ => ReportType::Feature,
+                            };
+                            let key = (report_type, global.report_id);
+                            let offset = offsets.entry(key).or_insert(0);
+                            let is_constant = value & 0x01 != 0;
+                            let is_variable = value & 0x02 != 0;
+
+                            // Array fields only hold indexes, so they use the
+                            // first usage of the range.
+                            let count = global.report_count;
+                            for index in 0..count {
+                                let usage = if is_variable {
+                                    local.usage_at(index)
+                                } else {
+                                    local.usage_at(0)
+                                };
+                                let (usage_page, usage) = usage.unwrap_or((global.usage_page, 0));
+                                descriptor.fields.push(ReportField {
+                                    report_type,
+                                    report_id: global.report_id,
+                                    usage_page,
+                                    usage,
+                                    bit_offset: *offset,
+                                    bit_size: global.report_size,
+                                    logical_min: global.logical_min,
+                                    logical_max: global.logical_max,
+                                    is_variable,
+                                    is_constant,
+                                });
+                                *offset += global.report_size;
+                            }
+                            descriptor.report_sizes.insert(key, offset.div_ceil(8));
+                        }
+                        MAIN_COLLECTION => {
+                            if collection_depth == 0 && value == COLLECTION_APPLICATION {
+                                if let Some(usage) = local.usage_at(0) {
+                                    descriptor.applications.push(usage);
+                                }
+                            }
+                            collection_depth += 1;
+                        }
+                        MAIN_END_COLLECTION => {
+                            if collection_depth == 0 {
+                                return Err(DescriptorError::InvalidEndCollection(i));
+                            }
+                            collection_depth -= 1;
+                        }
+                        _ => (),
+                    }
+                    local = LocalState::default();
+                }
+                ITEM_TYPE_GLOBAL => match tag {
+                    GLOBAL_USAGE_PAGE => global.usage_page = value as u16,
+                    GLOBAL_LOGICAL_MIN => global.logical_min = signed_value(item_data),
+                    GLOBAL_LOGICAL_MAX => {
+                        // Many devices encode unsigned maximums without a
+                        // sign byte, so treat it as unsigned if the minimum is.
+                        let max = signed_value(item_data);
+                        global.logical_max = if global.logical_min >= 0 && max < 0 {
+                            value as i32
+                        } else {
+                            max
+                        };
+                    }
+                    GLOBAL_REPORT_SIZE => global.report_size = value as usize,
+                    GLOBAL_REPORT_ID => global.report_id = value as u8,
+                    GLOBAL_REPORT_COUNT => global.report_count = value as usize,
+                    GLOBAL_PUSH => global_stack.push(global.clone()),
+                    GLOBAL_POP => {
+                        let Some(state) = global_stack.pop() else {
+                            return Err(DescriptorError::InvalidPop(i));
+                        };
+                        global = state;
+                    }
+                    _ => (),
+                },
+                ITEM_TYPE_LOCAL => {
+                    // Usages that are 4 bytes long include the usage page
+                    let usage = if size == 4 {
+                        ((value >> 16) as u16, value as u16)
+                    } else {
+                        (global.usage_page, value as u16)
+                    };
+                    match tag {
+                        LOCAL_USAGE => local.usages.push(usage),
+                        LOCAL_USAGE_MIN => local.usage_min = Some(usage),
+                        LOCAL_USAGE_MAX => local.usage_max = Some(usage),
+                        _ => (),
+                    }
+                }
+                _ => (),
+            }
+
+            i += 1 + size;
+        }
+
+        Ok(descriptor)
+    }
+
+    /// Returns true if the device prefixes every report with a report id
+    pub fn uses_report_ids(&self) -> bool {
+        self.fields.iter().any(|field| field.report_id != 0)
+    }
+
+    /// Returns true if the descriptor describes a gamepad or joystick
+    pub fn is_gamepad(&self) -> bool {
+        self.applications.iter().any(|(page, usage)| {
+            *page == USAGE_PAGE_GENERIC_DESKTOP
+                && (*usage == USAGE_GAMEPAD || *usage == USAGE_JOYSTICK)
+        })
+    }
+
+    /// Returns the size in bytes of the given report, not including the
+    /// report id byte.
+    pub fn report_size(&self, report_type: ReportType, report_id: u8) -> usize {
+        self.report_sizes
+            .get(&(report_type, report_id))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Returns the size in bytes of the largest report of the given type, not
+    /// including the report id byte.
+    pub fn max_report_size(&self, report_type: ReportType) -> usize {
+        self.report_sizes
+            .iter()
+            .filter(|((kind, _), _)| *kind == report_type)
+            .map(|(_, size)| *size)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns all non-constant variable fields of the given report type
+    pub fn variable_fields(&self, report_type: ReportType) -> impl Iterator<Item = &ReportField> {
+        self.fields.iter().filter(move |field| {
+            field.report_type == report_type && field.is_variable && !field.is_constant
+        })
+    }
+}
+
+/// Returns the little-endian unsigned value of the given item data
+fn unsigned_value(data: &[u8]) -> u32 {
+    data.iter()
+        .enumerate()
+        .fold(0, |value, (i, byte)| value | (*byte as u32) << (8 * i))
+}
+
+/// Returns the little-endian signed value of the given item data
+fn signed_value(data: &[u8]) -> i32 {
+    let value = unsigned_value(data);
+    match data.len() {
+        1 => value as u8 as i8 as i32,
+        2 => value as u16 as i16 as i32,
+        _ => value as i32,
+    }
+}
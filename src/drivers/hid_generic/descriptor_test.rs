@@ -0,0 +1,109 @@
+use crate::drivers::hid_generic::descriptor::{
+    ReportDescriptor, ReportType, USAGE_HAT_SWITCH, USAGE_MAGNITUDE, USAGE_PAGE_BUTTON,
+    USAGE_PAGE_GENERIC_DESKTOP, USAGE_PAGE_PID, USAGE_X, USAGE_Y,
+};
+
+// Simple gamepad with 8 buttons, a hat switch, two axes and two rumble motors
+const DESCRIPTOR: [u8; 74] = [
+    0x05, 0x01, // Usage Page (Generic Desktop)
+    0x09, 0x05, // Usage (Game Pad)
+    0xA1, 0x01, // Collection (Application)
+    0x85, 0x01, //   Report ID (1)
+    0x05, 0x09, //   Usage Page (Button)
+    0x19, 0x01, //   Usage Minimum (1)
+    0x29, 0x08, //   Usage Maximum (8)
+    0x15, 0x00, //   Logical Minimum (0)
+    0x25, 0x01, //   Logical Maximum (1)
+    0x75, 0x01, //   Report Size (1)
+    0x95, 0x08, //   Report Count (8)
+    0x81, 0x02, //   Input (Data,Var,Abs)
+    0x05, 0x01, //   Usage Page (Generic Desktop)
+    0x09, 0x39, //   Usage (Hat switch)
+    0x15, 0x00, //   Logical Minimum (0)
+    0x25, 0x07, //   Logical Maximum (7)
+    0x75, 0x04, //   Report Size (4)
+    0x95, 0x01, //   Report Count (1)
+    0x81, 0x42, //   Input (Data,Var,Abs,Null State)
+    0x75, 0x04, //   Report Size (4)
+    0x95, 0x01, //   Report Count (1)
+    0x81, 0x03, //   Input (Const,Var,Abs)
+    0x09, 0x30, //   Usage (X)
+    0x09, 0x31, //   Usage (Y)
+    0x15, 0x00, //   Logical Minimum (0)
+    0x26, 0xFF, 0x00, // Logical Maximum (255)
+    0x75, 0x08, //   Report Size (8)
+    0x95, 0x02, //   Report Count (2)
+    0x81, 0x02, //   Input (Data,Var,Abs)
+    0x05, 0x0F, //   Usage Page (Physical Interface Device)
+    0x09, 0x70, //   Usage (Magnitude)
+    0x15, 0x00, //   Logical Minimum (0)
+    0x25, 0xFF, //   Logical Maximum (255)
+    0x75, 0x08, //   Report Size (8)
+    0x95, 0x02, //   Report Count (2)
+    0x91, 0x02, //   Output (Data,Var,Abs)
+    0xC0, // End Collection
+];
+
+#[test]
+fn test_parse_descriptor() {
+    let descriptor = ReportDescriptor::parse(&DESCRIPTOR).unwrap();
+    assert!(descriptor.is_gamepad());
+    assert!(descriptor.uses_report_ids());
+    assert_eq!(descriptor.report_size(ReportType::Input, 1), 4);
+    assert_eq!(descriptor.report_size(ReportType::Output, 1), 2);
+
+    let buttons = descriptor
+        .variable_fields(ReportType::Input)
+        .filter(|field| field.usage_page == USAGE_PAGE_BUTTON)
+        .count();
+    assert_eq!(buttons, 8);
+
+    // Unsigned logical maximums without a sign byte should stay positive
+    let magnitudes: Vec<_> = descriptor
+        .variable_fields(ReportType::Output)
+        .filter(|field| field.usage_page == USAGE_PAGE_PID && field.usage == USAGE_MAGNITUDE)
+        .collect();
+    assert_eq!(magnitudes.len(), 2);
+    assert_eq!(magnitudes[0].logical_max, 255);
+    assert_eq!(magnitudes[1].bit_offset, 8);
+}
+
+#[test]
+fn test_extract_input_report() {
+    let descriptor = ReportDescriptor::parse(&DESCRIPTOR).unwrap();
+    let data = [0b0000_0101, 0x02, 0x80, 0xFF];
+
+    let find = |page: u16, usage: u16| {
+        descriptor
+            .variable_fields(ReportType::Input)
+            .find(|field| field.usage_page == page && field.usage == usage)
+            .unwrap()
+    };
+
+    assert_eq!(find(USAGE_PAGE_BUTTON, 1).extract(&data), Some(1));
+    assert_eq!(find(USAGE_PAGE_BUTTON, 2).extract(&data), Some(0));
+    assert_eq!(find(USAGE_PAGE_BUTTON, 3).extract(&data), Some(1));
+    assert_eq!(
+        find(USAGE_PAGE_GENERIC_DESKTOP, USAGE_HAT_SWITCH).extract(&data),
+        Some(2)
+    );
+
+    let x = find(USAGE_PAGE_GENERIC_DESKTOP, USAGE_X);
+    assert_eq!(x.extract(&data), Some(128));
+    let y = find(USAGE_PAGE_GENERIC_DESKTOP, USAGE_Y);
+    assert_eq!(y.extract(&data), Some(255));
+    assert_eq!(y.normalize(255), 1.0);
+}
+
+#[test]
+fn test_insert_output_report() {
+    let descriptor = ReportDescriptor::parse(&DESCRIPTOR).unwrap();
+    let mut data = [0; 2];
+    for (field, value) in descriptor
+        .variable_fields(ReportType::Output)
+        .zip([1.0, 0.5])
+    {
+        field.insert(&mut data, field.denormalize(value));
+    }
+    assert_eq!(data, [0xFF, 0x80]);
+}
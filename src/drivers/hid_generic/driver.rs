@@ -0,0 +1,263 @@
+use std::{collections::HashMap, error::Error, ffi::CString, fs};
+
+use hidapi::HidDevice;
+
+use crate::udev::device::UdevDevice;
+
+use super::{
+    descriptor::{
+        ReportDescriptor, ReportField, ReportType, USAGE_ACCELERATOR, USAGE_BRAKE,
+        USAGE_HAT_SWITCH, USAGE_MAGNITUDE, USAGE_PAGE_BUTTON, USAGE_PAGE_GENERIC_DESKTOP,
+        USAGE_PAGE_LED, USAGE_PAGE_PID, USAGE_PAGE_SIMULATION, USAGE_RX, USAGE_RY, USAGE_RZ,
+        USAGE_X, USAGE_Y, USAGE_Z,
+    },
+    event::{Axis, AxisEvent, ButtonEvent, Event, HatEvent},
+};
+
+// HID buffer read timeout
+const HID_TIMEOUT: i32 = 10;
+
+/// Reads and parses the report descriptor of the given hidraw device from sysfs
+pub fn read_report_descriptor(
+    udevice: &UdevDevice,
+) -> Result<ReportDescriptor, Box<dyn Error + Send + Sync>> {
+    let path = format!(
+        "/sys/class/hidraw/{}/device/report_descriptor",
+        udevice.sysname()
+    );
+    let data = fs::read(path)?;
+    Ok(ReportDescriptor::parse(&data)?)
+}
+
+/// Returns true if the given hidraw device can be handled by the generic driver
+pub fn is_supported(udevice: &UdevDevice) -> bool {
+    match read_report_descriptor(udevice) {
+        Ok(descriptor) => descriptor.is_gamepad(),
+        Err(e) => {
+            log::debug!("Unable to read report descriptor: {e:?}");
+            false
+        }
+    }
+}
+
+/// Generic driver for HID gamepads that uses the report descriptor of the
+/// device to decode input reports and encode output reports.
+pub struct Driver {
+    /// HIDRAW device instance
+    device: HidDevice,
+    /// Parsed report descriptor of the device
+    descriptor: ReportDescriptor,
+    /// Last known value of each input field, keyed by field index
+    state: HashMap<usize, i32>,
+    /// Last written data of each output report, keyed by report id
+    output_state: HashMap<u8, Vec<u8>>,
+}
+
+impl Driver {
+    pub fn new(udevice: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let path = udevice.devnode();
+        let descriptor = read_report_descriptor(&udevice)?;
+        if !descriptor.is_gamepad() {
+            return Err(format!("Device '{path}' is not a HID gamepad").into());
+        }
+
+        let cs_path = CString::new(path.clone())?;
+        let api = hidapi::HidApi::new()?;
+        let device = api.open_path(&cs_path)?;
+
+        Ok(Self {
+            device,
+            descriptor,
+            state: HashMap::new(),
+            output_state: HashMap::new(),
+        })
+    }
+
+    /// Returns the parsed report descriptor of the device
+    pub fn descriptor(&self) -> &ReportDescriptor {
+        &self.descriptor
+    }
+
+    /// Poll the device and read input reports
+    pub fn poll(&mut self) -> Result<Vec<Event>, Box<dyn Error + Send + Sync>> {
+        // Read data from the device into a buffer
+        let size = self.descriptor.max_report_size(ReportType::Input) + 1;
+        let mut buf = vec![0; size];
+        let bytes_read = self.device.read_timeout(&mut buf[..], HID_TIMEOUT)?;
+        if bytes_read == 0 {
+            return Ok(vec![]);
+        }
+
+        // Split off the report id if the device uses them
+        let (report_id, data) = if self.descriptor.uses_report_ids() {
+            (buf[0], &buf[1..bytes_read])
+        } else {
+            (0, &buf[..bytes_read])
+        };
+
+        // Translate any changed field values into events
+        let mut events = Vec::new();
+        for (i, field) in self.descriptor.fields.iter().enumerate() {
+            if field.report_type != ReportType::Input
+                || field.report_id != report_id
+                || !field.is_variable
+                || field.is_constant
+            {
+                continue;
+            }
+            let Some(value) = field.extract(data) else {
+                continue;
+            };
+            let old_value = self.state.insert(i, value);
+            if old_value == Some(value) {
+                continue;
+            }
+            if let Some(event) = translate_field(field, value) {
+                events.push(event);
+            }
+        }
+
+        if !events.is_empty() {
+            log::trace!("Got events: {events:?}");
+        }
+
+        Ok(events)
+    }
+
+    /// Set the rumble intensity of the device using the magnitude fields of
+    /// its physical interface device output report. The first magnitude is
+    /// treated as the strong motor and the second as the weak motor.
+    pub fn rumble(&mut self, strong: f64, weak: f64) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let fields: Vec<ReportField> = self
+            .descriptor
+            .variable_fields(ReportType::Output)
+            .filter(|field| field.usage_page == USAGE_PAGE_PID && field.usage == USAGE_MAGNITUDE)
+            .take(2)
+            .cloned()
+            .collect();
+        if fields.is_empty() {
+            return Err("Device does not support rumble".into());
+        }
+        let values = [strong, weak];
+        let fields = fields
+            .iter()
+            .zip(values)
+            .map(|(field, value)| (field, field.denormalize(value)))
+            .collect();
+        self.write_fields(fields)
+    }
+
+    /// Turn the LED with the given usage on or off
+    pub fn set_led(&mut self, usage: u16, on: bool) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let Some(field) = self
+            .descriptor
+            .variable_fields(ReportType::Output)
+            .find(|field| field.usage_page == USAGE_PAGE_LED && field.usage == usage)
+            .cloned()
+        else {
+            return Err(format!("Device does not have LED with usage {usage:#x}").into());
+        };
+        let value = if on {
+            field.logical_max
+        } else {
+            field.logical_min
+        };
+        self.write_fields(vec![(&field, value)])
+    }
+
+    /// Returns true if the device has output fields that can be used for rumble
+    pub fn has_rumble(&self) -> bool {
+        self.descriptor
+            .variable_fields(ReportType::Output)
+            .any(|field| field.usage_page == USAGE_PAGE_PID && field.usage == USAGE_MAGNITUDE)
+    }
+
+    /// Write the given field values to their output reports. Fields that are
+    /// not given keep their last written value.
+    fn write_fields(
+        &mut self,
+        fields: Vec<(&ReportField, i32)>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut report_ids: Vec<u8> = fields.iter().map(|(field, _)| field.report_id).collect();
+        report_ids.sort_unstable();
+        report_ids.dedup();
+
+        for report_id in report_ids {
+            let size = self.descriptor.report_size(ReportType::Output, report_id);
+            let data = self
+                .output_state
+                .entry(report_id)
+                .or_insert_with(|| vec![0; size]);
+            for (field, value) in fields.iter() {
+                if field.report_id == report_id {
+                    field.insert(data, *value);
+                }
+            }
+
+            // Reports are always prefixed with the report id, which is 0 if
+            // the device does not use report ids.
+            let mut report = Vec::with_capacity(size + 1);
+            report.push(report_id);
+            report.extend_from_slice(data);
+            self.device.write(&report)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Translate the given input field value into an event
+fn translate_field(field: &ReportField, value: i32) -> Option<Event> {
+    match field.usage_page {
+        USAGE_PAGE_BUTTON => Some(Event::Button(ButtonEvent {
+            index: field.usage,
+            pressed: value != 0,
+        })),
+        USAGE_PAGE_GENERIC_DESKTOP => {
+            let axis = match field.usage {
+                USAGE_X => Axis::X,
+                USAGE_Y => Axis::Y,
+                USAGE_Z => Axis::Z,
+                USAGE_RX => Axis::Rx,
+                USAGE_RY => Axis::Ry,
+                USAGE_RZ => Axis::Rz,
+                USAGE_HAT_SWITCH => {
+                    return Some(Event::Hat(HatEvent {
+                        direction: hat_direction(field, value),
+                    }))
+                }
+                _ => return None,
+            };
+            Some(Event::Axis(AxisEvent {
+                axis,
+                value: field.normalize(value),
+            }))
+        }
+        USAGE_PAGE_SIMULATION => {
+            let axis = match field.usage {
+                USAGE_ACCELERATOR => Axis::Accelerator,
+                USAGE_BRAKE => Axis::Brake,
+                _ => return None,
+            };
+            Some(Event::Axis(AxisEvent {
+                axis,
+                value: field.normalize(value),
+            }))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the 8-way direction of the given hat switch value, or [None] if
+/// the value is outside the logical range, which indicates it is centered.
+fn hat_direction(field: &ReportField, value: i32) -> Option<u8> {
+    if value < field.logical_min || value > field.logical_max {
+        return None;
+    }
+    let direction = (value - field.logical_min) as u8;
+    // 4-way hat switches only report the cardinal directions
+    if field.logical_max - field.logical_min == 3 {
+        return Some(direction * 2);
+    }
+    Some(direction)
+}
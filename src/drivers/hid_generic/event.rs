@@ -0,0 +1,42 @@
+/// Events that can be emitted by a generic HID gamepad
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    Button(ButtonEvent),
+    Axis(AxisEvent),
+    Hat(HatEvent),
+}
+
+/// Button usages are numbered starting at 1 on the HID button usage page
+#[derive(Clone, Debug, PartialEq)]
+pub struct ButtonEvent {
+    pub index: u16,
+    pub pressed: bool,
+}
+
+/// Absolute axes that can be reported by a generic HID gamepad
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+    Rx,
+    Ry,
+    Rz,
+    Accelerator,
+    Brake,
+}
+
+/// [AxisEvent] contains the axis value normalized between 0.0 and 1.0
+#[derive(Clone, Debug, PartialEq)]
+pub struct AxisEvent {
+    pub axis: Axis,
+    pub value: f64,
+}
+
+/// [HatEvent] contains the direction of a hat switch starting at 0 for "up"
+/// and increasing clockwise to 7 for "up-left". The direction is [None] if
+/// the hat switch is centered.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HatEvent {
+    pub direction: Option<u8>,
+}
@@ -0,0 +1,5 @@
+pub mod descriptor;
+#[cfg(test)]
+pub mod descriptor_test;
+pub mod driver;
+pub mod event;
@@ -107,6 +107,13 @@ impl Driver {
         Ok(events)
     }
 
+    /// Re-read the scale of all channels. This must be called after the scale
+    /// of the device was changed so values are still normalized correctly.
+    pub fn reload_scales(&mut self) {
+        update_scales(&self.accel, &mut self.accel_info);
+        update_scales(&self.gyro, &mut self.gyro_info);
+    }
+
     /// Polls all the channels from the accelerometer
     fn poll_accel(&self) -> Result<Option<Event>, Box<dyn Error + Send + Sync>> {
         // Read from each accel channel
@@ -189,6 +196,25 @@ impl Driver {
     }
 }
 
+/// Update the scale of the given channels
+fn update_scales(
+    channels: &HashMap<String, Channel>,
+    channel_info: &mut HashMap<String, AxisInfo>,
+) {
+    for (id, channel) in channels.iter() {
+        let Some(info) = channel_info.get_mut(id) else {
+            continue;
+        };
+        match channel.attr_read_float("scale") {
+            Ok(scale) => {
+                log::debug!("Updated scale for channel {id}: {scale}");
+                info.scale = scale;
+            }
+            Err(e) => log::warn!("Unable to read scale for channel {id}: {:?}", e),
+        }
+    }
+}
+
 /// Returns all channels and channel information from the given device matching
 /// the given channel type.
 fn get_channels_with_type(
@@ -0,0 +1,434 @@
+use std::{error::Error, ffi::CString};
+
+use hidapi::HidDevice;
+use packed_struct::{types::SizedInteger, PackedStruct};
+
+use crate::udev::device::UdevDevice;
+
+use super::{
+    event::{
+        BinaryInput, ButtonEvent, Event, InertialEvent, InertialInput, JoystickEvent, JoystickInput,
+    },
+    hid_report::{encode_rumble, PackedInputDataReport, StickCalibration},
+};
+
+// Report IDs
+pub const INPUT_REPORT_ID: u8 = 0x30;
+pub const SUBCOMMAND_REPLY_REPORT_ID: u8 = 0x21;
+const SUBCOMMAND_REPORT_ID: u8 = 0x01;
+const RUMBLE_REPORT_ID: u8 = 0x10;
+
+// Subcommands
+const SUBCOMMAND_SET_REPORT_MODE: u8 = 0x03;
+const SUBCOMMAND_SPI_READ: u8 = 0x10;
+const SUBCOMMAND_SET_PLAYER_LIGHTS: u8 = 0x30;
+const SUBCOMMAND_ENABLE_IMU: u8 = 0x40;
+const SUBCOMMAND_ENABLE_VIBRATION: u8 = 0x48;
+
+// Standard full report mode
+const REPORT_MODE_FULL: u8 = 0x30;
+
+// SPI flash addresses of the factory stick calibration
+const SPI_LEFT_STICK_CALIBRATION: u32 = 0x603D;
+const SPI_RIGHT_STICK_CALIBRATION: u32 = 0x6046;
+
+// Input report size
+const PACKET_SIZE: usize = 49;
+
+// Output report size
+const OUTPUT_PACKET_SIZE: usize = 49;
+
+// HID buffer read timeout
+const HID_TIMEOUT: i32 = 10;
+
+// Timeout waiting for subcommand replies
+const SUBCOMMAND_TIMEOUT: i32 = 50;
+const SUBCOMMAND_RETRIES: usize = 20;
+
+pub const VID: u16 = 0x057e;
+pub const PID_LEFT: u16 = 0x2006;
+pub const PID_RIGHT: u16 = 0x2007;
+
+/// Which half of a Joy-Con pair the device is
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JoyConSide {
+    Left,
+    Right,
+}
+
+pub struct Driver {
+    /// HIDRAW device instance
+    device: HidDevice,
+    /// Which half of the pair this Joy-Con is
+    side: JoyConSide,
+    /// Calibration of the stick on this Joy-Con
+    calibration: StickCalibration,
+    /// Packet counter sent with each output report
+    packet_counter: u8,
+    /// State for the device
+    state: Option<PackedInputDataReport>,
+}
+
+impl Driver {
+    pub fn new(udevice: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let path = udevice.devnode();
+
+        let cs_path = CString::new(path.clone())?;
+        let api = hidapi::HidApi::new()?;
+        let device = api.open_path(&cs_path)?;
+
+        let info = device.get_device_info()?;
+        if info.vendor_id() != VID {
+            return Err(format!("Device '{path}' is not a Joy-Con").into());
+        }
+        let side = match info.product_id() {
+            PID_LEFT => JoyConSide::Left,
+            PID_RIGHT => JoyConSide::Right,
+            _ => return Err(format!("Device '{path}' is not a Joy-Con").into()),
+        };
+
+        let mut driver = Self {
+            device,
+            side,
+            calibration: StickCalibration::default(),
+            packet_counter: 0,
+            state: None,
+        };
+        driver.initialize()?;
+
+        Ok(driver)
+    }
+
+    /// Returns which half of the Joy-Con pair this device is
+    pub fn side(&self) -> JoyConSide {
+        self.side
+    }
+
+    /// Returns the calibration of the stick on this Joy-Con
+    pub fn calibration(&self) -> StickCalibration {
+        self.calibration
+    }
+
+    /// Switch the controller into full report mode, enable the IMU and
+    /// vibration, and read the stick calibration.
+    fn initialize(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.send_subcommand(SUBCOMMAND_SET_REPORT_MODE, &[REPORT_MODE_FULL])?;
+        self.send_subcommand(SUBCOMMAND_ENABLE_IMU, &[0x01])?;
+        self.send_subcommand(SUBCOMMAND_ENABLE_VIBRATION, &[0x01])?;
+
+        // Light up the first player light
+        self.send_subcommand(SUBCOMMAND_SET_PLAYER_LIGHTS, &[0x01])?;
+
+        match self.read_stick_calibration() {
+            Ok(calibration) => {
+                log::debug!("Read stick calibration: {calibration:?}");
+                self.calibration = calibration;
+            }
+            Err(e) => {
+                log::warn!("Failed to read stick calibration, using defaults: {e:?}");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Read the factory stick calibration from the SPI flash
+    fn read_stick_calibration(&mut self) -> Result<StickCalibration, Box<dyn Error + Send + Sync>> {
+        let address = match self.side {
+            JoyConSide::Left => SPI_LEFT_STICK_CALIBRATION,
+            JoyConSide::Right => SPI_RIGHT_STICK_CALIBRATION,
+        };
+        let data = self.read_spi(address, 9)?;
+        let data: [u8; 9] = data
+            .as_slice()
+            .try_into()
+            .map_err(|_| "Invalid stick calibration size")?;
+        let calibration = match self.side {
+            JoyConSide::Left => StickCalibration::from_left(&data),
+            JoyConSide::Right => StickCalibration::from_right(&data),
+        };
+
+        Ok(calibration)
+    }
+
+    /// Read the given number of bytes from the SPI flash at the given address
+    fn read_spi(
+        &mut self,
+        address: u32,
+        size: u8,
+    ) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        let mut args = address.to_le_bytes().to_vec();
+        args.push(size);
+        let reply = self.send_subcommand(SUBCOMMAND_SPI_READ, &args)?;
+
+        // The reply data starts with the address and size that were read
+        let start = 5;
+        let end = start + size as usize;
+        if reply.len() < end {
+            return Err("SPI read reply is too short".into());
+        }
+
+        Ok(reply[start..end].to_vec())
+    }
+
+    /// Send the given subcommand and wait for its reply. Returns the reply
+    /// data of the subcommand.
+    fn send_subcommand(
+        &mut self,
+        subcommand: u8,
+        args: &[u8],
+    ) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        let mut buf = [0; OUTPUT_PACKET_SIZE];
+        buf[0] = SUBCOMMAND_REPORT_ID;
+        buf[1] = self.next_packet_counter();
+        buf[2..6].copy_from_slice(&encode_rumble(0));
+        buf[6..10].copy_from_slice(&encode_rumble(0));
+        buf[10] = subcommand;
+        let len = args.len().min(OUTPUT_PACKET_SIZE - 11);
+        buf[11..11 + len].copy_from_slice(&args[..len]);
+        self.device.write(&buf)?;
+
+        // Wait for the reply to the subcommand
+        let mut reply = [0; PACKET_SIZE];
+        for _ in 0..SUBCOMMAND_RETRIES {
+            let bytes_read = self.device.read_timeout(&mut reply, SUBCOMMAND_TIMEOUT)?;
+            if bytes_read < 15 || reply[0] != SUBCOMMAND_REPLY_REPORT_ID {
+                continue;
+            }
+            if reply[14] != subcommand {
+                continue;
+            }
+            return Ok(reply[15..bytes_read].to_vec());
+        }
+
+        Err(format!("Timed out waiting for reply to subcommand {subcommand:#04x}").into())
+    }
+
+    /// Returns the next value of the 4-bit packet counter
+    fn next_packet_counter(&mut self) -> u8 {
+        let counter = self.packet_counter;
+        self.packet_counter = (self.packet_counter + 1) & 0x0F;
+        counter
+    }
+
+    /// Rumble the Joy-Con with the given amplitude (0-100)
+    pub fn rumble(&mut self, amplitude: u8) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let data = encode_rumble(amplitude);
+        let mut buf = [0; 10];
+        buf[0] = RUMBLE_REPORT_ID;
+        buf[1] = self.next_packet_counter();
+        buf[2..6].copy_from_slice(&data);
+        buf[6..10].copy_from_slice(&data);
+        self.device.write(&buf)?;
+
+        Ok(())
+    }
+
+    /// Poll the device and read input reports
+    pub fn poll(&mut self) -> Result<Vec<Event>, Box<dyn Error + Send + Sync>> {
+        // Read data from the device into a buffer
+        let mut buf = [0; PACKET_SIZE];
+        let bytes_read = self.device.read_timeout(&mut buf[..], HID_TIMEOUT)?;
+        if bytes_read == 0 {
+            return Ok(vec![]);
+        }
+
+        let report_id = buf[0];
+        match report_id {
+            INPUT_REPORT_ID => (),
+            // Replies to subcommands also include the button state, but are
+            // only expected during initialization.
+            SUBCOMMAND_REPLY_REPORT_ID => return Ok(vec![]),
+            _ => {
+                log::warn!("Got unhandled report_id {report_id}, someone should look into that...");
+                return Ok(vec![]);
+            }
+        }
+
+        let input_report = PackedInputDataReport::unpack(&buf)?;
+
+        // Update the state
+        let old_state = self.update_state(input_report);
+
+        // Translate the state into a stream of input events
+        let events = self.translate_events(old_state);
+
+        Ok(events)
+    }
+
+    /// Update input state
+    fn update_state(
+        &mut self,
+        input_report: PackedInputDataReport,
+    ) -> Option<PackedInputDataReport> {
+        let old_state = self.state;
+        self.state = Some(input_report);
+        old_state
+    }
+
+    /// Translate the state into individual events
+    fn translate_events(&mut self, old_state: Option<PackedInputDataReport>) -> Vec<Event> {
+        let mut events = Vec::new();
+        let Some(state) = self.state else {
+            return events;
+        };
+
+        // Translate state changes into events if they have changed
+        let Some(old_state) = old_state else {
+            return events;
+        };
+
+        // Binary Events
+        if state.a != old_state.a {
+            events.push(Event::Button(ButtonEvent::A(BinaryInput {
+                pressed: state.a,
+            })));
+        }
+        if state.b != old_state.b {
+            events.push(Event::Button(ButtonEvent::B(BinaryInput {
+                pressed: state.b,
+            })));
+        }
+        if state.x != old_state.x {
+            events.push(Event::Button(ButtonEvent::X(BinaryInput {
+                pressed: state.x,
+            })));
+        }
+        if state.y != old_state.y {
+            events.push(Event::Button(ButtonEvent::Y(BinaryInput {
+                pressed: state.y,
+            })));
+        }
+        if state.r != old_state.r {
+            events.push(Event::Button(ButtonEvent::R(BinaryInput {
+                pressed: state.r,
+            })));
+        }
+        if state.zr != old_state.zr {
+            events.push(Event::Button(ButtonEvent::ZR(BinaryInput {
+                pressed: state.zr,
+            })));
+        }
+        if state.right_sl != old_state.right_sl {
+            events.push(Event::Button(ButtonEvent::RightSL(BinaryInput {
+                pressed: state.right_sl,
+            })));
+        }
+        if state.right_sr != old_state.right_sr {
+            events.push(Event::Button(ButtonEvent::RightSR(BinaryInput {
+                pressed: state.right_sr,
+            })));
+        }
+        if state.plus != old_state.plus {
+            events.push(Event::Button(ButtonEvent::Plus(BinaryInput {
+                pressed: state.plus,
+            })));
+        }
+        if state.home != old_state.home {
+            events.push(Event::Button(ButtonEvent::Home(BinaryInput {
+                pressed: state.home,
+            })));
+        }
+        if state.r_stick != old_state.r_stick {
+            events.push(Event::Button(ButtonEvent::RStick(BinaryInput {
+                pressed: state.r_stick,
+            })));
+        }
+        if state.l != old_state.l {
+            events.push(Event::Button(ButtonEvent::L(BinaryInput {
+                pressed: state.l,
+            })));
+        }
+        if state.zl != old_state.zl {
+            events.push(Event::Button(ButtonEvent::ZL(BinaryInput {
+                pressed: state.zl,
+            })));
+        }
+        if state.left_sl != old_state.left_sl {
+            events.push(Event::Button(ButtonEvent::LeftSL(BinaryInput {
+                pressed: state.left_sl,
+            })));
+        }
+        if state.left_sr != old_state.left_sr {
+            events.push(Event::Button(ButtonEvent::LeftSR(BinaryInput {
+                pressed: state.left_sr,
+            })));
+        }
+        if state.minus != old_state.minus {
+            events.push(Event::Button(ButtonEvent::Minus(BinaryInput {
+                pressed: state.minus,
+            })));
+        }
+        if state.capture != old_state.capture {
+            events.push(Event::Button(ButtonEvent::Capture(BinaryInput {
+                pressed: state.capture,
+            })));
+        }
+        if state.l_stick != old_state.l_stick {
+            events.push(Event::Button(ButtonEvent::LStick(BinaryInput {
+                pressed: state.l_stick,
+            })));
+        }
+        if state.dpad_up != old_state.dpad_up {
+            events.push(Event::Button(ButtonEvent::DPadUp(BinaryInput {
+                pressed: state.dpad_up,
+            })));
+        }
+        if state.dpad_right != old_state.dpad_right {
+            events.push(Event::Button(ButtonEvent::DPadRight(BinaryInput {
+                pressed: state.dpad_right,
+            })));
+        }
+        if state.dpad_down != old_state.dpad_down {
+            events.push(Event::Button(ButtonEvent::DPadDown(BinaryInput {
+                pressed: state.dpad_down,
+            })));
+        }
+        if state.dpad_left != old_state.dpad_left {
+            events.push(Event::Button(ButtonEvent::DPadLeft(BinaryInput {
+                pressed: state.dpad_left,
+            })));
+        }
+
+        // Axis events
+        match self.side {
+            JoyConSide::Left => {
+                if state.left_stick != old_state.left_stick {
+                    let (x, y) = state.left_stick_values();
+                    events.push(Event::Joystick(JoystickEvent::LStick(JoystickInput {
+                        x,
+                        y,
+                    })));
+                }
+            }
+            JoyConSide::Right => {
+                if state.right_stick != old_state.right_stick {
+                    let (x, y) = state.right_stick_values();
+                    events.push(Event::Joystick(JoystickEvent::RStick(JoystickInput {
+                        x,
+                        y,
+                    })));
+                }
+
+                // Only the right Joy-Con reports motion so a combined pair
+                // does not emit two conflicting IMU streams.
+                events.push(Event::Inertia(InertialEvent::Accelerometer(
+                    InertialInput {
+                        x: state.accel_x.to_primitive(),
+                        y: state.accel_y.to_primitive(),
+                        z: state.accel_z.to_primitive(),
+                    },
+                )));
+                events.push(Event::Inertia(InertialEvent::Gyro(InertialInput {
+                    x: state.gyro_x.to_primitive(),
+                    y: state.gyro_y.to_primitive(),
+                    z: state.gyro_z.to_primitive(),
+                })));
+            }
+        }
+
+        log::trace!("Got events: {events:?}");
+
+        events
+    }
+}
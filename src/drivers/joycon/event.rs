@@ -0,0 +1,92 @@
+/// Events that can be emitted by a Joy-Con
+#[derive(Clone, Debug)]
+pub enum Event {
+    Button(ButtonEvent),
+    Inertia(InertialEvent),
+    Joystick(JoystickEvent),
+}
+
+/// [BinaryInput] contains either pressed or unpressed
+#[derive(Clone, Debug)]
+pub struct BinaryInput {
+    pub pressed: bool,
+}
+
+/// Button events represend binary inputs
+#[derive(Clone, Debug)]
+pub enum ButtonEvent {
+    /// A Button (Right Joy-Con)
+    A(BinaryInput),
+    /// B Button (Right Joy-Con)
+    B(BinaryInput),
+    /// X Button (Right Joy-Con)
+    X(BinaryInput),
+    /// Y Button (Right Joy-Con)
+    Y(BinaryInput),
+    /// R shoulder button (Right Joy-Con)
+    R(BinaryInput),
+    /// ZR trigger button (Right Joy-Con)
+    ZR(BinaryInput),
+    /// SL rail button (Right Joy-Con)
+    RightSL(BinaryInput),
+    /// SR rail button (Right Joy-Con)
+    RightSR(BinaryInput),
+    /// Plus (+) button (Right Joy-Con)
+    Plus(BinaryInput),
+    /// Home button (Right Joy-Con)
+    Home(BinaryInput),
+    /// Z-axis button on the right stick (Right Joy-Con)
+    RStick(BinaryInput),
+    /// L shoulder button (Left Joy-Con)
+    L(BinaryInput),
+    /// ZL trigger button (Left Joy-Con)
+    ZL(BinaryInput),
+    /// SL rail button (Left Joy-Con)
+    LeftSL(BinaryInput),
+    /// SR rail button (Left Joy-Con)
+    LeftSR(BinaryInput),
+    /// Minus (-) button (Left Joy-Con)
+    Minus(BinaryInput),
+    /// Capture button (Left Joy-Con)
+    Capture(BinaryInput),
+    /// Z-axis button on the left stick (Left Joy-Con)
+    LStick(BinaryInput),
+    /// DPad up (Left Joy-Con)
+    DPadUp(BinaryInput),
+    /// DPad right (Left Joy-Con)
+    DPadRight(BinaryInput),
+    /// DPad down (Left Joy-Con)
+    DPadDown(BinaryInput),
+    /// DPad left (Left Joy-Con)
+    DPadLeft(BinaryInput),
+}
+
+/// [InertialInput] represents the state of the IMU (x, y, z) values
+#[derive(Clone, Debug)]
+pub struct InertialInput {
+    pub x: i16,
+    pub y: i16,
+    pub z: i16,
+}
+
+/// [InertialEvent] has data from the IMU
+#[derive(Clone, Debug)]
+pub enum InertialEvent {
+    Accelerometer(InertialInput),
+    Gyro(InertialInput),
+}
+
+/// [JoystickInput] is a double (x, y) axis with raw 12-bit values
+#[derive(Clone, Debug)]
+pub struct JoystickInput {
+    pub x: u16,
+    pub y: u16,
+}
+
+/// [JoystickEvent] are events that have (x, y) values in the absolute domain indicating how far
+/// left/right (x) and up/down (y) the joystick is off center.
+#[derive(Clone, Debug)]
+pub enum JoystickEvent {
+    LStick(JoystickInput),
+    RStick(JoystickInput),
+}
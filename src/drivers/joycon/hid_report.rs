@@ -0,0 +1,201 @@
+use packed_struct::prelude::*;
+
+/// Joy-Con standard full input report (0x30). This report is sent at 60Hz
+/// (Bluetooth) once the controller has been switched into full report mode
+/// and contains the button, stick, and IMU state.
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering = "msb0", size_bytes = "49")]
+pub struct PackedInputDataReport {
+    // byte 0
+    #[packed_field(bytes = "0")]
+    pub report_id: u8, // Report ID (always 0x30)
+
+    // byte 1-2
+    #[packed_field(bytes = "1")]
+    pub timer: u8,
+    #[packed_field(bits = "16..=19")]
+    pub battery_level: Integer<u8, packed_bits::Bits<4>>,
+    #[packed_field(bits = "20..=23")]
+    pub connection_info: Integer<u8, packed_bits::Bits<4>>,
+
+    // byte 3 (Right Joy-Con)
+    #[packed_field(bits = "24")]
+    pub zr: bool,
+    #[packed_field(bits = "25")]
+    pub r: bool,
+    #[packed_field(bits = "26")]
+    pub right_sl: bool,
+    #[packed_field(bits = "27")]
+    pub right_sr: bool,
+    #[packed_field(bits = "28")]
+    pub a: bool,
+    #[packed_field(bits = "29")]
+    pub b: bool,
+    #[packed_field(bits = "30")]
+    pub x: bool,
+    #[packed_field(bits = "31")]
+    pub y: bool,
+
+    // byte 4 (Shared)
+    #[packed_field(bits = "32")]
+    pub charging_grip: bool,
+    #[packed_field(bits = "34")]
+    pub capture: bool,
+    #[packed_field(bits = "35")]
+    pub home: bool,
+    #[packed_field(bits = "36")]
+    pub l_stick: bool,
+    #[packed_field(bits = "37")]
+    pub r_stick: bool,
+    #[packed_field(bits = "38")]
+    pub plus: bool,
+    #[packed_field(bits = "39")]
+    pub minus: bool,
+
+    // byte 5 (Left Joy-Con)
+    #[packed_field(bits = "40")]
+    pub zl: bool,
+    #[packed_field(bits = "41")]
+    pub l: bool,
+    #[packed_field(bits = "42")]
+    pub left_sl: bool,
+    #[packed_field(bits = "43")]
+    pub left_sr: bool,
+    #[packed_field(bits = "44")]
+    pub dpad_left: bool,
+    #[packed_field(bits = "45")]
+    pub dpad_right: bool,
+    #[packed_field(bits = "46")]
+    pub dpad_up: bool,
+    #[packed_field(bits = "47")]
+    pub dpad_down: bool,
+
+    // byte 6-11
+    #[packed_field(bytes = "6..=8")]
+    pub left_stick: [u8; 3], // 12-bit X and Y axis values
+    #[packed_field(bytes = "9..=11")]
+    pub right_stick: [u8; 3], // 12-bit X and Y axis values
+
+    // byte 12
+    #[packed_field(bytes = "12")]
+    pub vibrator_report: u8,
+
+    // byte 13-48
+    // The report contains three IMU samples taken 5ms apart. Only the most
+    // recent sample is used.
+    #[packed_field(bytes = "13..=36")]
+    pub imu_previous: [u8; 24],
+    #[packed_field(bytes = "37..=38", endian = "lsb")]
+    pub accel_x: Integer<i16, packed_bits::Bits<16>>,
+    #[packed_field(bytes = "39..=40", endian = "lsb")]
+    pub accel_y: Integer<i16, packed_bits::Bits<16>>,
+    #[packed_field(bytes = "41..=42", endian = "lsb")]
+    pub accel_z: Integer<i16, packed_bits::Bits<16>>,
+    #[packed_field(bytes = "43..=44", endian = "lsb")]
+    pub gyro_x: Integer<i16, packed_bits::Bits<16>>,
+    #[packed_field(bytes = "45..=46", endian = "lsb")]
+    pub gyro_y: Integer<i16, packed_bits::Bits<16>>,
+    #[packed_field(bytes = "47..=48", endian = "lsb")]
+    pub gyro_z: Integer<i16, packed_bits::Bits<16>>,
+}
+
+impl PackedInputDataReport {
+    /// Returns the raw 12-bit (x, y) values of the left stick
+    pub fn left_stick_values(&self) -> (u16, u16) {
+        decode_stick(&self.left_stick)
+    }
+
+    /// Returns the raw 12-bit (x, y) values of the right stick
+    pub fn right_stick_values(&self) -> (u16, u16) {
+        decode_stick(&self.right_stick)
+    }
+}
+
+/// Decode two 12-bit values packed into three bytes
+fn decode_stick(data: &[u8; 3]) -> (u16, u16) {
+    let x = data[0] as u16 | ((data[1] as u16 & 0x0F) << 8);
+    let y = (data[1] as u16 >> 4) | ((data[2] as u16) << 4);
+    (x, y)
+}
+
+/// Stick calibration read from the SPI flash of the controller. The values
+/// describe the raw 12-bit range of each stick axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StickCalibration {
+    pub x_min: u16,
+    pub x_center: u16,
+    pub x_max: u16,
+    pub y_min: u16,
+    pub y_center: u16,
+    pub y_max: u16,
+}
+
+impl Default for StickCalibration {
+    fn default() -> Self {
+        Self {
+            x_min: 500,
+            x_center: 2048,
+            x_max: 3500,
+            y_min: 500,
+            y_center: 2048,
+            y_max: 3500,
+        }
+    }
+}
+
+impl StickCalibration {
+    /// Parse the factory calibration of the left stick. The data is stored
+    /// as max above center, center, and min below center.
+    pub fn from_left(data: &[u8; 9]) -> Self {
+        let (above_x, above_y) = decode_stick(&[data[0], data[1], data[2]]);
+        let (center_x, center_y) = decode_stick(&[data[3], data[4], data[5]]);
+        let (below_x, below_y) = decode_stick(&[data[6], data[7], data[8]]);
+        Self::from_values(center_x, center_y, above_x, above_y, below_x, below_y)
+    }
+
+    /// Parse the factory calibration of the right stick. The data is stored
+    /// as center, min below center, and max above center.
+    pub fn from_right(data: &[u8; 9]) -> Self {
+        let (center_x, center_y) = decode_stick(&[data[0], data[1], data[2]]);
+        let (below_x, below_y) = decode_stick(&[data[3], data[4], data[5]]);
+        let (above_x, above_y) = decode_stick(&[data[6], data[7], data[8]]);
+        Self::from_values(center_x, center_y, above_x, above_y, below_x, below_y)
+    }
+
+    fn from_values(
+        center_x: u16,
+        center_y: u16,
+        above_x: u16,
+        above_y: u16,
+        below_x: u16,
+        below_y: u16,
+    ) -> Self {
+        // Unprogrammed flash reads as 0xFFF
+        if center_x == 0xFFF || center_y == 0xFFF || above_x == 0 || below_x == 0 {
+            return Self::default();
+        }
+        Self {
+            x_min: center_x.saturating_sub(below_x),
+            x_center: center_x,
+            x_max: center_x.saturating_add(above_x),
+            y_min: center_y.saturating_sub(below_y),
+            y_center: center_y,
+            y_max: center_y.saturating_add(above_y),
+        }
+    }
+}
+
+/// Encode the given rumble amplitude (0-100) into the 4 bytes used to drive
+/// a single linear resonant actuator. The high and low band frequencies are
+/// fixed at 320Hz and 160Hz.
+pub fn encode_rumble(amplitude: u8) -> [u8; 4] {
+    // Encoded frequency values for 320Hz (high band) and 160Hz (low band)
+    const HF: u16 = 0x0100;
+    const LF: u8 = 0x40;
+
+    let amplitude = amplitude.min(100);
+    let hf_amp = amplitude * 2;
+    let lf_amp = amplitude / 2 + 0x40;
+
+    [(HF & 0xFF) as u8, hf_amp + (HF >> 8) as u8, LF, lf_amp]
+}
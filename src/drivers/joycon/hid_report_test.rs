@@ -0,0 +1,59 @@
+use std::error::Error;
+
+use packed_struct::{types::SizedInteger, PackedStructSlice};
+
+use super::hid_report::{encode_rumble, PackedInputDataReport, StickCalibration};
+
+#[tokio::test]
+async fn test_joycon_input_report() -> Result<(), Box<dyn Error>> {
+    let report = PackedInputDataReport::unpack_from_slice(&DATA_A_DPAD_UP).unwrap();
+    println!("{report:?}");
+    assert!(report.a, "A should be pressed");
+    assert!(report.dpad_up, "DPad up should be pressed");
+    assert!(!report.b, "B should not be pressed");
+    assert_eq!(report.left_stick_values(), (0x7A5, 0x6B1));
+    assert_eq!(report.right_stick_values(), (0x800, 0x7FF));
+    assert_eq!(report.accel_x.to_primitive(), 0x0102);
+    assert_eq!(report.gyro_z.to_primitive(), -2);
+
+    Ok(())
+}
+
+#[test]
+fn test_joycon_stick_calibration() {
+    // Max above center: (1280, 1296), center: (2048, 2064), min below
+    // center: (1200, 1216)
+    let left = [0x00, 0x05, 0x51, 0x00, 0x08, 0x81, 0xB0, 0x04, 0x4C];
+    let calibration = StickCalibration::from_left(&left);
+    assert_eq!(calibration.x_center, 2048);
+    assert_eq!(calibration.x_min, 848);
+    assert_eq!(calibration.x_max, 3328);
+    assert_eq!(calibration.y_center, 2064);
+    assert_eq!(calibration.y_min, 848);
+    assert_eq!(calibration.y_max, 3360);
+
+    // The right stick stores center, min below, and max above
+    let right = [0x00, 0x08, 0x81, 0xB0, 0x04, 0x4C, 0x00, 0x05, 0x51];
+    assert_eq!(StickCalibration::from_right(&right), calibration);
+
+    // Unprogrammed calibration data should use the defaults
+    let empty = [0xFF; 9];
+    assert_eq!(
+        StickCalibration::from_left(&empty),
+        StickCalibration::default()
+    );
+}
+
+#[test]
+fn test_joycon_encode_rumble() {
+    assert_eq!(encode_rumble(0), [0x00, 0x01, 0x40, 0x40]);
+    assert_eq!(encode_rumble(100), [0x00, 0xC9, 0x40, 0x72]);
+    assert_eq!(encode_rumble(255), encode_rumble(100));
+}
+
+const DATA_A_DPAD_UP: [u8; 49] = [
+    0x30, 0x5c, 0x8e, 0x08, 0x00, 0x02, 0xa5, 0x17, 0x6b, 0x00, 0xf8, 0x7f, 0x09, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x01, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0xfe,
+    0xff,
+];
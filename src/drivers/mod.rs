@@ -1,10 +1,18 @@
+pub mod bitdo_ultimate;
 pub mod dualsense;
+pub mod firmware;
+#[cfg(test)]
+pub mod firmware_test;
 pub mod fts3528;
+pub mod hid_generic;
 pub mod horipad_steam;
 pub mod iio_imu;
+pub mod joycon;
 pub mod lego;
 pub mod legos;
+pub mod msi_claw;
 pub mod opineo;
 pub mod rog_ally;
+pub mod steam_controller;
 pub mod steam_deck;
 pub mod xpad_uhid;
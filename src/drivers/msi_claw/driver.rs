@@ -0,0 +1,95 @@
+use std::{error::Error, ffi::CString, sync::Mutex};
+
+use hidapi::HidDevice;
+use packed_struct::PackedStruct;
+
+use crate::udev::device::UdevDevice;
+
+use super::hid_report::{GamepadMode, PackedSwitchModeReport};
+
+// Report ID
+pub const REPORT_ID: u8 = 0x0f;
+
+// Hardware ID's
+pub const VID: u16 = 0x0db0;
+pub const XINPUT_PID: u16 = 0x1901;
+pub const DINPUT_PID: u16 = 0x1902;
+pub const DESKTOP_PID: u16 = 0x1903;
+pub const PIDS: [u16; 3] = [XINPUT_PID, DINPUT_PID, DESKTOP_PID];
+
+/// USB interface of the controller that implements the vendor protocol
+pub const VENDOR_INTERFACE: i32 = 2;
+
+/// Mode the controller is switched to when the composite device starts,
+/// unless another mode was requested with [set_requested_mode]
+pub const PREFERRED_MODE: GamepadMode = GamepadMode::XInput;
+
+/// Mode that was last requested over DBus. The controller reconnects after
+/// switching modes, so this keeps the requested mode from being reverted to
+/// the preferred mode.
+static REQUESTED_MODE: Mutex<Option<GamepadMode>> = Mutex::new(None);
+
+/// Returns the mode the controller should be in
+pub fn requested_mode() -> GamepadMode {
+    let mode = REQUESTED_MODE.lock().unwrap_or_else(|e| e.into_inner());
+    mode.unwrap_or(PREFERRED_MODE)
+}
+
+/// Set the mode the controller should be in
+pub fn set_requested_mode(mode: GamepadMode) {
+    let mut requested = REQUESTED_MODE.lock().unwrap_or_else(|e| e.into_inner());
+    *requested = Some(mode);
+}
+
+/// Returns true if the given device is the vendor interface of an MSI Claw
+/// controller
+pub fn is_supported(vid: u16, pid: u16, interface: i32) -> bool {
+    vid == VID && PIDS.contains(&pid) && interface == VENDOR_INTERFACE
+}
+
+pub struct Driver {
+    /// HIDRAW device instance
+    device: HidDevice,
+    /// Current mode of the controller
+    mode: Option<GamepadMode>,
+}
+
+impl Driver {
+    pub fn new(udevice: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let path = udevice.devnode();
+        let vid = udevice.id_vendor();
+        let pid = udevice.id_product();
+        if VID != vid || !PIDS.contains(&pid) {
+            return Err(format!("'{path}' is not an MSI Claw controller").into());
+        }
+
+        let cs_path = CString::new(path)?;
+        let api = hidapi::HidApi::new()?;
+        let device = api.open_path(&cs_path)?;
+
+        Ok(Self {
+            device,
+            mode: GamepadMode::from_pid(pid),
+        })
+    }
+
+    /// Returns the current mode of the controller
+    pub fn mode(&self) -> Option<GamepadMode> {
+        self.mode
+    }
+
+    /// Switch the controller to the given mode. The controller disconnects
+    /// and reconnects with the product id of the new mode.
+    pub fn set_mode(&mut self, mode: GamepadMode) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if self.mode == Some(mode) {
+            return Ok(());
+        }
+        let report = PackedSwitchModeReport::new(mode);
+        let data = report.pack()?;
+        self.device.write(&data)?;
+        log::debug!("Switched MSI Claw to {mode} mode");
+        self.mode = Some(mode);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+use std::fmt::Display;
+
+use packed_struct::prelude::*;
+
+use super::driver::{DESKTOP_PID, DINPUT_PID, REPORT_ID, XINPUT_PID};
+
+/// Header byte that precedes the command of every vendor report
+pub const COMMAND_HEADER: u8 = 0x3c;
+
+/// Commands of the vendor protocol
+#[derive(PrimitiveEnum_u8, Clone, Copy, PartialEq, Debug, Default)]
+pub enum Command {
+    #[default]
+    Ack = 0x06,
+    SwitchMode = 0x24,
+    ReadGamepadMode = 0x26,
+    GamepadModeAck = 0x27,
+}
+
+/// Hardware mode of the controller. The controller reconnects with a
+/// different product id after switching between the XInput, DInput and
+/// desktop modes.
+#[derive(PrimitiveEnum_u8, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum GamepadMode {
+    #[default]
+    Offline = 0,
+    XInput = 1,
+    DInput = 2,
+    Msi = 3,
+    /// The controller emulates a keyboard and mouse
+    Desktop = 4,
+    Bios = 5,
+    Testing = 6,
+}
+
+impl GamepadMode {
+    /// Modes that can be selected with [GamepadMode::from_name]
+    pub const SELECTABLE: [GamepadMode; 3] = [
+        GamepadMode::XInput,
+        GamepadMode::DInput,
+        GamepadMode::Desktop,
+    ];
+
+    /// Returns the name of the mode (e.g. "xinput")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GamepadMode::Offline => "offline",
+            GamepadMode::XInput => "xinput",
+            GamepadMode::DInput => "dinput",
+            GamepadMode::Msi => "msi",
+            GamepadMode::Desktop => "keyboard",
+            GamepadMode::Bios => "bios",
+            GamepadMode::Testing => "testing",
+        }
+    }
+
+    /// Returns the selectable mode with the given name
+    pub fn from_name(name: &str) -> Option<Self> {
+        GamepadMode::SELECTABLE
+            .into_iter()
+            .find(|mode| mode.as_str() == name)
+    }
+
+    /// Returns the mode the controller is in when it has the given product id
+    pub fn from_pid(pid: u16) -> Option<Self> {
+        match pid {
+            XINPUT_PID => Some(GamepadMode::XInput),
+            DINPUT_PID => Some(GamepadMode::DInput),
+            DESKTOP_PID => Some(GamepadMode::Desktop),
+            _ => None,
+        }
+    }
+}
+
+impl Display for GamepadMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Output report that switches the controller to the given mode
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering = "msb0", size_bytes = "64")]
+pub struct PackedSwitchModeReport {
+    // byte 0
+    #[packed_field(bytes = "0")]
+    pub report_id: u8, // Report ID (always 0x0f)
+
+    // byte 3
+    #[packed_field(bytes = "3")]
+    pub header: u8, // Command header (always 0x3c)
+
+    // byte 4
+    #[packed_field(bytes = "4", ty = "enum")]
+    pub command: Command,
+
+    // byte 5
+    #[packed_field(bytes = "5", ty = "enum")]
+    pub mode: GamepadMode,
+
+    // byte 6
+    #[packed_field(bytes = "6")]
+    pub macro_keys: u8, // Function of the M keys, 0 = macro, 1 = combination
+}
+
+impl PackedSwitchModeReport {
+    /// Returns a report that switches the controller to the given mode
+    pub fn new(mode: GamepadMode) -> Self {
+        Self {
+            mode,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for PackedSwitchModeReport {
+    fn default() -> Self {
+        Self {
+            report_id: REPORT_ID,
+            header: COMMAND_HEADER,
+            command: Command::SwitchMode,
+            mode: GamepadMode::XInput,
+            macro_keys: 0,
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use std::error::Error;
+
+use packed_struct::PackedStruct;
+
+use super::{
+    driver::{DESKTOP_PID, DINPUT_PID, XINPUT_PID},
+    hid_report::{GamepadMode, PackedSwitchModeReport},
+};
+
+#[test]
+fn test_switch_mode_report() -> Result<(), Box<dyn Error>> {
+    let report = PackedSwitchModeReport::new(GamepadMode::DInput).pack()?;
+    assert_eq!(report.len(), 64);
+    assert_eq!(report[..7], [0x0f, 0x00, 0x00, 0x3c, 0x24, 0x02, 0x00]);
+    assert!(report[7..].iter().all(|byte| *byte == 0));
+
+    Ok(())
+}
+
+#[test]
+fn test_gamepad_mode() {
+    assert_eq!(GamepadMode::from_pid(XINPUT_PID), Some(GamepadMode::XInput));
+    assert_eq!(GamepadMode::from_pid(DINPUT_PID), Some(GamepadMode::DInput));
+    assert_eq!(
+        GamepadMode::from_pid(DESKTOP_PID),
+        Some(GamepadMode::Desktop)
+    );
+    assert_eq!(GamepadMode::from_pid(0x0000), None);
+
+    for mode in GamepadMode::SELECTABLE {
+        assert_eq!(GamepadMode::from_name(mode.as_str()), Some(mode));
+    }
+    assert_eq!(GamepadMode::from_name("bios"), None);
+}
@@ -0,0 +1,4 @@
+pub mod driver;
+pub mod hid_report;
+#[cfg(test)]
+pub mod hid_report_test;
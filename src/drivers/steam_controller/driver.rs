@@ -0,0 +1,441 @@
+use std::{error::Error, ffi::CString};
+
+use hidapi::HidDevice;
+use packed_struct::{types::SizedInteger, PackedStruct};
+
+use crate::{
+    drivers::steam_deck::hid_report::{Register, ReportType, TrackpadMode},
+    udev::device::UdevDevice,
+};
+
+use super::{
+    event::{
+        AccelerometerEvent, AccelerometerInput, AxisEvent, AxisInput, BinaryInput, ButtonEvent,
+        Event, TouchAxisInput, TriggerEvent, TriggerInput,
+    },
+    hid_report::{
+        DongleCommand, PackedInputDataReport, WirelessState, REPORT_TYPE_CONTROLLER_STATE,
+        REPORT_TYPE_STATUS, REPORT_TYPE_WIRELESS,
+    },
+    PID, VID,
+};
+
+/// Product ID of the wireless dongle
+pub const PID_WIRELESS: u16 = 0x1142;
+/// USB interface of the wired controller that implements the vendor protocol
+pub const WIRED_INTERFACE: i32 = 2;
+/// USB interfaces of the wireless dongle that implement the vendor protocol,
+/// one for each controller that can be connected to the dongle.
+pub const WIRELESS_INTERFACES: [i32; 4] = [1, 2, 3, 4];
+/// Scale to multiply accelerometer values to get in units of meters per second
+pub const ACCEL_SCALE: f64 = 0.0006125;
+/// Default time in seconds the dongle accepts new controllers when pairing
+pub const PAIRING_TIMEOUT_SEC: u8 = 60;
+/// Size of the HID packet
+const PACKET_SIZE: usize = 64;
+/// Timeout in milliseconds for reading an HID packet
+const HID_TIMEOUT: i32 = 10;
+
+/// Returns true if the given device is a vendor interface of the wired Steam
+/// Controller or the wireless dongle.
+pub fn is_supported(vid: u16, pid: u16, interface: i32) -> bool {
+    if vid != VID {
+        return false;
+    }
+    match pid {
+        PID => interface == WIRED_INTERFACE,
+        PID_WIRELESS => WIRELESS_INTERFACES.contains(&interface),
+        _ => false,
+    }
+}
+
+/// Left stick and left trackpad values of a controller state report. Both
+/// share the same axes in the report, so an axis is [None] if the report does
+/// not carry a new value for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LeftAxes {
+    pub stick: Option<(i16, i16)>,
+    pub pad: Option<(i16, i16)>,
+}
+
+impl LeftAxes {
+    /// Split the shared left axes of the given report. When only one of the
+    /// stick or trackpad is in use, the other one is centered. When both are
+    /// used at the same time, the controller alternates between them.
+    pub fn from_report(report: &PackedInputDataReport) -> Self {
+        let value = (report.l_x.to_primitive(), report.l_y.to_primitive());
+        let both = report.l_pad_and_stick;
+        if report.l_pad_touch {
+            Self {
+                stick: (!both).then_some((0, 0)),
+                pad: Some(value),
+            }
+        } else {
+            Self {
+                stick: Some(value),
+                pad: (!both).then_some((0, 0)),
+            }
+        }
+    }
+}
+
+pub struct Driver {
+    device: HidDevice,
+    state: Option<PackedInputDataReport>,
+    /// Whether the device is the wireless dongle
+    wireless: bool,
+    /// Whether a controller is connected. Always true for wired controllers.
+    connected: bool,
+    l_stick: (i16, i16),
+    l_pad: (i16, i16),
+}
+
+impl Driver {
+    pub fn new(udevice: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let path = udevice.devnode();
+        let cs_path = CString::new(path.clone())?;
+        let api = hidapi::HidApi::new()?;
+        let device = api.open_path(&cs_path)?;
+
+        let info = device.get_device_info()?;
+        if !is_supported(info.vendor_id(), info.product_id(), info.interface_number()) {
+            return Err(format!("Device '{path}' is not a Steam Controller").into());
+        }
+        let wireless = info.product_id() == PID_WIRELESS;
+
+        let driver = Self {
+            device,
+            state: None,
+            wireless,
+            connected: !wireless,
+            l_stick: (0, 0),
+            l_pad: (0, 0),
+        };
+
+        // The dongle only reports connection changes, so ask it whether a
+        // controller is already connected. A connected controller is woken up
+        // and starts sending input reports.
+        if wireless {
+            driver.request_wireless_state()?;
+        } else {
+            driver.set_lizard_mode(false)?;
+        }
+
+        Ok(driver)
+    }
+
+    /// Whether a controller is connected to the device
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    /// Poll the device and read input reports
+    pub fn poll(&mut self) -> Result<Vec<Event>, Box<dyn Error + Send + Sync>> {
+        // Read data from the device into a buffer
+        let mut buf = [0; PACKET_SIZE];
+        let bytes_read = self.device.read_timeout(&mut buf[..], HID_TIMEOUT)?;
+        if bytes_read == 0 {
+            return Ok(vec![]);
+        }
+        if bytes_read != PACKET_SIZE {
+            let msg = format!("Invalid input report size was received from gamepad device: {bytes_read}/{PACKET_SIZE}");
+            return Err(msg.into());
+        }
+
+        let report_type = buf[2];
+        match report_type {
+            REPORT_TYPE_CONTROLLER_STATE => {
+                // Input reports can arrive before the connection report if
+                // the controller was already on when the dongle was opened.
+                if !self.connected {
+                    self.handle_connection(WirelessState::Connected)?;
+                }
+                self.handle_input_report(buf)
+            }
+            REPORT_TYPE_WIRELESS => {
+                let state = WirelessState::try_from(buf[4])?;
+                self.handle_connection(state)
+            }
+            REPORT_TYPE_STATUS => {
+                log::trace!("Received status report: {:?}", &buf[..bytes_read]);
+                Ok(vec![])
+            }
+            _ => {
+                log::trace!("Ignoring report type {report_type}");
+                Ok(vec![])
+            }
+        }
+    }
+
+    /// Update the connection state of a wireless controller. Disconnecting
+    /// releases all inputs that are still pressed.
+    fn handle_connection(
+        &mut self,
+        state: WirelessState,
+    ) -> Result<Vec<Event>, Box<dyn Error + Send + Sync>> {
+        match state {
+            WirelessState::Connected => {
+                if self.connected {
+                    return Ok(vec![]);
+                }
+                log::info!("Steam Controller connected");
+                self.connected = true;
+                self.set_lizard_mode(false)?;
+                Ok(vec![])
+            }
+            WirelessState::Disconnected => {
+                if !self.connected {
+                    return Ok(vec![]);
+                }
+                log::info!("Steam Controller disconnected");
+                self.connected = false;
+                if self.state.is_none() {
+                    return Ok(vec![]);
+                }
+                let old_state = self.update_state(PackedInputDataReport::default());
+                Ok(self.translate(old_state))
+            }
+        }
+    }
+
+    /// Set lizard mode, which will automatically try to emulate mouse/keyboard
+    /// if enabled. Unlike the Steam Deck, the Steam Controller keeps keyboard
+    /// emulation disabled until it is re-enabled or turned off.
+    pub fn set_lizard_mode(&self, enabled: bool) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if enabled {
+            self.send_request(&[ReportType::DefaultMappings as u8])?;
+            self.send_request(&[ReportType::DefaultMouse as u8])?;
+            self.write_register(Register::SmoothAbsoluteMouse, 0x01)?;
+        } else {
+            self.send_request(&[ReportType::ClearMappings as u8])?;
+            self.write_register(Register::RPadMode, TrackpadMode::None as u16)?;
+            self.write_register(Register::SmoothAbsoluteMouse, 0x00)?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the given register value to the controller
+    pub fn write_register(
+        &self,
+        register: Register,
+        value: u16,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        // Only allow writing one register at a time (size: 3 bytes). The value
+        // is 16 bits, with the low bits first.
+        let request = [
+            ReportType::WriteRegister as u8,
+            3,
+            register as u8,
+            (value & 0xff) as u8,
+            (value >> 8) as u8,
+        ];
+        self.send_request(&request)
+    }
+
+    /// Ask the wireless dongle to report whether a controller is connected
+    pub fn request_wireless_state(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.send_dongle_command(DongleCommand::GetWirelessState, &[])
+    }
+
+    /// Enable or disable pairing of new controllers with the wireless dongle.
+    /// Pairing is disabled automatically after the given timeout in seconds.
+    pub fn set_pairing(
+        &self,
+        enabled: bool,
+        timeout_sec: u8,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.send_dongle_command(DongleCommand::EnablePairing, &[enabled as u8, timeout_sec])
+    }
+
+    /// Store a newly paired controller in the wireless dongle so it reconnects
+    /// automatically when it is turned on.
+    pub fn commit_pairing(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.send_dongle_command(DongleCommand::CommitDevice, &[])
+    }
+
+    /// Disconnect the controller from the wireless dongle, which turns the
+    /// controller off.
+    pub fn disconnect(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.send_dongle_command(DongleCommand::DisconnectDevice, &[])
+    }
+
+    /// Send the given command with its payload to the wireless dongle
+    fn send_dongle_command(
+        &self,
+        command: DongleCommand,
+        payload: &[u8],
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if !self.wireless {
+            return Err("Device is not a Steam Controller wireless dongle".into());
+        }
+        let mut request = vec![command as u8, payload.len() as u8];
+        request.extend_from_slice(payload);
+        self.send_request(&request)
+    }
+
+    /// Send the given request to the device as a feature report
+    fn send_request(&self, request: &[u8]) -> Result<(), Box<dyn Error + Send + Sync>> {
+        // The first byte is the report id, which is not used by this device
+        let mut buf = [0; PACKET_SIZE + 1];
+        buf[1..=request.len()].copy_from_slice(request);
+        self.device.send_feature_report(&buf)?;
+        Ok(())
+    }
+
+    /// Unpacks the buffer into a [PackedInputDataReport] structure and updates
+    /// the internal gamepad state
+    fn handle_input_report(
+        &mut self,
+        buf: [u8; PACKET_SIZE],
+    ) -> Result<Vec<Event>, Box<dyn Error + Send + Sync>> {
+        let input_report = PackedInputDataReport::unpack(&buf)?;
+
+        // Update the state
+        let old_state = self.update_state(input_report);
+
+        // Translate the state into a stream of input events
+        let events = self.translate(old_state);
+
+        Ok(events)
+    }
+
+    /// Update internal gamepad state
+    fn update_state(
+        &mut self,
+        input_report: PackedInputDataReport,
+    ) -> Option<PackedInputDataReport> {
+        let old_state = self.state;
+        self.state = Some(input_report);
+        old_state
+    }
+
+    /// Translate the state into individual events
+    fn translate(&mut self, old_state: Option<PackedInputDataReport>) -> Vec<Event> {
+        let mut events = Vec::new();
+        let Some(state) = self.state else {
+            return events;
+        };
+        let Some(old_state) = old_state else {
+            return events;
+        };
+
+        // Binary events
+        let buttons = [
+            (
+                state.a,
+                old_state.a,
+                ButtonEvent::A as fn(BinaryInput) -> ButtonEvent,
+            ),
+            (state.x, old_state.x, ButtonEvent::X),
+            (state.b, old_state.b, ButtonEvent::B),
+            (state.y, old_state.y, ButtonEvent::Y),
+            (state.start, old_state.start, ButtonEvent::Start),
+            (state.select, old_state.select, ButtonEvent::Select),
+            (state.steam, old_state.steam, ButtonEvent::Steam),
+            (state.down, old_state.down, ButtonEvent::DPadDown),
+            (state.up, old_state.up, ButtonEvent::DPadUp),
+            (state.left, old_state.left, ButtonEvent::DPadLeft),
+            (state.right, old_state.right, ButtonEvent::DPadRight),
+            (state.l1, old_state.l1, ButtonEvent::L1),
+            (state.l2, old_state.l2, ButtonEvent::L2),
+            (state.l3, old_state.l3, ButtonEvent::L3),
+            (state.l4, old_state.l4, ButtonEvent::L4),
+            (state.r1, old_state.r1, ButtonEvent::R1),
+            (state.r2, old_state.r2, ButtonEvent::R2),
+            (state.r4, old_state.r4, ButtonEvent::R4),
+            (
+                state.r_pad_touch,
+                old_state.r_pad_touch,
+                ButtonEvent::RPadTouch,
+            ),
+            (
+                state.l_pad_touch,
+                old_state.l_pad_touch,
+                ButtonEvent::LPadTouch,
+            ),
+            (
+                state.r_pad_press,
+                old_state.r_pad_press,
+                ButtonEvent::RPadPress,
+            ),
+            (
+                state.l_pad_press,
+                old_state.l_pad_press,
+                ButtonEvent::LPadPress,
+            ),
+        ];
+        for (pressed, old_pressed, event) in buttons {
+            if pressed != old_pressed {
+                events.push(Event::Button(event(BinaryInput { pressed })));
+            }
+        }
+
+        // Axis events
+        let axes = LeftAxes::from_report(&state);
+        if let Some(stick) = axes.stick {
+            if stick != self.l_stick {
+                self.l_stick = stick;
+                events.push(Event::Axis(AxisEvent::LStick(AxisInput {
+                    x: stick.0,
+                    y: stick.1,
+                })));
+            }
+        }
+        if let Some(pad) = axes.pad {
+            if pad != self.l_pad || state.l_pad_touch != old_state.l_pad_touch {
+                self.l_pad = pad;
+                events.push(Event::Axis(AxisEvent::LPad(TouchAxisInput {
+                    index: 0,
+                    is_touching: state.l_pad_touch,
+                    x: pad.0,
+                    y: pad.1,
+                })));
+            }
+        }
+        if state.r_pad_x != old_state.r_pad_x
+            || state.r_pad_y != old_state.r_pad_y
+            || state.r_pad_touch != old_state.r_pad_touch
+        {
+            events.push(Event::Axis(AxisEvent::RPad(TouchAxisInput {
+                index: 0,
+                is_touching: state.r_pad_touch,
+                x: state.r_pad_x.to_primitive(),
+                y: state.r_pad_y.to_primitive(),
+            })));
+        }
+
+        // Trigger events
+        if state.l_trigg != old_state.l_trigg {
+            events.push(Event::Trigger(TriggerEvent::LTrigger(TriggerInput {
+                value: state.l_trigg,
+            })));
+        }
+        if state.r_trigg != old_state.r_trigg {
+            events.push(Event::Trigger(TriggerEvent::RTrigger(TriggerInput {
+                value: state.r_trigg,
+            })));
+        }
+
+        // Accelerometer events
+        if !self.connected {
+            return events;
+        }
+        events.push(Event::Accelerometer(AccelerometerEvent::Accelerometer(
+            AccelerometerInput {
+                x: state.accel_x.to_primitive(),
+                y: state.accel_y.to_primitive(),
+                z: state.accel_z.to_primitive(),
+            },
+        )));
+        events.push(Event::Accelerometer(AccelerometerEvent::Attitude(
+            AccelerometerInput {
+                x: state.pitch.to_primitive(),
+                y: state.yaw.to_primitive(),
+                z: state.roll.to_primitive(),
+            },
+        )));
+
+        events
+    }
+}
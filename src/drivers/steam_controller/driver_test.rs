@@ -0,0 +1,50 @@
+use super::{
+    driver::{is_supported, LeftAxes, PID_WIRELESS},
+    hid_report::{PackedInputDataReport, WirelessState},
+    PID, VID,
+};
+
+#[test]
+fn test_is_supported() {
+    assert!(is_supported(VID, PID, 2));
+    assert!(!is_supported(VID, PID, 0));
+    for interface in 1..=4 {
+        assert!(is_supported(VID, PID_WIRELESS, interface));
+    }
+    assert!(!is_supported(VID, PID_WIRELESS, 0));
+    assert!(!is_supported(VID, 0x1205, 2));
+}
+
+#[test]
+fn test_wireless_state() {
+    assert_eq!(
+        WirelessState::try_from(0x01),
+        Ok(WirelessState::Disconnected)
+    );
+    assert_eq!(WirelessState::try_from(0x02), Ok(WirelessState::Connected));
+    assert!(WirelessState::try_from(0x00).is_err());
+}
+
+#[test]
+fn test_left_axes() {
+    let mut report = PackedInputDataReport::default();
+    report.l_x = 100.into();
+    report.l_y = (-200).into();
+
+    // Stick only
+    let axes = LeftAxes::from_report(&report);
+    assert_eq!(axes.stick, Some((100, -200)));
+    assert_eq!(axes.pad, Some((0, 0)));
+
+    // Trackpad only
+    report.l_pad_touch = true;
+    let axes = LeftAxes::from_report(&report);
+    assert_eq!(axes.stick, Some((0, 0)));
+    assert_eq!(axes.pad, Some((100, -200)));
+
+    // Both are used, so the other axis keeps its last value
+    report.l_pad_and_stick = true;
+    let axes = LeftAxes::from_report(&report);
+    assert_eq!(axes.stick, None);
+    assert_eq!(axes.pad, Some((100, -200)));
+}
@@ -0,0 +1,116 @@
+/// Events that can be emitted by the Steam Controller
+#[derive(Clone, Debug)]
+pub enum Event {
+    Button(ButtonEvent),
+    Accelerometer(AccelerometerEvent),
+    Axis(AxisEvent),
+    Trigger(TriggerEvent),
+}
+
+/// Binary input contain either pressed or unpressed
+#[derive(Clone, Debug)]
+pub struct BinaryInput {
+    pub pressed: bool,
+}
+
+/// Button events represend binary inputs
+#[derive(Clone, Debug)]
+pub enum ButtonEvent {
+    /// A Button
+    A(BinaryInput),
+    /// X Button
+    X(BinaryInput),
+    /// B Button
+    B(BinaryInput),
+    /// Y Button
+    Y(BinaryInput),
+    /// Right arrow (▶) button right of the STEAM button
+    Start(BinaryInput),
+    /// Left arrow (◀) button left of the STEAM button
+    Select(BinaryInput),
+    /// STEAM button in the center of the controller
+    Steam(BinaryInput),
+    /// Left trackpad pressed down
+    DPadDown(BinaryInput),
+    /// Left trackpad pressed up
+    DPadUp(BinaryInput),
+    /// Left trackpad pressed left
+    DPadLeft(BinaryInput),
+    /// Left trackpad pressed right
+    DPadRight(BinaryInput),
+    /// Left shoulder button
+    L1(BinaryInput),
+    /// Binary sensor for left analog trigger
+    L2(BinaryInput),
+    /// Left stick clicked
+    L3(BinaryInput),
+    /// Left grip lever on the back of the controller
+    L4(BinaryInput),
+    /// Right shoulder button
+    R1(BinaryInput),
+    /// Binary sensor for right analog trigger
+    R2(BinaryInput),
+    /// Right grip lever on the back of the controller
+    R4(BinaryInput),
+    /// Binary "touch" sensor for right trackpad
+    RPadTouch(BinaryInput),
+    /// Binary "touch" sensor for left trackpad
+    LPadTouch(BinaryInput),
+    /// Binary "press" sensor for right trackpad
+    RPadPress(BinaryInput),
+    /// Binary "press" sensor for left trackpad
+    LPadPress(BinaryInput),
+}
+
+/// Axis input contain (x, y) coordinates
+#[derive(Clone, Debug)]
+pub struct AxisInput {
+    pub x: i16,
+    pub y: i16,
+}
+
+/// Axis input contain (x, y) coordinates
+#[derive(Clone, Debug)]
+pub struct TouchAxisInput {
+    pub index: u8,
+    pub is_touching: bool,
+    pub x: i16,
+    pub y: i16,
+}
+
+/// Axis events are events that have (x, y) values
+#[derive(Clone, Debug)]
+pub enum AxisEvent {
+    LPad(TouchAxisInput),
+    RPad(TouchAxisInput),
+    LStick(AxisInput),
+}
+
+/// Trigger input contains non-negative integars
+#[derive(Clone, Debug)]
+pub struct TriggerInput {
+    pub value: u8,
+}
+
+/// Trigger events contain positive values indicating how far a trigger is pulled
+#[derive(Clone, Debug)]
+pub enum TriggerEvent {
+    LTrigger(TriggerInput),
+    RTrigger(TriggerInput),
+}
+
+/// AccelerometerInput represents the state of the accelerometer (x, y, z) values
+#[derive(Clone, Debug)]
+pub struct AccelerometerInput {
+    pub x: i16,
+    pub y: i16,
+    pub z: i16,
+}
+
+/// AccelerometerEvent has data from the accelerometer
+#[derive(Clone, Debug)]
+pub enum AccelerometerEvent {
+    Accelerometer(AccelerometerInput),
+    /// Pitch, yaw, roll
+    Attitude(AccelerometerInput),
+}
@@ -0,0 +1,215 @@
+//! Input reports of the original Steam Controller, both wired and through the
+//! wireless dongle. Feature reports are shared with the Steam Deck controller,
+//! see [crate::drivers::steam_deck].
+//! Source: https://github.com/torvalds/linux/blob/master/drivers/hid/hid-steam.c
+use packed_struct::prelude::*;
+
+/// Report type of the controller state input report
+pub const REPORT_TYPE_CONTROLLER_STATE: u8 = 0x01;
+/// Length of the data in the controller state input report
+pub const REPORT_SIZE_CONTROLLER_STATE: u8 = 0x3c;
+/// Report type of the wireless connection input report sent by the dongle
+pub const REPORT_TYPE_WIRELESS: u8 = 0x03;
+/// Report type of the status (battery) input report
+pub const REPORT_TYPE_STATUS: u8 = 0x04;
+
+// Input report axis ranges
+pub const TRIGG_MAX: f64 = 255.0;
+pub const STICK_X_MIN: f64 = -32767.0;
+pub const STICK_X_MAX: f64 = 32767.0;
+pub const STICK_Y_MIN: f64 = -32767.0;
+pub const STICK_Y_MAX: f64 = 32767.0;
+pub const PAD_X_MIN: f64 = -32767.0;
+pub const PAD_X_MAX: f64 = 32767.0;
+pub const PAD_Y_MIN: f64 = -32767.0;
+pub const PAD_Y_MAX: f64 = 32767.0;
+
+/// Feature reports only understood by the wireless dongle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DongleCommand {
+    /// Allow a new controller to pair with the dongle for the given timeout
+    EnablePairing = 0xad,
+    /// Disconnect the controller from the dongle, turning it off
+    DisconnectDevice = 0xb2,
+    /// Store the newly paired controller in the dongle
+    CommitDevice = 0xb3,
+    /// Ask the dongle to send a [REPORT_TYPE_WIRELESS] report with the current
+    /// connection state
+    GetWirelessState = 0xb4,
+}
+
+/// Connection state of a controller in a [REPORT_TYPE_WIRELESS] report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WirelessState {
+    Disconnected = 0x01,
+    Connected = 0x02,
+}
+
+impl TryFrom<u8> for WirelessState {
+    type Error = &'static str;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x01 => Ok(Self::Disconnected),
+            0x02 => Ok(Self::Connected),
+            _ => Err("Invalid wireless state"),
+        }
+    }
+}
+
+/// Controller state input report (0x01). The left stick and left trackpad
+/// share the same axis values, so only one of them is reported at a time.
+/// The 'l_pad_touch' bit is set when the values are from the trackpad.
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering = "msb0", size_bytes = "64")]
+pub struct PackedInputDataReport {
+    // byte 0-3
+    #[packed_field(bytes = "0")]
+    pub major_ver: u8, // Always 0x01
+    #[packed_field(bytes = "1")]
+    pub minor_ver: u8, // Always 0x00
+    #[packed_field(bytes = "2")]
+    pub report_type: u8, // Always 0x01
+    #[packed_field(bytes = "3")]
+    pub report_size: u8, // Always 0x3c
+
+    // byte 4-7
+    #[packed_field(bytes = "4..=7", endian = "lsb")]
+    pub frame: Integer<u32, packed_bits::Bits<32>>,
+
+    // byte 8
+    #[packed_field(bits = "64")]
+    pub a: bool,
+    #[packed_field(bits = "65")]
+    pub x: bool,
+    #[packed_field(bits = "66")]
+    pub b: bool,
+    #[packed_field(bits = "67")]
+    pub y: bool,
+    #[packed_field(bits = "68")]
+    pub l1: bool,
+    #[packed_field(bits = "69")]
+    pub r1: bool,
+    #[packed_field(bits = "70")]
+    pub l2: bool, // Left trigger fully pressed
+    #[packed_field(bits = "71")]
+    pub r2: bool, // Right trigger fully pressed
+
+    // byte 9
+    #[packed_field(bits = "72")]
+    pub l4: bool, // Left back lever
+    #[packed_field(bits = "73")]
+    pub start: bool, // Menu right
+    #[packed_field(bits = "74")]
+    pub steam: bool,
+    #[packed_field(bits = "75")]
+    pub select: bool, // Menu left
+    #[packed_field(bits = "76")]
+    pub down: bool, // Left pad down
+    #[packed_field(bits = "77")]
+    pub left: bool, // Left pad left
+    #[packed_field(bits = "78")]
+    pub right: bool, // Left pad right
+    #[packed_field(bits = "79")]
+    pub up: bool, // Left pad up
+
+    // byte 10
+    #[packed_field(bits = "80")]
+    pub l_pad_and_stick: bool, // Left pad and stick are used at the same time
+    #[packed_field(bits = "81")]
+    pub l3: bool, // Stick clicked
+    #[packed_field(bits = "83")]
+    pub r_pad_touch: bool,
+    #[packed_field(bits = "84")]
+    pub l_pad_touch: bool,
+    #[packed_field(bits = "85")]
+    pub r_pad_press: bool,
+    #[packed_field(bits = "86")]
+    pub l_pad_press: bool,
+    #[packed_field(bits = "87")]
+    pub r4: bool, // Right back lever
+
+    // byte 11-12
+    #[packed_field(bytes = "11")]
+    pub l_trigg: u8,
+    #[packed_field(bytes = "12")]
+    pub r_trigg: u8,
+
+    // byte 16-23
+    #[packed_field(bytes = "16..=17", endian = "lsb")]
+    pub l_x: Integer<i16, packed_bits::Bits<16>>, // Stick or left pad
+    #[packed_field(bytes = "18..=19", endian = "lsb")]
+    pub l_y: Integer<i16, packed_bits::Bits<16>>, // Stick or left pad
+    #[packed_field(bytes = "20..=21", endian = "lsb")]
+    pub r_pad_x: Integer<i16, packed_bits::Bits<16>>,
+    #[packed_field(bytes = "22..=23", endian = "lsb")]
+    pub r_pad_y: Integer<i16, packed_bits::Bits<16>>,
+
+    // byte 28-39
+    #[packed_field(bytes = "28..=29", endian = "lsb")]
+    pub accel_x: Integer<i16, packed_bits::Bits<16>>,
+    #[packed_field(bytes = "30..=31", endian = "lsb")]
+    pub accel_y: Integer<i16, packed_bits::Bits<16>>,
+    #[packed_field(bytes = "32..=33", endian = "lsb")]
+    pub accel_z: Integer<i16, packed_bits::Bits<16>>,
+    #[packed_field(bytes = "34..=35", endian = "lsb")]
+    pub pitch: Integer<i16, packed_bits::Bits<16>>,
+    #[packed_field(bytes = "36..=37", endian = "lsb")]
+    pub yaw: Integer<i16, packed_bits::Bits<16>>,
+    #[packed_field(bytes = "38..=39", endian = "lsb")]
+    pub roll: Integer<i16, packed_bits::Bits<16>>,
+}
+
+impl PackedInputDataReport {
+    /// Return a new empty input data report
+    pub fn new() -> Self {
+        Self {
+            major_ver: 0x01,
+            minor_ver: 0x00,
+            report_type: REPORT_TYPE_CONTROLLER_STATE,
+            report_size: REPORT_SIZE_CONTROLLER_STATE,
+            frame: Integer::from_primitive(0),
+            a: false,
+            x: false,
+            b: false,
+            y: false,
+            l1: false,
+            r1: false,
+            l2: false,
+            r2: false,
+            l4: false,
+            start: false,
+            steam: false,
+            select: false,
+            down: false,
+            left: false,
+            right: false,
+            up: false,
+            l_pad_and_stick: false,
+            l3: false,
+            r_pad_touch: false,
+            l_pad_touch: false,
+            r_pad_press: false,
+            l_pad_press: false,
+            r4: false,
+            l_trigg: 0,
+            r_trigg: 0,
+            l_x: Integer::from_primitive(0),
+            l_y: Integer::from_primitive(0),
+            r_pad_x: Integer::from_primitive(0),
+            r_pad_y: Integer::from_primitive(0),
+            accel_x: Integer::from_primitive(0),
+            accel_y: Integer::from_primitive(0),
+            accel_z: Integer::from_primitive(0),
+            pitch: Integer::from_primitive(0),
+            yaw: Integer::from_primitive(0),
+            roll: Integer::from_primitive(0),
+        }
+    }
+}
+
+impl Default for PackedInputDataReport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
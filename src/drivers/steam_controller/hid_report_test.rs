@@ -0,0 +1,30 @@
+use packed_struct::{types::SizedInteger, PackedStruct};
+
+use super::hid_report::PackedInputDataReport;
+
+#[test]
+fn test_steam_controller_input_report() {
+    let mut report = PackedInputDataReport::default();
+    report.a = true;
+    report.steam = true;
+    report.r4 = true;
+    report.l_trigg = 0xff;
+    report.l_x = 0x1234.into();
+    report.r_pad_y = (-2).into();
+
+    let data = report.pack().unwrap();
+    assert_eq!(data[0..4], [0x01, 0x00, 0x01, 0x3c]);
+    assert_eq!(data[8], 0x80, "A should be the highest bit of byte 8");
+    assert_eq!(data[9], 0x20, "Steam should be bit 5 of byte 9");
+    assert_eq!(
+        data[10], 0x01,
+        "Right back lever should be bit 0 of byte 10"
+    );
+    assert_eq!(data[11], 0xff);
+    assert_eq!(data[16..18], [0x34, 0x12]);
+    assert_eq!(data[22..24], [0xfe, 0xff]);
+
+    let unpacked = PackedInputDataReport::unpack(&data).unwrap();
+    assert_eq!(unpacked, report);
+    assert_eq!(unpacked.l_x.to_primitive(), 0x1234);
+}
@@ -0,0 +1,12 @@
+pub mod driver;
+#[cfg(test)]
+pub mod driver_test;
+pub mod event;
+pub mod hid_report;
+#[cfg(test)]
+pub mod hid_report_test;
+
+/// Vendor id of the wired Steam Controller
+pub const VID: u16 = 0x28de;
+/// Product id of the wired Steam Controller
+pub const PID: u16 = 0x1102;
@@ -1,6 +1,6 @@
-use std::{error::Error, ffi::CString};
+use std::{collections::HashMap, error::Error, ffi::CString};
 
-use crate::drivers::steam_deck::hid_report::PackedInputDataReport;
+use crate::drivers::{firmware::FirmwareInfo, steam_deck::hid_report::PackedInputDataReport};
 use hidapi::HidDevice;
 use packed_struct::{
     types::{Integer, SizedInteger},
@@ -12,7 +12,10 @@ use super::{
         AccelerometerEvent, AccelerometerInput, AxisEvent, AxisInput, BinaryInput, ButtonEvent,
         Event, TouchAxisInput, TriggerEvent, TriggerInput,
     },
-    hid_report::{PackedMappingsReport, PackedRumbleReport, Register, ReportType, TrackpadMode},
+    hid_report::{
+        Attribute, PackedMappingsReport, PackedRumbleReport, Register, ReportType, StringAttribute,
+        TrackpadMode,
+    },
 };
 
 /// Vendor ID
@@ -155,6 +158,51 @@ impl Driver {
         Ok(())
     }
 
+    /// Send the given feature report request to the device and return the
+    /// feature report the device replies with.
+    fn feature_request(&self, request: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        // The first byte is the report id, which is not used by this device
+        let mut buf = [0; PACKET_SIZE + 1];
+        buf[1..=request.len()].copy_from_slice(request);
+        self.device.send_feature_report(&buf)?;
+
+        let mut buf = [0; PACKET_SIZE + 1];
+        let bytes_read = self.device.get_feature_report(&mut buf)?;
+        let reply = buf[1..bytes_read.max(1)].to_vec();
+        if reply.first() != request.first() {
+            return Err("Unexpected feature report reply from device".into());
+        }
+
+        Ok(reply)
+    }
+
+    /// Read the firmware build time, board revision, and serial number of the
+    /// controller.
+    pub fn get_firmware_info(&self) -> Result<FirmwareInfo, Box<dyn Error + Send + Sync>> {
+        let reply = self.feature_request(&[ReportType::GetAttrib as u8])?;
+        let attributes = parse_attributes_report(&reply)?;
+
+        let request = [
+            ReportType::GetSerial as u8,
+            0x15,
+            StringAttribute::UnitSerial as u8,
+        ];
+        let reply = self.feature_request(&request)?;
+        let serial_number = parse_serial_report(&reply)?;
+
+        let firmware_build_time = attributes.get(&(Attribute::FirmwareBuildTime as u8));
+        let board_revision = attributes.get(&(Attribute::BoardRevision as u8));
+        Ok(FirmwareInfo {
+            firmware_version: firmware_build_time
+                .map(|time| format!("0x{time:08x}"))
+                .unwrap_or_default(),
+            serial_number,
+            board_revision: board_revision
+                .map(|revision| revision.to_string())
+                .unwrap_or_default(),
+        })
+    }
+
     /// Strangely, the only known method to disable keyboard emulation only does
     /// so for a few seconds, whereas disabling the mouse is permanent until
     /// re-enabled.  This means we have to run a separate thread which wakes up
@@ -430,3 +478,40 @@ impl Driver {
         events
     }
 }
+
+/// Parse the reply of a [ReportType::GetAttrib] report into a map of
+/// attribute ids to their values.
+pub fn parse_attributes_report(
+    buf: &[u8],
+) -> Result<HashMap<u8, u32>, Box<dyn Error + Send + Sync>> {
+    if buf.len() < 2 || buf[0] != ReportType::GetAttrib as u8 {
+        return Err("Invalid attributes report".into());
+    }
+    // Each attribute is an 8-bit id followed by a 32-bit little endian value
+    let len = (buf[1] as usize).min(buf.len() - 2);
+    let attributes = buf[2..2 + len]
+        .chunks_exact(5)
+        .map(|chunk| {
+            let value = u32::from_le_bytes([chunk[1], chunk[2], chunk[3], chunk[4]]);
+            (chunk[0], value)
+        })
+        .collect();
+    Ok(attributes)
+}
+
+/// Parse the reply of a [ReportType::GetSerial] report into the serial
+/// number string.
+pub fn parse_serial_report(buf: &[u8]) -> Result<String, Box<dyn Error + Send + Sync>> {
+    if buf.len() < 3 || buf[0] != ReportType::GetSerial as u8 {
+        return Err("Invalid serial number report".into());
+    }
+    // The string starts after the length and attribute bytes and is null
+    // terminated.
+    let end = (3 + buf[1] as usize).min(buf.len());
+    let data = &buf[3..end];
+    let end = data
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(data.len());
+    Ok(String::from_utf8_lossy(&data[..end]).to_string())
+}
@@ -88,6 +88,28 @@ impl TryFrom<u8> for ReportType {
     }
 }
 
+/// Attributes returned by the [ReportType::GetAttrib] report
+pub enum Attribute {
+    UniqueId = 0x00,
+    ProductId = 0x01,
+    ProductRevision = 0x02,
+    Capabilities = 0x03,
+    FirmwareBuildTime = 0x04,
+    RadioFirmwareBuildTime = 0x05,
+    RadioDeviceId0 = 0x06,
+    RadioDeviceId1 = 0x07,
+    DongleFirmwareBuildTime = 0x08,
+    BoardRevision = 0x09,
+    BootloaderBuildTime = 0x0a,
+    ConnectionIntervalUs = 0x0b,
+}
+
+/// String attributes returned by the [ReportType::GetSerial] report
+pub enum StringAttribute {
+    BoardSerial = 0x00,
+    UnitSerial = 0x01,
+}
+
 /// Register settings
 pub enum Register {
     MouseSensitivity = 0x00,
@@ -21,6 +21,8 @@ pub enum Capability {
     Keyboard(Keyboard),
     Touchpad(Touchpad),
     Touchscreen(Touch),
+    /// Switches that report the state of the device, like whether it is docked
+    Switch(Switch),
 }
 
 impl fmt::Display for Capability {
@@ -35,6 +37,7 @@ impl fmt::Display for Capability {
             Capability::DBus(_) => write!(f, "DBus"),
             Capability::Touchpad(_) => write!(f, "Touchpad"),
             Capability::Touchscreen(_) => write!(f, "Touchscreen"),
+            Capability::Switch(_) => write!(f, "Switch"),
         }
     }
 }
@@ -69,6 +72,9 @@ impl FromStr for Capability {
             "Touchscreen" => Ok(Capability::Touchscreen(Touch::from_str(
                 parts.join(":").as_str(),
             )?)),
+            "Switch" => Ok(Capability::Switch(Switch::from_str(
+                parts.join(":").as_str(),
+            )?)),
             _ => Err(()),
         }
     }
@@ -115,6 +121,17 @@ impl From<CapabilityConfig> for Capability {
                 return Capability::Gamepad(Gamepad::Trigger(trigger));
             }
 
+            // Dial
+            if let Some(dial_string) = gamepad.dial.as_ref() {
+                let dial = GamepadDial::from_str(dial_string);
+                if dial.is_err() {
+                    log::error!("Invalid or unimplemented dial: {dial_string}");
+                    return Capability::NotImplemented;
+                }
+                let dial = dial.unwrap();
+                return Capability::Gamepad(Gamepad::Dial(dial));
+            }
+
             // Gyro
             if let Some(_gyro_capability) = gamepad.gyro.as_ref() {
                 unimplemented!();
@@ -134,6 +151,13 @@ impl From<CapabilityConfig> for Capability {
             return Capability::Keyboard(key);
         }
 
+        // Keysyms must be resolved using the keyboard layout before they can
+        // be converted into a capability.
+        if let Some(keysym) = value.keysym.as_ref() {
+            log::error!("Unresolved keysym: {keysym}");
+            return Capability::NotImplemented;
+        }
+
         // Mouse
         if let Some(mouse) = value.mouse.as_ref() {
             // Motion
@@ -212,6 +236,17 @@ impl From<CapabilityConfig> for Capability {
             }
         }
 
+        // Switch
+        if let Some(switch_string) = value.switch.as_ref() {
+            let switch = Switch::from_str(switch_string);
+            if switch.is_err() {
+                log::error!("Invalid or unimplemented switch: {switch_string}");
+                return Capability::NotImplemented;
+            }
+            let switch = switch.unwrap();
+            return Capability::Switch(switch);
+        }
+
         Capability::NotImplemented
     }
 }
@@ -233,6 +268,9 @@ pub enum Gamepad {
     /// Gyro events measure the angular velocity of a device measured
     /// with (x, y, z) values normalized to degrees per second.
     Gyro,
+    /// Gamepad Dial typically uses a single signed value that represents how
+    /// many detents a dial or scroll wheel was rotated since the last event
+    Dial(GamepadDial),
 }
 
 impl fmt::Display for Gamepad {
@@ -243,6 +281,7 @@ impl fmt::Display for Gamepad {
             Gamepad::Trigger(_) => write!(f, "Trigger"),
             Gamepad::Accelerometer => write!(f, "Accelerometer"),
             Gamepad::Gyro => write!(f, "Gyro"),
+            Gamepad::Dial(_) => write!(f, "Dial"),
         }
     }
 }
@@ -266,6 +305,9 @@ impl FromStr for Gamepad {
             )?)),
             "Accelerometer" => Ok(Gamepad::Accelerometer),
             "Gyro" => Ok(Gamepad::Gyro),
+            "Dial" => Ok(Gamepad::Dial(GamepadDial::from_str(
+                parts.join(":").as_str(),
+            )?)),
             _ => Err(()),
         }
     }
@@ -390,6 +432,10 @@ pub enum GamepadButton {
     Screenshot,
     /// Dedicated mute button
     Mute,
+    /// Headphone jack detect, "pressed" while headphones are plugged in
+    HeadphoneInsert,
+    /// Microphone jack detect, "pressed" while a microphone is plugged in
+    MicrophoneInsert,
     /// Directional Pad up
     DPadUp,
     /// Directional Pad down
@@ -451,6 +497,8 @@ impl fmt::Display for GamepadButton {
             GamepadButton::LeftTop => write!(f, "LeftTop"),
             GamepadButton::LeftTrigger => write!(f, "LeftTrigger"),
             GamepadButton::Mute => write!(f, "Mute"),
+            GamepadButton::HeadphoneInsert => write!(f, "HeadphoneInsert"),
+            GamepadButton::MicrophoneInsert => write!(f, "MicrophoneInsert"),
             GamepadButton::North => write!(f, "North"),
             GamepadButton::QuickAccess => write!(f, "QuickAccess"),
             GamepadButton::QuickAccess2 => write!(f, "QuickAccess2"),
@@ -492,6 +540,8 @@ impl FromStr for GamepadButton {
             "LeftTop" => Ok(GamepadButton::LeftTop),
             "LeftTrigger" => Ok(GamepadButton::LeftTrigger),
             "Mute" => Ok(GamepadButton::Mute),
+            "HeadphoneInsert" => Ok(GamepadButton::HeadphoneInsert),
+            "MicrophoneInsert" => Ok(GamepadButton::MicrophoneInsert),
             "North" => Ok(GamepadButton::North),
             "QuickAccess" => Ok(GamepadButton::QuickAccess),
             "QuickAccess2" => Ok(GamepadButton::QuickAccess2),
@@ -552,6 +602,33 @@ impl FromStr for GamepadAxis {
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GamepadDial {
+    LeftStickDial,
+    RightStickDial,
+}
+
+impl fmt::Display for GamepadDial {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GamepadDial::LeftStickDial => write!(f, "LeftStickDial"),
+            GamepadDial::RightStickDial => write!(f, "RightStickDial"),
+        }
+    }
+}
+
+impl FromStr for GamepadDial {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "LeftStickDial" => Ok(GamepadDial::LeftStickDial),
+            "RightStickDial" => Ok(GamepadDial::RightStickDial),
+            _ => Err(()),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum GamepadTrigger {
     LeftTrigger,
@@ -703,6 +780,7 @@ pub enum Keyboard {
     KeyMinus,
     KeyMuhenkan,
     KeyMute,
+    KeyMicMute,
     KeyN,
     KeyNextSong,
     KeyNumlock,
@@ -870,6 +948,7 @@ impl fmt::Display for Keyboard {
             Keyboard::KeyMinus => write!(f, "KeyMinus"),
             Keyboard::KeyMuhenkan => write!(f, "KeyMuhenkan"),
             Keyboard::KeyMute => write!(f, "KeyMute"),
+            Keyboard::KeyMicMute => write!(f, "KeyMicMute"),
             Keyboard::KeyN => write!(f, "KeyN"),
             Keyboard::KeyNextSong => write!(f, "KeyNextSong"),
             Keyboard::KeyNumlock => write!(f, "KeyNumlock"),
@@ -1041,6 +1120,7 @@ impl FromStr for Keyboard {
             "KeyMinus" => Ok(Keyboard::KeyMinus),
             "KeyMuhenkan" => Ok(Keyboard::KeyMuhenkan),
             "KeyMute" => Ok(Keyboard::KeyMute),
+            "KeyMicMute" => Ok(Keyboard::KeyMicMute),
             "KeyN" => Ok(Keyboard::KeyN),
             "KeyNextSong" => Ok(Keyboard::KeyNextSong),
             "KeyNumlock" => Ok(Keyboard::KeyNumlock),
@@ -1196,3 +1276,27 @@ impl FromStr for TouchButton {
         }
     }
 }
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Switch {
+    /// The device is connected to a dock
+    Docked,
+}
+
+impl fmt::Display for Switch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Switch::Docked => write!(f, "Docked"),
+        }
+    }
+}
+
+impl FromStr for Switch {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Docked" => Ok(Switch::Docked),
+            _ => Err(()),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+//! Conditions under which events of source devices configured with
+//! 'raw_passthrough' may bypass the composite device and be written to
+//! target devices directly from the source device thread.
+
+/// State of a composite device that decides whether source events may bypass
+/// it
+#[derive(Debug, Default)]
+pub struct BypassConditions {
+    /// Input is intercepted
+    pub intercepted: bool,
+    /// The composite device is inhibited
+    pub inhibited: bool,
+    /// A source device is being calibrated
+    pub calibrating: bool,
+    /// Events are cloned to every capable target device
+    pub clone_output: bool,
+    /// A debug target device records emitted events
+    pub debugging: bool,
+    /// Usage statistics of source inputs are collected
+    pub collecting_statistics: bool,
+}
+
+impl BypassConditions {
+    /// Returns true if nothing in the composite device would alter, consume
+    /// or count source events, so they can bypass it. Bypassed events never
+    /// reach the composite device, so bypass is disabled while input
+    /// statistics are collected to count every event.
+    pub fn allows_bypass(&self) -> bool {
+        !(self.intercepted
+            || self.inhibited
+            || self.calibrating
+            || self.clone_output
+            || self.debugging
+            || self.collecting_statistics)
+    }
+}
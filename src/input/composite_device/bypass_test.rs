@@ -0,0 +1,41 @@
+use super::bypass::BypassConditions;
+
+#[test]
+fn test_bypass_conditions() {
+    assert!(BypassConditions::default().allows_bypass());
+
+    let blocking = [
+        BypassConditions {
+            intercepted: true,
+            ..Default::default()
+        },
+        BypassConditions {
+            inhibited: true,
+            ..Default::default()
+        },
+        BypassConditions {
+            calibrating: true,
+            ..Default::default()
+        },
+        BypassConditions {
+            clone_output: true,
+            ..Default::default()
+        },
+        BypassConditions {
+            debugging: true,
+            ..Default::default()
+        },
+    ];
+    for conditions in blocking {
+        assert!(!conditions.allows_bypass(), "{conditions:?}");
+    }
+}
+
+#[test]
+fn test_bypass_disabled_while_collecting_statistics() {
+    let conditions = BypassConditions {
+        collecting_statistics: true,
+        ..Default::default()
+    };
+    assert!(!conditions.allows_bypass());
+}
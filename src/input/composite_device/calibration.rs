@@ -0,0 +1,433 @@
+//! Calibration of gamepad sticks and IMUs for composite devices
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::path::{get_calibration_path, get_controller_calibration_path},
+    input::{
+        capability::{Capability, Gamepad, GamepadAxis},
+        event::{native::NativeEvent, value::InputValue},
+    },
+    udev::device::UdevDevice,
+};
+
+/// Stick values with a magnitude below this threshold are considered to be
+/// at rest when measuring the center of a stick.
+const STICK_REST_THRESHOLD: f64 = 0.25;
+
+/// Number of consecutive gyro samples in each window that is checked for
+/// motion when measuring the gyro bias.
+const GYRO_WINDOW_SIZE: usize = 50;
+
+/// Gyro sample windows whose variance is within this factor of the stillest
+/// window are considered to be at rest. Raw gyro units differ between
+/// devices, so motion is detected relative to the noise of the device.
+const GYRO_STILL_FACTOR: f64 = 2.0;
+
+/// Persisted calibration results of a composite device
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Calibration {
+    pub left_stick: Option<StickCalibration>,
+    pub right_stick: Option<StickCalibration>,
+    pub gyro: Option<GyroCalibration>,
+}
+
+/// Measured center and extents of a stick. Values are in the normalized
+/// stick range of -1.0 to 1.0.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct StickCalibration {
+    pub center_x: f64,
+    pub center_y: f64,
+    pub min_x: f64,
+    pub max_x: f64,
+    pub min_y: f64,
+    pub max_y: f64,
+}
+
+impl StickCalibration {
+    /// Apply the calibration to the given stick value
+    fn apply(&self, x: Option<f64>, y: Option<f64>) -> (Option<f64>, Option<f64>) {
+        let x = x.map(|x| scale(x, self.center_x, self.min_x, self.max_x));
+        let y = y.map(|y| scale(y, self.center_y, self.min_y, self.max_y));
+        (x, y)
+    }
+}
+
+/// Measured resting bias of a gyro
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct GyroCalibration {
+    pub bias_x: f64,
+    pub bias_y: f64,
+    pub bias_z: f64,
+}
+
+impl Calibration {
+    /// Returns the path to the calibration file for the given device id
+    fn path(device_id: &str) -> PathBuf {
+        get_calibration_path().join(format!("{device_id}.yaml"))
+    }
+
+    /// Returns the path to the calibration overlay for the given controller id
+    fn controller_path(controller_id: &str) -> PathBuf {
+        get_controller_calibration_path().join(format!("{controller_id}.yaml"))
+    }
+
+    /// Load the calibration of the given device id. Returns [None] if the
+    /// device has not been calibrated.
+    pub fn load(device_id: &str) -> Result<Option<Self>, Box<dyn Error>> {
+        Calibration::load_from(&Calibration::path(device_id))
+    }
+
+    /// Save the calibration for the given device id
+    pub fn save(&self, device_id: &str) -> Result<(), Box<dyn Error>> {
+        self.save_to(&Calibration::path(device_id))
+    }
+
+    /// Load the calibration overlay of the given controller id. Returns
+    /// [None] if the controller has not been calibrated.
+    pub fn load_controller(controller_id: &str) -> Result<Option<Self>, Box<dyn Error>> {
+        Calibration::load_from(&Calibration::controller_path(controller_id))
+    }
+
+    /// Save the calibration as an overlay for the given controller id
+    pub fn save_controller(&self, controller_id: &str) -> Result<(), Box<dyn Error>> {
+        self.save_to(&Calibration::controller_path(controller_id))
+    }
+
+    fn load_from(path: &Path) -> Result<Option<Self>, Box<dyn Error>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let file = fs::File::open(path)?;
+        let calibration = serde_yaml::from_reader(file)?;
+        Ok(Some(calibration))
+    }
+
+    fn save_to(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let data = serde_yaml::to_string(self)?;
+        fs::write(path, data)?;
+        Ok(())
+    }
+
+    /// Returns this calibration with any stick or gyro calibration defined in
+    /// the given overlay taking precedence
+    pub fn overlay(&self, overlay: &Calibration) -> Calibration {
+        Calibration {
+            left_stick: overlay
+                .left_stick
+                .clone()
+                .or_else(|| self.left_stick.clone()),
+            right_stick: overlay
+                .right_stick
+                .clone()
+                .or_else(|| self.right_stick.clone()),
+            gyro: overlay.gyro.clone().or_else(|| self.gyro.clone()),
+        }
+    }
+
+    /// Apply the calibration to the given event
+    pub fn apply(&self, event: &mut NativeEvent) {
+        let cap = event.as_capability();
+        let value = event.get_value();
+        let value = match (cap, value) {
+            (Capability::Gamepad(Gamepad::Axis(axis)), InputValue::Vector2 { x, y }) => {
+                let calibration = match axis {
+                    GamepadAxis::LeftStick => self.left_stick.as_ref(),
+                    GamepadAxis::RightStick => self.right_stick.as_ref(),
+                    _ => None,
+                };
+                let Some(calibration) = calibration else {
+                    return;
+                };
+                let (x, y) = calibration.apply(x, y);
+                InputValue::Vector2 { x, y }
+            }
+            (Capability::Gamepad(Gamepad::Gyro), InputValue::Vector3 { x, y, z }) => {
+                let Some(calibration) = self.gyro.as_ref() else {
+                    return;
+                };
+                InputValue::Vector3 {
+                    x: x.map(|x| x - calibration.bias_x),
+                    y: y.map(|y| y - calibration.bias_y),
+                    z: z.map(|z| z - calibration.bias_z),
+                }
+            }
+            _ => return,
+        };
+        event.set_value(value);
+    }
+}
+
+/// Returns a stable identifier for the given device that is used to name its
+/// calibration file. E.g. "054c-0ce6" or "054c-0ce6-a0ab51c1d2e3"
+pub fn get_device_id(device: &UdevDevice) -> String {
+    let mut id = format!("{:04x}-{:04x}", device.id_vendor(), device.id_product());
+    let uniq = device.uniq();
+    let uniq: String = uniq
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase();
+    if !uniq.is_empty() {
+        id = format!("{id}-{uniq}");
+    }
+    id
+}
+
+/// Returns an identifier for the given device based on its unique identifier
+/// (e.g. a serial number or MAC address) that is used to name its calibration
+/// overlay. Returns [None] if the device does not report a unique identifier.
+/// E.g. "a0ab51c1d2e3"
+pub fn get_controller_id(device: &UdevDevice) -> Option<String> {
+    let uniq: String = device
+        .uniq()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase();
+    if uniq.is_empty() {
+        return None;
+    }
+    Some(uniq)
+}
+
+/// The [Calibrator] collects raw source events while a calibration is in
+/// progress. The device should be at rest when calibration starts, then
+/// each stick should be rotated through its full range of motion. The gyro
+/// bias is only measured while the sticks are at rest and the device is held
+/// still.
+#[derive(Debug, Default)]
+pub struct Calibrator {
+    left_stick: StickSamples,
+    right_stick: StickSamples,
+    gyro: GyroSamples,
+}
+
+impl Calibrator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the given raw event. Returns true if the event was recorded as
+    /// a calibration sample.
+    pub fn record(&mut self, event: &NativeEvent) -> bool {
+        match (event.as_capability(), event.get_value()) {
+            (Capability::Gamepad(Gamepad::Axis(axis)), InputValue::Vector2 { x, y }) => {
+                match axis {
+                    GamepadAxis::LeftStick => self.left_stick.record(x, y),
+                    GamepadAxis::RightStick => self.right_stick.record(x, y),
+                    _ => return false,
+                }
+            }
+            (Capability::Gamepad(Gamepad::Gyro), InputValue::Vector3 { x, y, z }) => {
+                // Moving the sticks also moves the device
+                if self.left_stick.is_at_rest() && self.right_stick.is_at_rest() {
+                    self.gyro.record(x, y, z);
+                } else {
+                    self.gyro.interrupt();
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Finish the calibration and return the results. Any stick or gyro that
+    /// did not report events keeps its given previous calibration.
+    pub fn finish(self, previous: &Calibration) -> Calibration {
+        Calibration {
+            left_stick: self
+                .left_stick
+                .calibration()
+                .or_else(|| previous.left_stick.clone()),
+            right_stick: self
+                .right_stick
+                .calibration()
+                .or_else(|| previous.right_stick.clone()),
+            gyro: self.gyro.calibration().or_else(|| previous.gyro.clone()),
+        }
+    }
+}
+
+/// Samples collected from a single stick
+#[derive(Debug, Default)]
+struct StickSamples {
+    /// Last known position of the stick. Stick events may only contain the
+    /// axis that changed.
+    position: (f64, f64),
+    rest_sum: (f64, f64),
+    rest_count: usize,
+    min: (f64, f64),
+    max: (f64, f64),
+    has_samples: bool,
+}
+
+impl StickSamples {
+    fn record(&mut self, x: Option<f64>, y: Option<f64>) {
+        if let Some(x) = x {
+            self.position.0 = x;
+        }
+        if let Some(y) = y {
+            self.position.1 = y;
+        }
+        let (x, y) = self.position;
+        if !self.has_samples {
+            self.min = self.position;
+            self.max = self.position;
+            self.has_samples = true;
+        }
+        self.min = (self.min.0.min(x), self.min.1.min(y));
+        self.max = (self.max.0.max(x), self.max.1.max(y));
+        if x.hypot(y) < STICK_REST_THRESHOLD {
+            self.rest_sum = (self.rest_sum.0 + x, self.rest_sum.1 + y);
+            self.rest_count += 1;
+        }
+    }
+
+    /// Returns true if the last known position of the stick is at rest
+    fn is_at_rest(&self) -> bool {
+        self.position.0.hypot(self.position.1) < STICK_REST_THRESHOLD
+    }
+
+    fn calibration(&self) -> Option<StickCalibration> {
+        if !self.has_samples {
+            return None;
+        }
+        let (center_x, center_y) = if self.rest_count > 0 {
+            let count = self.rest_count as f64;
+            (self.rest_sum.0 / count, self.rest_sum.1 / count)
+        } else {
+            (0.0, 0.0)
+        };
+        Some(StickCalibration {
+            center_x,
+            center_y,
+            min_x: self.min.0,
+            max_x: self.max.0,
+            min_y: self.min.1,
+            max_y: self.max.1,
+        })
+    }
+}
+
+/// Window of consecutive gyro samples
+#[derive(Debug, Default, Clone)]
+struct GyroWindow {
+    sum: [f64; 3],
+    sum_squares: [f64; 3],
+    count: usize,
+}
+
+impl GyroWindow {
+    fn record(&mut self, value: [f64; 3]) {
+        for (i, value) in value.into_iter().enumerate() {
+            self.sum[i] += value;
+            self.sum_squares[i] += value * value;
+        }
+        self.count += 1;
+    }
+
+    fn mean(&self) -> [f64; 3] {
+        let count = self.count as f64;
+        self.sum.map(|sum| sum / count)
+    }
+
+    /// Returns the sum of the variances of all axes
+    fn variance(&self) -> f64 {
+        let count = self.count as f64;
+        let mean = self.mean();
+        (0..3)
+            .map(|i| (self.sum_squares[i] / count - mean[i] * mean[i]).max(0.0))
+            .sum()
+    }
+}
+
+/// Samples collected from a gyro, split into windows so samples taken while
+/// the device was moving can be rejected.
+#[derive(Debug, Default)]
+struct GyroSamples {
+    current: GyroWindow,
+    windows: Vec<GyroWindow>,
+}
+
+impl GyroSamples {
+    fn record(&mut self, x: Option<f64>, y: Option<f64>, z: Option<f64>) {
+        let value = [
+            x.unwrap_or_default(),
+            y.unwrap_or_default(),
+            z.unwrap_or_default(),
+        ];
+        self.current.record(value);
+        if self.current.count >= GYRO_WINDOW_SIZE {
+            self.windows.push(std::mem::take(&mut self.current));
+        }
+    }
+
+    /// Discard the current incomplete window because the device may have
+    /// moved
+    fn interrupt(&mut self) {
+        self.current = GyroWindow::default();
+    }
+
+    fn calibration(&self) -> Option<GyroCalibration> {
+        // Only use the incomplete window if no window was completed
+        let windows: Vec<&GyroWindow> = if self.windows.is_empty() {
+            (self.current.count > 0)
+                .then_some(&self.current)
+                .into_iter()
+                .collect()
+        } else {
+            self.windows.iter().collect()
+        };
+
+        // Average the windows that are about as still as the stillest one
+        let min_variance = windows
+            .iter()
+            .map(|window| window.variance())
+            .min_by(f64::total_cmp)?;
+        let max_variance = min_variance * GYRO_STILL_FACTOR + f64::EPSILON;
+        let mut sum = [0.0; 3];
+        let mut count = 0;
+        for window in windows {
+            if window.variance() > max_variance {
+                continue;
+            }
+            for (i, value) in window.sum.into_iter().enumerate() {
+                sum[i] += value;
+            }
+            count += window.count;
+        }
+        let count = count as f64;
+        Some(GyroCalibration {
+            bias_x: sum[0] / count,
+            bias_y: sum[1] / count,
+            bias_z: sum[2] / count,
+        })
+    }
+}
+
+/// Scale the given value so the center maps to 0.0 and the measured extents
+/// map to -1.0 and 1.0.
+fn scale(value: f64, center: f64, min: f64, max: f64) -> f64 {
+    let value = value - center;
+    let extent = if value >= 0.0 {
+        max - center
+    } else {
+        center - min
+    };
+    if extent <= f64::EPSILON {
+        return value.clamp(-1.0, 1.0);
+    }
+    (value / extent).clamp(-1.0, 1.0)
+}
@@ -0,0 +1,125 @@
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadAxis},
+    composite_device::calibration::{Calibration, Calibrator, StickCalibration},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+fn stick_event(x: f64, y: f64) -> NativeEvent {
+    NativeEvent::new(
+        Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+        InputValue::Vector2 {
+            x: Some(x),
+            y: Some(y),
+        },
+    )
+}
+
+#[test]
+fn test_stick_calibration() {
+    // Drifting stick that rests at (0.1, -0.1) and only reaches 0.8
+    let mut calibrator = Calibrator::new();
+    for (x, y) in [
+        (0.1, -0.1),
+        (0.8, -0.1),
+        (-0.6, -0.1),
+        (0.1, 0.8),
+        (0.1, -1.0),
+    ] {
+        calibrator.record(&stick_event(x, y));
+    }
+    let calibration = calibrator.finish(&Calibration::default());
+    assert!(calibration.right_stick.is_none());
+    assert!(calibration.gyro.is_none());
+
+    let mut event = stick_event(0.1, -0.1);
+    calibration.apply(&mut event);
+    let InputValue::Vector2 { x, y } = event.get_value() else {
+        panic!("Expected Vector2 value");
+    };
+    assert!(x.unwrap().abs() < 1e-9);
+    assert!(y.unwrap().abs() < 1e-9);
+
+    let mut event = stick_event(0.8, -1.0);
+    calibration.apply(&mut event);
+    let InputValue::Vector2 { x, y } = event.get_value() else {
+        panic!("Expected Vector2 value");
+    };
+    assert!((x.unwrap() - 1.0).abs() < 1e-9);
+    assert!((y.unwrap() + 1.0).abs() < 1e-9);
+}
+
+fn gyro_event(x: f64, y: f64, z: f64) -> NativeEvent {
+    NativeEvent::new(
+        Capability::Gamepad(Gamepad::Gyro),
+        InputValue::Vector3 {
+            x: Some(x),
+            y: Some(y),
+            z: Some(z),
+        },
+    )
+}
+
+#[test]
+fn test_gyro_calibration() {
+    // Gyro at rest with a bias of (1.0, -2.0, 0.5) and some noise
+    let mut calibrator = Calibrator::new();
+    for i in 0..200 {
+        let noise = if i % 2 == 0 { 0.1 } else { -0.1 };
+        calibrator.record(&gyro_event(1.0 + noise, -2.0 - noise, 0.5 + noise));
+    }
+
+    // Samples taken while the device is moving should be rejected
+    for i in 0..200 {
+        let motion = (i as f64 / 10.0).sin() * 50.0;
+        calibrator.record(&gyro_event(1.0 + motion, -2.0 + motion, 0.5 - motion));
+    }
+
+    // Samples taken while a stick is moved should be ignored
+    calibrator.record(&stick_event(0.9, 0.0));
+    for _ in 0..200 {
+        calibrator.record(&gyro_event(100.0, 100.0, 100.0));
+    }
+    calibrator.record(&stick_event(0.0, 0.0));
+
+    let calibration = calibrator.finish(&Calibration::default());
+    let gyro = calibration.gyro.as_ref().unwrap();
+    assert!((gyro.bias_x - 1.0).abs() < 1e-9);
+    assert!((gyro.bias_y + 2.0).abs() < 1e-9);
+    assert!((gyro.bias_z - 0.5).abs() < 1e-9);
+
+    let mut event = gyro_event(1.0, -2.0, 0.5);
+    calibration.apply(&mut event);
+    let InputValue::Vector3 { x, y, z } = event.get_value() else {
+        panic!("Expected Vector3 value");
+    };
+    assert!(x.unwrap().abs() < 1e-9);
+    assert!(y.unwrap().abs() < 1e-9);
+    assert!(z.unwrap().abs() < 1e-9);
+}
+
+#[test]
+fn test_calibration_overlay() {
+    let stick = |center_x: f64| StickCalibration {
+        center_x,
+        center_y: 0.0,
+        min_x: -1.0,
+        max_x: 1.0,
+        min_y: -1.0,
+        max_y: 1.0,
+    };
+    let device = Calibration {
+        left_stick: Some(stick(0.1)),
+        right_stick: Some(stick(0.2)),
+        gyro: None,
+    };
+    let controller = Calibration {
+        left_stick: Some(stick(0.3)),
+        right_stick: None,
+        gyro: None,
+    };
+
+    let calibration = device.overlay(&controller);
+    assert_eq!(calibration.left_stick, Some(stick(0.3)));
+    assert_eq!(calibration.right_stick, Some(stick(0.2)));
+    assert!(calibration.gyro.is_none());
+}
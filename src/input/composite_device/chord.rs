@@ -0,0 +1,49 @@
+//! Evaluation of the chord timing options of capability map mappings
+use std::{ops::Sub, time::Duration};
+
+use crate::config::ChordOptions;
+
+/// Returns true if source events pressed at the given times satisfy the given
+/// chord timing options. The press times must be in the order the source
+/// events are defined in the mapping.
+pub fn chord_timing_met<T>(chord: Option<&ChordOptions>, press_times: &[T]) -> bool
+where
+    T: Ord + Copy + Sub<Output = Duration>,
+{
+    let Some(chord) = chord else {
+        return true;
+    };
+
+    // Check that all source events were pressed within the max interval
+    if let Some(max_interval_ms) = chord.max_interval_ms {
+        let first = press_times.iter().min();
+        let last = press_times.iter().max();
+        if let (Some(first), Some(last)) = (first, last) {
+            if *last - *first > Duration::from_millis(max_interval_ms) {
+                return false;
+            }
+        }
+    }
+
+    // Check that all source events were pressed in the defined order
+    if chord.ordered.unwrap_or(false) && !press_times.windows(2).all(|t| t[0] <= t[1]) {
+        return false;
+    }
+
+    true
+}
+
+/// Returns how long a pressed source event should be held back before it is
+/// emitted untranslated, based on the given chords it is part of. Returns
+/// None if no chord emits partial input.
+pub fn partial_timeout<'a, I>(chords: I) -> Option<Duration>
+where
+    I: IntoIterator<Item = &'a ChordOptions>,
+{
+    chords
+        .into_iter()
+        .filter(|chord| chord.emit_partial.unwrap_or(false))
+        .filter_map(|chord| chord.max_interval_ms)
+        .max()
+        .map(Duration::from_millis)
+}
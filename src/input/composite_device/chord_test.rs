@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+use crate::{
+    config::ChordOptions,
+    input::composite_device::chord::{chord_timing_met, partial_timeout},
+};
+
+fn chord(max_interval_ms: Option<u64>, emit_partial: bool, ordered: bool) -> ChordOptions {
+    ChordOptions {
+        max_interval_ms,
+        emit_partial: Some(emit_partial),
+        ordered: Some(ordered),
+    }
+}
+
+#[test]
+fn test_chord_max_interval() {
+    let ms = Duration::from_millis;
+    let options = chord(Some(50), false, false);
+    assert!(chord_timing_met(Some(&options), &[ms(0), ms(50)]));
+    assert!(!chord_timing_met(Some(&options), &[ms(0), ms(51)]));
+
+    // The interval is measured between the first and last press, regardless
+    // of the order the source events are defined in.
+    assert!(chord_timing_met(Some(&options), &[ms(40), ms(0), ms(20)]));
+    assert!(!chord_timing_met(Some(&options), &[ms(100), ms(0), ms(20)]));
+
+    // Mappings without chord options always match
+    assert!(chord_timing_met(None, &[ms(0), ms(1000)]));
+}
+
+#[test]
+fn test_chord_ordered() {
+    let ms = Duration::from_millis;
+    let options = chord(None, false, true);
+    assert!(chord_timing_met(Some(&options), &[ms(0), ms(10), ms(10)]));
+    assert!(!chord_timing_met(Some(&options), &[ms(10), ms(0)]));
+
+    // Unordered chords can be pressed in any order
+    let options = chord(None, false, false);
+    assert!(chord_timing_met(Some(&options), &[ms(10), ms(0)]));
+
+    // Both options must be met
+    let options = chord(Some(50), false, true);
+    assert!(!chord_timing_met(Some(&options), &[ms(0), ms(100)]));
+    assert!(!chord_timing_met(Some(&options), &[ms(20), ms(0)]));
+}
+
+#[test]
+fn test_chord_partial_timeout() {
+    let partial = chord(Some(50), true, false);
+    let longer = chord(Some(80), true, false);
+    let not_partial = chord(Some(200), false, false);
+    let no_interval = chord(None, true, false);
+
+    // The longest interval of all chords that emit partial input is used
+    assert_eq!(
+        partial_timeout([&partial, &longer, &not_partial, &no_interval]),
+        Some(Duration::from_millis(80))
+    );
+    assert_eq!(partial_timeout([&not_partial, &no_interval]), None);
+    assert_eq!(partial_timeout([]), None);
+}
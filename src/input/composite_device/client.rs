@@ -1,14 +1,19 @@
 use std::collections::{HashMap, HashSet};
+use std::time::Duration;
 use thiserror::Error;
 use tokio::sync::mpsc::{channel, error::SendError, Sender};
 
-use crate::config::CompositeDeviceConfig;
-use crate::input::event::native::NativeEvent;
+use crate::config::{CompositeDeviceConfig, HapticsToRumble};
+use crate::input::event::native::{NativeEvent, ScheduledNativeEvent};
 use crate::input::target::client::TargetDeviceClient;
 use crate::input::{capability::Capability, event::Event, output_event::OutputEvent};
 use crate::udev::device::UdevDevice;
 
-use super::{CompositeCommand, InterceptMode};
+use super::{
+    default_profile::ProfileSource, drift::DriftCorrections, dropped::DropReason,
+    snapshot::DeviceSnapshot, statistics::CapabilityStatistics, touchscreen::TouchscreenMode,
+    CompositeCommand, InterceptMode,
+};
 
 /// Possible errors for a composite device client
 #[derive(Error, Debug)]
@@ -54,6 +59,16 @@ impl CompositeDeviceClient {
         Err(ClientError::ChannelClosed)
     }
 
+    /// Get the stable identifier of the composite device
+    pub async fn get_uuid(&self) -> Result<String, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx.send(CompositeCommand::GetUuid(tx)).await?;
+        if let Some(uuid) = rx.recv().await {
+            return Ok(uuid);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
     /// Process the given event from the given device
     pub async fn process_event(&self, device_id: String, event: Event) -> Result<(), ClientError> {
         self.tx
@@ -73,6 +88,21 @@ impl CompositeDeviceClient {
         Ok(())
     }
 
+    /// Add the given capabilities that the given source device discovered
+    /// after it was added (blocking)
+    pub fn blocking_update_source_capabilities(
+        &self,
+        device_id: String,
+        capabilities: Vec<Capability>,
+    ) -> Result<(), ClientError> {
+        self.tx
+            .blocking_send(CompositeCommand::UpdateSourceCapabilities(
+                device_id,
+                capabilities,
+            ))?;
+        Ok(())
+    }
+
     /// Process the given output event
     pub async fn process_output_event(&self, event: OutputEvent) -> Result<(), ClientError> {
         self.tx
@@ -130,6 +160,18 @@ impl CompositeDeviceClient {
         Err(ClientError::ChannelClosed)
     }
 
+    /// Get the capabilities that can reach target devices after translation
+    pub async fn get_translated_capabilities(&self) -> Result<HashSet<Capability>, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::GetTranslatedCapabilities(tx))
+            .await?;
+        if let Some(capabilities) = rx.recv().await {
+            return Ok(capabilities);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
     /// Set the intercept mode of the composite device
     pub async fn set_intercept_mode(&self, mode: InterceptMode) -> Result<(), ClientError> {
         self.tx
@@ -138,6 +180,36 @@ impl CompositeDeviceClient {
         Ok(())
     }
 
+    /// Set the intercept mode of the composite device after validating that
+    /// the mode is supported by the device's capabilities. If 'force' is true,
+    /// the mode is set without validation.
+    pub async fn set_intercept_mode_checked(
+        &self,
+        mode: InterceptMode,
+        force: bool,
+    ) -> Result<(), ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::SetInterceptModeChecked(mode, force, tx))
+            .await?;
+        if let Some(result) = rx.recv().await {
+            return match result {
+                Ok(_) => Ok(()),
+                Err(e) => Err(ClientError::ServiceError(e.into())),
+            };
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Set whether or not the composite device is inhibited from emitting
+    /// events to target devices
+    pub async fn set_inhibited(&self, inhibited: bool) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::SetInhibited(inhibited))
+            .await?;
+        Ok(())
+    }
+
     /// Get the intercept mode of the composite device
     pub async fn get_intercept_mode(&self) -> Result<InterceptMode, ClientError> {
         let (tx, mut rx) = channel(1);
@@ -148,6 +220,63 @@ impl CompositeDeviceClient {
         Err(ClientError::ChannelClosed)
     }
 
+    /// Get how input from the physical touchscreen is emitted to target
+    /// devices
+    pub async fn get_touchscreen_mode(&self) -> Result<TouchscreenMode, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::GetTouchscreenMode(tx))
+            .await?;
+        if let Some(mode) = rx.recv().await {
+            return Ok(mode);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Set how input from the physical touchscreen is emitted to target
+    /// devices
+    pub async fn set_touchscreen_mode(&self, mode: TouchscreenMode) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::SetTouchscreenMode(mode))
+            .await?;
+        Ok(())
+    }
+
+    /// Block or unblock the source device with the given id (e.g.
+    /// "evdev://event0") from passing its input events to target devices
+    pub async fn set_source_device_blocked(
+        &self,
+        device_id: String,
+        blocked: bool,
+    ) -> Result<(), ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::SetSourceDeviceBlocked(
+                device_id, blocked, tx,
+            ))
+            .await?;
+        if let Some(result) = rx.recv().await {
+            return match result {
+                Ok(_) => Ok(()),
+                Err(e) => Err(ClientError::ServiceError(e.into())),
+            };
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Get the ids of source devices that are blocked from passing their input
+    /// events to target devices
+    pub async fn get_blocked_source_devices(&self) -> Result<Vec<String>, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::GetBlockedSourceDevices(tx))
+            .await?;
+        if let Some(ids) = rx.recv().await {
+            return Ok(ids);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
     /// Get the source device paths of the composite device
     pub async fn get_source_device_paths(&self) -> Result<Vec<String>, ClientError> {
         let (tx, mut rx) = channel(1);
@@ -221,6 +350,22 @@ impl CompositeDeviceClient {
         Ok(())
     }
 
+    /// Set the target devices of the target loadout with the given name from
+    /// the global config on the composite device
+    pub async fn set_target_loadout(&self, name: String) -> Result<(), ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::SetTargetLoadout(name, tx))
+            .await?;
+        if let Some(result) = rx.recv().await {
+            return match result {
+                Ok(_) => Ok(()),
+                Err(e) => Err(ClientError::ServiceError(e.into())),
+            };
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
     /// Attach the given target devices to the composite device
     pub async fn attach_target_devices(
         &self,
@@ -242,6 +387,16 @@ impl CompositeDeviceClient {
         Err(ClientError::ChannelClosed)
     }
 
+    /// Get where the currently loaded profile was loaded from
+    pub async fn get_profile_source(&self) -> Result<ProfileSource, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx.send(CompositeCommand::GetProfileSource(tx)).await?;
+        if let Some(source) = rx.recv().await {
+            return Ok(source);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
     /// Load the device profile from the given path
     pub async fn load_profile_path(&self, path: String) -> Result<(), ClientError> {
         let (tx, mut rx) = channel(1);
@@ -257,6 +412,44 @@ impl CompositeDeviceClient {
         Err(ClientError::ChannelClosed)
     }
 
+    /// Temporarily load the device profile from the given path. The previous
+    /// profile is restored if the profile is not confirmed with
+    /// [CompositeDeviceClient::confirm_profile] within the given timeout.
+    pub async fn load_profile_temporary(
+        &self,
+        path: String,
+        timeout_secs: u64,
+    ) -> Result<(), ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::LoadProfileTemporary(
+                path,
+                timeout_secs,
+                tx,
+            ))
+            .await?;
+        if let Some(result) = rx.recv().await {
+            return match result {
+                Ok(_) => Ok(()),
+                Err(e) => Err(ClientError::ServiceError(e.into())),
+            };
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Confirm the temporarily loaded device profile so it is kept
+    pub async fn confirm_profile(&self) -> Result<(), ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx.send(CompositeCommand::ConfirmProfile(tx)).await?;
+        if let Some(result) = rx.recv().await {
+            return match result {
+                Ok(_) => Ok(()),
+                Err(e) => Err(ClientError::ServiceError(e.into())),
+            };
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
     /// Load the device profile from the given path
     pub async fn load_profile_from_yaml(&self, profile: String) -> Result<(), ClientError> {
         let (tx, mut rx) = channel(1);
@@ -339,6 +532,327 @@ impl CompositeDeviceClient {
         Ok(())
     }
 
+    /// Atomically load the device profile at the given path and switch to
+    /// the given target device types
+    pub async fn apply_configuration(
+        &self,
+        profile_path: String,
+        target_types: Vec<String>,
+    ) -> Result<(), ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::ApplyConfiguration(
+                profile_path,
+                target_types,
+                tx,
+            ))
+            .await?;
+        if let Some(result) = rx.recv().await {
+            return match result {
+                Ok(_) => Ok(()),
+                Err(e) => Err(ClientError::ServiceError(e.into())),
+            };
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Get a snapshot of the runtime state of the composite device
+    pub async fn get_snapshot(&self) -> Result<DeviceSnapshot, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx.send(CompositeCommand::GetSnapshot(tx)).await?;
+        if let Some(result) = rx.recv().await {
+            return match result {
+                Ok(snapshot) => Ok(snapshot),
+                Err(e) => Err(ClientError::ServiceError(e.into())),
+            };
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Restore the runtime state of the composite device from the given
+    /// snapshot
+    pub async fn restore_snapshot(&self, snapshot: DeviceSnapshot) -> Result<(), ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::RestoreSnapshot(snapshot, tx))
+            .await?;
+        if let Some(result) = rx.recv().await {
+            return match result {
+                Ok(_) => Ok(()),
+                Err(e) => Err(ClientError::ServiceError(e.into())),
+            };
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Get the force feedback output gain in percent (0-100)
+    pub async fn get_ff_gain(&self) -> Result<u32, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx.send(CompositeCommand::GetFFGain(tx)).await?;
+        if let Some(gain) = rx.recv().await {
+            return Ok(gain);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Set the force feedback output gain in percent (0-100)
+    pub async fn set_ff_gain(&self, gain: u32) -> Result<(), ClientError> {
+        self.tx.send(CompositeCommand::SetFFGain(gain)).await?;
+        Ok(())
+    }
+
+    /// Get the list of applications for which force feedback is muted
+    pub async fn get_ff_mute_apps(&self) -> Result<Vec<String>, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx.send(CompositeCommand::GetFFMuteApps(tx)).await?;
+        if let Some(apps) = rx.recv().await {
+            return Ok(apps);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Set the list of applications for which force feedback is muted
+    pub async fn set_ff_mute_apps(&self, apps: Vec<String>) -> Result<(), ClientError> {
+        self.tx.send(CompositeCommand::SetFFMuteApps(apps)).await?;
+        Ok(())
+    }
+
+    /// Returns whether or not force feedback is currently muted because of
+    /// the active application
+    pub async fn get_ff_muted(&self) -> Result<bool, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx.send(CompositeCommand::GetFFMuted(tx)).await?;
+        if let Some(muted) = rx.recv().await {
+            return Ok(muted);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Rumble all source devices capable of force feedback at the given
+    /// intensity (0.0 - 1.0) for the given duration. Returns the number of
+    /// source devices the rumble was sent to.
+    pub async fn test_rumble(
+        &self,
+        intensity: f64,
+        duration: Duration,
+    ) -> Result<usize, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::TestRumble(intensity, duration, tx))
+            .await?;
+        if let Some(count) = rx.recv().await {
+            return Ok(count);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Schedule the given event to be written to the target devices after its
+    /// delay, optionally repeating it. Returns the identifier of the scheduled
+    /// event that can be used to cancel it.
+    pub async fn schedule_event(&self, event: ScheduledNativeEvent) -> Result<u64, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::ScheduleEvent(event, tx))
+            .await?;
+        if let Some(id) = rx.recv().await {
+            return Ok(id);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Cancel the scheduled event with the given identifier
+    pub async fn cancel_scheduled_event(&self, id: u64) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::CancelScheduledEvent(id))
+            .await?;
+        Ok(())
+    }
+
+    /// Set the identifier of the application currently in focus
+    pub async fn set_active_app(&self, app: Option<String>) -> Result<(), ClientError> {
+        self.tx.send(CompositeCommand::SetActiveApp(app)).await?;
+        Ok(())
+    }
+
+    /// Re-create all target devices so they are only accessible by the
+    /// active user session after the session changed
+    pub async fn recreate_target_devices(&self) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::RecreateTargetDevices)
+            .await?;
+        Ok(())
+    }
+
+    /// Check whether the device is connected to a dock after a possible dock
+    /// device was added or removed
+    pub async fn update_docked(&self) -> Result<(), ClientError> {
+        self.tx.send(CompositeCommand::UpdateDocked).await?;
+        Ok(())
+    }
+
+    /// Get the options used to convert trackpad haptic commands into rumble
+    pub async fn get_haptics_to_rumble(&self) -> Result<HapticsToRumble, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::GetHapticsToRumble(tx))
+            .await?;
+        if let Some(options) = rx.recv().await {
+            return Ok(options);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Set the options used to convert trackpad haptic commands into rumble
+    pub async fn set_haptics_to_rumble(&self, options: HapticsToRumble) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::SetHapticsToRumble(options))
+            .await?;
+        Ok(())
+    }
+
+    /// Start capturing source events to calibrate sticks and IMUs
+    pub async fn start_calibration(&self) -> Result<(), ClientError> {
+        self.tx.send(CompositeCommand::StartCalibration).await?;
+        Ok(())
+    }
+
+    /// Finish the calibration in progress and persist the results
+    pub async fn finish_calibration(&self) -> Result<(), ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::FinishCalibration(tx))
+            .await?;
+        if let Some(result) = rx.recv().await {
+            return match result {
+                Ok(_) => Ok(()),
+                Err(e) => Err(ClientError::ServiceError(e.into())),
+            };
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Returns the stick drift severity from 0.0 (no drift) to 1.0 (severe drift)
+    pub async fn get_drift_severity(&self) -> Result<f64, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx.send(CompositeCommand::GetDriftSeverity(tx)).await?;
+        if let Some(severity) = rx.recv().await {
+            return Ok(severity);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Returns the currently suggested stick drift corrections
+    pub async fn get_drift_suggestions(&self) -> Result<DriftCorrections, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::GetDriftSuggestions(tx))
+            .await?;
+        if let Some(suggestions) = rx.recv().await {
+            return Ok(suggestions);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Returns the counts of events that were dropped before reaching a
+    /// target device by drop reason and capability
+    pub async fn get_dropped_events(
+        &self,
+    ) -> Result<HashMap<DropReason, HashMap<Capability, u64>>, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx.send(CompositeCommand::GetDroppedEvents(tx)).await?;
+        if let Some(counts) = rx.recv().await {
+            return Ok(counts);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Reset the counts of dropped events
+    pub async fn reset_dropped_events(&self) -> Result<(), ClientError> {
+        self.tx.send(CompositeCommand::ResetDroppedEvents).await?;
+        Ok(())
+    }
+
+    /// Returns the usage statistics of source inputs that were used
+    pub async fn get_input_statistics(
+        &self,
+    ) -> Result<HashMap<Capability, CapabilityStatistics>, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::GetInputStatistics(tx))
+            .await?;
+        if let Some(statistics) = rx.recv().await {
+            return Ok(statistics);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Returns whether or not usage statistics of source inputs are collected
+    pub async fn get_input_statistics_enabled(&self) -> Result<bool, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::GetInputStatisticsEnabled(tx))
+            .await?;
+        if let Some(enabled) = rx.recv().await {
+            return Ok(enabled);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Start or stop collecting usage statistics of source inputs
+    pub async fn set_input_statistics_enabled(&self, enabled: bool) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::SetInputStatisticsEnabled(enabled))
+            .await?;
+        Ok(())
+    }
+
+    /// Reset the usage statistics of source inputs
+    pub async fn reset_input_statistics(&self) -> Result<(), ClientError> {
+        self.tx.send(CompositeCommand::ResetInputStatistics).await?;
+        Ok(())
+    }
+
+    /// Returns whether or not suggested drift corrections are applied automatically
+    pub async fn get_drift_auto_apply(&self) -> Result<bool, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::GetDriftAutoApply(tx))
+            .await?;
+        if let Some(auto_apply) = rx.recv().await {
+            return Ok(auto_apply);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Set whether or not suggested drift corrections are applied automatically
+    pub async fn set_drift_auto_apply(&self, auto_apply: bool) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::SetDriftAutoApply(auto_apply))
+            .await?;
+        Ok(())
+    }
+
+    /// Apply and persist the currently suggested stick drift corrections
+    pub async fn apply_drift_correction(&self) -> Result<(), ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::ApplyDriftCorrection(tx))
+            .await?;
+        if let Some(result) = rx.recv().await {
+            return match result {
+                Ok(_) => Ok(()),
+                Err(e) => Err(ClientError::ServiceError(e.into())),
+            };
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Remove all stick drift corrections
+    pub async fn clear_drift_correction(&self) -> Result<(), ClientError> {
+        self.tx.send(CompositeCommand::ClearDriftCorrection).await?;
+        Ok(())
+    }
+
     /// Calls the suspend handler to perform system suspend-related tasks.
     pub async fn suspend(&self) -> Result<(), ClientError> {
         let (tx, mut rx) = channel(1);
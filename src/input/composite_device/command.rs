@@ -1,47 +1,111 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
 
 use tokio::sync::mpsc;
 
 use crate::{
-    config::CompositeDeviceConfig,
+    config::{CompositeDeviceConfig, HapticsToRumble},
     input::{
         capability::Capability,
-        event::{native::NativeEvent, Event},
+        event::{
+            native::{NativeEvent, ScheduledNativeEvent},
+            Event,
+        },
         output_event::OutputEvent,
         target::client::TargetDeviceClient,
     },
     udev::device::UdevDevice,
 };
 
-use super::InterceptMode;
+use super::{
+    default_profile::ProfileSource, drift::DriftCorrections, dropped::DropReason,
+    press::PressTimer, snapshot::DeviceSnapshot, statistics::CapabilityStatistics,
+    touchscreen::TouchscreenMode, translation::TranslatedEvent, InterceptMode,
+};
 
 /// CompositeDevice commands define all the different ways to interact with [CompositeDevice]
 /// over a channel. These commands are processed in an asyncronous thread and
 /// dispatched as they come in.
 #[derive(Debug, Clone)]
 pub enum CompositeCommand {
+    ApplyConfiguration(String, Vec<String>, mpsc::Sender<Result<(), String>>),
+    ApplyDriftCorrection(mpsc::Sender<Result<(), String>>),
     AttachTargetDevices(HashMap<String, TargetDeviceClient>),
+    CancelScheduledEvent(u64),
+    CheckSourceWatchdog,
+    ChordTimeout(NativeEvent, Instant),
+    ClearDriftCorrection,
+    FinishCalibration(mpsc::Sender<Result<(), String>>),
     GetConfig(mpsc::Sender<CompositeDeviceConfig>),
+    GetBlockedSourceDevices(mpsc::Sender<Vec<String>>),
     GetCapabilities(mpsc::Sender<HashSet<Capability>>),
     GetDBusDevicePaths(mpsc::Sender<Vec<String>>),
+    GetDriftAutoApply(mpsc::Sender<bool>),
+    GetDriftSeverity(mpsc::Sender<f64>),
+    GetDriftSuggestions(mpsc::Sender<DriftCorrections>),
+    GetDroppedEvents(mpsc::Sender<HashMap<DropReason, HashMap<Capability, u64>>>),
+    GetFFGain(mpsc::Sender<u32>),
+    GetFFMuteApps(mpsc::Sender<Vec<String>>),
+    GetFFMuted(mpsc::Sender<bool>),
+    GetHapticsToRumble(mpsc::Sender<HapticsToRumble>),
+    GetInputStatistics(mpsc::Sender<HashMap<Capability, CapabilityStatistics>>),
+    GetInputStatisticsEnabled(mpsc::Sender<bool>),
     GetInterceptMode(mpsc::Sender<InterceptMode>),
     GetName(mpsc::Sender<String>),
     GetProfileName(mpsc::Sender<String>),
+    GetProfileSource(mpsc::Sender<ProfileSource>),
+    GetSnapshot(mpsc::Sender<Result<DeviceSnapshot, String>>),
     GetSourceDevicePaths(mpsc::Sender<Vec<String>>),
     GetTargetCapabilities(mpsc::Sender<HashSet<Capability>>),
     GetTargetDevicePaths(mpsc::Sender<Vec<String>>),
+    GetTouchscreenMode(mpsc::Sender<TouchscreenMode>),
+    GetTranslatedCapabilities(mpsc::Sender<HashSet<Capability>>),
+    GetUuid(mpsc::Sender<String>),
     HandleEvent(NativeEvent),
+    HandleTranslatedEvents(Vec<TranslatedEvent>),
     LoadProfileFromYaml(String, mpsc::Sender<Result<(), String>>),
     LoadProfilePath(String, mpsc::Sender<Result<(), String>>),
+    LoadProfileTemporary(String, u64, mpsc::Sender<Result<(), String>>),
+    ConfirmProfile(mpsc::Sender<Result<(), String>>),
+    EmitScheduledEvent(u64, ScheduledNativeEvent),
+    PressTimer(PressTimer),
+    SystemKeyTimer(u64),
+    RevertProfilePreview(u64),
     ProcessEvent(String, Event),
     ProcessOutputEvent(OutputEvent),
+    RecreateTargetDevices,
+    ReloadDefaultProfile,
+    RestartSourceDevice(String),
     RemoveRecentEvent(Capability),
+    ResetDroppedEvents,
+    ResetInputStatistics,
+    RestoreSnapshot(DeviceSnapshot, mpsc::Sender<Result<(), String>>),
+    ScheduleEvent(ScheduledNativeEvent, mpsc::Sender<u64>),
+    SetActiveApp(Option<String>),
+    SetDriftAutoApply(bool),
+    SetFFGain(u32),
+    SetFFMuteApps(Vec<String>),
+    SetHapticsToRumble(HapticsToRumble),
+    SetInhibited(bool),
+    SetInputStatisticsEnabled(bool),
     SetInterceptActivation(Vec<Capability>, Capability),
     SetInterceptMode(InterceptMode),
+    SetInterceptModeChecked(InterceptMode, bool, mpsc::Sender<Result<(), String>>),
+    SetSourceDeviceBlocked(String, bool, mpsc::Sender<Result<(), String>>),
     SetTargetDevices(Vec<String>),
+    SetTargetLoadout(String, mpsc::Sender<Result<(), String>>),
+    SetTouchscreenMode(TouchscreenMode),
     SourceDeviceAdded(UdevDevice),
+    StartCalibration,
+    StopRumble(u64, Vec<String>),
+    TestRumble(f64, Duration, mpsc::Sender<usize>),
     SourceDeviceRemoved(UdevDevice),
     SourceDeviceStopped(UdevDevice),
+    UpdateDocked,
+    UpdateSourceBypass,
+    UpdateSourceCapabilities(String, Vec<Capability>),
     WriteChordEvent(Vec<NativeEvent>),
     WriteEvent(NativeEvent),
     WriteSendEvent(NativeEvent),
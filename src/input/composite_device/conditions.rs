@@ -0,0 +1,65 @@
+//! Evaluation of the conditions of capability map mappings
+use crate::{config::MappingCondition, input::capability::Capability};
+
+use super::InterceptMode;
+
+/// Returns true if all of the given mapping conditions are met. The given
+/// function is used to check whether a capability is currently held.
+pub fn conditions_met<F>(
+    conditions: Option<&Vec<MappingCondition>>,
+    intercept_mode: &InterceptMode,
+    is_held: F,
+) -> bool
+where
+    F: Fn(&Capability) -> bool,
+{
+    let Some(conditions) = conditions else {
+        return true;
+    };
+    conditions
+        .iter()
+        .all(|condition| condition_met(condition, intercept_mode, &is_held))
+}
+
+/// Returns true if the given mapping condition is met
+fn condition_met<F>(
+    condition: &MappingCondition,
+    intercept_mode: &InterceptMode,
+    is_held: &F,
+) -> bool
+where
+    F: Fn(&Capability) -> bool,
+{
+    if let Some(held) = condition.held.as_ref() {
+        if !is_held(&held.clone().into()) {
+            return false;
+        }
+    }
+    if let Some(not_held) = condition.not_held.as_ref() {
+        if is_held(&not_held.clone().into()) {
+            return false;
+        }
+    }
+    if let Some(mode) = condition.intercept_mode.as_ref() {
+        if !mode.eq_ignore_ascii_case(intercept_mode.as_str()) {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Returns all capabilities referenced by the given mapping conditions. The
+/// held state of these capabilities must be tracked to evaluate conditions.
+pub fn condition_capabilities(conditions: Option<&Vec<MappingCondition>>) -> Vec<Capability> {
+    let Some(conditions) = conditions else {
+        return Vec::new();
+    };
+    conditions
+        .iter()
+        .flat_map(|condition| [condition.held.as_ref(), condition.not_held.as_ref()])
+        .flatten()
+        .map(|config| config.clone().into())
+        .filter(|cap| *cap != Capability::NotImplemented)
+        .collect()
+}
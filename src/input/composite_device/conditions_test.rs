@@ -0,0 +1,69 @@
+use crate::{
+    config::CapabilityMap,
+    input::{
+        capability::{Capability, Keyboard},
+        composite_device::{
+            conditions::{condition_capabilities, conditions_met},
+            InterceptMode,
+        },
+    },
+};
+
+const CONDITIONAL_MAP: &str = r#"
+version: 2
+kind: CapabilityMap
+name: Conditional
+id: conditional
+mapping:
+  - name: Shifted F1
+    source_events:
+      - keyboard: KeyF1
+    target_event:
+      gamepad:
+        button: QuickAccess
+    conditions:
+      - held:
+          keyboard: KeyLeftShift
+      - not_held:
+          keyboard: KeyLeftCtrl
+  - name: Intercepted F2
+    source_events:
+      - keyboard: KeyF2
+    target_event:
+      gamepad:
+        button: Guide
+    conditions:
+      - intercept_mode: always
+"#;
+
+#[test]
+fn test_conditions_held() {
+    let map = CapabilityMap::_from_yaml(CONDITIONAL_MAP.to_string()).unwrap();
+    let conditions = map.mapping[0].conditions.as_ref();
+    let shift = Capability::Keyboard(Keyboard::KeyLeftShift);
+    let ctrl = Capability::Keyboard(Keyboard::KeyLeftCtrl);
+    let mode = InterceptMode::None;
+
+    assert!(!conditions_met(conditions, &mode, |_| false));
+    assert!(conditions_met(conditions, &mode, |cap| *cap == shift));
+    assert!(!conditions_met(conditions, &mode, |cap| *cap == shift || *cap == ctrl));
+    assert_eq!(condition_capabilities(conditions), vec![shift, ctrl]);
+}
+
+#[test]
+fn test_conditions_intercept_mode() {
+    let map = CapabilityMap::_from_yaml(CONDITIONAL_MAP.to_string()).unwrap();
+    let conditions = map.mapping[1].conditions.as_ref();
+
+    assert!(!conditions_met(conditions, &InterceptMode::None, |_| false));
+    assert!(conditions_met(conditions, &InterceptMode::Always, |_| {
+        false
+    }));
+    assert!(condition_capabilities(conditions).is_empty());
+}
+
+#[test]
+fn test_conditions_require_version_2() {
+    let map = CONDITIONAL_MAP.replace("version: 2", "version: 1");
+    assert!(CapabilityMap::_from_yaml(map).is_err());
+}
@@ -0,0 +1,73 @@
+//! The default device profile that is loaded when a composite device starts.
+//! A copy of the default profile is compiled into the binary so composite
+//! devices still work when the profile file is missing, e.g. on installs with
+//! a custom prefix.
+use std::{fmt, path::PathBuf, time::Duration};
+
+use crate::config::{path::get_profiles_path, DeviceProfile};
+
+/// Default device profile compiled into the binary
+pub const EMBEDDED_DEFAULT_PROFILE: &str =
+    include_str!("../../../rootfs/usr/share/inputplumber/profiles/default.yaml");
+
+/// How often to check whether the default profile file has appeared while
+/// the embedded default profile is in use
+pub const DEFAULT_PROFILE_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Where the currently loaded device profile was loaded from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProfileSource {
+    /// The profile was loaded from the file at the given path
+    File(String),
+    /// The default profile compiled into the binary was loaded because the
+    /// default profile file could not be loaded
+    Embedded,
+    /// The profile was loaded from the given YAML sent over DBus
+    Yaml(String),
+}
+
+impl ProfileSource {
+    /// Short identifier of the profile source, e.g. "embedded"
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProfileSource::File(_) => "file",
+            ProfileSource::Embedded => "embedded",
+            ProfileSource::Yaml(_) => "yaml",
+        }
+    }
+}
+
+impl fmt::Display for ProfileSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileSource::File(path) => write!(f, "file:{path}"),
+            _ => write!(f, "{}", self.as_str()),
+        }
+    }
+}
+
+/// Returns the path to the default profile file
+pub fn default_profile_path() -> PathBuf {
+    get_profiles_path().join("default.yaml")
+}
+
+/// Load the default profile from the default profile file, falling back to
+/// the embedded default profile if the file cannot be loaded.
+pub fn load_default_profile() -> (DeviceProfile, ProfileSource) {
+    let path = default_profile_path().to_string_lossy().to_string();
+    match DeviceProfile::from_yaml_file(path.clone()) {
+        Ok(profile) => (profile, ProfileSource::File(path)),
+        Err(e) => {
+            log::warn!(
+                "Unable to load default profile {path}: {e}. Using embedded default profile."
+            );
+            (load_embedded_default_profile(), ProfileSource::Embedded)
+        }
+    }
+}
+
+/// Load the default profile compiled into the binary
+pub fn load_embedded_default_profile() -> DeviceProfile {
+    DeviceProfile::from_yaml(EMBEDDED_DEFAULT_PROFILE.to_string())
+        .expect("embedded default profile should be valid")
+}
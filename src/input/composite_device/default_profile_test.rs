@@ -0,0 +1,23 @@
+use crate::input::composite_device::default_profile::{
+    load_embedded_default_profile, ProfileSource,
+};
+
+#[test]
+fn test_embedded_default_profile() {
+    let profile = load_embedded_default_profile();
+    assert_eq!(profile.name, "Default");
+}
+
+#[test]
+fn test_profile_source() {
+    let source = ProfileSource::File("/usr/share/inputplumber/profiles/default.yaml".into());
+    assert_eq!(source.as_str(), "file");
+    assert_eq!(
+        source.to_string(),
+        "file:/usr/share/inputplumber/profiles/default.yaml"
+    );
+    assert_eq!(ProfileSource::Embedded.to_string(), "embedded");
+    let source = ProfileSource::Yaml("version: 1".into());
+    assert_eq!(source.as_str(), "yaml");
+    assert_eq!(source.to_string(), "yaml");
+}
@@ -0,0 +1,248 @@
+//! Detection of and compensation for gamepad stick drift
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::DriftDetection,
+    input::{
+        capability::{Capability, Gamepad, GamepadAxis},
+        event::{native::NativeEvent, value::InputValue},
+    },
+};
+
+/// Stick values with a magnitude below this threshold can be considered to
+/// be at rest.
+const STICK_REST_THRESHOLD: f64 = 0.2;
+/// Maximum change between two stick values for the stick to be considered
+/// at rest. This avoids counting a stick moving through its center.
+const STICK_REST_MAX_CHANGE: f64 = 0.02;
+/// Weight of new resting samples in the moving averages
+const SMOOTHING: f64 = 0.01;
+/// Number of resting samples required before drift is reported
+const MIN_REST_SAMPLES: u64 = 200;
+/// Combined center offset and noise at which drift is considered severe
+const SEVERE_DRIFT: f64 = 0.2;
+/// Default maximum center offset that can be compensated automatically
+const DEFAULT_MAX_CENTER_OFFSET: f64 = 0.1;
+/// Default maximum deadzone that can be applied automatically
+const DEFAULT_MAX_DEADZONE: f64 = 0.2;
+
+/// Center and deadzone correction for a single stick. Values are in the
+/// normalized stick range of -1.0 to 1.0.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DriftCorrection {
+    pub center_x: f64,
+    pub center_y: f64,
+    pub deadzone: f64,
+}
+
+impl DriftCorrection {
+    /// Apply the correction to the given stick value
+    fn apply(&self, x: Option<f64>, y: Option<f64>) -> (Option<f64>, Option<f64>) {
+        let x = x.map(|x| apply_deadzone(x - self.center_x, self.deadzone));
+        let y = y.map(|y| apply_deadzone(y - self.center_y, self.deadzone));
+        (x, y)
+    }
+}
+
+/// Drift corrections applied to the sticks of a composite device
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DriftCorrections {
+    pub left_stick: Option<DriftCorrection>,
+    pub right_stick: Option<DriftCorrection>,
+}
+
+impl DriftCorrections {
+    /// Apply the corrections to the given event
+    pub fn apply(&self, event: &mut NativeEvent) {
+        let Capability::Gamepad(Gamepad::Axis(axis)) = event.as_capability() else {
+            return;
+        };
+        let InputValue::Vector2 { x, y } = event.get_value() else {
+            return;
+        };
+        let correction = match axis {
+            GamepadAxis::LeftStick => self.left_stick.as_ref(),
+            GamepadAxis::RightStick => self.right_stick.as_ref(),
+            _ => None,
+        };
+        let Some(correction) = correction else {
+            return;
+        };
+        let (x, y) = correction.apply(x, y);
+        event.set_value(InputValue::Vector2 { x, y });
+    }
+}
+
+/// The [DriftDetector] observes the resting position of each stick over time
+/// to measure how far the stick center has drifted and how noisy it is. The
+/// measurements are used to suggest corrections that are limited to the
+/// given bounds.
+#[derive(Debug)]
+pub struct DriftDetector {
+    left_stick: StickDrift,
+    right_stick: StickDrift,
+    max_center_offset: f64,
+    max_deadzone: f64,
+}
+
+impl Default for DriftDetector {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_CENTER_OFFSET, DEFAULT_MAX_DEADZONE)
+    }
+}
+
+impl DriftDetector {
+    /// Create a new [DriftDetector] which limits suggested corrections to the
+    /// given maximum center offset and deadzone.
+    pub fn new(max_center_offset: f64, max_deadzone: f64) -> Self {
+        Self {
+            left_stick: StickDrift::default(),
+            right_stick: StickDrift::default(),
+            max_center_offset: max_center_offset.abs(),
+            max_deadzone: max_deadzone.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Create a new [DriftDetector] from the given options. Returns [None] if
+    /// drift detection is not enabled.
+    pub fn from_options(options: &DriftDetection) -> Option<Self> {
+        if !options.enabled.unwrap_or(false) {
+            return None;
+        }
+        Some(Self::new(
+            options
+                .max_center_offset
+                .unwrap_or(DEFAULT_MAX_CENTER_OFFSET),
+            options.max_deadzone.unwrap_or(DEFAULT_MAX_DEADZONE),
+        ))
+    }
+
+    /// Discard all measurements
+    pub fn reset(&mut self) {
+        self.left_stick = StickDrift::default();
+        self.right_stick = StickDrift::default();
+    }
+
+    /// Record the given event. Returns true if the event was a resting stick
+    /// sample that updated the measurements.
+    pub fn record(&mut self, event: &NativeEvent) -> bool {
+        let Capability::Gamepad(Gamepad::Axis(axis)) = event.as_capability() else {
+            return false;
+        };
+        let InputValue::Vector2 { x, y } = event.get_value() else {
+            return false;
+        };
+        match axis {
+            GamepadAxis::LeftStick => self.left_stick.record(x, y),
+            GamepadAxis::RightStick => self.right_stick.record(x, y),
+            _ => false,
+        }
+    }
+
+    /// Returns the drift severity of the worst stick from 0.0 (no drift) to
+    /// 1.0 (severe drift).
+    pub fn severity(&self) -> f64 {
+        self.left_stick.severity().max(self.right_stick.severity())
+    }
+
+    /// Returns the suggested corrections for all sticks that have been
+    /// observed long enough to measure their drift.
+    pub fn suggestions(&self) -> DriftCorrections {
+        DriftCorrections {
+            left_stick: self
+                .left_stick
+                .suggestion(self.max_center_offset, self.max_deadzone),
+            right_stick: self
+                .right_stick
+                .suggestion(self.max_center_offset, self.max_deadzone),
+        }
+    }
+}
+
+/// Resting measurements of a single stick
+#[derive(Debug, Default)]
+struct StickDrift {
+    /// Last known position of the stick. Stick events may only contain the
+    /// axis that changed.
+    position: (f64, f64),
+    /// Moving average of the resting position
+    center: (f64, f64),
+    /// Moving average of the distance of resting samples from the center
+    noise: f64,
+    rest_count: u64,
+}
+
+impl StickDrift {
+    fn record(&mut self, x: Option<f64>, y: Option<f64>) -> bool {
+        let previous = self.position;
+        if let Some(x) = x {
+            self.position.0 = x;
+        }
+        if let Some(y) = y {
+            self.position.1 = y;
+        }
+        let (x, y) = self.position;
+        let change = (x - previous.0).hypot(y - previous.1);
+        if x.hypot(y) >= STICK_REST_THRESHOLD || change > STICK_REST_MAX_CHANGE {
+            return false;
+        }
+
+        // Use a plain average until enough samples were collected so the
+        // measurements do not start out biased towards zero.
+        let weight = SMOOTHING.max(1.0 / (self.rest_count + 1) as f64);
+        self.center.0 += (x - self.center.0) * weight;
+        self.center.1 += (y - self.center.1) * weight;
+        let distance = (x - self.center.0).hypot(y - self.center.1);
+        self.noise += (distance - self.noise) * weight;
+        self.rest_count = self.rest_count.saturating_add(1);
+        true
+    }
+
+    fn is_measured(&self) -> bool {
+        self.rest_count >= MIN_REST_SAMPLES
+    }
+
+    fn severity(&self) -> f64 {
+        if !self.is_measured() {
+            return 0.0;
+        }
+        let offset = self.center.0.hypot(self.center.1);
+        ((offset + 2.0 * self.noise) / SEVERE_DRIFT).clamp(0.0, 1.0)
+    }
+
+    fn suggestion(&self, max_center_offset: f64, max_deadzone: f64) -> Option<DriftCorrection> {
+        if !self.is_measured() {
+            return None;
+        }
+        let center_x = self.center.0.clamp(-max_center_offset, max_center_offset);
+        let center_y = self.center.1.clamp(-max_center_offset, max_center_offset);
+
+        // Any offset that could not be compensated by moving the center has
+        // to be covered by the deadzone.
+        let residual = (self.center.0 - center_x)
+            .abs()
+            .max((self.center.1 - center_y).abs());
+        let deadzone = (residual + 3.0 * self.noise).clamp(0.0, max_deadzone);
+
+        Some(DriftCorrection {
+            center_x,
+            center_y,
+            deadzone,
+        })
+    }
+}
+
+/// Apply the given deadzone to a centered axis value and rescale the
+/// remaining range to -1.0 to 1.0.
+fn apply_deadzone(value: f64, deadzone: f64) -> f64 {
+    if value.abs() <= deadzone {
+        return 0.0;
+    }
+    if deadzone >= 1.0 {
+        return 0.0;
+    }
+    let scaled = (value.abs() - deadzone) / (1.0 - deadzone);
+    (scaled * value.signum()).clamp(-1.0, 1.0)
+}
@@ -0,0 +1,100 @@
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadAxis},
+    composite_device::drift::{DriftCorrection, DriftCorrections, DriftDetector},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+fn stick_event(axis: GamepadAxis, x: f64, y: f64) -> NativeEvent {
+    NativeEvent::new(
+        Capability::Gamepad(Gamepad::Axis(axis)),
+        InputValue::Vector2 {
+            x: Some(x),
+            y: Some(y),
+        },
+    )
+}
+
+#[test]
+fn test_drift_detection() {
+    let mut detector = DriftDetector::new(0.1, 0.2);
+    assert_eq!(detector.severity(), 0.0);
+
+    // Left stick rests slightly off center with some noise
+    for i in 0..400 {
+        let noise = if i % 2 == 0 { 0.005 } else { -0.005 };
+        let event = stick_event(GamepadAxis::LeftStick, 0.05 + noise, -0.03);
+        detector.record(&event);
+    }
+
+    // Movement of the stick should not be counted as resting samples
+    let event = stick_event(GamepadAxis::LeftStick, 0.9, 0.0);
+    assert!(!detector.record(&event));
+
+    let suggestions = detector.suggestions();
+    assert!(suggestions.right_stick.is_none());
+    let correction = suggestions.left_stick.unwrap();
+    assert!((correction.center_x - 0.05).abs() < 0.01);
+    assert!((correction.center_y + 0.03).abs() < 0.01);
+    assert!(correction.deadzone > 0.0 && correction.deadzone < 0.05);
+
+    let severity = detector.severity();
+    assert!(severity > 0.0 && severity < 1.0);
+
+    detector.reset();
+    assert_eq!(detector.severity(), 0.0);
+    assert!(detector.suggestions().left_stick.is_none());
+}
+
+#[test]
+fn test_drift_correction_bounds() {
+    let mut detector = DriftDetector::new(0.1, 0.2);
+    for _ in 0..400 {
+        let event = stick_event(GamepadAxis::RightStick, 0.15, 0.0);
+        detector.record(&event);
+    }
+
+    // The center offset is limited and the rest is covered by the deadzone
+    let correction = detector.suggestions().right_stick.unwrap();
+    assert!((correction.center_x - 0.1).abs() < f64::EPSILON);
+    assert!((correction.deadzone - 0.05).abs() < 0.001);
+    assert!(detector.severity() > 0.7);
+}
+
+#[test]
+fn test_drift_correction_apply() {
+    let corrections = DriftCorrections {
+        left_stick: Some(DriftCorrection {
+            center_x: 0.1,
+            center_y: 0.0,
+            deadzone: 0.1,
+        }),
+        right_stick: None,
+    };
+
+    // Drifting resting position is corrected to zero
+    let mut event = stick_event(GamepadAxis::LeftStick, 0.15, 0.05);
+    corrections.apply(&mut event);
+    let InputValue::Vector2 { x, y } = event.get_value() else {
+        panic!("Expected Vector2 value");
+    };
+    assert_eq!(x, Some(0.0));
+    assert_eq!(y, Some(0.0));
+
+    // Full deflection still reaches the end of the range
+    let mut event = stick_event(GamepadAxis::LeftStick, -1.0, 1.0);
+    corrections.apply(&mut event);
+    let InputValue::Vector2 { x, y } = event.get_value() else {
+        panic!("Expected Vector2 value");
+    };
+    assert_eq!(x, Some(-1.0));
+    assert_eq!(y, Some(1.0));
+
+    // Sticks without a correction are left unchanged
+    let mut event = stick_event(GamepadAxis::RightStick, 0.15, 0.05);
+    corrections.apply(&mut event);
+    let InputValue::Vector2 { x, y } = event.get_value() else {
+        panic!("Expected Vector2 value");
+    };
+    assert_eq!(x, Some(0.15));
+    assert_eq!(y, Some(0.05));
+}
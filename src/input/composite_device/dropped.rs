@@ -0,0 +1,96 @@
+//! Counters of events that were dropped before reaching a target device. The
+//! counts are broken down by the reason each event was dropped and by its
+//! capability to help find out where an input stopped being processed.
+use std::{collections::HashMap, sync::Mutex};
+
+use crate::input::capability::Capability;
+
+/// The reason an event was dropped by a composite device
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DropReason {
+    /// The event capability is not implemented
+    NotImplemented,
+    /// The device profile translated the event into no events
+    Filtered,
+    /// The event did not change the state of an already active input
+    Duplicate,
+    /// The event was captured by intercept mode
+    Intercepted,
+    /// The event came from a blocked source device
+    BlockedSource,
+    /// The composite device is inhibited
+    Inhibited,
+    /// No target device is capable of handling the event
+    NoTarget,
+    /// The touchscreen mode blocks touchscreen input
+    TouchscreenBlocked,
+}
+
+impl DropReason {
+    /// Returns the name of the drop reason
+    pub fn as_str(&self) -> &str {
+        match self {
+            DropReason::NotImplemented => "not_implemented",
+            DropReason::Filtered => "filtered",
+            DropReason::Duplicate => "duplicate",
+            DropReason::Intercepted => "intercepted",
+            DropReason::BlockedSource => "blocked_source",
+            DropReason::Inhibited => "inhibited",
+            DropReason::NoTarget => "no_target",
+            DropReason::TouchscreenBlocked => "touchscreen_blocked",
+        }
+    }
+}
+
+/// Counts of dropped events by reason and capability. Counters can be updated
+/// from a shared reference.
+#[derive(Debug, Default)]
+pub struct DroppedEvents {
+    counts: Mutex<HashMap<DropReason, HashMap<Capability, u64>>>,
+}
+
+impl DroppedEvents {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that an event with the given capability was dropped for the
+    /// given reason
+    pub fn record(&self, reason: DropReason, cap: &Capability) {
+        let Ok(mut counts) = self.counts.lock() else {
+            return;
+        };
+        let count = counts
+            .entry(reason)
+            .or_default()
+            .entry(cap.clone())
+            .or_default();
+        *count = count.saturating_add(1);
+    }
+
+    /// Returns the total number of events dropped for the given reason
+    pub fn count(&self, reason: DropReason) -> u64 {
+        let Ok(counts) = self.counts.lock() else {
+            return 0;
+        };
+        counts
+            .get(&reason)
+            .map(|caps| caps.values().sum())
+            .unwrap_or_default()
+    }
+
+    /// Returns the counts of dropped events for each reason and capability
+    pub fn counts(&self) -> HashMap<DropReason, HashMap<Capability, u64>> {
+        let Ok(counts) = self.counts.lock() else {
+            return HashMap::new();
+        };
+        counts.clone()
+    }
+
+    /// Reset all counters
+    pub fn reset(&self) {
+        if let Ok(mut counts) = self.counts.lock() {
+            counts.clear();
+        }
+    }
+}
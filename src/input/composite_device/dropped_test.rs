@@ -0,0 +1,26 @@
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadButton},
+    composite_device::dropped::{DropReason, DroppedEvents},
+};
+
+#[test]
+fn test_dropped_events() {
+    let dropped = DroppedEvents::new();
+    let south = Capability::Gamepad(Gamepad::Button(GamepadButton::South));
+    let guide = Capability::Gamepad(Gamepad::Button(GamepadButton::Guide));
+
+    dropped.record(DropReason::NoTarget, &south);
+    dropped.record(DropReason::NoTarget, &south);
+    dropped.record(DropReason::NoTarget, &guide);
+    dropped.record(DropReason::Intercepted, &guide);
+    assert_eq!(dropped.count(DropReason::NoTarget), 3);
+    assert_eq!(dropped.count(DropReason::Intercepted), 1);
+    assert_eq!(dropped.count(DropReason::BlockedSource), 0);
+
+    let counts = dropped.counts();
+    assert_eq!(counts[&DropReason::NoTarget][&south], 2);
+    assert_eq!(counts[&DropReason::NoTarget][&guide], 1);
+
+    dropped.reset();
+    assert!(dropped.counts().is_empty());
+}
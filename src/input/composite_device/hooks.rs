@@ -0,0 +1,121 @@
+//! Execution of configured suspend and resume hook actions
+use std::{error::Error, time::Duration};
+
+use serde::Serialize;
+use tokio::process::Command;
+use zbus::{zvariant::DynamicType, Connection};
+
+use crate::config::{DBusMethodCall, HookAction, PowerHooks};
+
+/// Default maximum time to wait for each hook action
+const DEFAULT_TIMEOUT: Duration = Duration::from_millis(5000);
+
+/// Maximum number of arguments supported in a DBus hook call
+pub const MAX_DBUS_ARGS: usize = 4;
+
+/// System power state change that hooks can be executed for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerEvent {
+    Suspend,
+    Resume,
+}
+
+/// Execute all hook actions configured for the given power event in order.
+/// Failures are logged and do not stop the remaining actions.
+pub async fn run_power_hooks(conn: &Connection, hooks: &PowerHooks, event: PowerEvent) {
+    let actions = match event {
+        PowerEvent::Suspend => hooks.suspend.as_ref(),
+        PowerEvent::Resume => hooks.resume.as_ref(),
+    };
+    let Some(actions) = actions else {
+        return;
+    };
+    let timeout = hooks
+        .timeout_ms
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_TIMEOUT);
+
+    for action in actions {
+        log::debug!("Running {event:?} hook: {action:?}");
+        match tokio::time::timeout(timeout, run_hook_action(conn, action)).await {
+            Ok(Ok(())) => (),
+            Ok(Err(e)) => log::error!("Failed to run {event:?} hook {action:?}: {e:?}"),
+            Err(_) => log::error!("Timed out running {event:?} hook: {action:?}"),
+        }
+    }
+}
+
+/// Execute a single hook action
+pub async fn run_hook_action(
+    conn: &Connection,
+    action: &HookAction,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if let Some(command) = action.command.as_ref() {
+        run_command(command).await?;
+    }
+    if let Some(call) = action.dbus.as_ref() {
+        call_dbus_method(conn, call).await?;
+    }
+
+    Ok(())
+}
+
+/// Run the given program and arguments, returning an error if it exits with a
+/// non-zero status.
+pub async fn run_command(command: &[String]) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let Some((program, args)) = command.split_first() else {
+        return Err("Hook command is empty".into());
+    };
+    let output = Command::new(program)
+        .args(args)
+        .kill_on_drop(true)
+        .output()
+        .await?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "'{program}' exited with {}: {}",
+            output.status,
+            stderr.trim()
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Call the given DBus method with its string arguments
+async fn call_dbus_method(
+    conn: &Connection,
+    call: &DBusMethodCall,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let args = call.args.clone().unwrap_or_default();
+    match args.as_slice() {
+        [] => call_with_body(conn, call, &()).await,
+        [a] => call_with_body(conn, call, &(a,)).await,
+        [a, b] => call_with_body(conn, call, &(a, b)).await,
+        [a, b, c] => call_with_body(conn, call, &(a, b, c)).await,
+        [a, b, c, d] => call_with_body(conn, call, &(a, b, c, d)).await,
+        _ => Err(format!("DBus hook calls support at most {MAX_DBUS_ARGS} arguments").into()),
+    }
+}
+
+async fn call_with_body<B>(
+    conn: &Connection,
+    call: &DBusMethodCall,
+    body: &B,
+) -> Result<(), Box<dyn Error + Send + Sync>>
+where
+    B: Serialize + DynamicType,
+{
+    conn.call_method(
+        Some(call.destination.as_str()),
+        call.path.as_str(),
+        Some(call.interface.as_str()),
+        call.method.as_str(),
+        body,
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,8 @@
+use crate::input::composite_device::hooks::run_command;
+
+#[tokio::test]
+async fn test_run_command() {
+    assert!(run_command(&["true".to_string()]).await.is_ok());
+    assert!(run_command(&["false".to_string()]).await.is_err());
+    assert!(run_command(&[]).await.is_err());
+}
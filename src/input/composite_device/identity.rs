@@ -0,0 +1,127 @@
+//! Stable identifiers for composite devices. Unlike the DBus path of a
+//! composite device (e.g. "CompositeDevice0"), which depends on the order in
+//! which devices are discovered, these identifiers stay the same between
+//! boots.
+use crate::udev::device::UdevDevice;
+
+/// FNV-1a 128-bit offset basis
+const FNV_OFFSET: u128 = 0x6c62272e07bb014262b821756295c58d;
+/// FNV-1a 128-bit prime
+const FNV_PRIME: u128 = 0x0000000001000000000000000000013b;
+
+/// Returns the serial of the physical device the given source device belongs
+/// to, so all source devices of the same physical device (e.g. its hidraw and
+/// evdev nodes) get the same serial regardless of which is discovered first.
+/// See [select_serial] for the order in which serials are used.
+pub fn canonical_serial(device: &UdevDevice) -> String {
+    let (usb_serial, hid_uniq) = match device.get_device() {
+        Ok(device) => (usb_serial(&device), hid_uniq(&device)),
+        Err(_) => (None, None),
+    };
+    select_serial(
+        usb_serial.as_deref(),
+        hid_uniq.as_deref(),
+        device.uniq().as_str(),
+        device.id_vendor(),
+        device.id_product(),
+    )
+}
+
+/// Returns the serial number of the USB device the given device stems from
+fn usb_serial(device: &::udev::Device) -> Option<String> {
+    let usb_device = device
+        .parent_with_subsystem_devtype("usb", "usb_device")
+        .ok()??;
+    let serial = usb_device.attribute_value("serial")?;
+    Some(serial.to_string_lossy().to_string())
+}
+
+/// Returns the uniq value (e.g. the bluetooth address) of the HID device the
+/// given device stems from
+fn hid_uniq(device: &::udev::Device) -> Option<String> {
+    let hid_device = device.parent_with_subsystem("hid").ok()??;
+    let uniq = hid_device.property_value("HID_UNIQ")?;
+    Some(uniq.to_string_lossy().to_string())
+}
+
+/// Select the canonical serial of a physical device. The serial number of the
+/// parent USB device is preferred, then the uniq value of the parent HID
+/// device, then the uniq property of the source device itself. If none are
+/// set, the vendor and product id are used. Serials are normalized with
+/// [normalize_serial]. E.g. "a0ab51c1d2e3", "054c:0ce6"
+pub fn select_serial(
+    usb_serial: Option<&str>,
+    hid_uniq: Option<&str>,
+    uniq: &str,
+    vendor_id: u16,
+    product_id: u16,
+) -> String {
+    [usb_serial, hid_uniq, Some(uniq)]
+        .into_iter()
+        .flatten()
+        .find_map(normalize_serial)
+        .unwrap_or_else(|| format!("{vendor_id:04x}:{product_id:04x}"))
+}
+
+/// Returns the serial of the given source device that stays the same when the
+/// device is connected over a different transport (e.g. USB and bluetooth),
+/// if any. Unlike [canonical_serial], no vendor and product id fallback is used
+/// since it cannot tell apart identical devices.
+pub fn transport_serial(device: &UdevDevice) -> Option<String> {
+    normalize_serial(device.uniq().as_str())
+        .or_else(|| normalize_serial(device.serial_number().as_str()))
+}
+
+/// Normalize the given serial so that the same serial reported with different
+/// separators or casing can be compared. Returns None if the serial is empty
+/// or only consists of zeros. E.g. "A0:AB:51:C1:D2:E3" -> "a0ab51c1d2e3"
+pub fn normalize_serial(serial: &str) -> Option<String> {
+    let serial: String = serial
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    if serial.chars().all(|c| c == '0') {
+        return None;
+    }
+    Some(serial)
+}
+
+/// Returns a stable UUID derived from the given config name and source device
+/// serials. The index is used to tell apart multiple composite devices that
+/// would otherwise get the same UUID (e.g. identical gamepads without a
+/// serial number). E.g. "3f2b8e1c-5d0a-8c4e-9b71-0a6d2e4f8c13"
+pub fn composite_uuid(config_name: &str, serials: &[String], index: u32) -> String {
+    let mut hash = FNV_OFFSET;
+    let mut write = |bytes: &[u8]| {
+        for byte in bytes {
+            hash ^= *byte as u128;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    };
+    write(config_name.as_bytes());
+    for serial in serials {
+        // Separate each value so e.g. ["ab", "c"] and ["a", "bc"] differ
+        write(&[0]);
+        write(serial.as_bytes());
+    }
+    if index > 0 {
+        write(&[0]);
+        write(&index.to_le_bytes());
+    }
+
+    // Mark the UUID as a custom (version 8) RFC 4122 UUID
+    let mut bytes = hash.to_be_bytes();
+    bytes[6] = (bytes[6] & 0x0f) | 0x80;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
@@ -0,0 +1,102 @@
+use crate::input::composite_device::identity::{composite_uuid, normalize_serial, select_serial};
+
+#[test]
+fn test_composite_uuid_format() {
+    let uuid = composite_uuid("Steam Deck", &["28de:1205".to_string()], 0);
+    let parts: Vec<&str> = uuid.split('-').collect();
+    assert_eq!(
+        parts.iter().map(|part| part.len()).collect::<Vec<_>>(),
+        vec![8, 4, 4, 4, 12]
+    );
+    assert!(uuid
+        .chars()
+        .all(|c| c == '-' || c.is_ascii_hexdigit() && !c.is_ascii_uppercase()));
+    assert!(parts[2].starts_with('8'), "UUID should be version 8");
+    assert!(
+        matches!(parts[3].chars().next(), Some('8' | '9' | 'a' | 'b')),
+        "UUID should use the RFC 4122 variant"
+    );
+}
+
+#[test]
+fn test_composite_uuid_stable() {
+    let serials = vec!["a0:ab:51:c1:d2:e3".to_string()];
+    let uuid = composite_uuid("DualSense", &serials, 0);
+    assert_eq!(uuid, composite_uuid("DualSense", &serials, 0));
+    assert_ne!(uuid, composite_uuid("DualSense Edge", &serials, 0));
+    assert_ne!(uuid, composite_uuid("DualSense", &serials, 1));
+    assert_ne!(
+        uuid,
+        composite_uuid("DualSense", &["a0:ab:51:c1:d2:e4".to_string()], 0)
+    );
+    assert_ne!(
+        composite_uuid("A", &["ab".to_string(), "c".to_string()], 0),
+        composite_uuid("A", &["a".to_string(), "bc".to_string()], 0)
+    );
+}
+
+#[test]
+fn test_normalize_serial() {
+    let bluetooth = normalize_serial("A0:AB:51:C1:D2:E3");
+    assert_eq!(bluetooth.as_deref(), Some("a0ab51c1d2e3"));
+    assert_eq!(normalize_serial("a0ab51c1d2e3"), bluetooth);
+    assert_eq!(normalize_serial("a0-ab-51-c1-d2-e3"), bluetooth);
+    assert!(normalize_serial("").is_none());
+    assert!(normalize_serial("00:00:00:00:00:00").is_none());
+}
+
+#[test]
+fn test_select_serial() {
+    // The USB serial is preferred over any uniq value
+    let serial = select_serial(
+        Some("ABC123"),
+        Some("a0:ab:51:c1:d2:e3"),
+        "",
+        0x045e,
+        0x0b12,
+    );
+    assert_eq!(serial, "abc123");
+
+    // Bluetooth devices use the address of the parent HID device
+    let serial = select_serial(None, Some("A0:AB:51:C1:D2:E3"), "", 0x054c, 0x0ce6);
+    assert_eq!(serial, "a0ab51c1d2e3");
+
+    // Empty or zero serials are skipped
+    let serial = select_serial(Some(""), Some("00:00:00:00:00:00"), "a0:ab", 0x054c, 0x0ce6);
+    assert_eq!(serial, "a0ab");
+    let serial = select_serial(None, None, "", 0x054c, 0x0ce6);
+    assert_eq!(serial, "054c:0ce6");
+}
+
+#[test]
+fn test_composite_uuid_siblings() {
+    // A USB gamepad whose driver sets the uniq of its evdev node, while its
+    // vendor hidraw interface has none
+    let hidraw = select_serial(Some("3A0F19C2"), Some(""), "", 0x2dc8, 0x3106);
+    let evdev = select_serial(
+        Some("3A0F19C2"),
+        Some(""),
+        "e4:17:d8:00:11:22",
+        0x2dc8,
+        0x3106,
+    );
+    assert_eq!(
+        composite_uuid("8BitDo", &[hidraw], 0),
+        composite_uuid("8BitDo", &[evdev], 0)
+    );
+
+    // A bluetooth gamepad whose hidraw node has no input device to read the
+    // uniq from
+    let hidraw = select_serial(None, Some("a0:ab:51:c1:d2:e3"), "", 0x054c, 0x0ce6);
+    let evdev = select_serial(
+        None,
+        Some("a0:ab:51:c1:d2:e3"),
+        "a0:ab:51:c1:d2:e3",
+        0x054c,
+        0x0ce6,
+    );
+    assert_eq!(
+        composite_uuid("DualSense", &[hidraw], 0),
+        composite_uuid("DualSense", &[evdev], 0)
+    );
+}
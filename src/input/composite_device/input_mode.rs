@@ -0,0 +1,84 @@
+//! Global input modes that all composite devices can be switched between
+//! with a single call, like a "game" mode with a gamepad target device and a
+//! "desktop" mode with keyboard and mouse emulation.
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+
+use crate::config::{CompositeDeviceConfig, InputModeConfig, TargetDeviceConfig};
+
+/// Global input mode that all composite devices can be switched between with
+/// a single call. Each composite device config defines the device profile and
+/// target devices to use in each mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputMode {
+    /// A fullscreen game is running
+    Game,
+    /// The desktop is controlled with keyboard and mouse emulation
+    Desktop,
+}
+
+impl InputMode {
+    /// Returns the configuration of this mode in the given composite device
+    /// config, if any
+    pub fn get_config(self, config: &CompositeDeviceConfig) -> Option<&InputModeConfig> {
+        let modes = config.options.as_ref()?.input_modes.as_ref()?;
+        match self {
+            InputMode::Game => modes.game.as_ref(),
+            InputMode::Desktop => modes.desktop.as_ref(),
+        }
+    }
+}
+
+impl Display for InputMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputMode::Game => write!(f, "game"),
+            InputMode::Desktop => write!(f, "desktop"),
+        }
+    }
+}
+
+impl FromStr for InputMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "game" => Ok(InputMode::Game),
+            "desktop" => Ok(InputMode::Desktop),
+            _ => Err(format!("Invalid input mode: {s}")),
+        }
+    }
+}
+
+/// Returns the configuration each composite device switches to when the
+/// given input mode is set, sorted by composite device path. Composite
+/// devices whose config does not define the mode keep their current
+/// configuration and are not included.
+pub fn mode_transitions(
+    mode: InputMode,
+    configs: &HashMap<String, CompositeDeviceConfig>,
+) -> Vec<(String, InputModeConfig)> {
+    let mut transitions: Vec<(String, InputModeConfig)> = configs
+        .iter()
+        .filter_map(|(path, config)| Some((path.clone(), mode.get_config(config)?.clone())))
+        .collect();
+    transitions.sort_by(|a, b| a.0.cmp(&b.0));
+    transitions
+}
+
+/// Returns the target devices to create a composite device with. The target
+/// devices of the active input mode, if the config defines it, replace the
+/// given target devices.
+pub fn select_target_devices(
+    mode_config: Option<&InputModeConfig>,
+    target_types: Option<Vec<TargetDeviceConfig>>,
+) -> Option<Vec<TargetDeviceConfig>> {
+    let Some(mode_config) = mode_config else {
+        return target_types;
+    };
+    let target_devices = mode_config
+        .target_devices
+        .iter()
+        .map(|kind| TargetDeviceConfig::Type(kind.clone()))
+        .collect();
+    Some(target_devices)
+}
@@ -0,0 +1,132 @@
+use std::{collections::HashMap, str::FromStr};
+
+use crate::config::{CompositeDeviceConfig, TargetDeviceConfig};
+
+use super::input_mode::{mode_transitions, select_target_devices, InputMode};
+
+fn device_config(name: &str, input_modes: &str) -> CompositeDeviceConfig {
+    let yaml = format!(
+        "version: 1
+kind: CompositeDevice
+name: {name}
+matches: []
+source_devices: []
+options:
+  input_modes:
+{input_modes}
+"
+    );
+    serde_yaml::from_str(&yaml).unwrap()
+}
+
+fn configs() -> HashMap<String, CompositeDeviceConfig> {
+    let both = device_config(
+        "Both",
+        "    game:
+      profile: /usr/share/inputplumber/profiles/game.yaml
+      target_devices: [xb360]
+    desktop:
+      profile: /usr/share/inputplumber/profiles/desktop.yaml
+      target_devices: [keyboard, mouse]",
+    );
+    let game_only = device_config(
+        "Game only",
+        "    game:
+      profile: /usr/share/inputplumber/profiles/other.yaml
+      target_devices: [ds5]",
+    );
+    HashMap::from([
+        ("/CompositeDevice1".to_string(), game_only),
+        ("/CompositeDevice0".to_string(), both),
+    ])
+}
+
+#[test]
+fn test_input_mode_from_str() {
+    for mode in [InputMode::Game, InputMode::Desktop] {
+        assert_eq!(InputMode::from_str(mode.to_string().as_str()), Ok(mode));
+    }
+    assert!(InputMode::from_str("Game").is_err());
+    assert!(InputMode::from_str("").is_err());
+}
+
+#[test]
+fn test_input_mode_profile_selection() {
+    let configs = configs();
+    let both = &configs["/CompositeDevice0"];
+    let game = InputMode::Game.get_config(both).unwrap();
+    assert_eq!(game.profile, "/usr/share/inputplumber/profiles/game.yaml");
+    assert_eq!(game.target_devices, vec!["xb360"]);
+    let desktop = InputMode::Desktop.get_config(both).unwrap();
+    assert_eq!(
+        desktop.profile,
+        "/usr/share/inputplumber/profiles/desktop.yaml"
+    );
+    assert_eq!(desktop.target_devices, vec!["keyboard", "mouse"]);
+
+    // Modes that are not configured are not selected
+    let game_only = &configs["/CompositeDevice1"];
+    assert!(InputMode::Game.get_config(game_only).is_some());
+    assert!(InputMode::Desktop.get_config(game_only).is_none());
+    let yaml = "version: 1\nkind: CompositeDevice\nname: None\nmatches: []\nsource_devices: []\n";
+    let none: CompositeDeviceConfig = serde_yaml::from_str(yaml).unwrap();
+    assert!(InputMode::Game.get_config(&none).is_none());
+}
+
+#[test]
+fn test_input_mode_transitions() {
+    let configs = configs();
+
+    // Switching to game mode changes every device that defines it
+    let transitions = mode_transitions(InputMode::Game, &configs);
+    let profiles: Vec<(&str, &str)> = transitions
+        .iter()
+        .map(|(path, config)| (path.as_str(), config.profile.as_str()))
+        .collect();
+    assert_eq!(
+        profiles,
+        vec![
+            (
+                "/CompositeDevice0",
+                "/usr/share/inputplumber/profiles/game.yaml"
+            ),
+            (
+                "/CompositeDevice1",
+                "/usr/share/inputplumber/profiles/other.yaml"
+            ),
+        ]
+    );
+
+    // Switching to desktop mode leaves devices without a desktop config alone
+    let transitions = mode_transitions(InputMode::Desktop, &configs);
+    assert_eq!(transitions.len(), 1);
+    assert_eq!(transitions[0].0, "/CompositeDevice0");
+    assert_eq!(transitions[0].1.target_devices, vec!["keyboard", "mouse"]);
+
+    // Switching back to game mode restores the game configuration
+    let transitions = mode_transitions(InputMode::Game, &configs);
+    assert_eq!(transitions[0].1.target_devices, vec!["xb360"]);
+}
+
+#[test]
+fn test_input_mode_target_devices() {
+    let configs = configs();
+    let defaults = Some(vec![TargetDeviceConfig::Type("deck".to_string())]);
+
+    // Without an active mode the default target devices are used
+    assert_eq!(select_target_devices(None, defaults.clone()), defaults);
+
+    // The target devices of the active mode replace the defaults
+    let desktop = InputMode::Desktop.get_config(&configs["/CompositeDevice0"]);
+    assert_eq!(
+        select_target_devices(desktop, defaults.clone()),
+        Some(vec![
+            TargetDeviceConfig::Type("keyboard".to_string()),
+            TargetDeviceConfig::Type("mouse".to_string()),
+        ])
+    );
+    assert_eq!(
+        select_target_devices(desktop, None).map(|targets| targets.len()),
+        Some(2)
+    );
+}
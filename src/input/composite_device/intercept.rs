@@ -0,0 +1,67 @@
+//! Validation of the intercept modes a composite device can be switched to.
+use std::collections::HashSet;
+
+use crate::input::capability::Capability;
+
+use super::InterceptMode;
+
+/// Features of a composite device that decide which intercept modes it
+/// supports
+#[derive(Debug)]
+pub struct InterceptSupport<'a> {
+    /// Capabilities of the source devices
+    pub capabilities: &'a HashSet<Capability>,
+    /// Capabilities that must be pressed to activate intercept mode
+    pub activation_caps: &'a [Capability],
+    /// Whether a DBus target device can receive intercepted input
+    pub has_dbus_target: bool,
+    /// Whether a gamepad target device is attached
+    pub has_gamepad_target: bool,
+}
+
+/// Returns an error describing why the given intercept mode cannot be used
+/// with a composite device with the given features.
+pub fn check_intercept_mode(mode: InterceptMode, support: &InterceptSupport) -> Result<(), String> {
+    match mode {
+        InterceptMode::None => Ok(()),
+        InterceptMode::Pass => {
+            // The activation chord must be possible to press on the source
+            // devices, or intercept mode can never be activated.
+            let unsupported: Vec<String> = support
+                .activation_caps
+                .iter()
+                .filter(|cap| !support.capabilities.contains(cap))
+                .map(|cap| format!("{cap:?}"))
+                .collect();
+            if !unsupported.is_empty() {
+                return Err(format!(
+                    "Source devices do not support intercept activation capabilities: {}",
+                    unsupported.join(", ")
+                ));
+            }
+            Ok(())
+        }
+        InterceptMode::Always => {
+            if !support.has_dbus_target {
+                return Err("No DBus target device to receive intercepted input".into());
+            }
+            Ok(())
+        }
+        InterceptMode::GamepadOnly => {
+            if !support.has_dbus_target {
+                return Err("No DBus target device to receive intercepted input".into());
+            }
+            let has_gamepad_input = support
+                .capabilities
+                .iter()
+                .any(|cap| matches!(cap, Capability::Gamepad(_)));
+            if !has_gamepad_input {
+                return Err("Source devices do not support gamepad input".into());
+            }
+            if !support.has_gamepad_target {
+                return Err("No gamepad target device to intercept input from".into());
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::HashSet;
+
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadButton, Keyboard},
+    composite_device::{
+        intercept::{check_intercept_mode, InterceptSupport},
+        InterceptMode,
+    },
+};
+
+fn guide() -> Capability {
+    Capability::Gamepad(Gamepad::Button(GamepadButton::Guide))
+}
+
+#[test]
+fn test_intercept_mode_values() {
+    for mode in InterceptMode::ALL {
+        let value: u32 = mode.into();
+        assert_eq!(InterceptMode::try_from(value), Ok(mode));
+    }
+    assert_eq!(u32::from(InterceptMode::GamepadOnly), 3);
+    assert_eq!(InterceptMode::GamepadOnly.as_str(), "GamepadOnly");
+
+    // Unknown modes are rejected
+    assert!(InterceptMode::try_from(4).is_err());
+    assert!(InterceptMode::try_from(u32::MAX).is_err());
+}
+
+#[test]
+fn test_check_intercept_mode_supported() {
+    let capabilities = HashSet::from([guide()]);
+    let activation_caps = vec![guide()];
+    let support = InterceptSupport {
+        capabilities: &capabilities,
+        activation_caps: &activation_caps,
+        has_dbus_target: true,
+        has_gamepad_target: true,
+    };
+    for mode in InterceptMode::ALL {
+        assert_eq!(check_intercept_mode(mode, &support), Ok(()), "{mode:?}");
+    }
+}
+
+#[test]
+fn test_check_intercept_mode_unsupported() {
+    let keyboard = HashSet::from([Capability::Keyboard(Keyboard::KeyA)]);
+    let activation_caps = vec![guide()];
+
+    // Intercept mode can never be activated without the activation buttons
+    let support = InterceptSupport {
+        capabilities: &keyboard,
+        activation_caps: &activation_caps,
+        has_dbus_target: true,
+        has_gamepad_target: true,
+    };
+    assert!(check_intercept_mode(InterceptMode::Pass, &support).is_err());
+    assert_eq!(check_intercept_mode(InterceptMode::None, &support), Ok(()));
+    assert_eq!(
+        check_intercept_mode(InterceptMode::Always, &support),
+        Ok(())
+    );
+
+    // Only gamepad input can be intercepted in GamepadOnly mode
+    let error = check_intercept_mode(InterceptMode::GamepadOnly, &support).unwrap_err();
+    assert_eq!(error, "Source devices do not support gamepad input");
+
+    // Intercepted input needs a DBus target device
+    let gamepad = HashSet::from([guide()]);
+    let support = InterceptSupport {
+        capabilities: &gamepad,
+        activation_caps: &activation_caps,
+        has_dbus_target: false,
+        has_gamepad_target: true,
+    };
+    assert!(check_intercept_mode(InterceptMode::Always, &support).is_err());
+    assert!(check_intercept_mode(InterceptMode::GamepadOnly, &support).is_err());
+    assert_eq!(check_intercept_mode(InterceptMode::Pass, &support), Ok(()));
+
+    // GamepadOnly mode needs a gamepad target device to intercept from
+    let support = InterceptSupport {
+        capabilities: &gamepad,
+        activation_caps: &activation_caps,
+        has_dbus_target: true,
+        has_gamepad_target: false,
+    };
+    let error = check_intercept_mode(InterceptMode::GamepadOnly, &support).unwrap_err();
+    assert_eq!(error, "No gamepad target device to intercept input from");
+}
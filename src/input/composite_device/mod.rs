@@ -1,13 +1,80 @@
+pub mod bypass;
+#[cfg(test)]
+pub mod bypass_test;
+pub mod calibration;
+#[cfg(test)]
+pub mod calibration_test;
+pub mod chord;
+#[cfg(test)]
+pub mod chord_test;
 pub mod client;
 pub mod command;
+pub mod conditions;
+#[cfg(test)]
+pub mod conditions_test;
+pub mod default_profile;
+#[cfg(test)]
+pub mod default_profile_test;
+pub mod drift;
+#[cfg(test)]
+pub mod drift_test;
+pub mod dropped;
+#[cfg(test)]
+pub mod dropped_test;
+pub mod hooks;
+#[cfg(test)]
+pub mod hooks_test;
+pub mod identity;
+#[cfg(test)]
+pub mod identity_test;
+pub mod input_mode;
+#[cfg(test)]
+pub mod input_mode_test;
+pub mod intercept;
+#[cfg(test)]
+pub mod intercept_test;
+pub mod press;
+#[cfg(test)]
+pub mod press_test;
+pub mod routing;
+#[cfg(test)]
+pub mod routing_test;
+pub mod scheduler;
+#[cfg(test)]
+pub mod scheduler_test;
+pub mod settings;
+pub mod simulator;
+#[cfg(test)]
+pub mod simulator_test;
+pub mod snapshot;
+#[cfg(test)]
+pub mod snapshot_test;
+pub mod statistics;
+#[cfg(test)]
+pub mod statistics_test;
+pub mod system_keys;
+#[cfg(test)]
+pub mod system_keys_test;
+pub mod targets;
+#[cfg(test)]
+pub mod targets_test;
+pub mod touchscreen;
+#[cfg(test)]
+pub mod touchscreen_test;
+pub mod translation;
+#[cfg(test)]
+pub mod translation_test;
 
 use std::{
     borrow::Borrow,
     collections::{BTreeSet, HashMap, HashSet},
     error::Error,
+    time::Instant,
 };
 
-use evdev::InputEvent;
+use evdev::{FFEffectCode, FFEffectData, InputEvent};
+use glob_match::glob_match;
+use packed_struct::types::{Integer, SizedInteger};
 use tokio::{
     sync::mpsc,
     task::{JoinHandle, JoinSet},
@@ -17,26 +84,66 @@ use zbus::Connection;
 
 use crate::{
     config::{
-        path::get_profiles_path, CapabilityMap, CapabilityMapping, CompositeDeviceConfig,
-        DeviceProfile, ProfileMapping,
+        CapabilityMap, CapabilityMapping, CompositeDeviceConfig, DeviceProfile, DockDetection,
+        GlobalConfig, HapticsToRumble, ProfileMapping,
     },
-    dbus::interface::{
-        composite_device::CompositeDeviceInterface, source::iio_imu::SourceIioImuInterface,
+    dbus::{
+        alias::CapabilityAliases,
+        interface::{
+            composite_device::CompositeDeviceInterface, force_feedback::ForceFeedbackInterface,
+            source::iio_imu::SourceIioImuInterface,
+        },
     },
+    drivers::steam_deck::hid_report::{PackedHapticReport, PackedRumbleReport},
     input::{
-        capability::{Capability, Gamepad, GamepadButton, Mouse},
+        capability::{Capability, Gamepad, GamepadButton, Keyboard, Mouse, Switch},
         event::{
-            native::NativeEvent,
-            value::{InputValue, TranslationError},
+            native::{NativeEvent, ScheduledNativeEvent},
+            value::InputValue,
             Event,
         },
-        output_event::UinputOutputEvent,
-        source::{evdev::EventDevice, hidraw::HidRawDevice, iio::IioDevice, SourceDevice},
+        keymap::KeysymTable,
+        output_capability::{Haptic, OutputCapability},
+        output_event::{haptic_to_rumble, scale_ff_effect, scale_rumble, UinputOutputEvent},
+        source::{
+            command::BypassRoutes,
+            evdev::EventDevice,
+            external::ExternalDevice,
+            hidraw::HidRawDevice,
+            iio::IioDevice,
+            watchdog::{self, Abandoned, PendingRestarts},
+            SourceDevice,
+        },
     },
-    udev::{device::UdevDevice, hide_device, unhide_device},
+    udev::{device::UdevDevice, dock, hide_device, unhide_device},
 };
 
-use self::{client::CompositeDeviceClient, command::CompositeCommand};
+use self::{
+    bypass::BypassConditions,
+    calibration::{get_controller_id, get_device_id, Calibration, Calibrator},
+    chord::{chord_timing_met, partial_timeout},
+    client::CompositeDeviceClient,
+    command::CompositeCommand,
+    default_profile::{
+        default_profile_path, load_default_profile, ProfileSource, DEFAULT_PROFILE_RETRY_INTERVAL,
+    },
+    drift::{DriftCorrections, DriftDetector},
+    dropped::{DropReason, DroppedEvents},
+    hooks::PowerEvent,
+    intercept::{check_intercept_mode, InterceptSupport},
+    press::{PressAction, PressKind, PressTimer, PressTimings, PressTracker},
+    routing::capable_targets,
+    scheduler::Scheduler,
+    settings::DeviceSettings,
+    snapshot::{DeviceSnapshot, ProfileSnapshot, SNAPSHOT_VERSION},
+    statistics::InputStatistics,
+    system_keys::{SystemKeyAction, SystemKeyPolicy},
+    targets::{
+        attach_or_stop, profile_target_devices, request_attach, request_stop, same_target_devices,
+    },
+    touchscreen::{TouchMouse, TouchscreenMode},
+    translation::{translated_capabilities, TranslatedEvent, TranslationPool},
+};
 
 use super::{
     manager::ManagerCommand, output_event::OutputEvent, source::client::SourceDeviceClient,
@@ -46,6 +153,15 @@ use super::{
 /// Size of the command channel buffer for processing input events and commands.
 const BUFFER_SIZE: usize = 16384;
 
+/// Default delay in milliseconds between each event of an emitted chord
+const DEFAULT_CHORD_DELAY_MS: u64 = 80;
+
+/// Target device types emulated over USB/IP that must be stopped before the
+/// system suspends and recreated on resume
+const USB_TARGET_TYPES: &[&str] = &["deck", "steam-controller"];
+/// Source device id used for events emitted when the dock state changes
+const DOCK_DEVICE_ID: &str = "dock";
+
 /// The [InterceptMode] defines whether or not inputs should be routed over
 /// DBus instead of to the target devices. This can be used by overlays to
 /// intercept input.
@@ -62,6 +178,68 @@ pub enum InterceptMode {
     GamepadOnly,
 }
 
+impl InterceptMode {
+    /// All available intercept modes
+    pub const ALL: [InterceptMode; 4] = [
+        InterceptMode::None,
+        InterceptMode::Pass,
+        InterceptMode::Always,
+        InterceptMode::GamepadOnly,
+    ];
+
+    /// Returns the name of the intercept mode
+    pub fn as_str(&self) -> &str {
+        match self {
+            InterceptMode::None => "None",
+            InterceptMode::Pass => "Pass",
+            InterceptMode::Always => "Always",
+            InterceptMode::GamepadOnly => "GamepadOnly",
+        }
+    }
+}
+
+impl From<InterceptMode> for u32 {
+    fn from(mode: InterceptMode) -> Self {
+        match mode {
+            InterceptMode::None => 0,
+            InterceptMode::Pass => 1,
+            InterceptMode::Always => 2,
+            InterceptMode::GamepadOnly => 3,
+        }
+    }
+}
+
+impl TryFrom<u32> for InterceptMode {
+    type Error = String;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(InterceptMode::None),
+            1 => Ok(InterceptMode::Pass),
+            2 => Ok(InterceptMode::Always),
+            3 => Ok(InterceptMode::GamepadOnly),
+            _ => Err(format!("Invalid intercept mode: {value}")),
+        }
+    }
+}
+
+/// State saved while a temporarily loaded profile is awaiting confirmation.
+/// If the profile is not confirmed in time, this state is restored.
+#[derive(Debug)]
+struct ProfilePreview {
+    /// Unique id of the preview used to ignore reverts of earlier previews
+    id: u64,
+    /// Name of the profile loaded before the preview
+    profile: Option<String>,
+    /// Source of the profile loaded before the preview
+    source: ProfileSource,
+    /// Profile mappings loaded before the preview
+    config_map: HashMap<Capability, Vec<ProfileMapping>>,
+    /// Target device types before the preview, if the previewed profile
+    /// changes the target devices
+    target_devices: Option<Vec<String>>,
+}
+
 /// A [CompositeDevice] represents any number source input devices that
 /// can translate input to any target devices
 #[derive(Debug)]
@@ -74,6 +252,9 @@ pub struct CompositeDevice {
     config: CompositeDeviceConfig,
     /// Name of the [CompositeDeviceConfig] loaded for the device
     name: String,
+    /// Stable identifier of the device that does not change between boots
+    /// E.g. "3f2b8e1c-5d0a-8c4e-9b71-0a6d2e4f8c13"
+    uuid: String,
     /// Capabilities describe all input capabilities from all source devices
     capabilities: HashSet<Capability>,
     /// Capability mapping for the CompositeDevice
@@ -81,14 +262,37 @@ pub struct CompositeDevice {
     /// Name of the currently loaded [DeviceProfile] for the CompositeDevice.
     /// The [DeviceProfile] is used to translate input events.
     device_profile: Option<String>,
+    /// Where the currently loaded [DeviceProfile] was loaded from
+    profile_source: ProfileSource,
     /// Map of profile source events to translate to one or more profile mapping
     /// configs that define how the source event should be translated.
     device_profile_config_map: HashMap<Capability, Vec<ProfileMapping>>,
+    /// Previous profile state to restore if a temporarily loaded profile is
+    /// not confirmed in time
+    profile_preview: Option<ProfilePreview>,
+    /// Number of profile previews started, used to identify each preview
+    profile_preview_count: u64,
     /// List of input capabilities that can be translated by the capability map
     translatable_capabilities: Vec<Capability>,
     /// List of currently "pressed" actions used to translate multiple input
     /// sequences into a single input event.
     translatable_active_inputs: Vec<Capability>,
+    /// Time that each currently "pressed" translatable input was pressed.
+    /// This is used to apply the chord timing options of capability mappings.
+    translatable_press_times: HashMap<Capability, Instant>,
+    /// List of translatable inputs from incomplete chords that were emitted
+    /// untranslated after the chord timed out.
+    translatable_passthrough_inputs: HashSet<Capability>,
+    /// List of input capabilities used by the conditions of capability map
+    /// mappings
+    condition_capabilities: Vec<Capability>,
+    /// List of currently held inputs used by capability map conditions
+    held_condition_inputs: HashSet<Capability>,
+    /// State of source buttons with short, long, or double press mappings in
+    /// the device profile
+    press_tracker: PressTracker,
+    /// Policy for remapping the power and volume buttons
+    system_keys: SystemKeyPolicy,
     /// List of translated events that were emitted less than 8ms ago. This
     /// is required to support "on release" style buttons on some devices where
     /// a button "up" event will fire immediately after a "down" event upon
@@ -101,18 +305,91 @@ pub struct CompositeDevice {
     dbus_path: String,
     /// Mode defining how inputs should be routed
     intercept_mode: InterceptMode,
+    /// Mode defining how input from the physical touchscreen is emitted
+    touchscreen_mode: TouchscreenMode,
+    /// Converts touchscreen input into mouse input in the 'mouse' touchscreen
+    /// mode
+    touch_mouse: TouchMouse,
+    /// Whether or not events are blocked from being emitted to target devices.
+    /// Input state is still tracked while inhibited.
+    inhibited: bool,
+    /// Identifier used to persist the calibration and settings of the device
+    device_id: String,
+    /// User settings of the device that persist across restarts
+    settings: DeviceSettings,
+    /// Stick and IMU calibration applied to source events
+    calibration: Calibration,
+    /// Collects source events while a calibration is in progress
+    calibrator: Option<Calibrator>,
+    /// Controller ids of source devices that report a unique identifier, by
+    /// source device id. E.g. {"evdev://event0": "a0ab51c1d2e3"}
+    source_controller_ids: HashMap<String, String>,
+    /// Calibration applied to source devices with a controller-specific
+    /// calibration overlay instead of the device calibration, by source
+    /// device id
+    source_calibrations: HashMap<String, Calibration>,
+    /// Source devices that reported calibration samples during the
+    /// calibration in progress
+    calibration_sources: HashSet<String>,
+    /// Observes the resting position of sticks to detect drift. This is only
+    /// set if drift detection is enabled in the config.
+    drift_detector: Option<DriftDetector>,
+    /// Counts of events that were dropped before reaching a target device
+    dropped_events: DroppedEvents,
+    /// Usage statistics of source inputs
+    input_statistics: InputStatistics,
+    /// Stick drift corrections applied to source events
+    drift_corrections: DriftCorrections,
     /// Transmit channel for sending commands to this composite device
     tx: mpsc::Sender<CompositeCommand>,
     /// Receiver channel for listening for commands
     rx: mpsc::Receiver<CompositeCommand>,
+    /// Commands that should be processed at a later time, such as delayed
+    /// chord events
+    scheduler: Scheduler<CompositeCommand>,
+    /// Identifiers of events scheduled with [CompositeCommand::ScheduleEvent]
+    /// that were not cancelled and have not been emitted for the last time
+    scheduled_events: HashSet<u64>,
+    /// Identifier of the last scheduled event
+    scheduled_event_count: u64,
+    /// Identifier of the last rumble started with a scheduled stop
+    rumble_count: u64,
+    /// Identifier of the last rumble started on each source device, so a
+    /// scheduled stop does not cut off rumble that was started after it.
+    source_rumbles: HashMap<String, u64>,
+    /// Scheduled events that auto-fire the target events of held source
+    /// buttons. E.g. {Capability::Gamepad(Gamepad::Button(South)): [1, 2]}
+    auto_fire_events: HashMap<Capability, Vec<u64>>,
+    /// Optional pool of worker threads used to translate source events with
+    /// the device profile outside of the composite device loop
+    translation_pool: Option<TranslationPool>,
+    /// Generation of the device profile loaded in the translation pool. Events
+    /// translated with an older generation are translated again.
+    profile_generation: u64,
     /// Map of source device id to their respective transmitter channel.
     /// E.g. {"evdev://event0": <Sender>}
     source_devices: HashMap<String, SourceDeviceClient>,
+    /// Udev device of each running source device, used to restart source
+    /// devices that are stuck polling
+    source_devices_running: HashMap<String, UdevDevice>,
+    /// Source devices abandoned by the watchdog that are waiting to be
+    /// started again
+    source_restarts: PendingRestarts,
+    /// Time a source device may be stuck polling before it is restarted, or
+    /// [None] if the watchdog is disabled
+    source_watchdog_timeout: Option<Duration>,
     /// Source devices that this composite device will consume.
     source_devices_discovered: Vec<SourceDevice>,
+    /// Map of source device id to the output capabilities that source device
+    /// is able to handle. E.g. {"hidraw://hidraw0": [OutputCapability::ForceFeedback]}
+    source_output_capabilities: HashMap<String, HashSet<OutputCapability>>,
     /// HashSet of source devices that are blocked from passing their input events to target
     /// events.
     source_devices_blocked: HashSet<String>,
+    /// Capabilities of source devices configured with 'raw_passthrough',
+    /// whose input events may be written directly to target devices from the
+    /// source device thread. E.g. {"evdev://event0": [Capability::Keyboard(..)]}
+    source_bypass_capabilities: HashMap<String, Vec<Capability>>,
     /// Physical device path for source devices. E.g. ["/dev/input/event0"]
     source_device_paths: Vec<String>,
     /// All currently running source device threads
@@ -131,12 +408,35 @@ pub struct CompositeDevice {
     /// This is used to block/requeue multiple calls to set_target_devices().
     /// E.g. ["/org/shadowblip/InputPlumber/devices/target/gamepad0"]
     target_devices_queued: HashSet<String>,
+    /// Paths of target devices that were stopped after applying a
+    /// configuration failed while their attachment was still pending. Their
+    /// attachment is ignored when it arrives.
+    target_devices_discarded: HashSet<String>,
     /// List of active target device types (e.g. "deck", "ds5", "xb360") that
     /// were active before system suspend.
     target_devices_suspended: Vec<String>,
+    /// Whether the device is currently connected to a dock
+    docked: bool,
+    /// List of active target device types that were active before the device
+    /// was docked. Only set if 'dock_detection' defines docked target devices.
+    target_devices_undocked: Option<Vec<String>>,
     /// Map of DBusDevice DBus paths to their respective transmitter channel.
     /// E.g. {"/org/shadowblip/InputPlumber/devices/target/dbus0": <Sender>}
     target_dbus_devices: HashMap<String, TargetDeviceClient>,
+    /// Map of capabilities to DBus target devices that can handle them. Used
+    /// to mirror events to DBus target devices when 'clone_output' is enabled.
+    target_dbus_by_capability: HashMap<Capability, HashSet<String>>,
+    /// DBus paths of attached "debug" target devices. All emitted events are
+    /// mirrored to these devices regardless of their capabilities.
+    target_debug_devices: HashSet<String>,
+    /// Map of passthrough keyboard and mouse DBus paths to their respective
+    /// transmitter channel. Events that no other target device can handle
+    /// are written to these devices.
+    /// E.g. {"/org/shadowblip/InputPlumber/devices/target/keyboard0": <Sender>}
+    target_passthrough_devices: HashMap<String, TargetDeviceClient>,
+    /// Map of device capabilities to a list of passthrough target devices
+    /// that implement that capability.
+    target_passthrough_by_capability: HashMap<Capability, HashSet<String>>,
     /// Set of available Force Feedback effect IDs that are not in use
     /// TODO: Just use the keys from ff_effect_id_source_map to determine next id
     ff_effect_ids: BTreeSet<i16>,
@@ -144,6 +444,16 @@ pub struct CompositeDevice {
     /// This mapping maps the composite device effect ids to source device effect ids.
     /// E.g. {3: {"evdev://event0": 6, "evdev://event1": 2}}
     ff_effect_id_source_map: HashMap<i16, HashMap<String, i16>>,
+    /// Original (unscaled) data of uploaded force feedback effects so they can
+    /// be updated when the gain changes.
+    ff_effect_data: HashMap<i16, FFEffectData>,
+    /// Force feedback gain set by games using FF_GAIN events (0 - 0xFFFF)
+    ff_game_gain: u16,
+    /// Whether force feedback is muted because the active application is in
+    /// the list of applications to mute
+    ff_muted: bool,
+    /// Identifier of the application currently in focus
+    active_app: Option<String>,
     /// List of intercept mode activation Capabilities
     intercept_activation_caps: Vec<Capability>,
     /// Capability to send when intercept mode is activated for the first time.
@@ -162,41 +472,145 @@ impl CompositeDevice {
         config: CompositeDeviceConfig,
         device_info: UdevDevice,
         dbus_path: String,
+        uuid: String,
         capability_map: Option<CapabilityMap>,
     ) -> Result<Self, Box<dyn Error>> {
         log::info!("Creating CompositeDevice with config: {}", config.name);
         let (tx, rx) = mpsc::channel(BUFFER_SIZE);
         let name = config.name.clone();
+        let device_id = get_device_id(&device_info);
+        let calibration = match Calibration::load(&device_id) {
+            Ok(calibration) => calibration.unwrap_or_default(),
+            Err(e) => {
+                log::warn!("Failed to load calibration for {device_id}: {e:?}");
+                Calibration::default()
+            }
+        };
+        let settings = match DeviceSettings::load(&device_id) {
+            Ok(settings) => settings.unwrap_or_default(),
+            Err(e) => {
+                log::warn!("Failed to load settings for {device_id}: {e:?}");
+                DeviceSettings::default()
+            }
+        };
+        let drift_detector = config
+            .options
+            .as_ref()
+            .and_then(|options| options.drift_detection.as_ref())
+            .and_then(DriftDetector::from_options);
+        let drift_corrections = settings.drift_corrections.clone().unwrap_or_default();
+        let system_keys = SystemKeyPolicy::new(
+            config
+                .options
+                .as_ref()
+                .and_then(|options| options.system_keys.as_ref()),
+        );
+        let source_watchdog_timeout = config
+            .options
+            .as_ref()
+            .and_then(|options| options.source_watchdog_timeout_ms)
+            .unwrap_or(watchdog::DEFAULT_TIMEOUT_MS);
+        let source_watchdog_timeout =
+            (source_watchdog_timeout > 0).then(|| Duration::from_millis(source_watchdog_timeout));
+        let mut input_statistics = InputStatistics::new();
+        input_statistics.set_enabled(settings.input_statistics.unwrap_or(false));
+        let translation_workers = config
+            .options
+            .as_ref()
+            .and_then(|options| options.translation_workers)
+            .unwrap_or(0);
+        let translation_worker_per_source = config
+            .options
+            .as_ref()
+            .and_then(|options| options.translation_worker_per_source)
+            .unwrap_or(false);
+        let translation_pool = if translation_worker_per_source {
+            log::debug!("Using a translation worker thread per source device");
+            Some(TranslationPool::new_per_source(tx.clone()))
+        } else if translation_workers > 0 {
+            log::debug!("Using {translation_workers} translation worker thread(s)");
+            Some(TranslationPool::new(translation_workers, tx.clone())?)
+        } else {
+            None
+        };
         let mut device = Self {
             conn,
             manager,
             config,
             name,
+            uuid,
             capabilities: HashSet::new(),
             capability_map,
             device_profile: None,
+            profile_source: ProfileSource::Embedded,
             device_profile_config_map: HashMap::new(),
+            profile_preview: None,
+            profile_preview_count: 0,
             translatable_capabilities: Vec::new(),
             translatable_active_inputs: Vec::new(),
+            translatable_press_times: HashMap::new(),
+            translatable_passthrough_inputs: HashSet::new(),
+            condition_capabilities: Vec::new(),
+            held_condition_inputs: HashSet::new(),
+            press_tracker: PressTracker::new(),
+            system_keys,
             translated_recent_events: HashSet::new(),
             emitted_mappings: HashMap::new(),
             dbus_path,
             intercept_mode: InterceptMode::None,
+            touchscreen_mode: TouchscreenMode::default(),
+            touch_mouse: TouchMouse::new(),
+            inhibited: false,
+            device_id,
+            settings,
+            calibration,
+            calibrator: None,
+            source_controller_ids: HashMap::new(),
+            source_calibrations: HashMap::new(),
+            calibration_sources: HashSet::new(),
+            drift_detector,
+            dropped_events: DroppedEvents::new(),
+            input_statistics,
+            drift_corrections,
             tx,
             rx,
+            scheduler: Scheduler::new(),
+            scheduled_events: HashSet::new(),
+            scheduled_event_count: 0,
+            rumble_count: 0,
+            source_rumbles: HashMap::new(),
+            auto_fire_events: HashMap::new(),
+            translation_pool,
+            profile_generation: 0,
             source_devices: HashMap::new(),
+            source_devices_running: HashMap::new(),
+            source_restarts: PendingRestarts::new(),
+            source_watchdog_timeout,
             source_devices_discovered: Vec::new(),
+            source_output_capabilities: HashMap::new(),
             source_devices_blocked: HashSet::new(),
+            source_bypass_capabilities: HashMap::new(),
             source_device_paths: Vec::new(),
             source_device_tasks: JoinSet::new(),
             source_devices_used: Vec::new(),
             target_devices: HashMap::new(),
             target_devices_by_capability: HashMap::new(),
             target_devices_queued: HashSet::new(),
+            target_devices_discarded: HashSet::new(),
             target_devices_suspended: Vec::new(),
+            docked: false,
+            target_devices_undocked: None,
             target_dbus_devices: HashMap::new(),
+            target_dbus_by_capability: HashMap::new(),
+            target_debug_devices: HashSet::new(),
+            target_passthrough_devices: HashMap::new(),
+            target_passthrough_by_capability: HashMap::new(),
             ff_effect_ids: (0..64).collect(),
             ff_effect_id_source_map: HashMap::new(),
+            ff_effect_data: HashMap::new(),
+            ff_game_gain: u16::MAX,
+            ff_muted: false,
+            active_app: None,
             intercept_activation_caps: vec![Capability::Gamepad(Gamepad::Button(
                 GamepadButton::Guide,
             ))],
@@ -210,12 +624,22 @@ impl CompositeDevice {
             device.load_capability_map()?;
         }
 
-        // Load the default profile
-        let profile_dir = get_profiles_path();
-        let profile_path = profile_dir.join("default.yaml");
-        let profile_path = profile_path.to_string_lossy().to_string();
-        let profile = DeviceProfile::from_yaml_file(profile_path)?;
-        device.load_device_profile(profile)?;
+        // Load the default profile. If the profile file is missing, the
+        // embedded default profile is used until the file appears.
+        let (profile, source) = load_default_profile();
+        device.load_device_profile(profile, source)?;
+        if device.profile_source == ProfileSource::Embedded {
+            device.scheduler.schedule(
+                DEFAULT_PROFILE_RETRY_INTERVAL,
+                CompositeCommand::ReloadDefaultProfile,
+            );
+        }
+        if device.source_watchdog_timeout.is_some() {
+            device.scheduler.schedule(
+                watchdog::CHECK_INTERVAL,
+                CompositeCommand::CheckSourceWatchdog,
+            );
+        }
 
         // If a capability map is defined, add those target capabilities to
         // the hashset of implemented capabilities.
@@ -241,19 +665,29 @@ impl CompositeDevice {
         self.dbus_path.as_str()
     }
 
+    /// Returns the stable identifier of the composite device
+    pub fn uuid(&self) -> &str {
+        self.uuid.as_str()
+    }
+
     /// Creates a new instance of the composite device interface on DBus.
     pub async fn listen_on_dbus(&self) -> Result<JoinHandle<()>, Box<dyn Error>> {
         let conn = self.conn.clone();
         let client = self.client();
         let path = String::from(self.dbus_path());
+        let aliases = CapabilityAliases::from_config(&self.config);
         Ok(tokio::spawn(async move {
             log::debug!("Starting dbus interface: {path}");
-            let iface = CompositeDeviceInterface::new(client);
+            let iface = CompositeDeviceInterface::new(client.clone(), aliases);
             if let Err(e) = conn.object_server().at(path.clone(), iface).await {
                 log::debug!("Failed to start dbus interface {path}: {e:?}");
             } else {
                 log::debug!("Started listening on dbus interface: {path}");
             }
+            let iface = ForceFeedbackInterface::new(client);
+            if let Err(e) = conn.object_server().at(path.clone(), iface).await {
+                log::debug!("Failed to start force feedback dbus interface {path}: {e:?}");
+            }
         }))
     }
 
@@ -302,14 +736,47 @@ impl CompositeDevice {
         }
         self.target_devices = targets;
 
+        // Track the DBus target devices by capabilities they have
+        self.update_dbus_target_capabilities().await;
+
+        // Track the passthrough target devices by capabilities they have
+        for (path, target) in self.target_passthrough_devices.iter() {
+            let caps = match target.get_capabilities().await {
+                Ok(caps) => caps,
+                Err(e) => {
+                    log::error!("Failed to get passthrough target capabilities: {e:?}");
+                    continue;
+                }
+            };
+            for cap in caps {
+                self.target_passthrough_by_capability
+                    .entry(cap)
+                    .or_default()
+                    .insert(path.clone());
+            }
+        }
+
+        // Emit the initial dock state
+        if self.dock_detection().is_some() {
+            self.capabilities.insert(Capability::Switch(Switch::Docked));
+            self.update_docked().await;
+        }
+
         // Loop and listen for command events
         log::debug!("CompositeDevice started");
         let mut buffer = Vec::with_capacity(BUFFER_SIZE);
         'main: loop {
-            let num = self.rx.recv_many(&mut buffer, BUFFER_SIZE).await;
-            if num == 0 {
-                log::warn!("Unable to receive more commands. Channel closed.");
-                break;
+            tokio::select! {
+                num = self.rx.recv_many(&mut buffer, BUFFER_SIZE) => {
+                    if num == 0 {
+                        log::warn!("Unable to receive more commands. Channel closed.");
+                        break;
+                    }
+                }
+                _ = self.scheduler.wait() => {
+                    self.scheduler
+                        .drain_due(tokio::time::Instant::now(), &mut buffer);
+                }
             }
             let mut devices_removed = false;
             //log::trace!("Received {num} command(s)");
@@ -346,17 +813,60 @@ impl CompositeDevice {
                             log::error!("Failed to send target capabilities: {:?}", e);
                         }
                     }
+                    CompositeCommand::GetTranslatedCapabilities(sender) => {
+                        let caps = match self.get_translated_capabilities().await {
+                            Ok(caps) => caps,
+                            Err(e) => {
+                                log::error!("Failed to get translated capabilities: {e:?}");
+                                continue;
+                            }
+                        };
+                        if let Err(e) = sender.send(caps).await {
+                            log::error!("Failed to send translated capabilities: {:?}", e);
+                        }
+                    }
                     CompositeCommand::SetInterceptMode(mode) => self.set_intercept_mode(mode).await,
+                    CompositeCommand::SetInterceptModeChecked(mode, force, sender) => {
+                        let result = match force {
+                            true => Ok(()),
+                            false => self.check_intercept_mode(mode),
+                        };
+                        if result.is_ok() {
+                            self.set_intercept_mode(mode).await;
+                        }
+                        if let Err(e) = sender.send(result).await {
+                            log::error!("Failed to send intercept mode result: {:?}", e);
+                        }
+                    }
                     CompositeCommand::GetInterceptMode(sender) => {
                         if let Err(e) = sender.send(self.intercept_mode.clone()).await {
                             log::error!("Failed to send intercept mode: {:?}", e);
                         }
                     }
+                    CompositeCommand::GetTouchscreenMode(sender) => {
+                        if let Err(e) = sender.send(self.touchscreen_mode).await {
+                            log::error!("Failed to send touchscreen mode: {:?}", e);
+                        }
+                    }
                     CompositeCommand::GetConfig(sender) => {
                         if let Err(e) = sender.send(self.config.clone()).await {
                             log::error!("Failed to send config: {e:?}");
                         }
                     }
+                    CompositeCommand::SetSourceDeviceBlocked(device_id, blocked, sender) => {
+                        let result = self.set_source_device_blocked(device_id, blocked).await;
+                        if let Err(e) = sender.send(result).await {
+                            log::error!("Failed to send source device blocked result: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::GetBlockedSourceDevices(sender) => {
+                        let mut ids: Vec<String> =
+                            self.source_devices_blocked.iter().cloned().collect();
+                        ids.sort();
+                        if let Err(e) = sender.send(ids).await {
+                            log::error!("Failed to send blocked source devices: {:?}", e);
+                        }
+                    }
                     CompositeCommand::GetSourceDevicePaths(sender) => {
                         if let Err(e) = sender.send(self.get_source_device_paths()).await {
                             log::error!("Failed to send source device paths: {:?}", e);
@@ -403,6 +913,12 @@ impl CompositeDevice {
                             log::error!("Failed to set target devices: {e:?}");
                         }
                     }
+                    CompositeCommand::SetTargetLoadout(name, sender) => {
+                        let result = self.set_target_loadout(name).await;
+                        if let Err(e) = sender.send(result).await {
+                            log::error!("Failed to send set target loadout result: {:?}", e);
+                        }
+                    }
                     CompositeCommand::AttachTargetDevices(targets) => {
                         if let Err(e) = self.attach_target_devices(targets).await {
                             log::error!("Failed to attach target devices: {e:?}");
@@ -414,15 +930,42 @@ impl CompositeDevice {
                             log::error!("Failed to send device name: {:?}", e);
                         }
                     }
+                    CompositeCommand::GetUuid(sender) => {
+                        let uuid = self.uuid.clone();
+                        if let Err(e) = sender.send(uuid).await {
+                            log::error!("Failed to send device uuid: {:?}", e);
+                        }
+                    }
                     CompositeCommand::GetProfileName(sender) => {
                         let profile_name = self.device_profile.clone().unwrap_or_default();
                         if let Err(e) = sender.send(profile_name).await {
                             log::error!("Failed to send profile name: {:?}", e);
                         }
                     }
-                    CompositeCommand::LoadProfileFromYaml(profile, sender) => {
-                        log::debug!("Loading profile from yaml: {profile}");
-                        let profile = match DeviceProfile::from_yaml(profile) {
+                    CompositeCommand::GetProfileSource(sender) => {
+                        let source = self.profile_source.clone();
+                        if let Err(e) = sender.send(source).await {
+                            log::error!("Failed to send profile source: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::CheckSourceWatchdog => {
+                        self.check_source_watchdog().await;
+                    }
+                    CompositeCommand::RestartSourceDevice(device_id) => {
+                        // The source device may have been removed meanwhile
+                        if !self.source_restarts.is_pending(&device_id) {
+                            continue;
+                        }
+                        if let Err(e) = self.restart_source_device(device_id.clone()).await {
+                            log::error!("Failed to restart source device {device_id}: {e:?}");
+                        }
+                    }
+                    CompositeCommand::ReloadDefaultProfile => {
+                        self.reload_default_profile();
+                    }
+                    CompositeCommand::LoadProfileFromYaml(yaml, sender) => {
+                        log::debug!("Loading profile from yaml: {yaml}");
+                        let profile = match DeviceProfile::from_yaml(yaml.clone()) {
                             Ok(p) => p,
                             Err(e) => {
                                 if let Err(er) = sender.send(Err(e.to_string().into())).await {
@@ -431,7 +974,8 @@ impl CompositeDevice {
                                 continue;
                             }
                         };
-                        let result = match self.load_device_profile(profile) {
+                        let source = ProfileSource::Yaml(yaml);
+                        let result = match self.load_device_profile(profile, source) {
                             Ok(_) => Ok(()),
                             Err(e) => Err(e.to_string()),
                         };
@@ -441,6 +985,7 @@ impl CompositeDevice {
                     }
                     CompositeCommand::LoadProfilePath(path, sender) => {
                         log::debug!("Loading profile from path: {path}");
+                        let source = ProfileSource::File(path.clone());
                         let profile = match DeviceProfile::from_yaml_file(path) {
                             Ok(p) => p,
                             Err(e) => {
@@ -450,7 +995,16 @@ impl CompositeDevice {
                                 continue;
                             }
                         };
-                        let result = match self.load_device_profile(profile) {
+                        let result = match self.load_device_profile(profile, source) {
+                            Ok(_) => Ok(()),
+                            Err(e) => Err(e.to_string()),
+                        };
+                        if let Err(e) = sender.send(result).await {
+                            log::error!("Failed to send load profile result: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::LoadProfileTemporary(path, timeout_secs, sender) => {
+                        let result = match self.load_profile_temporary(path, timeout_secs).await {
                             Ok(_) => Ok(()),
                             Err(e) => Err(e.to_string()),
                         };
@@ -458,7 +1012,46 @@ impl CompositeDevice {
                             log::error!("Failed to send load profile result: {:?}", e);
                         }
                     }
-                    CompositeCommand::WriteEvent(event) => {
+                    CompositeCommand::ConfirmProfile(sender) => {
+                        let result = self.confirm_profile();
+                        if let Err(e) = sender.send(result).await {
+                            log::error!("Failed to send confirm profile result: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::RevertProfilePreview(id) => {
+                        self.revert_profile_preview(id);
+                    }
+                    CompositeCommand::PressTimer(timer) => {
+                        if let Err(e) = self.handle_press_timer(timer).await {
+                            log::error!("Failed to handle press timer: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::SystemKeyTimer(id) => {
+                        if let Err(e) = self.handle_system_key_timer(id).await {
+                            log::error!("Failed to handle system key timer: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::ScheduleEvent(event, sender) => {
+                        let id = self.schedule_event(event);
+                        if let Err(e) = sender.send(id).await {
+                            log::error!("Failed to send scheduled event id: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::CancelScheduledEvent(id) => {
+                        self.cancel_scheduled_event(id);
+                    }
+                    CompositeCommand::EmitScheduledEvent(id, event) => {
+                        if let Err(e) = self.emit_scheduled_event(id, event).await {
+                            log::error!("Failed to write scheduled event: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::StopRumble(id, source_ids) => {
+                        self.stop_rumble(id, source_ids).await;
+                    }
+                    CompositeCommand::WriteEvent(mut event) => {
+                        // Scheduled events are delayed on purpose, so only
+                        // measure their latency from when they are due.
+                        event.set_timestamp(Instant::now());
                         if let Err(e) = self.write_event(event).await {
                             log::error!("Failed to write event: {:?}", e);
                         }
@@ -478,9 +1071,157 @@ impl CompositeDevice {
                             log::error!("Failed to write event: {:?}", e);
                         }
                     }
+                    CompositeCommand::HandleTranslatedEvents(results) => {
+                        for result in results {
+                            if let Err(e) = self.handle_translated_event(result).await {
+                                log::error!("Failed to write translated event: {:?}", e);
+                            }
+                        }
+                    }
+                    CompositeCommand::UpdateDocked => {
+                        self.update_docked().await;
+                    }
+                    CompositeCommand::UpdateSourceBypass => {
+                        self.update_source_bypass().await;
+                    }
+                    CompositeCommand::UpdateSourceCapabilities(device_id, capabilities) => {
+                        self.update_source_capabilities(device_id, capabilities)
+                            .await;
+                    }
+                    CompositeCommand::RecreateTargetDevices => {
+                        self.handle_session_changed().await;
+                    }
                     CompositeCommand::RemoveRecentEvent(cap) => {
                         self.translated_recent_events.remove(&cap);
                     }
+                    CompositeCommand::GetFFGain(sender) => {
+                        let gain = self.settings.ff_gain.unwrap_or(100);
+                        if let Err(e) = sender.send(gain).await {
+                            log::error!("Failed to send FF gain: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::SetFFGain(gain) => self.set_ff_gain(gain).await,
+                    CompositeCommand::GetFFMuteApps(sender) => {
+                        if let Err(e) = sender.send(self.ff_mute_apps()).await {
+                            log::error!("Failed to send FF mute apps: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::SetFFMuteApps(apps) => self.set_ff_mute_apps(apps).await,
+                    CompositeCommand::GetFFMuted(sender) => {
+                        if let Err(e) = sender.send(self.ff_muted).await {
+                            log::error!("Failed to send FF muted: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::SetActiveApp(app) => self.set_active_app(app).await,
+                    CompositeCommand::GetHapticsToRumble(sender) => {
+                        if let Err(e) = sender.send(self.haptics_to_rumble()).await {
+                            log::error!("Failed to send haptics to rumble options: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::SetHapticsToRumble(options) => {
+                        self.set_haptics_to_rumble(options)
+                    }
+                    CompositeCommand::TestRumble(intensity, duration, sender) => {
+                        let count = self.test_rumble(intensity, duration).await;
+                        if let Err(e) = sender.send(count).await {
+                            log::error!("Failed to send test rumble result: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::StartCalibration => {
+                        log::info!("Starting calibration for: {dbus_path}");
+                        self.calibrator = Some(Calibrator::new());
+                        self.calibration_sources.clear();
+                        self.update_source_bypass().await;
+                    }
+                    CompositeCommand::FinishCalibration(sender) => {
+                        let result = self.finish_calibration().map_err(|e| e.to_string());
+                        self.update_source_bypass().await;
+                        if let Err(e) = sender.send(result).await {
+                            log::error!("Failed to send calibration result: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::GetDriftSeverity(sender) => {
+                        if let Err(e) = sender.send(self.drift_severity()).await {
+                            log::error!("Failed to send drift severity: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::GetDriftSuggestions(sender) => {
+                        if let Err(e) = sender.send(self.drift_suggestions()).await {
+                            log::error!("Failed to send drift suggestions: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::GetDriftAutoApply(sender) => {
+                        if let Err(e) = sender.send(self.drift_auto_apply()).await {
+                            log::error!("Failed to send drift auto apply: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::SetDriftAutoApply(auto_apply) => {
+                        self.set_drift_auto_apply(auto_apply)
+                    }
+                    CompositeCommand::ApplyDriftCorrection(sender) => {
+                        let result = self.apply_drift_correction().map_err(|e| e.to_string());
+                        if let Err(e) = sender.send(result).await {
+                            log::error!("Failed to send apply drift correction result: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::ClearDriftCorrection => self.clear_drift_correction(),
+                    CompositeCommand::GetDroppedEvents(sender) => {
+                        if let Err(e) = sender.send(self.dropped_events.counts()).await {
+                            log::error!("Failed to send dropped events: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::ResetDroppedEvents => self.dropped_events.reset(),
+                    CompositeCommand::GetInputStatistics(sender) => {
+                        if let Err(e) = sender.send(self.input_statistics.statistics()).await {
+                            log::error!("Failed to send input statistics: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::GetInputStatisticsEnabled(sender) => {
+                        if let Err(e) = sender.send(self.input_statistics.is_enabled()).await {
+                            log::error!("Failed to send input statistics enabled: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::SetInputStatisticsEnabled(enabled) => {
+                        self.set_input_statistics_enabled(enabled).await;
+                    }
+                    CompositeCommand::ResetInputStatistics => self.input_statistics.reset(),
+                    CompositeCommand::ChordTimeout(event, pressed_at) => {
+                        if let Err(e) = self.handle_chord_timeout(event, pressed_at).await {
+                            log::error!("Failed to handle chord timeout: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::GetSnapshot(sender) => {
+                        let result = self.snapshot().await.map_err(|e| e.to_string());
+                        if let Err(e) = sender.send(result).await {
+                            log::error!("Failed to send snapshot: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::RestoreSnapshot(snapshot, sender) => {
+                        let result = self
+                            .restore_snapshot(snapshot)
+                            .await
+                            .map_err(|e| e.to_string());
+                        if let Err(e) = sender.send(result).await {
+                            log::error!("Failed to send restore snapshot result: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::ApplyConfiguration(profile_path, device_types, sender) => {
+                        let result = self
+                            .apply_configuration(profile_path, device_types)
+                            .await
+                            .map_err(|e| e.to_string());
+                        if let Err(e) = sender.send(result).await {
+                            log::error!("Failed to send apply configuration result: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::SetInhibited(inhibited) => {
+                        self.set_inhibited(inhibited).await
+                    }
+                    CompositeCommand::SetTouchscreenMode(mode) => {
+                        if let Err(e) = self.set_touchscreen_mode(mode).await {
+                            log::error!("Failed to set touchscreen mode: {e:?}");
+                        }
+                    }
                     CompositeCommand::SetInterceptActivation(activation_caps, target_cap) => {
                         self.set_intercept_activation(activation_caps, target_cap)
                     }
@@ -528,6 +1269,12 @@ impl CompositeDevice {
                 log::error!("Failed to stop dbus device {path}: {e:?}");
             }
         }
+        for (path, target) in &self.target_passthrough_devices {
+            log::debug!("Stopping passthrough target device: {path}");
+            if let Err(e) = target.stop().await {
+                log::error!("Failed to stop passthrough device {path}: {e:?}");
+            }
+        }
 
         // Unhide all source devices
         for source_path in self.source_device_paths.clone() {
@@ -535,6 +1282,9 @@ impl CompositeDevice {
                 log::debug!("Skipping unhiding IIO device: {source_path}");
                 continue;
             }
+            if source_path.is_empty() {
+                continue;
+            }
             log::debug!("Un-hiding device: {}", source_path);
             if let Err(e) = unhide_device(source_path.clone()).await {
                 log::debug!("Unable to unhide device {source_path}: {:?}", e);
@@ -576,12 +1326,50 @@ impl CompositeDevice {
         self.target_dbus_devices = devices;
     }
 
+    /// Sets the passthrough keyboard and mouse target devices. These must be
+    /// set before the device is started.
+    pub fn set_passthrough_devices(&mut self, devices: HashMap<String, TargetDeviceClient>) {
+        self.target_passthrough_devices = devices;
+    }
+
     /// Return a list of source device paths (e.g. /dev/hidraw0, /dev/input/event0)
     /// that this composite device is managing
     fn get_source_device_paths(&self) -> Vec<String> {
         self.source_device_paths.clone()
     }
 
+    /// Block or unblock the running source device with the given id from
+    /// passing its input events to target devices.
+    async fn set_source_device_blocked(
+        &mut self,
+        device_id: String,
+        blocked: bool,
+    ) -> Result<(), String> {
+        // Devices blocked by the composite device config are not run, so only
+        // running source devices can be blocked or unblocked.
+        if !self.source_devices.contains_key(&device_id) {
+            return Err(format!(
+                "No running source device found with id: {device_id}"
+            ));
+        }
+        if !blocked {
+            log::info!("Unblocking source device: {device_id}");
+            self.source_devices_blocked.remove(&device_id);
+        } else if self.source_devices_blocked.insert(device_id.clone()) {
+            log::info!("Blocking source device: {device_id}");
+            // Clear target device state to release any inputs that were
+            // pressed on the device when it was blocked.
+            for (path, target) in self.target_devices.iter() {
+                if let Err(e) = target.clear_state().await {
+                    log::error!("Failed to clear state on target device {path}: {e:?}");
+                }
+            }
+        }
+        self.signal_blocked_sources_changed();
+        self.update_source_bypass().await;
+        Ok(())
+    }
+
     /// Start and run the source devices that this composite device will
     /// consume.
     async fn run_source_devices(&mut self) -> Result<(), Box<dyn Error>> {
@@ -595,6 +1383,10 @@ impl CompositeDevice {
                 log::debug!("Skipping hiding IIO device: {source_path}");
                 continue;
             }
+            // External devices have no device node to hide
+            if source_path.is_empty() {
+                continue;
+            }
             log::debug!("Hiding device: {}", source_path);
             hide_device(source_path).await?;
         }
@@ -620,9 +1412,17 @@ impl CompositeDevice {
             if let SourceDevice::Iio(_) = source_device {
                 SourceIioImuInterface::listen_on_dbus(self.conn.clone(), device.clone()).await?;
             }
+            self.source_devices_running
+                .insert(device_id.clone(), device.clone());
 
             self.source_device_tasks.spawn(async move {
                 if let Err(e) = source_device.run().await {
+                    // A new thread was already started for devices that were
+                    // restarted by the watchdog.
+                    if e.is::<Abandoned>() {
+                        log::debug!("Abandoned source device closed: {device_id}");
+                        return;
+                    }
                     log::error!("Failed running device: {:?}", e);
                 }
                 log::debug!("Source device closed");
@@ -632,6 +1432,7 @@ impl CompositeDevice {
             });
         }
         log::debug!("All source device tasks started");
+        self.update_source_bypass().await;
         Ok(())
     }
 
@@ -644,12 +1445,18 @@ impl CompositeDevice {
     ) -> Result<(), Box<dyn Error>> {
         if self.source_devices_blocked.contains(&device_id) {
             log::trace!("Blocking event! {:?}", raw_event);
+            let cap = match &raw_event {
+                Event::Evdev(event) => event.as_capability(),
+                Event::Native(event) => event.as_capability(),
+                Event::HIDRaw | Event::DBus(_) => Capability::NotImplemented,
+            };
+            self.dropped_events.record(DropReason::BlockedSource, &cap);
             return Ok(());
         }
         //log::trace!("Received event: {:?} from {device_id}", raw_event);
 
         // Convert the event into a NativeEvent
-        let event: NativeEvent = match raw_event {
+        let mut event: NativeEvent = match raw_event {
             Event::Evdev(event) => event.into(),
             Event::HIDRaw => todo!(),
             Event::Native(event) => event,
@@ -658,15 +1465,66 @@ impl CompositeDevice {
         let cap = event.as_capability();
         log::trace!("Event capability: {:?}", cap);
 
+        // Record raw values if a calibration is in progress, then apply the
+        // current calibration.
+        if let Some(calibrator) = self.calibrator.as_mut() {
+            if calibrator.record(&event) && !self.calibration_sources.contains(&device_id) {
+                self.calibration_sources.insert(device_id.clone());
+            }
+        }
+        match self.source_calibrations.get(&device_id) {
+            Some(calibration) => calibration.apply(&mut event),
+            None => self.calibration.apply(&mut event),
+        }
+
+        // Measure stick drift using the calibrated values, then apply any
+        // drift corrections.
+        self.record_drift(&event);
+        self.drift_corrections.apply(&mut event);
+        self.input_statistics.record(&event);
+
+        // Track the held state of inputs used by capability map conditions
+        if self.condition_capabilities.contains(&cap) {
+            if event.pressed() {
+                self.held_condition_inputs.insert(cap.clone());
+            } else {
+                self.held_condition_inputs.remove(&cap);
+            }
+        }
+
         // Only send valid events to the target device(s)
         if cap == Capability::NotImplemented {
             log::trace!(
                 "Refusing to send '{}' event to target devices.",
                 cap.to_string()
             );
+            self.dropped_events.record(DropReason::NotImplemented, &cap);
+            return Ok(());
+        }
+
+        // Offload translation using the device profile to the translation
+        // pool if one is configured. Events are handed back to this device
+        // in the order they were received from the source device.
+        if let Some(pool) = self.translation_pool.as_ref() {
+            let translate = self.device_profile.is_some()
+                && !self.is_translatable_capability(&cap)
+                && self.press_timings(&event).is_none()
+                && !self.tracks_system_key(&cap);
+            pool.submit(device_id.as_str(), event, translate);
             return Ok(());
         }
 
+        self.dispatch_event(event).await
+    }
+
+    /// Returns true if the given capability is translated by the capability map
+    fn is_translatable_capability(&self, cap: &Capability) -> bool {
+        self.capability_map.is_some() && self.translatable_capabilities.contains(cap)
+    }
+
+    /// Translate the given source event using the capability map and device
+    /// profile and write the results to the appropriate target devices.
+    async fn dispatch_event(&mut self, event: NativeEvent) -> Result<(), Box<dyn Error>> {
         // Check if the event needs to be translated based on the
         // capability map. Translated events will be re-enqueued, so this will
         // return early.
@@ -674,7 +1532,7 @@ impl CompositeDevice {
             "Translatable capabilities: {:?}",
             self.translatable_capabilities
         );
-        if self.capability_map.is_some() && self.translatable_capabilities.contains(&cap) {
+        if self.is_translatable_capability(&event.as_capability()) {
             log::trace!("Capability mapping found for event");
             self.translate_capability(&event).await?;
             return Ok(());
@@ -684,53 +1542,366 @@ impl CompositeDevice {
         Ok(())
     }
 
-    /// Process a single output event from a target device.
-    async fn process_output_event(&mut self, event: OutputEvent) -> Result<(), Box<dyn Error>> {
-        //log::trace!("Received output event: {:?}", event);
+    /// Handle an event that was processed by the translation pool
+    async fn handle_translated_event(
+        &mut self,
+        result: TranslatedEvent,
+    ) -> Result<(), Box<dyn Error>> {
+        let Some(events) = result.events else {
+            return self.dispatch_event(result.event).await;
+        };
+        // Translate the event again if the device profile changed while it
+        // was being translated.
+        if result.generation != self.profile_generation {
+            return self.handle_event(result.event).await;
+        }
+        self.write_translated_events(result.event, events).await
+    }
 
-        // Handle any output events that need to upload FF effect data
-        if let OutputEvent::Uinput(uinput) = event.borrow() {
-            match uinput {
-                UinputOutputEvent::FFUpload(id, data, target_dev) => {
-                    // If this effect was already uploaded, just return the id
-                    // back to the target device and inform all source devices
-                    // to update the effect with the given data.
-                    if let Some(source_effect_ids) = self.ff_effect_id_source_map.get(id) {
-                        for (source_id, source_effect_id) in source_effect_ids.iter() {
-                            let Some(source) = self.source_devices.get(source_id) else {
-                                continue;
-                            };
-                            log::debug!("Updating effect {source_effect_id} from {source_id}");
-                            if let Err(e) = source.update_effect(*source_effect_id, *data).await {
-                                log::error!("Error updating effect '{id}' on {source_id}: {e:?}");
-                            }
-                        }
-                        target_dev.send(Some(*id))?;
-                        return Ok(());
-                    }
+    /// Finish the calibration in progress, then persist and apply the results
+    fn finish_calibration(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(calibrator) = self.calibrator.take() else {
+            return Err("No calibration in progress".into());
+        };
+        let calibration = calibrator.finish(&self.calibration);
+        log::info!(
+            "Finished calibration for {}: {calibration:?}",
+            self.device_id
+        );
+        calibration.save(&self.device_id)?;
 
-                    // Upload the effect data to the source devices
-                    let mut source_effect_ids = HashMap::new();
-                    for (source_id, source) in self.source_devices.iter() {
-                        log::debug!("Uploading effect to {source_id}");
-                        match source.upload_effect(*data).await {
-                            Ok(source_effect_id) => {
-                                // An effect ID of -1 indicates the device does not support
-                                // FF events.
-                                if source_effect_id == -1 {
-                                    continue;
-                                }
-                                log::debug!("Successfully uploaded effect to {source_id} with source effect id {source_effect_id}");
-                                source_effect_ids.insert(source_id.clone(), source_effect_id);
-                            }
-                            Err(e) => {
-                                log::error!("Error uploading effect to {source_id}: {e:?}");
-                            }
-                        }
-                    }
+        // Save the results as an overlay for each calibrated controller, so
+        // they are applied on any composite device the controller is part of.
+        for source_id in self.calibration_sources.drain() {
+            let Some(controller_id) = self.source_controller_ids.get(&source_id) else {
+                continue;
+            };
+            log::info!("Saving calibration overlay for controller {controller_id}");
+            if let Err(e) = calibration.save_controller(controller_id) {
+                log::error!("Failed to save calibration overlay for {controller_id}: {e:?}");
+                continue;
+            }
+            self.source_calibrations
+                .insert(source_id, calibration.clone());
+        }
+        self.calibration = calibration;
 
-                    // If no source devices uploaded the effect, don't bother
-                    // allocating an effect id.
+        // Drift measurements and corrections were relative to the previous
+        // calibration.
+        if let Some(detector) = self.drift_detector.as_mut() {
+            detector.reset();
+        }
+        self.clear_drift_correction();
+        Ok(())
+    }
+
+    /// Record the given calibrated event to measure stick drift. If drift
+    /// corrections are applied automatically, the current suggestions replace
+    /// the active corrections.
+    fn record_drift(&mut self, event: &NativeEvent) {
+        let auto_apply = self.drift_auto_apply();
+        let Some(detector) = self.drift_detector.as_mut() else {
+            return;
+        };
+        if !detector.record(event) || !auto_apply {
+            return;
+        }
+        let suggestions = detector.suggestions();
+        if suggestions.left_stick.is_some() {
+            self.drift_corrections.left_stick = suggestions.left_stick;
+        }
+        if suggestions.right_stick.is_some() {
+            self.drift_corrections.right_stick = suggestions.right_stick;
+        }
+    }
+
+    /// Returns the drift severity of the worst stick from 0.0 (no drift) to
+    /// 1.0 (severe drift)
+    fn drift_severity(&self) -> f64 {
+        self.drift_detector
+            .as_ref()
+            .map(|detector| detector.severity())
+            .unwrap_or_default()
+    }
+
+    /// Returns the currently suggested stick drift corrections
+    fn drift_suggestions(&self) -> DriftCorrections {
+        self.drift_detector
+            .as_ref()
+            .map(|detector| detector.suggestions())
+            .unwrap_or_default()
+    }
+
+    /// Returns whether or not suggested drift corrections are applied
+    /// automatically
+    fn drift_auto_apply(&self) -> bool {
+        if let Some(auto_apply) = self.settings.drift_auto_apply {
+            return auto_apply;
+        }
+        self.config
+            .options
+            .as_ref()
+            .and_then(|options| options.drift_detection.as_ref())
+            .and_then(|options| options.auto_apply)
+            .unwrap_or(false)
+    }
+
+    /// Set whether or not suggested drift corrections are applied
+    /// automatically and persist it. If disabled, the corrections applied by
+    /// the user are restored.
+    fn set_drift_auto_apply(&mut self, auto_apply: bool) {
+        log::debug!("Setting drift auto apply to {auto_apply}");
+        self.settings.drift_auto_apply = Some(auto_apply);
+        if let Err(e) = self.settings.save(&self.device_id) {
+            log::error!("Failed to save settings for {}: {e:?}", self.device_id);
+        }
+        if !auto_apply {
+            self.drift_corrections = self.settings.drift_corrections.clone().unwrap_or_default();
+            self.request_source_bypass_update();
+        }
+    }
+
+    /// Start or stop collecting input statistics and persist it
+    async fn set_input_statistics_enabled(&mut self, enabled: bool) {
+        log::debug!("Setting input statistics enabled to {enabled}");
+        self.input_statistics.set_enabled(enabled);
+        self.settings.input_statistics = Some(enabled);
+        if let Err(e) = self.settings.save(&self.device_id) {
+            log::error!("Failed to save settings for {}: {e:?}", self.device_id);
+        }
+        self.update_source_bypass().await;
+    }
+
+    /// Apply the currently suggested drift corrections and persist them
+    fn apply_drift_correction(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(detector) = self.drift_detector.as_ref() else {
+            return Err("Drift detection is not enabled".into());
+        };
+        let suggestions = detector.suggestions();
+        if suggestions.left_stick.is_none() && suggestions.right_stick.is_none() {
+            return Err("Sticks have not been at rest long enough to measure drift".into());
+        }
+        let mut corrections = self.settings.drift_corrections.clone().unwrap_or_default();
+        if suggestions.left_stick.is_some() {
+            corrections.left_stick = suggestions.left_stick;
+        }
+        if suggestions.right_stick.is_some() {
+            corrections.right_stick = suggestions.right_stick;
+        }
+        log::info!(
+            "Applying drift corrections for {}: {corrections:?}",
+            self.device_id
+        );
+        self.settings.drift_corrections = Some(corrections.clone());
+        self.settings.save(&self.device_id)?;
+        self.drift_corrections = corrections;
+        self.request_source_bypass_update();
+        Ok(())
+    }
+
+    /// Remove all drift corrections
+    fn clear_drift_correction(&mut self) {
+        log::debug!("Clearing drift corrections for {}", self.device_id);
+        self.settings.drift_corrections = None;
+        if let Err(e) = self.settings.save(&self.device_id) {
+            log::error!("Failed to save settings for {}: {e:?}", self.device_id);
+        }
+        self.drift_corrections = DriftCorrections::default();
+        self.request_source_bypass_update();
+    }
+
+    /// Returns the combined force feedback gain (0.0 - 1.0) from the gain set
+    /// by the user and the gain set by games. The gain is always zero while
+    /// force feedback is muted for the active application.
+    fn ff_gain(&self) -> f64 {
+        if self.ff_muted {
+            return 0.0;
+        }
+        let user_gain = self.settings.ff_gain.unwrap_or(100).min(100) as f64 / 100.0;
+        let game_gain = self.ff_game_gain as f64 / u16::MAX as f64;
+        user_gain * game_gain
+    }
+
+    /// Set the force feedback gain in percent (0-100) and persist it
+    async fn set_ff_gain(&mut self, gain: u32) {
+        let gain = gain.min(100);
+        log::debug!("Setting FF gain to {gain}%");
+        self.settings.ff_gain = Some(gain);
+        if let Err(e) = self.settings.save(&self.device_id) {
+            log::error!("Failed to save settings for {}: {e:?}", self.device_id);
+        }
+        self.update_ff_effects().await;
+    }
+
+    /// Returns the list of application identifiers for which force feedback
+    /// is muted. User settings take precedence over the composite device config.
+    fn ff_mute_apps(&self) -> Vec<String> {
+        if let Some(apps) = self.settings.ff_mute_apps.as_ref() {
+            return apps.clone();
+        }
+        self.config
+            .options
+            .as_ref()
+            .and_then(|options| options.ff_mute_apps.clone())
+            .unwrap_or_default()
+    }
+
+    /// Set the list of applications for which force feedback is muted and
+    /// persist it
+    async fn set_ff_mute_apps(&mut self, apps: Vec<String>) {
+        log::debug!("Setting FF mute apps to {apps:?}");
+        self.settings.ff_mute_apps = Some(apps);
+        if let Err(e) = self.settings.save(&self.device_id) {
+            log::error!("Failed to save settings for {}: {e:?}", self.device_id);
+        }
+        self.update_ff_muted().await;
+    }
+
+    /// Set the identifier of the application currently in focus
+    async fn set_active_app(&mut self, app: Option<String>) {
+        log::debug!("Setting active application to {app:?}");
+        self.active_app = app;
+        self.update_ff_muted().await;
+    }
+
+    /// Mute force feedback if the active application is in the list of
+    /// applications to mute, or unmute it if it is not.
+    async fn update_ff_muted(&mut self) {
+        let muted = self.active_app.as_ref().is_some_and(|app| {
+            self.ff_mute_apps()
+                .iter()
+                .any(|pattern| glob_match(pattern.as_str(), app.as_str()))
+        });
+        if self.ff_muted == muted {
+            return;
+        }
+        log::info!(
+            "{} force feedback for application {:?}",
+            if muted { "Muting" } else { "Unmuting" },
+            self.active_app
+        );
+        self.ff_muted = muted;
+        if muted {
+            self.stop_ff_effects().await;
+        }
+        self.update_ff_effects().await;
+    }
+
+    /// Stop all uploaded force feedback effects on source devices
+    async fn stop_ff_effects(&self) {
+        for source_effect_ids in self.ff_effect_id_source_map.values() {
+            for (source_id, source_effect_id) in source_effect_ids.iter() {
+                let Some(source) = self.source_devices.get(source_id) else {
+                    continue;
+                };
+                let event = InputEvent::new_now(
+                    evdev::EventType::FORCEFEEDBACK.0,
+                    *source_effect_id as u16,
+                    0,
+                );
+                if let Err(e) = source.write_event(OutputEvent::Evdev(event)).await {
+                    log::error!("Failed to stop FF effect on {source_id}: {e:?}");
+                }
+            }
+        }
+    }
+
+    /// Returns the options used to convert trackpad haptic commands into
+    /// rumble. User settings take precedence over the composite device config.
+    fn haptics_to_rumble(&self) -> HapticsToRumble {
+        let config = self
+            .config
+            .options
+            .as_ref()
+            .and_then(|options| options.haptics_to_rumble.as_ref());
+        self.settings
+            .haptics_to_rumble
+            .clone()
+            .unwrap_or_default()
+            .or(config)
+    }
+
+    /// Set the options used to convert trackpad haptic commands into rumble
+    /// and persist them
+    fn set_haptics_to_rumble(&mut self, options: HapticsToRumble) {
+        log::debug!("Setting haptics to rumble options to {options:?}");
+        self.settings.haptics_to_rumble = Some(options);
+        if let Err(e) = self.settings.save(&self.device_id) {
+            log::error!("Failed to save settings for {}: {e:?}", self.device_id);
+        }
+    }
+
+    /// Update all uploaded force feedback effects on source devices using
+    /// the current gain.
+    async fn update_ff_effects(&self) {
+        let gain = self.ff_gain();
+        for (effect_id, data) in self.ff_effect_data.iter() {
+            let Some(source_effect_ids) = self.ff_effect_id_source_map.get(effect_id) else {
+                continue;
+            };
+            let data = scale_ff_effect(*data, gain);
+            for (source_id, source_effect_id) in source_effect_ids.iter() {
+                let Some(source) = self.source_devices.get(source_id) else {
+                    continue;
+                };
+                if let Err(e) = source.update_effect(*source_effect_id, data).await {
+                    log::error!("Error updating effect '{effect_id}' on {source_id}: {e:?}");
+                }
+            }
+        }
+    }
+
+    /// Process a single output event from a target device.
+    async fn process_output_event(&mut self, event: OutputEvent) -> Result<(), Box<dyn Error>> {
+        //log::trace!("Received output event: {:?}", event);
+
+        // Handle any output events that need to upload FF effect data
+        if let OutputEvent::Uinput(uinput) = event.borrow() {
+            match uinput {
+                UinputOutputEvent::FFUpload(id, data, target_dev) => {
+                    // If this effect was already uploaded, just return the id
+                    // back to the target device and inform all source devices
+                    // to update the effect with the given data.
+                    let scaled_data = scale_ff_effect(*data, self.ff_gain());
+                    if let Some(source_effect_ids) = self.ff_effect_id_source_map.get(id) {
+                        self.ff_effect_data.insert(*id, *data);
+                        for (source_id, source_effect_id) in source_effect_ids.iter() {
+                            let Some(source) = self.source_devices.get(source_id) else {
+                                continue;
+                            };
+                            log::debug!("Updating effect {source_effect_id} from {source_id}");
+                            if let Err(e) =
+                                source.update_effect(*source_effect_id, scaled_data).await
+                            {
+                                log::error!("Error updating effect '{id}' on {source_id}: {e:?}");
+                            }
+                        }
+                        target_dev.send(Some(*id))?;
+                        return Ok(());
+                    }
+
+                    // Upload the effect data to the source devices
+                    let mut source_effect_ids = HashMap::new();
+                    for (source_id, source) in self.source_devices.iter() {
+                        log::debug!("Uploading effect to {source_id}");
+                        match source.upload_effect(scaled_data).await {
+                            Ok(source_effect_id) => {
+                                // An effect ID of -1 indicates the device does not support
+                                // FF events.
+                                if source_effect_id == -1 {
+                                    continue;
+                                }
+                                log::debug!("Successfully uploaded effect to {source_id} with source effect id {source_effect_id}");
+                                source_effect_ids.insert(source_id.clone(), source_effect_id);
+                            }
+                            Err(e) => {
+                                log::error!("Error uploading effect to {source_id}: {e:?}");
+                            }
+                        }
+                    }
+
+                    // If no source devices uploaded the effect, don't bother
+                    // allocating an effect id.
                     if source_effect_ids.is_empty() {
                         log::debug!("No source device available to handle FF effect");
                         target_dev.send(None)?;
@@ -742,6 +1913,7 @@ impl CompositeDevice {
                         log::debug!("Uploaded effect with effect id {id}");
                         self.ff_effect_ids.remove(&id);
                         self.ff_effect_id_source_map.insert(id, source_effect_ids);
+                        self.ff_effect_data.insert(id, *data);
                         target_dev.send(Some(id))?;
                     } else {
                         target_dev.send(None)?;
@@ -766,6 +1938,7 @@ impl CompositeDevice {
                     log::debug!("Erased effect with effect id {effect_id}");
                     self.ff_effect_ids.insert(effect_id);
                     self.ff_effect_id_source_map.remove(&effect_id);
+                    self.ff_effect_data.remove(&effect_id);
                 }
             }
 
@@ -775,6 +1948,28 @@ impl CompositeDevice {
             return Ok(());
         }
 
+        // Trackpad haptic events may need to be converted into rumble for
+        // source devices that do not have trackpad haptics.
+        if let OutputEvent::SteamDeckHaptics(report) = event.borrow() {
+            self.process_haptic_event(report).await;
+            return Ok(());
+        }
+
+        // Games can set the gain of all force feedback effects using FF_GAIN
+        if let OutputEvent::Evdev(input_event) = event.borrow() {
+            if input_event.event_type().0 == evdev::EventType::FORCEFEEDBACK.0
+                && input_event.code() == FFEffectCode::FF_GAIN.0
+            {
+                self.ff_game_gain = input_event.value().clamp(0, u16::MAX as i32) as u16;
+                log::debug!("Game set FF gain to {}", self.ff_game_gain);
+                self.update_ff_effects().await;
+                return Ok(());
+            }
+        }
+
+        // Scale any rumble in the event by the current gain
+        let event = scale_rumble(event, self.ff_gain());
+
         // TODO: Only write the event to devices that are capabile of handling it
         for (source_id, source) in self.source_devices.iter() {
             // If this is a force feedback event, translate the effect id into
@@ -822,8 +2017,393 @@ impl CompositeDevice {
         Ok(())
     }
 
+    /// Write the given trackpad haptic report to source devices that support
+    /// trackpad haptics, and convert it into rumble for source devices that
+    /// only support force feedback.
+    async fn process_haptic_event(&mut self, report: &PackedHapticReport) {
+        let haptic_caps = [
+            OutputCapability::Haptics(Haptic::TrackpadLeft),
+            OutputCapability::Haptics(Haptic::TrackpadRight),
+        ];
+        // Converted rumble is dropped while force feedback is muted
+        let config = self.haptics_to_rumble();
+        let rumble = if self.ff_muted {
+            None
+        } else {
+            haptic_to_rumble(report, Some(&config))
+        };
+
+        let mut rumble_sources = Vec::new();
+        for (source_id, source) in self.source_devices.iter() {
+            let Some(caps) = self.source_output_capabilities.get(source_id) else {
+                continue;
+            };
+
+            // Pass the haptic report unmodified if trackpad haptics are supported
+            if haptic_caps.iter().any(|cap| caps.contains(cap)) {
+                let event = OutputEvent::SteamDeckHaptics(*report);
+                if let Err(e) = source.write_event(event).await {
+                    log::error!("Failed to send Output event to {}. {:?}", source_id, e)
+                }
+                continue;
+            }
+
+            if !caps.contains(&OutputCapability::ForceFeedback) {
+                continue;
+            }
+            let Some((rumble_report, _)) = rumble.as_ref() else {
+                continue;
+            };
+            log::trace!("Converting haptic report to rumble for {source_id}");
+            let event = OutputEvent::SteamDeckRumble(*rumble_report);
+            if let Err(e) = source.write_event(event).await {
+                log::error!("Failed to send Output event to {}. {:?}", source_id, e)
+            }
+            rumble_sources.push(source_id.clone());
+        }
+
+        // Stop the converted rumble after the haptic duration has elapsed
+        let Some((_, duration)) = rumble else {
+            return;
+        };
+        if rumble_sources.is_empty() {
+            return;
+        }
+        let id = self.track_rumble(&rumble_sources);
+        self.scheduler
+            .schedule(duration, CompositeCommand::StopRumble(id, rumble_sources));
+    }
+
+    /// Rumble all source devices capable of force feedback at the given
+    /// intensity (0.0 - 1.0) for the given duration, so force feedback routing
+    /// can be verified without a game. The gain and mute settings are not
+    /// applied. Returns the number of source devices the rumble was sent to.
+    async fn test_rumble(&mut self, intensity: f64, duration: Duration) -> usize {
+        let speed = (intensity.clamp(0.0, 1.0) * u16::MAX as f64).round() as u16;
+        let mut report = PackedRumbleReport::new();
+        report.intensity = 1;
+        report.left_speed = Integer::from_primitive(speed);
+        report.right_speed = Integer::from_primitive(speed);
+
+        let mut rumble_sources = Vec::new();
+        for (source_id, source) in self.source_devices.iter() {
+            let Some(caps) = self.source_output_capabilities.get(source_id) else {
+                continue;
+            };
+            if !caps.contains(&OutputCapability::ForceFeedback) {
+                continue;
+            }
+            log::debug!("Sending test rumble to {source_id}");
+            let event = OutputEvent::SteamDeckRumble(report);
+            if let Err(e) = source.write_event(event).await {
+                log::error!("Failed to send test rumble to {}. {:?}", source_id, e);
+                continue;
+            }
+            rumble_sources.push(source_id.clone());
+        }
+
+        // Stop the rumble after the duration has elapsed
+        let count = rumble_sources.len();
+        if count == 0 {
+            return count;
+        }
+        let id = self.track_rumble(&rumble_sources);
+        self.scheduler
+            .schedule(duration, CompositeCommand::StopRumble(id, rumble_sources));
+
+        count
+    }
+
+    /// Record a new rumble started on the given source devices. Returns the
+    /// identifier that must be passed to [CompositeCommand::StopRumble].
+    fn track_rumble(&mut self, source_ids: &[String]) -> u64 {
+        self.rumble_count += 1;
+        let id = self.rumble_count;
+        for source_id in source_ids {
+            self.source_rumbles.insert(source_id.clone(), id);
+        }
+        id
+    }
+
+    /// Stop the rumble with the given identifier on the given source devices.
+    /// Source devices where a newer rumble was started since are skipped.
+    async fn stop_rumble(&mut self, id: u64, source_ids: Vec<String>) {
+        for source_id in source_ids {
+            if self.source_rumbles.get(&source_id) != Some(&id) {
+                log::trace!("Skipping stale rumble stop {id} for {source_id}");
+                continue;
+            }
+            self.source_rumbles.remove(&source_id);
+            let Some(source) = self.source_devices.get(&source_id) else {
+                continue;
+            };
+            let event = OutputEvent::SteamDeckRumble(PackedRumbleReport::new());
+            if let Err(e) = source.write_event(event).await {
+                log::debug!("Failed to stop rumble of {source_id}: {e:?}");
+            }
+        }
+    }
+
+    /// Schedule the given event to be written to the target devices. Returns
+    /// the identifier that can be used to cancel the event.
+    fn schedule_event(&mut self, event: ScheduledNativeEvent) -> u64 {
+        self.scheduled_event_count += 1;
+        let id = self.scheduled_event_count;
+        self.scheduled_events.insert(id);
+        self.scheduler.schedule(
+            event.wait_time(),
+            CompositeCommand::EmitScheduledEvent(id, event),
+        );
+        id
+    }
+
+    /// Cancel the scheduled event with the given identifier, including any
+    /// remaining repetitions
+    fn cancel_scheduled_event(&mut self, id: u64) {
+        if self.scheduled_events.remove(&id) {
+            log::debug!("Cancelled scheduled event {id}");
+        }
+    }
+
+    /// Write the given scheduled event to the target devices and schedule its
+    /// next repetition, if any
+    async fn emit_scheduled_event(
+        &mut self,
+        id: u64,
+        scheduled: ScheduledNativeEvent,
+    ) -> Result<(), Box<dyn Error>> {
+        // The event was cancelled
+        if !self.scheduled_events.contains(&id) {
+            return Ok(());
+        }
+        match scheduled.next_repetition() {
+            Some(next) => self.scheduler.schedule(
+                next.wait_time(),
+                CompositeCommand::EmitScheduledEvent(id, next),
+            ),
+            None => {
+                self.scheduled_events.remove(&id);
+            }
+        }
+
+        // Scheduled events are delayed on purpose, so only measure their
+        // latency from when they are due.
+        let mut event: NativeEvent = scheduled.into();
+        event.set_timestamp(Instant::now());
+        self.write_event(event).await
+    }
+
     /// Translate and write the given event to the appropriate target devices
     async fn handle_event(&mut self, event: NativeEvent) -> Result<(), Box<dyn Error>> {
+        // A remapped power key is passed through if it is held long enough
+        if self.tracks_system_key(&event.as_capability()) {
+            self.update_system_key(event.pressed()).await?;
+        }
+
+        // Source buttons with press modifier mappings are translated once the
+        // kind of press is known.
+        if let Some(timings) = self.press_timings(&event) {
+            let cap = event.as_capability();
+            let actions = self.press_tracker.update(&cap, event.pressed(), &timings);
+            return self.run_press_actions(cap, actions).await;
+        }
+
+        // Source buttons with auto-fire mappings repeatedly press and release
+        // their target events while held.
+        let auto_fire = self.auto_fire_interval(&event);
+        if auto_fire.is_some() || self.auto_fire_events.contains_key(&event.as_capability()) {
+            return self.update_auto_fire(event, auto_fire).await;
+        }
+
+        // Translate the event using the device profile.
+        let events = if self.device_profile.is_some() {
+            self.translate_event(&event).await?
+        } else {
+            vec![event.clone()]
+        };
+        self.write_translated_events(event, events).await
+    }
+
+    /// Returns true if the given source capability is a system key that is
+    /// remapped by the device profile and passed through on a long press
+    fn tracks_system_key(&self, cap: &Capability) -> bool {
+        self.system_keys.tracks(cap) && self.device_profile_config_map.contains_key(cap)
+    }
+
+    /// Update the long press state of the remapped power key
+    async fn update_system_key(&mut self, pressed: bool) -> Result<(), Box<dyn Error>> {
+        match self.system_keys.update(pressed) {
+            SystemKeyAction::None => Ok(()),
+            SystemKeyAction::Schedule(delay, id) => {
+                self.scheduler
+                    .schedule(delay, CompositeCommand::SystemKeyTimer(id));
+                Ok(())
+            }
+            SystemKeyAction::ReleasePower => self.write_power_key(false).await,
+        }
+    }
+
+    /// Pass the remapped power key through if the press with the given id is
+    /// still held
+    async fn handle_system_key_timer(&mut self, id: u64) -> Result<(), Box<dyn Error>> {
+        if !self.system_keys.expire(id) {
+            return Ok(());
+        }
+        log::info!("Power key held, passing it through to target devices");
+        self.write_power_key(true).await
+    }
+
+    /// Write the untranslated power key to the target devices
+    async fn write_power_key(&mut self, pressed: bool) -> Result<(), Box<dyn Error>> {
+        let event = NativeEvent::new(
+            Capability::Keyboard(Keyboard::KeyPower),
+            InputValue::Bool(pressed),
+        );
+        self.write_translated_events(event.clone(), vec![event])
+            .await
+    }
+
+    /// Returns the press timings of the given source event if the device
+    /// profile has short, long, or double press mappings for it
+    fn press_timings(&self, event: &NativeEvent) -> Option<PressTimings> {
+        if !matches!(event.get_value(), InputValue::Bool(_)) {
+            return None;
+        }
+        let mappings = self.device_profile_config_map.get(&event.as_capability())?;
+        PressTimings::from_mappings(mappings)
+    }
+
+    /// Returns the auto-fire interval of the given source event if the device
+    /// profile has an auto-fire mapping for it
+    fn auto_fire_interval(&self, event: &NativeEvent) -> Option<Duration> {
+        if !matches!(event.get_value(), InputValue::Bool(_)) {
+            return None;
+        }
+        let mappings = self.device_profile_config_map.get(&event.as_capability())?;
+        mappings
+            .iter()
+            .filter_map(|mapping| mapping.auto_fire_ms)
+            .filter(|interval| *interval > 0)
+            .min()
+            .map(Duration::from_millis)
+    }
+
+    /// Start auto-firing the target events of the given source button when it
+    /// is pressed, or stop auto-firing when it is released. While auto-firing,
+    /// each target button is pressed and released once every interval.
+    async fn update_auto_fire(
+        &mut self,
+        event: NativeEvent,
+        interval: Option<Duration>,
+    ) -> Result<(), Box<dyn Error>> {
+        let cap = event.as_capability();
+        for id in self.auto_fire_events.remove(&cap).unwrap_or_default() {
+            self.cancel_scheduled_event(id);
+        }
+
+        let events = self.translate_event(&event).await?;
+        let Some(interval) = interval.filter(|_| event.pressed()) else {
+            return self.write_translated_events(event, events).await;
+        };
+
+        // Only buttons can be auto-fired, other target events are emitted as-is
+        let (buttons, others): (Vec<_>, Vec<_>) = events
+            .into_iter()
+            .partition(|target| matches!(target.get_value(), InputValue::Bool(true)));
+        let mut ids = Vec::with_capacity(buttons.len() * 2);
+        for press in buttons {
+            let release = NativeEvent::new(press.as_capability(), InputValue::Bool(false));
+            let press = ScheduledNativeEvent::new(press, Duration::ZERO).with_repeat(interval, 0);
+            let release = ScheduledNativeEvent::new(release, interval / 2).with_repeat(interval, 0);
+            ids.push(self.schedule_event(press));
+            ids.push(self.schedule_event(release));
+        }
+        log::debug!("Auto-firing {cap:?} every {interval:?}");
+        self.auto_fire_events.insert(cap, ids);
+
+        if others.is_empty() {
+            return Ok(());
+        }
+        self.write_translated_events(event, others).await
+    }
+
+    /// Handle the given press timer that was scheduled by the press tracker
+    async fn handle_press_timer(&mut self, timer: PressTimer) -> Result<(), Box<dyn Error>> {
+        match timer {
+            PressTimer::Expired { capability, id } => {
+                let actions = self.press_tracker.expire(&capability, id);
+                self.run_press_actions(capability, actions).await
+            }
+            PressTimer::Release { capability, kind } => {
+                self.write_press_events(&capability, kind, false).await
+            }
+        }
+    }
+
+    /// Run the given actions of the press tracker for the given source button
+    async fn run_press_actions(
+        &mut self,
+        capability: Capability,
+        actions: Vec<PressAction>,
+    ) -> Result<(), Box<dyn Error>> {
+        for action in actions {
+            match action {
+                PressAction::Press(kind) => {
+                    self.write_press_events(&capability, kind, true).await?;
+                }
+                PressAction::Release(kind) => {
+                    self.write_press_events(&capability, kind, false).await?;
+                }
+                PressAction::Tap(kind) => {
+                    self.write_press_events(&capability, kind, true).await?;
+                    let timer = PressTimer::Release {
+                        capability: capability.clone(),
+                        kind,
+                    };
+                    self.scheduler.schedule(
+                        Duration::from_millis(self.chord_delay()),
+                        CompositeCommand::PressTimer(timer),
+                    );
+                }
+                PressAction::Schedule(delay, timer) => {
+                    self.scheduler
+                        .schedule(delay, CompositeCommand::PressTimer(timer));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Translate and write the press or release of the given source button
+    /// using only the device profile mappings of the given press kind
+    async fn write_press_events(
+        &mut self,
+        capability: &Capability,
+        kind: PressKind,
+        pressed: bool,
+    ) -> Result<(), Box<dyn Error>> {
+        let event = NativeEvent::new(capability.clone(), InputValue::Bool(pressed));
+        let events = match self.device_profile_config_map.get(capability) {
+            Some(mappings) => translation::translate_press(mappings, kind, &event),
+            None => vec![],
+        };
+        self.write_translated_events(event, events).await
+    }
+
+    /// Write the given events that were translated from the given source event
+    /// to the appropriate target devices
+    async fn write_translated_events(
+        &mut self,
+        event: NativeEvent,
+        mut events: Vec<NativeEvent>,
+    ) -> Result<(), Box<dyn Error>> {
+        // The device profile can translate an event into nothing
+        if events.is_empty() {
+            self.dropped_events
+                .record(DropReason::Filtered, &event.as_capability());
+            return Ok(());
+        }
+
         // Check if we need to reverse the event list.
         let is_pressed = event.pressed();
         // Check if this is is a single event or multiple events.
@@ -831,12 +2411,10 @@ impl CompositeDevice {
         // Track the delay for chord events.
         let mut sleep_time = 0;
 
-        // Translate the event using the device profile.
-        let mut events = if self.device_profile.is_some() {
-            self.translate_event(&event).await?
-        } else {
-            vec![event]
-        };
+        // Use the chord delay of the matching profile mapping if one is defined.
+        let chord_delay = self
+            .mapping_chord_delay(&event)
+            .unwrap_or_else(|| self.chord_delay());
 
         // Check if we need to reverse the event list.
         if events.len() > 1 {
@@ -846,7 +2424,7 @@ impl CompositeDevice {
                 events = events.into_iter().rev().collect();
                 // To support on_release events, we need to sleep past the time it takes to emit
                 // the down events.
-                sleep_time = 80 * events.len() as u64;
+                sleep_time = chord_delay * events.len() as u64;
                 //log::trace!("Chord is an UP event. New chord: {events:?}");
             }
         }
@@ -865,47 +2443,77 @@ impl CompositeDevice {
                 | Capability::DBus(_) => {}
                 Capability::Keyboard(_) => {
                     if !self.is_new_active_event(&cap, is_pressed) {
+                        self.dropped_events.record(DropReason::Duplicate, &cap);
                         continue;
                     }
                     if self
                         .is_intercept_event(&event, is_pressed, intercept)
                         .await?
                     {
+                        self.dropped_events.record(DropReason::Intercepted, &cap);
                         continue;
                     }
                 }
                 Capability::Gamepad(ref t) => match t {
                     Gamepad::Button(_) => {
                         if !self.is_new_active_event(&cap, is_pressed) {
+                            self.dropped_events.record(DropReason::Duplicate, &cap);
                             continue;
                         }
                         if self
                             .is_intercept_event(&event, is_pressed, intercept)
                             .await?
                         {
+                            self.dropped_events.record(DropReason::Intercepted, &cap);
                             continue;
                         }
                     }
                     Gamepad::Axis(_)
                     | Gamepad::Trigger(_)
                     | Gamepad::Accelerometer
-                    | Gamepad::Gyro => {}
+                    | Gamepad::Gyro
+                    | Gamepad::Dial(_) => {}
                 },
                 Capability::Mouse(ref t) => match t {
                     Mouse::Motion => {}
+                    // Wheel scrolling translated from a dial is relative and
+                    // is never released, so it is not tracked as active.
+                    Mouse::Button(_) if matches!(event.get_value(), InputValue::Float(_)) => {}
                     Mouse::Button(_) => {
                         if !self.is_new_active_event(&cap, is_pressed) {
+                            self.dropped_events.record(DropReason::Duplicate, &cap);
                             continue;
                         }
                         if self
                             .is_intercept_event(&event, is_pressed, intercept)
                             .await?
                         {
+                            self.dropped_events.record(DropReason::Intercepted, &cap);
                             continue;
                         }
                     }
                 },
-                Capability::Touchscreen(_) => (),
+                Capability::Touchscreen(_) => {
+                    // Touches are always sent over DBus while input is
+                    // intercepted so overlays remain usable by touch.
+                    if self.intercept_mode != InterceptMode::Always {
+                        match self.touchscreen_mode {
+                            TouchscreenMode::Passthrough => (),
+                            TouchscreenMode::Block => {
+                                self.dropped_events
+                                    .record(DropReason::TouchscreenBlocked, &cap);
+                                continue;
+                            }
+                            TouchscreenMode::Mouse => {
+                                for event in self.touch_mouse.translate(&event) {
+                                    self.write_event(event).await?;
+                                }
+                                continue;
+                            }
+                        }
+                    }
+                }
+                Capability::Switch(_) => (),
             }
 
             // if this is a chord with no matches to the intercept_active_inputs, add a keypress
@@ -913,15 +2521,12 @@ impl CompositeDevice {
             // through or miss events if they aren't properly
             // timed.
             if is_chord {
-                let tx = self.tx.clone();
-                tokio::spawn(async move {
-                    tokio::time::sleep(Duration::from_millis(sleep_time)).await;
-                    if let Err(e) = tx.send(CompositeCommand::WriteEvent(event)).await {
-                        log::error!("Failed to send chord event command: {:?}", e);
-                    }
-                });
+                self.scheduler.schedule(
+                    Duration::from_millis(sleep_time),
+                    CompositeCommand::WriteEvent(event),
+                );
                 // Increment the sleep time.
-                sleep_time += 80;
+                sleep_time += chord_delay;
                 continue;
             }
 
@@ -954,6 +2559,16 @@ impl CompositeDevice {
     async fn write_event(&self, event: NativeEvent) -> Result<(), Box<dyn Error>> {
         let cap = event.as_capability();
 
+        // Record all emitted events on debug target devices
+        for path in self.target_debug_devices.iter() {
+            let Some(target) = self.target_devices.get(path) else {
+                continue;
+            };
+            if let Err(e) = target.write_event(event.clone()).await {
+                log::error!("Failed to write event to debug device: {path}: {e:?}");
+            }
+        }
+
         // If this event implements the DBus capability, send the event to DBus devices
         if matches!(cap, Capability::DBus(_)) {
             log::trace!("Emit dbus event: {:?}", event);
@@ -968,6 +2583,7 @@ impl CompositeDevice {
         // target devices.
         if self.intercept_mode == InterceptMode::Always {
             log::trace!("Emit intercepted event: {:?}", event);
+            self.dropped_events.record(DropReason::Intercepted, &cap);
             for target in self.target_dbus_devices.values() {
                 target.write_event(event.clone()).await?;
             }
@@ -980,24 +2596,75 @@ impl CompositeDevice {
             && matches!(cap, Capability::Gamepad(_))
         {
             log::trace!("Emit intercepted event: {:?}", event);
+            self.dropped_events.record(DropReason::Intercepted, &cap);
             for target in self.target_dbus_devices.values() {
                 target.write_event(event.clone()).await?;
             }
             return Ok(());
         }
 
+        // Events are not emitted to target devices while inhibited
+        if self.inhibited {
+            log::trace!("Device is inhibited. Dropping event: {:?}", event);
+            self.dropped_events.record(DropReason::Inhibited, &cap);
+            return Ok(());
+        }
+
+        // If clone output is enabled, mirror the event to DBus target devices
+        // capable of handling it in addition to any other capable target
+        // devices.
+        let clone_output = self
+            .config
+            .options
+            .as_ref()
+            .and_then(|options| options.clone_output)
+            .unwrap_or(false);
+        if clone_output {
+            let paths = capable_targets(&cap, &self.target_dbus_by_capability);
+            if !paths.is_empty() {
+                log::trace!("Emit cloned event: {:?}", event);
+            }
+            for path in paths {
+                // Targets capable of the event already receive it below
+                let receives_event = self
+                    .target_devices_by_capability
+                    .get(&cap)
+                    .is_some_and(|paths| paths.contains(&path));
+                if receives_event {
+                    continue;
+                }
+                let target = self
+                    .target_dbus_devices
+                    .get(&path)
+                    .or_else(|| self.target_devices.get(&path));
+                let Some(target) = target else {
+                    continue;
+                };
+                if let Err(e) = target.write_event(event.clone()).await {
+                    log::error!("Failed to write cloned event to: {path}: {e:?}");
+                }
+            }
+        }
+
         // Find all target devices capable of handling this event
-        let Some(target_paths) = self.target_devices_by_capability.get(&cap) else {
+        let target_devices: Vec<(&str, &TargetDeviceClient)> = self
+            .target_devices_by_capability
+            .get(&cap)
+            .map(|target_paths| {
+                target_paths
+                    .iter()
+                    .filter_map(|path| {
+                        let device = self.target_devices.get(path);
+                        device.map(|client| (path.as_str(), client))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        if target_devices.is_empty() {
             log::trace!("No target devices capable of handling this event: {cap}");
+            self.write_passthrough_event(event).await;
             return Ok(());
-        };
-        let target_devices: Vec<(&str, &TargetDeviceClient)> = target_paths
-            .iter()
-            .filter_map(|path| {
-                let device = self.target_devices.get(path);
-                device.map(|client| (path.as_str(), client))
-            })
-            .collect();
+        }
 
         // Only write the event to devices that are capabile of handling it
         log::trace!("Emit passed event: {:?}", event);
@@ -1009,6 +2676,32 @@ impl CompositeDevice {
         Ok(())
     }
 
+    /// Write the given event to the passthrough target devices capable of
+    /// handling it. This keeps basic keyboard and mouse input working while
+    /// no other target device can handle the event, such as during startup
+    /// or while target devices are being changed.
+    async fn write_passthrough_event(&self, event: NativeEvent) {
+        let cap = event.as_capability();
+        let Some(target_paths) = self.target_passthrough_by_capability.get(&cap) else {
+            let reason = if cap == Capability::NotImplemented {
+                DropReason::NotImplemented
+            } else {
+                DropReason::NoTarget
+            };
+            self.dropped_events.record(reason, &cap);
+            return;
+        };
+        log::trace!("Emit passthrough event: {:?}", event);
+        for path in target_paths {
+            let Some(target) = self.target_passthrough_devices.get(path) else {
+                continue;
+            };
+            if let Err(e) = target.write_event(event.clone()).await {
+                log::error!("Failed to write passthrough event to: {path}: {e:?}");
+            }
+        }
+    }
+
     /// Handles writing events that come from the dbus send_event interface
     async fn write_send_event(&mut self, event: NativeEvent) -> Result<(), Box<dyn Error>> {
         let cap = event.as_capability();
@@ -1019,13 +2712,8 @@ impl CompositeDevice {
         let cap = event.as_capability();
         if self.translated_recent_events.contains(&cap) {
             log::debug!("Event emitted too quickly. Delaying emission.");
-            let tx = self.tx.clone();
-            tokio::task::spawn(async move {
-                tokio::time::sleep(sleep_time).await;
-                if let Err(e) = tx.send(CompositeCommand::WriteEvent(event)).await {
-                    log::error!("Failed to send delayed event command: {:?}", e);
-                }
-            });
+            self.scheduler
+                .schedule(sleep_time, CompositeCommand::WriteEvent(event));
 
             return Ok(());
         }
@@ -1033,14 +2721,9 @@ impl CompositeDevice {
         // Add the event to our list of recently device translated events
         self.translated_recent_events.insert(event.as_capability());
 
-        // Spawn a task to remove the event from recent translated
-        let tx = self.tx.clone();
-        tokio::task::spawn(async move {
-            tokio::time::sleep(sleep_time).await;
-            if let Err(e) = tx.send(CompositeCommand::RemoveRecentEvent(cap)).await {
-                log::error!("Failed to send remove recent event command: {:?}", e);
-            }
-        });
+        // Schedule removing the event from recent translated
+        self.scheduler
+            .schedule(sleep_time, CompositeCommand::RemoveRecentEvent(cap));
 
         //log::trace!("Emitting event: {:?}", event);
         self.write_event(event).await?;
@@ -1049,25 +2732,43 @@ impl CompositeDevice {
     }
 
     // Handles writing chord events that come fron the dbus send_button_chord interface
-    async fn write_chord_events(&self, events: Vec<NativeEvent>) -> Result<(), Box<dyn Error>> {
+    async fn write_chord_events(&mut self, events: Vec<NativeEvent>) -> Result<(), Box<dyn Error>> {
         // Track the delay for chord events.
+        let chord_delay = self.chord_delay();
         let mut sleep_time = 0;
 
         for event in events {
-            let tx = self.tx.clone();
             log::debug!("Send event {:?} at sleep time {sleep_time}", event);
-            tokio::spawn(async move {
-                tokio::time::sleep(Duration::from_millis(sleep_time)).await;
-                if let Err(e) = tx.send(CompositeCommand::WriteEvent(event)).await {
-                    log::error!("Failed to send chord event command: {:?}", e);
-                }
-            });
+            self.scheduler.schedule(
+                Duration::from_millis(sleep_time),
+                CompositeCommand::WriteEvent(event),
+            );
             // Increment the sleep time.
-            sleep_time += 80;
+            sleep_time += chord_delay;
         }
         Ok(())
     }
 
+    /// Returns the delay in milliseconds between each event of an emitted
+    /// chord from the composite device config
+    fn chord_delay(&self) -> u64 {
+        self.config
+            .options
+            .as_ref()
+            .and_then(|options| options.chord_delay_ms)
+            .unwrap_or(DEFAULT_CHORD_DELAY_MS)
+    }
+
+    /// Returns the chord delay defined in the device profile mapping that
+    /// matches the given source event, if any.
+    fn mapping_chord_delay(&self, event: &NativeEvent) -> Option<u64> {
+        let mappings = self.device_profile_config_map.get(&event.as_capability())?;
+        mappings
+            .iter()
+            .filter(|mapping| mapping.source_matches_properties(event))
+            .find_map(|mapping| mapping.chord_delay_ms)
+    }
+
     /// Loads the input capabilities to translate from the capability map
     fn load_capability_map(&mut self) -> Result<(), Box<dyn Error>> {
         let Some(map) = self.capability_map.as_ref() else {
@@ -1083,21 +2784,53 @@ impl CompositeDevice {
                 }
                 self.translatable_capabilities.push(cap);
             }
+            let conditions = conditions::condition_capabilities(mapping.conditions.as_ref());
+            self.condition_capabilities.extend(conditions);
         }
 
         Ok(())
     }
 
+    /// Returns true if all conditions of the given capability mapping are met
+    fn is_mapping_condition_met(&self, mapping: &CapabilityMapping) -> bool {
+        conditions::conditions_met(mapping.conditions.as_ref(), &self.intercept_mode, |cap| {
+            self.held_condition_inputs.contains(cap)
+                || self.translatable_active_inputs.contains(cap)
+        })
+    }
+
     /// Sets the intercept mode to the given value
     async fn set_intercept_mode(&mut self, mode: InterceptMode) {
         log::debug!("Setting intercept mode to: {:?}", mode);
         self.intercept_mode = mode;
+        self.update_source_bypass().await;
+
+        // Touches are sent over DBus while intercepting, so any mouse button
+        // held by a touch is released on the target devices.
+        if mode == InterceptMode::Always {
+            for event in self.touch_mouse.release() {
+                if let Err(e) = self.write_event(event).await {
+                    log::error!("Failed to release touch mouse button: {e:?}");
+                }
+            }
+        }
 
         // Nothing else is required when turning off input interception.
         if mode == InterceptMode::None || mode == InterceptMode::Pass {
             return;
         }
 
+        // Source buttons are not handled while intercepting, so their target
+        // events are no longer auto-fired.
+        let ids: Vec<u64> = self
+            .auto_fire_events
+            .drain()
+            .flat_map(|(_, ids)| ids)
+            .collect();
+        for id in ids {
+            self.cancel_scheduled_event(id);
+        }
+
         // If intercept mode is being turned on, clear the state from
         // any target devices to prevent further input events.
         for (path, device) in self.target_devices.iter() {
@@ -1115,13 +2848,117 @@ impl CompositeDevice {
         }
     }
 
+    /// Sets how input from the physical touchscreen is emitted to target
+    /// devices. Any mouse button held by a touch is released first.
+    async fn set_touchscreen_mode(&mut self, mode: TouchscreenMode) -> Result<(), Box<dyn Error>> {
+        log::debug!("Setting touchscreen mode to: {}", mode.as_str());
+        for event in self.touch_mouse.release() {
+            self.write_event(event).await?;
+        }
+        self.touchscreen_mode = mode;
+        self.update_source_bypass().await;
+        Ok(())
+    }
+
+    /// Returns an error describing why the given intercept mode cannot be
+    /// used with the capabilities of this composite device.
+    fn check_intercept_mode(&self, mode: InterceptMode) -> Result<(), String> {
+        let support = InterceptSupport {
+            capabilities: &self.capabilities,
+            activation_caps: &self.intercept_activation_caps,
+            has_dbus_target: !self.target_dbus_devices.is_empty(),
+            has_gamepad_target: self.target_devices.keys().any(|p| p.contains("gamepad")),
+        };
+        check_intercept_mode(mode, &support)
+    }
+
+    /// Returns true if all source events of the given mapping are currently
+    /// pressed and satisfy the chord timing options of the mapping.
+    fn is_chord_pressed(&self, mapping: &CapabilityMapping) -> bool {
+        let mut press_times = Vec::with_capacity(mapping.source_events.len());
+        for source_event in mapping.source_events.iter() {
+            let cap = source_event.clone().into();
+            if cap == Capability::NotImplemented {
+                continue;
+            }
+            if !self.translatable_active_inputs.contains(&cap) {
+                return false;
+            }
+            // Inputs that were already emitted untranslated cannot complete a chord
+            if self.translatable_passthrough_inputs.contains(&cap) {
+                return false;
+            }
+            if let Some(pressed_at) = self.translatable_press_times.get(&cap) {
+                press_times.push(*pressed_at);
+            }
+        }
+
+        if !chord_timing_met(mapping.chord.as_ref(), &press_times) {
+            log::trace!("Chord '{}' does not meet its timing options", mapping.name);
+            return false;
+        }
+
+        true
+    }
+
+    /// Emits the given translatable input event untranslated if it is still
+    /// pressed and was not consumed by a chord before its timeout expired.
+    async fn handle_chord_timeout(
+        &mut self,
+        event: NativeEvent,
+        pressed_at: Instant,
+    ) -> Result<(), Box<dyn Error>> {
+        let cap = event.as_capability();
+
+        // Ignore the timeout if the input was released or pressed again since
+        if self.translatable_press_times.get(&cap) != Some(&pressed_at) {
+            return Ok(());
+        }
+
+        // Ignore the timeout if the input is part of an emitted chord
+        let is_consumed = self.emitted_mappings.values().any(|mapping| {
+            mapping
+                .source_events
+                .iter()
+                .any(|source| Capability::from(source.clone()) == cap)
+        });
+        if is_consumed {
+            return Ok(());
+        }
+
+        log::trace!("Chord timed out. Emitting partial chord input: {:?}", cap);
+        self.translatable_passthrough_inputs.insert(cap);
+        self.handle_event(event).await
+    }
+
+    /// Set whether or not events are blocked from being emitted to target
+    /// devices. When inhibited, the state of all target devices is cleared so
+    /// no inputs are left pressed.
+    async fn set_inhibited(&mut self, inhibited: bool) {
+        if self.inhibited == inhibited {
+            return;
+        }
+        log::debug!("Setting inhibited to: {inhibited}");
+        self.inhibited = inhibited;
+        self.update_source_bypass().await;
+        if !inhibited {
+            return;
+        }
+
+        for (path, device) in self.target_devices.iter() {
+            log::debug!("Clearing state on device: {path}");
+            if let Err(e) = device.clear_state().await {
+                log::error!("Failed to clear state on target device {path}: {e:?}");
+            }
+        }
+    }
+
     /// Translates the given event into a different event based on the given
     /// [CapabilityMap].
     async fn translate_capability(&mut self, event: &NativeEvent) -> Result<(), Box<dyn Error>> {
-        // Get the capability map to translate input events
-        let Some(map) = self.capability_map.as_ref() else {
+        if self.capability_map.is_none() {
             return Err("Cannot translate device capability without capability map!".into());
-        };
+        }
 
         // Add or remove the event from translatable_active_inputs.
         let event_capability = event.as_capability();
@@ -1132,7 +2969,10 @@ impl CompositeDevice {
         if event.pressed() {
             if capability_idx.is_none() {
                 log::trace!("Adding capability to active inputs: {:?}", event_capability);
-                self.translatable_active_inputs.push(event_capability);
+                self.translatable_active_inputs
+                    .push(event_capability.clone());
+                self.translatable_press_times
+                    .insert(event_capability.clone(), Instant::now());
                 log::trace!(
                     "Active translatable inputs: {:?}",
                     self.translatable_active_inputs
@@ -1147,14 +2987,51 @@ impl CompositeDevice {
             );
             let idx = capability_idx.unwrap();
             self.translatable_active_inputs.remove(idx);
+            self.translatable_press_times.remove(&event_capability);
             log::trace!(
                 "Active translatable inputs: {:?}",
                 self.translatable_active_inputs
             );
+
+            // If the input was emitted untranslated because its chord timed
+            // out, emit the release untranslated as well.
+            if self
+                .translatable_passthrough_inputs
+                .remove(&event_capability)
+            {
+                log::trace!("Releasing partial chord input: {:?}", event_capability);
+                return self.handle_event(event.clone()).await;
+            }
         } else {
             return Ok(());
         }
 
+        // Get the capability map to translate input events
+        let Some(map) = self.capability_map.as_ref() else {
+            return Ok(());
+        };
+
+        // Emit the input untranslated if it is only used by mappings whose
+        // conditions are not met.
+        if event.pressed() {
+            let has_active_mapping = map.mapping.iter().any(|mapping| {
+                mapping
+                    .source_events
+                    .iter()
+                    .any(|source| Capability::from(source.clone()) == event_capability)
+                    && self.is_mapping_condition_met(mapping)
+            });
+            if !has_active_mapping {
+                log::trace!(
+                    "Mapping conditions not met for input: {:?}",
+                    event_capability
+                );
+                self.translatable_passthrough_inputs
+                    .insert(event_capability);
+                return self.handle_event(event.clone()).await;
+            }
+        }
+
         // Keep a list of events to emit. The reason for this is some mapped
         // capabilities may use one or more of the same source capability and
         // they would release at the same time.
@@ -1195,19 +3072,7 @@ impl CompositeDevice {
 
             // If the event is pressed, check for any matches to send a 'press' event
             if event.pressed() {
-                let mut is_missing_source_event = false;
-                for source_event in mapping.source_events.iter() {
-                    let cap = source_event.clone().into();
-                    if cap == Capability::NotImplemented {
-                        continue;
-                    }
-                    if !self.translatable_active_inputs.contains(&cap) {
-                        is_missing_source_event = true;
-                        break;
-                    }
-                }
-
-                if !is_missing_source_event {
+                if self.is_chord_pressed(mapping) && self.is_mapping_condition_met(mapping) {
                     let cap = mapping.target_event.clone().into();
                     if cap == Capability::NotImplemented {
                         continue;
@@ -1221,6 +3086,33 @@ impl CompositeDevice {
             }
         }
 
+        // If the pressed input is part of an incomplete chord that should emit
+        // partial input, schedule a timeout to emit it untranslated.
+        if event.pressed() {
+            let is_consumed = self.emitted_mappings.values().any(|mapping| {
+                mapping
+                    .source_events
+                    .iter()
+                    .any(|source| Capability::from(source.clone()) == event_capability)
+            });
+            let chords = map
+                .mapping
+                .iter()
+                .filter(|mapping| {
+                    mapping
+                        .source_events
+                        .iter()
+                        .any(|source| Capability::from(source.clone()) == event_capability)
+                })
+                .filter_map(|mapping| mapping.chord.as_ref());
+            let timeout = partial_timeout(chords);
+            let pressed_at = self.translatable_press_times.get(&event_capability);
+            if let (false, Some(timeout), Some(pressed_at)) = (is_consumed, timeout, pressed_at) {
+                let command = CompositeCommand::ChordTimeout(event.clone(), *pressed_at);
+                self.scheduler.schedule(timeout, command);
+            }
+        }
+
         // Emit the translated events. If this translated event has been emitted
         // very recently, delay sending subsequent events of the same type.
         let sleep_time = Duration::from_millis(4);
@@ -1230,13 +3122,8 @@ impl CompositeDevice {
             let cap = event.as_capability();
             if self.translated_recent_events.contains(&cap) {
                 log::debug!("Event emitted too quickly. Delaying emission.");
-                let tx = self.tx.clone();
-                tokio::task::spawn(async move {
-                    tokio::time::sleep(sleep_time).await;
-                    if let Err(e) = tx.send(CompositeCommand::HandleEvent(event)).await {
-                        log::error!("Failed to send delayed event command: {:?}", e);
-                    }
-                });
+                self.scheduler
+                    .schedule(sleep_time, CompositeCommand::HandleEvent(event));
 
                 continue;
             }
@@ -1244,14 +3131,9 @@ impl CompositeDevice {
             // Add the event to our list of recently device translated events
             self.translated_recent_events.insert(event.as_capability());
 
-            // Spawn a task to remove the event from recent translated
-            let tx = self.tx.clone();
-            tokio::task::spawn(async move {
-                tokio::time::sleep(sleep_time).await;
-                if let Err(e) = tx.send(CompositeCommand::RemoveRecentEvent(cap)).await {
-                    log::error!("Failed to send remove recent event command: {:?}", e);
-                }
-            });
+            // Schedule removing the event from recent translated
+            self.scheduler
+                .schedule(sleep_time, CompositeCommand::RemoveRecentEvent(cap));
 
             log::trace!("Emitting event: {:?}", event);
             self.handle_event(event).await?;
@@ -1266,83 +3148,27 @@ impl CompositeDevice {
         &self,
         event: &NativeEvent,
     ) -> Result<Vec<NativeEvent>, Box<dyn Error>> {
-        // Lookup the profile mapping associated with this event capability. If
-        // none is found, return the original un-translated event.
-        let source_cap = event.as_capability();
-        if let Some(mappings) = self.device_profile_config_map.get(&source_cap) {
-            // Find which mappings in the device profile matches this source event
-            let matched_mappings = mappings
-                .iter()
-                .filter(|mapping| mapping.source_matches_properties(event));
-
-            let mut events = Vec::new();
-            // Based on all found mappings, translate the event
-            for mapping in matched_mappings {
-                log::trace!(
-                    "Found translation for event {:?} in profile mapping: {}",
-                    source_cap,
-                    mapping.name
-                );
-
-                // Translate the event into the defined target event(s)
-                for target_event in mapping.target_events.iter() {
-                    // TODO: We can cache this conversion for faster translation
-                    let target_cap: Capability = target_event.clone().into();
-                    let result = event.get_value().translate(
-                        &source_cap,
-                        &mapping.source_event,
-                        &target_cap,
-                        target_event,
-                    );
-                    let value = match result {
-                        Ok(v) => v,
-                        Err(err) => {
-                            match err {
-                                TranslationError::NotImplemented => {
-                                    log::warn!(
-                                        "Translation not implemented for profile mapping '{}': {:?} -> {:?}",
-                                        mapping.name,
-                                        source_cap,
-                                        target_cap,
-                                    );
-                                    continue;
-                                }
-                                TranslationError::ImpossibleTranslation(msg) => {
-                                    log::warn!(
-                                        "Impossible translation for profile mapping '{}': {msg}",
-                                        mapping.name
-                                    );
-                                    continue;
-                                }
-                                TranslationError::InvalidSourceConfig(msg) => {
-                                    log::warn!("Invalid source event config in profile mapping '{}': {msg}", mapping.name);
-                                    continue;
-                                }
-                                TranslationError::InvalidTargetConfig(msg) => {
-                                    log::warn!("Invalid target event config in profile mapping '{}': {msg}", mapping.name);
-                                    continue;
-                                }
-                            }
-                        }
-                    };
-                    if matches!(value, InputValue::None) {
-                        continue;
-                    }
-
-                    let event = NativeEvent::new_translated(source_cap.clone(), target_cap, value);
-                    events.push(event);
-                }
-            }
-            return Ok(events);
-        }
-
-        log::trace!("No translation mapping found for event: {:?}", source_cap);
-        Ok(vec![event.clone()])
+        Ok(translation::translate_event(
+            &self.device_profile_config_map,
+            event,
+        ))
     }
 
     /// Executed whenever a source device is added to this [CompositeDevice].
     async fn on_source_device_added(&mut self, device: UdevDevice) -> Result<(), Box<dyn Error>> {
-        if let Err(e) = self.add_source_device(device) {
+        let source_device = match self.create_source_device(device) {
+            Ok(source_device) => source_device,
+            Err(e) => return Err(e.to_string().into()),
+        };
+        self.start_source_device(source_device).await
+    }
+
+    /// Keep track of the given created source device and start running it
+    async fn start_source_device(
+        &mut self,
+        source_device: SourceDevice,
+    ) -> Result<(), Box<dyn Error>> {
+        if let Err(e) = self.track_source_device(source_device) {
             return Err(e.to_string().into());
         }
         self.run_source_devices().await?;
@@ -1370,6 +3196,16 @@ impl CompositeDevice {
             self.source_devices_used.remove(idx);
         };
         self.source_devices_blocked.remove(&id);
+        self.source_devices_running.remove(&id);
+        self.source_restarts.finish(&id);
+        self.source_bypass_capabilities.remove(&id);
+        self.source_controller_ids.remove(&id);
+        self.source_calibrations.remove(&id);
+        self.source_output_capabilities.remove(&id);
+        self.source_rumbles.remove(&id);
+        if let Some(pool) = self.translation_pool.as_ref() {
+            pool.remove_source(&id);
+        }
 
         // Signal to DBus that source devices have changed
         self.signal_sources_changed().await;
@@ -1386,48 +3222,116 @@ impl CompositeDevice {
         Ok(())
     }
 
+    /// Restart any source devices that have been stuck polling for longer
+    /// than the watchdog timeout
+    async fn check_source_watchdog(&mut self) {
+        let Some(timeout) = self.source_watchdog_timeout else {
+            return;
+        };
+        let stalled: Vec<String> = self
+            .source_devices_running
+            .keys()
+            .filter(|id| watchdog::is_stalled(id, timeout))
+            .cloned()
+            .collect();
+        for device_id in stalled {
+            log::warn!(
+                "Source device {device_id} has been stuck for more than {timeout:?}. Restarting it."
+            );
+            if let Err(e) = self.restart_source_device(device_id.clone()).await {
+                log::error!("Failed to restart source device {device_id}: {e:?}");
+            }
+        }
+        self.scheduler.schedule(
+            watchdog::CHECK_INTERVAL,
+            CompositeCommand::CheckSourceWatchdog,
+        );
+    }
+
+    /// Abandon the stuck thread of the source device with the given id and
+    /// start the source device again. The stuck thread may still hold the
+    /// device (e.g. its evdev grab), so the old source device is only
+    /// replaced once the new one could be created. Until then, the source
+    /// device is kept and the restart is retried.
+    async fn restart_source_device(&mut self, device_id: String) -> Result<(), Box<dyn Error>> {
+        let Some(device) = self.source_devices_running.get(&device_id).cloned() else {
+            return Err(format!("No running source device found with id: {device_id}").into());
+        };
+        if !self.source_restarts.begin(&device_id) {
+            return Err(format!("Source device {device_id} is not running").into());
+        }
+        self.source_devices.remove(&device_id);
+
+        let source_device = match self.create_source_device(device.clone()) {
+            Ok(source_device) => source_device,
+            Err(e) => {
+                let delay = self.source_restarts.failed(&device_id);
+                log::warn!(
+                    "Unable to start source device {device_id} again, retrying in {delay:?}: {e:?}"
+                );
+                self.scheduler
+                    .schedule(delay, CompositeCommand::RestartSourceDevice(device_id));
+                return Ok(());
+            }
+        };
+
+        // Keep the source device blocked if it was blocked over DBus
+        let blocked = self.source_devices_blocked.contains(&device_id);
+        self.on_source_device_removed(device).await?;
+        self.start_source_device(source_device).await?;
+        if blocked && self.source_devices.contains_key(&device_id) {
+            self.source_devices_blocked.insert(device_id.clone());
+        }
+
+        self.signal_source_device_restarted(device_id);
+        Ok(())
+    }
+
     /// Creates and adds a source device using the given [SourceDeviceInfo]
     fn add_source_device(
         &mut self,
         device: UdevDevice,
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let source_device = self.create_source_device(device)?;
+        self.track_source_device(source_device)
+    }
+
+    /// Creates a source device using the given [SourceDeviceInfo]. This opens
+    /// the device, which can fail if it is still held by another thread.
+    fn create_source_device(
+        &self,
+        device: UdevDevice,
+    ) -> Result<SourceDevice, Box<dyn Error + Send + Sync>> {
         // Check to see if this source device should be blocked.
-        let mut is_blocked = false;
-        let mut is_blocked_evdev = false;
-        if let Some(source_config) = self.config.get_matching_device(&device) {
-            if let Some(blocked) = source_config.blocked {
-                is_blocked = blocked;
-            }
-        }
+        let source_config = self.config.get_matching_device(&device);
+        let is_blocked = source_config
+            .as_ref()
+            .and_then(|config| config.blocked)
+            .unwrap_or(false);
 
         let subsystem = device.subsystem();
-
         let source_device = match subsystem.as_str() {
             "input" => {
                 log::debug!("Adding source device: {:?}", device.name());
-                if is_blocked {
-                    is_blocked_evdev = true;
-                }
-                let device = EventDevice::new(device, self.client(), is_blocked)?;
+                let device =
+                    EventDevice::new(device, self.client(), is_blocked, source_config.as_ref())?;
                 SourceDevice::Event(device)
             }
             "hidraw" => {
                 log::debug!("Adding source device: {:?}", device.name());
-                let device = HidRawDevice::new(device, self.client())?;
+                let device = HidRawDevice::new(device, self.client(), source_config.as_ref())?;
                 SourceDevice::HidRaw(device)
             }
             "iio" => {
-                // Get any defined config for the IIO device
-                let config = if let Some(device_config) = self.config.get_matching_device(&device) {
-                    device_config.iio
-                } else {
-                    None
-                };
-
                 log::debug!("Adding source device: {:?}", device.name());
-                let device = IioDevice::new(device, self.client(), config)?;
+                let device = IioDevice::new(device, self.client(), source_config.as_ref())?;
                 SourceDevice::Iio(device)
             }
+            "external" => {
+                log::debug!("Adding source device: {:?}", device.name());
+                let device = ExternalDevice::new(device, self.client(), source_config.as_ref())?;
+                SourceDevice::External(device)
+            }
             _ => {
                 return Err(format!(
                     "Unspported subsystem: {subsystem}, unable to add source device {}",
@@ -1437,11 +3341,42 @@ impl CompositeDevice {
             }
         };
 
+        Ok(source_device)
+    }
+
+    /// Keep track of the capabilities, settings and paths of the given
+    /// created source device so it is started with [Self::run_source_devices]
+    fn track_source_device(
+        &mut self,
+        source_device: SourceDevice,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let source_config = self
+            .config
+            .get_matching_device(source_device.get_device_ref());
+        let is_blocked = source_config
+            .as_ref()
+            .and_then(|config| config.blocked)
+            .unwrap_or(false);
+        let is_blocked_evdev = is_blocked && matches!(source_device, SourceDevice::Event(_));
+        let controller_id = get_controller_id(source_device.get_device_ref());
+
         // Get the capabilities of the source device.
         // TODO: When we *remove* a source device, we also need to remove
         // capabilities
         if !is_blocked {
             let capabilities = source_device.get_capabilities()?;
+
+            // Keep track of source devices that may bypass the composite device
+            let raw_passthrough = source_config
+                .as_ref()
+                .and_then(|config| config.raw_passthrough)
+                .unwrap_or(false);
+            if raw_passthrough {
+                self.source_bypass_capabilities
+                    .insert(source_device.get_id(), capabilities.clone());
+            }
+
+            self.input_statistics.track(capabilities.iter());
             for cap in capabilities {
                 if self.translatable_capabilities.contains(&cap) {
                     continue;
@@ -1450,20 +3385,42 @@ impl CompositeDevice {
             }
         }
 
-        // Check if this device should be blocked from sending events to target devices.
+        // Keep track of the output capabilities of the source device so output
+        // events can be converted if needed.
         let id = source_device.get_id();
-        if let Some(device_config) = self
-            .config
-            .get_matching_device(source_device.get_device_ref())
-        {
-            if let Some(blocked) = device_config.blocked {
-                // Blocked event devices should still be run so they can be
-                // EVIOGRAB'd
-                if blocked && !is_blocked_evdev {
-                    self.source_devices_blocked.insert(id.clone());
+        match source_device.get_output_capabilities() {
+            Ok(caps) => {
+                self.source_output_capabilities
+                    .insert(id.clone(), caps.into_iter().collect());
+            }
+            Err(e) => {
+                log::debug!("Unable to get output capabilities for {id}: {e:?}");
+            }
+        }
+
+        // Apply the calibration overlay of the controller if it was calibrated
+        // on any composite device.
+        if let Some(controller_id) = controller_id {
+            match Calibration::load_controller(&controller_id) {
+                Ok(Some(overlay)) => {
+                    log::info!("Applying calibration overlay for controller {controller_id}");
+                    self.source_calibrations
+                        .insert(id.clone(), self.calibration.overlay(&overlay));
+                }
+                Ok(None) => (),
+                Err(e) => {
+                    log::warn!("Failed to load calibration overlay for {controller_id}: {e:?}")
                 }
             }
-        };
+            self.source_controller_ids.insert(id.clone(), controller_id);
+        }
+
+        // Check if this device should be blocked from sending events to target
+        // devices. Blocked event devices should still be run so they can be
+        // EVIOGRAB'd
+        if is_blocked && !is_blocked_evdev {
+            self.source_devices_blocked.insert(id.clone());
+        }
 
         // TODO: Based on the capability map in the config, translate
         // the capabilities.
@@ -1476,25 +3433,92 @@ impl CompositeDevice {
         Ok(())
     }
 
-    /// Load the given device profile from the given path
-    pub fn load_device_profile(&mut self, profile: DeviceProfile) -> Result<(), Box<dyn Error>> {
-        log::debug!("Loading device profile {}", profile.name);
+    /// Load the given device profile that was loaded from the given source
+    /// and switch to the target devices defined in the profile, if any
+    pub fn load_device_profile(
+        &mut self,
+        profile: DeviceProfile,
+        source: ProfileSource,
+    ) -> Result<(), Box<dyn Error>> {
+        let target_devices = profile_target_devices(&profile, &GlobalConfig::load());
+        self.load_device_profile_mappings(profile, source)?;
+
+        // Set the target devices to use if it is defined in the profile
+        if let Some(target_devices) = target_devices {
+            let tx = self.tx.clone();
+            tokio::task::spawn(async move {
+                if let Err(e) = tx
+                    .send(CompositeCommand::SetTargetDevices(target_devices))
+                    .await
+                {
+                    log::error!("Failed to send set target devices: {e:?}");
+                }
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Load the given device profile that was loaded from the given source
+    /// without changing the target devices
+    fn load_device_profile_mappings(
+        &mut self,
+        profile: DeviceProfile,
+        source: ProfileSource,
+    ) -> Result<(), Box<dyn Error>> {
+        log::info!(
+            dbus_path = self.dbus_path.as_str(), profile_name = profile.name.as_str();
+            "Loading device profile {} on {}", profile.name, self.dbus_path
+        );
+        let touchscreen_mode = match profile.touchscreen.as_deref() {
+            Some(mode) => mode.parse::<TouchscreenMode>()?,
+            None => TouchscreenMode::default(),
+        };
+
+        // Loading a profile replaces any pending profile preview
+        self.profile_preview = None;
+
         // Remove all outdated capability mappings.
         log::debug!("Clearing old device profile mappings");
         self.device_profile_config_map.clear();
 
         // Load and parse the device profile
         self.device_profile = Some(profile.name.clone());
+        self.touchscreen_mode = touchscreen_mode;
+        self.touch_mouse = TouchMouse::new();
+
+        // Keysym target events are translated into keys using the active
+        // keyboard layout.
+        let uses_keysyms = profile.mapping.iter().any(|mapping| {
+            mapping
+                .target_events
+                .iter()
+                .any(|event| event.keysym.is_some())
+        });
+        let keysyms = uses_keysyms.then(KeysymTable::load_active);
 
         // Loop through every mapping in the profile, extract the source and target events,
         // and map them into our profile map.
         for mapping in profile.mapping.iter() {
             log::trace!("Loading mapping from profile: {}", mapping.name);
+            let mapping = match keysyms.as_ref() {
+                Some(keysyms) => keysyms.resolve_mapping(mapping),
+                None => mapping.clone(),
+            };
 
             // Convert the source event configuration in the mapping into a
             // capability that can be easily matched on during event translation
             let source_event_cap: Capability = mapping.source_event.clone().into();
 
+            // System keys keep working unless remapping them is allowed
+            if !self.system_keys.allows_remap(&source_event_cap) {
+                log::warn!(
+                    "Ignoring mapping '{}': remapping {source_event_cap} is not allowed by the device config",
+                    mapping.name
+                );
+                continue;
+            }
+
             // Convert the target events configuration into a vector of capabilities
             // that can be easily used to create translated events.
             let mut target_events_caps = Vec::new();
@@ -1508,20 +3532,7 @@ impl CompositeDevice {
                 .device_profile_config_map
                 .entry(source_event_cap)
                 .or_default();
-            config_map.push(mapping.clone());
-        }
-
-        // Set the target devices to use if it is defined in the profile
-        if let Some(target_devices) = profile.target_devices {
-            let tx = self.tx.clone();
-            tokio::task::spawn(async move {
-                if let Err(e) = tx
-                    .send(CompositeCommand::SetTargetDevices(target_devices))
-                    .await
-                {
-                    log::error!("Failed to send set target devices: {e:?}");
-                }
-            });
+            config_map.push(mapping);
         }
 
         // Clear the state from all target devices
@@ -1535,10 +3546,175 @@ impl CompositeDevice {
             }
         });
 
+        self.update_translation_pool();
+        self.profile_source = source;
+
         log::debug!("Successfully loaded device profile: {}", profile.name);
         Ok(())
     }
 
+    /// Load the default profile file if it has appeared while the embedded
+    /// default profile is in use. Checks again later if the file is still
+    /// missing.
+    fn reload_default_profile(&mut self) {
+        // Another profile was loaded in the meantime
+        if self.profile_source != ProfileSource::Embedded {
+            return;
+        }
+        let path = default_profile_path();
+        if path.exists() {
+            let path = path.to_string_lossy().to_string();
+            log::info!("Default profile appeared at {path}. Reloading default profile.");
+            match DeviceProfile::from_yaml_file(path.clone()) {
+                Ok(profile) => match self.load_device_profile(profile, ProfileSource::File(path)) {
+                    Ok(_) => return,
+                    Err(e) => log::error!("Failed to load default profile: {e}"),
+                },
+                // The file may not be completely written yet
+                Err(e) => log::warn!("Unable to load default profile: {e}"),
+            }
+        }
+        self.scheduler.schedule(
+            DEFAULT_PROFILE_RETRY_INTERVAL,
+            CompositeCommand::ReloadDefaultProfile,
+        );
+    }
+
+    /// Temporarily load the device profile from the given path. If the
+    /// profile is not confirmed within the given timeout, the profile that was
+    /// loaded before the preview is restored.
+    async fn load_profile_temporary(
+        &mut self,
+        path: String,
+        timeout_secs: u64,
+    ) -> Result<(), Box<dyn Error>> {
+        log::debug!("Temporarily loading profile from path: {path}");
+        if timeout_secs == 0 {
+            return Err("Profile preview timeout must be greater than zero".into());
+        }
+        let source = ProfileSource::File(path.clone());
+        let profile = DeviceProfile::from_yaml_file(path)?;
+
+        // If a preview is already pending, keep the state from before the
+        // first preview so a revert restores the last confirmed profile.
+        let old_profile = self.device_profile.clone();
+        let old_profile_config_map = self.device_profile_config_map.clone();
+        let mut preview = match self.profile_preview.take() {
+            Some(preview) => preview,
+            None => ProfilePreview {
+                id: 0,
+                profile: old_profile.clone(),
+                source: self.profile_source.clone(),
+                config_map: old_profile_config_map.clone(),
+                target_devices: None,
+            },
+        };
+        if preview.target_devices.is_none()
+            && profile_target_devices(&profile, &GlobalConfig::load()).is_some()
+        {
+            let mut target_types = Vec::new();
+            for target in self.target_devices.values() {
+                match target.get_type().await {
+                    Ok(kind) => target_types.push(kind),
+                    Err(e) => {
+                        self.profile_preview = Some(preview);
+                        return Err(format!("Failed to request target type: {e:?}").into());
+                    }
+                }
+            }
+            preview.target_devices = Some(target_types);
+        }
+
+        if let Err(e) = self.load_device_profile(profile, source) {
+            self.device_profile = old_profile;
+            self.device_profile_config_map = old_profile_config_map;
+            self.update_translation_pool();
+            self.profile_preview = Some(preview);
+            return Err(e);
+        }
+
+        // Schedule the revert in case the profile is never confirmed
+        self.profile_preview_count += 1;
+        preview.id = self.profile_preview_count;
+        self.scheduler.schedule(
+            Duration::from_secs(timeout_secs),
+            CompositeCommand::RevertProfilePreview(preview.id),
+        );
+        self.profile_preview = Some(preview);
+        log::info!("Reverting profile in {timeout_secs}s unless it is confirmed");
+
+        Ok(())
+    }
+
+    /// Confirm the temporarily loaded profile so it is not reverted
+    fn confirm_profile(&mut self) -> Result<(), String> {
+        if self.profile_preview.take().is_none() {
+            return Err("No temporary profile is waiting for confirmation".to_string());
+        }
+        log::info!(
+            "Confirmed temporary profile: {}",
+            self.device_profile.clone().unwrap_or_default()
+        );
+        Ok(())
+    }
+
+    /// Restore the profile that was loaded before the profile preview with the
+    /// given id if it was not confirmed.
+    fn revert_profile_preview(&mut self, id: u64) {
+        if self.profile_preview.as_ref().map(|p| p.id) != Some(id) {
+            return;
+        }
+        let Some(preview) = self.profile_preview.take() else {
+            return;
+        };
+        log::warn!(
+            "Temporary profile was not confirmed in time. Reverting to profile: {}",
+            preview.profile.clone().unwrap_or_default()
+        );
+        self.device_profile = preview.profile.clone();
+        self.profile_source = preview.source;
+        self.device_profile_config_map = preview.config_map;
+        self.update_translation_pool();
+        if self.profile_source == ProfileSource::Embedded {
+            self.reload_default_profile();
+        }
+
+        // Restore the target devices if the previewed profile changed them
+        let tx = self.tx.clone();
+        let target_devices = self.target_devices.clone();
+        let target_types = preview.target_devices;
+        tokio::task::spawn(async move {
+            if let Some(target_types) = target_types {
+                if let Err(e) = tx
+                    .send(CompositeCommand::SetTargetDevices(target_types))
+                    .await
+                {
+                    log::error!("Failed to send set target devices: {e:?}");
+                }
+            }
+            for (path, device) in target_devices.iter() {
+                log::debug!("Clearing state on device: {path}");
+                if let Err(e) = device.clear_state().await {
+                    log::error!("Failed to clear state on target device {path}: {e:?}");
+                }
+            }
+        });
+
+        self.signal_profile_reverted(preview.profile.unwrap_or_default());
+    }
+
+    /// Update the device profile used by the translation pool
+    fn update_translation_pool(&mut self) {
+        // Profile mappings also decide which source events may bypass the
+        // composite device.
+        self.request_source_bypass_update();
+
+        let Some(pool) = self.translation_pool.as_ref() else {
+            return;
+        };
+        self.profile_generation = pool.set_profile(self.device_profile_config_map.clone());
+    }
+
     fn set_intercept_activation(
         &mut self,
         activation_caps: Vec<Capability>,
@@ -1546,6 +3722,201 @@ impl CompositeDevice {
     ) {
         self.intercept_activation_caps = activation_caps;
         self.intercept_mode_target_cap = target_cap;
+        self.request_source_bypass_update();
+    }
+
+    /// Ask the composite device to re-evaluate which source devices can
+    /// bypass it. This is used where the device cannot be awaited directly.
+    fn request_source_bypass_update(&self) {
+        if self.source_bypass_capabilities.is_empty() {
+            return;
+        }
+        let tx = self.tx.clone();
+        tokio::task::spawn(async move {
+            if let Err(e) = tx.send(CompositeCommand::UpdateSourceBypass).await {
+                log::error!("Failed to request source bypass update: {e:?}");
+            }
+        });
+    }
+
+    /// Update the bypass routes of all source devices configured with
+    /// 'raw_passthrough'. Source events can only bypass the composite device
+    /// if nothing in the composite device would alter or consume them, so
+    /// bypass is disabled while input is intercepted, inhibited, calibrated,
+    /// cloned, recorded by a debug target device or counted in the input
+    /// statistics.
+    async fn update_source_bypass(&self) {
+        if self.source_bypass_capabilities.is_empty() {
+            return;
+        }
+        let clone_output = self
+            .config
+            .options
+            .as_ref()
+            .and_then(|options| options.clone_output)
+            .unwrap_or(false);
+        let enabled = BypassConditions {
+            intercepted: self.intercept_mode != InterceptMode::None,
+            inhibited: self.inhibited,
+            calibrating: self.calibrator.is_some(),
+            clone_output,
+            debugging: !self.target_debug_devices.is_empty(),
+            collecting_statistics: self.input_statistics.is_enabled(),
+        }
+        .allows_bypass();
+
+        for (id, caps) in self.source_bypass_capabilities.iter() {
+            let Some(source) = self.source_devices.get(id) else {
+                continue;
+            };
+            let routes = if enabled && !self.source_devices_blocked.contains(id) {
+                self.get_bypass_routes(caps)
+            } else {
+                None
+            };
+            if let Err(e) = source.set_bypass(routes).await {
+                log::error!("Failed to set bypass for source device {id}: {e:?}");
+            }
+        }
+    }
+
+    /// Rebuild the map of capabilities to DBus target devices that is used to
+    /// mirror events when 'clone_output' is enabled. Includes the DBus
+    /// devices of the composite device and any attached "dbus" target devices.
+    async fn update_dbus_target_capabilities(&mut self) {
+        let mut targets_by_capability: HashMap<Capability, HashSet<String>> = HashMap::new();
+        let targets = self
+            .target_dbus_devices
+            .iter()
+            .chain(self.target_devices.iter());
+        for (path, target) in targets {
+            if self.target_devices.contains_key(path) {
+                match target.get_type().await {
+                    Ok(kind) if kind == "dbus" => (),
+                    Ok(_) => continue,
+                    Err(e) => {
+                        log::error!("Failed to get target device type: {e:?}");
+                        continue;
+                    }
+                }
+            }
+            let caps = match target.get_capabilities().await {
+                Ok(caps) => caps,
+                Err(e) => {
+                    log::error!("Failed to get dbus target capabilities: {e:?}");
+                    continue;
+                }
+            };
+            for cap in caps {
+                targets_by_capability
+                    .entry(cap)
+                    .or_default()
+                    .insert(path.clone());
+            }
+        }
+        self.target_dbus_by_capability = targets_by_capability;
+    }
+
+    /// Add the given capabilities that the given source device discovered
+    /// after it was added, e.g. because the controller only sends some
+    /// reports after first use.
+    async fn update_source_capabilities(
+        &mut self,
+        device_id: String,
+        capabilities: Vec<Capability>,
+    ) {
+        if self.source_devices_blocked.contains(&device_id) {
+            return;
+        }
+
+        // Newly discovered capabilities of source devices with
+        // 'raw_passthrough' may be able to bypass the composite device
+        let mut bypass_changed = false;
+        if let Some(bypass_caps) = self.source_bypass_capabilities.get_mut(&device_id) {
+            for cap in capabilities.iter() {
+                if !bypass_caps.contains(cap) {
+                    bypass_caps.push(cap.clone());
+                    bypass_changed = true;
+                }
+            }
+        }
+
+        self.input_statistics.track(capabilities.iter());
+        let mut changed = false;
+        for cap in capabilities {
+            if self.translatable_capabilities.contains(&cap) {
+                continue;
+            }
+            if self.capabilities.insert(cap.clone()) {
+                log::info!("Discovered capability {cap} of source device {device_id}");
+                changed = true;
+            }
+        }
+
+        if bypass_changed {
+            self.update_source_bypass().await;
+        }
+        if changed {
+            self.signal_capabilities_changed();
+        }
+    }
+
+    /// Returns the target devices that events of the given source capabilities
+    /// can be written to directly. Returns None if no capability can bypass the
+    /// composite device.
+    fn get_bypass_routes(&self, caps: &[Capability]) -> Option<BypassRoutes> {
+        let mut routes = BypassRoutes::new();
+        for cap in caps {
+            if !self.is_bypass_capability(cap) {
+                continue;
+            }
+            let Some(paths) = self.target_devices_by_capability.get(cap) else {
+                continue;
+            };
+            let targets: Vec<TargetDeviceClient> = paths
+                .iter()
+                .filter_map(|path| self.target_devices.get(path))
+                .cloned()
+                .collect();
+            if targets.is_empty() {
+                continue;
+            }
+            routes.insert(cap.clone(), targets);
+        }
+
+        if routes.is_empty() {
+            return None;
+        }
+        Some(routes)
+    }
+
+    /// Returns true if events of the given capability are passed through the
+    /// composite device unchanged and can be written to target devices
+    /// directly by the source device.
+    fn is_bypass_capability(&self, cap: &Capability) -> bool {
+        if *cap == Capability::NotImplemented
+            || self.is_translatable_capability(cap)
+            || self.device_profile_config_map.contains_key(cap)
+            || self.condition_capabilities.contains(cap)
+            || self.intercept_activation_caps.contains(cap)
+        {
+            return false;
+        }
+
+        // Stick and gyro events are altered by calibration and drift correction
+        // and touchscreen events by the touchscreen mode.
+        match cap {
+            Capability::Gamepad(Gamepad::Axis(_)) | Capability::Gamepad(Gamepad::Gyro) => {
+                self.calibration.left_stick.is_none()
+                    && self.calibration.right_stick.is_none()
+                    && self.calibration.gyro.is_none()
+                    && self.drift_detector.is_none()
+                    && self.drift_corrections.left_stick.is_none()
+                    && self.drift_corrections.right_stick.is_none()
+            }
+            Capability::Touchscreen(_) => self.touchscreen_mode == TouchscreenMode::Passthrough,
+            _ => true,
+        }
     }
 
     /// Adds or removes the given capability to the active inputs and returns true. If an up event is
@@ -1607,6 +3978,7 @@ impl CompositeDevice {
             // Send the intercept target.
             log::debug!("Found activation chord!");
             self.set_intercept_mode(InterceptMode::Always).await;
+            self.signal_intercept_activated();
             let target_event =
                 NativeEvent::new(self.intercept_mode_target_cap.clone(), event.get_value());
             log::trace!("Release event: {target_event:?}");
@@ -1680,6 +4052,7 @@ impl CompositeDevice {
                 self.intercept_active_inputs.clear();
 
                 self.set_intercept_mode(InterceptMode::Always).await;
+                self.signal_intercept_activated();
                 // Generate a new chord
                 let event = NativeEvent::new(
                     self.intercept_mode_target_cap.clone(),
@@ -1732,6 +4105,18 @@ impl CompositeDevice {
         Ok(false)
     }
 
+    /// Set the target devices of the target loadout with the given name from
+    /// the global config
+    async fn set_target_loadout(&mut self, name: String) -> Result<(), String> {
+        let Some(device_types) = GlobalConfig::load().target_loadout(name.as_str()) else {
+            return Err(format!("Unknown target loadout: {name}"));
+        };
+        log::info!("Using target loadout '{name}': {device_types:?}");
+        self.set_target_devices(device_types)
+            .await
+            .map_err(|e| format!("Failed to set target devices: {e}"))
+    }
+
     /// Set the given target devices on the composite device. This will create
     /// new target devices, attach them to this device, and stop/remove any
     /// existing devices.
@@ -1795,59 +4180,265 @@ impl CompositeDevice {
                 log::error!("Failed to stop old target device: {e:?}");
             }
         }
+        self.update_source_bypass().await;
+        self.update_dbus_target_capabilities().await;
+
+        // Create new target devices using the input manager
+        for kind in device_types_to_start {
+            let target_path = match self.create_target_device(kind).await {
+                Ok(path) => path,
+                Err(e) => {
+                    log::error!("{e}");
+                    continue;
+                }
+            };
+
+            // Attach the target device
+            if let Err(e) = self.attach_target_device(target_path.clone()).await {
+                log::error!("{e}");
+            }
+
+            // Enqueue the target device to wait for the attachment message from
+            // the input manager to prevent multiple calls to set_target_devices()
+            // from mangling attachment.
+            self.target_devices_queued.insert(target_path);
+        }
+        // Signal change in target devices to DBus
+        // TODO: Check this
+        //self.signal_targets_changed().await;
+
+        Ok(())
+    }
+
+    /// Atomically load the device profile at the given path and switch to the
+    /// given target device types. New target devices are created before any
+    /// state is changed. If any step fails, the previous profile and target
+    /// devices are kept and any newly created target devices are stopped.
+    async fn apply_configuration(
+        &mut self,
+        profile_path: String,
+        device_types: Vec<String>,
+    ) -> Result<(), Box<dyn Error>> {
+        log::info!(
+            "Applying configuration with profile {profile_path} and targets {device_types:?}"
+        );
+        if !self.target_devices_queued.is_empty() {
+            return Err("Target devices are still waiting for attachment".into());
+        }
+
+        // Stage the profile first so an invalid profile changes nothing
+        let source = ProfileSource::File(profile_path.clone());
+        let profile = DeviceProfile::from_yaml_file(profile_path)?;
+
+        // The given target devices replace any defined in the profile
+        if let Some(profile_targets) = profile_target_devices(&profile, &GlobalConfig::load()) {
+            if !same_target_devices(&profile_targets, &device_types) {
+                log::warn!(
+                    "Ignoring target devices {profile_targets:?} of profile '{}' in favor of {device_types:?}",
+                    profile.name
+                );
+            }
+        }
+
+        // Identify which target devices are new
+        let mut device_types_to_start: Vec<String> = vec![];
+        for kind in device_types.iter() {
+            if self.target_kind_running(kind).await? {
+                continue;
+            }
+            device_types_to_start.push(kind.clone());
+        }
+
+        // Identify the targets that need to close
+        let mut targets_to_stop: HashMap<String, TargetDeviceClient> = HashMap::new();
+        for (path, target) in self.target_devices.iter() {
+            let target_type = match target.get_type().await {
+                Ok(value) => value,
+                Err(e) => {
+                    return Err(format!("Failed to request target type: {e:?}").into());
+                }
+            };
+            if !device_types.contains(&target_type) {
+                targets_to_stop.insert(path.clone(), target.clone());
+            }
+        }
+
+        // Create all new target devices before touching the current state
+        let mut target_paths = Vec::new();
+        for kind in device_types_to_start {
+            match self.create_target_device(kind).await {
+                Ok(path) => target_paths.push(path),
+                Err(e) => {
+                    self.stop_created_target_devices(target_paths).await;
+                    return Err(e);
+                }
+            }
+        }
+
+        // Swap in the staged profile, keeping the old one in case of failure
+        let old_profile = self.device_profile.clone();
+        let old_profile_source = self.profile_source.clone();
+        let old_profile_config_map = self.device_profile_config_map.clone();
+        if let Err(e) = self.load_device_profile_mappings(profile, source) {
+            self.device_profile = old_profile;
+            self.profile_source = old_profile_source;
+            self.device_profile_config_map = old_profile_config_map;
+            self.update_translation_pool();
+            self.stop_created_target_devices(target_paths).await;
+            return Err(e);
+        }
+
+        // Attach the new target devices. If any fails to attach, all new
+        // target devices are stopped and any pending attachments are ignored.
+        if let Err(e) = attach_or_stop(&self.manager, &target_paths, &self.dbus_path).await {
+            self.target_devices_discarded
+                .extend(e.attached.iter().cloned());
+            self.device_profile = old_profile;
+            self.profile_source = old_profile_source;
+            self.device_profile_config_map = old_profile_config_map;
+            self.update_translation_pool();
+            return Err(e.into());
+        }
+        for target_path in target_paths {
+            self.target_devices_queued.insert(target_path);
+        }
+
+        // Stop all old target devices that aren't going to persist
+        for (path, target) in targets_to_stop.into_iter() {
+            log::debug!("Stopping old target device: {path}");
+            self.target_devices.remove(&path);
+            for (_, target_devices) in self.target_devices_by_capability.iter_mut() {
+                target_devices.remove(&path);
+            }
+            if let Err(e) = target.stop().await {
+                log::error!("Failed to stop old target device: {e:?}");
+            }
+        }
+        self.update_source_bypass().await;
+        self.update_dbus_target_capabilities().await;
+
+        Ok(())
+    }
+
+    /// Returns a snapshot of the runtime state of the composite device. If a
+    /// profile preview is pending, the state from before the preview is used.
+    async fn snapshot(&self) -> Result<DeviceSnapshot, Box<dyn Error>> {
+        let preview = self.profile_preview.as_ref();
+        let profile_source = preview
+            .map(|preview| &preview.source)
+            .unwrap_or(&self.profile_source);
+
+        let target_devices = match preview.and_then(|preview| preview.target_devices.clone()) {
+            Some(target_devices) => target_devices,
+            None => {
+                let mut targets = Vec::with_capacity(self.target_devices.len());
+                for (path, target) in self.target_devices.iter() {
+                    let kind = match target.get_type().await {
+                        Ok(kind) => kind,
+                        Err(e) => {
+                            return Err(format!("Failed to request target type: {e:?}").into());
+                        }
+                    };
+                    targets.push((path.clone(), kind));
+                }
+                snapshot::sort_by_path(&mut targets);
+                targets.into_iter().map(|(_, kind)| kind).collect()
+            }
+        };
+
+        let mut blocked_source_devices: Vec<String> =
+            self.source_devices_blocked.iter().cloned().collect();
+        blocked_source_devices.sort();
+
+        Ok(DeviceSnapshot {
+            version: SNAPSHOT_VERSION,
+            profile: ProfileSnapshot::from_source(profile_source),
+            intercept_mode: self.intercept_mode.as_str().to_string(),
+            target_devices,
+            blocked_source_devices,
+        })
+    }
 
-        let composite_path = self.dbus_path.clone();
+    /// Restore the runtime state from the given snapshot. Blocked source
+    /// devices in the snapshot that are not running are ignored, and running
+    /// source devices that are not in the snapshot are unblocked.
+    async fn restore_snapshot(&mut self, snapshot: DeviceSnapshot) -> Result<(), Box<dyn Error>> {
+        log::info!(
+            "Restoring snapshot with profile {:?} and targets {:?}",
+            snapshot.profile.as_ref().map(|profile| match profile {
+                ProfileSnapshot::File { path } => path.as_str(),
+                ProfileSnapshot::Yaml { .. } => "yaml",
+            }),
+            snapshot.target_devices
+        );
+        // Parse everything first so an invalid snapshot changes nothing
+        let intercept_mode = snapshot.intercept_mode()?;
+        let profile = match snapshot.profile {
+            Some(ProfileSnapshot::File { path }) => Some((
+                DeviceProfile::from_yaml_file(path.clone())?,
+                ProfileSource::File(path),
+            )),
+            Some(ProfileSnapshot::Yaml { yaml }) => Some((
+                DeviceProfile::from_yaml(yaml.clone())?,
+                ProfileSource::Yaml(yaml),
+            )),
+            None => None,
+        };
 
-        // Create new target devices using the input manager
-        for kind in device_types_to_start {
-            log::debug!("Requesting to create device: {kind}");
-            let (sender, mut receiver) = mpsc::channel(1);
-            self.manager
-                .send(ManagerCommand::CreateTargetDevice { kind, sender })
-                .await?;
-            let Some(response) = receiver.recv().await else {
-                log::warn!("Channel closed waiting for response from input manager");
-                continue;
-            };
-            let target_path = match response {
-                Ok(path) => path,
-                Err(e) => {
-                    let err = format!("Failed to create target: {e:?}");
-                    log::error!("{err}");
-                    continue;
-                }
-            };
+        if let Some((profile, source)) = profile {
+            self.load_device_profile(profile, source)?;
+        }
+        if !snapshot.target_devices.is_empty() {
+            self.set_target_devices(snapshot.target_devices).await?;
+        }
 
-            // Attach the target device
-            log::debug!("Requesting to attach target device {target_path} to {composite_path}");
-            let (sender, mut receiver) = mpsc::channel(1);
-            self.manager
-                .send(ManagerCommand::AttachTargetDevice {
-                    target_path: target_path.clone(),
-                    composite_path: composite_path.clone(),
-                    sender,
-                })
-                .await?;
-            let Some(response) = receiver.recv().await else {
-                log::warn!("Channel closed waiting for response from input manager");
+        let device_ids: Vec<String> = self.source_devices.keys().cloned().collect();
+        for device_id in device_ids {
+            let blocked = snapshot.blocked_source_devices.contains(&device_id);
+            if blocked == self.source_devices_blocked.contains(&device_id) {
                 continue;
-            };
-            if let Err(e) = response {
-                log::error!("Failed to attach target device: {e:?}");
             }
-
-            // Enqueue the target device to wait for the attachment message from
-            // the input manager to prevent multiple calls to set_target_devices()
-            // from mangling attachment.
-            self.target_devices_queued.insert(target_path);
+            self.set_source_device_blocked(device_id, blocked).await?;
         }
-        // Signal change in target devices to DBus
-        // TODO: Check this
-        //self.signal_targets_changed().await;
+
+        self.set_intercept_mode(intercept_mode).await;
 
         Ok(())
     }
 
+    /// Request the input manager to create a target device of the given kind.
+    /// Returns the DBus path of the created target device.
+    async fn create_target_device(&self, kind: String) -> Result<String, Box<dyn Error>> {
+        log::debug!("Requesting to create device: {kind}");
+        let options = self.config.get_target_device_create_options(&kind);
+        let (sender, mut receiver) = mpsc::channel(1);
+        self.manager
+            .send(ManagerCommand::CreateTargetDevice {
+                kind,
+                options,
+                sender,
+            })
+            .await?;
+        let Some(response) = receiver.recv().await else {
+            return Err("Channel closed waiting for response from input manager".into());
+        };
+        match response {
+            Ok(path) => Ok(path),
+            Err(e) => Err(format!("Failed to create target: {e:?}").into()),
+        }
+    }
+
+    /// Request the input manager to attach the given target device to this
+    /// composite device.
+    async fn attach_target_device(&self, target_path: String) -> Result<(), Box<dyn Error>> {
+        request_attach(&self.manager, target_path, self.dbus_path.clone()).await
+    }
+
+    /// Stop the given target devices that were created but are not in use
+    async fn stop_created_target_devices(&self, target_paths: Vec<String>) {
+        request_stop(&self.manager, target_paths).await;
+    }
+
     // Deterimines if a given target device kind is already running
     async fn target_kind_running(&self, kind: &str) -> Result<bool, Box<dyn Error>> {
         // TODO: Save this on the DS5 target device so we can properly look it up.
@@ -1898,6 +4489,25 @@ impl CompositeDevice {
         Ok(target_caps)
     }
 
+    /// Returns the capabilities that can actually reach target devices after
+    /// applying the capability map and the currently loaded device profile.
+    async fn get_translated_capabilities(&self) -> Result<HashSet<Capability>, Box<dyn Error>> {
+        let translated_caps = translated_capabilities(
+            &self.capabilities,
+            self.capability_map.as_ref(),
+            &self.device_profile_config_map,
+        );
+
+        // Only capabilities implemented by a target device can reach it
+        let target_caps = self.get_target_capabilities().await?;
+        let caps = translated_caps
+            .intersection(&target_caps)
+            .cloned()
+            .collect();
+
+        Ok(caps)
+    }
+
     /// Attach the given target devices to the composite device
     async fn attach_target_devices(
         &mut self,
@@ -1907,6 +4517,13 @@ impl CompositeDevice {
 
         // Keep track of all target devices
         for (path, target) in targets.into_iter() {
+            // Target devices that were stopped while their attachment was
+            // pending are not attached.
+            if self.target_devices_discarded.remove(&path) {
+                log::debug!("Ignoring attachment of discarded target device: {path}");
+                continue;
+            }
+
             // Query the target device for its capabilities
             let caps = match target.get_capabilities().await {
                 Ok(caps) => caps,
@@ -1923,6 +4540,18 @@ impl CompositeDevice {
             }
             log::debug!("Attached device {path} to {dbus_path}");
 
+            // Keep track of debug target devices so they receive all events
+            let target_devices = &self.target_devices;
+            self.target_debug_devices
+                .retain(|path| target_devices.contains_key(path));
+            match target.get_type().await {
+                Ok(kind) if kind == "debug" => {
+                    self.target_debug_devices.insert(path.clone());
+                }
+                Ok(_) => (),
+                Err(e) => log::error!("Failed to get target device type: {e:?}"),
+            }
+
             // Add the target device
             self.target_devices_queued.remove(&path);
             self.target_devices.insert(path.clone(), target);
@@ -1943,6 +4572,8 @@ impl CompositeDevice {
         }
         // TODO: check this
         //self.signal_targets_changed().await;
+        self.update_source_bypass().await;
+        self.update_dbus_target_capabilities().await;
 
         Ok(())
     }
@@ -2012,9 +4643,333 @@ impl CompositeDevice {
         });
     }
 
+    /// Emit a DBus signal when the source capabilities change
+    fn signal_capabilities_changed(&self) {
+        let dbus_path = self.dbus_path.clone();
+        let conn = self.conn.clone();
+
+        tokio::task::spawn(async move {
+            // Get the object instance at the given path so we can send DBus signal
+            // updates
+            let iface_ref = match conn
+                .object_server()
+                .interface::<_, CompositeDeviceInterface>(dbus_path.clone())
+                .await
+            {
+                Ok(iface) => iface,
+                Err(e) => {
+                    log::error!(
+                        "Failed to get DBus interface for composite device to signal: {e:?}"
+                    );
+                    return;
+                }
+            };
+
+            // Emit the capabilities changed signals
+            let iface = iface_ref.get().await;
+            if let Err(e) = iface.capabilities_changed(iface_ref.signal_context()).await {
+                log::error!("Failed to send capabilities changed signal: {e:?}");
+            }
+            if let Err(e) = iface
+                .translated_capabilities_changed(iface_ref.signal_context())
+                .await
+            {
+                log::error!("Failed to send translated capabilities changed signal: {e:?}");
+            }
+        });
+    }
+
+    /// Emit a DBus signal when blocked source devices change
+    fn signal_blocked_sources_changed(&self) {
+        let dbus_path = self.dbus_path.clone();
+        let conn = self.conn.clone();
+
+        tokio::task::spawn(async move {
+            // Get the object instance at the given path so we can send DBus signal
+            // updates
+            let iface_ref = match conn
+                .object_server()
+                .interface::<_, CompositeDeviceInterface>(dbus_path.clone())
+                .await
+            {
+                Ok(iface) => iface,
+                Err(e) => {
+                    log::error!(
+                        "Failed to get DBus interface for composite device to signal: {e:?}"
+                    );
+                    return;
+                }
+            };
+
+            // Emit the blocked source devices changed signal
+            let iface = iface_ref.get().await;
+            if let Err(e) = iface
+                .blocked_source_devices_changed(iface_ref.signal_context())
+                .await
+            {
+                log::error!("Failed to send blocked source devices changed signal: {e:?}");
+            }
+        });
+    }
+
+    /// Emit a DBus signal when a stuck source device was restarted
+    fn signal_source_device_restarted(&self, device_id: String) {
+        let dbus_path = self.dbus_path.clone();
+        let conn = self.conn.clone();
+
+        tokio::task::spawn(async move {
+            // Get the object instance at the given path so we can send DBus signal
+            // updates
+            let iface_ref = match conn
+                .object_server()
+                .interface::<_, CompositeDeviceInterface>(dbus_path.clone())
+                .await
+            {
+                Ok(iface) => iface,
+                Err(e) => {
+                    log::error!(
+                        "Failed to get DBus interface for composite device to signal: {e:?}"
+                    );
+                    return;
+                }
+            };
+
+            // Emit the source device restarted signal
+            if let Err(e) = CompositeDeviceInterface::emit_source_device_restarted(
+                iface_ref.signal_context(),
+                device_id,
+            )
+            .await
+            {
+                log::error!("Failed to send source device restarted signal: {e:?}");
+            }
+        });
+    }
+
+    /// Emit a DBus signal when a temporary profile was reverted
+    fn signal_profile_reverted(&self, profile_name: String) {
+        let dbus_path = self.dbus_path.clone();
+        let conn = self.conn.clone();
+
+        tokio::task::spawn(async move {
+            // Get the object instance at the given path so we can send DBus signal
+            // updates
+            let iface_ref = match conn
+                .object_server()
+                .interface::<_, CompositeDeviceInterface>(dbus_path.clone())
+                .await
+            {
+                Ok(iface) => iface,
+                Err(e) => {
+                    log::error!(
+                        "Failed to get DBus interface for composite device to signal: {e:?}"
+                    );
+                    return;
+                }
+            };
+
+            // Emit the profile reverted signal
+            if let Err(e) = CompositeDeviceInterface::emit_profile_reverted(
+                iface_ref.signal_context(),
+                profile_name,
+            )
+            .await
+            {
+                log::error!("Failed to send profile reverted signal: {e:?}");
+            }
+        });
+    }
+
+    /// Emit a DBus signal when the intercept activation chord triggers
+    fn signal_intercept_activated(&self) {
+        let dbus_path = self.dbus_path.clone();
+        let conn = self.conn.clone();
+        let capabilities = self.intercept_activation_caps.clone();
+        let aliases = CapabilityAliases::from_config(&self.config);
+
+        tokio::task::spawn(async move {
+            // Get the object instance at the given path so we can send DBus signal
+            // updates
+            let iface_ref = match conn
+                .object_server()
+                .interface::<_, CompositeDeviceInterface>(dbus_path.clone())
+                .await
+            {
+                Ok(iface) => iface,
+                Err(e) => {
+                    log::error!(
+                        "Failed to get DBus interface for composite device to signal: {e:?}"
+                    );
+                    return;
+                }
+            };
+
+            // Emit the intercept activated signal
+            if let Err(e) = CompositeDeviceInterface::emit_intercept_activated(
+                iface_ref.signal_context(),
+                capabilities,
+                &aliases,
+            )
+            .await
+            {
+                log::error!("Failed to send intercept activated signal: {e:?}");
+            }
+        });
+    }
+
+    /// Execute the suspend or resume hooks defined in the device config
+    async fn run_power_hooks(&self, event: PowerEvent) {
+        let Some(hooks) = self
+            .config
+            .options
+            .as_ref()
+            .and_then(|options| options.power_hooks.as_ref())
+        else {
+            return;
+        };
+        log::info!("Running {event:?} hooks for: {}", self.dbus_path);
+        hooks::run_power_hooks(&self.conn, hooks, event).await;
+    }
+
+    /// Returns the dock detection options of the composite device config
+    fn dock_detection(&self) -> Option<&DockDetection> {
+        self.config
+            .options
+            .as_ref()
+            .and_then(|options| options.dock_detection.as_ref())
+    }
+
+    /// Check whether the device is connected to a dock and emit a
+    /// 'Switch:Docked' event if the dock state changed. If the config defines
+    /// target devices to use while docked, switch to them until undocked.
+    async fn update_docked(&mut self) {
+        let Some(dock_config) = self.dock_detection().cloned() else {
+            return;
+        };
+        let docked = {
+            let dock_config = dock_config.clone();
+            match tokio::task::spawn_blocking(move || dock::is_docked(&dock_config)).await {
+                Ok(docked) => docked,
+                Err(e) => {
+                    log::error!("Failed to check dock state: {e:?}");
+                    return;
+                }
+            }
+        };
+        if docked == self.docked {
+            return;
+        }
+        log::info!(
+            "Dock state of {} changed to docked: {docked}",
+            self.dbus_path
+        );
+        self.docked = docked;
+
+        // Emit the dock state as if it came from a source device
+        let event = NativeEvent::new(Capability::Switch(Switch::Docked), InputValue::Bool(docked));
+        if let Err(e) = self
+            .process_event(DOCK_DEVICE_ID.to_string(), Event::Native(event))
+            .await
+        {
+            log::error!("Failed to process dock event: {e:?}");
+        }
+
+        // Switch target devices if configured
+        let Some(docked_types) = dock_config.target_devices else {
+            return;
+        };
+        let target_types = if docked {
+            let mut target_types = Vec::with_capacity(self.target_devices.len());
+            for target in self.target_devices.values() {
+                match target.get_type().await {
+                    Ok(kind) => target_types.push(kind),
+                    Err(e) => log::error!("Failed to get target device type: {e:?}"),
+                }
+            }
+
+            // DBus and debug target devices are kept while docked
+            let mut docked_types = docked_types;
+            for kind in target_types.iter() {
+                let keep = kind.as_str() == "dbus" || kind.as_str() == "debug";
+                if keep && !docked_types.contains(kind) {
+                    docked_types.push(kind.clone());
+                }
+            }
+            self.target_devices_undocked = Some(target_types);
+            docked_types
+        } else {
+            let Some(target_types) = self.target_devices_undocked.take() else {
+                return;
+            };
+            target_types
+        };
+        if let Err(e) = self.set_target_devices(target_types).await {
+            log::error!("Failed to set target devices for dock state: {e:?}");
+        }
+    }
+
+    /// Called when notified by the input manager that the active user session
+    /// changed. Processes of the previous session may still have the target
+    /// devices open, so all target devices are re-created to close those
+    /// handles and grant access only to the new session.
+    async fn handle_session_changed(&mut self) {
+        let recreate = self
+            .config
+            .options
+            .as_ref()
+            .and_then(|options| options.recreate_targets_on_session_change)
+            .unwrap_or(true);
+        if !recreate {
+            return;
+        }
+
+        let mut target_types = Vec::with_capacity(self.target_devices.len());
+        let mut targets_to_stop = HashMap::new();
+        for (path, target) in self.target_devices.clone().into_iter() {
+            let target_type = match target.get_type().await {
+                Ok(kind) => kind,
+                Err(err) => {
+                    log::error!("Failed to get target device type: {err:?}");
+                    continue;
+                }
+            };
+
+            // DBus target devices have no device nodes to hand off
+            if target_type.as_str() != "dbus" {
+                targets_to_stop.insert(path, target);
+            }
+            target_types.push(target_type);
+        }
+        if targets_to_stop.is_empty() {
+            return;
+        }
+        log::info!("Re-creating target devices for new session: {target_types:?}");
+
+        for (path, target) in targets_to_stop.into_iter() {
+            log::debug!("Stopping target device: {path}");
+            self.target_devices.remove(&path);
+            for (_, target_devices) in self.target_devices_by_capability.iter_mut() {
+                target_devices.remove(&path);
+            }
+            if let Err(e) = target.stop().await {
+                log::error!("Failed to stop target device: {e:?}");
+            }
+        }
+
+        // Wait a few beats to ensure that the target devices are really gone
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        if let Err(e) = self.set_target_devices(target_types).await {
+            log::error!("Failed to re-create target devices: {e:?}");
+        }
+    }
+
     /// Called when notified by the input manager that system suspend is about
     /// to happen.
     async fn handle_suspend(&mut self) {
+        // Run any configured suspend hooks while source devices are still active
+        self.run_power_hooks(PowerEvent::Suspend).await;
+
         // Clear the list of suspended target devices
         self.target_devices_suspended.clear();
 
@@ -2032,8 +4987,8 @@ impl CompositeDevice {
                 }
             };
 
-            // The "deck" target device does not support suspend
-            if target_type.as_str() == "deck" {
+            // The USB/IP Steam target devices do not support suspend
+            if USB_TARGET_TYPES.contains(&target_type.as_str()) {
                 targets_to_stop.insert(path, target);
             }
 
@@ -2058,6 +5013,8 @@ impl CompositeDevice {
             // Wait a few beats to ensure that the target device is really gone
             tokio::time::sleep(Duration::from_millis(200)).await;
         }
+        self.update_source_bypass().await;
+        self.update_dbus_target_capabilities().await;
     }
 
     /// Called when notified by the input manager that system resume is about
@@ -2068,8 +5025,15 @@ impl CompositeDevice {
             self.target_devices_suspended
         );
 
-        // Only handle resume if a deck controller target device was used
-        if !self.target_devices_suspended.contains(&"deck".to_string()) {
+        // Run any configured resume hooks
+        self.run_power_hooks(PowerEvent::Resume).await;
+
+        // Only handle resume if a USB/IP target device was stopped on suspend
+        let has_stopped_targets = self
+            .target_devices_suspended
+            .iter()
+            .any(|kind| USB_TARGET_TYPES.contains(&kind.as_str()));
+        if !has_stopped_targets {
             self.target_devices_suspended.clear();
             return;
         }
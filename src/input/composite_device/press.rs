@@ -0,0 +1,240 @@
+//! Detection of short, long, and double presses of source buttons used by
+//! device profile mappings with a press modifier.
+use std::{collections::HashMap, str::FromStr, time::Duration};
+
+use crate::{config::ProfileMapping, input::capability::Capability};
+
+/// Default time in milliseconds a source button must be held to trigger long
+/// press mappings
+pub const DEFAULT_HOLD_MS: u64 = 500;
+/// Default maximum time in milliseconds between two presses of a source button
+/// to trigger double press mappings
+pub const DEFAULT_DOUBLE_PRESS_MS: u64 = 300;
+
+/// Kind of press of a source button that triggers a profile mapping
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PressKind {
+    /// Button is pressed and released before it counts as a long press
+    Short,
+    /// Button is held for at least the hold time
+    Long,
+    /// Button is pressed twice within the double press time
+    Double,
+}
+
+impl PressKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PressKind::Short => "short",
+            PressKind::Long => "long",
+            PressKind::Double => "double",
+        }
+    }
+}
+
+impl FromStr for PressKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "short" => Ok(PressKind::Short),
+            "long" => Ok(PressKind::Long),
+            "double" => Ok(PressKind::Double),
+            _ => Err(format!("Invalid press kind: {s}")),
+        }
+    }
+}
+
+/// Timings used to tell apart the press kinds of a source button. A timing is
+/// only set if the source button has mappings for that press kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PressTimings {
+    /// Time the button must be held to trigger long press mappings
+    pub hold: Option<Duration>,
+    /// Maximum time between two presses to trigger double press mappings
+    pub double_press: Option<Duration>,
+}
+
+impl PressTimings {
+    /// Returns the press timings of the given profile mappings of a single
+    /// source button, or [None] if none of the mappings use a press modifier.
+    pub fn from_mappings(mappings: &[ProfileMapping]) -> Option<Self> {
+        if mappings.iter().all(|mapping| mapping.press.is_none()) {
+            return None;
+        }
+        let mut timings = Self::default();
+        for mapping in mappings {
+            match press_kind(mapping) {
+                PressKind::Short => (),
+                PressKind::Long => {
+                    let hold = mapping.hold_ms.unwrap_or(DEFAULT_HOLD_MS);
+                    let hold = Duration::from_millis(hold);
+                    timings.hold = Some(timings.hold.map_or(hold, |t| t.min(hold)));
+                }
+                PressKind::Double => {
+                    let window = mapping.double_press_ms.unwrap_or(DEFAULT_DOUBLE_PRESS_MS);
+                    let window = Duration::from_millis(window);
+                    timings.double_press =
+                        Some(timings.double_press.map_or(window, |t| t.min(window)));
+                }
+            }
+        }
+        Some(timings)
+    }
+}
+
+/// Returns the press kind of the given profile mapping. Mappings without a
+/// press modifier, or with an invalid one, are treated as short presses.
+pub fn press_kind(mapping: &ProfileMapping) -> PressKind {
+    let Some(press) = mapping.press.as_ref() else {
+        return PressKind::Short;
+    };
+    match PressKind::from_str(press) {
+        Ok(kind) => kind,
+        Err(e) => {
+            log::warn!("{e} in profile mapping '{}'", mapping.name);
+            PressKind::Short
+        }
+    }
+}
+
+/// Timer scheduled by the [PressTracker] that should be sent back to it once
+/// it is due
+#[derive(Debug, Clone, PartialEq)]
+pub enum PressTimer {
+    /// The hold time or double press window of the press with the given id
+    /// ended
+    Expired { capability: Capability, id: u64 },
+    /// Release the mappings of a tapped press kind
+    Release {
+        capability: Capability,
+        kind: PressKind,
+    },
+}
+
+/// Action the [super::CompositeDevice] should take for a source button
+#[derive(Debug, Clone, PartialEq)]
+pub enum PressAction {
+    /// Emit the press of the mappings of the given kind
+    Press(PressKind),
+    /// Emit the release of the mappings of the given kind
+    Release(PressKind),
+    /// Emit the press of the mappings of the given kind, followed by their
+    /// release after a short delay
+    Tap(PressKind),
+    /// Schedule the given timer after the given delay
+    Schedule(Duration, PressTimer),
+}
+
+/// State of a single source button
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PressState {
+    /// Button is held and it is not known yet which kind of press it is
+    Pressed { id: u64 },
+    /// Button was released once and may be pressed again for a double press
+    Released { id: u64 },
+    /// Mappings of the given press kind are pressed
+    Active(PressKind),
+}
+
+/// The [PressTracker] keeps the state of source buttons with press modifier
+/// mappings and decides which mappings should be emitted.
+#[derive(Debug, Default)]
+pub struct PressTracker {
+    states: HashMap<Capability, PressState>,
+    /// Id of the last press, used to ignore timers of earlier presses
+    sequence: u64,
+}
+
+impl PressTracker {
+    /// Create a new [PressTracker]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update the state of the given source button and return the actions to
+    /// take.
+    pub fn update(
+        &mut self,
+        capability: &Capability,
+        pressed: bool,
+        timings: &PressTimings,
+    ) -> Vec<PressAction> {
+        let state = self.states.get(capability).copied();
+        if pressed {
+            return match state {
+                None if timings.hold.is_none() && timings.double_press.is_none() => {
+                    self.states
+                        .insert(capability.clone(), PressState::Active(PressKind::Short));
+                    vec![PressAction::Press(PressKind::Short)]
+                }
+                None => {
+                    let id = self.next_id();
+                    self.states
+                        .insert(capability.clone(), PressState::Pressed { id });
+                    match timings.hold {
+                        Some(hold) => vec![self.expire_after(hold, capability, id)],
+                        None => vec![],
+                    }
+                }
+                Some(PressState::Released { .. }) => {
+                    self.states
+                        .insert(capability.clone(), PressState::Active(PressKind::Double));
+                    vec![PressAction::Press(PressKind::Double)]
+                }
+                // Repeated press events while the button is held
+                Some(_) => vec![],
+            };
+        }
+
+        match state {
+            Some(PressState::Pressed { .. }) => match timings.double_press {
+                Some(window) => {
+                    let id = self.next_id();
+                    self.states
+                        .insert(capability.clone(), PressState::Released { id });
+                    vec![self.expire_after(window, capability, id)]
+                }
+                None => {
+                    self.states.remove(capability);
+                    vec![PressAction::Tap(PressKind::Short)]
+                }
+            },
+            Some(PressState::Active(kind)) => {
+                self.states.remove(capability);
+                vec![PressAction::Release(kind)]
+            }
+            Some(PressState::Released { .. }) | None => vec![],
+        }
+    }
+
+    /// Handle the given expired timer of the given source button and return
+    /// the actions to take.
+    pub fn expire(&mut self, capability: &Capability, id: u64) -> Vec<PressAction> {
+        match self.states.get(capability).copied() {
+            Some(PressState::Pressed { id: current }) if current == id => {
+                self.states
+                    .insert(capability.clone(), PressState::Active(PressKind::Long));
+                vec![PressAction::Press(PressKind::Long)]
+            }
+            Some(PressState::Released { id: current }) if current == id => {
+                self.states.remove(capability);
+                vec![PressAction::Tap(PressKind::Short)]
+            }
+            _ => vec![],
+        }
+    }
+
+    fn next_id(&mut self) -> u64 {
+        self.sequence = self.sequence.wrapping_add(1);
+        self.sequence
+    }
+
+    fn expire_after(&self, delay: Duration, capability: &Capability, id: u64) -> PressAction {
+        let timer = PressTimer::Expired {
+            capability: capability.clone(),
+            id,
+        };
+        PressAction::Schedule(delay, timer)
+    }
+}
@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+use crate::{
+    config::DeviceProfile,
+    input::{
+        capability::{Capability, Gamepad, GamepadButton},
+        composite_device::press::{PressAction, PressKind, PressTimer, PressTimings, PressTracker},
+    },
+};
+
+const PROFILE: &str = r#"
+version: 1
+kind: DeviceProfile
+name: Press Test
+mapping:
+  - name: South tap
+    source_event:
+      gamepad:
+        button: South
+    target_events:
+      - gamepad:
+          button: East
+  - name: South hold
+    source_event:
+      gamepad:
+        button: South
+    target_events:
+      - gamepad:
+          button: North
+    press: long
+    hold_ms: 700
+  - name: South double press
+    source_event:
+      gamepad:
+        button: South
+    target_events:
+      - gamepad:
+          button: West
+    press: double
+"#;
+
+fn south() -> Capability {
+    Capability::Gamepad(Gamepad::Button(GamepadButton::South))
+}
+
+fn timings(hold_ms: Option<u64>, double_press_ms: Option<u64>) -> PressTimings {
+    PressTimings {
+        hold: hold_ms.map(Duration::from_millis),
+        double_press: double_press_ms.map(Duration::from_millis),
+    }
+}
+
+/// Returns the id of the timer scheduled by the given actions
+fn scheduled_id(actions: &[PressAction]) -> u64 {
+    match actions {
+        [PressAction::Schedule(_, PressTimer::Expired { id, .. })] => *id,
+        _ => panic!("Expected a single scheduled timer, got {actions:?}"),
+    }
+}
+
+#[test]
+fn test_press_timings_from_mappings() {
+    let profile = DeviceProfile::from_yaml(PROFILE.to_string()).unwrap();
+    let timings = PressTimings::from_mappings(&profile.mapping).unwrap();
+    assert_eq!(timings.hold, Some(Duration::from_millis(700)));
+    assert_eq!(timings.double_press, Some(Duration::from_millis(300)));
+
+    // Mappings without press modifiers are translated as usual
+    assert!(PressTimings::from_mappings(&profile.mapping[..1]).is_none());
+}
+
+#[test]
+fn test_press_kind_from_str() {
+    for kind in [PressKind::Short, PressKind::Long, PressKind::Double] {
+        assert_eq!(kind.as_str().parse::<PressKind>(), Ok(kind));
+    }
+    assert!("triple".parse::<PressKind>().is_err());
+}
+
+#[test]
+fn test_short_press() {
+    let mut tracker = PressTracker::new();
+    let timings = timings(Some(500), None);
+
+    let actions = tracker.update(&south(), true, &timings);
+    scheduled_id(&actions);
+    let actions = tracker.update(&south(), false, &timings);
+    assert_eq!(actions, vec![PressAction::Tap(PressKind::Short)]);
+}
+
+#[test]
+fn test_long_press() {
+    let mut tracker = PressTracker::new();
+    let timings = timings(Some(500), None);
+
+    let id = scheduled_id(&tracker.update(&south(), true, &timings));
+    let actions = tracker.expire(&south(), id);
+    assert_eq!(actions, vec![PressAction::Press(PressKind::Long)]);
+    let actions = tracker.update(&south(), false, &timings);
+    assert_eq!(actions, vec![PressAction::Release(PressKind::Long)]);
+}
+
+#[test]
+fn test_double_press() {
+    let mut tracker = PressTracker::new();
+    let timings = timings(Some(500), Some(300));
+
+    let hold_id = scheduled_id(&tracker.update(&south(), true, &timings));
+    let double_id = scheduled_id(&tracker.update(&south(), false, &timings));
+    let actions = tracker.update(&south(), true, &timings);
+    assert_eq!(actions, vec![PressAction::Press(PressKind::Double)]);
+
+    // Timers of the first press are ignored
+    assert!(tracker.expire(&south(), hold_id).is_empty());
+    assert!(tracker.expire(&south(), double_id).is_empty());
+
+    let actions = tracker.update(&south(), false, &timings);
+    assert_eq!(actions, vec![PressAction::Release(PressKind::Double)]);
+}
+
+#[test]
+fn test_double_press_window_expired() {
+    let mut tracker = PressTracker::new();
+    let timings = timings(None, Some(300));
+
+    assert!(tracker.update(&south(), true, &timings).is_empty());
+    let id = scheduled_id(&tracker.update(&south(), false, &timings));
+    let actions = tracker.expire(&south(), id);
+    assert_eq!(actions, vec![PressAction::Tap(PressKind::Short)]);
+}
+
+#[test]
+fn test_short_press_only() {
+    let mut tracker = PressTracker::new();
+    let timings = timings(None, None);
+
+    let actions = tracker.update(&south(), true, &timings);
+    assert_eq!(actions, vec![PressAction::Press(PressKind::Short)]);
+    let actions = tracker.update(&south(), false, &timings);
+    assert_eq!(actions, vec![PressAction::Release(PressKind::Short)]);
+}
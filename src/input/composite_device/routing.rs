@@ -0,0 +1,35 @@
+//! Routing of cloned events to every target device capable of handling them
+//! when the 'clone_output' option is enabled.
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::input::{
+    capability::Capability,
+    event::dbus::{actions_from_capability, Action},
+};
+
+/// Returns the paths of all target devices that can handle the given
+/// capability, sorted by path. Target devices that only handle DBus actions
+/// (e.g. "dbus" target devices) are included if the capability translates to
+/// one of their actions.
+pub fn capable_targets(
+    cap: &Capability,
+    targets_by_capability: &HashMap<Capability, HashSet<String>>,
+) -> Vec<String> {
+    let mut caps = vec![cap.clone()];
+    if !matches!(cap, Capability::DBus(_)) {
+        let actions = actions_from_capability(cap.clone());
+        caps.extend(
+            actions
+                .into_iter()
+                .filter(|action| *action != Action::None)
+                .map(Capability::DBus),
+        );
+    }
+
+    let paths: BTreeSet<&String> = caps
+        .iter()
+        .filter_map(|cap| targets_by_capability.get(cap))
+        .flatten()
+        .collect();
+    paths.into_iter().cloned().collect()
+}
@@ -0,0 +1,54 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadButton, Keyboard},
+    composite_device::routing::capable_targets,
+    event::dbus::Action,
+};
+
+fn targets_by_capability(
+    targets: &[(&str, Vec<Capability>)],
+) -> HashMap<Capability, HashSet<String>> {
+    let mut by_capability: HashMap<Capability, HashSet<String>> = HashMap::new();
+    for (path, caps) in targets {
+        for cap in caps {
+            by_capability
+                .entry(cap.clone())
+                .or_default()
+                .insert(path.to_string());
+        }
+    }
+    by_capability
+}
+
+#[test]
+fn test_capable_targets() {
+    let south = Capability::Gamepad(Gamepad::Button(GamepadButton::South));
+    let gyro = Capability::Gamepad(Gamepad::Gyro);
+    let key = Capability::Keyboard(Keyboard::KeyA);
+    let targets = targets_by_capability(&[
+        ("/target/gamepad0", vec![south.clone()]),
+        (
+            "/target/dbus0",
+            vec![Capability::DBus(Action::Accept), gyro.clone()],
+        ),
+    ]);
+
+    // Both targets can handle the button, the DBus target by translating it
+    // into a DBus action
+    assert_eq!(
+        capable_targets(&south, &targets),
+        vec!["/target/dbus0".to_string(), "/target/gamepad0".to_string()]
+    );
+
+    // Only the DBus target can handle gyro events
+    assert_eq!(
+        capable_targets(&gyro, &targets),
+        vec!["/target/dbus0".to_string()]
+    );
+
+    // Events that no target can handle are dropped
+    assert!(capable_targets(&key, &targets).is_empty());
+    let back = Capability::DBus(Action::Back);
+    assert!(capable_targets(&back, &targets).is_empty());
+}
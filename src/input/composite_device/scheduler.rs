@@ -0,0 +1,81 @@
+//! Scheduler for delaying commands inside the composite device loop
+use std::collections::BTreeMap;
+
+use tokio::time::{Duration, Instant};
+
+/// A [Scheduler] holds items that should be processed at a later time. It is
+/// polled from inside the [super::CompositeDevice] loop so delayed events
+/// (like chord events) do not require a separate task per event. Items that
+/// are due at the same time are returned in the order they were scheduled.
+#[derive(Debug)]
+pub struct Scheduler<T> {
+    /// Scheduled items ordered by their deadline and insertion order
+    queue: BTreeMap<(Instant, u64), T>,
+    /// Sequence number used to keep insertion order for equal deadlines
+    sequence: u64,
+}
+
+impl<T> Default for Scheduler<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Scheduler<T> {
+    /// Create a new empty [Scheduler]
+    pub fn new() -> Self {
+        Self {
+            queue: BTreeMap::new(),
+            sequence: 0,
+        }
+    }
+
+    /// Schedule the given item to be due after the given delay
+    pub fn schedule(&mut self, delay: Duration, item: T) {
+        self.schedule_at(Instant::now() + delay, item);
+    }
+
+    /// Schedule the given item to be due at the given deadline
+    pub fn schedule_at(&mut self, deadline: Instant, item: T) {
+        self.queue.insert((deadline, self.sequence), item);
+        self.sequence = self.sequence.wrapping_add(1);
+    }
+
+    /// Returns the deadline of the next scheduled item
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.queue.keys().next().map(|(deadline, _)| *deadline)
+    }
+
+    /// Move all items that are due at the given time into the given buffer.
+    /// Returns the number of items that were moved.
+    pub fn drain_due(&mut self, now: Instant, buffer: &mut Vec<T>) -> usize {
+        let mut count = 0;
+        while let Some(entry) = self.queue.first_entry() {
+            if entry.key().0 > now {
+                break;
+            }
+            buffer.push(entry.remove());
+            count += 1;
+        }
+        count
+    }
+
+    /// Wait until the next scheduled item is due. If nothing is scheduled,
+    /// this will never complete.
+    pub async fn wait(&self) {
+        match self.next_deadline() {
+            Some(deadline) => tokio::time::sleep_until(deadline).await,
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Returns the number of scheduled items
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns true if no items are scheduled
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
@@ -0,0 +1,49 @@
+use tokio::time::{Duration, Instant};
+
+use crate::input::composite_device::scheduler::Scheduler;
+
+#[test]
+fn test_scheduler_order() {
+    let mut scheduler = Scheduler::new();
+    let now = Instant::now();
+    scheduler.schedule_at(now + Duration::from_millis(20), "c");
+    scheduler.schedule_at(now + Duration::from_millis(10), "a");
+    scheduler.schedule_at(now + Duration::from_millis(10), "b");
+    assert_eq!(scheduler.len(), 3);
+    assert_eq!(
+        scheduler.next_deadline(),
+        Some(now + Duration::from_millis(10))
+    );
+
+    // Nothing should be due yet
+    let mut buffer = vec![];
+    assert_eq!(scheduler.drain_due(now, &mut buffer), 0);
+    assert!(buffer.is_empty());
+
+    // Items with the same deadline keep their insertion order
+    let count = scheduler.drain_due(now + Duration::from_millis(15), &mut buffer);
+    assert_eq!(count, 2);
+    assert_eq!(buffer, vec!["a", "b"]);
+
+    buffer.clear();
+    scheduler.drain_due(now + Duration::from_millis(20), &mut buffer);
+    assert_eq!(buffer, vec!["c"]);
+    assert!(scheduler.is_empty());
+    assert_eq!(scheduler.next_deadline(), None);
+}
+
+#[tokio::test]
+async fn test_scheduler_wait() {
+    let mut scheduler = Scheduler::new();
+    scheduler.schedule(Duration::from_millis(8), 1);
+    scheduler.schedule(Duration::ZERO, 0);
+
+    let mut buffer = vec![];
+    scheduler.wait().await;
+    scheduler.drain_due(Instant::now(), &mut buffer);
+    assert_eq!(buffer, vec![0]);
+
+    scheduler.wait().await;
+    scheduler.drain_due(Instant::now(), &mut buffer);
+    assert_eq!(buffer, vec![0, 1]);
+}
@@ -0,0 +1,58 @@
+//! Persisted user settings for composite devices
+use std::{error::Error, fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{path::get_settings_path, HapticsToRumble};
+
+use super::drift::DriftCorrections;
+
+/// User settings of a composite device that persist across restarts
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DeviceSettings {
+    /// Force feedback output gain in percent (0-100)
+    pub ff_gain: Option<u32>,
+    /// Overrides of how trackpad haptic commands are converted into rumble
+    pub haptics_to_rumble: Option<HapticsToRumble>,
+    /// Applications for which force feedback is muted. Overrides the list
+    /// from the composite device config.
+    pub ff_mute_apps: Option<Vec<String>>,
+    /// Whether suggested stick drift corrections are applied automatically.
+    /// Overrides the option from the composite device config.
+    pub drift_auto_apply: Option<bool>,
+    /// Stick drift corrections that were applied by the user
+    pub drift_corrections: Option<DriftCorrections>,
+    /// Whether usage statistics of source inputs are collected
+    pub input_statistics: Option<bool>,
+}
+
+impl DeviceSettings {
+    /// Returns the path to the settings file for the given device id
+    fn path(device_id: &str) -> PathBuf {
+        get_settings_path().join(format!("{device_id}.yaml"))
+    }
+
+    /// Load the settings of the given device id. Returns [None] if no
+    /// settings have been saved for the device.
+    pub fn load(device_id: &str) -> Result<Option<Self>, Box<dyn Error>> {
+        let path = DeviceSettings::path(device_id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let file = fs::File::open(path)?;
+        let settings = serde_yaml::from_reader(file)?;
+        Ok(Some(settings))
+    }
+
+    /// Save the settings for the given device id
+    pub fn save(&self, device_id: &str) -> Result<(), Box<dyn Error>> {
+        let path = DeviceSettings::path(device_id);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let data = serde_yaml::to_string(self)?;
+        fs::write(path, data)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,344 @@
+//! Offline simulation of a [CapabilityMap]. Follows the same chord, timing,
+//! and condition rules as the [CompositeDevice] so authors can validate
+//! capability maps without the hardware they are written for.
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use crate::{
+    config::{CapabilityMap, CapabilityMapping},
+    input::capability::Capability,
+};
+
+use super::{chord, conditions, InterceptMode};
+
+/// An input event emitted by the simulated composite device
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulatedEvent {
+    /// Time since the start of the simulation that the event was emitted
+    pub time: Duration,
+    pub capability: Capability,
+    pub pressed: bool,
+    /// True if the event was produced by a capability mapping
+    pub translated: bool,
+}
+
+/// A synthetic source input to feed to the simulator
+#[derive(Debug, Clone, PartialEq)]
+pub enum SimulatedInput {
+    Press(Capability),
+    Release(Capability),
+    InterceptMode(InterceptMode),
+    /// Only advance the time of the simulation
+    Wait,
+}
+
+impl SimulatedInput {
+    /// Parse a single line of simulator input in the form
+    /// "[<time_ms>] <press|release|intercept|wait> [<capability|mode>]". E.g.
+    /// "120 press Gamepad:Button:South". Returns the optional time in
+    /// milliseconds since the start of the simulation and the parsed input,
+    /// or None for empty lines and comments starting with '#'.
+    pub fn parse(line: &str) -> Result<Option<(Option<Duration>, Self)>, String> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(None);
+        }
+        let mut parts: Vec<&str> = line.split_whitespace().collect();
+        let time = match parts.first().map(|part| part.parse::<u64>()) {
+            Some(Ok(ms)) => {
+                parts.remove(0);
+                Some(Duration::from_millis(ms))
+            }
+            _ => None,
+        };
+
+        let input = match parts.as_slice() {
+            ["press", cap] => SimulatedInput::Press(parse_capability(cap)?),
+            ["release", cap] => SimulatedInput::Release(parse_capability(cap)?),
+            ["intercept", mode] => {
+                let Some(mode) = InterceptMode::ALL
+                    .iter()
+                    .find(|m| m.as_str().eq_ignore_ascii_case(mode))
+                else {
+                    return Err(format!("Invalid intercept mode: {mode}"));
+                };
+                SimulatedInput::InterceptMode(*mode)
+            }
+            ["wait"] if time.is_some() => SimulatedInput::Wait,
+            _ => return Err(format!("Invalid input: {line}")),
+        };
+
+        Ok(Some((time, input)))
+    }
+}
+
+/// Parse the given capability string. E.g. "Gamepad:Button:South"
+fn parse_capability(value: &str) -> Result<Capability, String> {
+    match value.parse::<Capability>() {
+        Ok(Capability::NotImplemented) | Err(_) => Err(format!("Invalid capability: {value}")),
+        Ok(cap) => Ok(cap),
+    }
+}
+
+/// Simulates the translation of source inputs by a [CapabilityMap]
+#[derive(Debug)]
+pub struct CapabilityMapSimulator {
+    map: CapabilityMap,
+    intercept_mode: InterceptMode,
+    /// Current time of the simulation
+    time: Duration,
+    /// Source capabilities used by mappings in the capability map
+    translatable_capabilities: HashSet<Capability>,
+    /// All source inputs that are currently held
+    held_inputs: HashSet<Capability>,
+    /// Translatable source inputs that are currently held, in press order
+    translatable_active_inputs: Vec<Capability>,
+    /// Time each translatable source input was pressed
+    translatable_press_times: HashMap<Capability, Duration>,
+    /// Translatable source inputs that were emitted untranslated
+    translatable_passthrough_inputs: HashSet<Capability>,
+    /// Mappings whose target event is currently pressed
+    emitted_mappings: HashMap<String, CapabilityMapping>,
+    /// Partial chord timeouts of the form (deadline, capability, pressed at)
+    chord_timeouts: Vec<(Duration, Capability, Duration)>,
+}
+
+impl CapabilityMapSimulator {
+    /// Create a new simulator for the given capability map
+    pub fn new(map: CapabilityMap) -> Self {
+        let translatable_capabilities = map
+            .mapping
+            .iter()
+            .flat_map(|mapping| mapping.source_events.iter())
+            .map(|source| Capability::from(source.clone()))
+            .filter(|cap| *cap != Capability::NotImplemented)
+            .collect();
+        Self {
+            map,
+            intercept_mode: InterceptMode::None,
+            time: Duration::ZERO,
+            translatable_capabilities,
+            held_inputs: HashSet::new(),
+            translatable_active_inputs: Vec::new(),
+            translatable_press_times: HashMap::new(),
+            translatable_passthrough_inputs: HashSet::new(),
+            emitted_mappings: HashMap::new(),
+            chord_timeouts: Vec::new(),
+        }
+    }
+
+    /// Returns the current time of the simulation
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    /// Process the given input at the given time since the start of the
+    /// simulation and return all events emitted up to and including it.
+    /// Times earlier than the current time of the simulation are clamped.
+    pub fn process(&mut self, time: Duration, input: SimulatedInput) -> Vec<SimulatedEvent> {
+        let mut events = self.advance(time);
+        match input {
+            SimulatedInput::Press(cap) => events.extend(self.handle_input(cap, true)),
+            SimulatedInput::Release(cap) => events.extend(self.handle_input(cap, false)),
+            SimulatedInput::InterceptMode(mode) => self.intercept_mode = mode,
+            SimulatedInput::Wait => (),
+        }
+        events
+    }
+
+    /// Advance the simulation to the given time and return any events
+    /// emitted by expired chord timeouts.
+    pub fn advance(&mut self, time: Duration) -> Vec<SimulatedEvent> {
+        let mut events = Vec::new();
+        self.chord_timeouts
+            .sort_by_key(|(deadline, _, _)| *deadline);
+        while let Some((deadline, _, _)) = self.chord_timeouts.first() {
+            if *deadline > time {
+                break;
+            }
+            let (deadline, cap, pressed_at) = self.chord_timeouts.remove(0);
+            self.time = self.time.max(deadline);
+            events.extend(self.handle_chord_timeout(cap, pressed_at));
+        }
+        self.time = self.time.max(time);
+        events
+    }
+
+    /// Handle a source input being pressed or released at the current time
+    fn handle_input(&mut self, cap: Capability, pressed: bool) -> Vec<SimulatedEvent> {
+        if pressed {
+            self.held_inputs.insert(cap.clone());
+        } else {
+            self.held_inputs.remove(&cap);
+        }
+
+        if !self.translatable_capabilities.contains(&cap) {
+            return vec![self.event(cap, pressed, false)];
+        }
+
+        // Add or remove the input from the active inputs
+        let capability_idx = self
+            .translatable_active_inputs
+            .iter()
+            .position(|c| c == &cap);
+        if pressed {
+            if capability_idx.is_some() {
+                return Vec::new();
+            }
+            self.translatable_active_inputs.push(cap.clone());
+            self.translatable_press_times.insert(cap.clone(), self.time);
+        } else if let Some(idx) = capability_idx {
+            self.translatable_active_inputs.remove(idx);
+            self.translatable_press_times.remove(&cap);
+
+            // Release inputs that were emitted untranslated as-is
+            if self.translatable_passthrough_inputs.remove(&cap) {
+                return vec![self.event(cap, false, false)];
+            }
+        } else {
+            return Vec::new();
+        }
+
+        // Emit the input untranslated if it is only used by mappings whose
+        // conditions are not met.
+        if pressed {
+            let has_active_mapping = self.map.mapping.iter().any(|mapping| {
+                mapping
+                    .source_events
+                    .iter()
+                    .any(|source| Capability::from(source.clone()) == cap)
+                    && self.is_mapping_condition_met(mapping)
+            });
+            if !has_active_mapping {
+                self.translatable_passthrough_inputs.insert(cap.clone());
+                return vec![self.event(cap, true, false)];
+            }
+        }
+
+        let mut events = Vec::new();
+        let mut emitted = Vec::new();
+        let mut released = Vec::new();
+        for mapping in self.map.mapping.iter() {
+            // Release mappings once none of their source inputs are held
+            if !pressed && self.emitted_mappings.contains_key(&mapping.name) {
+                let has_source_event_pressed = mapping
+                    .source_events
+                    .iter()
+                    .map(|source| Capability::from(source.clone()))
+                    .filter(|source| *source != Capability::NotImplemented)
+                    .any(|source| self.translatable_active_inputs.contains(&source));
+                let target = Capability::from(mapping.target_event.clone());
+                if !has_source_event_pressed && target != Capability::NotImplemented {
+                    events.push(self.event(target, false, true));
+                    released.push(mapping.name.clone());
+                }
+            }
+
+            // Press mappings whose chord is complete
+            if pressed && self.is_chord_pressed(mapping) && self.is_mapping_condition_met(mapping) {
+                let target = Capability::from(mapping.target_event.clone());
+                if target == Capability::NotImplemented {
+                    continue;
+                }
+                events.push(self.event(target, true, true));
+                emitted.push(mapping.clone());
+            }
+        }
+        for name in released {
+            self.emitted_mappings.remove(&name);
+        }
+        for mapping in emitted {
+            self.emitted_mappings.insert(mapping.name.clone(), mapping);
+        }
+
+        // Schedule a timeout to emit partial chord input untranslated
+        if pressed && !self.is_consumed(&cap) {
+            let chords = self
+                .map
+                .mapping
+                .iter()
+                .filter(|mapping| {
+                    mapping
+                        .source_events
+                        .iter()
+                        .any(|source| Capability::from(source.clone()) == cap)
+                })
+                .filter_map(|mapping| mapping.chord.as_ref());
+            if let Some(timeout) = chord::partial_timeout(chords) {
+                let deadline = self.time + timeout;
+                self.chord_timeouts.push((deadline, cap, self.time));
+            }
+        }
+
+        events
+    }
+
+    /// Emit the given input untranslated if it is still held and was not
+    /// consumed by a chord before its timeout expired.
+    fn handle_chord_timeout(
+        &mut self,
+        cap: Capability,
+        pressed_at: Duration,
+    ) -> Vec<SimulatedEvent> {
+        if self.translatable_press_times.get(&cap) != Some(&pressed_at) {
+            return Vec::new();
+        }
+        if self.is_consumed(&cap) {
+            return Vec::new();
+        }
+        self.translatable_passthrough_inputs.insert(cap.clone());
+        vec![self.event(cap, true, false)]
+    }
+
+    /// Returns true if the given input is part of an emitted chord
+    fn is_consumed(&self, cap: &Capability) -> bool {
+        self.emitted_mappings.values().any(|mapping| {
+            mapping
+                .source_events
+                .iter()
+                .any(|source| Capability::from(source.clone()) == *cap)
+        })
+    }
+
+    /// Returns true if all conditions of the given capability mapping are met
+    fn is_mapping_condition_met(&self, mapping: &CapabilityMapping) -> bool {
+        conditions::conditions_met(mapping.conditions.as_ref(), &self.intercept_mode, |cap| {
+            self.held_inputs.contains(cap)
+        })
+    }
+
+    /// Returns true if all source events of the given mapping are currently
+    /// pressed and satisfy the chord timing options of the mapping.
+    fn is_chord_pressed(&self, mapping: &CapabilityMapping) -> bool {
+        let mut press_times = Vec::with_capacity(mapping.source_events.len());
+        for source_event in mapping.source_events.iter() {
+            let cap = source_event.clone().into();
+            if cap == Capability::NotImplemented {
+                continue;
+            }
+            if !self.translatable_active_inputs.contains(&cap) {
+                return false;
+            }
+            if self.translatable_passthrough_inputs.contains(&cap) {
+                return false;
+            }
+            if let Some(pressed_at) = self.translatable_press_times.get(&cap) {
+                press_times.push(*pressed_at);
+            }
+        }
+
+        chord::chord_timing_met(mapping.chord.as_ref(), &press_times)
+    }
+
+    /// Create an emitted event at the current time of the simulation
+    fn event(&self, capability: Capability, pressed: bool, translated: bool) -> SimulatedEvent {
+        SimulatedEvent {
+            time: self.time,
+            capability,
+            pressed,
+            translated,
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use std::time::Duration;
+
+use crate::{
+    config::CapabilityMap,
+    input::{
+        capability::{Capability, Gamepad, GamepadButton, Keyboard},
+        composite_device::{
+            simulator::{CapabilityMapSimulator, SimulatedEvent, SimulatedInput},
+            InterceptMode,
+        },
+    },
+};
+
+const CHORD_MAP: &str = r#"
+version: 2
+kind: CapabilityMap
+name: Chords
+id: chords
+mapping:
+  - name: Guide
+    source_events:
+      - keyboard: KeyLeftMeta
+      - keyboard: KeyG
+    target_event:
+      gamepad:
+        button: Guide
+    chord:
+      max_interval_ms: 50
+      emit_partial: true
+  - name: Screenshot
+    source_events:
+      - keyboard: KeyLeftCtrl
+      - keyboard: KeyS
+    target_event:
+      gamepad:
+        button: Screenshot
+    chord:
+      max_interval_ms: 50
+      ordered: true
+  - name: QuickAccess
+    source_events:
+      - keyboard: KeyF1
+    target_event:
+      gamepad:
+        button: QuickAccess
+    conditions:
+      - intercept_mode: always
+"#;
+
+fn simulator() -> CapabilityMapSimulator {
+    let map = CapabilityMap::_from_yaml(CHORD_MAP.to_string()).unwrap();
+    CapabilityMapSimulator::new(map)
+}
+
+fn event(ms: u64, capability: Capability, pressed: bool, translated: bool) -> SimulatedEvent {
+    SimulatedEvent {
+        time: Duration::from_millis(ms),
+        capability,
+        pressed,
+        translated,
+    }
+}
+
+#[test]
+fn test_simulator_parse_input() {
+    let input = SimulatedInput::parse("120 press Keyboard:KeyG").unwrap();
+    assert_eq!(
+        input,
+        Some((
+            Some(Duration::from_millis(120)),
+            SimulatedInput::Press(Capability::Keyboard(Keyboard::KeyG))
+        ))
+    );
+    let input = SimulatedInput::parse("intercept Always").unwrap();
+    assert_eq!(
+        input,
+        Some((None, SimulatedInput::InterceptMode(InterceptMode::Always)))
+    );
+    assert_eq!(SimulatedInput::parse("# comment").unwrap(), None);
+    assert!(SimulatedInput::parse("press Keyboard:Invalid").is_err());
+    assert!(SimulatedInput::parse("wait").is_err());
+}
+
+#[test]
+fn test_simulator_chord() {
+    let mut simulator = simulator();
+    let meta = Capability::Keyboard(Keyboard::KeyLeftMeta);
+    let g = Capability::Keyboard(Keyboard::KeyG);
+    let guide = Capability::Gamepad(Gamepad::Button(GamepadButton::Guide));
+
+    let ms = Duration::from_millis;
+    assert!(simulator
+        .process(ms(0), SimulatedInput::Press(meta.clone()))
+        .is_empty());
+    let events = simulator.process(ms(20), SimulatedInput::Press(g.clone()));
+    assert_eq!(events, vec![event(20, guide.clone(), true, true)]);
+
+    // The target event is only released once all source inputs are released
+    assert!(simulator
+        .process(ms(30), SimulatedInput::Release(g))
+        .is_empty());
+    let events = simulator.process(ms(40), SimulatedInput::Release(meta));
+    assert_eq!(events, vec![event(40, guide, false, true)]);
+}
+
+#[test]
+fn test_simulator_partial_chord() {
+    let mut simulator = simulator();
+    let meta = Capability::Keyboard(Keyboard::KeyLeftMeta);
+    let ms = Duration::from_millis;
+
+    simulator.process(ms(0), SimulatedInput::Press(meta.clone()));
+    let events = simulator.process(ms(100), SimulatedInput::Release(meta.clone()));
+    assert_eq!(
+        events,
+        vec![
+            event(50, meta.clone(), true, false),
+            event(100, meta, false, false)
+        ]
+    );
+}
+
+#[test]
+fn test_simulator_partial_chord_completed_late() {
+    let mut simulator = simulator();
+    let meta = Capability::Keyboard(Keyboard::KeyLeftMeta);
+    let g = Capability::Keyboard(Keyboard::KeyG);
+    let ms = Duration::from_millis;
+
+    // Once the partial input was emitted, completing the chord late emits
+    // the remaining input untranslated after its own timeout instead of the
+    // chord.
+    simulator.process(ms(0), SimulatedInput::Press(meta.clone()));
+    let events = simulator.process(ms(80), SimulatedInput::Press(g.clone()));
+    assert_eq!(events, vec![event(50, meta, true, false)]);
+    let events = simulator.process(ms(140), SimulatedInput::Wait);
+    assert_eq!(events, vec![event(130, g, true, false)]);
+}
+
+#[test]
+fn test_simulator_ordered_chord() {
+    let mut simulator = simulator();
+    let ctrl = Capability::Keyboard(Keyboard::KeyLeftCtrl);
+    let s = Capability::Keyboard(Keyboard::KeyS);
+    let screenshot = Capability::Gamepad(Gamepad::Button(GamepadButton::Screenshot));
+    let ms = Duration::from_millis;
+
+    // Pressing the source events in the defined order emits the chord
+    simulator.process(ms(0), SimulatedInput::Press(ctrl.clone()));
+    let events = simulator.process(ms(10), SimulatedInput::Press(s.clone()));
+    assert_eq!(events, vec![event(10, screenshot, true, true)]);
+    simulator.process(ms(20), SimulatedInput::Release(s.clone()));
+    simulator.process(ms(30), SimulatedInput::Release(ctrl.clone()));
+
+    // Pressing them out of order does not
+    assert!(simulator
+        .process(ms(100), SimulatedInput::Press(s.clone()))
+        .is_empty());
+    let events = simulator.process(ms(110), SimulatedInput::Press(ctrl.clone()));
+    assert!(events.is_empty());
+
+    // Neither does pressing them further apart than the max interval
+    simulator.process(ms(120), SimulatedInput::Release(ctrl.clone()));
+    simulator.process(ms(130), SimulatedInput::Release(s.clone()));
+    simulator.process(ms(200), SimulatedInput::Press(ctrl));
+    let events = simulator.process(ms(300), SimulatedInput::Press(s));
+    assert!(events.is_empty());
+}
+
+#[test]
+fn test_simulator_conditions() {
+    let mut simulator = simulator();
+    let f1 = Capability::Keyboard(Keyboard::KeyF1);
+    let qam = Capability::Gamepad(Gamepad::Button(GamepadButton::QuickAccess));
+    let ms = Duration::from_millis;
+
+    // The mapping only applies while intercept mode is 'always'
+    let events = simulator.process(ms(0), SimulatedInput::Press(f1.clone()));
+    assert_eq!(events, vec![event(0, f1.clone(), true, false)]);
+    simulator.process(ms(10), SimulatedInput::Release(f1.clone()));
+
+    simulator.process(ms(20), SimulatedInput::InterceptMode(InterceptMode::Always));
+    let events = simulator.process(ms(30), SimulatedInput::Press(f1));
+    assert_eq!(events, vec![event(30, qam, true, true)]);
+}
@@ -0,0 +1,88 @@
+//! Snapshots of the runtime state of a composite device. Frontends can
+//! export a snapshot as JSON and restore it later, e.g. to restore a session
+//! after the daemon restarts.
+use serde::{Deserialize, Serialize};
+
+use super::{default_profile::ProfileSource, InterceptMode};
+
+/// Current version of the snapshot format
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Runtime state of a composite device
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DeviceSnapshot {
+    pub version: u32,
+    /// The loaded device profile. This is [None] if the embedded default
+    /// profile is loaded.
+    pub profile: Option<ProfileSnapshot>,
+    /// Name of the intercept mode, e.g. "GamepadOnly"
+    pub intercept_mode: String,
+    /// Kinds of the target devices ordered by their DBus path, so gamepads
+    /// are listed in the order they were created. E.g. ["xb360", "keyboard"]
+    pub target_devices: Vec<String>,
+    /// Ids of the blocked source devices, e.g. ["evdev://event3"]
+    pub blocked_source_devices: Vec<String>,
+}
+
+impl DeviceSnapshot {
+    /// Parse the snapshot from the given JSON string
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let snapshot: Self =
+            serde_json::from_str(json).map_err(|e| format!("Invalid snapshot: {e}"))?;
+        if snapshot.version > SNAPSHOT_VERSION {
+            return Err(format!(
+                "Unsupported snapshot version {}, expected {SNAPSHOT_VERSION} or lower",
+                snapshot.version
+            ));
+        }
+        Ok(snapshot)
+    }
+
+    /// Serialize the snapshot to a JSON string
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(self).map_err(|e| e.to_string())
+    }
+
+    /// Returns the intercept mode of the snapshot
+    pub fn intercept_mode(&self) -> Result<InterceptMode, String> {
+        InterceptMode::ALL
+            .into_iter()
+            .find(|mode| mode.as_str() == self.intercept_mode)
+            .ok_or_else(|| format!("Invalid intercept mode: {}", self.intercept_mode))
+    }
+}
+
+/// Where the device profile of a snapshot is loaded from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum ProfileSnapshot {
+    /// The profile file at the given path
+    File { path: String },
+    /// The given profile YAML that was sent over DBus
+    Yaml { yaml: String },
+}
+
+impl ProfileSnapshot {
+    /// Returns the snapshot of the given profile source, or [None] if the
+    /// embedded default profile is loaded
+    pub fn from_source(source: &ProfileSource) -> Option<Self> {
+        match source {
+            ProfileSource::File(path) => Some(Self::File { path: path.clone() }),
+            ProfileSource::Yaml(yaml) => Some(Self::Yaml { yaml: yaml.clone() }),
+            ProfileSource::Embedded => None,
+        }
+    }
+}
+
+/// Sort the given (path, kind) pairs of target devices by their DBus path.
+/// Paths end with a number that increases for each device of the same type
+/// (e.g. ".../target/gamepad10" comes after ".../target/gamepad2").
+pub fn sort_by_path(targets: &mut [(String, String)]) {
+    targets.sort_by_cached_key(|(path, _)| {
+        let name = path.rsplit('/').next().unwrap_or(path.as_str());
+        let prefix = name.trim_end_matches(|c: char| c.is_ascii_digit());
+        let index: u64 = name[prefix.len()..].parse().unwrap_or_default();
+        (prefix.to_string(), index)
+    });
+}
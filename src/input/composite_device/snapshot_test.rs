@@ -0,0 +1,87 @@
+use crate::input::composite_device::{
+    default_profile::ProfileSource,
+    snapshot::{sort_by_path, DeviceSnapshot, ProfileSnapshot, SNAPSHOT_VERSION},
+    InterceptMode,
+};
+
+fn snapshot() -> DeviceSnapshot {
+    DeviceSnapshot {
+        version: SNAPSHOT_VERSION,
+        profile: Some(ProfileSnapshot::File {
+            path: "/usr/share/inputplumber/profiles/default.yaml".to_string(),
+        }),
+        intercept_mode: "GamepadOnly".to_string(),
+        target_devices: vec!["xb360".to_string(), "keyboard".to_string()],
+        blocked_source_devices: vec!["evdev://event3".to_string()],
+    }
+}
+
+#[test]
+fn test_snapshot_round_trip() {
+    let snapshot = snapshot();
+    let json = snapshot.to_json().unwrap();
+    assert!(json.contains(r#""source":"file""#));
+    assert_eq!(DeviceSnapshot::from_json(json.as_str()).unwrap(), snapshot);
+    assert_eq!(
+        snapshot.intercept_mode().unwrap(),
+        InterceptMode::GamepadOnly
+    );
+
+    let mut snapshot = snapshot;
+    snapshot.profile = Some(ProfileSnapshot::Yaml {
+        yaml: "version: 1\nkind: DeviceProfile\nname: Test\nmapping: []\n".to_string(),
+    });
+    let json = snapshot.to_json().unwrap();
+    assert_eq!(DeviceSnapshot::from_json(json.as_str()).unwrap(), snapshot);
+}
+
+#[test]
+fn test_invalid_snapshot() {
+    assert!(DeviceSnapshot::from_json("{}").is_err());
+
+    // Snapshots from newer versions are rejected
+    let mut snapshot = snapshot();
+    snapshot.version = SNAPSHOT_VERSION + 1;
+    let json = snapshot.to_json().unwrap();
+    assert!(DeviceSnapshot::from_json(json.as_str()).is_err());
+
+    snapshot.intercept_mode = "Sometimes".to_string();
+    assert!(snapshot.intercept_mode().is_err());
+}
+
+#[test]
+fn test_profile_snapshot_from_source() {
+    let source = ProfileSource::File("/tmp/profile.yaml".to_string());
+    assert_eq!(
+        ProfileSnapshot::from_source(&source),
+        Some(ProfileSnapshot::File {
+            path: "/tmp/profile.yaml".to_string()
+        })
+    );
+    let source = ProfileSource::Yaml("version: 1".to_string());
+    assert_eq!(
+        ProfileSnapshot::from_source(&source),
+        Some(ProfileSnapshot::Yaml {
+            yaml: "version: 1".to_string()
+        })
+    );
+    assert_eq!(ProfileSnapshot::from_source(&ProfileSource::Embedded), None);
+}
+
+#[test]
+fn test_sort_by_path() {
+    let prefix = "/org/shadowblip/InputPlumber/devices/target";
+    let mut targets: Vec<(String, String)> = [
+        ("gamepad10", "ds5"),
+        ("keyboard0", "keyboard"),
+        ("gamepad2", "deck"),
+        ("gamepad0", "xb360"),
+    ]
+    .into_iter()
+    .map(|(name, kind)| (format!("{prefix}/{name}"), kind.to_string()))
+    .collect();
+
+    sort_by_path(&mut targets);
+    let kinds: Vec<&str> = targets.iter().map(|(_, kind)| kind.as_str()).collect();
+    assert_eq!(kinds, vec!["xb360", "deck", "ds5", "keyboard"]);
+}
@@ -0,0 +1,124 @@
+//! Usage statistics of the source inputs of a composite device, like how
+//! often each button was pressed and how far each axis is usually moved. The
+//! statistics are only kept in memory and help to find inputs that are
+//! rarely used and worth remapping.
+use std::collections::HashMap;
+
+use crate::input::{
+    capability::Capability,
+    event::{native::NativeEvent, value::InputValue},
+};
+
+/// Number of buckets of each axis usage histogram. Each bucket covers an equal
+/// range of the axis magnitude between 0.0 and 1.0.
+pub const AXIS_BUCKETS: usize = 10;
+
+/// Usage statistics of a single capability
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CapabilityStatistics {
+    /// Number of times the input was pressed
+    pub presses: u64,
+    /// Number of axis events in each magnitude bucket
+    pub histogram: [u64; AXIS_BUCKETS],
+    /// Whether or not the input is currently pressed
+    pressed: bool,
+    /// Last known position of a two dimensional axis, since axis events may
+    /// only update one of the values
+    position: (f64, f64),
+}
+
+impl CapabilityStatistics {
+    /// Record a press of the input if it was not already pressed
+    fn update_pressed(&mut self, pressed: bool) {
+        if pressed && !self.pressed {
+            self.presses = self.presses.saturating_add(1);
+        }
+        self.pressed = pressed;
+    }
+
+    /// Add the given axis magnitude (0.0 - 1.0) to the histogram
+    fn add_magnitude(&mut self, magnitude: f64) {
+        let bucket = (magnitude.clamp(0.0, 1.0) * AXIS_BUCKETS as f64) as usize;
+        let count = &mut self.histogram[bucket.min(AXIS_BUCKETS - 1)];
+        *count = count.saturating_add(1);
+    }
+}
+
+/// Collects usage statistics of source inputs. Capabilities have to be
+/// tracked before their events are recorded so that recording events does
+/// not allocate.
+#[derive(Debug, Default)]
+pub struct InputStatistics {
+    enabled: bool,
+    statistics: HashMap<Capability, CapabilityStatistics>,
+}
+
+impl InputStatistics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if events are being recorded
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Start or stop recording events. Collected statistics are kept when
+    /// recording is stopped.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Track the given capabilities so their events can be recorded
+    pub fn track<'a>(&mut self, capabilities: impl IntoIterator<Item = &'a Capability>) {
+        for cap in capabilities {
+            if !self.statistics.contains_key(cap) {
+                self.statistics.insert(cap.clone(), Default::default());
+            }
+        }
+    }
+
+    /// Record the given source event if recording is enabled. Events of
+    /// capabilities that are not tracked are ignored.
+    pub fn record(&mut self, event: &NativeEvent) {
+        if !self.enabled {
+            return;
+        }
+        let Some(stats) = self.statistics.get_mut(&event.as_capability()) else {
+            return;
+        };
+        match event.get_value() {
+            InputValue::Bool(pressed) => stats.update_pressed(pressed),
+            InputValue::Float(value) => {
+                stats.update_pressed(value != 0.0);
+                stats.add_magnitude(value.abs());
+            }
+            InputValue::Vector2 { x, y } => {
+                let (last_x, last_y) = stats.position;
+                let position = (x.unwrap_or(last_x), y.unwrap_or(last_y));
+                stats.position = position;
+                stats.add_magnitude(position.0.hypot(position.1));
+            }
+            _ => (),
+        }
+    }
+
+    /// Returns the statistics of all tracked capabilities that were used
+    pub fn statistics(&self) -> HashMap<Capability, CapabilityStatistics> {
+        self.statistics
+            .iter()
+            .filter(|(_, stats)| stats.presses > 0 || stats.histogram.iter().any(|c| *c > 0))
+            .map(|(cap, stats)| (cap.clone(), stats.clone()))
+            .collect()
+    }
+
+    /// Reset the statistics of all tracked capabilities
+    pub fn reset(&mut self) {
+        for stats in self.statistics.values_mut() {
+            *stats = CapabilityStatistics {
+                pressed: stats.pressed,
+                ..Default::default()
+            };
+        }
+    }
+}
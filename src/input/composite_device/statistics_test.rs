@@ -0,0 +1,68 @@
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadAxis, GamepadButton, GamepadTrigger},
+    composite_device::statistics::{InputStatistics, AXIS_BUCKETS},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+#[test]
+fn test_button_presses() {
+    let south = Capability::Gamepad(Gamepad::Button(GamepadButton::South));
+    let paddle = Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle1));
+    let mut statistics = InputStatistics::new();
+    statistics.track([&south, &paddle]);
+
+    // Nothing is recorded until enabled
+    statistics.record(&NativeEvent::new(south.clone(), InputValue::Bool(true)));
+    assert!(statistics.statistics().is_empty());
+
+    statistics.set_enabled(true);
+    for pressed in [true, true, false, true, false] {
+        statistics.record(&NativeEvent::new(south.clone(), InputValue::Bool(pressed)));
+    }
+    let stats = statistics.statistics();
+    assert_eq!(stats[&south].presses, 2);
+    // Unused inputs are not reported
+    assert!(!stats.contains_key(&paddle));
+
+    statistics.reset();
+    assert!(statistics.statistics().is_empty());
+}
+
+#[test]
+fn test_axis_histogram() {
+    let stick = Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick));
+    let trigger = Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTrigger));
+    let mut statistics = InputStatistics::new();
+    statistics.set_enabled(true);
+    statistics.track([&stick, &trigger]);
+
+    let axis = |x: Option<f64>, y: Option<f64>| {
+        NativeEvent::new(stick.clone(), InputValue::Vector2 { x, y })
+    };
+    statistics.record(&axis(Some(0.05), Some(0.0)));
+    statistics.record(&axis(Some(0.6), None));
+    statistics.record(&axis(None, Some(0.8)));
+    statistics.record(&NativeEvent::new(trigger.clone(), InputValue::Float(1.0)));
+
+    let stats = statistics.statistics();
+    let mut expected = [0; AXIS_BUCKETS];
+    expected[0] = 1;
+    expected[6] = 1;
+    expected[AXIS_BUCKETS - 1] = 1;
+    assert_eq!(stats[&stick].histogram, expected);
+    assert_eq!(stats[&stick].presses, 0);
+
+    assert_eq!(stats[&trigger].presses, 1);
+    assert_eq!(stats[&trigger].histogram[AXIS_BUCKETS - 1], 1);
+
+    // Untracked capabilities are ignored
+    let right = Capability::Gamepad(Gamepad::Axis(GamepadAxis::RightStick));
+    statistics.record(&NativeEvent::new(
+        right.clone(),
+        InputValue::Vector2 {
+            x: Some(1.0),
+            y: None,
+        },
+    ));
+    assert!(!statistics.statistics().contains_key(&right));
+}
@@ -0,0 +1,124 @@
+//! Policy for remapping system keys (the power and volume buttons) with device
+//! profiles. Handhelds can use these buttons for shortcuts, but remapping
+//! them must be explicitly allowed in the composite device config. A remapped
+//! power key that is held long enough is still passed through, so the system
+//! can always be powered off.
+use std::{collections::HashSet, str::FromStr, time::Duration};
+
+use crate::{
+    config::SystemKeys,
+    input::capability::{Capability, Keyboard},
+};
+
+/// Default time in milliseconds a remapped power key must be held before it
+/// is passed through
+pub const DEFAULT_POWER_LONG_PRESS_MS: u64 = 3000;
+
+/// Returns true if the given capability is a system key
+pub fn is_system_key(capability: &Capability) -> bool {
+    matches!(
+        capability,
+        Capability::Keyboard(Keyboard::KeyPower)
+            | Capability::Keyboard(Keyboard::KeyVolumeUp)
+            | Capability::Keyboard(Keyboard::KeyVolumeDown)
+    )
+}
+
+/// Action to take after a remapped power key event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemKeyAction {
+    /// Nothing to do
+    None,
+    /// Call [SystemKeyPolicy::expire] with the given press id after the delay
+    Schedule(Duration, u64),
+    /// Release the power key that was passed through
+    ReleasePower,
+}
+
+/// Tracks which system keys may be remapped and whether a remapped power key
+/// was held long enough to be passed through
+#[derive(Debug, Clone, Default)]
+pub struct SystemKeyPolicy {
+    allowed: HashSet<Capability>,
+    power_long_press: Option<Duration>,
+    /// Id of the current power key press, used to ignore expired timers of
+    /// earlier presses
+    press_id: u64,
+    power_held: bool,
+    power_passed_through: bool,
+}
+
+impl SystemKeyPolicy {
+    /// Create a new policy from the given config options. No system keys may
+    /// be remapped if no options are given.
+    pub fn new(options: Option<&SystemKeys>) -> Self {
+        let names = options
+            .and_then(|options| options.allow_remap.clone())
+            .unwrap_or_default();
+        let mut allowed = HashSet::new();
+        for name in names {
+            let capability =
+                Capability::from_str(name.as_str()).unwrap_or(Capability::NotImplemented);
+            if !is_system_key(&capability) {
+                log::warn!("Invalid system key to allow remapping: {name}");
+                continue;
+            }
+            allowed.insert(capability);
+        }
+        let long_press = options
+            .and_then(|options| options.power_long_press_ms)
+            .unwrap_or(DEFAULT_POWER_LONG_PRESS_MS);
+        let power_long_press = (long_press > 0).then(|| Duration::from_millis(long_press));
+
+        Self {
+            allowed,
+            power_long_press,
+            ..Default::default()
+        }
+    }
+
+    /// Returns true if device profiles may remap the given capability
+    pub fn allows_remap(&self, capability: &Capability) -> bool {
+        !is_system_key(capability) || self.allowed.contains(capability)
+    }
+
+    /// Returns true if events of the given capability must be passed to
+    /// [SystemKeyPolicy::update] when the device profile remaps it
+    pub fn tracks(&self, capability: &Capability) -> bool {
+        self.power_long_press.is_some()
+            && *capability == Capability::Keyboard(Keyboard::KeyPower)
+            && self.allowed.contains(capability)
+    }
+
+    /// Update the state of the remapped power key with the given press or
+    /// release
+    pub fn update(&mut self, pressed: bool) -> SystemKeyAction {
+        let Some(long_press) = self.power_long_press else {
+            return SystemKeyAction::None;
+        };
+        if pressed {
+            if self.power_held {
+                return SystemKeyAction::None;
+            }
+            self.power_held = true;
+            self.press_id += 1;
+            return SystemKeyAction::Schedule(long_press, self.press_id);
+        }
+
+        self.power_held = false;
+        if std::mem::take(&mut self.power_passed_through) {
+            return SystemKeyAction::ReleasePower;
+        }
+        SystemKeyAction::None
+    }
+
+    /// Called when the long press timer of the given press expires. Returns
+    /// true if the power key should be pressed because it is still held.
+    pub fn expire(&mut self, press_id: u64) -> bool {
+        if !self.power_held || press_id != self.press_id || self.power_passed_through {
+            return false;
+        }
+        self.power_passed_through = true;
+        true
+    }
+}
@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+use crate::{
+    config::SystemKeys,
+    input::{
+        capability::{Capability, Gamepad, GamepadButton, Keyboard},
+        composite_device::system_keys::{
+            is_system_key, SystemKeyAction, SystemKeyPolicy, DEFAULT_POWER_LONG_PRESS_MS,
+        },
+    },
+};
+
+const POWER: Capability = Capability::Keyboard(Keyboard::KeyPower);
+const VOLUME_UP: Capability = Capability::Keyboard(Keyboard::KeyVolumeUp);
+const VOLUME_DOWN: Capability = Capability::Keyboard(Keyboard::KeyVolumeDown);
+
+fn options(allow_remap: &[&str], power_long_press_ms: Option<u64>) -> SystemKeys {
+    SystemKeys {
+        allow_remap: Some(allow_remap.iter().map(|name| name.to_string()).collect()),
+        power_long_press_ms,
+    }
+}
+
+#[test]
+fn test_is_system_key() {
+    assert!(is_system_key(&POWER));
+    assert!(is_system_key(&VOLUME_UP));
+    assert!(is_system_key(&VOLUME_DOWN));
+    assert!(!is_system_key(&Capability::Keyboard(Keyboard::KeyA)));
+    assert!(!is_system_key(&Capability::Gamepad(Gamepad::Button(
+        GamepadButton::South
+    ))));
+}
+
+#[test]
+fn test_remap_requires_opt_in() {
+    let policy = SystemKeyPolicy::new(None);
+    assert!(!policy.allows_remap(&POWER));
+    assert!(!policy.allows_remap(&VOLUME_UP));
+    assert!(!policy.allows_remap(&VOLUME_DOWN));
+    assert!(policy.allows_remap(&Capability::Keyboard(Keyboard::KeyA)));
+    assert!(!policy.tracks(&POWER));
+
+    // Only the listed system keys can be remapped and invalid names are ignored
+    let options = options(&["Keyboard:KeyVolumeUp", "Keyboard:KeyA", "Invalid"], None);
+    let policy = SystemKeyPolicy::new(Some(&options));
+    assert!(!policy.allows_remap(&POWER));
+    assert!(policy.allows_remap(&VOLUME_UP));
+    assert!(!policy.allows_remap(&VOLUME_DOWN));
+    assert!(!policy.tracks(&VOLUME_UP));
+}
+
+#[test]
+fn test_power_long_press_passthrough() {
+    let options = options(&["Keyboard:KeyPower"], None);
+    let mut policy = SystemKeyPolicy::new(Some(&options));
+    assert!(policy.allows_remap(&POWER));
+    assert!(policy.tracks(&POWER));
+
+    // A short press is not passed through
+    let delay = Duration::from_millis(DEFAULT_POWER_LONG_PRESS_MS);
+    assert_eq!(policy.update(true), SystemKeyAction::Schedule(delay, 1));
+    assert_eq!(policy.update(false), SystemKeyAction::None);
+    assert!(!policy.expire(1));
+
+    // A long press is passed through until released
+    assert_eq!(policy.update(true), SystemKeyAction::Schedule(delay, 2));
+    assert_eq!(policy.update(true), SystemKeyAction::None);
+    assert!(policy.expire(2));
+    assert!(!policy.expire(2));
+    assert_eq!(policy.update(false), SystemKeyAction::ReleasePower);
+    assert_eq!(policy.update(false), SystemKeyAction::None);
+}
+
+#[test]
+fn test_stale_timer_is_ignored() {
+    let options = options(&["Keyboard:KeyPower"], Some(1000));
+    let mut policy = SystemKeyPolicy::new(Some(&options));
+    let delay = Duration::from_millis(1000);
+
+    assert_eq!(policy.update(true), SystemKeyAction::Schedule(delay, 1));
+    assert_eq!(policy.update(false), SystemKeyAction::None);
+    assert_eq!(policy.update(true), SystemKeyAction::Schedule(delay, 2));
+
+    // The timer of the first press expires while the second press is held
+    assert!(!policy.expire(1));
+    assert!(policy.expire(2));
+}
+
+#[test]
+fn test_power_passthrough_disabled() {
+    let options = options(&["Keyboard:KeyPower"], Some(0));
+    let mut policy = SystemKeyPolicy::new(Some(&options));
+    assert!(policy.allows_remap(&POWER));
+    assert!(!policy.tracks(&POWER));
+    assert_eq!(policy.update(true), SystemKeyAction::None);
+    assert!(!policy.expire(0));
+}
@@ -0,0 +1,112 @@
+//! Selection of the target devices requested by a device profile, and
+//! requests to the input manager to attach or stop created target devices.
+use std::{collections::HashSet, error::Error};
+
+use thiserror::Error;
+use tokio::sync::mpsc;
+
+use crate::{
+    config::{DeviceProfile, GlobalConfig},
+    input::manager::ManagerCommand,
+};
+
+/// Error returned if not all created target devices could be attached
+#[derive(Error, Debug)]
+#[error("failed to attach target device {path}: {reason}")]
+pub struct AttachError {
+    /// Path of the target device that failed to attach
+    pub path: String,
+    /// Paths of the target devices that were attached before the failure.
+    /// Their attachment may still be pending on the composite device.
+    pub attached: Vec<String>,
+    pub reason: String,
+}
+
+/// Returns the target devices defined in the given profile, either directly
+/// or by the name of a target loadout in the given global config
+pub fn profile_target_devices(
+    profile: &DeviceProfile,
+    config: &GlobalConfig,
+) -> Option<Vec<String>> {
+    if profile.target_devices.is_some() {
+        return profile.target_devices.clone();
+    }
+    let name = profile.target_loadout.as_ref()?;
+    let device_types = config.target_loadout(name.as_str());
+    if device_types.is_none() {
+        log::warn!(
+            "Unknown target loadout in profile '{}': {name}",
+            profile.name
+        );
+    }
+    device_types
+}
+
+/// Returns true if both lists contain the same target device types,
+/// regardless of order
+pub fn same_target_devices(a: &[String], b: &[String]) -> bool {
+    let a: HashSet<&String> = a.iter().collect();
+    let b: HashSet<&String> = b.iter().collect();
+    a == b
+}
+
+/// Request the input manager to attach the given target device to the
+/// composite device at the given path
+pub async fn request_attach(
+    manager: &mpsc::Sender<ManagerCommand>,
+    target_path: String,
+    composite_path: String,
+) -> Result<(), Box<dyn Error>> {
+    log::debug!("Requesting to attach target device {target_path} to {composite_path}");
+    let (sender, mut receiver) = mpsc::channel(1);
+    manager
+        .send(ManagerCommand::AttachTargetDevice {
+            target_path,
+            composite_path,
+            sender,
+        })
+        .await?;
+    let Some(response) = receiver.recv().await else {
+        return Err("Channel closed waiting for response from input manager".into());
+    };
+    if let Err(e) = response {
+        return Err(format!("Failed to attach target device: {e:?}").into());
+    }
+    Ok(())
+}
+
+/// Request the input manager to stop the given target devices
+pub async fn request_stop(manager: &mpsc::Sender<ManagerCommand>, target_paths: Vec<String>) {
+    for path in target_paths {
+        log::debug!("Rolling back created target device: {path}");
+        if let Err(e) = manager
+            .send(ManagerCommand::StopTargetDevice { path })
+            .await
+        {
+            log::error!("Failed to stop target device: {e:?}");
+        }
+    }
+}
+
+/// Request the input manager to attach all of the given created target
+/// devices to the composite device at the given path. If any of them fails to
+/// attach, all of the given target devices are stopped, including those that
+/// were already attached.
+pub async fn attach_or_stop(
+    manager: &mpsc::Sender<ManagerCommand>,
+    target_paths: &[String],
+    composite_path: &str,
+) -> Result<(), AttachError> {
+    for (i, path) in target_paths.iter().enumerate() {
+        let result = request_attach(manager, path.clone(), composite_path.to_string()).await;
+        if let Err(e) = result {
+            request_stop(manager, target_paths.to_vec()).await;
+            return Err(AttachError {
+                path: path.clone(),
+                attached: target_paths[..i].to_vec(),
+                reason: e.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
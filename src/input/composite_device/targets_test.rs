@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use tokio::sync::mpsc;
+
+use crate::{
+    config::{DeviceProfile, GlobalConfig},
+    input::{
+        composite_device::targets::{attach_or_stop, profile_target_devices, same_target_devices},
+        manager::{ManagerCommand, ManagerError},
+    },
+};
+
+fn profile(content: &str) -> DeviceProfile {
+    DeviceProfile::from_yaml(content.to_string()).expect("valid profile")
+}
+
+fn to_strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+#[test]
+fn test_profile_target_devices() {
+    let config = GlobalConfig {
+        target_loadouts: Some(HashMap::from([(
+            "deck-like".to_string(),
+            to_strings(&["deck", "keyboard"]),
+        )])),
+        ..Default::default()
+    };
+
+    // Profiles without targets do not change the target devices
+    let none = profile("version: 1\nkind: DeviceProfile\nname: None\nmapping: []\n");
+    assert_eq!(profile_target_devices(&none, &config), None);
+
+    // Target devices defined directly in the profile
+    let direct = profile(
+        "version: 1\nkind: DeviceProfile\nname: Direct\ntarget_devices: [xb360, mouse]\nmapping: []\n",
+    );
+    assert_eq!(
+        profile_target_devices(&direct, &config),
+        Some(to_strings(&["xb360", "mouse"]))
+    );
+
+    // Target devices selected by a target loadout
+    let loadout = profile(
+        "version: 1\nkind: DeviceProfile\nname: Loadout\ntarget_loadout: deck-like\nmapping: []\n",
+    );
+    assert_eq!(
+        profile_target_devices(&loadout, &config),
+        Some(to_strings(&["deck", "keyboard"]))
+    );
+    assert_eq!(
+        profile_target_devices(&loadout, &GlobalConfig::default()),
+        None
+    );
+
+    // Direct target devices take precedence over a target loadout
+    let both = profile(
+        "version: 1\nkind: DeviceProfile\nname: Both\ntarget_devices: [xb360]\ntarget_loadout: deck-like\nmapping: []\n",
+    );
+    assert_eq!(
+        profile_target_devices(&both, &config),
+        Some(to_strings(&["xb360"]))
+    );
+}
+
+#[test]
+fn test_same_target_devices() {
+    let targets = to_strings(&["xb360", "mouse", "keyboard"]);
+    assert!(same_target_devices(&targets, &targets));
+    assert!(same_target_devices(
+        &targets,
+        &to_strings(&["keyboard", "xb360", "mouse"])
+    ));
+    assert!(!same_target_devices(
+        &targets,
+        &to_strings(&["xb360", "mouse"])
+    ));
+    assert!(!same_target_devices(
+        &targets,
+        &to_strings(&["deck", "mouse", "keyboard"])
+    ));
+    assert!(same_target_devices(&[], &[]));
+}
+
+/// Requests received by a fake input manager
+#[derive(Debug, PartialEq)]
+enum Request {
+    Attach(String),
+    Stop(String),
+}
+
+/// Start a fake input manager that fails to attach the given target device
+/// and records all attach and stop requests
+fn fake_manager(fail_path: &str) -> (mpsc::Sender<ManagerCommand>, mpsc::Receiver<Request>) {
+    let (tx, mut rx) = mpsc::channel(8);
+    let (requests_tx, requests_rx) = mpsc::channel(32);
+    let fail_path = fail_path.to_string();
+    tokio::spawn(async move {
+        while let Some(cmd) = rx.recv().await {
+            match cmd {
+                ManagerCommand::AttachTargetDevice {
+                    target_path,
+                    sender,
+                    ..
+                } => {
+                    let response = if target_path == fail_path {
+                        Err(ManagerError::AttachTargetDeviceFailed(
+                            "Failed to find target device".into(),
+                        ))
+                    } else {
+                        Ok(())
+                    };
+                    requests_tx
+                        .send(Request::Attach(target_path))
+                        .await
+                        .unwrap();
+                    sender.send(response).await.unwrap();
+                }
+                ManagerCommand::StopTargetDevice { path } => {
+                    requests_tx.send(Request::Stop(path)).await.unwrap();
+                }
+                _ => (),
+            }
+        }
+    });
+    (tx, requests_rx)
+}
+
+fn drain(requests: &mut mpsc::Receiver<Request>) -> Vec<Request> {
+    let mut received = Vec::new();
+    while let Ok(request) = requests.try_recv() {
+        received.push(request);
+    }
+    received
+}
+
+#[tokio::test]
+async fn test_attach_or_stop() {
+    let paths = to_strings(&["/target/gamepad0", "/target/mouse0"]);
+    let (manager, mut requests) = fake_manager("/target/unknown");
+
+    attach_or_stop(&manager, &paths, "/composite0")
+        .await
+        .expect("all target devices attach");
+    assert_eq!(
+        drain(&mut requests),
+        vec![
+            Request::Attach("/target/gamepad0".to_string()),
+            Request::Attach("/target/mouse0".to_string()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_attach_or_stop_failure() {
+    let paths = to_strings(&["/target/gamepad0", "/target/mouse0", "/target/keyboard0"]);
+    let (manager, mut requests) = fake_manager("/target/mouse0");
+
+    // Attaching stops at the first failure and every created target device is
+    // stopped, including the one that was already attached.
+    let err = attach_or_stop(&manager, &paths, "/composite0")
+        .await
+        .expect_err("attaching the mouse fails");
+    assert_eq!(err.path, "/target/mouse0");
+    assert_eq!(err.attached, to_strings(&["/target/gamepad0"]));
+
+    // Stop requests are sent without a response, so wait for the fake manager
+    // to receive them.
+    let mut received = Vec::new();
+    while received.len() < 5 {
+        received.push(requests.recv().await.unwrap());
+    }
+    assert_eq!(
+        received,
+        vec![
+            Request::Attach("/target/gamepad0".to_string()),
+            Request::Attach("/target/mouse0".to_string()),
+            Request::Stop("/target/gamepad0".to_string()),
+            Request::Stop("/target/mouse0".to_string()),
+            Request::Stop("/target/keyboard0".to_string()),
+        ]
+    );
+}
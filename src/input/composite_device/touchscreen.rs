@@ -0,0 +1,134 @@
+//! Modes that decide how input from the physical touchscreen is emitted to
+//! target devices.
+use std::str::FromStr;
+
+use crate::input::{
+    capability::{Capability, Mouse, MouseButton, Touch},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+/// Distance in pixels the mouse moves when a finger moves across the full
+/// width of the touchscreen
+pub const MOUSE_SCALE_X: f64 = 1920.0;
+/// Distance in pixels the mouse moves when a finger moves across the full
+/// height of the touchscreen
+pub const MOUSE_SCALE_Y: f64 = 1080.0;
+
+/// How input from the physical touchscreen is emitted to target devices
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TouchscreenMode {
+    /// Touches are passed to the virtual touchscreen
+    #[default]
+    Passthrough,
+    /// Touches move the mouse and press the left mouse button
+    Mouse,
+    /// Touches are dropped, e.g. to avoid palm touches during gameplay
+    Block,
+}
+
+impl TouchscreenMode {
+    /// All available touchscreen modes
+    pub const ALL: [TouchscreenMode; 3] = [
+        TouchscreenMode::Passthrough,
+        TouchscreenMode::Mouse,
+        TouchscreenMode::Block,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TouchscreenMode::Passthrough => "passthrough",
+            TouchscreenMode::Mouse => "mouse",
+            TouchscreenMode::Block => "block",
+        }
+    }
+}
+
+impl FromStr for TouchscreenMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "passthrough" => Ok(TouchscreenMode::Passthrough),
+            "mouse" => Ok(TouchscreenMode::Mouse),
+            "block" => Ok(TouchscreenMode::Block),
+            _ => Err(format!("Invalid touchscreen mode: {s}")),
+        }
+    }
+}
+
+/// Converts touchscreen events into relative mouse events. Only the first
+/// finger is tracked. Touching the screen presses the left mouse button and
+/// moving the finger moves the mouse by the distance it travelled.
+#[derive(Debug, Default)]
+pub struct TouchMouse {
+    /// Last position of the tracked finger while it is touching
+    last_position: Option<(f64, f64)>,
+}
+
+impl TouchMouse {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if the left mouse button is held by a touch
+    pub fn is_pressed(&self) -> bool {
+        self.last_position.is_some()
+    }
+
+    /// Translate the given touchscreen event into mouse events
+    pub fn translate(&mut self, event: &NativeEvent) -> Vec<NativeEvent> {
+        if event.as_capability() != Capability::Touchscreen(Touch::Motion) {
+            return vec![];
+        }
+        let InputValue::Touch {
+            index,
+            is_touching,
+            x,
+            y,
+            ..
+        } = event.get_value()
+        else {
+            return vec![];
+        };
+        if index != 0 {
+            return vec![];
+        }
+        if !is_touching {
+            return self.release();
+        }
+
+        let Some((last_x, last_y)) = self.last_position else {
+            self.last_position = Some((x.unwrap_or_default(), y.unwrap_or_default()));
+            return vec![left_button(true)];
+        };
+        let x = x.unwrap_or(last_x);
+        let y = y.unwrap_or(last_y);
+        self.last_position = Some((x, y));
+
+        let dx = ((x - last_x) * MOUSE_SCALE_X).round();
+        let dy = ((y - last_y) * MOUSE_SCALE_Y).round();
+        if dx == 0.0 && dy == 0.0 {
+            return vec![];
+        }
+        let value = InputValue::Vector2 {
+            x: Some(dx),
+            y: Some(dy),
+        };
+        vec![NativeEvent::new(Capability::Mouse(Mouse::Motion), value)]
+    }
+
+    /// Release the left mouse button if it is held by a touch
+    pub fn release(&mut self) -> Vec<NativeEvent> {
+        if self.last_position.take().is_none() {
+            return vec![];
+        }
+        vec![left_button(false)]
+    }
+}
+
+fn left_button(pressed: bool) -> NativeEvent {
+    NativeEvent::new(
+        Capability::Mouse(Mouse::Button(MouseButton::Left)),
+        InputValue::Bool(pressed),
+    )
+}
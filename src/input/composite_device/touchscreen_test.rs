@@ -0,0 +1,70 @@
+use crate::input::{
+    capability::{Capability, Mouse, MouseButton, Touch},
+    composite_device::touchscreen::{TouchMouse, TouchscreenMode},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+fn touch(index: u8, is_touching: bool, x: f64, y: f64) -> NativeEvent {
+    let value = InputValue::Touch {
+        index,
+        is_touching,
+        pressure: None,
+        x: Some(x),
+        y: Some(y),
+    };
+    NativeEvent::new(Capability::Touchscreen(Touch::Motion), value)
+}
+
+/// Returns the capabilities and debug formatted values of the given events
+fn events(events: Vec<NativeEvent>) -> Vec<(Capability, String)> {
+    events
+        .into_iter()
+        .map(|event| (event.as_capability(), format!("{:?}", event.get_value())))
+        .collect()
+}
+
+fn value(value: InputValue) -> String {
+    format!("{value:?}")
+}
+
+#[test]
+fn test_touchscreen_mode_from_str() {
+    for mode in TouchscreenMode::ALL {
+        assert_eq!(mode.as_str().parse::<TouchscreenMode>(), Ok(mode));
+    }
+    assert!("mouse_only".parse::<TouchscreenMode>().is_err());
+    assert_eq!(TouchscreenMode::default(), TouchscreenMode::Passthrough);
+}
+
+#[test]
+fn test_touch_mouse() {
+    let left = Capability::Mouse(Mouse::Button(MouseButton::Left));
+    let motion = Capability::Mouse(Mouse::Motion);
+    let mut mouse = TouchMouse::new();
+
+    let pressed = mouse.translate(&touch(0, true, 0.5, 0.5));
+    assert_eq!(
+        events(pressed),
+        vec![(left.clone(), value(InputValue::Bool(true)))]
+    );
+    assert!(mouse.is_pressed());
+
+    let moved = mouse.translate(&touch(0, true, 0.6, 0.4));
+    let motion_value = InputValue::Vector2 {
+        x: Some(192.0),
+        y: Some(-108.0),
+    };
+    assert_eq!(events(moved), vec![(motion, value(motion_value))]);
+
+    // Other fingers are ignored
+    assert!(mouse.translate(&touch(1, true, 0.1, 0.1)).is_empty());
+    assert!(mouse.translate(&touch(0, true, 0.6, 0.4)).is_empty());
+
+    let released = mouse.translate(&touch(0, false, 0.6, 0.4));
+    assert_eq!(
+        events(released),
+        vec![(left, value(InputValue::Bool(false)))]
+    );
+    assert!(!mouse.is_pressed());
+    assert!(mouse.release().is_empty());
+}
@@ -0,0 +1,487 @@
+//! Translation of events using a [DeviceProfile] and an optional pool of
+//! worker threads to offload translation from the [CompositeDevice] loop.
+use std::{
+    collections::hash_map::DefaultHasher,
+    collections::{HashMap, HashSet},
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex, RwLock},
+    thread,
+};
+
+use tokio::sync::mpsc;
+
+use crate::{
+    config::{CapabilityMap, ProfileMapping},
+    input::{
+        capability::Capability,
+        event::{
+            native::NativeEvent,
+            value::{InputValue, TranslationError},
+        },
+        latency,
+    },
+};
+
+use super::{
+    command::CompositeCommand,
+    press::{self, PressKind},
+};
+
+/// Map of profile source events to the profile mappings that define how the
+/// source event should be translated.
+pub type ProfileMap = HashMap<Capability, Vec<ProfileMapping>>;
+
+/// Maximum number of queued events a worker of the [TranslationPool]
+/// translates as a single batch. Events are not batched in low latency mode.
+pub const MAX_BATCH_SIZE: usize = 256;
+
+/// Profile mappings of a single source capability together with the target
+/// capabilities of each mapping, so they only need to be converted once for
+/// a batch of events.
+type CompiledMappings<'a> = Vec<(&'a ProfileMapping, Vec<Capability>)>;
+
+/// Returns the capabilities produced from the given source capabilities by
+/// the given capability map and device profile. Source capabilities consumed
+/// by the capability map are replaced by its target capabilities, and any
+/// capability without a profile mapping passes through untranslated.
+pub fn translated_capabilities(
+    source_caps: &HashSet<Capability>,
+    map: Option<&CapabilityMap>,
+    profile: &ProfileMap,
+) -> HashSet<Capability> {
+    let mut caps = source_caps.clone();
+    if let Some(map) = map {
+        // Source capabilities of mappings with conditions or chords that emit
+        // partial input can still be emitted untranslated.
+        let mut consumed = HashSet::new();
+        let mut passed = HashSet::new();
+        for mapping in map.mapping.iter() {
+            let can_pass = mapping.conditions.is_some()
+                || mapping
+                    .chord
+                    .as_ref()
+                    .is_some_and(|chord| chord.emit_partial.unwrap_or(false));
+            for source in mapping.source_events.iter() {
+                let cap: Capability = source.clone().into();
+                if can_pass {
+                    passed.insert(cap.clone());
+                }
+                consumed.insert(cap);
+            }
+        }
+        caps.retain(|cap| !consumed.contains(cap) || passed.contains(cap));
+
+        for mapping in map.mapping.iter() {
+            let cap: Capability = mapping.target_event.clone().into();
+            if cap == Capability::NotImplemented {
+                continue;
+            }
+            caps.insert(cap);
+        }
+    }
+
+    let mut translated = HashSet::new();
+    for cap in caps {
+        let Some(mappings) = profile.get(&cap) else {
+            translated.insert(cap);
+            continue;
+        };
+        for mapping in mappings {
+            for target_event in mapping.target_events.iter() {
+                let target_cap: Capability = target_event.clone().into();
+                if target_cap == Capability::NotImplemented {
+                    continue;
+                }
+                translated.insert(target_cap);
+            }
+        }
+    }
+    translated
+}
+
+/// Translates the given event into a Vec of events based on the given
+/// profile mappings. If no mapping is found, the original un-translated event
+/// is returned.
+pub fn translate_event(profile: &ProfileMap, event: &NativeEvent) -> Vec<NativeEvent> {
+    translate_events(profile, std::slice::from_ref(event))
+        .pop()
+        .unwrap_or_default()
+}
+
+/// Translates each of the given events based on the given profile mappings
+/// and returns the translated events of each source event in the same order.
+/// High-rate streams like IMU or touch motion usually alternate between only
+/// a few capabilities, so the profile lookup and target capability conversion
+/// is done once per capability in the batch instead of once per event. Only
+/// workers of the [TranslationPool] translate queued events in batches;
+/// events translated in the composite device loop are translated one at a
+/// time.
+pub fn translate_events(profile: &ProfileMap, events: &[NativeEvent]) -> Vec<Vec<NativeEvent>> {
+    // Mappings of each capability seen in this batch. Batches rarely contain
+    // more than a few capabilities, so a linear search is faster than hashing.
+    let mut compiled: Vec<(Capability, Option<CompiledMappings>)> = Vec::new();
+
+    let mut results = Vec::with_capacity(events.len());
+    for event in events {
+        let source_cap = event.as_capability();
+        let index = match compiled.iter().position(|(cap, _)| *cap == source_cap) {
+            Some(index) => index,
+            None => {
+                let mappings = profile.get(&source_cap).map(|mappings| compile(mappings));
+                compiled.push((source_cap, mappings));
+                compiled.len() - 1
+            }
+        };
+
+        // If no mapping is found, return the original un-translated event.
+        let Some(mappings) = compiled[index].1.as_ref() else {
+            log::trace!(
+                "No translation mapping found for event: {:?}",
+                compiled[index].0
+            );
+            results.push(vec![event.clone()]);
+            continue;
+        };
+
+        // Find which mappings in the device profile matches this source event
+        let matched_mappings = mappings
+            .iter()
+            .filter(|(mapping, _)| mapping.source_matches_properties(event))
+            .map(|(mapping, targets)| (*mapping, targets.as_slice()));
+        results.push(translate_mappings(matched_mappings, event));
+    }
+
+    results
+}
+
+/// Translates the given event into a Vec of events using only the profile
+/// mappings of the given press kind.
+pub fn translate_press(
+    mappings: &[ProfileMapping],
+    kind: PressKind,
+    event: &NativeEvent,
+) -> Vec<NativeEvent> {
+    let mappings = compile(mappings);
+    let matched_mappings = mappings
+        .iter()
+        .filter(|(mapping, _)| {
+            press::press_kind(mapping) == kind && mapping.source_matches_properties(event)
+        })
+        .map(|(mapping, targets)| (*mapping, targets.as_slice()));
+
+    translate_mappings(matched_mappings, event)
+}
+
+/// Convert the target events of the given mappings into capabilities
+fn compile(mappings: &[ProfileMapping]) -> CompiledMappings<'_> {
+    mappings
+        .iter()
+        .map(|mapping| {
+            let targets = mapping
+                .target_events
+                .iter()
+                .map(|target_event| target_event.clone().into())
+                .collect();
+            (mapping, targets)
+        })
+        .collect()
+}
+
+/// Translates the given event into a Vec of events using the given profile
+/// mappings and the target capabilities of each mapping.
+fn translate_mappings<'a>(
+    mappings: impl Iterator<Item = (&'a ProfileMapping, &'a [Capability])>,
+    event: &NativeEvent,
+) -> Vec<NativeEvent> {
+    let source_cap = event.as_capability();
+    let event_timestamp = event.timestamp();
+    let mut events = Vec::new();
+    // Based on all found mappings, translate the event
+    for (mapping, target_caps) in mappings {
+        log::trace!(
+            "Found translation for event {:?} in profile mapping: {}",
+            source_cap,
+            mapping.name
+        );
+
+        // Translate the event into the defined target event(s)
+        for (target_event, target_cap) in mapping.target_events.iter().zip(target_caps) {
+            let result = event.get_value().translate(
+                &source_cap,
+                &mapping.source_event,
+                target_cap,
+                target_event,
+            );
+            let value = match result {
+                Ok(v) => v,
+                Err(err) => match err {
+                    TranslationError::NotImplemented => {
+                        log::warn!(
+                            "Translation not implemented for profile mapping '{}': {:?} -> {:?}",
+                            mapping.name,
+                            source_cap,
+                            target_cap,
+                        );
+                        continue;
+                    }
+                    TranslationError::ImpossibleTranslation(msg) => {
+                        log::warn!(
+                            "Impossible translation for profile mapping '{}': {msg}",
+                            mapping.name
+                        );
+                        continue;
+                    }
+                    TranslationError::InvalidSourceConfig(msg) => {
+                        log::warn!(
+                            "Invalid source event config in profile mapping '{}': {msg}",
+                            mapping.name
+                        );
+                        continue;
+                    }
+                    TranslationError::InvalidTargetConfig(msg) => {
+                        log::warn!(
+                            "Invalid target event config in profile mapping '{}': {msg}",
+                            mapping.name
+                        );
+                        continue;
+                    }
+                },
+            };
+            if matches!(value, InputValue::None) {
+                continue;
+            }
+
+            let mut event =
+                NativeEvent::new_translated(source_cap.clone(), target_cap.clone(), value);
+            event.set_timestamp(event_timestamp);
+            let bounds = target_event
+                .mouse
+                .as_ref()
+                .and_then(|mouse| mouse.motion.as_ref())
+                .and_then(|motion| motion.bounds.as_ref());
+            if let Some(bounds) = bounds {
+                event.set_motion_bounds(Some((bounds.width, bounds.height)));
+            }
+            let repeat_rate = target_event
+                .mouse
+                .as_ref()
+                .and_then(|mouse| mouse.wheel.as_ref())
+                .and_then(|wheel| wheel.repeat_rate);
+            event.set_wheel_repeat_rate(repeat_rate);
+            events.push(event);
+        }
+    }
+
+    events
+}
+
+/// Result of an event that was processed by the [TranslationPool]
+#[derive(Debug, Clone)]
+pub struct TranslatedEvent {
+    /// The original source event
+    pub event: NativeEvent,
+    /// Events translated using the device profile. This is [None] if the
+    /// event was not meant to be translated by the pool.
+    pub events: Option<Vec<NativeEvent>>,
+    /// Generation of the device profile that was used to translate the event
+    pub generation: u64,
+}
+
+/// Event to process by a worker of the [TranslationPool]
+struct Job {
+    event: NativeEvent,
+    translate: bool,
+}
+
+/// The [TranslationPool] translates source events using the device profile on
+/// a pool of worker threads. All events from the same source device are
+/// handled by the same worker, so the order of events from each source device
+/// is preserved. Workers translate all queued events as one batch and send
+/// the results back to the [CompositeDevice] as
+/// [CompositeCommand::HandleTranslatedEvents] commands, which are handled in
+/// the order they are received.
+#[derive(Debug)]
+pub struct TranslationPool {
+    workers: Vec<std::sync::mpsc::Sender<Job>>,
+    /// Dedicated worker of each source device if the pool was created with
+    /// [TranslationPool::new_per_source]
+    source_workers: Option<Mutex<HashMap<String, std::sync::mpsc::Sender<Job>>>>,
+    composite_device: mpsc::Sender<CompositeCommand>,
+    profile: Arc<RwLock<(u64, Arc<ProfileMap>)>>,
+}
+
+impl TranslationPool {
+    /// Create a new [TranslationPool] with the given number of worker threads
+    /// that send translated events to the given composite device channel.
+    pub fn new(
+        size: usize,
+        composite_device: mpsc::Sender<CompositeCommand>,
+    ) -> Result<Self, std::io::Error> {
+        let profile = Arc::new(RwLock::new((0, Arc::new(ProfileMap::new()))));
+        let mut workers = Vec::with_capacity(size);
+        for i in 0..size.max(1) {
+            let name = format!("translation-{i}");
+            workers.push(Self::spawn_worker(name, &profile, &composite_device)?);
+        }
+
+        Ok(Self {
+            workers,
+            source_workers: None,
+            composite_device,
+            profile,
+        })
+    }
+
+    /// Create a new [TranslationPool] that starts a dedicated worker thread
+    /// for each source device when its first event is submitted. This avoids
+    /// multiple high-rate source devices (e.g. IMU, touchpad, and gamepad)
+    /// sharing a worker.
+    pub fn new_per_source(composite_device: mpsc::Sender<CompositeCommand>) -> Self {
+        Self {
+            workers: Vec::new(),
+            source_workers: Some(Mutex::new(HashMap::new())),
+            composite_device,
+            profile: Arc::new(RwLock::new((0, Arc::new(ProfileMap::new())))),
+        }
+    }
+
+    /// Spawn a worker thread with the given name that translates jobs and
+    /// sends the results to the given composite device channel.
+    fn spawn_worker(
+        name: String,
+        profile: &Arc<RwLock<(u64, Arc<ProfileMap>)>>,
+        composite_device: &mpsc::Sender<CompositeCommand>,
+    ) -> Result<std::sync::mpsc::Sender<Job>, std::io::Error> {
+        let (tx, rx) = std::sync::mpsc::channel::<Job>();
+        let profile = profile.clone();
+        let composite_device = composite_device.clone();
+        thread::Builder::new().name(name.clone()).spawn(move || {
+            while let Ok(job) = rx.recv() {
+                // Take any events that were queued while the last batch was
+                // being processed, so event storms are translated in batches
+                let mut jobs = vec![job];
+                let batch_size = latency::batch_size(MAX_BATCH_SIZE);
+                jobs.extend(rx.try_iter().take(batch_size - 1));
+                let results = Self::process(&profile, jobs);
+                let command = CompositeCommand::HandleTranslatedEvents(results);
+                if composite_device.blocking_send(command).is_err() {
+                    break;
+                }
+            }
+            log::debug!("Translation worker {name} stopped");
+        })?;
+
+        Ok(tx)
+    }
+
+    /// Translate the given jobs using the current device profile
+    fn process(profile: &RwLock<(u64, Arc<ProfileMap>)>, jobs: Vec<Job>) -> Vec<TranslatedEvent> {
+        let (generation, profile) = match profile.read() {
+            Ok(profile) => (profile.0, profile.1.clone()),
+            Err(poisoned) => {
+                let profile = poisoned.into_inner();
+                (profile.0, profile.1.clone())
+            }
+        };
+        let (events, translate): (Vec<NativeEvent>, Vec<bool>) = jobs
+            .into_iter()
+            .map(|job| (job.event, job.translate))
+            .unzip();
+
+        // Translate all events that are meant to be translated in one call
+        let to_translate: Vec<NativeEvent> = events
+            .iter()
+            .zip(translate.iter())
+            .filter(|(_, translate)| **translate)
+            .map(|(event, _)| event.clone())
+            .collect();
+        let mut translated = translate_events(profile.as_ref(), &to_translate).into_iter();
+
+        events
+            .into_iter()
+            .zip(translate)
+            .map(|(event, translate)| TranslatedEvent {
+                event,
+                events: if translate { translated.next() } else { None },
+                generation,
+            })
+            .collect()
+    }
+
+    /// Replace the device profile used to translate events. Returns the new
+    /// profile generation.
+    pub fn set_profile(&self, profile: ProfileMap) -> u64 {
+        let mut current = match self.profile.write() {
+            Ok(current) => current,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        current.0 = current.0.wrapping_add(1);
+        current.1 = Arc::new(profile);
+        current.0
+    }
+
+    /// Submit the given event from the given source device. If `translate`
+    /// is false, the event is passed back untranslated so it stays in order
+    /// with the other events from the source device.
+    pub fn submit(&self, source_id: &str, event: NativeEvent, translate: bool) {
+        let job = Job { event, translate };
+
+        // Send the job to the dedicated worker of the source device
+        if let Some(source_workers) = self.source_workers.as_ref() {
+            let mut source_workers = match source_workers.lock() {
+                Ok(workers) => workers,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            if !source_workers.contains_key(source_id) {
+                let name = format!("translation-{source_id}");
+                match Self::spawn_worker(name, &self.profile, &self.composite_device) {
+                    Ok(worker) => {
+                        source_workers.insert(source_id.to_string(), worker);
+                    }
+                    Err(e) => {
+                        log::error!("Failed to start translation worker for {source_id}: {e:?}");
+                        return;
+                    }
+                }
+            }
+            if let Some(worker) = source_workers.get(source_id) {
+                if let Err(e) = worker.send(job) {
+                    log::error!("Failed to send event to translation worker: {e:?}");
+                }
+            }
+            return;
+        }
+
+        let mut hasher = DefaultHasher::new();
+        source_id.hash(&mut hasher);
+        let index = (hasher.finish() % self.workers.len() as u64) as usize;
+        if let Err(e) = self.workers[index].send(job) {
+            log::error!("Failed to send event to translation worker: {e:?}");
+        }
+    }
+
+    /// Stop the dedicated worker of the given source device once it has
+    /// processed all submitted events. Does nothing if the pool is not
+    /// sharded by source device.
+    pub fn remove_source(&self, source_id: &str) {
+        let Some(source_workers) = self.source_workers.as_ref() else {
+            return;
+        };
+        let mut source_workers = match source_workers.lock() {
+            Ok(workers) => workers,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        source_workers.remove(source_id);
+    }
+
+    /// Returns the number of worker threads in the pool
+    pub fn size(&self) -> usize {
+        match self.source_workers.as_ref() {
+            Some(source_workers) => match source_workers.lock() {
+                Ok(workers) => workers.len(),
+                Err(poisoned) => poisoned.into_inner().len(),
+            },
+            None => self.workers.len(),
+        }
+    }
+}
@@ -0,0 +1,399 @@
+use std::{collections::HashSet, time::Instant};
+
+use tokio::sync::mpsc;
+
+use crate::{
+    config::{CapabilityMap, DeviceProfile},
+    input::{
+        capability::{
+            Capability, Gamepad, GamepadAxis, GamepadButton, GamepadDial, Keyboard, Mouse,
+            MouseButton,
+        },
+        composite_device::{
+            command::CompositeCommand,
+            translation::{
+                translate_event, translate_events, translated_capabilities, ProfileMap,
+                TranslatedEvent, TranslationPool,
+            },
+        },
+        event::{native::NativeEvent, value::InputValue},
+    },
+};
+
+const PROFILE_PATH: &str = "rootfs/usr/share/inputplumber/profiles/mouse_keyboard_wasd.yaml";
+
+const WHEEL_PROFILE: &str = r#"
+version: 1
+kind: DeviceProfile
+name: Wheel
+mapping:
+  - name: Dial Up
+    source_event:
+      gamepad:
+        dial: RightStickDial
+    target_events:
+      - mouse:
+          button: WheelUp
+          wheel:
+            threshold: 2.0
+  - name: Dial Down
+    source_event:
+      gamepad:
+        dial: RightStickDial
+    target_events:
+      - mouse:
+          button: WheelDown
+  - name: Stick Up
+    source_event:
+      gamepad:
+        axis:
+          name: LeftStick
+          direction: up
+    target_events:
+      - mouse:
+          button: WheelUp
+          wheel:
+            threshold: 0.5
+            repeat_rate: 10.0
+"#;
+
+fn load_profile_map() -> ProfileMap {
+    let profile = DeviceProfile::from_yaml_file(PROFILE_PATH.to_string()).unwrap();
+    to_profile_map(profile)
+}
+
+fn to_profile_map(profile: DeviceProfile) -> ProfileMap {
+    let mut map = ProfileMap::new();
+    for mapping in profile.mapping {
+        let cap: Capability = mapping.source_event.clone().into();
+        map.entry(cap).or_default().push(mapping);
+    }
+    map
+}
+
+fn button_event(button: GamepadButton, pressed: bool) -> NativeEvent {
+    NativeEvent::new(
+        Capability::Gamepad(Gamepad::Button(button)),
+        InputValue::Bool(pressed),
+    )
+}
+
+fn stick_event(axis: GamepadAxis, x: f64) -> NativeEvent {
+    NativeEvent::new(
+        Capability::Gamepad(Gamepad::Axis(axis)),
+        InputValue::Vector2 {
+            x: Some(x),
+            y: Some(0.0),
+        },
+    )
+}
+
+fn imu_event(capability: Gamepad, value: f64) -> NativeEvent {
+    NativeEvent::new(
+        Capability::Gamepad(capability),
+        InputValue::Vector3 {
+            x: Some(value),
+            y: Some(-value),
+            z: Some(0.0),
+        },
+    )
+}
+
+/// Receive translated events from the pool until the given number of results
+/// was received
+fn recv_results(rx: &mut mpsc::Receiver<CompositeCommand>, count: usize) -> Vec<TranslatedEvent> {
+    let mut results = Vec::with_capacity(count);
+    while results.len() < count {
+        let Some(CompositeCommand::HandleTranslatedEvents(batch)) = rx.blocking_recv() else {
+            panic!("Expected translated events");
+        };
+        assert!(!batch.is_empty());
+        results.extend(batch);
+    }
+    assert_eq!(results.len(), count);
+    results
+}
+
+#[test]
+fn test_translate_event() {
+    let map = load_profile_map();
+
+    let event = button_event(GamepadButton::DPadUp, true);
+    let events = translate_event(&map, &event);
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        events[0].as_capability().to_string(),
+        "Keyboard:KeyUp".to_string()
+    );
+    assert!(events[0].pressed());
+
+    // Events without a mapping are returned untranslated
+    let event = button_event(GamepadButton::Screenshot, true);
+    let events = translate_event(&map, &event);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].as_capability(), event.as_capability());
+}
+
+#[test]
+fn test_translate_events() {
+    let map = load_profile_map();
+
+    // Batches translate each event like it was translated on its own
+    let events = vec![
+        button_event(GamepadButton::DPadUp, true),
+        imu_event(Gamepad::Gyro, 0.5),
+        stick_event(GamepadAxis::LeftStick, 0.9),
+        button_event(GamepadButton::Screenshot, true),
+        imu_event(Gamepad::Accelerometer, 0.1),
+        stick_event(GamepadAxis::LeftStick, 0.1),
+        button_event(GamepadButton::DPadUp, false),
+    ];
+    let results = translate_events(&map, &events);
+    assert_eq!(results.len(), events.len());
+    for (event, translated) in events.iter().zip(results) {
+        let expected = translate_event(&map, event);
+        assert_eq!(translated.len(), expected.len());
+        for (translated, expected) in translated.iter().zip(expected.iter()) {
+            assert_eq!(translated.as_capability(), expected.as_capability());
+            assert_eq!(translated.pressed(), expected.pressed());
+        }
+    }
+
+    assert!(translate_events(&map, &[]).is_empty());
+}
+
+#[test]
+fn test_translate_dial_to_wheel() {
+    let profile = DeviceProfile::from_yaml(WHEEL_PROFILE.to_string()).unwrap();
+    let map = to_profile_map(profile);
+    let dial = |value: f64| {
+        NativeEvent::new(
+            Capability::Gamepad(Gamepad::Dial(GamepadDial::RightStickDial)),
+            InputValue::Float(value),
+        )
+    };
+
+    // Positive rotation scrolls up using the configured threshold
+    let events = translate_event(&map, &dial(1.0));
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        events[0].as_capability(),
+        Capability::Mouse(Mouse::Button(MouseButton::WheelUp))
+    );
+    let InputValue::Float(detents) = events[0].get_value() else {
+        panic!("Expected Float value");
+    };
+    assert_eq!(detents, 0.5);
+
+    // Negative rotation scrolls down using the default threshold
+    let events = translate_event(&map, &dial(-2.0));
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        events[0].as_capability(),
+        Capability::Mouse(Mouse::Button(MouseButton::WheelDown))
+    );
+    let InputValue::Float(detents) = events[0].get_value() else {
+        panic!("Expected Float value");
+    };
+    assert_eq!(detents, 2.0);
+
+    // No rotation does not scroll
+    let events = translate_event(&map, &dial(0.0));
+    assert!(events.is_empty());
+}
+
+#[test]
+fn test_translate_axis_to_wheel() {
+    let profile = DeviceProfile::from_yaml(WHEEL_PROFILE.to_string()).unwrap();
+    let map = to_profile_map(profile);
+    let stick = |y: f64| {
+        NativeEvent::new(
+            Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+            InputValue::Vector2 {
+                x: Some(0.0),
+                y: Some(y),
+            },
+        )
+    };
+
+    // The wheel threshold is used instead of the axis deadzone
+    let events = translate_event(&map, &stick(-0.4));
+    assert_eq!(events.len(), 1);
+    assert!(!events[0].pressed());
+
+    let events = translate_event(&map, &stick(-0.6));
+    assert_eq!(events.len(), 1);
+    assert!(events[0].pressed());
+    assert_eq!(events[0].get_wheel_repeat_rate(), Some(10.0));
+}
+
+#[test]
+fn test_translation_pool_order() {
+    let (tx, mut rx) = mpsc::channel(1024);
+    let pool = TranslationPool::new(4, tx).unwrap();
+    let generation = pool.set_profile(load_profile_map());
+    assert_eq!(generation, 1);
+
+    // Submit interleaved events from two source devices
+    let count = 200;
+    for i in 0..count {
+        let x = i as f64 / count as f64;
+        pool.submit(
+            "evdev://event0",
+            stick_event(GamepadAxis::LeftStick, x),
+            false,
+        );
+        pool.submit(
+            "evdev://event1",
+            stick_event(GamepadAxis::RightStick, x),
+            true,
+        );
+    }
+
+    // Events from each source device must arrive in order
+    let mut last = [-1.0, -1.0];
+    for result in recv_results(&mut rx, count * 2) {
+        assert_eq!(result.generation, generation);
+        let index = match result.event.as_capability() {
+            Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)) => {
+                assert!(result.events.is_none());
+                0
+            }
+            _ => {
+                assert!(result.events.is_some());
+                1
+            }
+        };
+        let InputValue::Vector2 { x: Some(x), .. } = result.event.get_value() else {
+            panic!("Expected Vector2 value");
+        };
+        assert!(x > last[index]);
+        last[index] = x;
+    }
+}
+
+#[test]
+fn test_translation_pool_per_source() {
+    let (tx, mut rx) = mpsc::channel(1024);
+    let pool = TranslationPool::new_per_source(tx);
+    let generation = pool.set_profile(load_profile_map());
+    assert_eq!(pool.size(), 0);
+
+    // Each source device is handled by its own worker
+    let count = 100;
+    let sources = ["evdev://event0", "evdev://event1", "iio://iio:device0"];
+    for i in 0..count {
+        let x = i as f64 / count as f64;
+        for source in sources {
+            pool.submit(source, stick_event(GamepadAxis::LeftStick, x), true);
+        }
+    }
+    assert_eq!(pool.size(), sources.len());
+
+    for result in recv_results(&mut rx, count * sources.len()) {
+        assert_eq!(result.generation, generation);
+        assert!(result.events.is_some());
+    }
+
+    pool.remove_source("evdev://event1");
+    assert_eq!(pool.size(), sources.len() - 1);
+}
+
+/// Compares translating events in a single thread with the translation pool.
+/// Run with: cargo test bench_translation_pool -- --ignored --nocapture
+#[test]
+#[ignore]
+fn bench_translation_pool() {
+    let map = load_profile_map();
+    let count = 100_000;
+    let sources = ["evdev://event0", "evdev://event1", "hidraw://hidraw0"];
+    let events: Vec<NativeEvent> = (0..count)
+        .map(|i| button_event(GamepadButton::DPadUp, i % 2 == 0))
+        .collect();
+
+    let start = Instant::now();
+    for event in events.iter() {
+        let _ = translate_event(&map, event);
+    }
+    let inline = start.elapsed();
+    println!("Inline: {count} events in {inline:?}");
+
+    for size in [1, 2, 4] {
+        let (tx, mut rx) = mpsc::channel(count);
+        let pool = TranslationPool::new(size, tx).unwrap();
+        pool.set_profile(map.clone());
+        let start = Instant::now();
+        for (i, event) in events.iter().enumerate() {
+            pool.submit(sources[i % sources.len()], event.clone(), true);
+        }
+        recv_results(&mut rx, count);
+        let elapsed = start.elapsed();
+        println!("Pool ({size} workers): {count} events in {elapsed:?}");
+    }
+}
+
+const CHORD_MAP: &str = r#"
+version: 2
+kind: CapabilityMap
+name: Chords
+id: chords
+mapping:
+  - name: Guide
+    source_events:
+      - keyboard: KeyLeftMeta
+      - keyboard: KeyG
+    target_event:
+      gamepad:
+        button: Guide
+  - name: QuickAccess
+    source_events:
+      - keyboard: KeyF1
+    target_event:
+      gamepad:
+        button: QuickAccess
+    conditions:
+      - intercept_mode: always
+"#;
+
+const SWAP_PROFILE: &str = r#"
+version: 1
+kind: DeviceProfile
+name: Swap
+mapping:
+  - name: Guide
+    source_event:
+      gamepad:
+        button: Guide
+    target_events:
+      - gamepad:
+          button: Start
+"#;
+
+#[test]
+fn test_translated_capabilities() {
+    let meta = Capability::Keyboard(Keyboard::KeyLeftMeta);
+    let g = Capability::Keyboard(Keyboard::KeyG);
+    let f1 = Capability::Keyboard(Keyboard::KeyF1);
+    let south = Capability::Gamepad(Gamepad::Button(GamepadButton::South));
+    let source_caps = HashSet::from([meta, g, f1.clone(), south.clone()]);
+    let map = CapabilityMap::_from_yaml(CHORD_MAP.to_string()).unwrap();
+    let profile = to_profile_map(DeviceProfile::from_yaml(SWAP_PROFILE.to_string()).unwrap());
+
+    // Source capabilities consumed by the capability map are replaced by its
+    // target capabilities. Inputs of mappings with conditions can still be
+    // emitted untranslated.
+    let caps = translated_capabilities(&source_caps, Some(&map), &profile);
+    assert_eq!(
+        caps,
+        HashSet::from([
+            f1,
+            south,
+            Capability::Gamepad(Gamepad::Button(GamepadButton::Start)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::QuickAccess)),
+        ])
+    );
+
+    // Without a capability map all source capabilities pass through
+    let caps = translated_capabilities(&source_caps, None, &ProfileMap::new());
+    assert_eq!(caps, source_caps);
+}
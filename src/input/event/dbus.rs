@@ -32,6 +32,9 @@ pub enum Action {
     VolumeUp,
     VolumeDown,
     VolumeMute,
+    MicMute,
+    HeadphoneInsert,
+    MicrophoneInsert,
     Keyboard,
     Screenshot,
     Touch,
@@ -63,6 +66,9 @@ impl Action {
             Action::VolumeUp => "ui_volume_up",
             Action::VolumeDown => "ui_volume_down",
             Action::VolumeMute => "ui_volume_mute",
+            Action::MicMute => "ui_mic_mute",
+            Action::HeadphoneInsert => "ui_headphone_insert",
+            Action::MicrophoneInsert => "ui_microphone_insert",
             Action::Keyboard => "ui_osk",
             Action::Screenshot => "ui_screenshot",
             Action::Touch => "ui_touch",
@@ -102,6 +108,9 @@ impl FromStr for Action {
             "ui_volume_up" => Ok(Action::VolumeUp),
             "ui_volume_down" => Ok(Action::VolumeDown),
             "ui_volume_mute" => Ok(Action::VolumeMute),
+            "ui_mic_mute" => Ok(Action::MicMute),
+            "ui_headphone_insert" => Ok(Action::HeadphoneInsert),
+            "ui_microphone_insert" => Ok(Action::MicrophoneInsert),
             "ui_osk" => Ok(Action::Keyboard),
             "ui_screenshot" => Ok(Action::Screenshot),
             "ui_touch" => Ok(Action::Touch),
@@ -173,7 +182,7 @@ impl DBusEvent {
 }
 
 /// Returns an array of DBus event actions from the given event capability.
-fn actions_from_capability(capability: Capability) -> Vec<Action> {
+pub fn actions_from_capability(capability: Capability) -> Vec<Action> {
     match capability {
         Capability::None => vec![Action::None],
         Capability::NotImplemented => vec![Action::None],
@@ -192,7 +201,9 @@ fn actions_from_capability(capability: Capability) -> Vec<Action> {
                 GamepadButton::QuickAccess2 => vec![Action::Quick2],
                 GamepadButton::Keyboard => vec![Action::Keyboard],
                 GamepadButton::Screenshot => vec![Action::Screenshot],
-                GamepadButton::Mute => vec![Action::VolumeMute],
+                GamepadButton::Mute => vec![Action::MicMute],
+                GamepadButton::HeadphoneInsert => vec![Action::HeadphoneInsert],
+                GamepadButton::MicrophoneInsert => vec![Action::MicrophoneInsert],
                 GamepadButton::DPadUp => vec![Action::Up],
                 GamepadButton::DPadDown => vec![Action::Down],
                 GamepadButton::DPadLeft => vec![Action::Left],
@@ -345,6 +356,7 @@ fn actions_from_capability(capability: Capability) -> Vec<Action> {
             Keyboard::KeyInsert => vec![Action::None],
             Keyboard::KeyDelete => vec![Action::None],
             Keyboard::KeyMute => vec![Action::VolumeMute],
+            Keyboard::KeyMicMute => vec![Action::MicMute],
             Keyboard::KeyVolumeDown => vec![Action::VolumeDown],
             Keyboard::KeyVolumeUp => vec![Action::VolumeUp],
             Keyboard::KeyPower => vec![Action::None],
@@ -403,6 +415,7 @@ fn actions_from_capability(capability: Capability) -> Vec<Action> {
             Touch::Motion => vec![Action::Touch],
             Touch::Button(_) => vec![Action::None],
         },
+        Capability::Switch(_) => vec![Action::None],
     }
 }
 
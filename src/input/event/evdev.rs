@@ -1,10 +1,12 @@
 use std::collections::HashMap;
 
-use evdev::{AbsInfo, AbsoluteAxisCode, EventType, InputEvent, KeyCode, RelativeAxisCode};
+use evdev::{
+    AbsInfo, AbsoluteAxisCode, EventType, InputEvent, KeyCode, RelativeAxisCode, SwitchCode,
+};
 
 use crate::input::capability::{
     Capability, Gamepad, GamepadAxis, GamepadButton, GamepadTrigger, Keyboard, Mouse, MouseButton,
-    Touch, TouchButton, Touchpad,
+    Switch, Touch, TouchButton, Touchpad,
 };
 
 use super::{native::NativeEvent, value::InputValue};
@@ -25,8 +27,7 @@ impl EvdevEvent {
         // Select the appropiate value type based on the type of input. E.g. Axis
         // values should be in the form of a [InputValue::Vector2].
         match event_type {
-            EventType::KEY => {
-                KeyCode::new(code);
+            EventType::KEY | EventType::SWITCH => {
                 if normal_value > 0.5 {
                     InputValue::Bool(true)
                 } else {
@@ -166,6 +167,25 @@ impl EvdevEvent {
                 KeyCode::BTN_THUMBR => {
                     Capability::Gamepad(Gamepad::Button(GamepadButton::RightStick))
                 }
+                // Paddles, as exposed by xpad for the Xbox Elite controllers
+                KeyCode::BTN_TRIGGER_HAPPY5 => {
+                    Capability::Gamepad(Gamepad::Button(GamepadButton::RightPaddle1))
+                }
+                KeyCode::BTN_TRIGGER_HAPPY6 => {
+                    Capability::Gamepad(Gamepad::Button(GamepadButton::RightPaddle2))
+                }
+                KeyCode::BTN_TRIGGER_HAPPY7 => {
+                    Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle1))
+                }
+                KeyCode::BTN_TRIGGER_HAPPY8 => {
+                    Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle2))
+                }
+                KeyCode::BTN_TRIGGER_HAPPY9 => {
+                    Capability::Gamepad(Gamepad::Button(GamepadButton::RightPaddle3))
+                }
+                KeyCode::BTN_TRIGGER_HAPPY10 => {
+                    Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle3))
+                }
                 // Keyboard Buttons
                 KeyCode::KEY_0 => Capability::Keyboard(Keyboard::Key0),
                 KeyCode::KEY_1 => Capability::Keyboard(Keyboard::Key1),
@@ -319,7 +339,7 @@ impl EvdevEvent {
                 KeyCode::KEY_MAIL => Capability::NotImplemented,
                 KeyCode::KEY_MEDIA => Capability::NotImplemented,
                 KeyCode::KEY_MENU => Capability::NotImplemented,
-                KeyCode::KEY_MICMUTE => Capability::NotImplemented,
+                KeyCode::KEY_MICMUTE => Capability::Keyboard(Keyboard::KeyMicMute),
                 KeyCode::KEY_MINUS => Capability::Keyboard(Keyboard::KeyMinus),
                 KeyCode::KEY_MOVE => Capability::NotImplemented,
                 KeyCode::KEY_MSDOS => Capability::NotImplemented,
@@ -449,7 +469,10 @@ impl EvdevEvent {
                 _ => Capability::NotImplemented,
             },
             EventType::MISC => Capability::NotImplemented,
-            EventType::SWITCH => Capability::NotImplemented,
+            EventType::SWITCH => match SwitchCode(code) {
+                SwitchCode::SW_DOCK => Capability::Switch(Switch::Docked),
+                _ => Capability::NotImplemented,
+            },
             EventType::LED => Capability::NotImplemented,
             EventType::SOUND => Capability::NotImplemented,
             EventType::REPEAT => Capability::NotImplemented,
@@ -555,7 +578,9 @@ fn event_type_from_capability(capability: Capability) -> Option<EventType> {
             Gamepad::Trigger(_) => Some(EventType::ABSOLUTE),
             Gamepad::Accelerometer => None,
             Gamepad::Gyro => None,
+            Gamepad::Dial(_) => None,
         },
+        Capability::Switch(_) => Some(EventType::SWITCH),
         _ => None,
     }
 }
@@ -619,6 +644,8 @@ fn event_codes_from_capability(capability: Capability) -> Vec<u16> {
                 GamepadButton::RightTrigger => vec![KeyCode::BTN_TR2.0],
                 GamepadButton::Screenshot => vec![KeyCode::KEY_RECORD.0],
                 GamepadButton::Mute => vec![],
+                GamepadButton::HeadphoneInsert => vec![],
+                GamepadButton::MicrophoneInsert => vec![],
                 GamepadButton::Select => vec![KeyCode::BTN_SELECT.0],
                 GamepadButton::South => vec![KeyCode::BTN_SOUTH.0],
                 GamepadButton::Start => vec![KeyCode::BTN_START.0],
@@ -658,6 +685,7 @@ fn event_codes_from_capability(capability: Capability) -> Vec<u16> {
             },
             Gamepad::Accelerometer => vec![],
             Gamepad::Gyro => vec![],
+            Gamepad::Dial(_) => vec![],
         },
         Capability::Mouse(mouse) => match mouse {
             Mouse::Motion => vec![RelativeAxisCode::REL_X.0, RelativeAxisCode::REL_Y.0],
@@ -784,6 +812,7 @@ fn event_codes_from_capability(capability: Capability) -> Vec<u16> {
             Keyboard::KeyMinus => vec![KeyCode::KEY_MINUS.0],
             Keyboard::KeyMuhenkan => vec![KeyCode::KEY_MUHENKAN.0],
             Keyboard::KeyMute => vec![KeyCode::KEY_MUTE.0],
+            Keyboard::KeyMicMute => vec![KeyCode::KEY_MICMUTE.0],
             Keyboard::KeyN => vec![KeyCode::KEY_N.0],
             Keyboard::KeyNextSong => vec![KeyCode::KEY_NEXTSONG.0],
             Keyboard::KeyNumlock => vec![KeyCode::KEY_NUMLOCK.0],
@@ -879,6 +908,9 @@ fn event_codes_from_capability(capability: Capability) -> Vec<u16> {
                 TouchButton::Press => vec![KeyCode::BTN_LEFT.0],
             },
         },
+        Capability::Switch(switch) => match switch {
+            Switch::Docked => vec![SwitchCode::SW_DOCK.0],
+        },
     }
 }
 
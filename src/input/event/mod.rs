@@ -1,7 +1,11 @@
 pub mod dbus;
 pub mod evdev;
 pub mod native;
+#[cfg(test)]
+pub mod native_test;
 pub mod value;
+#[cfg(test)]
+pub mod value_test;
 
 /// Events are events that flow from source devices to target devices
 #[derive(Debug, Clone)]
@@ -19,6 +19,15 @@ pub struct NativeEvent {
     source_capability: Option<Capability>,
     /// The value of the input event.
     value: InputValue,
+    /// Optional width and height in pixels of a box that translated mouse
+    /// motion should be constrained to.
+    motion_bounds: Option<(f64, f64)>,
+    /// Optional number of detents per second that a translated mouse wheel
+    /// button should keep scrolling while it is held.
+    wheel_repeat_rate: Option<f64>,
+    /// Time the event was read from the source device. Translated events
+    /// keep the time of the event they were translated from.
+    timestamp: Instant,
 }
 
 impl NativeEvent {
@@ -28,6 +37,9 @@ impl NativeEvent {
             capability,
             value,
             source_capability: None,
+            motion_bounds: None,
+            wheel_repeat_rate: None,
+            timestamp: Instant::now(),
         }
     }
 
@@ -42,6 +54,9 @@ impl NativeEvent {
             capability,
             source_capability: Some(source_capability),
             value,
+            motion_bounds: None,
+            wheel_repeat_rate: None,
+            timestamp: Instant::now(),
         }
     }
 
@@ -55,6 +70,11 @@ impl NativeEvent {
         self.value.clone()
     }
 
+    /// Sets the value of this event
+    pub fn set_value(&mut self, value: InputValue) {
+        self.value = value;
+    }
+
     /// Returns true if this event is a translated event and has a source
     /// capability defined.
     pub fn is_translated(&self) -> bool {
@@ -71,6 +91,40 @@ impl NativeEvent {
         self.source_capability.clone()
     }
 
+    /// Set the width and height, as a fraction of the screen size, of a box
+    /// that translated mouse motion should be constrained to
+    pub fn set_motion_bounds(&mut self, bounds: Option<(f64, f64)>) {
+        self.motion_bounds = bounds;
+    }
+
+    /// Returns the width and height, as a fraction of the screen size, of the
+    /// box that translated mouse motion should be constrained to
+    pub fn get_motion_bounds(&self) -> Option<(f64, f64)> {
+        self.motion_bounds
+    }
+
+    /// Set the number of detents per second that a translated mouse wheel
+    /// button should keep scrolling while it is held
+    pub fn set_wheel_repeat_rate(&mut self, rate: Option<f64>) {
+        self.wheel_repeat_rate = rate;
+    }
+
+    /// Returns the number of detents per second that a translated mouse
+    /// wheel button should keep scrolling while it is held
+    pub fn get_wheel_repeat_rate(&self) -> Option<f64> {
+        self.wheel_repeat_rate
+    }
+
+    /// Set the time the event was read from the source device
+    pub fn set_timestamp(&mut self, timestamp: Instant) {
+        self.timestamp = timestamp;
+    }
+
+    /// Returns the time the event was read from the source device
+    pub fn timestamp(&self) -> Instant {
+        self.timestamp
+    }
+
     /// Returns whether or not the event is "pressed"
     pub fn pressed(&self) -> bool {
         self.value.pressed()
@@ -116,6 +170,9 @@ impl NativeEvent {
             capability,
             value,
             source_capability: None,
+            motion_bounds: None,
+            wheel_repeat_rate: None,
+            timestamp: Instant::now(),
         }
     }
 }
@@ -129,6 +186,9 @@ impl From<EvdevEvent> for NativeEvent {
             capability,
             value,
             source_capability: None,
+            motion_bounds: None,
+            wheel_repeat_rate: None,
+            timestamp: Instant::now(),
         }
     }
 }
@@ -140,23 +200,24 @@ impl From<ScheduledNativeEvent> for NativeEvent {
 }
 
 /// A scheduled event represents an input event that should be sent sometime in
-/// the future.
+/// the future. It can optionally be repeated at a fixed interval.
 #[derive(Debug, Clone)]
 pub struct ScheduledNativeEvent {
     event: NativeEvent,
     scheduled_time: Instant,
     wait_time: Duration,
+    /// Time to wait between each repetition of the event
+    repeat_interval: Option<Duration>,
+    /// Number of times the event is still emitted. Zero repeats the event
+    /// until it is cancelled.
+    count: u32,
 }
 
 impl ScheduledNativeEvent {
     /// Create a new scheduled event with the given time to wait before being
     /// emitted.
     pub fn new(event: NativeEvent, wait_time: Duration) -> Self {
-        Self {
-            event,
-            scheduled_time: Instant::now(),
-            wait_time,
-        }
+        Self::new_with_time(event, Instant::now(), wait_time)
     }
 
     /// Create a new scheduled event with the given timestamp and wait time before
@@ -166,11 +227,66 @@ impl ScheduledNativeEvent {
             event,
             scheduled_time: timestamp,
             wait_time,
+            repeat_interval: None,
+            count: 1,
         }
     }
 
+    /// Repeat the event at the given interval until it was emitted the given
+    /// number of times. A count of zero repeats the event until it is
+    /// cancelled.
+    pub fn with_repeat(mut self, interval: Duration, count: u32) -> Self {
+        self.repeat_interval = Some(interval);
+        self.count = count;
+        self
+    }
+
+    /// Returns the event that will be emitted
+    pub fn event(&self) -> &NativeEvent {
+        &self.event
+    }
+
+    /// Returns the time to wait before the event is emitted
+    pub fn wait_time(&self) -> Duration {
+        self.wait_time
+    }
+
+    /// Returns the time to wait between each repetition of the event
+    pub fn repeat_interval(&self) -> Option<Duration> {
+        self.repeat_interval
+    }
+
+    /// Returns the number of times the event is still emitted. Zero means the
+    /// event repeats until it is cancelled.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Returns the next repetition of this event after it was emitted, if any
+    pub fn next_repetition(&self) -> Option<Self> {
+        let interval = self.repeat_interval?;
+        let count = match self.count {
+            0 => 0,
+            1 => return None,
+            count => count - 1,
+        };
+        Some(Self {
+            event: self.event.clone(),
+            scheduled_time: Instant::now(),
+            wait_time: interval,
+            repeat_interval: self.repeat_interval,
+            count,
+        })
+    }
+
     /// Returns true when the scheduled event is ready to be emitted
     pub fn is_ready(&self) -> bool {
         self.scheduled_time.elapsed() > self.wait_time
     }
+
+    /// Returns the remaining time until the scheduled event is ready to be
+    /// emitted
+    pub fn time_until_ready(&self) -> Duration {
+        self.wait_time.saturating_sub(self.scheduled_time.elapsed())
+    }
 }
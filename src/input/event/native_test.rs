@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadButton},
+    event::{
+        native::{NativeEvent, ScheduledNativeEvent},
+        value::InputValue,
+    },
+};
+
+fn south() -> NativeEvent {
+    let cap = Capability::Gamepad(Gamepad::Button(GamepadButton::South));
+    NativeEvent::new(cap, InputValue::Bool(true))
+}
+
+#[test]
+fn test_scheduled_event_once() {
+    let event = ScheduledNativeEvent::new(south(), Duration::from_millis(50));
+    assert_eq!(event.wait_time(), Duration::from_millis(50));
+    assert_eq!(event.count(), 1);
+    assert!(event.repeat_interval().is_none());
+    assert!(event.next_repetition().is_none());
+}
+
+#[test]
+fn test_scheduled_event_repeat() {
+    let interval = Duration::from_millis(100);
+    let event = ScheduledNativeEvent::new(south(), Duration::ZERO).with_repeat(interval, 3);
+
+    let second = event.next_repetition().unwrap();
+    assert_eq!(second.wait_time(), interval);
+    assert_eq!(second.count(), 2);
+    assert_eq!(
+        second.event().as_capability(),
+        event.event().as_capability()
+    );
+
+    let third = second.next_repetition().unwrap();
+    assert_eq!(third.count(), 1);
+    assert!(third.next_repetition().is_none());
+}
+
+#[test]
+fn test_scheduled_event_repeat_forever() {
+    let interval = Duration::from_millis(100);
+    let mut event = ScheduledNativeEvent::new(south(), Duration::ZERO).with_repeat(interval, 0);
+    for _ in 0..10 {
+        event = event.next_repetition().unwrap();
+        assert_eq!(event.count(), 0);
+    }
+}
@@ -1,10 +1,15 @@
 use crate::{
     config::CapabilityConfig,
-    input::capability::{Capability, Gamepad, Mouse, Touch, Touchpad},
+    input::capability::{Capability, Gamepad, Mouse, MouseButton, Touch, Touchpad},
 };
 
 use super::dbus::Action;
 
+/// Touch index used for touches warped to an absolute position by button
+/// mappings. The last index supported by touchscreen target devices is used
+/// so warped touches do not collide with touches from physical devices.
+pub const WARP_TOUCH_INDEX: u8 = 9;
+
 /// Possible errors while doing input value translation
 pub enum TranslationError {
     /// Translation not yet implemented
@@ -130,6 +135,8 @@ impl InputValue {
                                 Gamepad::Accelerometer => Err(TranslationError::NotImplemented),
                                 // Gamepad Button -> Gyro
                                 Gamepad::Gyro => Err(TranslationError::NotImplemented),
+                                // Gamepad Button -> Dial
+                                Gamepad::Dial(_) => Err(TranslationError::NotImplemented),
                             },
                             // Gamepad Button -> Mouse
                             Capability::Mouse(mouse) => match mouse {
@@ -149,10 +156,12 @@ impl InputValue {
                             // Gamepad Button -> Touchscreen
                             Capability::Touchscreen(touch) => match touch {
                                 // Gamepad Button -> Touchscreen Motion
-                                Touch::Motion => Err(TranslationError::NotImplemented),
+                                Touch::Motion => self.translate_button_to_touch(target_config),
                                 // Gamepad Button -> Touchscreen Button
                                 Touch::Button(_) => Err(TranslationError::NotImplemented),
                             },
+                            // Gamepad Button -> Switch
+                            Capability::Switch(_) => Err(TranslationError::NotImplemented),
                         }
                     }
                     // Axis -> ...
@@ -178,6 +187,8 @@ impl InputValue {
                                 Gamepad::Accelerometer => Err(TranslationError::NotImplemented),
                                 // Axis -> Gyro
                                 Gamepad::Gyro => Err(TranslationError::NotImplemented),
+                                // Axis -> Dial
+                                Gamepad::Dial(_) => Err(TranslationError::NotImplemented),
                             },
                             // Axis -> Mouse
                             Capability::Mouse(mouse) => match mouse {
@@ -185,7 +196,8 @@ impl InputValue {
                                 Mouse::Motion => self
                                     .translate_axis_to_mouse_motion(source_config, target_config),
                                 // Axis -> Mouse Button
-                                Mouse::Button(_) => self.translate_axis_to_button(source_config),
+                                Mouse::Button(_) => self
+                                    .translate_axis_to_mouse_button(source_config, target_config),
                             },
                             // Axis -> Keyboard
                             Capability::Keyboard(_) => self.translate_axis_to_button(source_config),
@@ -197,6 +209,8 @@ impl InputValue {
                             },
                             // Axis -> Touchscreen
                             Capability::Touchscreen(_) => Err(TranslationError::NotImplemented),
+                            // Axis -> Switch
+                            Capability::Switch(_) => Err(TranslationError::NotImplemented),
                         }
                     }
                     // Trigger -> ...
@@ -223,6 +237,8 @@ impl InputValue {
                             Gamepad::Accelerometer => Err(TranslationError::NotImplemented),
                             // Trigger -> Gyro
                             Gamepad::Gyro => Err(TranslationError::NotImplemented),
+                            // Trigger -> Dial
+                            Gamepad::Dial(_) => Err(TranslationError::NotImplemented),
                         },
                         // Trigger -> Mouse
                         Capability::Mouse(mouse) => match mouse {
@@ -241,11 +257,47 @@ impl InputValue {
                         },
                         // Trigger -> Touchscreen
                         Capability::Touchscreen(_) => Err(TranslationError::NotImplemented),
+                        // Trigger -> Switch
+                        Capability::Switch(_) => Err(TranslationError::NotImplemented),
                     },
                     // Accelerometer -> ...
                     Gamepad::Accelerometer => Err(TranslationError::NotImplemented),
                     // Gyro -> ...
                     Gamepad::Gyro => Err(TranslationError::NotImplemented),
+                    // Dial -> ...
+                    Gamepad::Dial(_) => match target_cap {
+                        // Dial -> None
+                        Capability::None => Ok(InputValue::None),
+                        // Dial -> NotImplemented
+                        Capability::NotImplemented => Ok(InputValue::None),
+                        // Dial -> Sync
+                        Capability::Sync => Ok(InputValue::None),
+                        // Dial -> DBus
+                        Capability::DBus(_) => Ok(self.clone()),
+                        // Dial -> Gamepad
+                        Capability::Gamepad(gamepad) => match gamepad {
+                            // Dial -> Dial
+                            Gamepad::Dial(_) => Ok(self.clone()),
+                            _ => Err(TranslationError::NotImplemented),
+                        },
+                        // Dial -> Mouse
+                        Capability::Mouse(mouse) => match mouse {
+                            // Dial -> Mouse Motion
+                            Mouse::Motion => Err(TranslationError::NotImplemented),
+                            // Dial -> Mouse Button
+                            Mouse::Button(button) => {
+                                self.translate_dial_to_wheel(button, target_config)
+                            }
+                        },
+                        // Dial -> Keyboard
+                        Capability::Keyboard(_) => Err(TranslationError::NotImplemented),
+                        // Dial -> Touchpad
+                        Capability::Touchpad(_) => Err(TranslationError::NotImplemented),
+                        // Dial -> Touchscreen
+                        Capability::Touchscreen(_) => Err(TranslationError::NotImplemented),
+                        // Dial -> Switch
+                        Capability::Switch(_) => Err(TranslationError::NotImplemented),
+                    },
                 }
             }
 
@@ -269,6 +321,7 @@ impl InputValue {
                     Gamepad::Trigger(_) => Err(TranslationError::NotImplemented),
                     Gamepad::Accelerometer => Err(TranslationError::NotImplemented),
                     Gamepad::Gyro => Err(TranslationError::NotImplemented),
+                    Gamepad::Dial(_) => Err(TranslationError::NotImplemented),
                 },
                 // Keyboard Key -> Mouse
                 Capability::Mouse(mouse) => match mouse {
@@ -280,7 +333,14 @@ impl InputValue {
                 // Keyboard Key -> Touchpad
                 Capability::Touchpad(_) => Err(TranslationError::NotImplemented),
                 // Keyboard Key -> Touchscreen
-                Capability::Touchscreen(_) => Err(TranslationError::NotImplemented),
+                Capability::Touchscreen(touch) => match touch {
+                    // Keyboard Key -> Touchscreen Motion
+                    Touch::Motion => self.translate_button_to_touch(target_config),
+                    // Keyboard Key -> Touchscreen Button
+                    Touch::Button(_) => Err(TranslationError::NotImplemented),
+                },
+                // Keyboard Key -> Switch
+                Capability::Switch(_) => Err(TranslationError::NotImplemented),
             },
 
             // Touchpad -> ...
@@ -337,6 +397,8 @@ impl InputValue {
                             // Touchpad Motion -> Touchscreen Button
                             Touch::Button(_) => Err(TranslationError::NotImplemented),
                         },
+                        // Touchpad Motion -> Switch
+                        Capability::Switch(_) => Err(TranslationError::NotImplemented),
                     },
                     Touch::Button(_) => Err(TranslationError::NotImplemented),
                 },
@@ -392,6 +454,8 @@ impl InputValue {
                             // Touchpad Motion -> Touchscreen Button
                             Touch::Button(_) => Err(TranslationError::NotImplemented),
                         },
+                        // Touchpad Motion -> Switch
+                        Capability::Switch(_) => Err(TranslationError::NotImplemented),
                     },
                     Touch::Button(_) => Err(TranslationError::NotImplemented),
                 },
@@ -447,6 +511,8 @@ impl InputValue {
                             // Touchpad Motion -> Touchscreen Button
                             Touch::Button(_) => Err(TranslationError::NotImplemented),
                         },
+                        // Touchpad Motion -> Switch
+                        Capability::Switch(_) => Err(TranslationError::NotImplemented),
                     },
                     Touch::Button(_) => Err(TranslationError::NotImplemented),
                 },
@@ -507,10 +573,42 @@ impl InputValue {
                         // Touchscreen Motion -> Touchscreen Button
                         Touch::Button(_) => Err(TranslationError::NotImplemented),
                     },
+                    // Touchscreen Motion -> Switch
+                    Capability::Switch(_) => Err(TranslationError::NotImplemented),
                 },
                 // Touchscreen Button -> ...
                 Touch::Button(_) => Err(TranslationError::NotImplemented),
             },
+
+            // Switch -> ...
+            Capability::Switch(_) => match target_cap {
+                // Switch -> None
+                Capability::None => Ok(InputValue::None),
+                // Switch -> NotImplemented
+                Capability::NotImplemented => Ok(InputValue::None),
+                // Switch -> Sync
+                Capability::Sync => Ok(InputValue::Bool(false)),
+                // Switch -> DBus
+                Capability::DBus(_) => Ok(self.clone()),
+                // Switch -> Gamepad
+                Capability::Gamepad(gamepad) => match gamepad {
+                    Gamepad::Button(_) => Ok(self.clone()),
+                    _ => Err(TranslationError::NotImplemented),
+                },
+                // Switch -> Mouse
+                Capability::Mouse(mouse) => match mouse {
+                    Mouse::Motion => Err(TranslationError::NotImplemented),
+                    Mouse::Button(_) => Ok(self.clone()),
+                },
+                // Switch -> Keyboard
+                Capability::Keyboard(_) => Ok(self.clone()),
+                // Switch -> Touchpad
+                Capability::Touchpad(_) => Err(TranslationError::NotImplemented),
+                // Switch -> Touchscreen
+                Capability::Touchscreen(_) => Err(TranslationError::NotImplemented),
+                // Switch -> Switch
+                Capability::Switch(_) => Ok(self.clone()),
+            },
         }
     }
 
@@ -726,6 +824,40 @@ impl InputValue {
         InputValue::Float(button_value)
     }
 
+    /// Translate the button value into a touch at the absolute position defined
+    /// in the given config. A dedicated touch index is used so the touch does
+    /// not interfere with touches from physical touch devices. The touch is
+    /// only pressed while the button is pressed if 'press' is configured,
+    /// otherwise only its position is updated.
+    fn translate_button_to_touch(
+        &self,
+        target_config: &CapabilityConfig,
+    ) -> Result<InputValue, TranslationError> {
+        let Some(touch_config) = target_config.touchscreen.as_ref() else {
+            return Err(TranslationError::InvalidTargetConfig(
+                "No touchscreen config to translate button to touch".to_string(),
+            ));
+        };
+        let Some(warp) = touch_config
+            .motion
+            .as_ref()
+            .and_then(|motion| motion.warp.as_ref())
+        else {
+            return Err(TranslationError::InvalidTargetConfig(
+                "No warp position defined to translate button to touch".to_string(),
+            ));
+        };
+
+        let is_touching = warp.press.unwrap_or(false) && self.pressed();
+        Ok(InputValue::Touch {
+            index: WARP_TOUCH_INDEX,
+            is_touching,
+            pressure: Some(if is_touching { 1.0 } else { 0.0 }),
+            x: Some(warp.x.clamp(0.0, 1.0)),
+            y: Some(warp.y.clamp(0.0, 1.0)),
+        })
+    }
+
     /// Translate the axis value into a button value based on the given config.
     fn translate_axis_to_button(
         &self,
@@ -818,6 +950,75 @@ impl InputValue {
         }
     }
 
+    /// Translate the axis value into a mouse button value. If the target is a
+    /// mouse wheel button with a threshold, the threshold is used to consider
+    /// the axis as 'pressed' instead of the source axis deadzone.
+    fn translate_axis_to_mouse_button(
+        &self,
+        source_config: &CapabilityConfig,
+        target_config: &CapabilityConfig,
+    ) -> Result<InputValue, TranslationError> {
+        let threshold = target_config
+            .mouse
+            .as_ref()
+            .and_then(|mouse| mouse.wheel.as_ref())
+            .and_then(|wheel| wheel.threshold);
+        let Some(threshold) = threshold else {
+            return self.translate_axis_to_button(source_config);
+        };
+
+        let mut source_config = source_config.clone();
+        if let Some(axis) = source_config
+            .gamepad
+            .as_mut()
+            .and_then(|gamepad| gamepad.axis.as_mut())
+        {
+            axis.deadzone = Some(threshold);
+        }
+        self.translate_axis_to_button(&source_config)
+    }
+
+    /// Translate the dial value into the number of detents the given mouse
+    /// wheel button should scroll. Rotation in the positive direction scrolls
+    /// up or right and rotation in the negative direction scrolls down or left.
+    fn translate_dial_to_wheel(
+        &self,
+        button: &MouseButton,
+        target_config: &CapabilityConfig,
+    ) -> Result<InputValue, TranslationError> {
+        // Get the dial rotation required to scroll one detent
+        let threshold = target_config
+            .mouse
+            .as_ref()
+            .and_then(|mouse| mouse.wheel.as_ref())
+            .and_then(|wheel| wheel.threshold)
+            .unwrap_or(1.0);
+        if threshold <= 0.0 {
+            return Err(TranslationError::InvalidTargetConfig(
+                "Wheel threshold must be greater than 0".into(),
+            ));
+        }
+
+        // Get the input value as a float
+        let InputValue::Float(value) = self else {
+            return Err(TranslationError::ImpossibleTranslation(
+                "Only float values can be translated from dial to mouse wheel".into(),
+            ));
+        };
+
+        // Only scroll if the dial was rotated in the direction of the wheel
+        let value = match button {
+            MouseButton::WheelUp | MouseButton::WheelRight => *value,
+            MouseButton::WheelDown | MouseButton::WheelLeft => -*value,
+            _ => return Err(TranslationError::NotImplemented),
+        };
+        if value <= 0.0 {
+            return Ok(InputValue::None);
+        }
+
+        Ok(InputValue::Float(value / threshold))
+    }
+
     /// Translate the trigger value into a button value based on the given config.
     fn translate_trigger_to_button(
         &self,
@@ -0,0 +1,123 @@
+use crate::{
+    config::CapabilityConfig,
+    input::{
+        capability::{Capability, Gamepad, GamepadButton, Touch},
+        event::value::{InputValue, WARP_TOUCH_INDEX},
+    },
+};
+
+fn warp_config(content: &str) -> CapabilityConfig {
+    serde_yaml::from_str(content).expect("valid capability config")
+}
+
+/// Translate the given button state into a touchscreen touch using the given
+/// target config
+fn translate_warp(pressed: bool, target_config: &CapabilityConfig) -> InputValue {
+    let source_cap = Capability::Gamepad(Gamepad::Button(GamepadButton::QuickAccess));
+    let source_config = CapabilityConfig::default();
+    let target_cap = Capability::Touchscreen(Touch::Motion);
+    let Ok(value) = InputValue::Bool(pressed).translate(
+        &source_cap,
+        &source_config,
+        &target_cap,
+        target_config,
+    ) else {
+        panic!("Failed to translate button to touch");
+    };
+    value
+}
+
+#[test]
+fn test_warp_updates_position_without_press() {
+    let config = warp_config(
+        r#"
+touchscreen:
+  motion:
+    warp:
+      x: 0.25
+      y: 0.75
+"#,
+    );
+
+    for pressed in [true, false] {
+        let value = translate_warp(pressed, &config);
+        assert!(
+            matches!(
+                value,
+                InputValue::Touch {
+                    index: WARP_TOUCH_INDEX,
+                    is_touching: false,
+                    x: Some(x),
+                    y: Some(y),
+                    ..
+                } if x == 0.25 && y == 0.75
+            ),
+            "unexpected value: {value:?}"
+        );
+    }
+}
+
+#[test]
+fn test_warp_press() {
+    let config = warp_config(
+        r#"
+touchscreen:
+  motion:
+    warp:
+      x: 1.5
+      y: 0.5
+      press: true
+"#,
+    );
+
+    // The touch is pressed on the dedicated index while the button is held
+    let value = translate_warp(true, &config);
+    assert!(
+        matches!(
+            value,
+            InputValue::Touch {
+                index: WARP_TOUCH_INDEX,
+                is_touching: true,
+                pressure: Some(pressure),
+                x: Some(x),
+                y: Some(y),
+            } if pressure == 1.0 && x == 1.0 && y == 0.5
+        ),
+        "unexpected value: {value:?}"
+    );
+
+    // The touch is lifted when the button is released
+    let value = translate_warp(false, &config);
+    assert!(
+        matches!(
+            value,
+            InputValue::Touch {
+                index: WARP_TOUCH_INDEX,
+                is_touching: false,
+                ..
+            }
+        ),
+        "unexpected value: {value:?}"
+    );
+    assert_ne!(WARP_TOUCH_INDEX, 0);
+}
+
+#[test]
+fn test_warp_requires_position() {
+    let source_cap = Capability::Gamepad(Gamepad::Button(GamepadButton::QuickAccess));
+    let target_cap = Capability::Touchscreen(Touch::Motion);
+    let config = warp_config(
+        r#"
+touchscreen:
+  motion:
+    speed_pps: 800
+"#,
+    );
+    let result = InputValue::Bool(true).translate(
+        &source_cap,
+        &CapabilityConfig::default(),
+        &target_cap,
+        &config,
+    );
+    assert!(result.is_err());
+}
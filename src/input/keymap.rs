@@ -0,0 +1,457 @@
+//! Translation of XKB keysyms into keyboard keys using the active keyboard
+//! layout. This allows device profiles to map to the character a key produces
+//! (e.g. "z") instead of the physical key (e.g. "KeyZ"), which differ on
+//! layouts like AZERTY or QWERTZ.
+use std::{collections::HashMap, error::Error, fs, path::PathBuf};
+
+use crate::config::{CapabilityConfig, ProfileMapping};
+
+use super::capability::Keyboard;
+
+/// Directory containing the XKB symbols files
+pub const XKB_SYMBOLS_PATH: &str = "/usr/share/X11/xkb/symbols";
+/// Layout used when the active layout cannot be determined or loaded
+pub const DEFAULT_LAYOUT: &str = "us";
+/// Files that may define the active keyboard layout, in order of precedence
+const LAYOUT_CONFIG_PATHS: &[&str] = &["/etc/vconsole.conf", "/etc/default/keyboard"];
+/// Maximum depth of nested includes in XKB symbols files
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Built-in US layout used if no XKB symbols files are available
+const US_SYMBOLS: &str = r#"
+default partial alphanumeric_keys
+xkb_symbols "basic" {
+    key <TLDE> { [ grave, asciitilde ] };
+    key <AE01> { [ 1, exclam ] };
+    key <AE02> { [ 2, at ] };
+    key <AE03> { [ 3, numbersign ] };
+    key <AE04> { [ 4, dollar ] };
+    key <AE05> { [ 5, percent ] };
+    key <AE06> { [ 6, asciicircum ] };
+    key <AE07> { [ 7, ampersand ] };
+    key <AE08> { [ 8, asterisk ] };
+    key <AE09> { [ 9, parenleft ] };
+    key <AE10> { [ 0, parenright ] };
+    key <AE11> { [ minus, underscore ] };
+    key <AE12> { [ equal, plus ] };
+    key <AD01> { [ q, Q ] };
+    key <AD02> { [ w, W ] };
+    key <AD03> { [ e, E ] };
+    key <AD04> { [ r, R ] };
+    key <AD05> { [ t, T ] };
+    key <AD06> { [ y, Y ] };
+    key <AD07> { [ u, U ] };
+    key <AD08> { [ i, I ] };
+    key <AD09> { [ o, O ] };
+    key <AD10> { [ p, P ] };
+    key <AD11> { [ bracketleft, braceleft ] };
+    key <AD12> { [ bracketright, braceright ] };
+    key <AC01> { [ a, A ] };
+    key <AC02> { [ s, S ] };
+    key <AC03> { [ d, D ] };
+    key <AC04> { [ f, F ] };
+    key <AC05> { [ g, G ] };
+    key <AC06> { [ h, H ] };
+    key <AC07> { [ j, J ] };
+    key <AC08> { [ k, K ] };
+    key <AC09> { [ l, L ] };
+    key <AC10> { [ semicolon, colon ] };
+    key <AC11> { [ apostrophe, quotedbl ] };
+    key <AB01> { [ z, Z ] };
+    key <AB02> { [ x, X ] };
+    key <AB03> { [ c, C ] };
+    key <AB04> { [ v, V ] };
+    key <AB05> { [ b, B ] };
+    key <AB06> { [ n, N ] };
+    key <AB07> { [ m, M ] };
+    key <AB08> { [ comma, less ] };
+    key <AB09> { [ period, greater ] };
+    key <AB10> { [ slash, question ] };
+    key <BKSL> { [ backslash, bar ] };
+    key <SPCE> { [ space ] };
+};
+"#;
+
+/// Returns the keyboard key at the given XKB key name
+fn xkb_key(name: &str) -> Option<Keyboard> {
+    let key = match name {
+        "TLDE" => Keyboard::KeyGrave,
+        "AE01" => Keyboard::Key1,
+        "AE02" => Keyboard::Key2,
+        "AE03" => Keyboard::Key3,
+        "AE04" => Keyboard::Key4,
+        "AE05" => Keyboard::Key5,
+        "AE06" => Keyboard::Key6,
+        "AE07" => Keyboard::Key7,
+        "AE08" => Keyboard::Key8,
+        "AE09" => Keyboard::Key9,
+        "AE10" => Keyboard::Key0,
+        "AE11" => Keyboard::KeyMinus,
+        "AE12" => Keyboard::KeyEqual,
+        "AD01" => Keyboard::KeyQ,
+        "AD02" => Keyboard::KeyW,
+        "AD03" => Keyboard::KeyE,
+        "AD04" => Keyboard::KeyR,
+        "AD05" => Keyboard::KeyT,
+        "AD06" => Keyboard::KeyY,
+        "AD07" => Keyboard::KeyU,
+        "AD08" => Keyboard::KeyI,
+        "AD09" => Keyboard::KeyO,
+        "AD10" => Keyboard::KeyP,
+        "AD11" => Keyboard::KeyLeftBrace,
+        "AD12" => Keyboard::KeyRightBrace,
+        "AC01" => Keyboard::KeyA,
+        "AC02" => Keyboard::KeyS,
+        "AC03" => Keyboard::KeyD,
+        "AC04" => Keyboard::KeyF,
+        "AC05" => Keyboard::KeyG,
+        "AC06" => Keyboard::KeyH,
+        "AC07" => Keyboard::KeyJ,
+        "AC08" => Keyboard::KeyK,
+        "AC09" => Keyboard::KeyL,
+        "AC10" => Keyboard::KeySemicolon,
+        "AC11" => Keyboard::KeyApostrophe,
+        "AB01" => Keyboard::KeyZ,
+        "AB02" => Keyboard::KeyX,
+        "AB03" => Keyboard::KeyC,
+        "AB04" => Keyboard::KeyV,
+        "AB05" => Keyboard::KeyB,
+        "AB06" => Keyboard::KeyN,
+        "AB07" => Keyboard::KeyM,
+        "AB08" => Keyboard::KeyComma,
+        "AB09" => Keyboard::KeyDot,
+        "AB10" => Keyboard::KeySlash,
+        "BKSL" => Keyboard::KeyBackslash,
+        "LSGT" => Keyboard::Key102nd,
+        "SPCE" => Keyboard::KeySpace,
+        _ => return None,
+    };
+    Some(key)
+}
+
+/// The key and shift level that produce a keysym
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeysymEntry {
+    pub key: Keyboard,
+    /// Shift level starting at 1. Level 2 is reached with Shift, level 3 with
+    /// AltGr, and level 4 with Shift and AltGr.
+    pub level: usize,
+}
+
+impl KeysymEntry {
+    /// Returns the modifier keys that must be held to reach the level
+    pub fn modifiers(&self) -> Vec<Keyboard> {
+        match self.level {
+            2 => vec![Keyboard::KeyLeftShift],
+            3 => vec![Keyboard::KeyRightAlt],
+            4 => vec![Keyboard::KeyLeftShift, Keyboard::KeyRightAlt],
+            _ => vec![],
+        }
+    }
+
+    /// Returns the modifier keys followed by the key itself
+    pub fn keys(&self) -> Vec<Keyboard> {
+        let mut keys = self.modifiers();
+        keys.push(self.key.clone());
+        keys
+    }
+}
+
+/// Table to look up which keys produce a keysym with a keyboard layout
+#[derive(Debug, Clone, Default)]
+pub struct KeysymTable {
+    keysyms: HashMap<String, KeysymEntry>,
+}
+
+impl KeysymTable {
+    /// Load the table of the active keyboard layout. Falls back to the US
+    /// layout if the active layout cannot be loaded.
+    pub fn load_active() -> Self {
+        let (layout, variant) = active_layout();
+        match Self::load(&layout, variant.as_deref()) {
+            Ok(table) => table,
+            Err(e) => {
+                log::warn!("Failed to load keyboard layout '{layout}': {e:?}");
+                Self::us()
+            }
+        }
+    }
+
+    /// Load the table of the given XKB layout and optional variant from the
+    /// system XKB symbols files
+    pub fn load(layout: &str, variant: Option<&str>) -> Result<Self, Box<dyn Error>> {
+        let reader = |file: &str| {
+            let path = PathBuf::from(XKB_SYMBOLS_PATH).join(file);
+            fs::read_to_string(path).ok()
+        };
+        Self::from_symbols(layout, variant, &reader)
+    }
+
+    /// Create a table of the built-in US layout
+    pub fn us() -> Self {
+        let reader = |_: &str| Some(US_SYMBOLS.to_string());
+        Self::from_symbols(DEFAULT_LAYOUT, None, &reader).unwrap_or_default()
+    }
+
+    /// Create a table of the given layout and optional variant. The given
+    /// reader returns the contents of an XKB symbols file by its name.
+    pub fn from_symbols(
+        layout: &str,
+        variant: Option<&str>,
+        reader: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut keys = Vec::new();
+        load_section(layout, variant, reader, &mut keys, 0)?;
+
+        // Prefer the lowest level that produces a keysym
+        let mut keysyms: HashMap<String, KeysymEntry> = HashMap::new();
+        for (name, levels) in keys {
+            let Some(key) = xkb_key(&name) else {
+                continue;
+            };
+            for (i, keysym) in levels.into_iter().enumerate() {
+                let level = i + 1;
+                if keysym == "NoSymbol" || keysym == "VoidSymbol" {
+                    continue;
+                }
+                if keysyms.get(&keysym).is_some_and(|e| e.level <= level) {
+                    continue;
+                }
+                let key = key.clone();
+                keysyms.insert(keysym, KeysymEntry { key, level });
+            }
+        }
+
+        Ok(Self { keysyms })
+    }
+
+    /// Returns the key and level that produce the given keysym
+    pub fn get(&self, keysym: &str) -> Option<&KeysymEntry> {
+        self.keysyms.get(keysym)
+    }
+
+    /// Replace all keysym target events of the given profile mapping with the
+    /// keyboard keys that produce the keysym, preceded by any modifier keys.
+    pub fn resolve_mapping(&self, mapping: &ProfileMapping) -> ProfileMapping {
+        let mut mapping = mapping.clone();
+        let mut target_events = Vec::with_capacity(mapping.target_events.len());
+        for event in mapping.target_events {
+            let Some(keysym) = event.keysym.as_ref() else {
+                target_events.push(event);
+                continue;
+            };
+            let Some(entry) = self.get(keysym) else {
+                log::error!(
+                    "Keysym '{keysym}' in mapping '{}' is not on the keyboard layout",
+                    mapping.name
+                );
+                continue;
+            };
+            for key in entry.keys() {
+                target_events.push(CapabilityConfig {
+                    keyboard: Some(key.to_string()),
+                    ..Default::default()
+                });
+            }
+        }
+        mapping.target_events = target_events;
+        mapping
+    }
+}
+
+/// Returns the active XKB layout and variant. Only the first layout is used if
+/// multiple layouts are configured.
+pub fn active_layout() -> (String, Option<String>) {
+    let first = |value: &str| {
+        let value = value.split(',').next().unwrap_or_default().trim();
+        (!value.is_empty()).then(|| value.to_string())
+    };
+
+    if let Ok(layout) = std::env::var("XKB_DEFAULT_LAYOUT") {
+        if let Some(layout) = first(&layout) {
+            let variant = std::env::var("XKB_DEFAULT_VARIANT").ok();
+            return (layout, variant.as_deref().and_then(first));
+        }
+    }
+
+    for path in LAYOUT_CONFIG_PATHS {
+        let Ok(content) = fs::read_to_string(path) else {
+            continue;
+        };
+        let (layout, variant) = parse_layout_config(&content);
+        if let Some(layout) = layout.as_deref().and_then(first) {
+            return (layout, variant.as_deref().and_then(first));
+        }
+    }
+
+    (DEFAULT_LAYOUT.to_string(), None)
+}
+
+/// Parse the XKBLAYOUT and XKBVARIANT values of a shell style config file
+/// like /etc/vconsole.conf
+pub fn parse_layout_config(content: &str) -> (Option<String>, Option<String>) {
+    let mut layout = None;
+    let mut variant = None;
+    for line in content.lines() {
+        let Some((key, value)) = line.trim().split_once('=') else {
+            continue;
+        };
+        let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+        match key.trim() {
+            "XKBLAYOUT" => layout = Some(value.to_string()),
+            "XKBVARIANT" => variant = Some(value.to_string()),
+            _ => (),
+        }
+    }
+    (layout, variant)
+}
+
+/// Load the keys of the given section of an XKB symbols file into the given
+/// list of key names and their keysyms. Keys defined later override keys
+/// defined earlier, including keys from included sections.
+fn load_section(
+    file: &str,
+    section: Option<&str>,
+    reader: &dyn Fn(&str) -> Option<String>,
+    keys: &mut Vec<(String, Vec<String>)>,
+    depth: usize,
+) -> Result<(), Box<dyn Error>> {
+    if depth > MAX_INCLUDE_DEPTH {
+        return Err(format!("Too many nested includes in '{file}'").into());
+    }
+    let Some(content) = reader(file) else {
+        return Err(format!("Unable to read XKB symbols file '{file}'").into());
+    };
+    let content = strip_comments(&content);
+    let Some(body) = find_section(&content, section) else {
+        let name = section.unwrap_or("default");
+        return Err(format!("Unable to find section '{name}' in '{file}'").into());
+    };
+
+    for statement in body.split(';') {
+        // Include statements are not terminated by a semicolon, so they are
+        // at the start of the following statement.
+        let mut statement = statement.trim();
+        while let Some(include) = statement.strip_prefix("include") {
+            let (line, rest) = include.split_once('\n').unwrap_or((include, ""));
+            statement = rest.trim();
+            let include = line.trim().trim_matches('"');
+            for part in include.split('+').filter(|part| !part.is_empty()) {
+                let (file, section) = split_include(part);
+                // Includes of other files are optional, e.g. level3 switches
+                if let Err(e) = load_section(file, section, reader, keys, depth + 1) {
+                    log::debug!("Skipping include '{part}': {e}");
+                }
+            }
+        }
+
+        let Some(rest) = statement.strip_prefix("key") else {
+            continue;
+        };
+        let Some((name, levels)) = parse_key(rest) else {
+            continue;
+        };
+        match keys.iter_mut().find(|(key, _)| *key == name) {
+            Some(entry) => entry.1 = levels,
+            None => keys.push((name, levels)),
+        }
+    }
+
+    Ok(())
+}
+
+/// Split an include like "latin(type4)" into its file and section
+fn split_include(include: &str) -> (&str, Option<&str>) {
+    match include.split_once('(') {
+        Some((file, section)) => (file, Some(section.trim_end_matches(')'))),
+        None => (include, None),
+    }
+}
+
+/// Parse the key name and keysyms of the first group of a key statement like
+/// `<AD01> { [ a, A ] }` or `<AD01> { type[Group1]="FOUR_LEVEL", symbols[Group1]= [ a, A ] }`
+fn parse_key(statement: &str) -> Option<(String, Vec<String>)> {
+    let start = statement.find('<')?;
+    let end = statement.find('>')?;
+    let name = statement.get(start + 1..end)?.to_string();
+
+    // Skip group indices like `symbols[Group1]= [ a, A ]`
+    let mut rest = &statement[end..];
+    loop {
+        let start = rest.find('[')?;
+        let end = rest[start..].find(']')? + start;
+        let inner = rest[start + 1..end].trim();
+        rest = &rest[end + 1..];
+        if inner.starts_with("Group") {
+            continue;
+        }
+        let levels = inner
+            .split(',')
+            .map(|keysym| keysym.trim().to_string())
+            .collect();
+        return Some((name, levels));
+    }
+}
+
+/// Remove all comments from the given XKB file contents
+fn strip_comments(content: &str) -> String {
+    content
+        .lines()
+        .map(|line| match line.find("//") {
+            Some(i) => &line[..i],
+            None => line,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Returns the body of the given `xkb_symbols` section, or the section marked
+/// as default (or the first section) if no section is given.
+fn find_section<'a>(content: &'a str, section: Option<&str>) -> Option<&'a str> {
+    let mut first = None;
+    let mut offset = 0;
+    while let Some(i) = content[offset..].find("xkb_symbols") {
+        let start = offset + i;
+        let header_end = start + content[start..].find('{')?;
+        let header = &content[start..header_end];
+        let name = header.split('"').nth(1).unwrap_or_default();
+        let is_default = content[offset..start]
+            .lines()
+            .last()
+            .is_some_and(|line| line.contains("default"));
+
+        // Find the matching closing brace of the section
+        let mut depth = 0;
+        let mut end = None;
+        for (j, c) in content[header_end..].char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        end = Some(header_end + j);
+                        break;
+                    }
+                }
+                _ => (),
+            }
+        }
+        let end = end?;
+        let body = &content[header_end + 1..end];
+
+        match section {
+            Some(section) if section == name => return Some(body),
+            None if is_default => return Some(body),
+            _ => (),
+        }
+        if first.is_none() {
+            first = Some(body);
+        }
+        offset = end;
+    }
+
+    if section.is_none() {
+        return first;
+    }
+    None
+}
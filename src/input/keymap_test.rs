@@ -0,0 +1,138 @@
+use crate::{
+    config::DeviceProfile,
+    input::{
+        capability::Keyboard,
+        keymap::{parse_layout_config, KeysymTable},
+    },
+};
+
+const LATIN: &str = r#"
+// Common Latin alphabet layout
+
+default partial
+xkb_symbols "basic" {
+    key <AE01>	{ [         1,     exclam,  onesuperior,   exclamdown ]	};
+    key <AD01>	{ [         q,          Q,           at,  Greek_OMEGA ]	};
+    key <AD02>	{ [         w,          W,        U017F,      section ]	};
+    key <AC01>	{ [         a,          A,           ae,           AE ]	};
+    key <AB01>	{ [         z,          Z, guillemotleft,        less ]	};
+};
+"#;
+
+const FR: &str = r#"
+default partial alphanumeric_keys
+xkb_symbols "basic" {
+
+    include "latin"
+
+    name[Group1]="French";
+
+    key <AE01>	{ [ ampersand,          1,  onesuperior,   exclamdown ]	};
+    key <AD01>	{ [         a,          A,           ae,           AE ]	};
+    key <AD02>	{ [         z,          Z, guillemotleft,        less ]	};
+    key <AC01>	{ [         q,          Q,           at,  Greek_OMEGA ]	};
+    key <AB01>	{ [         w,          W,      lstroke,      Lstroke ]	};
+
+    include "level3(ralt_switch)"
+};
+
+partial alphanumeric_keys
+xkb_symbols "nodeadkeys" {
+    include "fr(basic)"
+
+    key <AE01>	{ [ ampersand,          1,  asciitilde,   exclamdown ]	};
+};
+"#;
+
+fn reader(file: &str) -> Option<String> {
+    match file {
+        "latin" => Some(LATIN.to_string()),
+        "fr" => Some(FR.to_string()),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_keysym_table_azerty() {
+    let table = KeysymTable::from_symbols("fr", None, &reader).unwrap();
+
+    let entry = table.get("z").unwrap();
+    assert_eq!(entry.key, Keyboard::KeyW);
+    assert_eq!(entry.keys(), vec![Keyboard::KeyW]);
+
+    let entry = table.get("Z").unwrap();
+    assert_eq!(entry.keys(), vec![Keyboard::KeyLeftShift, Keyboard::KeyW]);
+
+    let entry = table.get("1").unwrap();
+    assert_eq!(entry.keys(), vec![Keyboard::KeyLeftShift, Keyboard::Key1]);
+
+    let entry = table.get("at").unwrap();
+    assert_eq!(entry.keys(), vec![Keyboard::KeyRightAlt, Keyboard::KeyA]);
+
+    // Keys from the included layout that are not overridden are kept
+    let entry = table.get("Greek_OMEGA").unwrap();
+    assert_eq!(entry.level, 4);
+    assert_eq!(entry.key, Keyboard::KeyA);
+}
+
+#[test]
+fn test_keysym_table_variant() {
+    let table = KeysymTable::from_symbols("fr", Some("nodeadkeys"), &reader).unwrap();
+    let entry = table.get("asciitilde").unwrap();
+    assert_eq!(entry.keys(), vec![Keyboard::KeyRightAlt, Keyboard::Key1]);
+    assert_eq!(table.get("a").unwrap().key, Keyboard::KeyQ);
+
+    assert!(KeysymTable::from_symbols("fr", Some("missing"), &reader).is_err());
+    assert!(KeysymTable::from_symbols("de", None, &reader).is_err());
+}
+
+#[test]
+fn test_keysym_table_us() {
+    let table = KeysymTable::us();
+    assert_eq!(table.get("z").unwrap().key, Keyboard::KeyZ);
+    assert_eq!(table.get("space").unwrap().key, Keyboard::KeySpace);
+    let entry = table.get("question").unwrap();
+    assert_eq!(
+        entry.keys(),
+        vec![Keyboard::KeyLeftShift, Keyboard::KeySlash]
+    );
+}
+
+#[test]
+fn test_resolve_mapping() {
+    let profile = r#"
+version: 1
+kind: DeviceProfile
+name: Keysym Test
+mapping:
+  - name: South
+    source_event:
+      gamepad:
+        button: South
+    target_events:
+      - keyboard: KeyLeftCtrl
+      - keysym: Z
+"#;
+    let profile = DeviceProfile::from_yaml(profile.to_string()).unwrap();
+    let table = KeysymTable::from_symbols("fr", None, &reader).unwrap();
+    let mapping = table.resolve_mapping(&profile.mapping[0]);
+
+    let keys: Vec<_> = mapping
+        .target_events
+        .iter()
+        .map(|event| event.keyboard.clone().unwrap())
+        .collect();
+    assert_eq!(keys, vec!["KeyLeftCtrl", "KeyLeftShift", "KeyW"]);
+}
+
+#[test]
+fn test_parse_layout_config() {
+    let content = "KEYMAP=fr\nXKBLAYOUT=\"fr,us\"\nXKBVARIANT=nodeadkeys\n";
+    let (layout, variant) = parse_layout_config(content);
+    assert_eq!(layout.as_deref(), Some("fr,us"));
+    assert_eq!(variant.as_deref(), Some("nodeadkeys"));
+
+    let (layout, variant) = parse_layout_config("KEYMAP=us\n");
+    assert!(layout.is_none());
+    assert!(variant.is_none());
+}
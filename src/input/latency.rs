@@ -0,0 +1,99 @@
+//! Global low latency mode, which trades power for input latency. While
+//! enabled, source and target devices poll at a higher rate, idle target
+//! devices are not suspended, the translation pool translates each event as
+//! soon as it arrives instead of in batches, and device threads are moved to
+//! the realtime scheduler where permitted. Device threads check the mode each
+//! time they are polled, so changes apply without recreating devices.
+use std::{
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::Duration,
+};
+
+use crate::input::scheduling;
+
+/// Fastest rate at which devices without a pollable file descriptor are
+/// polled in low latency mode
+pub const LOW_LATENCY_POLL_RATE: Duration = Duration::from_millis(1);
+
+/// Whether low latency mode is enabled
+static ENABLED: AtomicBool = AtomicBool::new(false);
+/// Incremented each time low latency mode is enabled or disabled
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Returns true if low latency mode is enabled
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Enable or disable low latency mode. Returns true if the mode changed.
+pub fn set_enabled(enabled: bool) -> bool {
+    if ENABLED.swap(enabled, Ordering::Relaxed) == enabled {
+        return false;
+    }
+    GENERATION.fetch_add(1, Ordering::Relaxed);
+    if enabled {
+        log::info!("Low latency mode enabled");
+    } else {
+        log::info!("Low latency mode disabled");
+    }
+    true
+}
+
+/// Returns the number of times low latency mode was enabled or disabled
+pub fn generation() -> u64 {
+    GENERATION.load(Ordering::Relaxed)
+}
+
+/// Returns the rate a device with the given default poll rate should be
+/// polled at in the current mode
+pub fn poll_rate(default: Duration) -> Duration {
+    if is_enabled() {
+        return default.min(LOW_LATENCY_POLL_RATE);
+    }
+    default
+}
+
+/// Returns the maximum number of queued events that may be handled as a
+/// single batch in the current mode. Events are not batched in low latency
+/// mode.
+pub fn batch_size(default: usize) -> usize {
+    if is_enabled() {
+        return 1;
+    }
+    default
+}
+
+/// Applies the scheduling options of the current mode to a device thread
+/// and re-applies them when the mode changes
+#[derive(Debug)]
+pub struct ModeWatcher {
+    generation: u64,
+}
+
+impl ModeWatcher {
+    /// Create a new watcher and apply the scheduling options of the current
+    /// mode to the calling thread. This should be called at the start of a
+    /// blocking device thread.
+    pub fn new() -> Self {
+        let generation = generation();
+        scheduling::configure_current_thread();
+        Self { generation }
+    }
+
+    /// Re-apply the scheduling options to the calling thread if the mode
+    /// changed since the last call
+    pub fn update(&mut self) {
+        let generation = generation();
+        if generation == self.generation {
+            return;
+        }
+        self.generation = generation;
+        scheduling::configure_current_thread();
+    }
+}
+
+impl Default for ModeWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,30 @@
+use std::time::Duration;
+
+use crate::input::latency::{
+    batch_size, generation, is_enabled, poll_rate, set_enabled, LOW_LATENCY_POLL_RATE,
+};
+
+#[test]
+fn test_low_latency_mode() {
+    // Low latency mode is global, so all checks are done in a single test
+    let slow = Duration::from_millis(8);
+    let fast = Duration::from_micros(500);
+    assert!(!is_enabled());
+    assert_eq!(poll_rate(slow), slow);
+    assert_eq!(batch_size(256), 256);
+
+    let start = generation();
+    assert!(set_enabled(true));
+    assert!(!set_enabled(true));
+    assert_eq!(generation(), start + 1);
+    assert!(is_enabled());
+    assert_eq!(poll_rate(slow), LOW_LATENCY_POLL_RATE);
+    assert_eq!(poll_rate(fast), fast);
+    assert_eq!(batch_size(256), 1);
+
+    // Defaults are restored when disabled
+    assert!(set_enabled(false));
+    assert_eq!(generation(), start + 2);
+    assert_eq!(poll_rate(slow), slow);
+    assert_eq!(batch_size(256), 256);
+}
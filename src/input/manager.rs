@@ -6,42 +6,67 @@ use std::time::Duration;
 
 use ::procfs::CpuInfo;
 use ::udev::MonitorBuilder;
+use futures_util::StreamExt;
 use mio::{Events, Interest, Poll, Token};
 use thiserror::Error;
 use tokio::sync::mpsc;
 use tokio::task;
 use tokio::task::JoinHandle;
 use zbus::fdo::ManagedObjects;
+use zbus::proxy::CacheProperties;
 use zbus::zvariant::ObjectPath;
+use zbus::zvariant::OwnedObjectPath;
 use zbus::Connection;
 
 use crate::bluetooth::device1::Device1Proxy;
+use crate::config::lint::lint_device_configs;
+use crate::config::load_device_config_files;
 use crate::config::path::get_capability_maps_paths;
-use crate::config::path::get_devices_paths;
 use crate::config::CapabilityMap;
 use crate::config::CompositeDeviceConfig;
+use crate::config::GlobalConfig;
 use crate::config::SourceDevice;
+use crate::config::TargetDeviceConfig;
+use crate::config::TargetDeviceOptions;
 use crate::constants::BUS_PREFIX;
 use crate::constants::BUS_SOURCES_PREFIX;
 use crate::constants::BUS_TARGETS_PREFIX;
 use crate::dbus::interface::composite_device::CompositeDeviceInterface;
+use crate::dbus::interface::force_feedback::ForceFeedbackInterface;
 use crate::dbus::interface::manager::ManagerInterface;
 use crate::dbus::interface::source::evdev::SourceEventDeviceInterface;
+use crate::dbus::interface::source::firmware::SourceFirmwareInterface;
 use crate::dbus::interface::source::hidraw::SourceHIDRawInterface;
 use crate::dbus::interface::source::iio_imu::SourceIioImuInterface;
+use crate::dbus::interface::source::msi_claw::SourceMsiClawInterface;
 use crate::dbus::interface::source::udev::SourceUdevDeviceInterface;
+use crate::dbus::logind::LoginManagerProxy;
+use crate::dbus::logind::SeatProxy;
+use crate::dbus::logind::SessionProxy;
 use crate::dmi::data::DMIData;
 use crate::dmi::get_cpu_info;
 use crate::dmi::get_dmi_data;
+use crate::input::composite_device::identity;
+use crate::input::composite_device::input_mode::mode_transitions;
+use crate::input::composite_device::input_mode::select_target_devices;
+use crate::input::composite_device::input_mode::InputMode;
 use crate::input::composite_device::CompositeDevice;
 use crate::input::source::evdev;
+use crate::input::source::external;
 use crate::input::source::hidraw;
 use crate::input::source::iio;
+use crate::input::source::network;
+use crate::input::target::activity;
+use crate::input::target::preflight;
+use crate::input::target::preflight::PreflightReport;
 use crate::input::target::TargetDevice;
 use crate::input::target::TargetDeviceTypeId;
+use crate::input::uinput;
 use crate::udev;
+use crate::udev::authorize;
 use crate::udev::device::AttributeGetter;
 use crate::udev::device::UdevDevice;
+use crate::udev::seat;
 
 use super::composite_device::client::CompositeDeviceClient;
 use super::target::client::TargetDeviceClient;
@@ -57,8 +82,12 @@ const BUFFER_SIZE: usize = 20480;
 pub enum ManagerError {
     #[error("failed to create target device")]
     CreateTargetDeviceFailed(String),
+    #[error("target device preflight checks failed")]
+    TargetDevicePreflightFailed(PreflightReport),
     #[error("failed to attach target device")]
     AttachTargetDeviceFailed(String),
+    #[error("failed to create composite device")]
+    CreateCompositeDeviceFailed(String),
 }
 
 /// Manager commands define all the different ways to interact with [Manager]
@@ -75,8 +104,14 @@ pub enum ManagerCommand {
     CreateCompositeDevice {
         config: CompositeDeviceConfig,
     },
+    CreateCompositeDeviceFromSources {
+        sources: Vec<String>,
+        name: String,
+        sender: mpsc::Sender<Result<String, ManagerError>>,
+    },
     CreateTargetDevice {
         kind: String,
+        options: Option<TargetDeviceOptions>,
         sender: mpsc::Sender<Result<String, ManagerError>>,
     },
     StopTargetDevice {
@@ -95,12 +130,41 @@ pub enum ManagerCommand {
         sender: mpsc::Sender<bool>,
     },
     SetManageAllDevices(bool),
+    SetInhibited(bool),
+    GetActiveApp {
+        sender: mpsc::Sender<Option<String>>,
+    },
+    SetActiveApp(Option<String>),
+    GetInputMode {
+        sender: mpsc::Sender<Option<InputMode>>,
+    },
+    SetInputMode(InputMode),
     SystemSleep {
         sender: mpsc::Sender<()>,
     },
     SystemWake {
         sender: mpsc::Sender<()>,
     },
+    UsbDeviceDeferred {
+        sysname: String,
+        syspath: String,
+    },
+    UsbDeviceRemoved {
+        sysname: String,
+    },
+    DockDevicesChanged,
+    GetTargetSupportDegraded {
+        sender: mpsc::Sender<bool>,
+    },
+    UinputRecovered,
+    GetCompositeDevicePath {
+        id: String,
+        sender: mpsc::Sender<Option<String>>,
+    },
+    ActiveSessionChanged {
+        seat: String,
+        session: String,
+    },
 }
 
 /// Manages input devices
@@ -138,6 +202,10 @@ pub struct Manager {
     /// Map of source devices being used by a [CompositeDevice].
     /// E.g. {"evdev://event0": "/org/shadowblip/InputPlumber/CompositeDevice0"}
     source_devices_used: HashMap<String, String>,
+    /// Map of source devices being used by a [CompositeDevice] to their
+    /// transport independent serial and bus type.
+    /// E.g. {"hidraw://hidraw0": ("a0ab51c1d2e3", 0x05)}
+    source_device_transports: HashMap<String, (String, u16)>,
     /// Mapping of DBus path to its corresponding [CompositeDevice] handle
     /// E.g. {"/org/shadowblip/InputPlumber/CompositeDevice0": <Handle>}
     composite_devices: HashMap<String, CompositeDeviceClient>,
@@ -151,12 +219,40 @@ pub struct Manager {
     /// Mapping of DBus path to its corresponding [CompositeDeviceConfig]
     /// E.g. {"/org/shadowblip/InputPlumber/CompositeDevice0": <CompositeDeviceConfig>}
     used_configs: HashMap<String, CompositeDeviceConfig>,
+    /// Mapping of stable composite device identifiers to their DBus path
+    /// E.g. {"3f2b8e1c-5d0a-8c4e-9b71-0a6d2e4f8c13": "/org/shadowblip/InputPlumber/CompositeDevice0"}
+    composite_device_uuids: HashMap<String, String>,
+    /// Map of HID devices claimed by a [CompositeDevice] with the
+    /// 'hide_sibling_devices' option enabled.
+    /// E.g. {"/sys/devices/.../0005:054C:0CE6.0001": "/org/shadowblip/InputPlumber/CompositeDevice0"}
+    claimed_hid_devices: HashMap<String, String>,
+    /// Map of sibling input devices hidden on behalf of a [CompositeDevice].
+    /// E.g. {"/org/shadowblip/InputPlumber/CompositeDevice0": Vec<"/dev/input/event5">}
+    hidden_sibling_devices: HashMap<String, Vec<String>>,
     /// Mapping of target devices to their respective handles
     /// E.g. {"/org/shadowblip/InputPlumber/devices/target/dbus0": <Handle>}
     target_devices: HashMap<String, TargetDeviceClient>,
+    /// Mapping of target devices to the unique identifier they were created
+    /// with. E.g. {"/org/shadowblip/InputPlumber/devices/target/gamepad0": "00:11:22:33:44:55"}
+    target_device_identities: HashMap<String, String>,
     /// Defines whether or not InputPlumber should try to automatically manage all
     /// input devices that have a [CompositeDeviceConfig] definition
     manage_all_devices: bool,
+    /// Defines whether or not all composite devices are blocked from emitting
+    /// events to target devices
+    inhibited: bool,
+    /// Identifier of the application currently in focus, as reported by an
+    /// external integration (e.g. a Steam app id or a desktop file name)
+    active_app: Option<String>,
+    /// Global input mode that composite devices are switched to, if any
+    input_mode: Option<InputMode>,
+    /// Active logind session of each seat
+    active_sessions: HashMap<String, String>,
+    /// Options used to recover uinput when it is unavailable
+    uinput_options: uinput::RecoveryOptions,
+    /// Whether or not uinput target devices are currently unavailable. While
+    /// degraded, uinput is recovered in the background.
+    target_support_degraded: bool,
 }
 
 impl Manager {
@@ -188,11 +284,22 @@ impl Manager {
             source_devices: HashMap::new(),
             source_device_dbus_paths: HashMap::new(),
             source_devices_used: HashMap::new(),
+            source_device_transports: HashMap::new(),
             target_devices: HashMap::new(),
+            target_device_identities: HashMap::new(),
             used_configs: HashMap::new(),
+            composite_device_uuids: HashMap::new(),
+            claimed_hid_devices: HashMap::new(),
+            hidden_sibling_devices: HashMap::new(),
             composite_device_sources: HashMap::new(),
             composite_device_targets: HashMap::new(),
             manage_all_devices: false,
+            inhibited: false,
+            active_app: None,
+            input_mode: None,
+            active_sessions: HashMap::new(),
+            uinput_options: uinput::RecoveryOptions::from_config(&GlobalConfig::load()),
+            target_support_degraded: false,
         }
     }
 
@@ -223,13 +330,33 @@ impl Manager {
             });
         }
 
+        // Warn about any device configs that can mis-match devices
+        self.lint_device_configs().await;
+
+        // De-authorize USB devices that should be claimed before they are readable
+        self.setup_usb_authorization().await;
+
+        // Make sure uinput is available before any target devices are created
+        if !uinput::is_available() {
+            uinput::run_hook(&self.uinput_options).await;
+            if !uinput::is_available() {
+                self.start_uinput_recovery();
+            }
+        }
+
         log::debug!("Starting input manager task...");
 
         let _ = tokio::join!(
             Self::discover_all_devices(&cmd_tx_all_devices),
             Self::watch_iio_devices(self.tx.clone()),
+            Self::watch_usb_devices(self.tx.clone()),
+            Self::watch_dock_devices(self.tx.clone()),
+            Self::watch_seats(self.dbus.clone(), self.tx.clone()),
+            Self::report_user_activity(self.dbus.clone()),
             Self::watch_devnodes(self.tx.clone(), &mut watcher_rx),
             Self::listen_on_dbus(dbus_for_listen_on_dbus, self.tx.clone()),
+            Self::listen_for_external_drivers(self.tx.clone()),
+            Self::listen_for_network_input(self.tx.clone()),
             self.events_loop()
         );
 
@@ -247,15 +374,36 @@ impl Manager {
                         log::error!("Error creating composite device: {:?}", e);
                     }
                 }
+                ManagerCommand::CreateCompositeDeviceFromSources {
+                    sources,
+                    name,
+                    sender,
+                } => {
+                    log::debug!("Got request to create composite device from: {sources:?}");
+                    let result = self
+                        .create_composite_device_from_sources(name, sources)
+                        .await
+                        .map_err(|e| ManagerError::CreateCompositeDeviceFailed(e.to_string()));
+                    if let Err(e) = sender.send(result).await {
+                        log::error!("Failed to send response: {e:?}");
+                    }
+                }
                 ManagerCommand::CompositeDeviceStopped(path) => {
                     if let Err(e) = self.on_composite_device_stopped(path).await {
                         log::error!("Error handling stopped composite device: {:?}", e);
                     }
                 }
-                ManagerCommand::CreateTargetDevice { kind, sender } => {
+                ManagerCommand::CreateTargetDevice {
+                    kind,
+                    options,
+                    sender,
+                } => {
                     // Create the target device
                     log::debug!("Got request to create target device: {kind}");
-                    let device = match self.create_and_start_target_device(kind.as_str()).await {
+                    let result = self
+                        .create_and_start_target_device(kind.as_str(), options.as_ref())
+                        .await;
+                    let device = match result {
                         Ok(device) => device,
                         Err(err) => {
                             if let Err(e) = sender.send(Err(err)).await {
@@ -310,8 +458,15 @@ impl Manager {
                     // Send the attach command to the composite device
                     let mut targets = HashMap::new();
                     targets.insert(target_path.clone(), target.clone());
-                    if let Err(e) = device.attach_target_devices(targets).await {
-                        log::error!("Failed to send attach command: {e:?}");
+                    let response = match device.attach_target_devices(targets).await {
+                        Ok(_) => Ok(()),
+                        Err(e) => {
+                            log::error!("Failed to send attach command: {e:?}");
+                            Err(ManagerError::AttachTargetDeviceFailed(e.to_string()))
+                        }
+                    };
+                    if let Err(e) = sender.send(response).await {
+                        log::error!("Failed to send response: {e:?}");
                     }
                     log::debug!("Finished handling attach request for: {target_path}");
                 }
@@ -329,18 +484,27 @@ impl Manager {
                 ManagerCommand::TargetDeviceStopped { path } => {
                     log::debug!("Target device stopped: {path}");
                     self.target_devices.remove(&path);
+                    self.target_device_identities.remove(&path);
                 }
                 ManagerCommand::DeviceAdded { device } => {
                     let dev_name = device.name();
                     let dev_sysname = device.sysname();
+                    let device_id = device.get_id();
 
                     if let Err(e) = self.on_device_added(device).await {
-                        log::error!("Error adding device '{dev_name} ({dev_sysname})': {e}");
+                        log::error!(
+                            device_id = device_id.as_str();
+                            "Error adding device '{dev_name} ({dev_sysname})': {e}"
+                        );
                     }
                 }
                 ManagerCommand::DeviceRemoved { device } => {
+                    let device_id = device.get_id();
                     if let Err(e) = self.on_device_removed(device).await {
-                        log::error!("Error removing device: {e}");
+                        log::error!(
+                            device_id = device_id.as_str();
+                            "Error removing device {device_id}: {e}"
+                        );
                     }
                 }
                 ManagerCommand::SetManageAllDevices(manage_all_devices) => {
@@ -380,11 +544,146 @@ impl Manager {
                         }
                     }
                 }
+                ManagerCommand::UsbDeviceDeferred { sysname, syspath } => {
+                    log::info!("Authorizing deferred USB device: {sysname}");
+                    task::spawn(async move {
+                        let result = authorize::authorize_usb_device(&sysname, &syspath).await;
+                        if let Err(e) = result {
+                            log::error!("Failed to authorize USB device {sysname}: {e:?}");
+                        }
+                    });
+                }
+                ManagerCommand::UsbDeviceRemoved { sysname } => {
+                    task::spawn(async move {
+                        if let Err(e) = authorize::remove_usb_device(&sysname).await {
+                            log::error!(
+                                "Failed to remove hide rule for USB device {sysname}: {e:?}"
+                            );
+                        }
+                    });
+                }
+                ManagerCommand::ActiveSessionChanged {
+                    seat: seat_id,
+                    session,
+                } => {
+                    if session.is_empty() {
+                        log::debug!("No active session on {seat_id}");
+                        continue;
+                    }
+                    let previous = self
+                        .active_sessions
+                        .insert(seat_id.clone(), session.clone());
+                    if previous.as_ref() == Some(&session) {
+                        continue;
+                    }
+                    log::info!("Active session on {seat_id} changed to {session}");
+
+                    // Hand off the target devices to the new session if the
+                    // session was switched (e.g. fast user switching)
+                    let composite_devices = if previous.is_some() {
+                        self.composite_devices.clone()
+                    } else {
+                        HashMap::new()
+                    };
+                    task::spawn(async move {
+                        if let Err(e) = seat::set_active_seat(&seat_id).await {
+                            log::error!("Failed to assign target devices to {seat_id}: {e:?}");
+                        }
+                        for (path, device) in composite_devices.iter() {
+                            if let Err(e) = device.recreate_target_devices().await {
+                                log::error!("Failed to re-create target devices of {path}: {e:?}");
+                            }
+                        }
+                    });
+                }
+                ManagerCommand::GetTargetSupportDegraded { sender } => {
+                    if let Err(e) = sender.send(self.target_support_degraded).await {
+                        log::error!("Failed to send response: {e:?}");
+                    }
+                }
+                ManagerCommand::UinputRecovered => {
+                    log::info!("Target device support recovered");
+                    self.target_support_degraded = false;
+                    self.signal_target_support_degraded_changed();
+                    if let Err(e) = self.discover_unmanaged_devices() {
+                        log::error!("Failed to discover unmanaged devices: {e:?}");
+                    }
+                }
+                ManagerCommand::GetCompositeDevicePath { id, sender } => {
+                    let path = self.get_composite_device_path(id.as_str());
+                    if let Err(e) = sender.send(path).await {
+                        log::error!("Failed to send response: {e:?}");
+                    }
+                }
                 ManagerCommand::GetManageAllDevices { sender } => {
                     if let Err(e) = sender.send(self.manage_all_devices).await {
                         log::error!("Failed to send response: {e:?}");
                     }
                 }
+                ManagerCommand::DockDevicesChanged => {
+                    for (path, device) in self.composite_devices.iter() {
+                        if let Err(e) = device.update_docked().await {
+                            log::error!("Failed to update dock state of {path}: {e:?}");
+                        }
+                    }
+                }
+                ManagerCommand::SetInhibited(inhibited) => {
+                    log::info!("Setting inhibition of all devices to: {inhibited}");
+                    self.inhibited = inhibited;
+                    for (path, device) in self.composite_devices.iter() {
+                        if let Err(e) = device.set_inhibited(inhibited).await {
+                            log::error!("Failed to set inhibited on {path}: {e:?}");
+                        }
+                    }
+                }
+                ManagerCommand::GetActiveApp { sender } => {
+                    if let Err(e) = sender.send(self.active_app.clone()).await {
+                        log::error!("Failed to send response: {e:?}");
+                    }
+                }
+                ManagerCommand::SetActiveApp(app) => {
+                    log::info!("Setting active application to: {app:?}");
+                    if self.active_app == app {
+                        continue;
+                    }
+                    self.active_app = app;
+                    for (path, device) in self.composite_devices.iter() {
+                        if let Err(e) = device.set_active_app(self.active_app.clone()).await {
+                            log::error!("Failed to set active application on {path}: {e:?}");
+                        }
+                    }
+                }
+                ManagerCommand::GetInputMode { sender } => {
+                    if let Err(e) = sender.send(self.input_mode).await {
+                        log::error!("Failed to send response: {e:?}");
+                    }
+                }
+                ManagerCommand::SetInputMode(mode) => {
+                    log::info!("Setting input mode to: {mode}");
+                    self.input_mode = Some(mode);
+
+                    // Apply the configuration of the mode to each composite
+                    // device that defines one. This is done in a separate
+                    // task as composite devices may send commands to the
+                    // manager while applying their configuration.
+                    let mut devices = Vec::new();
+                    for (path, mode_config) in mode_transitions(mode, &self.used_configs) {
+                        let Some(device) = self.composite_devices.get(&path) else {
+                            continue;
+                        };
+                        devices.push((path, device.clone(), mode_config));
+                    }
+                    tokio::task::spawn(async move {
+                        for (path, device, config) in devices {
+                            let result = device
+                                .apply_configuration(config.profile, config.target_devices)
+                                .await;
+                            if let Err(e) = result {
+                                log::error!("Failed to set input mode {mode} on {path}: {e:?}");
+                            }
+                        }
+                    });
+                }
                 ManagerCommand::SystemSleep { sender } => {
                     log::info!("Preparing for system suspend");
 
@@ -410,6 +709,9 @@ impl Manager {
                 ManagerCommand::SystemWake { sender } => {
                     log::info!("Preparing for system resume");
 
+                    // Reapply IMU settings that may have been reset during suspend
+                    iio::settings::reapply_all();
+
                     // Call the resume handler on each composite device and wait
                     // for a response.
                     let composite_devices = self.composite_devices.clone();
@@ -445,6 +747,75 @@ impl Manager {
         Ok(())
     }
 
+    /// Create and start a [CompositeDevice] that combines the source devices
+    /// at the given device paths. Returns the DBus path of the created
+    /// composite device.
+    async fn create_composite_device_from_sources(
+        &mut self,
+        name: String,
+        sources: Vec<String>,
+    ) -> Result<String, Box<dyn Error>> {
+        let config = CompositeDeviceConfig::from_source_paths(name, &sources)?;
+
+        // Look up the source devices and ensure they are not already in use
+        let mut devices = Vec::with_capacity(sources.len());
+        for path in sources.iter() {
+            let path = std::path::Path::new(path);
+            let (Some(base_path), Some(name)) = (path.parent(), path.file_name()) else {
+                return Err(format!("Invalid source device path: {path:?}").into());
+            };
+            let base_path = base_path.to_string_lossy();
+            let name = name.to_string_lossy().to_string();
+            let subsystem = UdevDevice::from_devnode(&base_path, &name).subsystem();
+            let device = ::udev::Device::from_subsystem_sysname(subsystem, name)
+                .map_err(|e| format!("Unable to find source device {path:?}: {e}"))?;
+            let device = UdevDevice::from(device);
+            let id = device.get_id();
+            if let Some(used_by) = self.source_devices_used.get(&id) {
+                return Err(format!("Source device {path:?} already in use by {used_by}").into());
+            }
+            devices.push(device);
+        }
+
+        // Create the composite device with the first source device
+        let mut devices = devices.into_iter();
+        let Some(device) = devices.next() else {
+            return Err("No source devices given".into());
+        };
+        let Some(source_device) = config.get_matching_device(&device) else {
+            return Err(format!("Source device {} does not match config", device.devnode()).into());
+        };
+        let composite = self
+            .create_composite_device_from_config(&config, device)
+            .await?;
+        let composite_path = composite.dbus_path().to_string();
+        let target_devices = config.target_devices.clone();
+        self.start_composite_device(composite, config.clone(), target_devices, source_device)
+            .await?;
+
+        // Add the remaining source devices to the composite device
+        for device in devices {
+            let Some(source_device) = config.get_matching_device(&device) else {
+                continue;
+            };
+            let Some(client) = self.composite_devices.get(&composite_path) else {
+                return Err(format!("Composite device {composite_path} not found").into());
+            };
+            let id = device.get_id();
+            self.add_device_to_composite_device(device, client).await?;
+            self.source_devices_used
+                .insert(id.clone(), composite_path.clone());
+            self.composite_device_sources
+                .entry(composite_path.clone())
+                .or_default()
+                .push(source_device.clone());
+            self.source_devices.insert(id, source_device);
+        }
+        log::info!("Created composite device {composite_path} from sources: {sources:?}");
+
+        Ok(composite_path)
+    }
+
     /// Create a [CompositeDevice] from the given configuration
     async fn create_composite_device_from_config(
         &mut self,
@@ -463,12 +834,14 @@ impl Manager {
         // Create a composite device to manage these devices
         log::info!("Found matching source device for: {:?}", config.name);
         let config = config.clone();
+        let uuid = self.next_composite_uuid(&config, &device)?;
         let device = CompositeDevice::new(
             self.dbus.clone(),
             self.tx.clone(),
             config,
             device,
             self.next_composite_dbus_path()?,
+            uuid,
             capability_map,
         )?;
 
@@ -486,15 +859,40 @@ impl Manager {
         Ok(device)
     }
 
-    /// Create target input device to emulate based on the given device type.
-    async fn create_target_device(&mut self, kind: &str) -> Result<TargetDevice, Box<dyn Error>> {
+    /// Create target input device to emulate based on the given device type
+    /// and optional identity overrides.
+    async fn create_target_device(
+        &mut self,
+        kind: &str,
+        options: Option<&TargetDeviceOptions>,
+    ) -> Result<TargetDevice, Box<dyn Error>> {
         log::trace!("Creating target device: {kind}");
+
+        // Check that the target device can be created to report why it cannot
+        let report = preflight::check(kind, options, &self.target_device_identities);
+        if !report.is_ok() {
+            report.log();
+            if report.failures.iter().any(|f| f.is_uinput_unavailable()) {
+                self.start_uinput_recovery();
+            }
+            return Err(report.into());
+        }
+
         let Ok(target_id) = TargetDeviceTypeId::try_from(kind) else {
             return Err("Invalid target device ID".to_string().into());
         };
 
         // Create the target device to emulate based on the kind
-        let device = TargetDevice::from_type_id(target_id, self.dbus.clone())?;
+        let requires_uinput = target_id.uses_uinput(options);
+        let device = match TargetDevice::from_type_id(target_id, self.dbus.clone(), options) {
+            Ok(device) => device,
+            Err(e) => {
+                if requires_uinput && !uinput::is_available() {
+                    self.start_uinput_recovery();
+                }
+                return Err(e);
+            }
+        };
 
         Ok(device)
     }
@@ -551,11 +949,16 @@ impl Manager {
     async fn create_and_start_target_device(
         &mut self,
         kind: &str,
+        options: Option<&TargetDeviceOptions>,
     ) -> Result<HashMap<String, TargetDeviceClient>, ManagerError> {
         // Create the target device
-        let device = match self.create_target_device(kind).await {
+        let device = match self.create_target_device(kind, options).await {
             Ok(device) => device,
             Err(e) => {
+                let e = match e.downcast::<PreflightReport>() {
+                    Ok(report) => return Err(ManagerError::TargetDevicePreflightFailed(*report)),
+                    Err(e) => e,
+                };
                 let err = format!("Error creating target device: {e:?}");
                 log::error!("{err}");
                 return Err(ManagerError::CreateTargetDeviceFailed(err));
@@ -571,25 +974,73 @@ impl Manager {
                 return Err(ManagerError::CreateTargetDeviceFailed(err));
             }
         };
+        self.add_target_device_identities(&paths, options);
 
         Ok(paths)
     }
 
+    /// Keep track of the unique identifier the given started target devices
+    /// were created with, so duplicates can be detected.
+    fn add_target_device_identities(
+        &mut self,
+        target_devices: &HashMap<String, TargetDeviceClient>,
+        options: Option<&TargetDeviceOptions>,
+    ) {
+        let Some(uniq) = options.and_then(|options| options.uniq.as_ref()) else {
+            return;
+        };
+        for path in target_devices.keys() {
+            self.target_device_identities
+                .insert(path.clone(), uniq.clone());
+        }
+    }
+
     /// Starts the given [CompositeDevice]
     async fn start_composite_device(
         &mut self,
         mut device: CompositeDevice,
         config: CompositeDeviceConfig,
-        target_types: Option<Vec<String>>,
+        target_types: Option<Vec<TargetDeviceConfig>>,
         source_device: SourceDevice,
     ) -> Result<JoinHandle<()>, Box<dyn Error>> {
+        // Use the target devices of the active input mode if the config
+        // defines them
+        let mode_config = self
+            .input_mode
+            .and_then(|mode| mode.get_config(&config))
+            .cloned();
+        let target_types = select_target_devices(mode_config.as_ref(), target_types);
+
+        // Don't take ownership of source devices if their target devices
+        // cannot be created. The source devices will be considered again
+        // once uinput has recovered.
+        let passthrough = config
+            .options
+            .as_ref()
+            .and_then(|options| options.passthrough_fallback)
+            .unwrap_or(false);
+        let requires_uinput = passthrough
+            || target_types.iter().flatten().any(|target| {
+                TargetDeviceTypeId::try_from(target.kind())
+                    .map(|id| id.uses_uinput(target.options()))
+                    .unwrap_or(false)
+            });
+        if requires_uinput && !uinput::is_available() {
+            self.start_uinput_recovery();
+            return Err(format!("{} is unavailable", uinput::UINPUT_PATH).into());
+        }
+
         // Keep track of the source devices that this composite device is
         // using.
         let source_device_ids = device.get_source_devices_used();
         let composite_path = String::from(device.dbus_path());
-        log::debug!(
-            "Starting CompositeDevice at {composite_path} with the following sources: {source_device_ids:?}"
+        let uuid = device.uuid().to_string();
+        log::info!(
+            dbus_path = composite_path.as_str(), config_name = config.name.as_str(), uuid = uuid.as_str();
+            "Starting CompositeDevice at {composite_path} ({uuid}) with the following sources: {source_device_ids:?}"
         );
+        self.composite_device_uuids
+            .insert(uuid, composite_path.clone());
         for id in source_device_ids {
             self.source_devices_used
                 .insert(id.clone(), composite_path.clone());
@@ -611,12 +1062,43 @@ impl Manager {
         // Get a handle to the device
         let client = device.client();
 
+        // Newly added devices should respect any active inhibition
+        if self.inhibited {
+            client.set_inhibited(true).await?;
+        }
+        if self.active_app.is_some() {
+            client.set_active_app(self.active_app.clone()).await?;
+        }
+        if let Some(mode_config) = mode_config {
+            // The profile is loaded once the composite device is running
+            let client = client.clone();
+            tokio::task::spawn(async move {
+                if let Err(e) = client.load_profile_path(mode_config.profile).await {
+                    log::error!("Failed to load profile of input mode: {e:?}");
+                }
+            });
+        }
+
         // Keep track of target devices that this composite device is using
         let mut target_device_paths = Vec::new();
 
+        // Create the passthrough keyboard and mouse before the composite device
+        // runs, so basic input is available as soon as source devices are
+        // grabbed.
+        if passthrough {
+            log::debug!("Creating passthrough target devices for {composite_path}");
+            let mut passthrough_devices = Vec::new();
+            for kind in ["keyboard", "mouse"] {
+                passthrough_devices.push(self.create_target_device(kind, None).await?);
+            }
+            let passthrough_devices = self.start_target_devices(passthrough_devices).await?;
+            target_device_paths.extend(passthrough_devices.keys().cloned());
+            device.set_passthrough_devices(passthrough_devices);
+        }
+
         // Create a DBus target device
         log::debug!("Creating target devices for {composite_path}");
-        let dbus_device = self.create_target_device("dbus").await?;
+        let dbus_device = self.create_target_device("dbus", None).await?;
         let dbus_devices = self.start_target_devices(vec![dbus_device]).await?;
         let dbus_paths = dbus_devices.keys();
         for dbus_path in dbus_paths {
@@ -627,14 +1109,21 @@ impl Manager {
         // Create target devices based on the configuration
         let mut target_devices = Vec::new();
         if let Some(target_devices_config) = target_types {
-            for kind in target_devices_config {
-                let device = self.create_target_device(kind.as_str()).await?;
-                target_devices.push(device);
+            for target_config in target_devices_config {
+                let device = self
+                    .create_target_device(target_config.kind(), target_config.options())
+                    .await?;
+                target_devices.push((device, target_config.options().cloned()));
             }
         }
 
         // Start the target input devices
-        let targets = self.start_target_devices(target_devices).await?;
+        let mut targets = HashMap::new();
+        for (device, options) in target_devices {
+            let started = self.start_target_devices(vec![device]).await?;
+            self.add_target_device_identities(&started, options.as_ref());
+            targets.extend(started);
+        }
         let target_paths = targets.keys();
         for target_path in target_paths {
             target_device_paths.push(target_path.clone());
@@ -655,9 +1144,15 @@ impl Manager {
         let tx = self.tx.clone();
         Ok(tokio::spawn(async move {
             if let Err(e) = device.run(targets).await {
-                log::error!("Error running {composite_path}: {}", e.to_string());
+                log::error!(
+                    dbus_path = composite_path.as_str();
+                    "Error running {composite_path}: {}", e.to_string()
+                );
             }
-            log::debug!("Composite device stopped running: {composite_path}");
+            log::debug!(
+                dbus_path = composite_path.as_str();
+                "Composite device stopped running: {composite_path}"
+            );
             if let Err(e) = tx
                 .send(ManagerCommand::CompositeDeviceStopped(
                     composite_path.clone(),
@@ -674,7 +1169,7 @@ impl Manager {
 
     /// Called when a composite device stops running
     async fn on_composite_device_stopped(&mut self, path: String) -> Result<(), Box<dyn Error>> {
-        log::debug!("Removing composite device: {}", path);
+        log::info!(dbus_path = path.as_str(); "Removing composite device: {}", path);
 
         // Remove the DBus interface
         let dbus_path = ObjectPath::from_string_unchecked(path.clone());
@@ -690,6 +1185,13 @@ impl Manager {
             } else {
                 log::debug!("Stopped dbus interface: {dbus_path}");
             }
+            let result = conn
+                .object_server()
+                .remove::<ForceFeedbackInterface, ObjectPath>(dbus_path.clone())
+                .await;
+            if let Err(e) = result {
+                log::error!("Failed to remove force feedback dbus interface {dbus_path}: {e:?}");
+            }
         });
 
         // Find any source devices that were in use by the composite device
@@ -702,6 +1204,23 @@ impl Manager {
         }
         for id in to_remove {
             self.source_devices_used.remove::<String>(&id);
+            self.source_device_transports.remove::<String>(&id);
+        }
+
+        self.composite_device_uuids
+            .retain(|_, composite_path| *composite_path != path);
+
+        // Release any HID devices claimed by the composite device and unhide
+        // their sibling input devices
+        self.claimed_hid_devices
+            .retain(|_, composite_path| *composite_path != path);
+        if let Some(siblings) = self.hidden_sibling_devices.remove(&path) {
+            for devnode in siblings {
+                log::debug!("Unhiding sibling device: {devnode}");
+                if let Err(e) = udev::unhide_device(devnode.clone()).await {
+                    log::debug!("Unable to unhide sibling device {devnode}: {e:?}");
+                }
+            }
         }
 
         // Find any target devices that were in use by the composite device
@@ -731,6 +1250,21 @@ impl Manager {
         id: String,
         device: UdevDevice,
     ) -> Result<(), Box<dyn Error>> {
+        // Check if this device is a controller that is already part of a
+        // composite device but connected over a different transport (e.g. a
+        // controller that was plugged in over USB while connected over
+        // bluetooth). It is added to the same composite device instead of
+        // creating a duplicate that would emit the same inputs twice.
+        if let Some((composite_device, source_device)) = self.find_transport_match(&id, &device) {
+            log::info!(
+                device_id = id.as_str(), dbus_path = composite_device.as_str();
+                "Source device {id} is connected over a different transport, adding it to existing composite device: {composite_device:?}"
+            );
+            self.attach_source_device(id, device, composite_device, source_device)
+                .await?;
+            return Ok(());
+        }
+
         // Check all existing composite devices to see if this device is part of
         // their config
         'start: for composite_device in self.composite_devices.keys() {
@@ -815,26 +1349,14 @@ impl Manager {
                 }
             }
 
-            log::info!("Found missing {} device, adding source device {id} to existing composite device: {composite_device:?}", device.subsystem());
-            let Some(client) = self.composite_devices.get(composite_device.as_str()) else {
-                log::error!("No existing composite device found for key {composite_device:?}");
-                continue;
-            };
-
-            self.add_device_to_composite_device(device, client).await?;
-            self.source_devices_used
-                .insert(id.clone(), composite_device.clone());
+            log::info!(
+                device_id = id.as_str(), dbus_path = composite_device.as_str();
+                "Found missing {} device, adding source device {id} to existing composite device: {composite_device:?}",
+                device.subsystem()
+            );
             let composite_id = composite_device.clone();
-            if !self.composite_device_sources.contains_key(&composite_id) {
-                self.composite_device_sources
-                    .insert(composite_id.clone(), Vec::new());
-            }
-            let sources = self
-                .composite_device_sources
-                .get_mut(&composite_id)
-                .unwrap();
-            sources.push(source_device.clone());
-            self.source_devices.insert(id, source_device.clone());
+            self.attach_source_device(id, device, composite_id, source_device)
+                .await?;
 
             return Ok(());
         }
@@ -877,12 +1399,15 @@ impl Manager {
                     }
                 }
                 log::info!(
+                    device_id = id.as_str(), config_name = config.name.as_str();
                     "Found a matching {} device {id}, creating CompositeDevice",
                     device.subsystem()
                 );
+                let sibling = device.clone();
                 let dev = self
                     .create_composite_device_from_config(&config, device)
                     .await?;
+                self.add_source_device_transport(&id, &sibling);
 
                 // Get the target input devices from the config
                 let target_devices_config = config.target_devices.clone();
@@ -895,6 +1420,7 @@ impl Manager {
                     source_device.clone(),
                 )
                 .await?;
+                self.claim_hid_device(&id, &sibling).await;
 
                 return Ok(());
             }
@@ -906,6 +1432,184 @@ impl Manager {
         Ok(())
     }
 
+    /// Add the given source device to the given running [CompositeDevice]
+    async fn attach_source_device(
+        &mut self,
+        id: String,
+        device: UdevDevice,
+        composite_device: String,
+        source_device: SourceDevice,
+    ) -> Result<(), Box<dyn Error>> {
+        let Some(client) = self.composite_devices.get(composite_device.as_str()) else {
+            return Err(format!("CompostiteDevice {composite_device} not found").into());
+        };
+
+        let sibling = device.clone();
+        self.add_device_to_composite_device(device, client).await?;
+        self.source_devices_used
+            .insert(id.clone(), composite_device.clone());
+        self.add_source_device_transport(&id, &sibling);
+        if !self
+            .composite_device_sources
+            .contains_key(&composite_device)
+        {
+            self.composite_device_sources
+                .insert(composite_device.clone(), Vec::new());
+        }
+        let sources = self
+            .composite_device_sources
+            .get_mut(&composite_device)
+            .unwrap();
+        sources.push(source_device.clone());
+        self.source_devices.insert(id.clone(), source_device);
+        self.claim_hid_device(&id, &sibling).await;
+
+        Ok(())
+    }
+
+    /// Keep track of the serial and bus type of the given source device to
+    /// recognize it when it connects over a different transport
+    fn add_source_device_transport(&mut self, id: &str, device: &UdevDevice) {
+        let Some(serial) = identity::transport_serial(device) else {
+            return;
+        };
+        self.source_device_transports
+            .insert(id.to_string(), (serial, device.id_bustype()));
+    }
+
+    /// Returns the path of the running [CompositeDevice] that uses the same
+    /// physical device as the given source device over a different transport,
+    /// along with the matching source config. Devices without a serial cannot
+    /// be told apart and never match.
+    fn find_transport_match(
+        &self,
+        id: &str,
+        device: &UdevDevice,
+    ) -> Option<(String, SourceDevice)> {
+        let serial = identity::transport_serial(device)?;
+        let bus_type = device.id_bustype();
+        let (source_id, _) = self.source_device_transports.iter().find(
+            |(source_id, (other_serial, other_bus_type))| {
+                source_id.as_str() != id && *other_serial == serial && *other_bus_type != bus_type
+            },
+        )?;
+        let composite_device = self.source_devices_used.get(source_id)?;
+        let config = self.used_configs.get(composite_device)?;
+
+        // Only add the device if it is part of the config of the composite
+        // device
+        let source_device = config.get_matching_device(device)?;
+        if source_device.ignore.unwrap_or(false) {
+            return None;
+        }
+
+        Some((composite_device.clone(), source_device))
+    }
+
+    /// Called after a source device was added to a [CompositeDevice]. If the
+    /// config of the composite device has 'hide_sibling_devices' enabled,
+    /// the HID device the source device stems from is claimed and any other
+    /// input devices created by the kernel driver for the same HID device
+    /// that are not part of the config are hidden and released from other
+    /// composite devices.
+    async fn claim_hid_device(&mut self, id: &str, device: &UdevDevice) {
+        let Some(composite_path) = self.source_devices_used.get(id).cloned() else {
+            return;
+        };
+        let Some(config) = self.used_configs.get(&composite_path) else {
+            return;
+        };
+        let hide_siblings = config
+            .options
+            .as_ref()
+            .and_then(|options| options.hide_sibling_devices)
+            .unwrap_or(false);
+        if !hide_siblings {
+            return;
+        }
+        let Some(hid_parent) = device.hid_parent_syspath() else {
+            return;
+        };
+        if self.claimed_hid_devices.contains_key(&hid_parent) {
+            return;
+        }
+        log::debug!("Composite device {composite_path} claimed HID device {hid_parent}");
+        self.claimed_hid_devices
+            .insert(hid_parent.clone(), composite_path.clone());
+
+        // Mask sibling input devices that were added before the HID device
+        // was claimed.
+        let devices = match udev::discover_devices("input") {
+            Ok(devices) => devices,
+            Err(e) => {
+                log::warn!("Unable to discover sibling devices of {hid_parent}: {e:?}");
+                return;
+            }
+        };
+        for device in devices {
+            let device = UdevDevice::from(device);
+            if device.hid_parent_syspath().as_ref() != Some(&hid_parent) {
+                continue;
+            }
+            let sibling_id = device.get_id();
+            if let Some(used_by) = self.source_devices_used.get(&sibling_id) {
+                if *used_by == composite_path {
+                    continue;
+                }
+                log::info!(
+                    "Releasing duplicate device {sibling_id} from composite device {used_by}"
+                );
+                if let Err(e) = self
+                    .on_source_device_removed(device.clone(), sibling_id.clone())
+                    .await
+                {
+                    log::warn!("Failed to release duplicate device {sibling_id}: {e:?}");
+                }
+            }
+            self.mask_sibling_device(&sibling_id, &device).await;
+        }
+    }
+
+    /// Hide the given input device if the HID device it stems from has been
+    /// claimed by a [CompositeDevice] whose config does not include it.
+    /// Returns true if the device was masked and should not be used.
+    async fn mask_sibling_device(&mut self, id: &str, device: &UdevDevice) -> bool {
+        if device.subsystem() != "input" || device.devnode().is_empty() {
+            return false;
+        }
+        let Some(hid_parent) = device.hid_parent_syspath() else {
+            return false;
+        };
+        let Some(composite_path) = self.claimed_hid_devices.get(&hid_parent).cloned() else {
+            return false;
+        };
+        let Some(config) = self.used_configs.get(&composite_path) else {
+            return false;
+        };
+        if config.get_matching_device(device).is_some() {
+            return false;
+        }
+
+        let devnode = device.devnode();
+        let siblings = self
+            .hidden_sibling_devices
+            .entry(composite_path.clone())
+            .or_default();
+        if siblings.contains(&devnode) {
+            return true;
+        }
+        log::info!(
+            "Masking duplicate device {id} ({}) of HID device claimed by {composite_path}",
+            device.name()
+        );
+        if let Err(e) = udev::hide_device(devnode.clone()).await {
+            log::warn!("Unable to hide duplicate device {id}: {e:?}");
+        }
+        siblings.push(devnode);
+
+        true
+    }
+
     /// Called when any source device is removed
     async fn on_source_device_removed(
         &mut self,
@@ -942,6 +1646,7 @@ impl Manager {
         self.source_devices.remove(&id);
         self.source_device_dbus_paths.remove(&id);
         self.source_devices_used.remove(&id);
+        self.source_device_transports.remove(&id);
 
         Ok(())
     }
@@ -959,14 +1664,17 @@ impl Manager {
         let sysname = sys_name.clone();
         let dev = device.clone();
 
-        log::debug!("Device added: {dev_name} ({dev_sysname}): {dev_path}");
+        // Get the device id
+        let id = device.get_id();
+
+        log::info!(
+            device_id = id.as_str();
+            "Device added: {dev_name} ({dev_sysname}): {dev_path}"
+        );
 
         // Get the device subsystem
         let subsystem = device.subsystem();
 
-        // Get the device id
-        let id = device.get_id();
-
         // Create a DBus interface depending on the device subsystem
         match subsystem.as_str() {
             "input" => {
@@ -975,6 +1683,13 @@ impl Manager {
                     return Ok(());
                 }
 
+                // Legacy joystick nodes are only used for devices that have
+                // no event node, which is always preferred.
+                if device.is_joydev() && device.has_event_sibling() {
+                    log::trace!("Joydev device discarded in favor of its event node: {dev_name} ({dev_sysname}) at {dev_path}");
+                    return Ok(());
+                }
+
                 log::debug!("Event device added: {dev_name} ({dev_sysname})");
 
                 // Create a DBus interface for the event device
@@ -1034,6 +1749,12 @@ impl Manager {
                     log::trace!("{dev_name} ({dev_sysname}) is a real device - {dev_path}");
                 }
 
+                // Skip duplicate devices created by the kernel driver of a HID
+                // device that was already claimed by a composite device.
+                if self.mask_sibling_device(&id, &device).await {
+                    return Ok(());
+                }
+
                 // Signal that a source device was added
                 log::debug!("Spawning task to add source device: {id}");
                 self.on_source_device_added(id.clone(), device).await?;
@@ -1064,10 +1785,28 @@ impl Manager {
                     if let Err(e) = result {
                         log::error!("Error creating source udev dbus interface: {e:?}");
                     }
-                    let result = SourceHIDRawInterface::listen_on_dbus(conn, sysname, dev).await;
+                    let result = SourceHIDRawInterface::listen_on_dbus(
+                        conn.clone(),
+                        sysname.clone(),
+                        dev.clone(),
+                    )
+                    .await;
                     if let Err(e) = result {
                         log::error!("Error creating source evdev dbus interface: {e:?}");
                     }
+                    let result = SourceMsiClawInterface::listen_on_dbus(
+                        conn.clone(),
+                        sysname.clone(),
+                        dev.clone(),
+                    )
+                    .await;
+                    if let Err(e) = result {
+                        log::error!("Error creating source MSI Claw dbus interface: {e:?}");
+                    }
+                    let result = SourceFirmwareInterface::listen_on_dbus(conn, sysname, dev).await;
+                    if let Err(e) = result {
+                        log::warn!("Error creating source firmware dbus interface: {e:?}");
+                    }
                     log::debug!("Finished adding source device on dbus");
                 });
 
@@ -1188,6 +1927,15 @@ impl Manager {
                 log::debug!("Finished adding event device {id}");
             }
 
+            "external" => {
+                log::debug!("External device added: {dev_name} ({dev_sysname})");
+
+                // Signal that a source device was added
+                log::debug!("Spawing task to add source device: {id}");
+                self.on_source_device_added(id.clone(), device).await?;
+                log::debug!("Finished adding external device {id}");
+            }
+
             _ => {
                 return Err(format!("Device subsystem not supported: {subsystem:?}").into());
             }
@@ -1200,11 +1948,23 @@ impl Manager {
         let dev_name = device.name();
         let sys_name = device.sysname();
         let subsystem = device.subsystem();
-        log::debug!("Device removed: {dev_name} ({sys_name})");
+        let device_id = device.get_id();
+        log::info!(
+            device_id = device_id.as_str();
+            "Device removed: {dev_name} ({sys_name})"
+        );
+        if subsystem == "iio" {
+            iio::settings::remove(&sys_name);
+        }
         let path = ObjectPath::from_string_unchecked(format!("{BUS_SOURCES_PREFIX}/{sys_name}"));
         log::debug!("Device dbus path: {path}");
         let conn = self.dbus.clone();
+        // External devices do not have any source dbus interfaces
+        let has_dbus_interfaces = subsystem != "external";
         task::spawn(async move {
+            if !has_dbus_interfaces {
+                return;
+            }
             log::debug!("Stopping dbus interfaces: {path}");
 
             // Stop generic interfaces
@@ -1226,6 +1986,16 @@ impl Manager {
                         .await
                 }
                 "hidraw" => {
+                    // Only known controllers have a firmware interface
+                    let _ = conn
+                        .object_server()
+                        .remove::<SourceFirmwareInterface, ObjectPath>(path.clone())
+                        .await;
+                    // Only MSI Claw controllers have a mode interface
+                    let _ = conn
+                        .object_server()
+                        .remove::<SourceMsiClawInterface, ObjectPath>(path.clone())
+                        .await;
                     conn.object_server()
                         .remove::<SourceHIDRawInterface, ObjectPath>(path.clone())
                         .await
@@ -1288,6 +2058,33 @@ impl Manager {
         Err(Box::from("No available dbus path left"))
     }
 
+    /// Returns the next available stable identifier for a composite device
+    /// created from the given config and source device
+    fn next_composite_uuid(
+        &self,
+        config: &CompositeDeviceConfig,
+        device: &UdevDevice,
+    ) -> Result<String, Box<dyn Error>> {
+        let serials = vec![identity::canonical_serial(device)];
+        for i in 0u32.. {
+            let uuid = identity::composite_uuid(config.name.as_str(), &serials, i);
+            if !self.composite_device_uuids.contains_key(&uuid) {
+                return Ok(uuid);
+            }
+        }
+
+        Err(Box::from("No available composite device uuid left"))
+    }
+
+    /// Returns the DBus path of the composite device with the given stable
+    /// identifier or DBus path
+    fn get_composite_device_path(&self, id: &str) -> Option<String> {
+        if self.composite_devices.contains_key(id) {
+            return Some(id.to_string());
+        }
+        self.composite_device_uuids.get(&id.to_lowercase()).cloned()
+    }
+
     /// Watch for IIO device events
     fn watch_iio_devices(
         cmd_tx: mpsc::Sender<ManagerCommand>,
@@ -1338,6 +2135,155 @@ impl Manager {
         })
     }
 
+    /// Watch for USB devices whose authorization was deferred by the USB
+    /// authorization udev rule
+    fn watch_usb_devices(
+        cmd_tx: mpsc::Sender<ManagerCommand>,
+    ) -> tokio::task::JoinHandle<Result<(), Box<dyn Error + std::marker::Send + Sync>>> {
+        task::spawn_blocking(move || {
+            let mut monitor = MonitorBuilder::new()?
+                .match_subsystem_devtype("usb", "usb_device")?
+                .listen()?;
+
+            let mut poll = Poll::new()?;
+            let mut events = Events::with_capacity(1024);
+            poll.registry()
+                .register(&mut monitor, Token(0), Interest::READABLE)?;
+
+            loop {
+                if poll.poll(&mut events, None).is_err() {
+                    std::thread::sleep(Duration::from_millis(10));
+                    continue;
+                }
+                for event in monitor.iter() {
+                    let action = event.action().unwrap_or_default();
+                    let device = event.device();
+                    let sysname = device.sysname().to_string_lossy().to_string();
+                    let syspath = device.syspath().to_string_lossy().to_string();
+
+                    match action.to_string_lossy().trim() {
+                        "add" => {
+                            if !authorize::is_deferred(&device) {
+                                continue;
+                            }
+                            log::debug!("Got udev add action for deferred USB device {sysname}");
+                            cmd_tx.blocking_send(ManagerCommand::UsbDeviceDeferred {
+                                sysname,
+                                syspath,
+                            })?;
+                        }
+                        "remove" => {
+                            cmd_tx.blocking_send(ManagerCommand::UsbDeviceRemoved { sysname })?;
+                        }
+                        unhandled_action => {
+                            log::trace!("Unhandled udev action for USB device {sysname}: {unhandled_action}");
+                        }
+                    }
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        })
+    }
+
+    /// Watch for USB devices and display connectors that can indicate that the
+    /// device was connected to or disconnected from a dock
+    fn watch_dock_devices(
+        cmd_tx: mpsc::Sender<ManagerCommand>,
+    ) -> tokio::task::JoinHandle<Result<(), Box<dyn Error + std::marker::Send + Sync>>> {
+        task::spawn_blocking(move || {
+            let mut monitor = MonitorBuilder::new()?
+                .match_subsystem_devtype("usb", "usb_device")?
+                .match_subsystem("drm")?
+                .listen()?;
+
+            let mut poll = Poll::new()?;
+            let mut events = Events::with_capacity(1024);
+            poll.registry()
+                .register(&mut monitor, Token(0), Interest::READABLE)?;
+
+            loop {
+                if poll.poll(&mut events, None).is_err() {
+                    std::thread::sleep(Duration::from_millis(10));
+                    continue;
+                }
+                let mut changed = false;
+                for event in monitor.iter() {
+                    let action = event.action().unwrap_or_default();
+                    let device = event.device();
+                    let subsystem = device.subsystem().unwrap_or_default().to_string_lossy();
+                    let sysname = device.sysname().to_string_lossy();
+
+                    // Display hotplug is reported as a change of the DRM card
+                    match (subsystem.as_ref(), action.to_string_lossy().trim()) {
+                        ("usb", "add" | "remove") | ("drm", "change") => {
+                            log::trace!("Got udev action for possible dock device {sysname}");
+                            changed = true;
+                        }
+                        _ => (),
+                    }
+                }
+                if changed {
+                    cmd_tx.blocking_send(ManagerCommand::DockDevicesChanged)?;
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        })
+    }
+
+    /// Install the udev rule to de-authorize USB devices of composite device
+    /// configs with 'usb_authorization' enabled, and authorize any devices
+    /// that are still waiting from a previous run.
+    async fn setup_usb_authorization(&self) {
+        let configs = self.load_device_configs().await;
+        let mut ids: Vec<(u16, u16)> = configs
+            .iter()
+            .filter(|config| {
+                let options = config.options.as_ref();
+                let enabled = options
+                    .and_then(|options| options.usb_authorization)
+                    .unwrap_or(false);
+                let auto_manage = options
+                    .and_then(|options| options.auto_manage)
+                    .unwrap_or(false);
+                enabled && auto_manage && config.has_valid_matches(&self.dmi_data, &self.cpu_info)
+            })
+            .flat_map(|config| config.get_usb_ids())
+            .collect();
+        ids.sort();
+        ids.dedup();
+        if !ids.is_empty() {
+            log::info!("Deferring authorization of USB devices: {ids:04x?}");
+        }
+        if let Err(e) = authorize::install_authorization_rule(&ids).await {
+            log::error!("Failed to install USB authorization rule: {e:?}");
+            return;
+        }
+
+        // Find any USB devices that were left de-authorized
+        let deferred: Vec<(String, String)> = match udev::discover_devices("usb") {
+            Ok(devices) => devices
+                .iter()
+                .filter(|device| authorize::is_deferred(device))
+                .map(|device| {
+                    (
+                        device.sysname().to_string_lossy().to_string(),
+                        device.syspath().to_string_lossy().to_string(),
+                    )
+                })
+                .collect(),
+            Err(e) => {
+                log::error!("Failed to discover USB devices: {e:?}");
+                return;
+            }
+        };
+        for (sysname, syspath) in deferred {
+            log::info!("Authorizing deferred USB device: {sysname}");
+            if let Err(e) = authorize::authorize_usb_device(&sysname, &syspath).await {
+                log::error!("Failed to authorize USB device {sysname}: {e:?}");
+            }
+        }
+    }
+
     /// Watch for appearance and disappearence of devices is /dev and associate the corresponding udev device
     async fn watch_devnodes(
         cmd_tx: mpsc::Sender<ManagerCommand>,
@@ -1451,6 +2397,82 @@ impl Manager {
         Ok(())
     }
 
+    /// Send device added events for all source devices that are not currently
+    /// used by a composite device, so they can be considered again.
+    fn discover_unmanaged_devices(&self) -> Result<(), Box<dyn Error>> {
+        let mut devices: Vec<UdevDevice> = Vec::new();
+        for subsystem in ["hidraw", "input", "iio"] {
+            let discovered = udev::discover_devices(subsystem)?;
+            devices.extend(discovered.into_iter().map(UdevDevice::from));
+        }
+        let devices: Vec<UdevDevice> = devices
+            .into_iter()
+            .filter(|device| !self.source_devices_used.contains_key(&device.get_id()))
+            .collect();
+        log::debug!("Re-discovering {} unmanaged device(s)", devices.len());
+
+        let tx = self.tx.clone();
+        task::spawn(async move {
+            if let Err(e) = Manager::discover_devices(&tx, devices).await {
+                log::error!("Failed to discover unmanaged devices: {e:?}");
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Mark target device support as degraded and start recovering uinput in
+    /// the background. Does nothing if recovery is already running.
+    fn start_uinput_recovery(&mut self) {
+        if self.target_support_degraded {
+            return;
+        }
+        log::warn!(
+            "{} is unavailable, target device support is degraded",
+            uinput::UINPUT_PATH
+        );
+        self.target_support_degraded = true;
+        self.signal_target_support_degraded_changed();
+
+        let options = self.uinput_options.clone();
+        let tx = self.tx.clone();
+        task::spawn(async move {
+            uinput::wait_until_available(&options).await;
+            if let Err(e) = tx.send(ManagerCommand::UinputRecovered).await {
+                log::error!("Failed to send uinput recovered: {e:?}");
+            }
+        });
+    }
+
+    /// Emit a DBus signal when target device support becomes degraded or
+    /// recovers
+    fn signal_target_support_degraded_changed(&self) {
+        let conn = self.dbus.clone();
+        let manager_path = format!("{}/Manager", BUS_PREFIX);
+
+        task::spawn(async move {
+            let iface_ref = match conn
+                .object_server()
+                .interface::<_, ManagerInterface>(manager_path)
+                .await
+            {
+                Ok(iface) => iface,
+                Err(e) => {
+                    log::debug!("Failed to get DBus interface for manager to signal: {e:?}");
+                    return;
+                }
+            };
+
+            let iface = iface_ref.get().await;
+            if let Err(e) = iface
+                .target_support_degraded_changed(iface_ref.signal_context())
+                .await
+            {
+                log::error!("Failed to send target support degraded signal: {e:?}");
+            }
+        });
+    }
+
     async fn discover_devices(
         manager_tx: &mpsc::Sender<ManagerCommand>,
         devices: Vec<UdevDevice>,
@@ -1513,48 +2535,10 @@ impl Manager {
     /// to automatically create a [CompositeDevice].
     pub async fn load_device_configs(&self) -> Vec<CompositeDeviceConfig> {
         let task = task::spawn_blocking(move || {
-            let mut devices: Vec<CompositeDeviceConfig> = Vec::new();
-            let paths = get_devices_paths();
-
-            // Look for composite device profiles in all known locations
-            for path in paths.iter() {
-                log::trace!("Checking {path:?} for composite device configs");
-                let files = fs::read_dir(path);
-                if files.is_err() {
-                    log::debug!("Failed to load directory {path:?}: {}", files.unwrap_err());
-                    continue;
-                }
-                let mut files: Vec<_> = files.unwrap().map(|r| r.unwrap()).collect();
-                files.sort_by_key(|dir| dir.file_name());
-
-                // Look at each file in the directory and try to load them
-                for file in files {
-                    let filename = file.file_name();
-                    let filename = filename.as_os_str().to_str().unwrap();
-
-                    // Skip any non-yaml files
-                    if !filename.ends_with(".yaml") {
-                        continue;
-                    }
-
-                    // Try to load the composite device profile
-                    log::trace!("Found file: {}", file.path().display());
-                    let device =
-                        CompositeDeviceConfig::from_yaml_file(file.path().display().to_string());
-                    if device.is_err() {
-                        log::warn!(
-                            "Failed to parse composite device config '{}': {}",
-                            file.path().display(),
-                            device.unwrap_err()
-                        );
-                        continue;
-                    }
-                    let device = device.unwrap();
-                    devices.push(device);
-                }
-            }
-
-            devices
+            load_device_config_files()
+                .into_iter()
+                .map(|(_, config)| config)
+                .collect()
         });
 
         let result = task.await;
@@ -1565,6 +2549,24 @@ impl Manager {
         result.unwrap_or_default()
     }
 
+    /// Checks all device configs that match this system for source device
+    /// matchers that overlap and logs which config will be used.
+    async fn lint_device_configs(&self) {
+        let data = self.dmi_data.clone();
+        let cpu_info = self.cpu_info.clone();
+        let task = task::spawn_blocking(move || lint_device_configs(&data, &cpu_info));
+        let overlaps = match task.await {
+            Ok(overlaps) => overlaps,
+            Err(e) => {
+                log::error!("Failed to run task to lint device configs: {e:?}");
+                return;
+            }
+        };
+        for overlap in overlaps {
+            log::warn!("Overlapping device configs: {overlap}");
+        }
+    }
+
     /// Creates a DBus object and return the (active) handle to the listener
     async fn listen_on_dbus(
         dbus: Connection,
@@ -1579,6 +2581,181 @@ impl Manager {
         })
     }
 
+    /// Listen for external driver processes that provide their own source
+    /// devices over the driver socket
+    async fn listen_for_external_drivers(tx: mpsc::Sender<ManagerCommand>) {
+        if let Err(e) = external::server::listen(tx).await {
+            log::error!("Failed to listen for external drivers: {e:?}");
+        }
+    }
+
+    /// Listen for input from remote devices on each address configured in
+    /// the global config
+    async fn listen_for_network_input(tx: mpsc::Sender<ManagerCommand>) {
+        let addresses = GlobalConfig::load().network_listen.unwrap_or_default();
+        let mut tasks = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            let address: network::NetworkAddress = match address.parse() {
+                Ok(address) => address,
+                Err(e) => {
+                    log::error!("Unable to listen for network input: {e}");
+                    continue;
+                }
+            };
+            let tx = tx.clone();
+            tasks.push(task::spawn(async move {
+                if let Err(e) = network::listen(address.clone(), tx).await {
+                    log::error!("Failed to listen for network input on {address}: {e:?}");
+                }
+            }));
+        }
+        for task in tasks {
+            let _ = task.await;
+        }
+    }
+
+    /// Watch for changes to the active session of each seat using logind so
+    /// target devices can be assigned to the seat of the active session. Seat
+    /// assignment is disabled if logind is unavailable.
+    async fn watch_seats(conn: Connection, cmd_tx: mpsc::Sender<ManagerCommand>) {
+        let login_manager = match LoginManagerProxy::new(&conn).await {
+            Ok(proxy) => proxy,
+            Err(e) => {
+                log::warn!("Unable to connect to logind, seat assignment disabled: {e:?}");
+                return;
+            }
+        };
+        let seats = match login_manager.list_seats().await {
+            Ok(seats) => seats,
+            Err(e) => {
+                log::warn!("Unable to list seats, seat assignment disabled: {e:?}");
+                return;
+            }
+        };
+
+        let mut tasks = Vec::with_capacity(seats.len());
+        for (seat_id, path) in seats {
+            log::debug!("Watching active session of {seat_id}");
+            let task = Self::watch_seat(conn.clone(), cmd_tx.clone(), seat_id, path);
+            tasks.push(task::spawn(task));
+        }
+        for task in tasks {
+            if let Err(e) = task.await {
+                log::error!("Failed to join seat watcher: {e:?}");
+            }
+        }
+    }
+
+    /// Watch for changes to the active session of the given seat
+    async fn watch_seat(
+        conn: Connection,
+        cmd_tx: mpsc::Sender<ManagerCommand>,
+        seat_id: String,
+        path: OwnedObjectPath,
+    ) {
+        let seat = match SeatProxy::builder(&conn).path(path) {
+            Ok(builder) => builder.build().await,
+            Err(e) => Err(e),
+        };
+        let seat = match seat {
+            Ok(seat) => seat,
+            Err(e) => {
+                log::error!("Failed to create proxy for {seat_id}: {e:?}");
+                return;
+            }
+        };
+
+        // Assign target devices to the seat if it already has an active session
+        let mut changes = seat.receive_active_session_changed().await;
+        if let Ok((session, _)) = seat.active_session().await {
+            let cmd = ManagerCommand::ActiveSessionChanged {
+                seat: seat_id.clone(),
+                session,
+            };
+            if let Err(e) = cmd_tx.send(cmd).await {
+                log::error!("Unable to send command: {e:?}");
+            }
+        }
+
+        while let Some(change) = changes.next().await {
+            let session = match change.get().await {
+                Ok((session, _)) => session,
+                Err(e) => {
+                    log::error!("Failed to get active session of {seat_id}: {e:?}");
+                    continue;
+                }
+            };
+            let cmd = ManagerCommand::ActiveSessionChanged {
+                seat: seat_id.clone(),
+                session,
+            };
+            if let Err(e) = cmd_tx.send(cmd).await {
+                log::error!("Unable to send command: {e:?}");
+                return;
+            }
+        }
+    }
+
+    /// Report user activity to logind while input is written to target
+    /// devices by clearing the idle hint of the active session of each seat.
+    async fn report_user_activity(conn: Connection) {
+        if !GlobalConfig::load().report_user_activity.unwrap_or(true) {
+            log::debug!("User activity reporting is disabled");
+            return;
+        }
+        let login_manager = match LoginManagerProxy::new(&conn).await {
+            Ok(proxy) => proxy,
+            Err(e) => {
+                log::warn!("Unable to connect to logind, activity reporting disabled: {e:?}");
+                return;
+            }
+        };
+
+        let mut interval = tokio::time::interval(activity::REPORT_INTERVAL);
+        loop {
+            interval.tick().await;
+            if !activity::active_within(activity::REPORT_INTERVAL) {
+                continue;
+            }
+            let seats = match login_manager.list_seats().await {
+                Ok(seats) => seats,
+                Err(e) => {
+                    log::debug!("Unable to list seats: {e:?}");
+                    continue;
+                }
+            };
+            for (seat_id, path) in seats {
+                if let Err(e) = Self::clear_idle_hint(&conn, path).await {
+                    log::debug!("Failed to report user activity on {seat_id}: {e:?}");
+                }
+            }
+        }
+    }
+
+    /// Clear the idle hint of the active session of the given seat
+    async fn clear_idle_hint(conn: &Connection, seat: OwnedObjectPath) -> zbus::Result<()> {
+        let seat = SeatProxy::builder(conn)
+            .path(seat)?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?;
+        let (session_id, path) = seat.active_session().await?;
+        if session_id.is_empty() {
+            return Ok(());
+        }
+        let session = SessionProxy::builder(conn)
+            .path(path)?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?;
+        if session.idle_hint().await? {
+            log::debug!("Clearing idle hint of session {session_id}");
+            session.set_idle_hint(false).await?;
+        }
+
+        Ok(())
+    }
+
     async fn add_device_to_composite_device(
         &self,
         device: UdevDevice,
@@ -2,8 +2,25 @@
 pub mod capability;
 pub mod composite_device;
 pub mod event;
+pub mod keymap;
+#[cfg(test)]
+pub mod keymap_test;
+pub mod latency;
+#[cfg(test)]
+pub mod latency_test;
 pub mod manager;
 pub mod output_capability;
 pub mod output_event;
+#[cfg(test)]
+pub mod output_event_test;
+pub mod readiness;
+#[cfg(test)]
+pub mod readiness_test;
+pub mod scheduling;
+#[cfg(test)]
+pub mod scheduling_test;
 pub mod source;
 pub mod target;
+pub mod uinput;
+#[cfg(test)]
+pub mod uinput_test;
@@ -17,6 +17,8 @@ pub enum OutputCapability {
 pub enum LED {
     Brightness,
     Color,
+    /// Keyboard lock state indicators (e.g. CapsLock, NumLock, ScrollLock)
+    Indicator,
 }
 
 /// Haptic capabilities
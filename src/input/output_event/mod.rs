@@ -1,10 +1,16 @@
-use std::sync::mpsc::Sender;
+use std::{sync::mpsc::Sender, time::Duration};
 
-use ::evdev::{FFEffectData, InputEvent};
+use ::evdev::{FFEffectData, FFEffectKind, FFEnvelope, InputEvent};
+use packed_struct::types::{Integer, SizedInteger};
 
-use crate::drivers::{
-    dualsense::hid_report::SetStatePackedOutputData,
-    steam_deck::hid_report::{PackedHapticReport, PackedRumbleReport, PadSide},
+use crate::{
+    config::HapticsToRumble,
+    drivers::{
+        dualsense::hid_report::SetStatePackedOutputData,
+        steam_deck::hid_report::{
+            CommandType, Intensity, PackedHapticReport, PackedRumbleReport, PadSide,
+        },
+    },
 };
 
 use super::output_capability::{Haptic, OutputCapability};
@@ -83,3 +89,191 @@ pub enum UinputOutputEvent {
     /// Effect id to erase
     FFErase(u32),
 }
+
+/// Default rumble intensity of haptic "tick" commands
+pub const DEFAULT_HAPTIC_TICK_STRENGTH: f64 = 0.5;
+/// Default rumble intensity of haptic "click" commands
+pub const DEFAULT_HAPTIC_CLICK_STRENGTH: f64 = 1.0;
+
+/// Curve used to scale the intensity of trackpad haptic commands when they are
+/// converted into rumble.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HapticCurve {
+    #[default]
+    Linear,
+    Quadratic,
+    Cubic,
+    SquareRoot,
+}
+
+impl HapticCurve {
+    /// Names of all available curves
+    pub const NAMES: [&'static str; 4] = ["linear", "quadratic", "cubic", "sqrt"];
+
+    /// Apply the curve to the given normalized (0.0 - 1.0) value
+    pub fn apply(&self, value: f64) -> f64 {
+        let value = value.clamp(0.0, 1.0);
+        match self {
+            HapticCurve::Linear => value,
+            HapticCurve::Quadratic => value.powi(2),
+            HapticCurve::Cubic => value.powi(3),
+            HapticCurve::SquareRoot => value.sqrt(),
+        }
+    }
+}
+
+impl From<&str> for HapticCurve {
+    fn from(value: &str) -> Self {
+        match value {
+            "quadratic" => HapticCurve::Quadratic,
+            "cubic" => HapticCurve::Cubic,
+            "sqrt" => HapticCurve::SquareRoot,
+            _ => HapticCurve::Linear,
+        }
+    }
+}
+
+/// Convert the given Steam Deck trackpad haptic report into a rumble report
+/// for source devices that lack trackpad haptics. Returns the rumble report
+/// along with how long the rumble should play before it is stopped.
+pub fn haptic_to_rumble(
+    report: &PackedHapticReport,
+    config: Option<&HapticsToRumble>,
+) -> Option<(PackedRumbleReport, Duration)> {
+    let enabled = config.and_then(|c| c.enabled).unwrap_or(true);
+    if !enabled {
+        return None;
+    }
+    let curve: HapticCurve = config
+        .and_then(|c| c.curve.as_ref())
+        .map(|c| c.as_str().into())
+        .unwrap_or_default();
+    let scale = config.and_then(|c| c.scale).unwrap_or(1.0).max(0.0);
+    let duration_scale = config
+        .and_then(|c| c.duration_scale)
+        .unwrap_or(1.0)
+        .clamp(0.0, 100.0);
+
+    // Ticks are lighter than clicks
+    let strength = match report.cmd_type {
+        CommandType::Off => return None,
+        CommandType::Tick => config
+            .and_then(|c| c.tick_strength)
+            .unwrap_or(DEFAULT_HAPTIC_TICK_STRENGTH),
+        CommandType::Click => config
+            .and_then(|c| c.click_strength)
+            .unwrap_or(DEFAULT_HAPTIC_CLICK_STRENGTH),
+    };
+    let strength = strength.clamp(0.0, 1.0);
+
+    // Gain is measured in decibels, ranging from -24 to +6
+    let gain = (report.gain.clamp(-24, 6) as f64 - 6.0) / 20.0;
+    let gain = 10f64.powf(gain);
+
+    let value = (curve.apply(strength * gain) * scale).clamp(0.0, 1.0);
+    let speed = (value * u16::MAX as f64).round() as u16;
+    if speed == 0 {
+        return None;
+    }
+
+    let duration = match report.intensity {
+        Intensity::Default => Duration::from_millis(30),
+        Intensity::Short => Duration::from_millis(20),
+        Intensity::Medium => Duration::from_millis(40),
+        Intensity::Long => Duration::from_millis(80),
+        Intensity::Insane => Duration::from_millis(160),
+    };
+    let duration = duration.mul_f64(duration_scale);
+
+    let mut rumble = PackedRumbleReport::new();
+    rumble.intensity = 1;
+    match report.side {
+        PadSide::Left => {
+            rumble.left_speed = Integer::from_primitive(speed);
+        }
+        PadSide::Right => {
+            rumble.right_speed = Integer::from_primitive(speed);
+        }
+        PadSide::Both => {
+            rumble.left_speed = Integer::from_primitive(speed);
+            rumble.right_speed = Integer::from_primitive(speed);
+        }
+    }
+
+    Some((rumble, duration))
+}
+
+/// Scale the magnitudes of the given force feedback effect by the given gain
+/// (0.0 - 1.0).
+pub fn scale_ff_effect(data: FFEffectData, gain: f64) -> FFEffectData {
+    let gain = gain.clamp(0.0, 1.0);
+    let scale_u16 = |value: u16| (value as f64 * gain).round() as u16;
+    let scale_i16 = |value: i16| (value as f64 * gain).round() as i16;
+    let scale_envelope = |envelope: FFEnvelope| FFEnvelope {
+        attack_level: scale_u16(envelope.attack_level),
+        fade_level: scale_u16(envelope.fade_level),
+        ..envelope
+    };
+
+    let kind = match data.kind {
+        FFEffectKind::Constant { level, envelope } => FFEffectKind::Constant {
+            level: scale_i16(level),
+            envelope: scale_envelope(envelope),
+        },
+        FFEffectKind::Ramp {
+            start_level,
+            end_level,
+            envelope,
+        } => FFEffectKind::Ramp {
+            start_level: scale_i16(start_level),
+            end_level: scale_i16(end_level),
+            envelope: scale_envelope(envelope),
+        },
+        FFEffectKind::Periodic {
+            waveform,
+            period,
+            magnitude,
+            offset,
+            phase,
+            envelope,
+        } => FFEffectKind::Periodic {
+            waveform,
+            period,
+            magnitude: scale_i16(magnitude),
+            offset: scale_i16(offset),
+            phase,
+            envelope: scale_envelope(envelope),
+        },
+        FFEffectKind::Rumble {
+            strong_magnitude,
+            weak_magnitude,
+        } => FFEffectKind::Rumble {
+            strong_magnitude: scale_u16(strong_magnitude),
+            weak_magnitude: scale_u16(weak_magnitude),
+        },
+        kind => kind,
+    };
+
+    FFEffectData { kind, ..data }
+}
+
+/// Scale the rumble intensity of the given output event by the given gain
+/// (0.0 - 1.0). Events without rumble are returned unchanged.
+pub fn scale_rumble(event: OutputEvent, gain: f64) -> OutputEvent {
+    let gain = gain.clamp(0.0, 1.0);
+    match event {
+        OutputEvent::DualSense(mut report) => {
+            let scale = |value: u8| (value as f64 * gain).round() as u8;
+            report.rumble_emulation_left = scale(report.rumble_emulation_left);
+            report.rumble_emulation_right = scale(report.rumble_emulation_right);
+            OutputEvent::DualSense(report)
+        }
+        OutputEvent::SteamDeckRumble(mut report) => {
+            let scale = |value: u16| (value as f64 * gain).round() as u16;
+            report.left_speed = Integer::from_primitive(scale(report.left_speed.to_primitive()));
+            report.right_speed = Integer::from_primitive(scale(report.right_speed.to_primitive()));
+            OutputEvent::SteamDeckRumble(report)
+        }
+        event => event,
+    }
+}
@@ -0,0 +1,260 @@
+use std::time::Duration;
+
+use ::evdev::{FFEffectData, FFEffectKind, FFEnvelope, FFReplay, FFTrigger, FFWaveform};
+use packed_struct::types::{Integer, SizedInteger};
+
+use crate::{
+    config::HapticsToRumble,
+    drivers::{
+        dualsense::hid_report::SetStatePackedOutputData,
+        steam_deck::hid_report::{
+            CommandType, Intensity, PackedHapticReport, PackedRumbleReport, PadSide,
+        },
+    },
+    input::output_event::{
+        haptic_to_rumble, scale_ff_effect, scale_rumble, HapticCurve, OutputEvent,
+    },
+};
+
+fn haptic_report(side: PadSide, cmd_type: CommandType, intensity: Intensity) -> PackedHapticReport {
+    let mut report = PackedHapticReport::new();
+    report.side = side;
+    report.cmd_type = cmd_type;
+    report.intensity = intensity;
+    report.gain = 6;
+    report
+}
+
+#[test]
+fn test_haptic_curve() {
+    assert_eq!(HapticCurve::from("linear"), HapticCurve::Linear);
+    assert_eq!(HapticCurve::from("quadratic"), HapticCurve::Quadratic);
+    assert_eq!(HapticCurve::from("cubic"), HapticCurve::Cubic);
+    assert_eq!(HapticCurve::from("sqrt"), HapticCurve::SquareRoot);
+    assert_eq!(HapticCurve::from("invalid"), HapticCurve::Linear);
+
+    assert_eq!(HapticCurve::Linear.apply(0.5), 0.5);
+    assert_eq!(HapticCurve::Quadratic.apply(0.5), 0.25);
+    assert_eq!(HapticCurve::Cubic.apply(0.5), 0.125);
+    assert_eq!(HapticCurve::SquareRoot.apply(0.25), 0.5);
+
+    // Values outside of the normalized range should be clamped
+    assert_eq!(HapticCurve::Linear.apply(2.0), 1.0);
+    assert_eq!(HapticCurve::SquareRoot.apply(-1.0), 0.0);
+}
+
+#[test]
+fn test_haptic_to_rumble() {
+    // Clicks rumble at full strength by default
+    let report = haptic_report(PadSide::Left, CommandType::Click, Intensity::Default);
+    let (rumble, duration) = haptic_to_rumble(&report, None).unwrap();
+    assert_eq!(rumble.left_speed.to_primitive(), u16::MAX);
+    assert_eq!(rumble.right_speed.to_primitive(), 0);
+    assert_eq!(duration, Duration::from_millis(30));
+
+    // Ticks are lighter than clicks
+    let report = haptic_report(PadSide::Right, CommandType::Tick, Intensity::Long);
+    let (rumble, duration) = haptic_to_rumble(&report, None).unwrap();
+    assert_eq!(rumble.left_speed.to_primitive(), 0);
+    assert_eq!(rumble.right_speed.to_primitive(), 32768);
+    assert_eq!(duration, Duration::from_millis(80));
+
+    // Lower gain should produce weaker rumble
+    let mut report = haptic_report(PadSide::Both, CommandType::Click, Intensity::Short);
+    report.gain = -24;
+    let (rumble, _) = haptic_to_rumble(&report, None).unwrap();
+    let speed = rumble.left_speed.to_primitive();
+    assert!(speed > 0 && speed < u16::MAX / 10);
+    assert_eq!(rumble.right_speed.to_primitive(), speed);
+
+    // Haptic "off" commands do not produce rumble
+    let report = haptic_report(PadSide::Both, CommandType::Off, Intensity::Default);
+    assert!(haptic_to_rumble(&report, None).is_none());
+}
+
+#[test]
+fn test_haptic_to_rumble_config() {
+    let report = haptic_report(PadSide::Both, CommandType::Tick, Intensity::Medium);
+
+    let config = HapticsToRumble {
+        enabled: Some(false),
+        ..Default::default()
+    };
+    assert!(haptic_to_rumble(&report, Some(&config)).is_none());
+
+    let config = HapticsToRumble {
+        curve: Some("quadratic".to_string()),
+        duration_scale: Some(2.0),
+        ..Default::default()
+    };
+    let (rumble, duration) = haptic_to_rumble(&report, Some(&config)).unwrap();
+    assert_eq!(rumble.left_speed.to_primitive(), 16384);
+    assert_eq!(rumble.right_speed.to_primitive(), 16384);
+    assert_eq!(duration, Duration::from_millis(80));
+
+    // A zero scale or strength results in no rumble at all
+    let config = HapticsToRumble {
+        scale: Some(0.0),
+        ..Default::default()
+    };
+    assert!(haptic_to_rumble(&report, Some(&config)).is_none());
+    let config = HapticsToRumble {
+        tick_strength: Some(0.0),
+        ..Default::default()
+    };
+    assert!(haptic_to_rumble(&report, Some(&config)).is_none());
+}
+
+fn ff_effect(kind: FFEffectKind) -> FFEffectData {
+    FFEffectData {
+        direction: 0,
+        trigger: FFTrigger {
+            button: 0,
+            interval: 0,
+        },
+        replay: FFReplay {
+            length: 100,
+            delay: 0,
+        },
+        kind,
+    }
+}
+
+fn envelope() -> FFEnvelope {
+    FFEnvelope {
+        attack_length: 10,
+        attack_level: 1001,
+        fade_length: 20,
+        fade_level: 3,
+    }
+}
+
+/// Returns the strong and weak magnitude of the given rumble effect scaled by
+/// the given gain
+fn scaled_rumble_effect(strong: u16, weak: u16, gain: f64) -> (u16, u16) {
+    let data = ff_effect(FFEffectKind::Rumble {
+        strong_magnitude: strong,
+        weak_magnitude: weak,
+    });
+    let scaled = scale_ff_effect(data, gain);
+    assert_eq!(scaled.replay.length, 100);
+    let FFEffectKind::Rumble {
+        strong_magnitude,
+        weak_magnitude,
+    } = scaled.kind
+    else {
+        panic!("Scaling changed the effect kind: {:?}", scaled.kind);
+    };
+    (strong_magnitude, weak_magnitude)
+}
+
+#[test]
+fn test_scale_ff_effect_rumble() {
+    assert_eq!(scaled_rumble_effect(u16::MAX, 1000, 0.0), (0, 0));
+    assert_eq!(scaled_rumble_effect(u16::MAX, 1000, 1.0), (u16::MAX, 1000));
+    // 65535 * 0.5 = 32767.5 and 1001 * 0.5 = 500.5 are rounded up
+    assert_eq!(scaled_rumble_effect(u16::MAX, 1001, 0.5), (32768, 501));
+    // 3 * 0.25 = 0.75 is rounded up, 1 * 0.25 = 0.25 is rounded down
+    assert_eq!(scaled_rumble_effect(3, 1, 0.25), (1, 0));
+    // The gain is clamped between 0.0 and 1.0
+    assert_eq!(scaled_rumble_effect(1000, 1000, 2.0), (1000, 1000));
+    assert_eq!(scaled_rumble_effect(1000, 1000, -1.0), (0, 0));
+}
+
+#[test]
+fn test_scale_ff_effect_levels() {
+    // Signed levels and envelopes are scaled with rounding
+    let data = ff_effect(FFEffectKind::Constant {
+        level: -1001,
+        envelope: envelope(),
+    });
+    let FFEffectKind::Constant { level, envelope } = scale_ff_effect(data, 0.5).kind else {
+        panic!("Scaling changed the effect kind");
+    };
+    assert_eq!(level, -501);
+    assert_eq!(envelope.attack_level, 501);
+    assert_eq!(envelope.fade_level, 2);
+    assert_eq!(envelope.attack_length, 10);
+    assert_eq!(envelope.fade_length, 20);
+
+    let data = ff_effect(FFEffectKind::Periodic {
+        waveform: FFWaveform::Sine,
+        period: 50,
+        magnitude: i16::MAX,
+        offset: -100,
+        phase: 90,
+        envelope: envelope(),
+    });
+    let FFEffectKind::Periodic {
+        period,
+        magnitude,
+        offset,
+        phase,
+        ..
+    } = scale_ff_effect(data, 0.0).kind
+    else {
+        panic!("Scaling changed the effect kind");
+    };
+    assert_eq!((period, magnitude, offset, phase), (50, 0, 0, 90));
+
+    let data = ff_effect(FFEffectKind::Ramp {
+        start_level: i16::MIN,
+        end_level: i16::MAX,
+        envelope: envelope(),
+    });
+    let FFEffectKind::Ramp {
+        start_level,
+        end_level,
+        ..
+    } = scale_ff_effect(data, 1.0).kind
+    else {
+        panic!("Scaling changed the effect kind");
+    };
+    assert_eq!((start_level, end_level), (i16::MIN, i16::MAX));
+}
+
+/// Returns the left and right rumble of the given DualSense and Steam Deck
+/// rumble reports scaled by the given gain
+fn scaled_rumble_reports(left: u8, right: u8, speed: u16, gain: f64) -> ((u8, u8), (u16, u16)) {
+    let report = SetStatePackedOutputData {
+        rumble_emulation_left: left,
+        rumble_emulation_right: right,
+        ..Default::default()
+    };
+    let OutputEvent::DualSense(report) = scale_rumble(OutputEvent::DualSense(report), gain) else {
+        panic!("Scaling changed the output event type");
+    };
+    let dualsense = (report.rumble_emulation_left, report.rumble_emulation_right);
+
+    let mut report = PackedRumbleReport::new();
+    report.left_speed = Integer::from_primitive(speed);
+    report.right_speed = Integer::from_primitive(u16::MAX - speed);
+    let OutputEvent::SteamDeckRumble(report) =
+        scale_rumble(OutputEvent::SteamDeckRumble(report), gain)
+    else {
+        panic!("Scaling changed the output event type");
+    };
+    let deck = (
+        report.left_speed.to_primitive(),
+        report.right_speed.to_primitive(),
+    );
+
+    (dualsense, deck)
+}
+
+#[test]
+fn test_scale_rumble() {
+    assert_eq!(scaled_rumble_reports(255, 1, 1000, 0.0), ((0, 0), (0, 0)));
+    assert_eq!(
+        scaled_rumble_reports(255, 1, 1000, 1.0),
+        ((255, 1), (1000, u16::MAX - 1000))
+    );
+    // 255 * 0.5 = 127.5, 1 * 0.5 = 0.5 and 1001 * 0.5 = 500.5 are rounded up
+    assert_eq!(
+        scaled_rumble_reports(255, 1, 1001, 0.5),
+        ((128, 1), (501, 32267))
+    );
+    // 3 * 0.25 = 0.75 and 65534 * 0.25 = 16383.5 are rounded up, 1 * 0.25 =
+    // 0.25 is rounded down
+    assert_eq!(scaled_rumble_reports(3, 1, 1, 0.25), ((1, 0), (0, 16384)));
+}
@@ -0,0 +1,81 @@
+//! Event-driven waiting for blocking device threads. Device implementations
+//! that expose a file descriptor (e.g. evdev or uinput nodes) are only polled
+//! when the descriptor becomes readable or a command is received, instead of
+//! sleeping for a fixed poll rate between each iteration.
+use std::{
+    io,
+    os::fd::{AsRawFd, RawFd},
+    time::Duration,
+};
+
+use tokio::{
+    io::{unix::AsyncFd, Interest},
+    runtime::Handle,
+    sync::mpsc,
+};
+
+/// Maximum time to wait for a device to become ready before polling it
+/// anyway. This guards against missed readiness notifications.
+pub const MAX_WAIT: Duration = Duration::from_millis(250);
+
+/// File descriptor owned by a device implementation. The descriptor is not
+/// closed when this is dropped.
+#[derive(Debug)]
+pub struct DeviceFd(RawFd);
+
+impl AsRawFd for DeviceFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0
+    }
+}
+
+/// Register the given file descriptor with the reactor of the current tokio
+/// runtime to wait for it to become readable.
+pub fn register(fd: RawFd) -> io::Result<AsyncFd<DeviceFd>> {
+    AsyncFd::with_interest(DeviceFd(fd), Interest::READABLE)
+}
+
+/// Reason a device thread was woken up by [wait]
+#[derive(Debug)]
+pub enum Wake<T> {
+    /// The file descriptor of the device is readable
+    Readable,
+    /// A command was received. [None] if the channel was closed.
+    Command(Option<T>),
+    /// The timeout expired
+    Timeout,
+}
+
+/// Block the current thread until the given file descriptor is readable, a
+/// command is received on the given channel, or the timeout expires. Must be
+/// called from a blocking thread of the given runtime.
+pub fn wait<T>(
+    runtime: &Handle,
+    fd: Option<&AsyncFd<DeviceFd>>,
+    rx: &mut mpsc::Receiver<T>,
+    timeout: Duration,
+) -> Wake<T> {
+    runtime.block_on(async {
+        tokio::select! {
+            command = rx.recv() => Wake::Command(command),
+            _ = readable(fd) => Wake::Readable,
+            _ = tokio::time::sleep(timeout) => Wake::Timeout,
+        }
+    })
+}
+
+/// Wait for the given file descriptor to become readable. Readiness is
+/// cleared immediately, so the device must be polled until it has no more
+/// data before waiting again. Never completes if no descriptor is given.
+async fn readable(fd: Option<&AsyncFd<DeviceFd>>) {
+    let Some(fd) = fd else {
+        return std::future::pending().await;
+    };
+    match fd.readable().await {
+        Ok(mut guard) => guard.clear_ready(),
+        Err(e) => {
+            log::debug!("Failed to wait for device to become readable: {e:?}");
+            tokio::time::sleep(MAX_WAIT).await;
+        }
+    }
+}
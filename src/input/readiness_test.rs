@@ -0,0 +1,44 @@
+use std::{
+    io::Write,
+    os::{fd::AsRawFd, unix::net::UnixStream},
+    time::Duration,
+};
+
+use tokio::sync::mpsc;
+
+use crate::input::readiness::{self, Wake};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_readiness_wait() {
+    let (mut writer, reader) = UnixStream::pair().unwrap();
+    reader.set_nonblocking(true).unwrap();
+    let (tx, mut rx) = mpsc::channel::<u8>(1);
+
+    let task = tokio::task::spawn_blocking(move || {
+        let runtime = tokio::runtime::Handle::current();
+        let fd = readiness::register(reader.as_raw_fd()).unwrap();
+        let timeout = Duration::from_millis(10);
+
+        // Nothing is ready yet
+        let wake = readiness::wait(&runtime, Some(&fd), &mut rx, timeout);
+        assert!(matches!(wake, Wake::Timeout));
+
+        // Commands wake up the thread
+        tx.blocking_send(1).unwrap();
+        let wake = readiness::wait(&runtime, Some(&fd), &mut rx, timeout);
+        assert!(matches!(wake, Wake::Command(Some(1))));
+
+        // Readable file descriptors wake up the thread
+        writer.write_all(&[0]).unwrap();
+        let wake = readiness::wait(&runtime, Some(&fd), &mut rx, Duration::from_secs(5));
+        assert!(matches!(wake, Wake::Readable));
+
+        // Closed channels are reported
+        drop(tx);
+        let wake = readiness::wait(&runtime, None, &mut rx, timeout);
+        assert!(matches!(wake, Wake::Command(None)));
+        drop(fd);
+        drop(reader);
+    });
+    task.await.unwrap();
+}
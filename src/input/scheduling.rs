@@ -0,0 +1,200 @@
+//! Scheduling policy and CPU affinity for blocking device threads. Options
+//! are read from the environment when the first device thread starts:
+//!
+//! - `INPUTPLUMBER_SCHED_POLICY`: "fifo" to use realtime `SCHED_FIFO`
+//!   scheduling, or "other" (default) to keep the normal scheduler.
+//! - `INPUTPLUMBER_SCHED_PRIORITY`: `SCHED_FIFO` priority from 1 to 99
+//!   (default: 10).
+//! - `INPUTPLUMBER_CPU_AFFINITY`: list of CPUs the threads may run on,
+//!   e.g. "2,3" or "0-1,4".
+//!
+//! These can be set for the service with a systemd drop-in using
+//! `Environment=`. While low latency mode is enabled, threads use
+//! `SCHED_FIFO` with the configured priority even if the policy is "other".
+use std::{env, error::Error, sync::OnceLock};
+
+use nix::{
+    sched::{sched_setaffinity, CpuSet},
+    unistd::Pid,
+};
+
+use crate::input::latency;
+
+/// Default realtime priority used with [SchedPolicy::Fifo]
+const DEFAULT_FIFO_PRIORITY: i32 = 10;
+
+/// Scheduling options shared by all device threads
+static OPTIONS: OnceLock<SchedulingOptions> = OnceLock::new();
+
+/// Scheduling policy for device threads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchedPolicy {
+    Other,
+    Fifo(i32),
+}
+
+/// Scheduling options for blocking device threads
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchedulingOptions {
+    pub policy: SchedPolicy,
+    /// SCHED_FIFO priority used in low latency mode
+    pub low_latency_priority: i32,
+    pub cpu_affinity: Option<Vec<usize>>,
+}
+
+impl SchedulingOptions {
+    /// Read the scheduling options from the environment. Invalid values are
+    /// logged and ignored.
+    pub fn from_env() -> Self {
+        let priority = match env::var("INPUTPLUMBER_SCHED_PRIORITY") {
+            Ok(value) => match parse_priority(value.as_str()) {
+                Ok(priority) => priority,
+                Err(e) => {
+                    log::warn!("Invalid INPUTPLUMBER_SCHED_PRIORITY '{value}': {e}");
+                    DEFAULT_FIFO_PRIORITY
+                }
+            },
+            Err(_) => DEFAULT_FIFO_PRIORITY,
+        };
+
+        let policy = match env::var("INPUTPLUMBER_SCHED_POLICY") {
+            Ok(value) => match value.to_lowercase().as_str() {
+                "fifo" => SchedPolicy::Fifo(priority),
+                "other" | "" => SchedPolicy::Other,
+                _ => {
+                    log::warn!("Invalid INPUTPLUMBER_SCHED_POLICY '{value}'");
+                    SchedPolicy::Other
+                }
+            },
+            Err(_) => SchedPolicy::Other,
+        };
+
+        let cpu_affinity = match env::var("INPUTPLUMBER_CPU_AFFINITY") {
+            Ok(value) => match parse_cpu_list(value.as_str()) {
+                Ok(cpus) if cpus.is_empty() => None,
+                Ok(cpus) => Some(cpus),
+                Err(e) => {
+                    log::warn!("Invalid INPUTPLUMBER_CPU_AFFINITY '{value}': {e}");
+                    None
+                }
+            },
+            Err(_) => None,
+        };
+
+        Self {
+            policy,
+            low_latency_priority: priority,
+            cpu_affinity,
+        }
+    }
+}
+
+/// Apply the configured scheduling policy and CPU affinity to the calling
+/// thread. This should be called at the start of a blocking device thread
+/// and again when low latency mode changes.
+pub fn configure_current_thread() {
+    let options = OPTIONS.get_or_init(SchedulingOptions::from_env);
+
+    if let Some(cpus) = options.cpu_affinity.as_ref() {
+        if let Err(e) = set_cpu_affinity(cpus) {
+            log::warn!("Failed to set CPU affinity of device thread: {e:?}");
+        }
+    }
+
+    match options.policy {
+        SchedPolicy::Fifo(priority) => {
+            if let Err(e) = set_fifo_scheduling(priority) {
+                log::warn!("Failed to set SCHED_FIFO scheduling of device thread: {e:?}");
+            }
+        }
+        SchedPolicy::Other if latency::is_enabled() => {
+            // Realtime scheduling usually requires CAP_SYS_NICE, so only
+            // raise the priority where permitted.
+            if let Err(e) = set_fifo_scheduling(options.low_latency_priority) {
+                log::debug!("Unable to raise priority of device thread: {e:?}");
+            }
+        }
+        SchedPolicy::Other => {
+            if let Err(e) = restore_normal_scheduling() {
+                log::warn!("Failed to restore scheduling of device thread: {e:?}");
+            }
+        }
+    }
+}
+
+/// Restrict the calling thread to the given CPUs
+fn set_cpu_affinity(cpus: &[usize]) -> Result<(), Box<dyn Error>> {
+    let mut cpu_set = CpuSet::new();
+    for cpu in cpus {
+        cpu_set.set(*cpu)?;
+    }
+    sched_setaffinity(Pid::from_raw(0), &cpu_set)?;
+    Ok(())
+}
+
+/// Switch the calling thread to the SCHED_FIFO realtime scheduler with the
+/// given priority.
+fn set_fifo_scheduling(priority: i32) -> Result<(), Box<dyn Error>> {
+    let param = libc::sched_param {
+        sched_priority: priority,
+    };
+    // SAFETY: a tid of 0 refers to the calling thread and the param pointer
+    // is valid for the duration of the call.
+    let result = unsafe { libc::sched_setscheduler(0, libc::SCHED_FIFO, &param) };
+    if result != 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(())
+}
+
+/// Switch the calling thread back to the normal scheduler if it was moved to
+/// another scheduler (e.g. by low latency mode)
+fn restore_normal_scheduling() -> Result<(), Box<dyn Error>> {
+    // SAFETY: a tid of 0 refers to the calling thread.
+    let policy = unsafe { libc::sched_getscheduler(0) };
+    if policy == libc::SCHED_OTHER {
+        return Ok(());
+    }
+    let param = libc::sched_param { sched_priority: 0 };
+    // SAFETY: a tid of 0 refers to the calling thread and the param pointer
+    // is valid for the duration of the call.
+    let result = unsafe { libc::sched_setscheduler(0, libc::SCHED_OTHER, &param) };
+    if result != 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(())
+}
+
+/// Parse the given SCHED_FIFO priority
+pub fn parse_priority(value: &str) -> Result<i32, Box<dyn Error>> {
+    let priority: i32 = value.trim().parse()?;
+    if !(1..=99).contains(&priority) {
+        return Err(format!("priority must be between 1 and 99, got {priority}").into());
+    }
+    Ok(priority)
+}
+
+/// Parse a list of CPUs in the kernel's cpu list format (e.g. "0-2,5")
+pub fn parse_cpu_list(value: &str) -> Result<Vec<usize>, Box<dyn Error>> {
+    let mut cpus = vec![];
+    for part in value.split(',') {
+        let part = part.trim();
+        if part.is_empty() {
+            continue;
+        }
+        match part.split_once('-') {
+            Some((start, end)) => {
+                let start: usize = start.trim().parse()?;
+                let end: usize = end.trim().parse()?;
+                if start > end {
+                    return Err(format!("invalid cpu range '{part}'").into());
+                }
+                cpus.extend(start..=end);
+            }
+            None => cpus.push(part.parse()?),
+        }
+    }
+    cpus.sort_unstable();
+    cpus.dedup();
+    Ok(cpus)
+}
@@ -0,0 +1,21 @@
+use crate::input::scheduling::{parse_cpu_list, parse_priority};
+
+#[test]
+fn test_parse_cpu_list() {
+    assert_eq!(parse_cpu_list("0").unwrap(), vec![0]);
+    assert_eq!(parse_cpu_list("2,3").unwrap(), vec![2, 3]);
+    assert_eq!(parse_cpu_list("0-2, 5").unwrap(), vec![0, 1, 2, 5]);
+    assert_eq!(parse_cpu_list("3,1-3").unwrap(), vec![1, 2, 3]);
+    assert!(parse_cpu_list("").unwrap().is_empty());
+    assert!(parse_cpu_list("3-1").is_err());
+    assert!(parse_cpu_list("a").is_err());
+}
+
+#[test]
+fn test_parse_priority() {
+    assert_eq!(parse_priority("1").unwrap(), 1);
+    assert_eq!(parse_priority(" 99 ").unwrap(), 99);
+    assert!(parse_priority("0").is_err());
+    assert!(parse_priority("100").is_err());
+    assert!(parse_priority("high").is_err());
+}
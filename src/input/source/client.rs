@@ -9,7 +9,7 @@ use tokio::sync::mpsc::{
 
 use crate::input::output_event::OutputEvent;
 
-use super::command::SourceCommand;
+use super::command::{BypassRoutes, SourceCommand};
 
 /// Possible errors for a source device client
 #[derive(Error, Debug)]
@@ -101,6 +101,14 @@ impl SourceDeviceClient {
         }
     }
 
+    /// Write input events with the given capabilities directly to the given
+    /// target devices instead of the composite device. Passing None sends
+    /// all input events to the composite device again.
+    pub async fn set_bypass(&self, routes: Option<BypassRoutes>) -> Result<(), ClientError> {
+        self.tx.send(SourceCommand::SetBypass(routes)).await?;
+        Ok(())
+    }
+
     /// Stop the source device.
     pub async fn stop(&self) -> Result<(), ClientError> {
         self.tx.send(SourceCommand::Stop).await?;
@@ -1,8 +1,14 @@
-use std::{error::Error, sync::mpsc::Sender};
+use std::{collections::HashMap, error::Error, sync::mpsc::Sender};
 
 use evdev::FFEffectData;
 
-use crate::input::output_event::OutputEvent;
+use crate::input::{
+    capability::Capability, output_event::OutputEvent, target::client::TargetDeviceClient,
+};
+
+/// Target devices that input events of each capability are written to
+/// directly while the source device bypasses the composite device
+pub type BypassRoutes = HashMap<Capability, Vec<TargetDeviceClient>>;
 
 /// A [SourceCommand] is a message that can be sent to a [SourceDevice] over
 /// a channel.
@@ -15,5 +21,6 @@ pub enum SourceCommand {
     ),
     UpdateEffect(i16, FFEffectData),
     EraseEffect(i16, Sender<Result<(), Box<dyn Error + Send + Sync>>>),
+    SetBypass(Option<BypassRoutes>),
     Stop,
 }
@@ -0,0 +1,47 @@
+//! Incremental capability discovery for source devices. Some controllers
+//! only send certain reports (e.g. touchpad or IMU) after an enable command
+//! or on first use, so the capabilities reported when the source device is
+//! added can be incomplete. Capabilities of polled events that were not
+//! reported before are discovered and forwarded to the composite device.
+use std::collections::HashSet;
+
+use crate::input::{capability::Capability, event::native::NativeEvent};
+
+/// Tracks the known capabilities of a source device
+#[derive(Debug, Clone, Default)]
+pub struct CapabilityDiscovery {
+    known: HashSet<Capability>,
+}
+
+impl CapabilityDiscovery {
+    /// Create a new discovery with the capabilities the source device
+    /// reported when it was added
+    pub fn new(capabilities: Vec<Capability>) -> Self {
+        Self {
+            known: capabilities.into_iter().collect(),
+        }
+    }
+
+    /// Add the given capabilities and return the ones that were not known
+    pub fn extend(&mut self, capabilities: Vec<Capability>) -> Vec<Capability> {
+        capabilities
+            .into_iter()
+            .filter(|capability| !is_ignored(capability))
+            .filter(|capability| self.known.insert(capability.clone()))
+            .collect()
+    }
+
+    /// Returns the capabilities of the given events that were not known
+    pub fn discover(&mut self, events: &[NativeEvent]) -> Vec<Capability> {
+        let capabilities = events.iter().map(|event| event.as_capability()).collect();
+        self.extend(capabilities)
+    }
+}
+
+/// Returns true if the given capability is never reported as discovered
+fn is_ignored(capability: &Capability) -> bool {
+    matches!(
+        capability,
+        Capability::None | Capability::NotImplemented | Capability::Sync
+    )
+}
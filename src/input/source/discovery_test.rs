@@ -0,0 +1,33 @@
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadButton, Touch, TouchButton, Touchpad},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+use super::discovery::CapabilityDiscovery;
+
+const SOUTH: Capability = Capability::Gamepad(Gamepad::Button(GamepadButton::South));
+const TOUCHPAD: Capability =
+    Capability::Touchpad(Touchpad::CenterPad(Touch::Button(TouchButton::Press)));
+
+#[test]
+fn test_discover_new_capabilities() {
+    let mut discovery = CapabilityDiscovery::new(vec![SOUTH]);
+    let events = vec![
+        NativeEvent::new(SOUTH, InputValue::Bool(true)),
+        NativeEvent::new(TOUCHPAD, InputValue::Bool(true)),
+        NativeEvent::new(TOUCHPAD, InputValue::Bool(false)),
+        NativeEvent::new(Capability::NotImplemented, InputValue::None),
+    ];
+    assert_eq!(discovery.discover(&events), vec![TOUCHPAD]);
+
+    // Capabilities are only discovered once
+    assert!(discovery.discover(&events).is_empty());
+}
+
+#[test]
+fn test_extend_capabilities() {
+    let mut discovery = CapabilityDiscovery::default();
+    let discovered = discovery.extend(vec![Capability::Sync, SOUTH, SOUTH]);
+    assert_eq!(discovered, vec![SOUTH]);
+    assert!(discovery.extend(vec![SOUTH]).is_empty());
+}
@@ -1,23 +1,27 @@
 pub mod blocked;
 pub mod gamepad;
+pub mod joydev;
+#[cfg(test)]
+pub mod joydev_test;
 
 use std::{collections::HashMap, error::Error, time::Duration};
 
 use evdev::{Device, EventType};
 
 use crate::{
-    constants::BUS_SOURCES_PREFIX, input::composite_device::client::CompositeDeviceClient,
+    config, constants::BUS_SOURCES_PREFIX, input::composite_device::client::CompositeDeviceClient,
     udev::device::UdevDevice,
 };
 
-use self::{blocked::BlockedEventDevice, gamepad::GamepadEventDevice};
+use self::{blocked::BlockedEventDevice, gamepad::GamepadEventDevice, joydev::JoydevDevice};
 
-use super::{SourceDeviceCompatible, SourceDriver, SourceDriverOptions};
+use super::{quirks::Quirks, SourceDeviceCompatible, SourceDriver, SourceDriverOptions};
 
 /// List of available drivers
 enum DriverType {
     Blocked,
     Gamepad,
+    Joydev,
 }
 
 /// [EventDevice] represents an input device using the input event subsystem.
@@ -25,6 +29,7 @@ enum DriverType {
 pub enum EventDevice {
     Blocked(SourceDriver<BlockedEventDevice>),
     Gamepad(SourceDriver<GamepadEventDevice>),
+    Joydev(SourceDriver<JoydevDevice>),
 }
 
 impl SourceDeviceCompatible for EventDevice {
@@ -32,6 +37,7 @@ impl SourceDeviceCompatible for EventDevice {
         match self {
             EventDevice::Blocked(source_driver) => source_driver.info_ref(),
             EventDevice::Gamepad(source_driver) => source_driver.info_ref(),
+            EventDevice::Joydev(source_driver) => source_driver.info_ref(),
         }
     }
 
@@ -39,6 +45,7 @@ impl SourceDeviceCompatible for EventDevice {
         match self {
             EventDevice::Blocked(source_driver) => source_driver.get_id(),
             EventDevice::Gamepad(source_driver) => source_driver.get_id(),
+            EventDevice::Joydev(source_driver) => source_driver.get_id(),
         }
     }
 
@@ -46,6 +53,7 @@ impl SourceDeviceCompatible for EventDevice {
         match self {
             EventDevice::Blocked(source_driver) => source_driver.client(),
             EventDevice::Gamepad(source_driver) => source_driver.client(),
+            EventDevice::Joydev(source_driver) => source_driver.client(),
         }
     }
 
@@ -53,6 +61,7 @@ impl SourceDeviceCompatible for EventDevice {
         match self {
             EventDevice::Blocked(source_driver) => source_driver.run().await,
             EventDevice::Gamepad(source_driver) => source_driver.run().await,
+            EventDevice::Joydev(source_driver) => source_driver.run().await,
         }
     }
 
@@ -62,6 +71,17 @@ impl SourceDeviceCompatible for EventDevice {
         match self {
             EventDevice::Blocked(source_driver) => source_driver.get_capabilities(),
             EventDevice::Gamepad(source_driver) => source_driver.get_capabilities(),
+            EventDevice::Joydev(source_driver) => source_driver.get_capabilities(),
+        }
+    }
+
+    fn get_output_capabilities(
+        &self,
+    ) -> Result<Vec<crate::input::output_capability::OutputCapability>, super::OutputError> {
+        match self {
+            EventDevice::Blocked(source_driver) => source_driver.get_output_capabilities(),
+            EventDevice::Gamepad(source_driver) => source_driver.get_output_capabilities(),
+            EventDevice::Joydev(source_driver) => source_driver.get_output_capabilities(),
         }
     }
 
@@ -69,6 +89,7 @@ impl SourceDeviceCompatible for EventDevice {
         match self {
             EventDevice::Blocked(source_driver) => source_driver.get_device_path(),
             EventDevice::Gamepad(source_driver) => source_driver.get_device_path(),
+            EventDevice::Joydev(source_driver) => source_driver.get_device_path(),
         }
     }
 }
@@ -78,10 +99,12 @@ impl EventDevice {
         device_info: UdevDevice,
         composite_device: CompositeDeviceClient,
         is_blocked: bool,
+        config: Option<&config::SourceDevice>,
     ) -> Result<Self, Box<dyn Error + Send + Sync>> {
         let driver_type = EventDevice::get_driver_type(&device_info, is_blocked);
 
-        match driver_type {
+        let quirks = Quirks::new(&device_info, config);
+        let mut device = match driver_type {
             DriverType::Blocked => {
                 let options = SourceDriverOptions {
                     poll_rate: Duration::from_millis(200),
@@ -90,13 +113,30 @@ impl EventDevice {
                 let device = BlockedEventDevice::new(device_info.clone())?;
                 let source_device =
                     SourceDriver::new_with_options(composite_device, device, device_info, options);
-                Ok(Self::Blocked(source_device))
+                Self::Blocked(source_device)
             }
             DriverType::Gamepad => {
                 let device = GamepadEventDevice::new(device_info.clone())?;
                 let source_device = SourceDriver::new(composite_device, device, device_info);
-                Ok(Self::Gamepad(source_device))
+                Self::Gamepad(source_device)
+            }
+            DriverType::Joydev => {
+                let device = JoydevDevice::new(device_info.clone())?;
+                let source_device = SourceDriver::new(composite_device, device, device_info);
+                Self::Joydev(source_device)
             }
+        };
+        device.set_quirks(quirks);
+
+        Ok(device)
+    }
+
+    /// Set the quirks that should be applied to input events from the device
+    fn set_quirks(&mut self, quirks: Quirks) {
+        match self {
+            EventDevice::Blocked(source_driver) => source_driver.set_quirks(quirks),
+            EventDevice::Gamepad(source_driver) => source_driver.set_quirks(quirks),
+            EventDevice::Joydev(source_driver) => source_driver.set_quirks(quirks),
         }
     }
 
@@ -105,6 +145,10 @@ impl EventDevice {
         // TODO: add implmentations for other classes of evdev devices (e.g.
         // driving wheels, touch, etc.)
         log::debug!("Finding driver for interface: {:?}", device);
+        // Joydev nodes cannot be grabbed, so they cannot be blocked either
+        if device.is_joydev() {
+            return DriverType::Joydev;
+        }
         if is_blocked {
             return DriverType::Blocked;
         }
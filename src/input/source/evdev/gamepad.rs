@@ -1,9 +1,13 @@
 use std::fmt::Debug;
-use std::{collections::HashMap, error::Error, os::fd::AsRawFd};
+use std::{
+    collections::HashMap,
+    error::Error,
+    os::fd::{AsRawFd, RawFd},
+};
 
 use evdev::{
-    AbsInfo, AbsoluteAxisCode, Device, EventType, FFEffect, FFEffectData, FFEffectKind, FFReplay,
-    FFTrigger, InputEvent,
+    AbsInfo, AbsoluteAxisCode, Device, EventSummary, EventType, FFEffect, FFEffectData,
+    FFEffectKind, FFReplay, FFTrigger, InputEvent,
 };
 use nix::fcntl::{FcntlArg, OFlag};
 use packed_struct::types::SizedInteger;
@@ -14,6 +18,7 @@ use crate::{
     input::{
         capability::{Capability, Gamepad, GamepadAxis, GamepadButton},
         event::{evdev::EvdevEvent, native::NativeEvent},
+        output_capability::{OutputCapability, LED},
         output_event::OutputEvent,
         source::{InputError, OutputError, SourceInputDevice, SourceOutputDevice},
     },
@@ -38,9 +43,8 @@ impl GamepadEventDevice {
         let mut device = Device::open(path.clone())?;
         device.grab()?;
 
-        // Set the device to do non-blocking reads
-        // TODO: use epoll to wake up when data is available
-        // https://github.com/emberian/evdev/blob/main/examples/evtest_nonblocking.rs
+        // Set the device to do non-blocking reads. The source driver waits for
+        // the device to become readable before polling it.
         let raw_fd = device.as_raw_fd();
         nix::fcntl::fcntl(raw_fd, FcntlArg::F_SETFL(OFlag::O_NONBLOCK))?;
 
@@ -275,6 +279,11 @@ impl SourceInputDevice for GamepadEventDevice {
         Ok(native_events)
     }
 
+    /// Returns the file descriptor of the device to wait for input events
+    fn poll_fd(&self) -> Option<RawFd> {
+        Some(self.device.as_raw_fd())
+    }
+
     /// Returns the possible input events this device is capable of emitting
     fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
         let mut capabilities = vec![];
@@ -357,6 +366,24 @@ impl SourceOutputDevice for GamepadEventDevice {
     fn write_event(&mut self, event: OutputEvent) -> Result<(), OutputError> {
         log::trace!("Received output event: {:?}", event);
 
+        // Forward lock state LED events (e.g. CapsLock) to devices with LEDs
+        if let OutputEvent::Evdev(input_event) = &event {
+            if let EventSummary::Led(_, code, _) = input_event.destructure() {
+                let supported = self
+                    .device
+                    .supported_leds()
+                    .map(|leds| leds.contains(code))
+                    .unwrap_or_default();
+                if !supported {
+                    return Ok(());
+                }
+                if let Err(e) = self.device.send_events(&[*input_event]) {
+                    log::error!("Failed to write LED event: {:?}", e);
+                }
+                return Ok(());
+            }
+        }
+
         // Only process output events if FF is supported
         let force_feedback = self.device.supported_ff();
         if force_feedback.is_none() {
@@ -445,6 +472,22 @@ impl SourceOutputDevice for GamepadEventDevice {
         self.ff_effects.remove(&effect_id);
         Ok(())
     }
+
+    /// Returns the possible output events this device is capable of handling
+    fn get_output_capabilities(&self) -> Result<Vec<OutputCapability>, OutputError> {
+        let mut capabilities = vec![];
+        if let Some(ff) = self.device.supported_ff() {
+            if ff.iter().count() > 0 {
+                capabilities.push(OutputCapability::ForceFeedback);
+            }
+        }
+        if let Some(leds) = self.device.supported_leds() {
+            if leds.iter().count() > 0 {
+                capabilities.push(OutputCapability::LED(LED::Indicator));
+            }
+        }
+        Ok(capabilities)
+    }
 }
 
 impl Debug for GamepadEventDevice {
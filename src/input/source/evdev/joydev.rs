@@ -0,0 +1,276 @@
+//! Fallback source implementation for devices that only expose a legacy
+//! joystick node (e.g. "/dev/input/js0") and no event node. Some unusual
+//! drivers only register with the joydev handler, so their input can only be
+//! read as `js_event` structures. The joydev axis and button maps are used to
+//! convert these events into evdev events, so they are translated the same
+//! way as events from evdev gamepads.
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::Debug,
+    fs::{File, OpenOptions},
+    io::{ErrorKind, Read},
+    os::{
+        fd::{AsRawFd, RawFd},
+        unix::fs::OpenOptionsExt,
+    },
+};
+
+use evdev::{AbsInfo, AbsoluteAxisCode, EventType, InputEvent, KeyCode};
+
+use crate::{
+    input::{
+        capability::{Capability, Gamepad, GamepadAxis, GamepadButton},
+        event::{evdev::EvdevEvent, native::NativeEvent},
+        source::{InputError, SourceInputDevice, SourceOutputDevice},
+    },
+    udev::device::UdevDevice,
+};
+
+/// Button pressed or released
+pub const JS_EVENT_BUTTON: u8 = 0x01;
+/// Joystick moved
+pub const JS_EVENT_AXIS: u8 = 0x02;
+/// Initial state of the device
+pub const JS_EVENT_INIT: u8 = 0x80;
+/// Maximum absolute value of joydev axis events
+pub const JS_AXIS_MAX: i32 = 32767;
+
+/// Size of the joydev axis map (ABS_CNT)
+const AXIS_MAP_SIZE: usize = 0x40;
+/// Size of the joydev button map (KEY_MAX - BTN_MISC + 1)
+const BUTTON_MAP_SIZE: usize = 0x200;
+/// Maximum number of events read in a single poll
+const READ_EVENTS: usize = 64;
+
+nix::ioctl_read!(jsiocgaxes, b'j', 0x11, u8);
+nix::ioctl_read!(jsiocgbuttons, b'j', 0x12, u8);
+nix::ioctl_read_buf!(jsiocgaxmap, b'j', 0x32, u8);
+nix::ioctl_read_buf!(jsiocgbtnmap, b'j', 0x34, u16);
+
+/// A single `js_event` read from a joydev node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JsEvent {
+    /// Event timestamp in milliseconds
+    pub time: u32,
+    /// Axis position or button state
+    pub value: i16,
+    /// Event type, e.g. [JS_EVENT_BUTTON]
+    pub kind: u8,
+    /// Axis or button number
+    pub number: u8,
+}
+
+impl JsEvent {
+    /// Size of a `js_event` in bytes
+    pub const SIZE: usize = 8;
+
+    /// Parse an event from its in-memory representation
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        Self {
+            time: u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            value: i16::from_ne_bytes([bytes[4], bytes[5]]),
+            kind: bytes[6],
+            number: bytes[7],
+        }
+    }
+}
+
+/// Maps joydev axis and button numbers to their evdev codes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JoydevMapping {
+    pub axes: Vec<AbsoluteAxisCode>,
+    pub buttons: Vec<KeyCode>,
+}
+
+impl JoydevMapping {
+    /// Query the axis and button maps from the given joydev node
+    fn from_fd(fd: RawFd) -> nix::Result<Self> {
+        let mut axis_count = 0;
+        let mut button_count = 0;
+        let mut axis_map = [0u8; AXIS_MAP_SIZE];
+        let mut button_map = [0u16; BUTTON_MAP_SIZE];
+        unsafe {
+            jsiocgaxes(fd, &mut axis_count)?;
+            jsiocgbuttons(fd, &mut button_count)?;
+            jsiocgaxmap(fd, &mut axis_map)?;
+            jsiocgbtnmap(fd, &mut button_map)?;
+        }
+
+        let axes = axis_map[..axis_count as usize]
+            .iter()
+            .map(|code| AbsoluteAxisCode(*code as u16))
+            .collect();
+        let buttons = button_map[..button_count as usize]
+            .iter()
+            .map(|code| KeyCode::new(*code))
+            .collect();
+
+        Ok(Self { axes, buttons })
+    }
+
+    /// Convert the given joydev event into an evdev event with axis
+    /// information to normalize its value.
+    pub fn to_evdev(&self, event: JsEvent) -> Option<EvdevEvent> {
+        let value = event.value as i32;
+        match event.kind & !JS_EVENT_INIT {
+            JS_EVENT_BUTTON => {
+                let code = self.buttons.get(event.number as usize)?;
+                let event = InputEvent::new(EventType::KEY.0, code.0, value);
+                Some(event.into())
+            }
+            JS_EVENT_AXIS => {
+                let axis = *self.axes.get(event.number as usize)?;
+                let (value, info) = match axis {
+                    // Hat switches are scaled to the full joydev range
+                    AbsoluteAxisCode::ABS_HAT0X | AbsoluteAxisCode::ABS_HAT0Y => {
+                        (value.signum(), AbsInfo::new(0, -1, 1, 0, 0, 0))
+                    }
+                    // Triggers rest at the joydev minimum
+                    AbsoluteAxisCode::ABS_Z | AbsoluteAxisCode::ABS_RZ => (
+                        (value + JS_AXIS_MAX) / 2,
+                        AbsInfo::new(0, 0, JS_AXIS_MAX, 0, 0, 0),
+                    ),
+                    _ => (value, AbsInfo::new(0, -JS_AXIS_MAX, JS_AXIS_MAX, 0, 0, 0)),
+                };
+                let event = InputEvent::new(EventType::ABSOLUTE.0, axis.0, value);
+                let mut event: EvdevEvent = event.into();
+                event.set_abs_info(info);
+                Some(event)
+            }
+            _ => None,
+        }
+    }
+
+    /// Translate the given joydev event into a native event. The last value of
+    /// each hat axis is tracked in the given state to detect DPad releases.
+    pub fn translate(
+        &self,
+        event: JsEvent,
+        hat_state: &mut HashMap<AbsoluteAxisCode, i32>,
+    ) -> Option<NativeEvent> {
+        log::trace!("Received event: {:?}", event);
+        let evdev_event = self.to_evdev(event)?;
+        let input_event = evdev_event.as_input_event();
+
+        let state = match input_event.event_type() {
+            EventType::ABSOLUTE => match AbsoluteAxisCode(input_event.code()) {
+                axis @ (AbsoluteAxisCode::ABS_HAT0X | AbsoluteAxisCode::ABS_HAT0Y) => {
+                    hat_state.insert(axis, input_event.value()).or(Some(0))
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+
+        Some(NativeEvent::from_evdev_raw(evdev_event, state))
+    }
+
+    /// Returns the capabilities of the mapped axes and buttons
+    pub fn capabilities(&self) -> Vec<Capability> {
+        let buttons = self
+            .buttons
+            .iter()
+            .map(|button| InputEvent::new(EventType::KEY.0, button.0, 0));
+        let axes = self
+            .axes
+            .iter()
+            .map(|axis| InputEvent::new(EventType::ABSOLUTE.0, axis.0, 0));
+
+        let mut capabilities = vec![];
+        for event in buttons.chain(axes) {
+            let cap = EvdevEvent::from(event).as_capability();
+            let caps = if cap == Capability::Gamepad(Gamepad::Axis(GamepadAxis::Hat0)) {
+                vec![
+                    Capability::Gamepad(Gamepad::Button(GamepadButton::DPadUp)),
+                    Capability::Gamepad(Gamepad::Button(GamepadButton::DPadDown)),
+                    Capability::Gamepad(Gamepad::Button(GamepadButton::DPadLeft)),
+                    Capability::Gamepad(Gamepad::Button(GamepadButton::DPadRight)),
+                ]
+            } else {
+                vec![cap]
+            };
+            for cap in caps {
+                if !capabilities.contains(&cap) {
+                    capabilities.push(cap);
+                }
+            }
+        }
+
+        capabilities
+    }
+}
+
+/// Source device implementation for legacy joydev (js*) nodes
+pub struct JoydevDevice {
+    file: File,
+    mapping: JoydevMapping,
+    hat_state: HashMap<AbsoluteAxisCode, i32>,
+}
+
+impl JoydevDevice {
+    /// Create a new [JoydevDevice] source device from the given udev info
+    pub fn new(device_info: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let path = device_info.devnode();
+        log::debug!("Opening joydev device at: {}", path);
+
+        // Joydev nodes cannot be grabbed, so input will still be visible to
+        // other readers of the node.
+        let file = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(path.as_str())?;
+        let mapping = JoydevMapping::from_fd(file.as_raw_fd())?;
+        log::debug!("Found joydev mapping for {path}: {mapping:?}");
+
+        Ok(Self {
+            file,
+            mapping,
+            hat_state: HashMap::new(),
+        })
+    }
+}
+
+impl SourceInputDevice for JoydevDevice {
+    /// Poll the given input device for input events
+    fn poll(&mut self) -> Result<Vec<NativeEvent>, InputError> {
+        // Joydev always returns whole events
+        let mut buffer = [0; JsEvent::SIZE * READ_EVENTS];
+        let size = match self.file.read(&mut buffer) {
+            Ok(size) => size,
+            Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(vec![]),
+            Err(e) => return Err(format!("Failed to read events: {e:?}").into()),
+        };
+
+        let events = buffer[..size]
+            .chunks_exact(JsEvent::SIZE)
+            .filter_map(|chunk| {
+                let event = JsEvent::from_bytes(chunk.try_into().ok()?);
+                self.mapping.translate(event, &mut self.hat_state)
+            })
+            .collect();
+
+        Ok(events)
+    }
+
+    /// Returns the file descriptor of the device to wait for input events
+    fn poll_fd(&self) -> Option<RawFd> {
+        Some(self.file.as_raw_fd())
+    }
+
+    /// Returns the possible input events this device is capable of emitting
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(self.mapping.capabilities())
+    }
+}
+
+impl SourceOutputDevice for JoydevDevice {}
+
+impl Debug for JoydevDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JoydevDevice")
+            .field("mapping", &self.mapping)
+            .field("hat_state", &self.hat_state)
+            .finish()
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use evdev::{AbsoluteAxisCode, KeyCode};
+
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadAxis, GamepadButton, GamepadTrigger},
+    event::value::InputValue,
+};
+
+use super::joydev::{JoydevMapping, JsEvent, JS_EVENT_AXIS, JS_EVENT_BUTTON, JS_EVENT_INIT};
+
+fn mapping() -> JoydevMapping {
+    JoydevMapping {
+        axes: vec![
+            AbsoluteAxisCode::ABS_X,
+            AbsoluteAxisCode::ABS_Y,
+            AbsoluteAxisCode::ABS_Z,
+            AbsoluteAxisCode::ABS_HAT0X,
+            AbsoluteAxisCode::ABS_HAT0Y,
+        ],
+        buttons: vec![KeyCode::BTN_SOUTH, KeyCode::BTN_EAST],
+    }
+}
+
+fn event(kind: u8, number: u8, value: i16) -> JsEvent {
+    JsEvent {
+        time: 0,
+        value,
+        kind,
+        number,
+    }
+}
+
+#[test]
+fn test_parse_event() {
+    let mut bytes = [0; JsEvent::SIZE];
+    bytes[..4].copy_from_slice(&1234u32.to_ne_bytes());
+    bytes[4..6].copy_from_slice(&(-200i16).to_ne_bytes());
+    bytes[6] = JS_EVENT_AXIS;
+    bytes[7] = 3;
+
+    let event = JsEvent::from_bytes(&bytes);
+    assert_eq!(event.time, 1234);
+    assert_eq!(event.value, -200);
+    assert_eq!(event.kind, JS_EVENT_AXIS);
+    assert_eq!(event.number, 3);
+}
+
+#[test]
+fn test_translate_button() {
+    let mapping = mapping();
+    let mut hat_state = HashMap::new();
+
+    let native = mapping
+        .translate(event(JS_EVENT_BUTTON, 1, 1), &mut hat_state)
+        .unwrap();
+    assert_eq!(
+        native.as_capability(),
+        Capability::Gamepad(Gamepad::Button(GamepadButton::East))
+    );
+    assert!(matches!(native.get_value(), InputValue::Bool(true)));
+
+    // Initial state events are translated like regular events
+    let native = mapping
+        .translate(event(JS_EVENT_BUTTON | JS_EVENT_INIT, 0, 0), &mut hat_state)
+        .unwrap();
+    assert_eq!(
+        native.as_capability(),
+        Capability::Gamepad(Gamepad::Button(GamepadButton::South))
+    );
+    assert!(matches!(native.get_value(), InputValue::Bool(false)));
+
+    // Unmapped buttons are ignored
+    assert!(mapping
+        .translate(event(JS_EVENT_BUTTON, 2, 1), &mut hat_state)
+        .is_none());
+}
+
+#[test]
+fn test_translate_axes() {
+    let mapping = mapping();
+    let mut hat_state = HashMap::new();
+
+    let native = mapping
+        .translate(event(JS_EVENT_AXIS, 0, -32767), &mut hat_state)
+        .unwrap();
+    assert_eq!(
+        native.as_capability(),
+        Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick))
+    );
+    assert!(matches!(
+        native.get_value(),
+        InputValue::Vector2 { x: Some(x), y: None } if x == -1.0
+    ));
+
+    // Triggers rest at the minimum joydev value
+    let native = mapping
+        .translate(event(JS_EVENT_AXIS, 2, -32767), &mut hat_state)
+        .unwrap();
+    assert_eq!(
+        native.as_capability(),
+        Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTrigger))
+    );
+    assert!(matches!(native.get_value(), InputValue::Float(value) if value == 0.0));
+    let native = mapping
+        .translate(event(JS_EVENT_AXIS, 2, 32767), &mut hat_state)
+        .unwrap();
+    assert!(matches!(native.get_value(), InputValue::Float(value) if value == 1.0));
+}
+
+#[test]
+fn test_translate_hat() {
+    let mapping = mapping();
+    let mut hat_state = HashMap::new();
+
+    let native = mapping
+        .translate(event(JS_EVENT_AXIS, 3, -32767), &mut hat_state)
+        .unwrap();
+    assert_eq!(
+        native.as_capability(),
+        Capability::Gamepad(Gamepad::Button(GamepadButton::DPadLeft))
+    );
+    assert!(matches!(native.get_value(), InputValue::Bool(true)));
+
+    // Releasing the hat releases the last pressed direction
+    let native = mapping
+        .translate(event(JS_EVENT_AXIS, 3, 0), &mut hat_state)
+        .unwrap();
+    assert_eq!(
+        native.as_capability(),
+        Capability::Gamepad(Gamepad::Button(GamepadButton::DPadLeft))
+    );
+    assert!(matches!(native.get_value(), InputValue::Bool(false)));
+
+    let native = mapping
+        .translate(event(JS_EVENT_AXIS, 4, 32767), &mut hat_state)
+        .unwrap();
+    assert_eq!(
+        native.as_capability(),
+        Capability::Gamepad(Gamepad::Button(GamepadButton::DPadDown))
+    );
+}
+
+#[test]
+fn test_capabilities() {
+    let capabilities = mapping().capabilities();
+    assert_eq!(
+        capabilities,
+        vec![
+            Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::East)),
+            Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+            Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTrigger)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadUp)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadDown)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadLeft)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadRight)),
+        ]
+    );
+}
@@ -0,0 +1,88 @@
+pub mod protocol;
+#[cfg(test)]
+pub mod protocol_test;
+pub mod server;
+pub mod socket;
+
+use std::error::Error;
+
+use crate::{
+    config, input::composite_device::client::CompositeDeviceClient, udev::device::UdevDevice,
+};
+
+use self::socket::SocketDevice;
+
+use super::{quirks::Quirks, SourceDeviceCompatible, SourceDriver};
+
+/// [ExternalDevice] represents an input device provided by an external driver
+/// process.
+#[derive(Debug)]
+pub enum ExternalDevice {
+    Socket(SourceDriver<SocketDevice>),
+}
+
+impl SourceDeviceCompatible for ExternalDevice {
+    fn get_device_ref(&self) -> &UdevDevice {
+        match self {
+            ExternalDevice::Socket(source_driver) => source_driver.info_ref(),
+        }
+    }
+
+    fn get_id(&self) -> String {
+        match self {
+            ExternalDevice::Socket(source_driver) => source_driver.get_id(),
+        }
+    }
+
+    fn client(&self) -> super::client::SourceDeviceClient {
+        match self {
+            ExternalDevice::Socket(source_driver) => source_driver.client(),
+        }
+    }
+
+    async fn run(self) -> Result<(), Box<dyn Error>> {
+        match self {
+            ExternalDevice::Socket(source_driver) => source_driver.run().await,
+        }
+    }
+
+    fn get_capabilities(
+        &self,
+    ) -> Result<Vec<crate::input::capability::Capability>, super::InputError> {
+        match self {
+            ExternalDevice::Socket(source_driver) => source_driver.get_capabilities(),
+        }
+    }
+
+    fn get_output_capabilities(
+        &self,
+    ) -> Result<Vec<crate::input::output_capability::OutputCapability>, super::OutputError> {
+        match self {
+            ExternalDevice::Socket(source_driver) => source_driver.get_output_capabilities(),
+        }
+    }
+
+    fn get_device_path(&self) -> String {
+        match self {
+            ExternalDevice::Socket(source_driver) => source_driver.get_device_path(),
+        }
+    }
+}
+
+impl ExternalDevice {
+    /// Create a new [ExternalDevice] associated with the given device and
+    /// composite device. The device must have been registered by an external
+    /// driver connected to the driver socket.
+    pub fn new(
+        device_info: UdevDevice,
+        composite_device: CompositeDeviceClient,
+        config: Option<&config::SourceDevice>,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let quirks = Quirks::new(&device_info, config);
+        let device = SocketDevice::new(device_info.clone())?;
+        let mut source_device = SourceDriver::new(composite_device, device, device_info);
+        source_device.set_quirks(quirks);
+
+        Ok(Self::Socket(source_device))
+    }
+}
@@ -0,0 +1,228 @@
+//! Wire protocol used by external driver processes. Messages are encoded as
+//! JSON objects, one message per line, with a "type" field describing the
+//! kind of message.
+//!
+//! A driver connects to the driver socket and must send a "register" message
+//! describing the device before sending any other message:
+//!
+//! ```json
+//! {"type": "register", "version": 1, "name": "My Gamepad", "capabilities": ["Gamepad:Button:South"]}
+//! ```
+//!
+//! InputPlumber responds with a "registered" message containing the id of the
+//! new source device, after which the driver can stream input events:
+//!
+//! ```json
+//! {"type": "event", "capability": "Gamepad:Button:South", "value": true}
+//! ```
+//!
+//! Drivers must reply to each "ping" message with a "pong" (or any other
+//! message) to keep the connection alive.
+use std::{error::Error, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::input::{
+    capability::Capability,
+    event::{native::NativeEvent, value::InputValue},
+    output_capability::OutputCapability,
+};
+
+/// Version of the external driver protocol
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Messages sent from an external driver process to InputPlumber
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DriverMessage {
+    /// Register the driver as a new source device
+    Register {
+        version: u32,
+        name: String,
+        vendor_id: Option<u16>,
+        product_id: Option<u16>,
+        /// Input capabilities (e.g. "Gamepad:Button:South")
+        capabilities: Vec<String>,
+        /// Output capabilities the driver can handle (e.g. "ForceFeedback")
+        #[serde(default)]
+        output_capabilities: Vec<String>,
+    },
+    /// An input event emitted by the device
+    Event {
+        capability: String,
+        value: Option<EventValue>,
+    },
+    /// Response to a [ServerMessage::Ping]
+    Pong,
+}
+
+/// Messages sent from InputPlumber to an external driver process
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// The driver was registered as a source device with the given id
+    Registered { id: String },
+    /// The last message from the driver could not be handled
+    Error { message: String },
+    /// Keepalive request. The driver must respond before the keepalive
+    /// timeout expires or it will be disconnected.
+    Ping,
+    /// Play a rumble effect with the given motor strengths. A magnitude of
+    /// zero for both motors stops the rumble.
+    Rumble {
+        strong_magnitude: u16,
+        weak_magnitude: u16,
+    },
+    /// The source device was stopped and the connection will be closed
+    Stop,
+}
+
+/// Value of an input event sent by an external driver
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EventValue {
+    Bool(bool),
+    Float(f64),
+    Touch {
+        index: u8,
+        is_touching: bool,
+        pressure: Option<f64>,
+        x: Option<f64>,
+        y: Option<f64>,
+    },
+    Vector3 {
+        x: Option<f64>,
+        y: Option<f64>,
+        z: f64,
+    },
+    Vector2 {
+        x: Option<f64>,
+        y: Option<f64>,
+    },
+}
+
+impl From<EventValue> for InputValue {
+    fn from(value: EventValue) -> Self {
+        match value {
+            EventValue::Bool(value) => InputValue::Bool(value),
+            EventValue::Float(value) => InputValue::Float(value),
+            EventValue::Touch {
+                index,
+                is_touching,
+                pressure,
+                x,
+                y,
+            } => InputValue::Touch {
+                index,
+                is_touching,
+                pressure,
+                x,
+                y,
+            },
+            EventValue::Vector3 { x, y, z } => InputValue::Vector3 { x, y, z: Some(z) },
+            EventValue::Vector2 { x, y } => InputValue::Vector2 { x, y },
+        }
+    }
+}
+
+impl EventValue {
+    /// Convert the given value into an event value. Vector3 values without a
+    /// Z component are sent with a Z of zero.
+    pub fn from_input_value(value: InputValue) -> Option<Self> {
+        match value {
+            InputValue::None => None,
+            InputValue::Bool(value) => Some(EventValue::Bool(value)),
+            InputValue::Float(value) => Some(EventValue::Float(value)),
+            InputValue::Vector2 { x, y } => Some(EventValue::Vector2 { x, y }),
+            InputValue::Vector3 { x, y, z } => Some(EventValue::Vector3 {
+                x,
+                y,
+                z: z.unwrap_or_default(),
+            }),
+            InputValue::Touch {
+                index,
+                is_touching,
+                pressure,
+                x,
+                y,
+            } => Some(EventValue::Touch {
+                index,
+                is_touching,
+                pressure,
+                x,
+                y,
+            }),
+        }
+    }
+}
+
+/// Decode a single line received from an external driver
+pub fn decode(line: &str) -> Result<DriverMessage, Box<dyn Error + Send + Sync>> {
+    Ok(serde_json::from_str(line.trim())?)
+}
+
+/// Encode the given message as a single newline-terminated line
+pub fn encode(message: &ServerMessage) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    Ok(line)
+}
+
+/// Decode a single line received from InputPlumber on the driver side of
+/// the connection
+pub fn decode_server(line: &str) -> Result<ServerMessage, Box<dyn Error + Send + Sync>> {
+    Ok(serde_json::from_str(line.trim())?)
+}
+
+/// Encode the given driver message as a single newline-terminated line
+pub fn encode_driver(message: &DriverMessage) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    Ok(line)
+}
+
+/// Parse the list of capability strings sent by a driver during registration
+pub fn parse_capabilities(
+    capabilities: &[String],
+) -> Result<Vec<Capability>, Box<dyn Error + Send + Sync>> {
+    capabilities
+        .iter()
+        .map(|cap| match Capability::from_str(cap.as_str()) {
+            Ok(Capability::DBus(_)) | Err(_) => Err(format!("invalid capability '{cap}'").into()),
+            Ok(capability) => Ok(capability),
+        })
+        .collect()
+}
+
+/// Parse the list of output capability strings sent by a driver during
+/// registration. Only force feedback is currently supported.
+pub fn parse_output_capabilities(
+    capabilities: &[String],
+) -> Result<Vec<OutputCapability>, Box<dyn Error + Send + Sync>> {
+    capabilities
+        .iter()
+        .map(|cap| match cap.as_str() {
+            "ForceFeedback" => Ok(OutputCapability::ForceFeedback),
+            _ => Err(format!("unsupported output capability '{cap}'").into()),
+        })
+        .collect()
+}
+
+/// Convert the given event message into a [NativeEvent]
+pub fn to_native_event(
+    capability: &str,
+    value: Option<EventValue>,
+) -> Result<NativeEvent, Box<dyn Error + Send + Sync>> {
+    let capability = Capability::from_str(capability)
+        .map_err(|_| format!("invalid capability '{capability}'"))?;
+    let value = value.map(InputValue::from).unwrap_or(InputValue::None);
+    Ok(NativeEvent::new(capability, value))
+}
+
+/// Convert the given [NativeEvent] into an event message
+pub fn from_native_event(event: &NativeEvent) -> DriverMessage {
+    DriverMessage::Event {
+        capability: event.as_capability().to_string(),
+        value: EventValue::from_input_value(event.get_value()),
+    }
+}
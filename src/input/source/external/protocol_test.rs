@@ -0,0 +1,192 @@
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadAxis, GamepadButton},
+    event::{native::NativeEvent, value::InputValue},
+    output_capability::OutputCapability,
+};
+
+use super::protocol::{
+    decode, decode_server, encode, encode_driver, from_native_event, parse_capabilities,
+    parse_output_capabilities, to_native_event, DriverMessage, EventValue, ServerMessage,
+    PROTOCOL_VERSION,
+};
+
+#[test]
+fn test_decode_register() {
+    let line = r#"{"type": "register", "version": 1, "name": "Test Pad", "vendor_id": 1234, "capabilities": ["Gamepad:Button:South"]}"#;
+    let message = decode(line).unwrap();
+    assert_eq!(
+        message,
+        DriverMessage::Register {
+            version: PROTOCOL_VERSION,
+            name: "Test Pad".to_string(),
+            vendor_id: Some(1234),
+            product_id: None,
+            capabilities: vec!["Gamepad:Button:South".to_string()],
+            output_capabilities: vec![],
+        }
+    );
+}
+
+#[test]
+fn test_decode_event_values() {
+    let cases = [
+        (r#"true"#, EventValue::Bool(true)),
+        (r#"0.5"#, EventValue::Float(0.5)),
+        (
+            r#"{"x": 0.25, "y": -1.0}"#,
+            EventValue::Vector2 {
+                x: Some(0.25),
+                y: Some(-1.0),
+            },
+        ),
+        (
+            r#"{"x": 1.0, "y": 2.0, "z": 3.0}"#,
+            EventValue::Vector3 {
+                x: Some(1.0),
+                y: Some(2.0),
+                z: 3.0,
+            },
+        ),
+        (
+            r#"{"index": 1, "is_touching": true, "x": 0.5, "y": 0.5}"#,
+            EventValue::Touch {
+                index: 1,
+                is_touching: true,
+                pressure: None,
+                x: Some(0.5),
+                y: Some(0.5),
+            },
+        ),
+    ];
+    for (value, expected) in cases {
+        let line = format!(
+            r#"{{"type": "event", "capability": "Gamepad:Button:South", "value": {value}}}"#
+        );
+        let DriverMessage::Event { value, .. } = decode(line.as_str()).unwrap() else {
+            panic!("expected an event message");
+        };
+        assert_eq!(value, Some(expected), "failed to decode value");
+    }
+}
+
+#[test]
+fn test_decode_invalid() {
+    assert!(decode("not json").is_err());
+    assert!(decode(r#"{"type": "unknown"}"#).is_err());
+}
+
+#[test]
+fn test_encode() {
+    let line = encode(&ServerMessage::Rumble {
+        strong_magnitude: 100,
+        weak_magnitude: 0,
+    })
+    .unwrap();
+    assert_eq!(
+        line,
+        "{\"type\":\"rumble\",\"strong_magnitude\":100,\"weak_magnitude\":0}\n"
+    );
+    assert_eq!(
+        encode(&ServerMessage::Ping).unwrap(),
+        "{\"type\":\"ping\"}\n"
+    );
+}
+
+#[test]
+fn test_parse_capabilities() {
+    let caps = vec![
+        "Gamepad:Button:South".to_string(),
+        "Gamepad:Axis:LeftStick".to_string(),
+    ];
+    assert_eq!(
+        parse_capabilities(&caps).unwrap(),
+        vec![
+            Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+            Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+        ]
+    );
+    assert!(parse_capabilities(&["Gamepad:Button:Invalid".to_string()]).is_err());
+
+    assert_eq!(
+        parse_output_capabilities(&["ForceFeedback".to_string()]).unwrap(),
+        vec![OutputCapability::ForceFeedback]
+    );
+    assert!(parse_output_capabilities(&["LED".to_string()]).is_err());
+}
+
+#[test]
+fn test_to_native_event() {
+    let event = to_native_event("Gamepad:Button:South", Some(EventValue::Bool(true))).unwrap();
+    assert_eq!(
+        event.as_capability(),
+        Capability::Gamepad(Gamepad::Button(GamepadButton::South))
+    );
+    assert!(matches!(event.get_value(), InputValue::Bool(true)));
+
+    let event = to_native_event("Gamepad:Button:South", None).unwrap();
+    assert!(matches!(event.get_value(), InputValue::None));
+
+    assert!(to_native_event("Invalid", None).is_err());
+}
+
+#[test]
+fn test_from_native_event() {
+    let event = NativeEvent::new(
+        Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+        InputValue::Vector2 {
+            x: Some(0.5),
+            y: None,
+        },
+    );
+    let message = from_native_event(&event);
+    assert_eq!(
+        message,
+        DriverMessage::Event {
+            capability: "Gamepad:Axis:LeftStick".to_string(),
+            value: Some(EventValue::Vector2 {
+                x: Some(0.5),
+                y: None,
+            }),
+        }
+    );
+
+    // Events sent by a network target device must decode on the other end
+    let line = encode_driver(&message).unwrap();
+    assert!(line.ends_with('\n'));
+    let DriverMessage::Event { capability, value } = decode(line.trim_end()).unwrap() else {
+        panic!("expected an event message");
+    };
+    let decoded = to_native_event(capability.as_str(), value).unwrap();
+    assert_eq!(decoded.as_capability(), event.as_capability());
+
+    let event = NativeEvent::new(
+        Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+        InputValue::None,
+    );
+    assert_eq!(
+        from_native_event(&event),
+        DriverMessage::Event {
+            capability: "Gamepad:Button:South".to_string(),
+            value: None,
+        }
+    );
+}
+
+#[test]
+fn test_decode_server() {
+    assert_eq!(
+        decode_server(r#"{"type": "ping"}"#).unwrap(),
+        ServerMessage::Ping
+    );
+    let line = encode(&ServerMessage::Registered {
+        id: "external0".to_string(),
+    })
+    .unwrap();
+    assert_eq!(
+        decode_server(line.as_str()).unwrap(),
+        ServerMessage::Registered {
+            id: "external0".to_string()
+        }
+    );
+    assert!(decode_server(r#"{"type": "pong"}"#).is_err());
+}
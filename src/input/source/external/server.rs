@@ -0,0 +1,283 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs,
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+use tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, Lines},
+    net::UnixListener,
+    sync::mpsc::{self, error::TrySendError},
+    time::{interval, timeout},
+};
+
+use crate::{
+    input::{
+        capability::Capability, event::native::NativeEvent, manager::ManagerCommand,
+        output_capability::OutputCapability,
+    },
+    udev::device::UdevDevice,
+};
+
+use super::protocol::{self, DriverMessage, ServerMessage, PROTOCOL_VERSION};
+
+/// Default path to the socket external drivers connect to
+pub const DRIVER_SOCKET_PATH: &str = "/run/inputplumber/drivers.sock";
+/// Size of the event and output channels for each connected driver
+const BUFFER_SIZE: usize = 2048;
+/// Time a driver has to register after connecting
+const REGISTER_TIMEOUT: Duration = Duration::from_secs(5);
+/// Interval between keepalive pings sent to a driver
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(5);
+/// Time without any message from a driver before it is disconnected
+const KEEPALIVE_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Connections from registered drivers that have not yet been claimed by a
+/// source device, keyed by sysname (e.g. "external0").
+static CONNECTIONS: OnceLock<Mutex<HashMap<String, DriverConnection>>> = OnceLock::new();
+/// Counter used to assign a unique sysname to each registered driver
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A [DriverConnection] is the source device side of a registered external
+/// driver. Events from the driver are received on the `events` channel and
+/// output messages are sent to the driver over the `output` channel.
+#[derive(Debug)]
+pub struct DriverConnection {
+    pub capabilities: Vec<Capability>,
+    pub output_capabilities: Vec<OutputCapability>,
+    pub events: mpsc::Receiver<NativeEvent>,
+    pub output: mpsc::Sender<ServerMessage>,
+}
+
+/// Take the connection of the registered driver with the given sysname so
+/// it can be used by a source device. A connection can only be taken once.
+pub fn take_connection(sysname: &str) -> Option<DriverConnection> {
+    let connections = CONNECTIONS.get_or_init(Default::default);
+    connections.lock().unwrap().remove(sysname)
+}
+
+/// Listen for external driver processes on the driver socket. The socket path
+/// can be overridden with the `INPUTPLUMBER_DRIVER_SOCKET` environment
+/// variable. Each registered driver is announced to the manager as a new
+/// source device, and removed again when the driver disconnects or stops
+/// responding.
+pub async fn listen(tx: mpsc::Sender<ManagerCommand>) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let path = std::env::var("INPUTPLUMBER_DRIVER_SOCKET")
+        .unwrap_or_else(|_| DRIVER_SOCKET_PATH.to_string());
+    let path = Path::new(path.as_str());
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // Remove any stale socket from a previous run
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+
+    let listener = UnixListener::bind(path)?;
+    log::info!("Listening for external drivers on {}", path.display());
+
+    loop {
+        let (stream, _) = match listener.accept().await {
+            Ok(conn) => conn,
+            Err(e) => {
+                log::error!("Failed to accept external driver connection: {e:?}");
+                continue;
+            }
+        };
+        let tx = tx.clone();
+        tokio::task::spawn(async move {
+            if let Err(e) = handle_connection(stream, tx).await {
+                log::warn!("External driver connection closed with error: {e}");
+            }
+        });
+    }
+}
+
+/// Register and supervise a single driver connection until it disconnects,
+/// stops responding, or its source device is stopped. Connections can use
+/// any stream transport, such as the driver socket or a network connection.
+pub async fn handle_connection<S>(
+    stream: S,
+    tx: mpsc::Sender<ManagerCommand>,
+) -> Result<(), Box<dyn Error + Send + Sync>>
+where
+    S: AsyncRead + AsyncWrite + Send,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+
+    // The first message must be a registration
+    let line = match timeout(REGISTER_TIMEOUT, lines.next_line()).await {
+        Ok(Ok(Some(line))) => line,
+        Ok(Ok(None)) => return Ok(()),
+        Ok(Err(e)) => return Err(e.into()),
+        Err(_) => {
+            send(&mut writer, &error_message("registration timed out")).await?;
+            return Err("driver did not register in time".into());
+        }
+    };
+    let (device, capabilities, output_capabilities) = match register(line.as_str()) {
+        Ok(registration) => registration,
+        Err(e) => {
+            send(&mut writer, &error_message(e.to_string().as_str())).await?;
+            return Err(e);
+        }
+    };
+
+    let sysname = device.sysname();
+    let id = device.get_id();
+    log::info!("External driver '{}' registered as {id}", device.name());
+
+    // Store the source device side of the connection until a source device
+    // claims it.
+    let (event_tx, event_rx) = mpsc::channel(BUFFER_SIZE);
+    let (output_tx, mut output_rx) = mpsc::channel(BUFFER_SIZE);
+    let connection = DriverConnection {
+        capabilities,
+        output_capabilities,
+        events: event_rx,
+        output: output_tx,
+    };
+    CONNECTIONS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap()
+        .insert(sysname.clone(), connection);
+
+    send(&mut writer, &ServerMessage::Registered { id: id.clone() }).await?;
+    tx.send(ManagerCommand::DeviceAdded {
+        device: device.clone(),
+    })
+    .await?;
+
+    let result = supervise(&mut lines, &mut writer, &event_tx, &mut output_rx).await;
+
+    // Clean up the connection and notify the manager that the device is gone
+    log::info!("External driver {id} disconnected");
+    take_connection(sysname.as_str());
+    let _ = send(&mut writer, &ServerMessage::Stop).await;
+    tx.send(ManagerCommand::DeviceRemoved { device }).await?;
+
+    result
+}
+
+/// Forward events and output messages between the driver and its source
+/// device, disconnecting the driver if it stops responding to pings.
+async fn supervise(
+    lines: &mut Lines<impl AsyncBufRead + Unpin>,
+    writer: &mut (impl AsyncWrite + Unpin),
+    event_tx: &mpsc::Sender<NativeEvent>,
+    output_rx: &mut mpsc::Receiver<ServerMessage>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut keepalive = interval(KEEPALIVE_INTERVAL);
+    let mut last_seen = Instant::now();
+
+    loop {
+        tokio::select! {
+            line = lines.next_line() => {
+                let Some(line) = line? else {
+                    return Ok(());
+                };
+                last_seen = Instant::now();
+                let message = match protocol::decode(line.as_str()) {
+                    Ok(message) => message,
+                    Err(e) => {
+                        send(writer, &error_message(e.to_string().as_str())).await?;
+                        continue;
+                    }
+                };
+                match message {
+                    DriverMessage::Event { capability, value } => {
+                        let event = match protocol::to_native_event(capability.as_str(), value) {
+                            Ok(event) => event,
+                            Err(e) => {
+                                send(writer, &error_message(e.to_string().as_str())).await?;
+                                continue;
+                            }
+                        };
+                        match event_tx.try_send(event) {
+                            Ok(_) => (),
+                            // Events are dropped if no source device is consuming them yet
+                            Err(TrySendError::Full(_)) => log::trace!("Dropping external driver event"),
+                            Err(TrySendError::Closed(_)) => return Ok(()),
+                        }
+                    }
+                    DriverMessage::Pong => (),
+                    DriverMessage::Register { .. } => {
+                        send(writer, &error_message("driver is already registered")).await?;
+                    }
+                }
+            }
+            message = output_rx.recv() => {
+                // The source device was stopped
+                let Some(message) = message.filter(|msg| *msg != ServerMessage::Stop) else {
+                    return Ok(());
+                };
+                send(writer, &message).await?;
+            }
+            _ = keepalive.tick() => {
+                if last_seen.elapsed() > KEEPALIVE_TIMEOUT {
+                    return Err("driver stopped responding".into());
+                }
+                send(writer, &ServerMessage::Ping).await?;
+            }
+        }
+    }
+}
+
+/// Validate the given registration message and create the device info for
+/// the new source device.
+fn register(
+    line: &str,
+) -> Result<(UdevDevice, Vec<Capability>, Vec<OutputCapability>), Box<dyn Error + Send + Sync>> {
+    let DriverMessage::Register {
+        version,
+        name,
+        vendor_id,
+        product_id,
+        capabilities,
+        output_capabilities,
+    } = protocol::decode(line)?
+    else {
+        return Err("expected a register message".into());
+    };
+
+    if version != PROTOCOL_VERSION {
+        return Err(
+            format!("unsupported protocol version {version}, expected {PROTOCOL_VERSION}").into(),
+        );
+    }
+    if name.is_empty() {
+        return Err("driver name cannot be empty".into());
+    }
+    let capabilities = protocol::parse_capabilities(&capabilities)?;
+    let output_capabilities = protocol::parse_output_capabilities(&output_capabilities)?;
+
+    let sysname = format!("external{}", NEXT_ID.fetch_add(1, Ordering::Relaxed));
+    let device = UdevDevice::from_external(sysname.as_str(), name.as_str(), vendor_id, product_id);
+
+    Ok((device, capabilities, output_capabilities))
+}
+
+/// Returns an error message to send to a driver
+fn error_message(message: &str) -> ServerMessage {
+    ServerMessage::Error {
+        message: message.to_string(),
+    }
+}
+
+/// Write the given message to the driver
+async fn send(
+    writer: &mut (impl AsyncWrite + Unpin),
+    message: &ServerMessage,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let line = protocol::encode(message)?;
+    writer.write_all(line.as_bytes()).await?;
+    Ok(())
+}
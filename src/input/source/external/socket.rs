@@ -0,0 +1,212 @@
+use std::{collections::HashMap, error::Error, fmt::Debug};
+
+use evdev::{FFEffectData, FFEffectKind};
+use packed_struct::types::SizedInteger;
+use tokio::sync::mpsc::error::TryRecvError;
+
+use crate::{
+    input::{
+        capability::Capability,
+        event::native::NativeEvent,
+        output_capability::OutputCapability,
+        output_event::OutputEvent,
+        source::{InputError, OutputError, SourceInputDevice, SourceOutputDevice},
+    },
+    udev::device::UdevDevice,
+};
+
+use super::{
+    protocol::ServerMessage,
+    server::{self, DriverConnection},
+};
+
+/// Maximum number of events to read from the driver in a single poll
+const MAX_EVENTS: usize = 256;
+
+/// Source device implementation for an external driver process connected
+/// over the driver socket.
+pub struct SocketDevice {
+    connection: DriverConnection,
+    ff_evdev_effects: HashMap<i16, FFEffectData>,
+}
+
+impl SocketDevice {
+    /// Create a new source device from the registered driver connection
+    /// matching the given device info.
+    pub fn new(device_info: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let sysname = device_info.sysname();
+        let Some(connection) = server::take_connection(sysname.as_str()) else {
+            return Err(format!("No external driver connection found for {sysname}").into());
+        };
+        Ok(Self {
+            connection,
+            ff_evdev_effects: HashMap::new(),
+        })
+    }
+
+    /// Returns the next available evdev effect id
+    fn next_ff_effect_id(&self) -> i16 {
+        const MAX: i16 = 2096;
+        let mut i = 0;
+        loop {
+            if !self.ff_evdev_effects.contains_key(&i) {
+                return i;
+            }
+            i += 1;
+            if i > MAX {
+                return -1;
+            }
+        }
+    }
+
+    /// Send the given message to the external driver
+    fn send(&self, message: ServerMessage) -> Result<(), Box<dyn Error>> {
+        self.connection
+            .output
+            .try_send(message)
+            .map_err(|e| format!("Failed to send message to external driver: {e:?}"))?;
+        Ok(())
+    }
+
+    /// Send a rumble message to the external driver
+    fn rumble(&self, strong_magnitude: u16, weak_magnitude: u16) -> Result<(), Box<dyn Error>> {
+        self.send(ServerMessage::Rumble {
+            strong_magnitude,
+            weak_magnitude,
+        })
+    }
+
+    /// Process the given evdev force feedback event.
+    fn process_evdev_ff(&mut self, input_event: evdev::InputEvent) -> Result<(), Box<dyn Error>> {
+        // Get the code (effect id) and value of the event
+        let (code, value) =
+            if let evdev::EventSummary::ForceFeedback(_, code, value) = input_event.destructure() {
+                (code, value)
+            } else {
+                log::debug!("Unhandled evdev output event: {:?}", input_event);
+                return Ok(());
+            };
+
+        // Find the effect data for this event
+        let effect_id = code.0 as i16;
+        let Some(effect_data) = self.ff_evdev_effects.get(&effect_id) else {
+            log::warn!("No effect id found: {}", code.0);
+            return Ok(());
+        };
+
+        // The value determines if the effect should be playing or not.
+        if value == 0 {
+            log::trace!("Stopping rumble");
+            return self.rumble(0, 0);
+        }
+
+        // Only rumble effects are forwarded to external drivers
+        if let FFEffectKind::Rumble {
+            strong_magnitude,
+            weak_magnitude,
+        } = effect_data.kind
+        {
+            self.rumble(strong_magnitude, weak_magnitude)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SourceInputDevice for SocketDevice {
+    /// Poll the given input device for input events
+    fn poll(&mut self) -> Result<Vec<NativeEvent>, InputError> {
+        let mut events = vec![];
+        while events.len() < MAX_EVENTS {
+            match self.connection.events.try_recv() {
+                Ok(event) => events.push(event),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    return Err("External driver disconnected".into());
+                }
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Returns the possible input events this device is capable of emitting
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(self.connection.capabilities.clone())
+    }
+}
+
+impl SourceOutputDevice for SocketDevice {
+    /// Write the given output event to the source device. Output events are
+    /// events that flow from an application (like a game) to the physical
+    /// input device, such as force feedback events.
+    fn write_event(&mut self, event: OutputEvent) -> Result<(), OutputError> {
+        log::trace!("Received output event: {:?}", event);
+        if !self
+            .connection
+            .output_capabilities
+            .contains(&OutputCapability::ForceFeedback)
+        {
+            return Ok(());
+        }
+        match event {
+            OutputEvent::Evdev(input_event) => Ok(self.process_evdev_ff(input_event)?),
+            OutputEvent::SteamDeckRumble(report) => Ok(self.rumble(
+                report.left_speed.to_primitive(),
+                report.right_speed.to_primitive(),
+            )?),
+            OutputEvent::Uinput(_) => Ok(()),
+            OutputEvent::DualSense(_) => Ok(()),
+            OutputEvent::SteamDeckHaptics(_) => Ok(()),
+        }
+    }
+
+    /// Upload the given force feedback effect data to the source device. Returns
+    /// a device-specific id of the uploaded effect if it is successful.
+    fn upload_effect(&mut self, effect: FFEffectData) -> Result<i16, OutputError> {
+        log::debug!("Uploading FF effect data");
+        let id = self.next_ff_effect_id();
+        if id == -1 {
+            return Err("Maximum FF effects uploaded".into());
+        }
+        self.ff_evdev_effects.insert(id, effect);
+
+        Ok(id)
+    }
+
+    /// Update the effect with the given id using the given effect data.
+    fn update_effect(&mut self, effect_id: i16, effect: FFEffectData) -> Result<(), OutputError> {
+        log::debug!("Updating FF effect data with id {effect_id}");
+        self.ff_evdev_effects.insert(effect_id, effect);
+        Ok(())
+    }
+
+    /// Erase the effect with the given id from the source device.
+    fn erase_effect(&mut self, effect_id: i16) -> Result<(), OutputError> {
+        log::debug!("Erasing FF effect data");
+        self.ff_evdev_effects.remove(&effect_id);
+        Ok(())
+    }
+
+    /// Returns the possible output events this device is capable of handling
+    fn get_output_capabilities(&self) -> Result<Vec<OutputCapability>, OutputError> {
+        Ok(self.connection.output_capabilities.clone())
+    }
+
+    /// Stop the source device.
+    fn stop(&mut self) -> Result<(), OutputError> {
+        if let Err(e) = self.send(ServerMessage::Stop) {
+            log::debug!("Failed to stop external driver: {e:?}");
+        }
+        Ok(())
+    }
+}
+
+impl Debug for SocketDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SocketDevice")
+            .field("capabilities", &self.connection.capabilities)
+            .field("ff_evdev_effects", &self.ff_evdev_effects)
+            .finish()
+    }
+}
@@ -1,25 +1,35 @@
+pub mod bitdo_ultimate;
 pub mod dualsense;
 pub mod fts3528;
+pub mod hid_generic;
 pub mod horipad_steam;
+pub mod joycon;
 pub mod lego_dinput_combined;
 pub mod lego_dinput_split;
 pub mod lego_fps_mode;
 pub mod lego_xinput;
 pub mod legos;
+pub mod msi_claw;
 pub mod opineo;
 pub mod rog_ally;
+pub mod steam_controller;
 pub mod steam_deck;
 pub mod xpad_uhid;
 
 use std::{error::Error, time::Duration};
 
+use bitdo_ultimate::BitdoUltimate;
+use hid_generic::HidGeneric;
 use horipad_steam::HoripadSteam;
+use joycon::JoyCon;
+use msi_claw::MsiClaw;
 use rog_ally::RogAlly;
+use steam_controller::SteamController;
 use xpad_uhid::XpadUhid;
 
 use crate::{
-    constants::BUS_SOURCES_PREFIX, drivers, input::composite_device::client::CompositeDeviceClient,
-    udev::device::UdevDevice,
+    config, constants::BUS_SOURCES_PREFIX, drivers,
+    input::composite_device::client::CompositeDeviceClient, udev::device::UdevDevice,
 };
 
 use self::{
@@ -29,21 +39,26 @@ use self::{
     legos::LegionSController, opineo::OrangePiNeoTouchpad, steam_deck::DeckController,
 };
 
-use super::{SourceDeviceCompatible, SourceDriver, SourceDriverOptions};
+use super::{quirks::Quirks, SourceDeviceCompatible, SourceDriver, SourceDriverOptions};
 
 /// List of available drivers
 enum DriverType {
     Unknown,
+    BitdoUltimate,
     DualSense,
     Fts3528Touchscreen,
+    HidGeneric,
     HoripadSteam,
+    JoyCon,
     LegionGoDCombined,
     LegionGoDSplit,
     LegionGoFPS,
     LegionGoS,
     LegionGoX,
+    MsiClaw,
     OrangePiNeo,
     RogAlly,
+    SteamController,
     SteamDeck,
     XpadUhid,
 }
@@ -51,16 +66,21 @@ enum DriverType {
 /// [HidRawDevice] represents an input device using the hidraw subsystem.
 #[derive(Debug)]
 pub enum HidRawDevice {
+    BitdoUltimate(SourceDriver<BitdoUltimate>),
     DualSense(SourceDriver<DualSenseController>),
     Fts3528Touchscreen(SourceDriver<Fts3528Touchscreen>),
+    HidGeneric(SourceDriver<HidGeneric>),
     HoripadSteam(SourceDriver<HoripadSteam>),
+    JoyCon(SourceDriver<JoyCon>),
     LegionGoDCombined(SourceDriver<LegionControllerDCombined>),
     LegionGoDSplit(SourceDriver<LegionControllerDSplit>),
     LegionGoFPS(SourceDriver<LegionControllerFPS>),
     LegionGoS(SourceDriver<LegionSController>),
     LegionGoX(SourceDriver<LegionControllerX>),
+    MsiClaw(SourceDriver<MsiClaw>),
     OrangePiNeo(SourceDriver<OrangePiNeoTouchpad>),
     RogAlly(SourceDriver<RogAlly>),
+    SteamController(SourceDriver<SteamController>),
     SteamDeck(SourceDriver<DeckController>),
     XpadUhid(SourceDriver<XpadUhid>),
 }
@@ -68,16 +88,21 @@ pub enum HidRawDevice {
 impl SourceDeviceCompatible for HidRawDevice {
     fn get_device_ref(&self) -> &UdevDevice {
         match self {
+            HidRawDevice::BitdoUltimate(source_driver) => source_driver.info_ref(),
             HidRawDevice::DualSense(source_driver) => source_driver.info_ref(),
             HidRawDevice::Fts3528Touchscreen(source_driver) => source_driver.info_ref(),
+            HidRawDevice::HidGeneric(source_driver) => source_driver.info_ref(),
             HidRawDevice::HoripadSteam(source_driver) => source_driver.info_ref(),
+            HidRawDevice::JoyCon(source_driver) => source_driver.info_ref(),
             HidRawDevice::LegionGoDCombined(source_driver) => source_driver.info_ref(),
             HidRawDevice::LegionGoDSplit(source_driver) => source_driver.info_ref(),
             HidRawDevice::LegionGoFPS(source_driver) => source_driver.info_ref(),
             HidRawDevice::LegionGoS(source_driver) => source_driver.info_ref(),
             HidRawDevice::LegionGoX(source_driver) => source_driver.info_ref(),
+            HidRawDevice::MsiClaw(source_driver) => source_driver.info_ref(),
             HidRawDevice::OrangePiNeo(source_driver) => source_driver.info_ref(),
             HidRawDevice::RogAlly(source_driver) => source_driver.info_ref(),
+            HidRawDevice::SteamController(source_driver) => source_driver.info_ref(),
             HidRawDevice::SteamDeck(source_driver) => source_driver.info_ref(),
             HidRawDevice::XpadUhid(source_driver) => source_driver.info_ref(),
         }
@@ -85,16 +110,21 @@ impl SourceDeviceCompatible for HidRawDevice {
 
     fn get_id(&self) -> String {
         match self {
+            HidRawDevice::BitdoUltimate(source_driver) => source_driver.get_id(),
             HidRawDevice::DualSense(source_driver) => source_driver.get_id(),
             HidRawDevice::Fts3528Touchscreen(source_driver) => source_driver.get_id(),
+            HidRawDevice::HidGeneric(source_driver) => source_driver.get_id(),
             HidRawDevice::HoripadSteam(source_driver) => source_driver.get_id(),
+            HidRawDevice::JoyCon(source_driver) => source_driver.get_id(),
             HidRawDevice::LegionGoDCombined(source_driver) => source_driver.get_id(),
             HidRawDevice::LegionGoDSplit(source_driver) => source_driver.get_id(),
             HidRawDevice::LegionGoFPS(source_driver) => source_driver.get_id(),
             HidRawDevice::LegionGoS(source_driver) => source_driver.get_id(),
             HidRawDevice::LegionGoX(source_driver) => source_driver.get_id(),
+            HidRawDevice::MsiClaw(source_driver) => source_driver.get_id(),
             HidRawDevice::OrangePiNeo(source_driver) => source_driver.get_id(),
             HidRawDevice::RogAlly(source_driver) => source_driver.get_id(),
+            HidRawDevice::SteamController(source_driver) => source_driver.get_id(),
             HidRawDevice::SteamDeck(source_driver) => source_driver.get_id(),
             HidRawDevice::XpadUhid(source_driver) => source_driver.get_id(),
         }
@@ -102,16 +132,21 @@ impl SourceDeviceCompatible for HidRawDevice {
 
     fn client(&self) -> super::client::SourceDeviceClient {
         match self {
+            HidRawDevice::BitdoUltimate(source_driver) => source_driver.client(),
             HidRawDevice::DualSense(source_driver) => source_driver.client(),
             HidRawDevice::Fts3528Touchscreen(source_driver) => source_driver.client(),
+            HidRawDevice::HidGeneric(source_driver) => source_driver.client(),
             HidRawDevice::HoripadSteam(source_driver) => source_driver.client(),
+            HidRawDevice::JoyCon(source_driver) => source_driver.client(),
             HidRawDevice::LegionGoDCombined(source_driver) => source_driver.client(),
             HidRawDevice::LegionGoDSplit(source_driver) => source_driver.client(),
             HidRawDevice::LegionGoFPS(source_driver) => source_driver.client(),
             HidRawDevice::LegionGoS(source_driver) => source_driver.client(),
             HidRawDevice::LegionGoX(source_driver) => source_driver.client(),
+            HidRawDevice::MsiClaw(source_driver) => source_driver.client(),
             HidRawDevice::OrangePiNeo(source_driver) => source_driver.client(),
             HidRawDevice::RogAlly(source_driver) => source_driver.client(),
+            HidRawDevice::SteamController(source_driver) => source_driver.client(),
             HidRawDevice::SteamDeck(source_driver) => source_driver.client(),
             HidRawDevice::XpadUhid(source_driver) => source_driver.client(),
         }
@@ -119,16 +154,21 @@ impl SourceDeviceCompatible for HidRawDevice {
 
     async fn run(self) -> Result<(), Box<dyn Error>> {
         match self {
+            HidRawDevice::BitdoUltimate(source_driver) => source_driver.run().await,
             HidRawDevice::DualSense(source_driver) => source_driver.run().await,
             HidRawDevice::Fts3528Touchscreen(source_driver) => source_driver.run().await,
+            HidRawDevice::HidGeneric(source_driver) => source_driver.run().await,
             HidRawDevice::HoripadSteam(source_driver) => source_driver.run().await,
+            HidRawDevice::JoyCon(source_driver) => source_driver.run().await,
             HidRawDevice::LegionGoDCombined(source_driver) => source_driver.run().await,
             HidRawDevice::LegionGoDSplit(source_driver) => source_driver.run().await,
             HidRawDevice::LegionGoFPS(source_driver) => source_driver.run().await,
             HidRawDevice::LegionGoS(source_driver) => source_driver.run().await,
             HidRawDevice::LegionGoX(source_driver) => source_driver.run().await,
+            HidRawDevice::MsiClaw(source_driver) => source_driver.run().await,
             HidRawDevice::OrangePiNeo(source_driver) => source_driver.run().await,
             HidRawDevice::RogAlly(source_driver) => source_driver.run().await,
+            HidRawDevice::SteamController(source_driver) => source_driver.run().await,
             HidRawDevice::SteamDeck(source_driver) => source_driver.run().await,
             HidRawDevice::XpadUhid(source_driver) => source_driver.run().await,
         }
@@ -138,33 +178,71 @@ impl SourceDeviceCompatible for HidRawDevice {
         &self,
     ) -> Result<Vec<crate::input::capability::Capability>, super::InputError> {
         match self {
+            HidRawDevice::BitdoUltimate(source_driver) => source_driver.get_capabilities(),
             HidRawDevice::DualSense(source_driver) => source_driver.get_capabilities(),
             HidRawDevice::Fts3528Touchscreen(source_driver) => source_driver.get_capabilities(),
+            HidRawDevice::HidGeneric(source_driver) => source_driver.get_capabilities(),
             HidRawDevice::HoripadSteam(source_driver) => source_driver.get_capabilities(),
+            HidRawDevice::JoyCon(source_driver) => source_driver.get_capabilities(),
             HidRawDevice::LegionGoDCombined(source_driver) => source_driver.get_capabilities(),
             HidRawDevice::LegionGoDSplit(source_driver) => source_driver.get_capabilities(),
             HidRawDevice::LegionGoFPS(source_driver) => source_driver.get_capabilities(),
             HidRawDevice::LegionGoS(source_driver) => source_driver.get_capabilities(),
             HidRawDevice::LegionGoX(source_driver) => source_driver.get_capabilities(),
+            HidRawDevice::MsiClaw(source_driver) => source_driver.get_capabilities(),
             HidRawDevice::OrangePiNeo(source_driver) => source_driver.get_capabilities(),
             HidRawDevice::RogAlly(source_driver) => source_driver.get_capabilities(),
+            HidRawDevice::SteamController(source_driver) => source_driver.get_capabilities(),
             HidRawDevice::SteamDeck(source_driver) => source_driver.get_capabilities(),
             HidRawDevice::XpadUhid(source_driver) => source_driver.get_capabilities(),
         }
     }
 
+    fn get_output_capabilities(
+        &self,
+    ) -> Result<Vec<crate::input::output_capability::OutputCapability>, super::OutputError> {
+        match self {
+            HidRawDevice::BitdoUltimate(source_driver) => source_driver.get_output_capabilities(),
+            HidRawDevice::DualSense(source_driver) => source_driver.get_output_capabilities(),
+            HidRawDevice::Fts3528Touchscreen(source_driver) => {
+                source_driver.get_output_capabilities()
+            }
+            HidRawDevice::HidGeneric(source_driver) => source_driver.get_output_capabilities(),
+            HidRawDevice::HoripadSteam(source_driver) => source_driver.get_output_capabilities(),
+            HidRawDevice::JoyCon(source_driver) => source_driver.get_output_capabilities(),
+            HidRawDevice::LegionGoDCombined(source_driver) => {
+                source_driver.get_output_capabilities()
+            }
+            HidRawDevice::LegionGoDSplit(source_driver) => source_driver.get_output_capabilities(),
+            HidRawDevice::LegionGoFPS(source_driver) => source_driver.get_output_capabilities(),
+            HidRawDevice::LegionGoS(source_driver) => source_driver.get_output_capabilities(),
+            HidRawDevice::LegionGoX(source_driver) => source_driver.get_output_capabilities(),
+            HidRawDevice::MsiClaw(source_driver) => source_driver.get_output_capabilities(),
+            HidRawDevice::OrangePiNeo(source_driver) => source_driver.get_output_capabilities(),
+            HidRawDevice::RogAlly(source_driver) => source_driver.get_output_capabilities(),
+            HidRawDevice::SteamController(source_driver) => source_driver.get_output_capabilities(),
+            HidRawDevice::SteamDeck(source_driver) => source_driver.get_output_capabilities(),
+            HidRawDevice::XpadUhid(source_driver) => source_driver.get_output_capabilities(),
+        }
+    }
+
     fn get_device_path(&self) -> String {
         match self {
+            HidRawDevice::BitdoUltimate(source_driver) => source_driver.get_device_path(),
             HidRawDevice::DualSense(source_driver) => source_driver.get_device_path(),
             HidRawDevice::Fts3528Touchscreen(source_driver) => source_driver.get_device_path(),
+            HidRawDevice::HidGeneric(source_driver) => source_driver.get_device_path(),
             HidRawDevice::HoripadSteam(source_driver) => source_driver.get_device_path(),
+            HidRawDevice::JoyCon(source_driver) => source_driver.get_device_path(),
             HidRawDevice::LegionGoDCombined(source_driver) => source_driver.get_device_path(),
             HidRawDevice::LegionGoDSplit(source_driver) => source_driver.get_device_path(),
             HidRawDevice::LegionGoFPS(source_driver) => source_driver.get_device_path(),
             HidRawDevice::LegionGoS(source_driver) => source_driver.get_device_path(),
             HidRawDevice::LegionGoX(source_driver) => source_driver.get_device_path(),
+            HidRawDevice::MsiClaw(source_driver) => source_driver.get_device_path(),
             HidRawDevice::OrangePiNeo(source_driver) => source_driver.get_device_path(),
             HidRawDevice::RogAlly(source_driver) => source_driver.get_device_path(),
+            HidRawDevice::SteamController(source_driver) => source_driver.get_device_path(),
             HidRawDevice::SteamDeck(source_driver) => source_driver.get_device_path(),
             HidRawDevice::XpadUhid(source_driver) => source_driver.get_device_path(),
         }
@@ -178,11 +256,13 @@ impl HidRawDevice {
     pub fn new(
         device_info: UdevDevice,
         composite_device: CompositeDeviceClient,
+        config: Option<&config::SourceDevice>,
     ) -> Result<Self, Box<dyn Error + Send + Sync>> {
         let driver_type = HidRawDevice::get_driver_type(&device_info);
 
-        match driver_type {
-            DriverType::Unknown => Err("No driver for hidraw interface found".into()),
+        let quirks = Quirks::new(&device_info, config);
+        let mut device = match driver_type {
+            DriverType::Unknown => return Err("No driver for hidraw interface found".into()),
             DriverType::DualSense => {
                 let options = SourceDriverOptions {
                     poll_rate: Duration::from_millis(1),
@@ -191,7 +271,7 @@ impl HidRawDevice {
                 let device = DualSenseController::new(device_info.clone())?;
                 let source_device =
                     SourceDriver::new_with_options(composite_device, device, device_info, options);
-                Ok(Self::DualSense(source_device))
+                Self::DualSense(source_device)
             }
             DriverType::SteamDeck => {
                 let options = SourceDriverOptions {
@@ -201,47 +281,57 @@ impl HidRawDevice {
                 let device = DeckController::new(device_info.clone())?;
                 let source_device =
                     SourceDriver::new_with_options(composite_device, device, device_info, options);
-                Ok(Self::SteamDeck(source_device))
+                Self::SteamDeck(source_device)
+            }
+            DriverType::SteamController => {
+                let options = SourceDriverOptions {
+                    poll_rate: Duration::from_millis(1),
+                    buffer_size: 2048,
+                };
+                let device = SteamController::new(device_info.clone())?;
+                let source_device =
+                    SourceDriver::new_with_options(composite_device, device, device_info, options);
+                Self::SteamController(source_device)
             }
             DriverType::LegionGoDCombined => {
                 let device = LegionControllerDCombined::new(device_info.clone())?;
                 let source_device = SourceDriver::new(composite_device, device, device_info);
-                Ok(Self::LegionGoDCombined(source_device))
+                Self::LegionGoDCombined(source_device)
             }
             DriverType::LegionGoDSplit => {
                 let device = LegionControllerDSplit::new(device_info.clone())?;
                 let source_device = SourceDriver::new(composite_device, device, device_info);
-                Ok(Self::LegionGoDSplit(source_device))
+                Self::LegionGoDSplit(source_device)
             }
             DriverType::LegionGoFPS => {
                 let device = LegionControllerFPS::new(device_info.clone())?;
                 let source_device = SourceDriver::new(composite_device, device, device_info);
-                Ok(Self::LegionGoFPS(source_device))
+                Self::LegionGoFPS(source_device)
             }
             DriverType::LegionGoX => {
                 let device = LegionControllerX::new(device_info.clone())?;
                 let source_device = SourceDriver::new(composite_device, device, device_info);
-                Ok(Self::LegionGoX(source_device))
+                Self::LegionGoX(source_device)
             }
             DriverType::LegionGoS => {
                 let device = LegionSController::new(device_info.clone())?;
                 let source_device = SourceDriver::new(composite_device, device, device_info);
-                Ok(Self::LegionGoS(source_device))
+                Self::LegionGoS(source_device)
             }
             DriverType::OrangePiNeo => {
                 let device = OrangePiNeoTouchpad::new(device_info.clone())?;
                 let source_device = SourceDriver::new(composite_device, device, device_info);
-                Ok(Self::OrangePiNeo(source_device))
+                Self::OrangePiNeo(source_device)
             }
             DriverType::Fts3528Touchscreen => {
                 let device = Fts3528Touchscreen::new(device_info.clone())?;
                 let source_device = SourceDriver::new(composite_device, device, device_info);
-                Ok(Self::Fts3528Touchscreen(source_device))
+                Self::Fts3528Touchscreen(source_device)
             }
             DriverType::XpadUhid => {
                 let device = XpadUhid::new(device_info.clone())?;
                 let source_device = SourceDriver::new(composite_device, device, device_info);
-                Ok(Self::XpadUhid(source_device))
+                Self::XpadUhid(source_device)
             }
             DriverType::RogAlly => {
                 let device = RogAlly::new(device_info.clone())?;
@@ -251,13 +341,69 @@ impl HidRawDevice {
                 };
                 let source_device =
                     SourceDriver::new_with_options(composite_device, device, device_info, options);
-                Ok(Self::RogAlly(source_device))
+                Self::RogAlly(source_device)
+            }
+            DriverType::MsiClaw => {
+                let device = MsiClaw::new(device_info.clone())?;
+                let options = SourceDriverOptions {
+                    poll_rate: Duration::from_millis(500),
+                    buffer_size: 1024,
+                };
+                let source_device =
+                    SourceDriver::new_with_options(composite_device, device, device_info, options);
+                Self::MsiClaw(source_device)
+            }
+            DriverType::HidGeneric => {
+                let device = HidGeneric::new(device_info.clone())?;
+                let source_device = SourceDriver::new(composite_device, device, device_info);
+                Self::HidGeneric(source_device)
             }
             DriverType::HoripadSteam => {
                 let device = HoripadSteam::new(device_info.clone())?;
                 let source_device = SourceDriver::new(composite_device, device, device_info);
-                Ok(Self::HoripadSteam(source_device))
+                Self::HoripadSteam(source_device)
+            }
+            DriverType::JoyCon => {
+                let options = SourceDriverOptions {
+                    poll_rate: Duration::from_millis(1),
+                    buffer_size: 2048,
+                };
+                let device = JoyCon::new(device_info.clone())?;
+                let source_device =
+                    SourceDriver::new_with_options(composite_device, device, device_info, options);
+                Self::JoyCon(source_device)
+            }
+            DriverType::BitdoUltimate => {
+                let device = BitdoUltimate::new(device_info.clone())?;
+                let source_device = SourceDriver::new(composite_device, device, device_info);
+                Self::BitdoUltimate(source_device)
             }
+        };
+        device.set_quirks(quirks);
+
+        Ok(device)
+    }
+
+    /// Set the quirks that should be applied to input events from the device
+    fn set_quirks(&mut self, quirks: Quirks) {
+        match self {
+            HidRawDevice::BitdoUltimate(source_driver) => source_driver.set_quirks(quirks),
+            HidRawDevice::DualSense(source_driver) => source_driver.set_quirks(quirks),
+            HidRawDevice::Fts3528Touchscreen(source_driver) => source_driver.set_quirks(quirks),
+            HidRawDevice::HidGeneric(source_driver) => source_driver.set_quirks(quirks),
+            HidRawDevice::HoripadSteam(source_driver) => source_driver.set_quirks(quirks),
+            HidRawDevice::JoyCon(source_driver) => source_driver.set_quirks(quirks),
+            HidRawDevice::LegionGoDCombined(source_driver) => source_driver.set_quirks(quirks),
+            HidRawDevice::LegionGoDSplit(source_driver) => source_driver.set_quirks(quirks),
+            HidRawDevice::LegionGoFPS(source_driver) => source_driver.set_quirks(quirks),
+            HidRawDevice::LegionGoS(source_driver) => source_driver.set_quirks(quirks),
+            HidRawDevice::LegionGoX(source_driver) => source_driver.set_quirks(quirks),
+            HidRawDevice::MsiClaw(source_driver) => source_driver.set_quirks(quirks),
+            HidRawDevice::OrangePiNeo(source_driver) => source_driver.set_quirks(quirks),
+            HidRawDevice::RogAlly(source_driver) => source_driver.set_quirks(quirks),
+            HidRawDevice::SteamController(source_driver) => source_driver.set_quirks(quirks),
+            HidRawDevice::SteamDeck(source_driver) => source_driver.set_quirks(quirks),
+            HidRawDevice::XpadUhid(source_driver) => source_driver.set_quirks(quirks),
         }
     }
 
@@ -279,6 +425,12 @@ impl HidRawDevice {
             return DriverType::SteamDeck;
         }
 
+        // Steam Controller, wired or through the wireless dongle
+        if drivers::steam_controller::driver::is_supported(vid, pid, device.interface_number()) {
+            log::info!("Detected Steam Controller");
+            return DriverType::SteamController;
+        }
+
         // Legion Go Dinput Combined
         if vid == drivers::lego::driver_dinput_combined::VID
             && pid == drivers::lego::driver_dinput_combined::PID
@@ -333,6 +485,12 @@ impl HidRawDevice {
             return DriverType::RogAlly;
         }
 
+        // MSI Claw
+        if drivers::msi_claw::driver::is_supported(vid, pid, device.interface_number()) {
+            log::info!("Detected MSI Claw");
+            return DriverType::MsiClaw;
+        }
+
         // XpadUhid
         let drivers = device.drivers();
         if drivers.contains(&"microsoft".to_string()) {
@@ -351,6 +509,33 @@ impl HidRawDevice {
             return DriverType::HoripadSteam;
         }
 
+        // Nintendo Switch Joy-Con
+        if vid == drivers::joycon::driver::VID
+            && [
+                drivers::joycon::driver::PID_LEFT,
+                drivers::joycon::driver::PID_RIGHT,
+            ]
+            .contains(&pid)
+        {
+            log::info!("Detected Nintendo Switch Joy-Con");
+            return DriverType::JoyCon;
+        }
+
+        // 8BitDo Ultimate 2.4GHz dongle vendor interface
+        if vid == drivers::bitdo_ultimate::driver::VID
+            && drivers::bitdo_ultimate::driver::PIDS.contains(&pid)
+            && device.interface_number() == drivers::bitdo_ultimate::driver::VENDOR_INTERFACE
+        {
+            log::info!("Detected 8BitDo Ultimate 2.4GHz");
+            return DriverType::BitdoUltimate;
+        }
+
+        // Generic HID gamepad
+        if drivers::hid_generic::driver::is_supported(device) {
+            log::info!("Detected generic HID gamepad. VID: {vid}, PID: {pid}");
+            return DriverType::HidGeneric;
+        }
+
         // Unknown
         log::warn!("No driver for hidraw interface found. VID: {vid}, PID: {pid}");
         DriverType::Unknown
@@ -0,0 +1,81 @@
+use std::{error::Error, fmt::Debug};
+
+use crate::{
+    drivers::bitdo_ultimate::{driver::Driver, event},
+    input::{
+        capability::{Capability, Gamepad, GamepadButton},
+        event::{native::NativeEvent, value::InputValue},
+        source::{InputError, SourceInputDevice, SourceOutputDevice},
+    },
+    udev::device::UdevDevice,
+};
+
+/// 8BitDo Ultimate 2.4GHz source device implementation. This only handles the
+/// vendor interface of the dongle, which reports the back paddles and the
+/// profile switch button that the XInput interface cannot represent.
+pub struct BitdoUltimate {
+    driver: Driver,
+}
+
+impl BitdoUltimate {
+    /// Create a new source device with the given udev
+    /// device information
+    pub fn new(device_info: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let driver = Driver::new(device_info)?;
+        Ok(Self { driver })
+    }
+}
+
+impl SourceOutputDevice for BitdoUltimate {}
+
+impl SourceInputDevice for BitdoUltimate {
+    /// Poll the given input device for input events
+    fn poll(&mut self) -> Result<Vec<NativeEvent>, InputError> {
+        let events = self.driver.poll()?;
+        let native_events = translate_events(events);
+        Ok(native_events)
+    }
+
+    /// Returns the possible input events this device is capable of emitting
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(CAPABILITIES.into())
+    }
+}
+
+impl Debug for BitdoUltimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BitdoUltimate").finish()
+    }
+}
+
+/// Translate the given events into native events
+fn translate_events(events: Vec<event::Event>) -> Vec<NativeEvent> {
+    events.into_iter().map(translate_event).collect()
+}
+
+/// Translate the given event into a native event
+fn translate_event(event: event::Event) -> NativeEvent {
+    match event {
+        event::Event::Button(button) => match button {
+            event::ButtonEvent::LeftPaddle(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle1)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::RightPaddle(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::RightPaddle1)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::Profile(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::QuickAccess2)),
+                InputValue::Bool(value.pressed),
+            ),
+        },
+    }
+}
+
+/// List of all capabilities that the driver implements
+pub const CAPABILITIES: &[Capability] = &[
+    Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle1)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::QuickAccess2)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::RightPaddle1)),
+];
@@ -14,6 +14,7 @@ use crate::{
             Touchpad,
         },
         event::{native::NativeEvent, value::InputValue},
+        output_capability::OutputCapability,
         output_event::OutputEvent,
         source::{InputError, OutputError, SourceInputDevice, SourceOutputDevice},
     },
@@ -204,6 +205,11 @@ impl SourceOutputDevice for DualSenseController {
         self.ff_evdev_effects.remove(&effect_id);
         Ok(())
     }
+
+    /// Returns the possible output events this device is capable of handling
+    fn get_output_capabilities(&self) -> Result<Vec<OutputCapability>, OutputError> {
+        Ok(vec![OutputCapability::ForceFeedback])
+    }
 }
 
 impl Debug for DualSenseController {
@@ -255,6 +261,14 @@ fn translate_event(event: dualsense::event::Event) -> NativeEvent {
                 Capability::Gamepad(Gamepad::Button(GamepadButton::Mute)),
                 InputValue::Bool(value.pressed),
             ),
+            dualsense::event::ButtonEvent::HeadphoneInsert(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::HeadphoneInsert)),
+                InputValue::Bool(value.pressed),
+            ),
+            dualsense::event::ButtonEvent::MicrophoneInsert(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::MicrophoneInsert)),
+                InputValue::Bool(value.pressed),
+            ),
             dualsense::event::ButtonEvent::DPadDown(value) => NativeEvent::new(
                 Capability::Gamepad(Gamepad::Button(GamepadButton::DPadDown)),
                 InputValue::Bool(value.pressed),
@@ -389,15 +403,17 @@ fn normalize_unsigned_value(raw_value: f64, max: f64) -> f64 {
 fn normalize_axis_value(event: &dualsense::event::AxisEvent) -> InputValue {
     match event {
         dualsense::event::AxisEvent::Pad(value) => {
-            let min = 0.0;
-            let max = dualsense::driver::DS5_TOUCHPAD_WIDTH;
-            let x = normalize_signed_value(value.x as f64, min, max);
-            let x = Some(x);
-
-            let min = 0.0;
-            let max = dualsense::driver::DS5_TOUCHPAD_HEIGHT;
-            let y = normalize_signed_value(value.y as f64, min, max);
-            let y = Some(y);
+            // Keep the last position of lifted touches
+            let (x, y) = if value.is_touching {
+                let max = dualsense::driver::DS5_TOUCHPAD_WIDTH;
+                let x = normalize_unsigned_value(value.x as f64, max);
+
+                let max = dualsense::driver::DS5_TOUCHPAD_HEIGHT;
+                let y = normalize_unsigned_value(value.y as f64, max);
+                (Some(x.clamp(0.0, 1.0)), Some(y.clamp(0.0, 1.0)))
+            } else {
+                (None, None)
+            };
 
             InputValue::Touch {
                 index: value.index,
@@ -462,12 +478,15 @@ pub const CAPABILITIES: &[Capability] = &[
     Capability::Gamepad(Gamepad::Button(GamepadButton::DPadUp)),
     Capability::Gamepad(Gamepad::Button(GamepadButton::East)),
     Capability::Gamepad(Gamepad::Button(GamepadButton::Guide)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::HeadphoneInsert)),
     Capability::Gamepad(Gamepad::Button(GamepadButton::LeftBumper)),
     Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle1)),
     Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle2)),
     Capability::Gamepad(Gamepad::Button(GamepadButton::LeftStick)),
     Capability::Gamepad(Gamepad::Button(GamepadButton::LeftStickTouch)),
     Capability::Gamepad(Gamepad::Button(GamepadButton::LeftTrigger)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::MicrophoneInsert)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::Mute)),
     Capability::Gamepad(Gamepad::Button(GamepadButton::North)),
     Capability::Gamepad(Gamepad::Button(GamepadButton::QuickAccess)),
     Capability::Gamepad(Gamepad::Button(GamepadButton::RightBumper)),
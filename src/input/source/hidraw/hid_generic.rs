@@ -0,0 +1,371 @@
+use std::{collections::HashMap, error::Error, fmt::Debug};
+
+use evdev::{FFEffectData, FFEffectKind};
+
+use crate::{
+    drivers::hid_generic::{
+        descriptor::{
+            ReportType, USAGE_ACCELERATOR, USAGE_BRAKE, USAGE_HAT_SWITCH, USAGE_PAGE_BUTTON,
+            USAGE_PAGE_GENERIC_DESKTOP, USAGE_PAGE_SIMULATION, USAGE_RX, USAGE_RY, USAGE_RZ,
+            USAGE_X, USAGE_Y, USAGE_Z,
+        },
+        driver::Driver,
+        event,
+    },
+    input::{
+        capability::{Capability, Gamepad, GamepadAxis, GamepadButton, GamepadTrigger},
+        event::{native::NativeEvent, value::InputValue},
+        output_capability::OutputCapability,
+        output_event::OutputEvent,
+        source::{InputError, OutputError, SourceInputDevice, SourceOutputDevice},
+    },
+    udev::device::UdevDevice,
+};
+
+/// Current state of the DPad, which is reported as a hat switch
+#[derive(Debug, Clone, Default)]
+struct DPadState {
+    up: bool,
+    down: bool,
+    left: bool,
+    right: bool,
+}
+
+/// Generic HID gamepad source device implementation. Input capabilities are
+/// discovered from the report descriptor of the device.
+pub struct HidGeneric {
+    driver: Driver,
+    capabilities: Vec<Capability>,
+    dpad: DPadState,
+    ff_evdev_effects: HashMap<i16, FFEffectData>,
+}
+
+impl HidGeneric {
+    /// Create a new source device with the given udev
+    /// device information
+    pub fn new(device_info: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let driver = Driver::new(device_info)?;
+        let capabilities = get_capabilities(&driver);
+        log::debug!("Discovered HID gamepad capabilities: {capabilities:?}");
+        Ok(Self {
+            driver,
+            capabilities,
+            dpad: DPadState::default(),
+            ff_evdev_effects: HashMap::new(),
+        })
+    }
+
+    /// Returns the next available evdev effect id
+    fn next_ff_effect_id(&self) -> i16 {
+        const MAX: i16 = 2096;
+        let mut i = 0;
+        loop {
+            if !self.ff_evdev_effects.contains_key(&i) {
+                return i;
+            }
+            i += 1;
+            if i > MAX {
+                return -1;
+            }
+        }
+    }
+
+    /// Process the given evdev force feedback event.
+    fn process_evdev_ff(&mut self, input_event: evdev::InputEvent) -> Result<(), Box<dyn Error>> {
+        // Get the code (effect id) and value of the event
+        let (code, value) =
+            if let evdev::EventSummary::ForceFeedback(_, code, value) = input_event.destructure() {
+                (code, value)
+            } else {
+                log::debug!("Unhandled evdev output event: {:?}", input_event);
+                return Ok(());
+            };
+
+        // Find the effect data for this event
+        let effect_id = code.0 as i16;
+        let Some(effect_data) = self.ff_evdev_effects.get(&effect_id) else {
+            log::warn!("No effect id found: {}", code.0);
+            return Ok(());
+        };
+
+        // The value determines if the effect should be playing or not.
+        if value == 0 {
+            log::trace!("Stopping rumble");
+            if let Err(e) = self.driver.rumble(0.0, 0.0) {
+                log::debug!("Failed to stop rumble: {:?}", e);
+            }
+            return Ok(());
+        }
+
+        // Only rumble effects are supported
+        if let FFEffectKind::Rumble {
+            strong_magnitude,
+            weak_magnitude,
+        } = effect_data.kind
+        {
+            let strong = strong_magnitude as f64 / u16::MAX as f64;
+            let weak = weak_magnitude as f64 / u16::MAX as f64;
+            if let Err(e) = self.driver.rumble(strong, weak) {
+                let err = format!("Failed to do rumble: {:?}", e);
+                return Err(err.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Process the given evdev LED event. Evdev LED codes are mapped to the
+    /// HID LED usage page, which starts at 1 with Num Lock.
+    fn process_evdev_led(&mut self, code: u16, value: i32) -> Result<(), Box<dyn Error>> {
+        let usage = code + 1;
+        if let Err(e) = self.driver.set_led(usage, value != 0) {
+            log::debug!("Failed to set LED {usage:#x}: {e:?}");
+        }
+        Ok(())
+    }
+
+    /// Translate the given events into native events
+    fn translate_events(&mut self, events: Vec<event::Event>) -> Vec<NativeEvent> {
+        let mut translated = Vec::with_capacity(events.len());
+        for event in events.into_iter() {
+            translated.extend(self.translate_event(event));
+        }
+        if !translated.is_empty() {
+            log::trace!("Translated events: {translated:?}");
+        };
+        translated
+    }
+
+    /// Translate the given event into native events
+    fn translate_event(&mut self, event: event::Event) -> Vec<NativeEvent> {
+        log::trace!("Got event {event:?}");
+        match event {
+            event::Event::Button(value) => {
+                let Some(button) = button_from_index(value.index) else {
+                    return vec![];
+                };
+                vec![NativeEvent::new(
+                    Capability::Gamepad(Gamepad::Button(button)),
+                    InputValue::Bool(value.pressed),
+                )]
+            }
+            event::Event::Axis(value) => {
+                // Convert the 0.0 - 1.0 value into a -1.0 - 1.0 stick value
+                let stick_value = value.value * 2.0 - 1.0;
+                let (capability, value) = match value.axis {
+                    event::Axis::X => (
+                        Gamepad::Axis(GamepadAxis::LeftStick),
+                        InputValue::Vector2 {
+                            x: Some(stick_value),
+                            y: None,
+                        },
+                    ),
+                    event::Axis::Y => (
+                        Gamepad::Axis(GamepadAxis::LeftStick),
+                        InputValue::Vector2 {
+                            x: None,
+                            y: Some(stick_value),
+                        },
+                    ),
+                    event::Axis::Z => (
+                        Gamepad::Axis(GamepadAxis::RightStick),
+                        InputValue::Vector2 {
+                            x: Some(stick_value),
+                            y: None,
+                        },
+                    ),
+                    event::Axis::Rz => (
+                        Gamepad::Axis(GamepadAxis::RightStick),
+                        InputValue::Vector2 {
+                            x: None,
+                            y: Some(stick_value),
+                        },
+                    ),
+                    event::Axis::Rx | event::Axis::Brake => (
+                        Gamepad::Trigger(GamepadTrigger::LeftTrigger),
+                        InputValue::Float(value.value),
+                    ),
+                    event::Axis::Ry | event::Axis::Accelerator => (
+                        Gamepad::Trigger(GamepadTrigger::RightTrigger),
+                        InputValue::Float(value.value),
+                    ),
+                };
+                vec![NativeEvent::new(Capability::Gamepad(capability), value)]
+            }
+            event::Event::Hat(value) => {
+                let direction = value.direction;
+                let is = |directions: [u8; 3]| direction.is_some_and(|d| directions.contains(&d));
+                let dpad = DPadState {
+                    up: is([7, 0, 1]),
+                    right: is([1, 2, 3]),
+                    down: is([3, 4, 5]),
+                    left: is([5, 6, 7]),
+                };
+
+                let mut events = Vec::new();
+                let changes = [
+                    (dpad.up, self.dpad.up, GamepadButton::DPadUp),
+                    (dpad.down, self.dpad.down, GamepadButton::DPadDown),
+                    (dpad.left, self.dpad.left, GamepadButton::DPadLeft),
+                    (dpad.right, self.dpad.right, GamepadButton::DPadRight),
+                ];
+                for (pressed, was_pressed, button) in changes {
+                    if pressed == was_pressed {
+                        continue;
+                    }
+                    events.push(NativeEvent::new(
+                        Capability::Gamepad(Gamepad::Button(button)),
+                        InputValue::Bool(pressed),
+                    ));
+                }
+                self.dpad = dpad;
+
+                events
+            }
+        }
+    }
+}
+
+impl SourceInputDevice for HidGeneric {
+    /// Poll the given input device for input events
+    fn poll(&mut self) -> Result<Vec<NativeEvent>, InputError> {
+        let events = self.driver.poll()?;
+        let native_events = self.translate_events(events);
+        Ok(native_events)
+    }
+
+    /// Returns the possible input events this device is capable of emitting
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(self.capabilities.clone())
+    }
+}
+
+impl SourceOutputDevice for HidGeneric {
+    /// Write the given output event to the source device. Output events are
+    /// events that flow from an application (like a game) to the physical
+    /// input device, such as force feedback events.
+    fn write_event(&mut self, event: OutputEvent) -> Result<(), OutputError> {
+        log::trace!("Received output event: {:?}", event);
+        match event {
+            OutputEvent::Evdev(input_event) => match input_event.destructure() {
+                evdev::EventSummary::Led(_, code, value) => {
+                    Ok(self.process_evdev_led(code.0, value)?)
+                }
+                _ => Ok(self.process_evdev_ff(input_event)?),
+            },
+            OutputEvent::DualSense(_) => Ok(()),
+            OutputEvent::Uinput(_) => Ok(()),
+            OutputEvent::SteamDeckHaptics(_packed_haptic_report) => Ok(()),
+            OutputEvent::SteamDeckRumble(_packed_rumble_report) => Ok(()),
+        }
+    }
+
+    /// Upload the given force feedback effect data to the source device. Returns
+    /// a device-specific id of the uploaded effect if it is successful.
+    fn upload_effect(&mut self, effect: FFEffectData) -> Result<i16, OutputError> {
+        if !self.driver.has_rumble() {
+            return Ok(-1);
+        }
+        log::debug!("Uploading FF effect data");
+        let id = self.next_ff_effect_id();
+        if id == -1 {
+            return Err("Maximum FF effects uploaded".into());
+        }
+        self.ff_evdev_effects.insert(id, effect);
+
+        Ok(id)
+    }
+
+    /// Update the effect with the given id using the given effect data.
+    fn update_effect(&mut self, effect_id: i16, effect: FFEffectData) -> Result<(), OutputError> {
+        log::debug!("Updating FF effect data with id {effect_id}");
+        self.ff_evdev_effects.insert(effect_id, effect);
+        Ok(())
+    }
+
+    /// Erase the effect with the given id from the source device.
+    fn erase_effect(&mut self, effect_id: i16) -> Result<(), OutputError> {
+        log::debug!("Erasing FF effect data");
+        self.ff_evdev_effects.remove(&effect_id);
+        Ok(())
+    }
+
+    /// Returns the possible output events this device is capable of handling
+    fn get_output_capabilities(&self) -> Result<Vec<OutputCapability>, OutputError> {
+        if self.driver.has_rumble() {
+            return Ok(vec![OutputCapability::ForceFeedback]);
+        }
+        Ok(vec![])
+    }
+}
+
+impl Debug for HidGeneric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HidGeneric")
+            .field("capabilities", &self.capabilities)
+            .finish()
+    }
+}
+
+/// Returns the gamepad button for the given HID button usage. Buttons follow
+/// the same order the kernel uses for HID gamepads (BTN_GAMEPAD + index - 1).
+fn button_from_index(index: u16) -> Option<GamepadButton> {
+    let button = match index {
+        1 => GamepadButton::South,
+        2 => GamepadButton::East,
+        3 => GamepadButton::LeftPaddle1,
+        4 => GamepadButton::North,
+        5 => GamepadButton::West,
+        6 => GamepadButton::RightPaddle1,
+        7 => GamepadButton::LeftBumper,
+        8 => GamepadButton::RightBumper,
+        9 => GamepadButton::LeftTrigger,
+        10 => GamepadButton::RightTrigger,
+        11 => GamepadButton::Select,
+        12 => GamepadButton::Start,
+        13 => GamepadButton::Guide,
+        14 => GamepadButton::LeftStick,
+        15 => GamepadButton::RightStick,
+        _ => return None,
+    };
+    Some(button)
+}
+
+/// Returns the capabilities of the device based on its report descriptor
+fn get_capabilities(driver: &Driver) -> Vec<Capability> {
+    let mut capabilities = Vec::new();
+    for field in driver.descriptor().variable_fields(ReportType::Input) {
+        let caps = match (field.usage_page, field.usage) {
+            (USAGE_PAGE_BUTTON, index) => match button_from_index(index) {
+                Some(button) => vec![Gamepad::Button(button)],
+                None => vec![],
+            },
+            (USAGE_PAGE_GENERIC_DESKTOP, USAGE_X | USAGE_Y) => {
+                vec![Gamepad::Axis(GamepadAxis::LeftStick)]
+            }
+            (USAGE_PAGE_GENERIC_DESKTOP, USAGE_Z | USAGE_RZ) => {
+                vec![Gamepad::Axis(GamepadAxis::RightStick)]
+            }
+            (USAGE_PAGE_GENERIC_DESKTOP, USAGE_RX) | (USAGE_PAGE_SIMULATION, USAGE_BRAKE) => {
+                vec![Gamepad::Trigger(GamepadTrigger::LeftTrigger)]
+            }
+            (USAGE_PAGE_GENERIC_DESKTOP, USAGE_RY) | (USAGE_PAGE_SIMULATION, USAGE_ACCELERATOR) => {
+                vec![Gamepad::Trigger(GamepadTrigger::RightTrigger)]
+            }
+            (USAGE_PAGE_GENERIC_DESKTOP, USAGE_HAT_SWITCH) => vec![
+                Gamepad::Button(GamepadButton::DPadUp),
+                Gamepad::Button(GamepadButton::DPadDown),
+                Gamepad::Button(GamepadButton::DPadLeft),
+                Gamepad::Button(GamepadButton::DPadRight),
+            ],
+            _ => vec![],
+        };
+        for cap in caps {
+            let cap = Capability::Gamepad(cap);
+            if !capabilities.contains(&cap) {
+                capabilities.push(cap);
+            }
+        }
+    }
+    capabilities
+}
@@ -0,0 +1,393 @@
+use std::{collections::HashMap, error::Error, fmt::Debug};
+
+use evdev::{FFEffectData, FFEffectKind};
+
+use crate::{
+    drivers::joycon::{
+        driver::{Driver, JoyConSide},
+        event,
+        hid_report::StickCalibration,
+    },
+    input::{
+        capability::{Capability, Gamepad, GamepadAxis, GamepadButton, GamepadTrigger},
+        event::{native::NativeEvent, value::InputValue},
+        output_capability::OutputCapability,
+        output_event::OutputEvent,
+        source::{InputError, OutputError, SourceInputDevice, SourceOutputDevice},
+    },
+    udev::device::UdevDevice,
+};
+
+/// Nintendo Switch Joy-Con source device implementation. Each half of a
+/// Joy-Con pair is its own source device which are combined into a single
+/// composite device.
+pub struct JoyCon {
+    driver: Driver,
+    ff_evdev_effects: HashMap<i16, FFEffectData>,
+}
+
+impl JoyCon {
+    /// Create a new Joy-Con source device with the given udev device
+    /// information
+    pub fn new(device_info: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let driver = Driver::new(device_info)?;
+        Ok(Self {
+            driver,
+            ff_evdev_effects: HashMap::new(),
+        })
+    }
+
+    /// Returns the next available evdev effect id
+    fn next_ff_effect_id(&self) -> i16 {
+        const MAX: i16 = 2096;
+        let mut i = 0;
+        loop {
+            if !self.ff_evdev_effects.contains_key(&i) {
+                return i;
+            }
+            i += 1;
+            if i > MAX {
+                return -1;
+            }
+        }
+    }
+
+    /// Process the given evdev force feedback event.
+    fn process_evdev_ff(&mut self, input_event: evdev::InputEvent) -> Result<(), Box<dyn Error>> {
+        // Get the code (effect id) and value of the event
+        let (code, value) =
+            if let evdev::EventSummary::ForceFeedback(_, code, value) = input_event.destructure() {
+                (code, value)
+            } else {
+                log::debug!("Unhandled evdev output event: {:?}", input_event);
+                return Ok(());
+            };
+
+        // Find the effect data for this event
+        let effect_id = code.0 as i16;
+        let Some(effect_data) = self.ff_evdev_effects.get(&effect_id) else {
+            log::warn!("No effect id found: {}", code.0);
+            return Ok(());
+        };
+
+        // The value determines if the effect should be playing or not.
+        if value == 0 {
+            log::trace!("Stopping rumble");
+            if let Err(e) = self.driver.rumble(0) {
+                log::debug!("Failed to stop rumble: {:?}", e);
+                return Ok(());
+            }
+            return Ok(());
+        }
+
+        // Perform the rumble based on the effect
+        // TODO: handle effect duration, etc.
+        if let FFEffectKind::Rumble {
+            strong_magnitude,
+            weak_magnitude,
+        } = effect_data.kind
+        {
+            // Each half of the pair drives one side of the rumble, like the
+            // left and right motors of a regular gamepad.
+            let magnitude = match self.driver.side() {
+                JoyConSide::Left => strong_magnitude,
+                JoyConSide::Right => weak_magnitude,
+            };
+
+            // Scale the rumble value to the Joy-Con amplitude range
+            let amplitude = magnitude as u32 * 100 / u16::MAX as u32;
+
+            // Do rumble
+            if let Err(e) = self.driver.rumble(amplitude as u8) {
+                let err = format!("Failed to do rumble: {:?}", e);
+                return Err(err.into());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl SourceInputDevice for JoyCon {
+    /// Poll the given input device for input events
+    fn poll(&mut self) -> Result<Vec<NativeEvent>, InputError> {
+        let events = self.driver.poll()?;
+        let native_events = translate_events(events, &self.driver.calibration());
+        Ok(native_events)
+    }
+
+    /// Returns the possible input events this device is capable of emitting
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        match self.driver.side() {
+            JoyConSide::Left => Ok(LEFT_CAPABILITIES.into()),
+            JoyConSide::Right => Ok(RIGHT_CAPABILITIES.into()),
+        }
+    }
+}
+
+impl SourceOutputDevice for JoyCon {
+    /// Write the given output event to the source device. Output events are
+    /// events that flow from an application (like a game) to the physical
+    /// input device, such as force feedback events.
+    fn write_event(&mut self, event: OutputEvent) -> Result<(), OutputError> {
+        log::trace!("Received output event: {:?}", event);
+        match event {
+            OutputEvent::Evdev(input_event) => Ok(self.process_evdev_ff(input_event)?),
+            OutputEvent::DualSense(_) => Ok(()),
+            OutputEvent::Uinput(_) => Ok(()),
+            OutputEvent::SteamDeckHaptics(_) => Ok(()),
+            OutputEvent::SteamDeckRumble(_) => Ok(()),
+        }
+    }
+
+    /// Upload the given force feedback effect data to the source device. Returns
+    /// a device-specific id of the uploaded effect if it is successful.
+    fn upload_effect(&mut self, effect: FFEffectData) -> Result<i16, OutputError> {
+        log::debug!("Uploading FF effect data");
+        let id = self.next_ff_effect_id();
+        if id == -1 {
+            return Err("Maximum FF effects uploaded".into());
+        }
+        self.ff_evdev_effects.insert(id, effect);
+
+        Ok(id)
+    }
+
+    /// Update the effect with the given id using the given effect data.
+    fn update_effect(&mut self, effect_id: i16, effect: FFEffectData) -> Result<(), OutputError> {
+        log::debug!("Updating FF effect data with id {effect_id}");
+        self.ff_evdev_effects.insert(effect_id, effect);
+        Ok(())
+    }
+
+    /// Erase the effect with the given id from the source device.
+    fn erase_effect(&mut self, effect_id: i16) -> Result<(), OutputError> {
+        log::debug!("Erasing FF effect data");
+        self.ff_evdev_effects.remove(&effect_id);
+        Ok(())
+    }
+
+    /// Returns the possible output events this device is capable of handling
+    fn get_output_capabilities(&self) -> Result<Vec<OutputCapability>, OutputError> {
+        Ok(vec![OutputCapability::ForceFeedback])
+    }
+}
+
+impl Debug for JoyCon {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JoyCon")
+            .field("side", &self.driver.side())
+            .field("ff_evdev_effects", &self.ff_evdev_effects)
+            .finish()
+    }
+}
+
+/// Returns a value between -1.0 and 1.0 based on the given raw value and the
+/// calibrated center, minimum, and maximum values.
+fn normalize_calibrated_value(raw_value: u16, min: u16, center: u16, max: u16) -> f64 {
+    let value = raw_value as f64 - center as f64;
+    let range = if value >= 0.0 {
+        max as f64 - center as f64
+    } else {
+        center as f64 - min as f64
+    };
+    if range <= 0.0 {
+        return 0.0;
+    }
+    (value / range).clamp(-1.0, 1.0)
+}
+
+/// Normalize the stick value to something between -1.0 and 1.0 using the
+/// stick calibration of the Joy-Con.
+fn normalize_axis_value(value: event::JoystickInput, calibration: &StickCalibration) -> InputValue {
+    let x = normalize_calibrated_value(
+        value.x,
+        calibration.x_min,
+        calibration.x_center,
+        calibration.x_max,
+    );
+
+    // The Y axis of the Joy-Con increases upwards
+    let y = -normalize_calibrated_value(
+        value.y,
+        calibration.y_min,
+        calibration.y_center,
+        calibration.y_max,
+    );
+
+    InputValue::Vector2 {
+        x: Some(x),
+        y: Some(y),
+    }
+}
+
+/// Translate the given events into native events
+fn translate_events(events: Vec<event::Event>, calibration: &StickCalibration) -> Vec<NativeEvent> {
+    let mut translated = Vec::with_capacity(events.len());
+    for event in events.into_iter() {
+        translated.push(translate_event(event, calibration));
+    }
+    if !translated.is_empty() {
+        log::trace!("Translated events: {translated:?}");
+    };
+    translated
+}
+
+/// Translate the given event into a native event
+fn translate_event(event: event::Event, calibration: &StickCalibration) -> NativeEvent {
+    log::trace!("Got event {event:?}");
+    match event {
+        event::Event::Button(button) => match button {
+            event::ButtonEvent::A(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::East)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::B(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::X(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::North)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::Y(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::West)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::R(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::RightBumper)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::ZR(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::RightTrigger)),
+                InputValue::Float(if value.pressed { 1.0 } else { 0.0 }),
+            ),
+            event::ButtonEvent::RightSL(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::RightPaddle2)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::RightSR(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::RightPaddle1)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::Plus(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::Start)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::Home(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::Guide)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::RStick(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::RightStick)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::L(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::LeftBumper)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::ZL(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTrigger)),
+                InputValue::Float(if value.pressed { 1.0 } else { 0.0 }),
+            ),
+            event::ButtonEvent::LeftSL(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle1)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::LeftSR(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle2)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::Minus(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::Select)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::Capture(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::Screenshot)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::LStick(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::LeftStick)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::DPadUp(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::DPadUp)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::DPadRight(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::DPadRight)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::DPadDown(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::DPadDown)),
+                InputValue::Bool(value.pressed),
+            ),
+            event::ButtonEvent::DPadLeft(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::DPadLeft)),
+                InputValue::Bool(value.pressed),
+            ),
+        },
+        event::Event::Joystick(axis) => match axis {
+            event::JoystickEvent::LStick(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+                normalize_axis_value(value, calibration),
+            ),
+            event::JoystickEvent::RStick(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Axis(GamepadAxis::RightStick)),
+                normalize_axis_value(value, calibration),
+            ),
+        },
+        event::Event::Inertia(inertia) => match inertia {
+            event::InertialEvent::Accelerometer(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Accelerometer),
+                InputValue::Vector3 {
+                    x: Some(value.x as f64),
+                    y: Some(value.y as f64),
+                    z: Some(value.z as f64),
+                },
+            ),
+            event::InertialEvent::Gyro(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Gyro),
+                InputValue::Vector3 {
+                    x: Some(value.x as f64),
+                    y: Some(value.y as f64),
+                    z: Some(value.z as f64),
+                },
+            ),
+        },
+    }
+}
+
+/// List of all capabilities that the left Joy-Con implements
+pub const LEFT_CAPABILITIES: &[Capability] = &[
+    Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::DPadDown)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::DPadLeft)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::DPadRight)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::DPadUp)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::LeftBumper)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle1)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle2)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::LeftStick)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::Screenshot)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::Select)),
+    Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTrigger)),
+];
+
+/// List of all capabilities that the right Joy-Con implements
+pub const RIGHT_CAPABILITIES: &[Capability] = &[
+    Capability::Gamepad(Gamepad::Accelerometer),
+    Capability::Gamepad(Gamepad::Axis(GamepadAxis::RightStick)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::East)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::Guide)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::North)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::RightBumper)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::RightPaddle1)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::RightPaddle2)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::RightStick)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::Start)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::West)),
+    Capability::Gamepad(Gamepad::Gyro),
+    Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::RightTrigger)),
+];
@@ -7,8 +7,8 @@ use crate::{
     },
     input::{
         capability::{
-            Capability, Gamepad, GamepadAxis, GamepadButton, GamepadTrigger, Mouse, MouseButton,
-            Touch, TouchButton, Touchpad,
+            Capability, Gamepad, GamepadAxis, GamepadButton, GamepadDial, GamepadTrigger, Mouse,
+            MouseButton, Touch, TouchButton, Touchpad,
         },
         event::{native::NativeEvent, value::InputValue},
         source::{InputError, SourceInputDevice, SourceOutputDevice},
@@ -289,9 +289,11 @@ fn translate_event(event: event::Event) -> NativeEvent {
                 Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::RightTrigger)),
                 normalize_trigger_value(trigg),
             ),
-            event::TriggerEvent::MouseWheel(_) => {
-                NativeEvent::new(Capability::NotImplemented, InputValue::Bool(false))
-            }
+            // The scroll wheel reports the number of detents it was rotated
+            event::TriggerEvent::MouseWheel(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Dial(GamepadDial::RightStickDial)),
+                InputValue::Float(value.value as f64),
+            ),
         },
         event::Event::MouseButton(button) => match button {
             event::MouseButtonEvent::Y3(value) => NativeEvent::new(
@@ -355,6 +357,7 @@ pub const CAPABILITIES: &[Capability] = &[
     Capability::Gamepad(Gamepad::Button(GamepadButton::West)),
     Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTrigger)),
     Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::RightTrigger)),
+    Capability::Gamepad(Gamepad::Dial(GamepadDial::RightStickDial)),
     Capability::Mouse(Mouse::Button(MouseButton::Extra)),
     Capability::Mouse(Mouse::Button(MouseButton::Left)),
     Capability::Mouse(Mouse::Button(MouseButton::Middle)),
@@ -0,0 +1,50 @@
+use std::{error::Error, fmt::Debug};
+
+use crate::{
+    drivers::msi_claw::driver::{requested_mode, Driver},
+    input::{
+        capability::Capability,
+        event::native::NativeEvent,
+        source::{InputError, SourceInputDevice, SourceOutputDevice},
+    },
+    udev::device::UdevDevice,
+};
+
+/// MSI Claw source device implementation. Input is read from the evdev
+/// devices of the controller, so this only switches the controller to the
+/// requested hardware mode.
+pub struct MsiClaw {
+    _driver: Driver,
+}
+
+impl MsiClaw {
+    /// Create a new source device with the given udev
+    /// device information
+    pub fn new(device_info: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let mut driver = Driver::new(device_info)?;
+        let mode = requested_mode();
+        if driver.mode() != Some(mode) {
+            log::info!("Switching MSI Claw to {mode} mode");
+            driver.set_mode(mode)?;
+        }
+        Ok(Self { _driver: driver })
+    }
+}
+
+impl Debug for MsiClaw {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MsiClaw").finish()
+    }
+}
+
+impl SourceInputDevice for MsiClaw {
+    fn poll(&mut self) -> Result<Vec<NativeEvent>, InputError> {
+        Ok(vec![])
+    }
+
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(vec![])
+    }
+}
+
+impl SourceOutputDevice for MsiClaw {}
@@ -0,0 +1,320 @@
+use std::{error::Error, fmt::Debug};
+
+use crate::{
+    drivers::steam_controller::{
+        self,
+        driver::{Driver, ACCEL_SCALE},
+    },
+    input::{
+        capability::{
+            Capability, Gamepad, GamepadAxis, GamepadButton, GamepadTrigger, Touch, TouchButton,
+            Touchpad,
+        },
+        event::{native::NativeEvent, value::InputValue},
+        source::{InputError, OutputError, SourceInputDevice, SourceOutputDevice},
+    },
+    udev::device::UdevDevice,
+};
+
+/// Original Steam Controller source device implementation, either wired or
+/// connected through the wireless dongle.
+pub struct SteamController {
+    driver: Driver,
+    device_info: UdevDevice,
+}
+
+impl SteamController {
+    /// Create a new Steam Controller source device with the given udev
+    /// device information
+    pub fn new(device_info: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let driver = Driver::new(device_info.clone())?;
+        Ok(Self {
+            driver,
+            device_info,
+        })
+    }
+}
+
+impl SourceInputDevice for SteamController {
+    /// Poll the given input device for input events
+    fn poll(&mut self) -> Result<Vec<NativeEvent>, InputError> {
+        let events = self.driver.poll()?;
+        let native_events = translate_events(events);
+        Ok(native_events)
+    }
+
+    /// Returns the possible input events this device is capable of emitting
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(CAPABILITIES.into())
+    }
+}
+
+impl SourceOutputDevice for SteamController {
+    /// Stop the source device and give the controller its mouse and keyboard
+    /// emulation back
+    fn stop(&mut self) -> Result<(), OutputError> {
+        if !self.driver.is_connected() {
+            return Ok(());
+        }
+        if let Err(e) = self.driver.set_lizard_mode(true) {
+            log::debug!("Failed to restore lizard mode: {e:?}");
+        }
+        Ok(())
+    }
+}
+
+impl Debug for SteamController {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SteamController")
+            .field("device_info", &self.device_info)
+            .finish()
+    }
+}
+
+/// Returns a value between -1.0 and 1.0 based on the given value with its
+/// minimum and maximum values.
+fn normalize_signed_value(raw_value: f64, min: f64, max: f64) -> f64 {
+    let mid = (max + min) / 2.0;
+    let event_value = raw_value - mid;
+
+    // Normalize the value
+    if event_value >= 0.0 {
+        let maximum = max - mid;
+        event_value / maximum
+    } else {
+        let minimum = min - mid;
+        let value = event_value / minimum;
+        -value
+    }
+}
+
+/// Normalize the value to something between -1.0 and 1.0 based on the Steam
+/// Controller's minimum and maximum axis ranges.
+fn normalize_axis_value(event: steam_controller::event::AxisEvent) -> InputValue {
+    match event {
+        steam_controller::event::AxisEvent::LPad(value)
+        | steam_controller::event::AxisEvent::RPad(value) => {
+            let min = steam_controller::hid_report::PAD_X_MIN;
+            let max = steam_controller::hid_report::PAD_X_MAX;
+            let x = normalize_signed_value(value.x as f64, min, max);
+            let x = (x + 1.0) / 2.0; // Convert from -1.0 - 1.0 range to 0.0 - 1.0 range
+            let x = Some(x);
+
+            let min = steam_controller::hid_report::PAD_Y_MAX; // uses inverted Y-axis
+            let max = steam_controller::hid_report::PAD_Y_MIN;
+            let y = normalize_signed_value(value.y as f64, min, max);
+            let y = -y; // Y-axis is inverted
+            let y = (y + 1.0) / 2.0; // Convert from -1.0 - 1.0 range to 0.0 - 1.0 range
+            let y = Some(y);
+
+            InputValue::Touch {
+                index: value.index,
+                is_touching: value.is_touching,
+                pressure: Some(1.0),
+                x,
+                y,
+            }
+        }
+        steam_controller::event::AxisEvent::LStick(value) => {
+            let min = steam_controller::hid_report::STICK_X_MIN;
+            let max = steam_controller::hid_report::STICK_X_MAX;
+            let x = normalize_signed_value(value.x as f64, min, max);
+            let x = Some(x);
+
+            let min = steam_controller::hid_report::STICK_Y_MAX; // uses inverted Y-axis
+            let max = steam_controller::hid_report::STICK_Y_MIN;
+            let y = normalize_signed_value(value.y as f64, min, max);
+            let y = Some(-y); // Y-Axis is inverted
+
+            InputValue::Vector2 { x, y }
+        }
+    }
+}
+
+/// Normalize the trigger value to something between 0.0 and 1.0 based on the
+/// Steam Controller's maximum axis ranges.
+fn normalize_trigger_value(event: steam_controller::event::TriggerEvent) -> InputValue {
+    let max = steam_controller::hid_report::TRIGG_MAX;
+    match event {
+        steam_controller::event::TriggerEvent::LTrigger(value)
+        | steam_controller::event::TriggerEvent::RTrigger(value) => {
+            InputValue::Float(value.value as f64 / max)
+        }
+    }
+}
+
+/// Translate the given Steam Controller events into native events
+fn translate_events(events: Vec<steam_controller::event::Event>) -> Vec<NativeEvent> {
+    events.into_iter().map(translate_event).collect()
+}
+
+/// Translate the given Steam Controller event into a native event
+fn translate_event(event: steam_controller::event::Event) -> NativeEvent {
+    match event {
+        steam_controller::event::Event::Button(button) => match button {
+            steam_controller::event::ButtonEvent::A(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+                InputValue::Bool(value.pressed),
+            ),
+            steam_controller::event::ButtonEvent::X(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::North)),
+                InputValue::Bool(value.pressed),
+            ),
+            steam_controller::event::ButtonEvent::B(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::East)),
+                InputValue::Bool(value.pressed),
+            ),
+            steam_controller::event::ButtonEvent::Y(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::West)),
+                InputValue::Bool(value.pressed),
+            ),
+            steam_controller::event::ButtonEvent::Start(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::Start)),
+                InputValue::Bool(value.pressed),
+            ),
+            steam_controller::event::ButtonEvent::Select(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::Select)),
+                InputValue::Bool(value.pressed),
+            ),
+            steam_controller::event::ButtonEvent::Steam(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::Guide)),
+                InputValue::Bool(value.pressed),
+            ),
+            steam_controller::event::ButtonEvent::DPadDown(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::DPadDown)),
+                InputValue::Bool(value.pressed),
+            ),
+            steam_controller::event::ButtonEvent::DPadUp(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::DPadUp)),
+                InputValue::Bool(value.pressed),
+            ),
+            steam_controller::event::ButtonEvent::DPadLeft(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::DPadLeft)),
+                InputValue::Bool(value.pressed),
+            ),
+            steam_controller::event::ButtonEvent::DPadRight(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::DPadRight)),
+                InputValue::Bool(value.pressed),
+            ),
+            steam_controller::event::ButtonEvent::L1(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::LeftBumper)),
+                InputValue::Bool(value.pressed),
+            ),
+            steam_controller::event::ButtonEvent::L2(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::LeftTrigger)),
+                InputValue::Bool(value.pressed),
+            ),
+            steam_controller::event::ButtonEvent::L3(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::LeftStick)),
+                InputValue::Bool(value.pressed),
+            ),
+            steam_controller::event::ButtonEvent::L4(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle1)),
+                InputValue::Bool(value.pressed),
+            ),
+            steam_controller::event::ButtonEvent::R1(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::RightBumper)),
+                InputValue::Bool(value.pressed),
+            ),
+            steam_controller::event::ButtonEvent::R2(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::RightTrigger)),
+                InputValue::Bool(value.pressed),
+            ),
+            steam_controller::event::ButtonEvent::R4(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::RightPaddle1)),
+                InputValue::Bool(value.pressed),
+            ),
+            steam_controller::event::ButtonEvent::RPadTouch(value) => NativeEvent::new(
+                Capability::Touchpad(Touchpad::RightPad(Touch::Button(TouchButton::Touch))),
+                InputValue::Bool(value.pressed),
+            ),
+            steam_controller::event::ButtonEvent::LPadTouch(value) => NativeEvent::new(
+                Capability::Touchpad(Touchpad::LeftPad(Touch::Button(TouchButton::Touch))),
+                InputValue::Bool(value.pressed),
+            ),
+            steam_controller::event::ButtonEvent::RPadPress(value) => NativeEvent::new(
+                Capability::Touchpad(Touchpad::RightPad(Touch::Button(TouchButton::Press))),
+                InputValue::Bool(value.pressed),
+            ),
+            steam_controller::event::ButtonEvent::LPadPress(value) => NativeEvent::new(
+                Capability::Touchpad(Touchpad::LeftPad(Touch::Button(TouchButton::Press))),
+                InputValue::Bool(value.pressed),
+            ),
+        },
+        steam_controller::event::Event::Accelerometer(accel) => match accel {
+            steam_controller::event::AccelerometerEvent::Accelerometer(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Accelerometer),
+                InputValue::Vector3 {
+                    x: Some(value.x as f64 * ACCEL_SCALE),
+                    y: Some(value.y as f64 * ACCEL_SCALE),
+                    z: Some(value.z as f64 * ACCEL_SCALE),
+                },
+            ),
+            steam_controller::event::AccelerometerEvent::Attitude(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Gyro),
+                InputValue::Vector3 {
+                    x: Some(value.x as f64),
+                    y: Some(value.y as f64),
+                    z: Some(value.z as f64),
+                },
+            ),
+        },
+        steam_controller::event::Event::Axis(axis) => match axis.clone() {
+            steam_controller::event::AxisEvent::LPad(_) => NativeEvent::new(
+                Capability::Touchpad(Touchpad::LeftPad(Touch::Motion)),
+                normalize_axis_value(axis),
+            ),
+            steam_controller::event::AxisEvent::RPad(_) => NativeEvent::new(
+                Capability::Touchpad(Touchpad::RightPad(Touch::Motion)),
+                normalize_axis_value(axis),
+            ),
+            steam_controller::event::AxisEvent::LStick(_) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+                normalize_axis_value(axis),
+            ),
+        },
+        steam_controller::event::Event::Trigger(trigg) => match trigg.clone() {
+            steam_controller::event::TriggerEvent::LTrigger(_) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTrigger)),
+                normalize_trigger_value(trigg),
+            ),
+            steam_controller::event::TriggerEvent::RTrigger(_) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::RightTrigger)),
+                normalize_trigger_value(trigg),
+            ),
+        },
+    }
+}
+
+/// List of all capabilities that the Steam Controller driver implements
+pub const CAPABILITIES: &[Capability] = &[
+    Capability::Gamepad(Gamepad::Accelerometer),
+    Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::DPadDown)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::DPadLeft)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::DPadRight)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::DPadUp)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::East)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::Guide)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::LeftBumper)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle1)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::LeftStick)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::LeftTrigger)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::North)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::RightBumper)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::RightPaddle1)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::RightTrigger)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::Select)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::Start)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::West)),
+    Capability::Gamepad(Gamepad::Gyro),
+    Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTrigger)),
+    Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::RightTrigger)),
+    Capability::Touchpad(Touchpad::LeftPad(Touch::Button(TouchButton::Press))),
+    Capability::Touchpad(Touchpad::LeftPad(Touch::Button(TouchButton::Touch))),
+    Capability::Touchpad(Touchpad::LeftPad(Touch::Motion)),
+    Capability::Touchpad(Touchpad::RightPad(Touch::Button(TouchButton::Press))),
+    Capability::Touchpad(Touchpad::RightPad(Touch::Button(TouchButton::Touch))),
+    Capability::Touchpad(Touchpad::RightPad(Touch::Motion)),
+];
@@ -25,6 +25,7 @@ use crate::{
             Touchpad,
         },
         event::{native::NativeEvent, value::InputValue},
+        output_capability::{Haptic, OutputCapability},
         output_event::OutputEvent,
         source::{InputError, OutputError, SourceInputDevice, SourceOutputDevice},
     },
@@ -267,6 +268,15 @@ impl SourceOutputDevice for DeckController {
         Ok(())
     }
 
+    /// Returns the possible output events this device is capable of handling
+    fn get_output_capabilities(&self) -> Result<Vec<OutputCapability>, OutputError> {
+        Ok(vec![
+            OutputCapability::ForceFeedback,
+            OutputCapability::Haptics(Haptic::TrackpadLeft),
+            OutputCapability::Haptics(Haptic::TrackpadRight),
+        ])
+    }
+
     /// Stop the source device and terminate the lizard mode task
     fn stop(&mut self) -> Result<(), OutputError> {
         *self.lizard_mode_running.lock().unwrap() = false;
@@ -2,6 +2,9 @@ pub mod accel_gyro_3d;
 pub mod accel_gyro_3d_new;
 pub mod bmi_imu;
 pub mod bmi_imu_new;
+pub mod settings;
+#[cfg(test)]
+pub mod settings_test;
 
 use std::error::Error;
 
@@ -14,7 +17,7 @@ use crate::{
 
 use self::{accel_gyro_3d_new::AccelGyro3dImu, bmi_imu_new::BmiImu};
 
-use super::{SourceDeviceCompatible, SourceDriver};
+use super::{quirks::Quirks, SourceDeviceCompatible, SourceDriver};
 
 /// List of available drivers
 enum DriverType {
@@ -68,6 +71,15 @@ impl SourceDeviceCompatible for IioDevice {
         }
     }
 
+    fn get_output_capabilities(
+        &self,
+    ) -> Result<Vec<crate::input::output_capability::OutputCapability>, super::OutputError> {
+        match self {
+            IioDevice::BmiImu(source_driver) => source_driver.get_output_capabilities(),
+            IioDevice::AccelGryo3D(source_driver) => source_driver.get_output_capabilities(),
+        }
+    }
+
     fn get_device_path(&self) -> String {
         match self {
             IioDevice::BmiImu(source_driver) => source_driver.get_device_path(),
@@ -83,22 +95,35 @@ impl IioDevice {
     pub fn new(
         device_info: UdevDevice,
         composite_device: CompositeDeviceClient,
-        config: Option<config::IIO>,
+        config: Option<&config::SourceDevice>,
     ) -> Result<Self, Box<dyn Error + Send + Sync>> {
         let driver_type = IioDevice::get_driver_type(&device_info);
 
-        match driver_type {
-            DriverType::Unknown => Err("No driver for iio interface found".into()),
+        let quirks = Quirks::new(&device_info, config);
+        let iio_config = config.and_then(|config| config.iio.clone());
+        let mut device = match driver_type {
+            DriverType::Unknown => return Err("No driver for iio interface found".into()),
             DriverType::BmiImu => {
-                let device = BmiImu::new(device_info.clone(), config)?;
+                let device = BmiImu::new(device_info.clone(), iio_config)?;
                 let source_device = SourceDriver::new(composite_device, device, device_info);
-                Ok(Self::BmiImu(source_device))
+                Self::BmiImu(source_device)
             }
             DriverType::AccelGryo3D => {
-                let device = AccelGyro3dImu::new(device_info.clone(), config)?;
+                let device = AccelGyro3dImu::new(device_info.clone(), iio_config)?;
                 let source_device = SourceDriver::new(composite_device, device, device_info);
-                Ok(Self::AccelGryo3D(source_device))
+                Self::AccelGryo3D(source_device)
             }
+        };
+        device.set_quirks(quirks);
+
+        Ok(device)
+    }
+
+    /// Set the quirks that should be applied to input events from the device
+    fn set_quirks(&mut self, quirks: Quirks) {
+        match self {
+            IioDevice::BmiImu(source_driver) => source_driver.set_quirks(quirks),
+            IioDevice::AccelGryo3D(source_driver) => source_driver.set_quirks(quirks),
         }
     }
 
@@ -6,13 +6,15 @@ use crate::{
     input::{
         capability::{Capability, Gamepad},
         event::{native::NativeEvent, value::InputValue},
-        source::{InputError, SourceInputDevice, SourceOutputDevice},
+        source::{iio::settings, InputError, SourceInputDevice, SourceOutputDevice},
     },
     udev::device::UdevDevice,
 };
 
 pub struct AccelGyro3dImu {
     driver: Driver,
+    id: String,
+    settings_generation: u64,
 }
 
 impl AccelGyro3dImu {
@@ -40,15 +42,27 @@ impl AccelGyro3dImu {
 
         let id = device_info.sysname();
         let name = device_info.name();
-        let driver = Driver::new(id, name, mount_matrix)?;
+        let driver = Driver::new(id.clone(), name, mount_matrix)?;
+        let settings_generation = settings::generation(&id);
 
-        Ok(Self { driver })
+        Ok(Self {
+            driver,
+            id,
+            settings_generation,
+        })
     }
 }
 
 impl SourceInputDevice for AccelGyro3dImu {
     /// Poll the given input device for input events
     fn poll(&mut self) -> Result<Vec<NativeEvent>, InputError> {
+        // Re-read the scale if the IMU settings were changed
+        let generation = settings::generation(&self.id);
+        if generation != self.settings_generation {
+            self.driver.reload_scales();
+            self.settings_generation = generation;
+        }
+
         let events = self.driver.poll()?;
         let native_events = translate_events(events);
         Ok(native_events)
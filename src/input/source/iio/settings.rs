@@ -0,0 +1,114 @@
+//! Runtime IMU settings of IIO source devices. Sample rates and scales chosen
+//! over DBus are kept for as long as InputPlumber runs so they can be
+//! reapplied after the system resumes, since IIO drivers may reset them to
+//! their defaults.
+use std::{collections::HashMap, error::Error, sync::RwLock};
+
+use crate::udev::device::{AttributeSetter, UdevDevice};
+
+/// Runtime settings of all IIO devices keyed by their sysname (e.g. "iio:device0")
+static SETTINGS: RwLock<Option<HashMap<String, ImuSettings>>> = RwLock::new(None);
+
+/// A configurable IMU attribute
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImuSetting {
+    AccelSampleRate,
+    AngvelSampleRate,
+    AccelScale,
+    AngvelScale,
+}
+
+impl ImuSetting {
+    /// Returns the IIO sysfs attribute of the setting
+    pub fn attribute(&self) -> &str {
+        match self {
+            ImuSetting::AccelSampleRate => "in_accel_sampling_frequency",
+            ImuSetting::AngvelSampleRate => "in_anglvel_sampling_frequency",
+            ImuSetting::AccelScale => "in_accel_scale",
+            ImuSetting::AngvelScale => "in_anglvel_scale",
+        }
+    }
+}
+
+/// Settings chosen for a single IIO device
+#[derive(Debug, Clone)]
+struct ImuSettings {
+    device: UdevDevice,
+    values: HashMap<ImuSetting, f64>,
+    /// Incremented each time a setting is applied to the device
+    generation: u64,
+}
+
+/// Set the given setting on the device and remember it so it can be
+/// reapplied on resume.
+pub fn set(device: &UdevDevice, setting: ImuSetting, value: f64) -> Result<(), Box<dyn Error>> {
+    write_attribute(device, setting, value)?;
+
+    let Ok(mut settings) = SETTINGS.write() else {
+        return Err("Failed to lock IMU settings".into());
+    };
+    let entry = settings
+        .get_or_insert_with(HashMap::new)
+        .entry(device.sysname())
+        .or_insert_with(|| ImuSettings {
+            device: device.clone(),
+            values: HashMap::new(),
+            generation: 0,
+        });
+    entry.values.insert(setting, value);
+    entry.generation += 1;
+
+    Ok(())
+}
+
+/// Returns a number that changes whenever settings are applied to the device
+/// with the given id. Source devices use this to detect when they need to
+/// re-read the scale of the device.
+pub fn generation(id: &str) -> u64 {
+    let Ok(settings) = SETTINGS.read() else {
+        return 0;
+    };
+    settings
+        .as_ref()
+        .and_then(|settings| settings.get(id))
+        .map(|entry| entry.generation)
+        .unwrap_or_default()
+}
+
+/// Forget the settings of the device with the given id after it was removed
+pub fn remove(id: &str) {
+    if let Ok(mut settings) = SETTINGS.write() {
+        if let Some(settings) = settings.as_mut() {
+            settings.remove(id);
+        }
+    }
+}
+
+/// Reapply the chosen settings to all IIO devices
+pub fn reapply_all() {
+    let Ok(mut settings) = SETTINGS.write() else {
+        return;
+    };
+    let Some(settings) = settings.as_mut() else {
+        return;
+    };
+    for (id, entry) in settings.iter_mut() {
+        for (setting, value) in entry.values.iter() {
+            log::debug!("Reapplying {setting:?} of {value} to {id}");
+            if let Err(e) = write_attribute(&entry.device, *setting, *value) {
+                log::warn!("Failed to reapply {setting:?} to {id}: {e:?}");
+            }
+        }
+        entry.generation += 1;
+    }
+}
+
+/// Write the given setting to the device
+fn write_attribute(
+    device: &UdevDevice,
+    setting: ImuSetting,
+    value: f64,
+) -> Result<(), Box<dyn Error>> {
+    let mut dev = device.get_device()?;
+    dev.set_attribute_on_tree(setting.attribute(), value.to_string().as_str())
+}
@@ -0,0 +1,20 @@
+use crate::input::source::iio::settings::{generation, ImuSetting};
+
+#[test]
+fn test_imu_setting_attribute() {
+    assert_eq!(
+        ImuSetting::AccelSampleRate.attribute(),
+        "in_accel_sampling_frequency"
+    );
+    assert_eq!(
+        ImuSetting::AngvelSampleRate.attribute(),
+        "in_anglvel_sampling_frequency"
+    );
+    assert_eq!(ImuSetting::AccelScale.attribute(), "in_accel_scale");
+    assert_eq!(ImuSetting::AngvelScale.attribute(), "in_anglvel_scale");
+}
+
+#[test]
+fn test_imu_settings_generation_unknown_device() {
+    assert_eq!(generation("iio:device-unknown"), 0);
+}
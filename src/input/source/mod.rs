@@ -1,5 +1,6 @@
 use std::{
     error::Error,
+    os::fd::RawFd,
     sync::{Arc, Mutex, MutexGuard},
     thread,
     time::Duration,
@@ -12,22 +13,45 @@ use tokio::sync::mpsc::{self, error::TryRecvError};
 use crate::udev::device::UdevDevice;
 
 use self::{
-    client::SourceDeviceClient, command::SourceCommand, evdev::EventDevice, hidraw::HidRawDevice,
+    client::SourceDeviceClient,
+    command::{BypassRoutes, SourceCommand},
+    discovery::CapabilityDiscovery,
+    evdev::EventDevice,
+    external::ExternalDevice,
+    hidraw::HidRawDevice,
     iio::IioDevice,
+    quirks::Quirks,
 };
 
 use super::{
     capability::Capability,
     composite_device::client::CompositeDeviceClient,
     event::{native::NativeEvent, Event},
+    latency,
+    output_capability::OutputCapability,
     output_event::OutputEvent,
+    readiness::{self, Wake},
+    target::client::TargetDeviceClient,
 };
 
 pub mod client;
 pub mod command;
+pub mod discovery;
+#[cfg(test)]
+pub mod discovery_test;
 pub mod evdev;
+pub mod external;
 pub mod hidraw;
 pub mod iio;
+pub mod network;
+#[cfg(test)]
+pub mod network_test;
+pub mod quirks;
+#[cfg(test)]
+pub mod quirks_test;
+pub mod watchdog;
+#[cfg(test)]
+pub mod watchdog_test;
 
 /// Size of the [SourceCommand] buffer for receiving output events
 const BUFFER_SIZE: usize = 2048;
@@ -106,6 +130,22 @@ pub trait SourceInputDevice {
 
     /// Returns the possible input events this device is capable of emitting
     fn get_capabilities(&self) -> Result<Vec<Capability>, InputError>;
+
+    /// Returns a non-blocking file descriptor that becomes readable when the
+    /// device has input events. If implemented, the device is only polled
+    /// when the descriptor is readable or a command is received instead of
+    /// at a fixed poll rate.
+    fn poll_fd(&self) -> Option<RawFd> {
+        None
+    }
+
+    /// Returns capabilities the device discovered since the last call, such
+    /// as touchpad or IMU capabilities that are only available after an
+    /// enable command. Capabilities of polled events are discovered
+    /// automatically and do not need to be returned here.
+    fn take_discovered_capabilities(&mut self) -> Vec<Capability> {
+        Vec::new()
+    }
 }
 
 /// A [SourceOutputDevice] is a device implementation that can handle output events
@@ -144,6 +184,11 @@ pub trait SourceOutputDevice {
         Ok(())
     }
 
+    /// Returns the possible output events this device is capable of handling
+    fn get_output_capabilities(&self) -> Result<Vec<OutputCapability>, OutputError> {
+        Ok(vec![])
+    }
+
     /// Stop the source device.
     fn stop(&mut self) -> Result<(), OutputError> {
         Ok(())
@@ -173,6 +218,7 @@ pub struct SourceDriver<T: SourceInputDevice + SourceOutputDevice> {
     implementation: Arc<Mutex<T>>,
     device_info: UdevDevice,
     composite_device: CompositeDeviceClient,
+    quirks: Quirks,
     tx: mpsc::Sender<SourceCommand>,
     rx: mpsc::Receiver<SourceCommand>,
 }
@@ -191,6 +237,7 @@ impl<T: SourceInputDevice + SourceOutputDevice + Send + 'static> SourceDriver<T>
             implementation: Arc::new(Mutex::new(device)),
             device_info,
             composite_device,
+            quirks: Quirks::default(),
             tx,
             rx,
         }
@@ -209,6 +256,7 @@ impl<T: SourceInputDevice + SourceOutputDevice + Send + 'static> SourceDriver<T>
             implementation: Arc::new(Mutex::new(device)),
             device_info,
             composite_device,
+            quirks: Quirks::default(),
             tx,
             rx,
         }
@@ -221,7 +269,21 @@ impl<T: SourceInputDevice + SourceOutputDevice + Send + 'static> SourceDriver<T>
 
     /// Returns the possible input events this device is capable of emitting
     pub fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
-        self.implementation.lock().unwrap().get_capabilities()
+        let capabilities = self.implementation.lock().unwrap().get_capabilities()?;
+        Ok(self.quirks.map_capabilities(capabilities))
+    }
+
+    /// Returns the possible output events this device is capable of handling
+    pub fn get_output_capabilities(&self) -> Result<Vec<OutputCapability>, OutputError> {
+        self.implementation
+            .lock()
+            .unwrap()
+            .get_output_capabilities()
+    }
+
+    /// Set the quirks that should be applied to input events from the device
+    pub fn set_quirks(&mut self, quirks: Quirks) {
+        self.quirks = quirks;
     }
 
     /// Returns the path to the device (e.g. "/dev/input/event0")
@@ -239,19 +301,88 @@ impl<T: SourceInputDevice + SourceOutputDevice + Send + 'static> SourceDriver<T>
         &self.device_info
     }
 
-    /// Run the source device, consuming the device.
+    /// Run the source device, consuming the device. Returns a
+    /// [watchdog::Abandoned] error if the device was restarted by the
+    /// watchdog while it was stuck polling.
     pub async fn run(self) -> Result<(), Box<dyn Error>> {
         let device_id = self.get_id();
+        let heartbeat = watchdog::register(&device_id);
+        let watchdog_id = device_id.clone();
+        let thread_heartbeat = heartbeat.clone();
+        let capabilities = self.get_capabilities().unwrap_or_default();
 
         // Spawn a blocking task to run the source device.
         let task =
             tokio::task::spawn_blocking(move || -> Result<(), Box<dyn Error + Send + Sync>> {
+                let mut latency_mode = latency::ModeWatcher::new();
                 let mut rx = self.rx;
+                let mut quirks = self.quirks;
+                let mut bypass: Option<BypassRoutes> = None;
+                let mut discovery = CapabilityDiscovery::new(capabilities);
                 let mut implementation = self.implementation.lock().unwrap();
+                let runtime = tokio::runtime::Handle::current();
+
+                // Wait for the device to become readable instead of polling
+                // at a fixed rate if the implementation supports it.
+                let fd = implementation.poll_fd().and_then(|fd| {
+                    readiness::register(fd)
+                        .inspect_err(|e| log::debug!("Failed to register {device_id}: {e:?}"))
+                        .ok()
+                });
+
+                // Initial events are ignored from the time the device is read
+                quirks.set_opened();
+
                 loop {
+                    latency_mode.update();
+
                     // Poll the implementation for events
-                    let events = implementation.poll()?;
+                    thread_heartbeat.begin_poll();
+                    let events = implementation.poll();
+                    thread_heartbeat.end_poll();
+
+                    // Stop without sending any events if the device was
+                    // restarted by the watchdog while it was stuck.
+                    if thread_heartbeat.is_abandoned() {
+                        log::warn!("Stopping abandoned source device thread: {device_id}");
+                        break;
+                    }
+                    let events = events?;
+                    let has_events = !events.is_empty();
+                    let events = quirks.apply(events);
+
+                    // Let the composite device know about capabilities that
+                    // were not reported when the device was added before
+                    // sending any of their events.
+                    let mut discovered =
+                        discovery.extend(implementation.take_discovered_capabilities());
+                    discovered.extend(discovery.discover(&events));
+                    if !discovered.is_empty() {
+                        log::debug!("Discovered capabilities of {device_id}: {discovered:?}");
+                        let result = self
+                            .composite_device
+                            .blocking_update_source_capabilities(device_id.clone(), discovered);
+                        if let Err(e) = result {
+                            return Err(e.to_string().into());
+                        }
+                    }
+
                     for event in events.into_iter() {
+                        // Write the event directly to target devices if the
+                        // composite device allows it for this capability
+                        let targets = bypass
+                            .as_ref()
+                            .and_then(|routes| routes.get(&event.as_capability()));
+                        if let Some(targets) = targets {
+                            if SourceDriver::<T>::write_bypass_event(&runtime, targets, &event) {
+                                continue;
+                            }
+                            // Fall back to the composite device if a target
+                            // device is gone until new routes are set.
+                            log::debug!("Disabling bypass for {device_id}");
+                            bypass = None;
+                        }
+
                         let event = Event::Native(event);
                         let result = self
                             .composite_device
@@ -262,73 +393,89 @@ impl<T: SourceInputDevice + SourceOutputDevice + Send + 'static> SourceDriver<T>
                     }
 
                     // Receive commands/output events
-                    if let Err(e) = SourceDriver::receive_commands(&mut rx, &mut implementation) {
+                    if let Err(e) =
+                        SourceDriver::receive_commands(&mut rx, &mut implementation, &mut bypass)
+                    {
                         log::debug!("Error receiving commands: {:?}", e);
                         break;
                     }
 
                     // Sleep for the configured duration
-                    thread::sleep(self.options.poll_rate);
+                    let Some(fd) = fd.as_ref() else {
+                        thread::sleep(latency::poll_rate(self.options.poll_rate));
+                        continue;
+                    };
+
+                    // Keep reading while the device has events, otherwise
+                    // wait for it to become readable or receive a command.
+                    if has_events {
+                        continue;
+                    }
+                    let timeout = quirks
+                        .time_until_release()
+                        .unwrap_or(readiness::MAX_WAIT)
+                        .min(readiness::MAX_WAIT);
+                    match readiness::wait(&runtime, Some(fd), &mut rx, timeout) {
+                        Wake::Command(Some(cmd)) => {
+                            if let Err(e) =
+                                SourceDriver::handle_command(cmd, &mut implementation, &mut bypass)
+                            {
+                                log::debug!("Error handling command: {:?}", e);
+                                break;
+                            }
+                        }
+                        Wake::Command(None) => {
+                            log::debug!("Receive channel disconnected");
+                            break;
+                        }
+                        Wake::Readable | Wake::Timeout => (),
+                    }
                 }
 
                 Ok(())
             });
 
         // Wait for the device to finish running.
-        if let Err(e) = task.await? {
+        let result = task.await?;
+        watchdog::unregister(&watchdog_id, &heartbeat);
+        if heartbeat.is_abandoned() {
+            return Err(Box::new(watchdog::Abandoned));
+        }
+        if let Err(e) = result {
             return Err(e.to_string().into());
         }
 
         Ok(())
     }
 
+    /// Write the given event directly to the given target devices. Returns
+    /// false if any of the target devices no longer exists.
+    fn write_bypass_event(
+        runtime: &tokio::runtime::Handle,
+        targets: &[TargetDeviceClient],
+        event: &NativeEvent,
+    ) -> bool {
+        for target in targets {
+            if let Err(e) = runtime.block_on(target.write_event(event.clone())) {
+                log::debug!("Failed to write bypass event: {e:?}");
+                return false;
+            }
+        }
+        true
+    }
+
     /// Read commands sent to this device from the channel until it is
     /// empty.
     fn receive_commands(
         rx: &mut mpsc::Receiver<SourceCommand>,
         implementation: &mut MutexGuard<'_, T>,
+        bypass: &mut Option<BypassRoutes>,
     ) -> Result<(), Box<dyn Error>> {
         const MAX_COMMANDS: u8 = 64;
         let mut commands_processed = 0;
         loop {
             match rx.try_recv() {
-                Ok(cmd) => match cmd {
-                    SourceCommand::UploadEffect(data, composite_dev) => {
-                        let res = match implementation.upload_effect(data) {
-                            Ok(id) => composite_dev.send(Ok(id)),
-                            Err(e) => {
-                                let err = format!("Failed to upload effect: {:?}", e);
-                                composite_dev.send(Err(err.into()))
-                            }
-                        };
-                        if let Err(err) = res {
-                            log::error!("Failed to send upload result: {:?}", err);
-                        }
-                    }
-                    SourceCommand::UpdateEffect(effect_id, data) => {
-                        implementation.update_effect(effect_id, data)?;
-                    }
-                    SourceCommand::EraseEffect(id, composite_dev) => {
-                        let res = match implementation.erase_effect(id) {
-                            Ok(_) => Ok(()),
-                            Err(e) => {
-                                let err = format!("Failed to erase effect: {e:?}");
-                                composite_dev.send(Err(err.into()))
-                            }
-                        };
-                        if let Err(err) = res {
-                            log::error!("Failed to send erase result: {:?}", err);
-                        }
-                    }
-                    SourceCommand::WriteEvent(event) => {
-                        log::trace!("Received output event: {:?}", event);
-                        implementation.write_event(event)?;
-                    }
-                    SourceCommand::Stop => {
-                        implementation.stop()?;
-                        return Err("Device stopped".into());
-                    }
-                },
+                Ok(cmd) => SourceDriver::handle_command(cmd, implementation, bypass)?,
                 Err(e) => match e {
                     TryRecvError::Empty => return Ok(()),
                     TryRecvError::Disconnected => {
@@ -345,6 +492,57 @@ impl<T: SourceInputDevice + SourceOutputDevice + Send + 'static> SourceDriver<T>
             }
         }
     }
+
+    /// Handle a single command sent to this device
+    fn handle_command(
+        cmd: SourceCommand,
+        implementation: &mut MutexGuard<'_, T>,
+        bypass: &mut Option<BypassRoutes>,
+    ) -> Result<(), Box<dyn Error>> {
+        match cmd {
+            SourceCommand::UploadEffect(data, composite_dev) => {
+                let res = match implementation.upload_effect(data) {
+                    Ok(id) => composite_dev.send(Ok(id)),
+                    Err(e) => {
+                        let err = format!("Failed to upload effect: {:?}", e);
+                        composite_dev.send(Err(err.into()))
+                    }
+                };
+                if let Err(err) = res {
+                    log::error!("Failed to send upload result: {:?}", err);
+                }
+            }
+            SourceCommand::UpdateEffect(effect_id, data) => {
+                implementation.update_effect(effect_id, data)?;
+            }
+            SourceCommand::EraseEffect(id, composite_dev) => {
+                let res = match implementation.erase_effect(id) {
+                    Ok(_) => Ok(()),
+                    Err(e) => {
+                        let err = format!("Failed to erase effect: {e:?}");
+                        composite_dev.send(Err(err.into()))
+                    }
+                };
+                if let Err(err) = res {
+                    log::error!("Failed to send erase result: {:?}", err);
+                }
+            }
+            SourceCommand::WriteEvent(event) => {
+                log::trace!("Received output event: {:?}", event);
+                implementation.write_event(event)?;
+            }
+            SourceCommand::SetBypass(routes) => {
+                log::debug!("Setting bypass routes: {routes:?}");
+                *bypass = routes;
+            }
+            SourceCommand::Stop => {
+                implementation.stop()?;
+                return Err("Device stopped".into());
+            }
+        }
+
+        Ok(())
+    }
 }
 
 pub(crate) trait SourceDeviceCompatible {
@@ -363,6 +561,9 @@ pub(crate) trait SourceDeviceCompatible {
     /// Returns the capabilities that this source device can fulfill.
     fn get_capabilities(&self) -> Result<Vec<Capability>, InputError>;
 
+    /// Returns the output capabilities that this source device can fulfill.
+    fn get_output_capabilities(&self) -> Result<Vec<OutputCapability>, OutputError>;
+
     /// Returns the full path to the device handler (e.g. /dev/input/event3, /dev/hidraw0)
     fn get_device_path(&self) -> String;
 }
@@ -373,6 +574,7 @@ pub enum SourceDevice {
     Event(EventDevice),
     HidRaw(HidRawDevice),
     Iio(IioDevice),
+    External(ExternalDevice),
 }
 
 impl SourceDevice {
@@ -382,6 +584,7 @@ impl SourceDevice {
             SourceDevice::Event(device) => device.get_device_ref(),
             SourceDevice::HidRaw(device) => device.get_device_ref(),
             SourceDevice::Iio(device) => device.get_device_ref(),
+            SourceDevice::External(device) => device.get_device_ref(),
         }
     }
 
@@ -391,6 +594,7 @@ impl SourceDevice {
             SourceDevice::Event(device) => device.get_id(),
             SourceDevice::HidRaw(device) => device.get_id(),
             SourceDevice::Iio(device) => device.get_id(),
+            SourceDevice::External(device) => device.get_id(),
         }
     }
 
@@ -400,6 +604,7 @@ impl SourceDevice {
             SourceDevice::Event(device) => device.client(),
             SourceDevice::HidRaw(device) => device.client(),
             SourceDevice::Iio(device) => device.client(),
+            SourceDevice::External(device) => device.client(),
         }
     }
 
@@ -409,6 +614,7 @@ impl SourceDevice {
             SourceDevice::Event(device) => device.run().await,
             SourceDevice::HidRaw(device) => device.run().await,
             SourceDevice::Iio(device) => device.run().await,
+            SourceDevice::External(device) => device.run().await,
         }
     }
 
@@ -418,6 +624,17 @@ impl SourceDevice {
             SourceDevice::Event(device) => device.get_capabilities(),
             SourceDevice::HidRaw(device) => device.get_capabilities(),
             SourceDevice::Iio(device) => device.get_capabilities(),
+            SourceDevice::External(device) => device.get_capabilities(),
+        }
+    }
+
+    /// Returns the output capabilities that this source device can fulfill.
+    pub fn get_output_capabilities(&self) -> Result<Vec<OutputCapability>, OutputError> {
+        match self {
+            SourceDevice::Event(device) => device.get_output_capabilities(),
+            SourceDevice::HidRaw(device) => device.get_output_capabilities(),
+            SourceDevice::Iio(device) => device.get_output_capabilities(),
+            SourceDevice::External(device) => device.get_output_capabilities(),
         }
     }
 
@@ -427,6 +644,7 @@ impl SourceDevice {
             SourceDevice::Event(device) => device.get_device_path(),
             SourceDevice::HidRaw(device) => device.get_device_path(),
             SourceDevice::Iio(device) => device.get_device_path(),
+            SourceDevice::External(device) => device.get_device_path(),
         }
     }
 }
@@ -0,0 +1,136 @@
+//! Network transport for input from remote devices, such as the "network"
+//! target device of an InputPlumber instance on another machine or in a VM.
+//! Remote devices connect over TCP or vsock and use the external driver
+//! protocol, so each connection is added as an external source device.
+//!
+//! Listening is disabled by default and can be enabled with the
+//! `network_listen` option of the global config, e.g. "tcp://0.0.0.0:31337"
+//! or "vsock://any:31337". Anyone that can connect to the address can send
+//! input, so it should only be exposed to trusted networks.
+use std::{error::Error, fmt, str::FromStr};
+
+use tokio::{net::TcpListener, sync::mpsc};
+use tokio_vsock::{VsockAddr, VsockListener};
+
+use crate::input::manager::ManagerCommand;
+
+use super::external::server;
+
+/// Default port used for network input
+pub const DEFAULT_PORT: u32 = 31337;
+/// Context id that listens on all vsock context ids
+pub const VSOCK_CID_ANY: u32 = u32::MAX;
+
+/// Address to listen on or connect to for network input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkAddress {
+    /// TCP address with a host and port, e.g. "192.168.1.10:31337"
+    Tcp(String),
+    /// Vsock address of a virtual machine or its host
+    Vsock { cid: u32, port: u32 },
+}
+
+impl FromStr for NetworkAddress {
+    type Err = String;
+
+    /// Parse an address in the form "tcp://<host>[:<port>]" or
+    /// "vsock://<cid>[:<port>]", where the cid can be "any" to listen on all
+    /// context ids.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid network address: {s}");
+        let (scheme, address) = s.split_once("://").ok_or_else(invalid)?;
+        match scheme {
+            "tcp" => {
+                if address.is_empty() {
+                    return Err(invalid());
+                }
+                // IPv6 addresses must be in brackets, e.g. "[::1]:31337"
+                let has_port = address.contains(':') && !address.ends_with(']');
+                let address = match has_port {
+                    true => address.to_string(),
+                    false => format!("{address}:{DEFAULT_PORT}"),
+                };
+                Ok(NetworkAddress::Tcp(address))
+            }
+            "vsock" => {
+                let (cid, port) = match address.split_once(':') {
+                    Some((cid, port)) => (cid, port.parse().map_err(|_| invalid())?),
+                    None => (address, DEFAULT_PORT),
+                };
+                let cid = match cid {
+                    "any" => VSOCK_CID_ANY,
+                    cid => cid.parse().map_err(|_| invalid())?,
+                };
+                Ok(NetworkAddress::Vsock { cid, port })
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for NetworkAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetworkAddress::Tcp(address) => write!(f, "tcp://{address}"),
+            NetworkAddress::Vsock { cid, port } if *cid == VSOCK_CID_ANY => {
+                write!(f, "vsock://any:{port}")
+            }
+            NetworkAddress::Vsock { cid, port } => write!(f, "vsock://{cid}:{port}"),
+        }
+    }
+}
+
+/// Listen for remote devices on the given address. Each remote device that
+/// registers is announced to the manager as a new source device.
+pub async fn listen(
+    address: NetworkAddress,
+    tx: mpsc::Sender<ManagerCommand>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    match address {
+        NetworkAddress::Tcp(ref addr) => {
+            let listener = TcpListener::bind(addr.as_str()).await?;
+            log::info!("Listening for network input on {address}");
+            loop {
+                let (stream, peer) = match listener.accept().await {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        log::error!("Failed to accept network input connection: {e:?}");
+                        continue;
+                    }
+                };
+                log::info!("Accepted network input connection from {peer}");
+                if let Err(e) = stream.set_nodelay(true) {
+                    log::debug!("Failed to disable Nagle's algorithm for {peer}: {e:?}");
+                }
+                spawn_connection(stream, tx.clone());
+            }
+        }
+        NetworkAddress::Vsock { cid, port } => {
+            let listener = VsockListener::bind(VsockAddr::new(cid, port))?;
+            log::info!("Listening for network input on {address}");
+            loop {
+                let (stream, peer) = match listener.accept().await {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        log::error!("Failed to accept network input connection: {e:?}");
+                        continue;
+                    }
+                };
+                log::info!("Accepted network input connection from {peer:?}");
+                spawn_connection(stream, tx.clone());
+            }
+        }
+    }
+}
+
+/// Handle the given connection from a remote device in a new task
+fn spawn_connection<S>(stream: S, tx: mpsc::Sender<ManagerCommand>)
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Send + 'static,
+{
+    tokio::task::spawn(async move {
+        if let Err(e) = server::handle_connection(stream, tx).await {
+            log::warn!("Network input connection closed with error: {e}");
+        }
+    });
+}
@@ -0,0 +1,65 @@
+use super::network::{NetworkAddress, DEFAULT_PORT, VSOCK_CID_ANY};
+
+#[test]
+fn test_parse_tcp_address() {
+    let address: NetworkAddress = "tcp://192.168.1.10:8000".parse().unwrap();
+    assert_eq!(
+        address,
+        NetworkAddress::Tcp("192.168.1.10:8000".to_string())
+    );
+
+    let address: NetworkAddress = "tcp://localhost".parse().unwrap();
+    assert_eq!(
+        address,
+        NetworkAddress::Tcp(format!("localhost:{DEFAULT_PORT}"))
+    );
+
+    let address: NetworkAddress = "tcp://[::1]".parse().unwrap();
+    assert_eq!(
+        address,
+        NetworkAddress::Tcp(format!("[::1]:{DEFAULT_PORT}"))
+    );
+
+    let address: NetworkAddress = "tcp://[::1]:8000".parse().unwrap();
+    assert_eq!(address, NetworkAddress::Tcp("[::1]:8000".to_string()));
+}
+
+#[test]
+fn test_parse_vsock_address() {
+    let address: NetworkAddress = "vsock://2:8000".parse().unwrap();
+    assert_eq!(address, NetworkAddress::Vsock { cid: 2, port: 8000 });
+
+    let address: NetworkAddress = "vsock://any".parse().unwrap();
+    assert_eq!(
+        address,
+        NetworkAddress::Vsock {
+            cid: VSOCK_CID_ANY,
+            port: DEFAULT_PORT
+        }
+    );
+}
+
+#[test]
+fn test_parse_invalid_address() {
+    for address in [
+        "",
+        "192.168.1.10:8000",
+        "tcp://",
+        "udp://localhost:8000",
+        "vsock://host:8000",
+        "vsock://2:port",
+    ] {
+        assert!(
+            address.parse::<NetworkAddress>().is_err(),
+            "{address} should be invalid"
+        );
+    }
+}
+
+#[test]
+fn test_display_address() {
+    for address in ["tcp://localhost:8000", "vsock://3:8000", "vsock://any:8000"] {
+        let parsed: NetworkAddress = address.parse().unwrap();
+        assert_eq!(parsed.to_string(), address);
+    }
+}
@@ -0,0 +1,756 @@
+//! Quirks describe device-specific behaviors that need to be corrected for
+//! source devices. Quirks can either be defined in the built-in quirks table
+//! for a given vendor/product id or be specified in the source device config.
+//! Quirks from the config override built-in quirks of the same kind.
+
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    config::{PalmRejection, SourceDevice, SourceQuirks},
+    input::{
+        capability::{Capability, Gamepad, GamepadButton, Touch, Touchpad},
+        event::{native::NativeEvent, value::InputValue},
+    },
+    udev::device::UdevDevice,
+};
+
+/// A [Quirk] is a behavior that should be applied to input events from a
+/// source device.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Quirk {
+    /// Ignore all input events for the given duration after the device is
+    /// opened. Some devices emit spurious events when they are first opened.
+    IgnoreInitialEvents(Duration),
+    /// Invert the x and/or y values of the given axis capability
+    InvertAxis {
+        capability: Capability,
+        x: bool,
+        y: bool,
+    },
+    /// The device reports the given key as a latched Fn-lock state (pressed
+    /// when locked, released when unlocked) instead of a momentary key press.
+    /// Every state change is converted into a full press and release.
+    FakeFnLock(Capability),
+    /// Swap and/or invert the x and y values of all axis and IMU events.
+    /// Swapping is done before inversion.
+    TransformAxes {
+        invert_x: bool,
+        invert_y: bool,
+        swap_axes: bool,
+    },
+    /// The device does not reliably report the release of the given button
+    /// (e.g. Guide on some firmwares only releases on the next press). The
+    /// button is released automatically after the given delay, and the late
+    /// release from the device is dropped.
+    AutoRelease {
+        capability: Capability,
+        delay: Duration,
+    },
+    /// Reject touchpad contacts that are likely to be a resting palm or
+    /// thumb. A contact is rejected until it is lifted if it starts within
+    /// one of the edge zones, and is ignored until its pressure reaches the
+    /// minimum pressure.
+    PalmRejection { min_pressure: f64, edges: EdgeZones },
+    /// The given button bounces because of a worn switch, reporting a quick
+    /// release and press while held or a double click when pressed once.
+    /// Releases are delayed by the given window, and a press within the
+    /// window is dropped together with the release.
+    Debounce {
+        capability: Capability,
+        window: Duration,
+    },
+    /// The device reports a paddle as the given capability (e.g. an extra
+    /// button, keyboard key or vendor code). Events are translated to the
+    /// given paddle capability, so profiles work the same way on all
+    /// controllers with paddles.
+    Paddle {
+        capability: Capability,
+        paddle: Capability,
+    },
+}
+
+/// Size of the zones along each edge of a touchpad as a fraction of the
+/// touchpad width or height
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EdgeZones {
+    pub left: f64,
+    pub right: f64,
+    pub top: f64,
+    pub bottom: f64,
+}
+
+impl EdgeZones {
+    /// Returns true if the given normalized position is within an edge zone
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x < self.left || x > 1.0 - self.right || y < self.top || y > 1.0 - self.bottom
+    }
+}
+
+impl Quirk {
+    /// Returns true if this quirk replaces the given quirk when both apply to
+    /// the same device, i.e. both are the same kind of quirk for the same
+    /// capability.
+    fn overrides(&self, other: &Quirk) -> bool {
+        match (self, other) {
+            (Quirk::IgnoreInitialEvents(_), Quirk::IgnoreInitialEvents(_)) => true,
+            (Quirk::InvertAxis { capability: a, .. }, Quirk::InvertAxis { capability: b, .. }) => {
+                a == b
+            }
+            (Quirk::FakeFnLock(a), Quirk::FakeFnLock(b)) => a == b,
+            (Quirk::TransformAxes { .. }, Quirk::TransformAxes { .. }) => true,
+            (
+                Quirk::AutoRelease { capability: a, .. },
+                Quirk::AutoRelease { capability: b, .. },
+            ) => a == b,
+            (Quirk::PalmRejection { .. }, Quirk::PalmRejection { .. }) => true,
+            (Quirk::Debounce { capability: a, .. }, Quirk::Debounce { capability: b, .. }) => {
+                a == b
+            }
+            (Quirk::Paddle { capability: a, .. }, Quirk::Paddle { capability: b, .. }) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Entry in the built-in quirks table
+struct QuirkEntry {
+    /// Vendor id of the device
+    vendor_id: u16,
+    /// Product id of the device
+    product_id: u16,
+    /// Optional name of the device for devices that expose multiple interfaces
+    /// with the same vendor and product id.
+    name: Option<&'static str>,
+    /// Quirks to apply to the device
+    quirks: &'static [Quirk],
+}
+
+impl QuirkEntry {
+    /// Returns true if the entry matches the given device
+    fn matches(&self, device: &UdevDevice) -> bool {
+        if self.vendor_id != device.id_vendor() || self.product_id != device.id_product() {
+            return false;
+        }
+        if let Some(name) = self.name {
+            return name == device.name();
+        }
+        true
+    }
+}
+
+/// Built-in table of known device quirks
+const QUIRKS: &[QuirkEntry] = &[
+    // Sony DualShock 3: reports all axes as zero (sticks pushed to the top
+    // left) until the controller is enabled after it is opened.
+    QuirkEntry {
+        vendor_id: 0x054c,
+        product_id: 0x0268,
+        name: Some("Sony PLAYSTATION(R)3 Controller"),
+        quirks: &[Quirk::IgnoreInitialEvents(Duration::from_millis(500))],
+    },
+    // Nintendo Switch Pro Controller: reports uncalibrated stick values while
+    // the driver reads the calibration from the controller.
+    QuirkEntry {
+        vendor_id: 0x057e,
+        product_id: 0x2009,
+        name: Some("Nintendo Switch Pro Controller"),
+        quirks: &[Quirk::IgnoreInitialEvents(Duration::from_millis(1000))],
+    },
+    // Xbox One S Controller over Bluetooth (older firmware): the Guide button
+    // release is only reported with the next press.
+    QuirkEntry {
+        vendor_id: 0x045e,
+        product_id: 0x02e0,
+        name: Some("Xbox Wireless Controller"),
+        quirks: &[Quirk::AutoRelease {
+            capability: Capability::Gamepad(Gamepad::Button(GamepadButton::Guide)),
+            delay: Duration::from_millis(200),
+        }],
+    },
+];
+
+/// Returns the quirks for the given device from the built-in quirks table
+pub fn builtin_quirks(device: &UdevDevice) -> Vec<Quirk> {
+    QUIRKS
+        .iter()
+        .filter(|entry| entry.matches(device))
+        .flat_map(|entry| entry.quirks.iter().cloned())
+        .collect()
+}
+
+/// Returns the given built-in quirks merged with the given quirks from the
+/// source device config. Config quirks replace built-in quirks of the same
+/// kind for the same capability.
+pub fn merge_quirks(builtin: Vec<Quirk>, config: Vec<Quirk>) -> Vec<Quirk> {
+    let mut quirks: Vec<Quirk> = builtin
+        .into_iter()
+        .filter(|quirk| !config.iter().any(|other| other.overrides(quirk)))
+        .collect();
+    quirks.extend(config);
+    quirks
+}
+
+/// Number of bounces suppressed by [Quirk::Debounce] for each capability,
+/// keyed by source device sysname (e.g. "event3").
+static SUPPRESSED_BOUNCES: OnceLock<Mutex<HashMap<String, HashMap<Capability, u64>>>> =
+    OnceLock::new();
+
+/// Returns the number of bounces suppressed for each capability of the source
+/// device with the given sysname
+pub fn suppressed_bounces(sysname: &str) -> HashMap<Capability, u64> {
+    let Ok(bounces) = SUPPRESSED_BOUNCES.get_or_init(Default::default).lock() else {
+        return HashMap::new();
+    };
+    bounces.get(sysname).cloned().unwrap_or_default()
+}
+
+/// Record that a bounce of the given capability was suppressed
+fn record_bounce(sysname: &str, capability: &Capability) {
+    let Ok(mut bounces) = SUPPRESSED_BOUNCES.get_or_init(Default::default).lock() else {
+        return;
+    };
+    let count = bounces
+        .entry(sysname.to_string())
+        .or_default()
+        .entry(capability.clone())
+        .or_default();
+    *count = count.saturating_add(1);
+}
+
+/// Reset the suppressed bounce counts of the source device with the given
+/// sysname
+fn reset_bounces(sysname: &str) {
+    if let Ok(mut bounces) = SUPPRESSED_BOUNCES.get_or_init(Default::default).lock() {
+        bounces.remove(sysname);
+    }
+}
+
+/// Set of [Quirk]s that are applied to input events from a source device
+#[derive(Debug, Clone)]
+pub struct Quirks {
+    quirks: Vec<Quirk>,
+    /// Sysname of the source device, used to count suppressed bounces
+    sysname: String,
+    /// Time at which the device was opened. [Quirk::IgnoreInitialEvents] is
+    /// measured from this point.
+    opened_at: Option<Instant>,
+    /// Time at which each pressed [Quirk::AutoRelease] button is released
+    pending_releases: HashMap<Capability, Instant>,
+    /// [Quirk::AutoRelease] buttons that were released automatically and
+    /// whose release from the device should be dropped
+    auto_released: HashSet<Capability>,
+    /// Whether each touchpad contact was accepted or rejected by
+    /// [Quirk::PalmRejection]
+    contacts: HashMap<(Capability, u8), bool>,
+    /// Time at which each held back [Quirk::Debounce] release is emitted
+    debounced_releases: HashMap<Capability, Instant>,
+}
+
+impl Default for Quirks {
+    fn default() -> Self {
+        Self {
+            quirks: Vec::new(),
+            sysname: String::new(),
+            opened_at: None,
+            pending_releases: HashMap::new(),
+            auto_released: HashSet::new(),
+            contacts: HashMap::new(),
+            debounced_releases: HashMap::new(),
+        }
+    }
+}
+
+impl Quirks {
+    /// Returns the quirks for the given device from the built-in quirks table
+    /// and the given source device config.
+    pub fn new(device: &UdevDevice, config: Option<&SourceDevice>) -> Self {
+        let quirks_config = config.and_then(|config| config.quirks.as_ref());
+        let use_builtin = quirks_config
+            .and_then(|config| config.builtin)
+            .unwrap_or(true);
+        let builtin = if use_builtin {
+            builtin_quirks(device)
+        } else {
+            Vec::new()
+        };
+        let configured = quirks_config.map(Quirks::from_config).unwrap_or_default();
+        let mut quirks = merge_quirks(builtin, configured);
+
+        // Axis transforms are only supported for evdev and iio devices
+        let subsystem = device.subsystem();
+        if let (Some(config), "input" | "iio") = (config, subsystem.as_str()) {
+            let invert_x = config.invert_x.unwrap_or(false);
+            let invert_y = config.invert_y.unwrap_or(false);
+            let swap_axes = config.swap_axes.unwrap_or(false);
+            if invert_x || invert_y || swap_axes {
+                quirks.push(Quirk::TransformAxes {
+                    invert_x,
+                    invert_y,
+                    swap_axes,
+                });
+            }
+        }
+        if !quirks.is_empty() {
+            log::debug!("Using quirks for {}: {quirks:?}", device.name());
+        }
+
+        // Counts from a previous device with the same sysname do not apply
+        let sysname = device.sysname();
+        reset_bounces(sysname.as_str());
+
+        Self {
+            quirks,
+            sysname,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the list of quirks defined in the given config
+    fn from_config(config: &SourceQuirks) -> Vec<Quirk> {
+        let mut quirks = Vec::new();
+        if let Some(ms) = config.ignore_initial_events_ms {
+            quirks.push(Quirk::IgnoreInitialEvents(Duration::from_millis(ms)));
+        }
+        for axis in config.invert_axis.iter().flatten() {
+            let Ok(capability) = Capability::from_str(axis.capability.as_str()) else {
+                log::warn!("Invalid capability for quirk: {}", axis.capability);
+                continue;
+            };
+            quirks.push(Quirk::InvertAxis {
+                capability,
+                x: axis.x.unwrap_or(false),
+                y: axis.y.unwrap_or(false),
+            });
+        }
+        for key in config.fake_fn_lock.iter().flatten() {
+            let Ok(capability) = Capability::from_str(key.as_str()) else {
+                log::warn!("Invalid capability for quirk: {key}");
+                continue;
+            };
+            quirks.push(Quirk::FakeFnLock(capability));
+        }
+        for button in config.auto_release.iter().flatten() {
+            let Ok(capability) = Capability::from_str(button.capability.as_str()) else {
+                log::warn!("Invalid capability for quirk: {}", button.capability);
+                continue;
+            };
+            quirks.push(Quirk::AutoRelease {
+                capability,
+                delay: Duration::from_millis(button.delay_ms),
+            });
+        }
+        if let Some(palm_rejection) = config.palm_rejection.as_ref() {
+            quirks.push(Quirks::palm_rejection_from_config(palm_rejection));
+        }
+        for button in config.debounce.iter().flatten() {
+            let Ok(capability) = Capability::from_str(button.capability.as_str()) else {
+                log::warn!("Invalid capability for quirk: {}", button.capability);
+                continue;
+            };
+            quirks.push(Quirk::Debounce {
+                capability,
+                window: Duration::from_millis(button.window_ms),
+            });
+        }
+        for mapping in config.paddles.iter().flatten() {
+            let Ok(capability) = Capability::from_str(mapping.capability.as_str()) else {
+                log::warn!("Invalid capability for quirk: {}", mapping.capability);
+                continue;
+            };
+            let Some(paddle) = paddle_from_str(mapping.paddle.as_str()) else {
+                log::warn!("Invalid paddle for quirk: {}", mapping.paddle);
+                continue;
+            };
+            quirks.push(Quirk::Paddle { capability, paddle });
+        }
+        quirks
+    }
+
+    /// Returns the palm rejection quirk defined in the given config
+    fn palm_rejection_from_config(config: &PalmRejection) -> Quirk {
+        let edges = config
+            .edge_exclusion
+            .as_ref()
+            .map(|edges| EdgeZones {
+                left: edges.left.unwrap_or_default(),
+                right: edges.right.unwrap_or_default(),
+                top: edges.top.unwrap_or_default(),
+                bottom: edges.bottom.unwrap_or_default(),
+            })
+            .unwrap_or_default();
+        Quirk::PalmRejection {
+            min_pressure: config.min_pressure.unwrap_or_default(),
+            edges,
+        }
+    }
+
+    /// Mark the device as opened. This should be called when the device
+    /// starts being read, so initial events are ignored from that point.
+    pub fn set_opened(&mut self) {
+        self.opened_at = Some(Instant::now());
+    }
+
+    /// Returns true if no quirks are defined
+    pub fn is_empty(&self) -> bool {
+        self.quirks.is_empty()
+    }
+
+    /// Returns the given capabilities of the device with the capabilities
+    /// that are reported as paddles replaced by their paddle capabilities
+    pub fn map_capabilities(&self, capabilities: Vec<Capability>) -> Vec<Capability> {
+        if !self
+            .quirks
+            .iter()
+            .any(|quirk| matches!(quirk, Quirk::Paddle { .. }))
+        {
+            return capabilities;
+        }
+        let mut mapped: Vec<Capability> = Vec::with_capacity(capabilities.len());
+        for capability in capabilities {
+            let capability = self.paddle_for(&capability).unwrap_or(capability);
+            if !mapped.contains(&capability) {
+                mapped.push(capability);
+            }
+        }
+        mapped
+    }
+
+    /// Returns the paddle capability the given capability is reported as, if
+    /// any
+    fn paddle_for(&self, capability: &Capability) -> Option<Capability> {
+        self.quirks.iter().find_map(|quirk| match quirk {
+            Quirk::Paddle {
+                capability: from,
+                paddle,
+            } if from == capability => Some(paddle.clone()),
+            _ => None,
+        })
+    }
+
+    /// Returns the time until the next automatic or held back button
+    /// release, if any
+    pub fn time_until_release(&self) -> Option<Duration> {
+        let now = Instant::now();
+        self.pending_releases
+            .values()
+            .chain(self.debounced_releases.values())
+            .map(|deadline| deadline.saturating_duration_since(now))
+            .min()
+    }
+
+    /// Apply all quirks to the given input events. This should also be
+    /// called without any events to emit automatic button releases.
+    pub fn apply(&mut self, events: Vec<NativeEvent>) -> Vec<NativeEvent> {
+        if self.is_empty() {
+            return events;
+        }
+
+        // Release any buttons whose auto release delay has expired
+        let now = Instant::now();
+        let expired: Vec<Capability> = self
+            .pending_releases
+            .iter()
+            .filter(|(_, deadline)| **deadline <= now)
+            .map(|(capability, _)| capability.clone())
+            .collect();
+        let mut releases = Vec::with_capacity(expired.len());
+        for capability in expired {
+            log::debug!("Automatically releasing {capability}");
+            self.pending_releases.remove(&capability);
+            self.auto_released.insert(capability.clone());
+            releases.push(NativeEvent::new(capability, InputValue::Bool(false)));
+        }
+
+        // Emit held back releases of debounced buttons that did not bounce
+        let expired: Vec<Capability> = self
+            .debounced_releases
+            .iter()
+            .filter(|(_, deadline)| **deadline <= now)
+            .map(|(capability, _)| capability.clone())
+            .collect();
+        for capability in expired {
+            self.debounced_releases.remove(&capability);
+            releases.push(NativeEvent::new(capability, InputValue::Bool(false)));
+        }
+
+        let mut events = events;
+        for quirk in self.quirks.iter() {
+            events = match quirk {
+                Quirk::IgnoreInitialEvents(duration) => {
+                    let opened_at = *self.opened_at.get_or_insert_with(Instant::now);
+                    if opened_at.elapsed() < *duration {
+                        return vec![];
+                    }
+                    events
+                }
+                Quirk::InvertAxis { capability, x, y } => events
+                    .into_iter()
+                    .map(|event| invert_axis(event, capability, *x, *y))
+                    .collect(),
+                Quirk::FakeFnLock(capability) => events
+                    .into_iter()
+                    .flat_map(|event| fake_fn_lock(event, capability))
+                    .collect(),
+                Quirk::TransformAxes {
+                    invert_x,
+                    invert_y,
+                    swap_axes,
+                } => events
+                    .into_iter()
+                    .map(|event| transform_axes(event, *invert_x, *invert_y, *swap_axes))
+                    .collect(),
+                Quirk::AutoRelease { capability, delay } => events
+                    .into_iter()
+                    .filter(|event| {
+                        auto_release(
+                            event,
+                            capability,
+                            *delay,
+                            &mut self.pending_releases,
+                            &mut self.auto_released,
+                        )
+                    })
+                    .collect(),
+                Quirk::PalmRejection {
+                    min_pressure,
+                    edges,
+                } => events
+                    .into_iter()
+                    .filter(|event| palm_rejection(event, *min_pressure, edges, &mut self.contacts))
+                    .collect(),
+                Quirk::Debounce { capability, window } => events
+                    .into_iter()
+                    .filter(|event| {
+                        match debounce(event, capability, *window, &mut self.debounced_releases) {
+                            DebounceAction::Pass => true,
+                            DebounceAction::Hold => false,
+                            DebounceAction::Bounce => {
+                                log::debug!(
+                                    "Suppressed bounce of {capability} on {}",
+                                    self.sysname
+                                );
+                                record_bounce(self.sysname.as_str(), capability);
+                                false
+                            }
+                        }
+                    })
+                    .collect(),
+                Quirk::Paddle { capability, paddle } => events
+                    .into_iter()
+                    .map(|event| remap_paddle(event, capability, paddle))
+                    .collect(),
+            };
+        }
+
+        if releases.is_empty() {
+            return events;
+        }
+        releases.extend(events);
+        releases
+    }
+}
+
+/// Returns true if the given capability is a paddle
+pub fn is_paddle(capability: &Capability) -> bool {
+    matches!(
+        capability,
+        Capability::Gamepad(Gamepad::Button(
+            GamepadButton::LeftPaddle1
+                | GamepadButton::LeftPaddle2
+                | GamepadButton::LeftPaddle3
+                | GamepadButton::RightPaddle1
+                | GamepadButton::RightPaddle2
+                | GamepadButton::RightPaddle3
+        ))
+    )
+}
+
+/// Parse the given paddle name (e.g. "RightPaddle1") or capability (e.g.
+/// "Gamepad:Button:RightPaddle1") into a paddle capability
+fn paddle_from_str(name: &str) -> Option<Capability> {
+    let capability = Capability::from_str(name)
+        .or_else(|_| Capability::from_str(format!("Gamepad:Button:{name}").as_str()))
+        .ok()?;
+    is_paddle(&capability).then_some(capability)
+}
+
+/// Translate the given event to the paddle capability if it matches the
+/// capability the device reports the paddle as
+fn remap_paddle(event: NativeEvent, capability: &Capability, paddle: &Capability) -> NativeEvent {
+    if &event.as_capability() != capability {
+        return event;
+    }
+    NativeEvent::new(paddle.clone(), event.get_value())
+}
+
+/// Tracks presses of the given auto release capability. Returns false if the
+/// given event is a release that should be dropped because the button was
+/// already released automatically.
+fn auto_release(
+    event: &NativeEvent,
+    capability: &Capability,
+    delay: Duration,
+    pending_releases: &mut HashMap<Capability, Instant>,
+    auto_released: &mut HashSet<Capability>,
+) -> bool {
+    if &event.as_capability() != capability {
+        return true;
+    }
+    if event.pressed() {
+        pending_releases.insert(capability.clone(), Instant::now() + delay);
+        auto_released.remove(capability);
+        return true;
+    }
+    if pending_releases.remove(capability).is_some() {
+        return true;
+    }
+    !auto_released.remove(capability)
+}
+
+/// Result of applying [Quirk::Debounce] to an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DebounceAction {
+    /// The event is passed through
+    Pass,
+    /// The release is held back until the debounce window expires
+    Hold,
+    /// The press bounced within the debounce window and is dropped together
+    /// with the held back release
+    Bounce,
+}
+
+/// Holds back releases of the given debounce capability until the window
+/// expires. A press while a release is held back is a bounce.
+fn debounce(
+    event: &NativeEvent,
+    capability: &Capability,
+    window: Duration,
+    held_releases: &mut HashMap<Capability, Instant>,
+) -> DebounceAction {
+    if &event.as_capability() != capability {
+        return DebounceAction::Pass;
+    }
+    if event.pressed() {
+        if held_releases.remove(capability).is_some() {
+            return DebounceAction::Bounce;
+        }
+        return DebounceAction::Pass;
+    }
+    held_releases.insert(capability.clone(), Instant::now() + window);
+    DebounceAction::Hold
+}
+
+/// Tracks touchpad contacts for palm rejection. Returns false if the given
+/// event belongs to a contact that is rejected. Contacts that start within an
+/// edge zone are rejected until they are lifted, while contacts below the
+/// minimum pressure are dropped until their pressure reaches it.
+fn palm_rejection(
+    event: &NativeEvent,
+    min_pressure: f64,
+    edges: &EdgeZones,
+    contacts: &mut HashMap<(Capability, u8), bool>,
+) -> bool {
+    let capability = event.as_capability();
+    let Capability::Touchpad(
+        Touchpad::LeftPad(Touch::Motion)
+        | Touchpad::RightPad(Touch::Motion)
+        | Touchpad::CenterPad(Touch::Motion),
+    ) = capability
+    else {
+        return true;
+    };
+    let InputValue::Touch {
+        index,
+        is_touching,
+        pressure,
+        x,
+        y,
+    } = event.get_value()
+    else {
+        return true;
+    };
+
+    // Only pass the release of contacts that were accepted
+    let key = (capability, index);
+    if !is_touching {
+        return contacts.remove(&key).unwrap_or(false);
+    }
+    if let Some(accepted) = contacts.get(&key) {
+        return *accepted;
+    }
+
+    if let (Some(x), Some(y)) = (x, y) {
+        if edges.contains(x, y) {
+            log::trace!("Rejecting touch {index} starting at the edge: ({x}, {y})");
+            contacts.insert(key, false);
+            return false;
+        }
+    }
+    if pressure.is_some_and(|pressure| pressure < min_pressure) {
+        return false;
+    }
+    contacts.insert(key, true);
+    true
+}
+
+/// Inverts the values of the given event if it matches the given capability
+fn invert_axis(event: NativeEvent, capability: &Capability, x: bool, y: bool) -> NativeEvent {
+    if &event.as_capability() != capability {
+        return event;
+    }
+    let InputValue::Vector2 {
+        x: value_x,
+        y: value_y,
+    } = event.get_value()
+    else {
+        return event;
+    };
+    let value = InputValue::Vector2 {
+        x: value_x.map(|v| if x { -v } else { v }),
+        y: value_y.map(|v| if y { -v } else { v }),
+    };
+    NativeEvent::new(event.as_capability(), value)
+}
+
+/// Swaps and/or inverts the x and y values of the given axis or IMU event
+fn transform_axes(
+    event: NativeEvent,
+    invert_x: bool,
+    invert_y: bool,
+    swap_axes: bool,
+) -> NativeEvent {
+    let transform = |x: Option<f64>, y: Option<f64>| {
+        let (x, y) = if swap_axes { (y, x) } else { (x, y) };
+        let x = x.map(|v| if invert_x { -v } else { v });
+        let y = y.map(|v| if invert_y { -v } else { v });
+        (x, y)
+    };
+    let value = match event.get_value() {
+        InputValue::Vector2 { x, y } => {
+            let (x, y) = transform(x, y);
+            InputValue::Vector2 { x, y }
+        }
+        InputValue::Vector3 { x, y, z } => {
+            let (x, y) = transform(x, y);
+            InputValue::Vector3 { x, y, z }
+        }
+        _ => return event,
+    };
+    NativeEvent::new(event.as_capability(), value)
+}
+
+/// Converts a latched Fn-lock state change into a full press and release
+fn fake_fn_lock(event: NativeEvent, capability: &Capability) -> Vec<NativeEvent> {
+    if &event.as_capability() != capability {
+        return vec![event];
+    }
+    vec![
+        NativeEvent::new(capability.clone(), InputValue::Bool(true)),
+        NativeEvent::new(capability.clone(), InputValue::Bool(false)),
+    ]
+}
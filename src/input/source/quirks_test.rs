@@ -0,0 +1,437 @@
+use std::{thread, time::Duration};
+
+use crate::{
+    config::SourceDevice,
+    input::{
+        capability::{Capability, Gamepad, GamepadAxis, GamepadButton, Keyboard, Touch, Touchpad},
+        event::{native::NativeEvent, value::InputValue},
+    },
+    udev::device::UdevDevice,
+};
+
+use super::quirks::{builtin_quirks, merge_quirks, suppressed_bounces, Quirk, Quirks};
+
+fn button(button: GamepadButton, pressed: bool) -> NativeEvent {
+    NativeEvent::new(
+        Capability::Gamepad(Gamepad::Button(button)),
+        InputValue::Bool(pressed),
+    )
+}
+
+fn quirks_from_config(sysname: &str, config: &str) -> Quirks {
+    let config: SourceDevice = serde_yaml::from_str(config).unwrap();
+    let device = UdevDevice::from_external(sysname, "Test Pad", None, None);
+    Quirks::new(&device, Some(&config))
+}
+
+fn touch(index: u8, is_touching: bool, pressure: f64, x: f64, y: f64) -> NativeEvent {
+    NativeEvent::new(
+        Capability::Touchpad(Touchpad::CenterPad(Touch::Motion)),
+        InputValue::Touch {
+            index,
+            is_touching,
+            pressure: Some(pressure),
+            x: Some(x),
+            y: Some(y),
+        },
+    )
+}
+
+#[test]
+fn test_ignore_initial_events() {
+    let mut quirks = quirks_from_config(
+        "initial0",
+        r#"
+group: gamepad
+quirks:
+  ignore_initial_events_ms: 20
+"#,
+    );
+
+    // Events are ignored from the time the device is opened, not from when
+    // the quirks were created.
+    thread::sleep(Duration::from_millis(30));
+    quirks.set_opened();
+    assert!(quirks
+        .apply(vec![button(GamepadButton::South, true)])
+        .is_empty());
+    thread::sleep(Duration::from_millis(30));
+    assert_eq!(
+        quirks.apply(vec![button(GamepadButton::South, true)]).len(),
+        1
+    );
+}
+
+#[test]
+fn test_invert_axis() {
+    let mut quirks = quirks_from_config(
+        "invert0",
+        r#"
+group: gamepad
+quirks:
+  invert_axis:
+    - capability: Gamepad:Axis:LeftStick
+      y: true
+"#,
+    );
+    let stick = |axis: GamepadAxis| {
+        NativeEvent::new(
+            Capability::Gamepad(Gamepad::Axis(axis)),
+            InputValue::Vector2 {
+                x: Some(0.5),
+                y: Some(0.25),
+            },
+        )
+    };
+    let events = quirks.apply(vec![
+        stick(GamepadAxis::LeftStick),
+        stick(GamepadAxis::RightStick),
+    ]);
+    assert!(matches!(
+        events[0].get_value(),
+        InputValue::Vector2 { x: Some(x), y: Some(y) } if x == 0.5 && y == -0.25
+    ));
+    assert!(matches!(
+        events[1].get_value(),
+        InputValue::Vector2 { x: Some(x), y: Some(y) } if x == 0.5 && y == 0.25
+    ));
+}
+
+#[test]
+fn test_fake_fn_lock() {
+    let mut quirks = quirks_from_config(
+        "fnlock0",
+        r#"
+group: keyboard
+quirks:
+  fake_fn_lock:
+    - Keyboard:KeyF13
+"#,
+    );
+    let f13 = Capability::Keyboard(Keyboard::KeyF13);
+
+    // Unlocking is reported as a full press and release too
+    for locked in [true, false] {
+        let events = quirks.apply(vec![NativeEvent::new(
+            f13.clone(),
+            InputValue::Bool(locked),
+        )]);
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|e| e.as_capability() == f13));
+        assert!(events[0].pressed());
+        assert!(!events[1].pressed());
+    }
+}
+
+#[test]
+fn test_auto_release() {
+    let mut quirks = quirks_from_config(
+        "release0",
+        r#"
+group: gamepad
+quirks:
+  auto_release:
+    - capability: Gamepad:Button:Guide
+      delay_ms: 20
+"#,
+    );
+    let guide = Capability::Gamepad(Gamepad::Button(GamepadButton::Guide));
+
+    let events = quirks.apply(vec![button(GamepadButton::Guide, true)]);
+    assert_eq!(events.len(), 1);
+    assert!(quirks.time_until_release().is_some());
+
+    // The button is released after the delay, and the late release from the
+    // device is dropped.
+    thread::sleep(Duration::from_millis(30));
+    let events = quirks.apply(vec![]);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].as_capability(), guide);
+    assert!(!events[0].pressed());
+    assert!(quirks
+        .apply(vec![button(GamepadButton::Guide, false)])
+        .is_empty());
+
+    // Releases before the delay are passed through
+    quirks.apply(vec![button(GamepadButton::Guide, true)]);
+    let events = quirks.apply(vec![button(GamepadButton::Guide, false)]);
+    assert_eq!(events.len(), 1);
+    assert!(quirks.time_until_release().is_none());
+}
+
+#[test]
+fn test_palm_rejection() {
+    let mut quirks = quirks_from_config(
+        "palm0",
+        r#"
+group: gamepad
+quirks:
+  palm_rejection:
+    min_pressure: 0.3
+    edge_exclusion:
+      left: 0.1
+      bottom: 0.2
+"#,
+    );
+
+    // Contacts starting at the edge are rejected until they are lifted, even
+    // if they move out of the edge zone.
+    assert!(quirks
+        .apply(vec![touch(0, true, 1.0, 0.05, 0.5)])
+        .is_empty());
+    assert!(quirks.apply(vec![touch(0, true, 1.0, 0.5, 0.5)]).is_empty());
+    assert!(quirks
+        .apply(vec![touch(0, false, 0.0, 0.5, 0.5)])
+        .is_empty());
+
+    // Light contacts are ignored until they reach the minimum pressure
+    assert!(quirks.apply(vec![touch(1, true, 0.1, 0.5, 0.5)]).is_empty());
+    assert_eq!(quirks.apply(vec![touch(1, true, 0.5, 0.5, 0.5)]).len(), 1);
+    assert_eq!(quirks.apply(vec![touch(1, true, 0.1, 0.5, 0.5)]).len(), 1);
+    assert_eq!(quirks.apply(vec![touch(1, false, 0.0, 0.5, 0.5)]).len(), 1);
+}
+
+fn debounce_quirks(sysname: &str) -> Quirks {
+    let config: SourceDevice = serde_yaml::from_str(
+        r#"
+group: gamepad
+quirks:
+  debounce:
+    - capability: Gamepad:Button:South
+      window_ms: 20
+"#,
+    )
+    .unwrap();
+    let device = UdevDevice::from_external(sysname, "Test Pad", None, None);
+    Quirks::new(&device, Some(&config))
+}
+
+#[test]
+fn test_debounce_suppresses_bounce() {
+    let mut quirks = debounce_quirks("debounce0");
+    let south = Capability::Gamepad(Gamepad::Button(GamepadButton::South));
+
+    // A double click from a single press is reported as one click
+    let events = quirks.apply(vec![button(GamepadButton::South, true)]);
+    assert_eq!(events.len(), 1);
+    assert!(events[0].pressed());
+    let events = quirks.apply(vec![
+        button(GamepadButton::South, false),
+        button(GamepadButton::South, true),
+        button(GamepadButton::South, false),
+    ]);
+    assert!(events.is_empty());
+    assert_eq!(suppressed_bounces("debounce0").get(&south), Some(&1));
+
+    // The final release is emitted once the window expires
+    assert!(quirks.time_until_release().is_some());
+    thread::sleep(Duration::from_millis(30));
+    let events = quirks.apply(vec![]);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].as_capability(), south);
+    assert!(!events[0].pressed());
+    assert!(quirks.time_until_release().is_none());
+}
+
+#[test]
+fn test_debounce_passes_other_buttons() {
+    let mut quirks = debounce_quirks("debounce1");
+    let events = quirks.apply(vec![
+        button(GamepadButton::East, true),
+        button(GamepadButton::East, false),
+        button(GamepadButton::East, true),
+    ]);
+    assert_eq!(events.len(), 3);
+    assert!(suppressed_bounces("debounce1").is_empty());
+}
+
+fn paddle_quirks() -> Quirks {
+    let config: SourceDevice = serde_yaml::from_str(
+        r#"
+group: gamepad
+quirks:
+  paddles:
+    - capability: Keyboard:KeyF13
+      paddle: LeftPaddle1
+    - capability: Gamepad:Button:Keyboard
+      paddle: Gamepad:Button:RightPaddle1
+    - capability: Keyboard:KeyF14
+      paddle: South
+"#,
+    )
+    .unwrap();
+    let device = UdevDevice::from_external("paddles0", "Test Pad", None, None);
+    Quirks::new(&device, Some(&config))
+}
+
+#[test]
+fn test_paddles_are_normalized() {
+    let mut quirks = paddle_quirks();
+    let f13 = Capability::Keyboard(Keyboard::KeyF13);
+    let events = quirks.apply(vec![
+        NativeEvent::new(f13.clone(), InputValue::Bool(true)),
+        button(GamepadButton::Keyboard, true),
+        button(GamepadButton::South, true),
+    ]);
+    let capabilities: Vec<Capability> = events.iter().map(|e| e.as_capability()).collect();
+    assert_eq!(
+        capabilities,
+        vec![
+            Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle1)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::RightPaddle1)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+        ]
+    );
+    assert!(events.iter().all(|e| e.pressed()));
+
+    // Reported capabilities are normalized too. Mappings to buttons that are
+    // not paddles are ignored.
+    let f14 = Capability::Keyboard(Keyboard::KeyF14);
+    let capabilities = quirks.map_capabilities(vec![
+        f13,
+        f14.clone(),
+        Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle1)),
+    ]);
+    assert_eq!(
+        capabilities,
+        vec![
+            Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle1)),
+            f14,
+        ]
+    );
+}
+
+#[test]
+fn test_builtin_quirks_lookup() {
+    // Matched by vendor id, product id and name
+    let ds3 = UdevDevice::from_external(
+        "event20",
+        "Sony PLAYSTATION(R)3 Controller",
+        Some(0x054c),
+        Some(0x0268),
+    );
+    assert_eq!(
+        builtin_quirks(&ds3),
+        vec![Quirk::IgnoreInitialEvents(Duration::from_millis(500))]
+    );
+
+    // Other interfaces of the same device do not match the name
+    let ds3_motion = UdevDevice::from_external(
+        "event21",
+        "Sony PLAYSTATION(R)3 Controller Motion Sensors",
+        Some(0x054c),
+        Some(0x0268),
+    );
+    assert!(builtin_quirks(&ds3_motion).is_empty());
+
+    // Unknown devices have no built-in quirks
+    let unknown = UdevDevice::from_external(
+        "event22",
+        "Sony PLAYSTATION(R)3 Controller",
+        Some(0x054c),
+        Some(0x05c4),
+    );
+    assert!(builtin_quirks(&unknown).is_empty());
+}
+
+#[test]
+fn test_merge_quirks() {
+    let guide = Capability::Gamepad(Gamepad::Button(GamepadButton::Guide));
+    let south = Capability::Gamepad(Gamepad::Button(GamepadButton::South));
+    let builtin = vec![
+        Quirk::IgnoreInitialEvents(Duration::from_millis(500)),
+        Quirk::AutoRelease {
+            capability: guide.clone(),
+            delay: Duration::from_millis(200),
+        },
+    ];
+
+    // Config quirks replace built-in quirks of the same kind and capability
+    let config = vec![
+        Quirk::IgnoreInitialEvents(Duration::from_millis(100)),
+        Quirk::AutoRelease {
+            capability: south.clone(),
+            delay: Duration::from_millis(50),
+        },
+    ];
+    assert_eq!(
+        merge_quirks(builtin.clone(), config),
+        vec![
+            Quirk::AutoRelease {
+                capability: guide.clone(),
+                delay: Duration::from_millis(200),
+            },
+            Quirk::IgnoreInitialEvents(Duration::from_millis(100)),
+            Quirk::AutoRelease {
+                capability: south,
+                delay: Duration::from_millis(50),
+            },
+        ]
+    );
+    assert_eq!(
+        merge_quirks(
+            builtin,
+            vec![Quirk::AutoRelease {
+                capability: guide.clone(),
+                delay: Duration::from_millis(300),
+            }]
+        ),
+        vec![
+            Quirk::IgnoreInitialEvents(Duration::from_millis(500)),
+            Quirk::AutoRelease {
+                capability: guide,
+                delay: Duration::from_millis(300),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_builtin_quirks_with_config() {
+    let device = UdevDevice::from_external(
+        "event23",
+        "Nintendo Switch Pro Controller",
+        Some(0x057e),
+        Some(0x2009),
+    );
+
+    // Built-in quirks apply without any config
+    let mut quirks = Quirks::new(&device, None);
+    quirks.set_opened();
+    assert!(quirks
+        .apply(vec![button(GamepadButton::South, true)])
+        .is_empty());
+
+    // Config quirks override built-in quirks
+    let config: SourceDevice = serde_yaml::from_str(
+        r#"
+group: gamepad
+quirks:
+  ignore_initial_events_ms: 0
+"#,
+    )
+    .unwrap();
+    let mut quirks = Quirks::new(&device, Some(&config));
+    quirks.set_opened();
+    assert_eq!(
+        quirks.apply(vec![button(GamepadButton::South, true)]).len(),
+        1
+    );
+
+    // Built-in quirks can be disabled
+    let config: SourceDevice = serde_yaml::from_str(
+        r#"
+group: gamepad
+quirks:
+  builtin: false
+"#,
+    )
+    .unwrap();
+    let mut quirks = Quirks::new(&device, Some(&config));
+    quirks.set_opened();
+    assert!(quirks.is_empty());
+    assert_eq!(
+        quirks.apply(vec![button(GamepadButton::South, true)]).len(),
+        1
+    );
+}
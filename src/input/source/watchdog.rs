@@ -0,0 +1,173 @@
+//! Watchdog that detects source devices whose thread is stuck polling the
+//! device, e.g. because a read from a hung hidraw device never returns. Each
+//! source device thread registers a [Heartbeat] and marks when it starts and
+//! finishes polling the device. The composite device periodically checks for
+//! stalled source devices and restarts them. A stuck thread cannot be
+//! stopped, so it is abandoned and exits as soon as its poll returns.
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+use thiserror::Error;
+
+/// Default time in milliseconds a source device may be stuck polling before
+/// it is restarted
+pub const DEFAULT_TIMEOUT_MS: u64 = 5000;
+/// Interval at which composite devices check for stalled source devices
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+/// Delay before retrying to start a source device that could not be started
+/// again after it was abandoned, e.g. because the stuck thread still holds
+/// its evdev grab. The delay doubles with each failed attempt.
+pub const RESTART_RETRY_DELAY: Duration = Duration::from_secs(1);
+/// Maximum delay between attempts to start an abandoned source device again
+pub const MAX_RESTART_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Reference point for [Heartbeat] timestamps
+static EPOCH: OnceLock<Instant> = OnceLock::new();
+/// Heartbeat of the running thread of each source device, by source device id
+static HEARTBEATS: OnceLock<Mutex<HashMap<String, Arc<Heartbeat>>>> = OnceLock::new();
+
+fn epoch() -> Instant {
+    *EPOCH.get_or_init(Instant::now)
+}
+
+fn heartbeats() -> &'static Mutex<HashMap<String, Arc<Heartbeat>>> {
+    HEARTBEATS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Error returned by a source device that was abandoned by the watchdog
+#[derive(Error, Debug)]
+#[error("source device was abandoned after being stuck polling")]
+pub struct Abandoned;
+
+/// Tracks whether the thread of a source device is polling the device
+#[derive(Debug, Default)]
+pub struct Heartbeat {
+    /// Milliseconds since [EPOCH] plus one when the current poll started, or
+    /// zero if the device is not being polled.
+    polling_since_ms: AtomicU64,
+    abandoned: AtomicBool,
+}
+
+impl Heartbeat {
+    /// Mark that the thread started polling the device
+    pub fn begin_poll(&self) {
+        let elapsed = epoch().elapsed().as_millis() as u64;
+        self.polling_since_ms.store(elapsed + 1, Ordering::Relaxed);
+    }
+
+    /// Mark that the thread finished polling the device
+    pub fn end_poll(&self) {
+        self.polling_since_ms.store(0, Ordering::Relaxed);
+    }
+
+    /// Returns how long the thread has been polling the device, or [None] if
+    /// it is not polling
+    pub fn polling_for(&self) -> Option<Duration> {
+        match self.polling_since_ms.load(Ordering::Relaxed) {
+            0 => None,
+            ms => Some((epoch() + Duration::from_millis(ms - 1)).elapsed()),
+        }
+    }
+
+    /// Returns true if the watchdog gave up on the thread. The thread should
+    /// stop as soon as possible without sending any more events.
+    pub fn is_abandoned(&self) -> bool {
+        self.abandoned.load(Ordering::Relaxed)
+    }
+}
+
+/// Register a new heartbeat for the thread of the source device with the
+/// given id, replacing the heartbeat of any previous thread
+pub fn register(device_id: &str) -> Arc<Heartbeat> {
+    let heartbeat = Arc::new(Heartbeat::default());
+    let mut heartbeats = heartbeats().lock().unwrap_or_else(|e| e.into_inner());
+    heartbeats.insert(device_id.to_string(), heartbeat.clone());
+    heartbeat
+}
+
+/// Remove the given heartbeat of the source device with the given id if it
+/// was not replaced by a newer thread
+pub fn unregister(device_id: &str, heartbeat: &Arc<Heartbeat>) {
+    let mut heartbeats = heartbeats().lock().unwrap_or_else(|e| e.into_inner());
+    if heartbeats
+        .get(device_id)
+        .is_some_and(|current| Arc::ptr_eq(current, heartbeat))
+    {
+        heartbeats.remove(device_id);
+    }
+}
+
+/// Returns true if the thread of the source device with the given id has
+/// been polling the device for longer than the given timeout
+pub fn is_stalled(device_id: &str, timeout: Duration) -> bool {
+    let heartbeats = heartbeats().lock().unwrap_or_else(|e| e.into_inner());
+    heartbeats
+        .get(device_id)
+        .and_then(|heartbeat| heartbeat.polling_for())
+        .is_some_and(|polling_for| polling_for > timeout)
+}
+
+/// Abandon the thread of the source device with the given id so a new
+/// thread can be started for the device. Returns false if no thread is
+/// registered for the device.
+pub fn abandon(device_id: &str) -> bool {
+    let mut heartbeats = heartbeats().lock().unwrap_or_else(|e| e.into_inner());
+    let Some(heartbeat) = heartbeats.remove(device_id) else {
+        return false;
+    };
+    heartbeat.abandoned.store(true, Ordering::Relaxed);
+    true
+}
+
+/// Source devices that were abandoned by the watchdog and are waiting for a
+/// new thread to be started, with the number of failed attempts to start it
+#[derive(Debug, Default)]
+pub struct PendingRestarts {
+    attempts: HashMap<String, u32>,
+}
+
+impl PendingRestarts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start restarting the source device with the given id. The stuck thread
+    /// is abandoned on the first attempt. Returns false if the device has no
+    /// running thread and is not already being restarted.
+    pub fn begin(&mut self, device_id: &str) -> bool {
+        if self.attempts.contains_key(device_id) {
+            return true;
+        }
+        if !abandon(device_id) {
+            return false;
+        }
+        self.attempts.insert(device_id.to_string(), 0);
+        true
+    }
+
+    /// Returns true if the source device with the given id is being restarted
+    pub fn is_pending(&self, device_id: &str) -> bool {
+        self.attempts.contains_key(device_id)
+    }
+
+    /// Record a failed attempt to start the source device with the given id
+    /// again. Returns the delay until the next attempt.
+    pub fn failed(&mut self, device_id: &str) -> Duration {
+        let attempts = self.attempts.entry(device_id.to_string()).or_default();
+        let delay = RESTART_RETRY_DELAY.saturating_mul(1 << (*attempts).min(16));
+        *attempts = attempts.saturating_add(1);
+        delay.min(MAX_RESTART_RETRY_DELAY)
+    }
+
+    /// Stop tracking the restart of the source device with the given id,
+    /// either because it was started again or because it was removed
+    pub fn finish(&mut self, device_id: &str) {
+        self.attempts.remove(device_id);
+    }
+}
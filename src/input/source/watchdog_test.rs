@@ -0,0 +1,94 @@
+use std::{thread, time::Duration};
+
+use crate::input::source::watchdog::{
+    abandon, is_stalled, register, unregister, PendingRestarts, MAX_RESTART_RETRY_DELAY,
+    RESTART_RETRY_DELAY,
+};
+
+#[test]
+fn test_heartbeat_polling() {
+    let heartbeat = register("watchdog://polling");
+    assert!(heartbeat.polling_for().is_none());
+
+    heartbeat.begin_poll();
+    thread::sleep(Duration::from_millis(20));
+    assert!(heartbeat.polling_for().unwrap() >= Duration::from_millis(10));
+    assert!(is_stalled("watchdog://polling", Duration::from_millis(5)));
+    assert!(!is_stalled("watchdog://polling", Duration::from_secs(60)));
+
+    heartbeat.end_poll();
+    assert!(heartbeat.polling_for().is_none());
+    assert!(!is_stalled("watchdog://polling", Duration::from_millis(5)));
+
+    unregister("watchdog://polling", &heartbeat);
+    assert!(!is_stalled("watchdog://unknown", Duration::ZERO));
+}
+
+#[test]
+fn test_abandon() {
+    let old = register("watchdog://abandon");
+    old.begin_poll();
+    assert!(abandon("watchdog://abandon"));
+    assert!(old.is_abandoned());
+    assert!(!abandon("watchdog://abandon"));
+
+    // The abandoned thread does not unregister the thread that replaced it
+    let new = register("watchdog://abandon");
+    unregister("watchdog://abandon", &old);
+    new.begin_poll();
+    thread::sleep(Duration::from_millis(10));
+    assert!(is_stalled("watchdog://abandon", Duration::ZERO));
+    assert!(!new.is_abandoned());
+
+    unregister("watchdog://abandon", &new);
+    assert!(!is_stalled("watchdog://abandon", Duration::ZERO));
+}
+
+#[test]
+fn test_pending_restarts() {
+    let mut restarts = PendingRestarts::new();
+
+    // Devices without a running thread cannot be restarted
+    assert!(!restarts.begin("watchdog://restart"));
+    assert!(!restarts.is_pending("watchdog://restart"));
+
+    // The stuck thread is abandoned on the first attempt
+    let old = register("watchdog://restart");
+    old.begin_poll();
+    assert!(restarts.begin("watchdog://restart"));
+    assert!(old.is_abandoned());
+    assert!(restarts.is_pending("watchdog://restart"));
+
+    // While the stuck thread still holds the device, starting it again
+    // fails and is retried with an increasing delay
+    assert_eq!(restarts.failed("watchdog://restart"), RESTART_RETRY_DELAY);
+    assert!(restarts.begin("watchdog://restart"));
+    assert_eq!(
+        restarts.failed("watchdog://restart"),
+        RESTART_RETRY_DELAY * 2
+    );
+    assert_eq!(
+        restarts.failed("watchdog://restart"),
+        RESTART_RETRY_DELAY * 4
+    );
+    for _ in 0..32 {
+        restarts.failed("watchdog://restart");
+    }
+    assert_eq!(
+        restarts.failed("watchdog://restart"),
+        MAX_RESTART_RETRY_DELAY
+    );
+    assert!(restarts.is_pending("watchdog://restart"));
+
+    // Once started again, or removed, the restart is finished
+    restarts.finish("watchdog://restart");
+    assert!(!restarts.is_pending("watchdog://restart"));
+    assert!(!restarts.begin("watchdog://restart"));
+
+    // A new thread can be restarted again, and its attempts start over
+    let new = register("watchdog://restart");
+    assert!(restarts.begin("watchdog://restart"));
+    assert!(new.is_abandoned());
+    assert_eq!(restarts.failed("watchdog://restart"), RESTART_RETRY_DELAY);
+    restarts.finish("watchdog://restart");
+}
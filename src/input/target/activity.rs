@@ -0,0 +1,50 @@
+//! User activity tracking for target devices. Physical devices are hidden
+//! from the compositor while InputPlumber manages them, so the session can be
+//! considered idle (e.g. the screen dims or the system suspends) while
+//! someone is playing with a controller. Target devices record when input is
+//! written so the activity can be reported to logind.
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+/// Interval at which user activity is reported to logind
+pub const REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Reference point for [LAST_ACTIVITY_MS]
+static EPOCH: OnceLock<Instant> = OnceLock::new();
+/// Milliseconds since [EPOCH] plus one when input was last written to any
+/// target device, or zero if no input was written yet.
+static LAST_ACTIVITY_MS: AtomicU64 = AtomicU64::new(0);
+
+fn epoch() -> Instant {
+    *EPOCH.get_or_init(Instant::now)
+}
+
+/// Record that input was written to a target device at the given time
+pub fn record_at(time: Instant) {
+    let elapsed = time.saturating_duration_since(epoch()).as_millis() as u64;
+    LAST_ACTIVITY_MS.fetch_max(elapsed + 1, Ordering::Relaxed);
+}
+
+/// Record that input was just written to a target device
+pub fn record() {
+    record_at(Instant::now());
+}
+
+/// Returns the last time input was written to any target device
+pub fn last_activity() -> Option<Instant> {
+    match LAST_ACTIVITY_MS.load(Ordering::Relaxed) {
+        0 => None,
+        ms => Some(epoch() + Duration::from_millis(ms - 1)),
+    }
+}
+
+/// Returns true if input was written to any target device within the given
+/// duration
+pub fn active_within(duration: Duration) -> bool {
+    last_activity().is_some_and(|last| last.elapsed() <= duration)
+}
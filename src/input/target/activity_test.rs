@@ -0,0 +1,15 @@
+use std::time::{Duration, Instant};
+
+use super::activity::{active_within, last_activity, record, record_at, REPORT_INTERVAL};
+
+#[test]
+fn test_record_activity() {
+    record();
+    let last = last_activity().unwrap();
+    assert!(last <= Instant::now());
+    assert!(active_within(REPORT_INTERVAL));
+
+    // Recording an earlier time does not move the last activity back
+    record_at(last - Duration::from_secs(60).min(last.elapsed()));
+    assert!(last_activity().unwrap() >= last);
+}
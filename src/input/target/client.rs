@@ -0,0 +1,91 @@
+use thiserror::Error;
+use tokio::sync::mpsc::{channel, error::SendError, Sender};
+
+use crate::input::{
+    capability::Capability, composite_device::client::CompositeDeviceClient,
+    event::native::NativeEvent,
+};
+
+use super::command::TargetCommand;
+
+/// Possible errors for a target device client
+#[derive(Error, Debug)]
+pub enum ClientError {
+    #[error("failed to send command to device")]
+    SendError(SendError<TargetCommand>),
+    #[error("device no longer exists")]
+    ChannelClosed,
+}
+
+impl From<SendError<TargetCommand>> for ClientError {
+    fn from(err: SendError<TargetCommand>) -> Self {
+        Self::SendError(err)
+    }
+}
+
+/// A client for communicating with a target device
+#[derive(Debug, Clone)]
+pub struct TargetDeviceClient {
+    tx: Sender<TargetCommand>,
+}
+
+impl From<Sender<TargetCommand>> for TargetDeviceClient {
+    fn from(tx: Sender<TargetCommand>) -> Self {
+        TargetDeviceClient::new(tx)
+    }
+}
+
+impl TargetDeviceClient {
+    pub fn new(tx: Sender<TargetCommand>) -> Self {
+        Self { tx }
+    }
+
+    /// Write the given input event to the target device
+    pub async fn write_event(&self, event: NativeEvent) -> Result<(), ClientError> {
+        self.tx.send(TargetCommand::WriteEvent(event)).await?;
+        Ok(())
+    }
+
+    /// Configure the target device with the given composite device
+    pub async fn set_composite_device(
+        &self,
+        composite_device: CompositeDeviceClient,
+    ) -> Result<(), ClientError> {
+        self.tx
+            .send(TargetCommand::SetCompositeDevice(composite_device))
+            .await?;
+        Ok(())
+    }
+
+    /// Get the input capabilities of the target device
+    pub async fn get_capabilities(&self) -> Result<Vec<Capability>, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx.send(TargetCommand::GetCapabilities(tx)).await?;
+        if let Some(capabilities) = rx.recv().await {
+            return Ok(capabilities);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Get the type identifier of the target device (e.g. "xb360")
+    pub async fn get_type(&self) -> Result<String, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx.send(TargetCommand::GetType(tx)).await?;
+        if let Some(kind) = rx.recv().await {
+            return Ok(kind);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Clear any local state on the target device
+    pub async fn clear_state(&self) -> Result<(), ClientError> {
+        self.tx.send(TargetCommand::ClearState).await?;
+        Ok(())
+    }
+
+    /// Stop the target device
+    pub async fn stop(&self) -> Result<(), ClientError> {
+        self.tx.send(TargetCommand::Stop).await?;
+        Ok(())
+    }
+}
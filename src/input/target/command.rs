@@ -0,0 +1,17 @@
+use tokio::sync::mpsc;
+
+use crate::input::{
+    capability::Capability, composite_device::client::CompositeDeviceClient,
+    event::native::NativeEvent,
+};
+
+/// Commands that can be sent to a [TargetDriver] over a channel
+#[derive(Debug, Clone)]
+pub enum TargetCommand {
+    WriteEvent(NativeEvent),
+    SetCompositeDevice(CompositeDeviceClient),
+    GetCapabilities(mpsc::Sender<Vec<Capability>>),
+    GetType(mpsc::Sender<String>),
+    ClearState,
+    Stop,
+}
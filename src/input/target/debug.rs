@@ -0,0 +1,171 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use zbus::Connection;
+
+use crate::{
+    dbus::interface::target::debug::TargetDebugInterface,
+    input::{capability::Capability, event::native::NativeEvent},
+};
+
+use super::{client::TargetDeviceClient, InputError, TargetInputDevice, TargetOutputDevice};
+
+/// Default number of events kept by the [DebugDevice]
+pub const DEFAULT_BUFFER_SIZE: usize = 512;
+
+/// An input event received by the [DebugDevice]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedEvent {
+    /// Time the event was received in milliseconds since the UNIX epoch
+    pub timestamp_ms: u64,
+    pub capability: String,
+    pub value: String,
+}
+
+/// Ring buffer of the most recent events received by a [DebugDevice]
+#[derive(Debug, Clone)]
+pub struct EventLog {
+    events: VecDeque<RecordedEvent>,
+    capacity: usize,
+}
+
+impl EventLog {
+    /// Create a new event log that keeps up to the given number of events
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            events: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Add the given event, dropping the oldest event if the log is full
+    pub fn push(&mut self, event: RecordedEvent) {
+        if self.capacity == 0 {
+            return;
+        }
+        while self.events.len() >= self.capacity {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+
+    /// Returns up to the given number of the most recent events, oldest first
+    pub fn latest(&self, count: usize) -> Vec<RecordedEvent> {
+        let skip = self.events.len().saturating_sub(count);
+        self.events.iter().skip(skip).cloned().collect()
+    }
+
+    /// Returns the number of events in the log
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns true if the log has no events
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Returns the maximum number of events kept in the log
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Change the maximum number of events kept in the log, dropping the
+    /// oldest events if there are too many.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.events.len() > capacity {
+            self.events.pop_front();
+        }
+    }
+
+    /// Remove all events from the log
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+}
+
+/// The [DebugDevice] is a target device that does not create any virtual
+/// device. Instead, it records every event it receives so users can inspect
+/// what a device profile produces over DBus without a game running.
+#[derive(Debug)]
+pub struct DebugDevice {
+    events: Arc<Mutex<EventLog>>,
+}
+
+impl DebugDevice {
+    /// Create a new [DebugDevice] instance.
+    pub fn new() -> Self {
+        Self {
+            events: Arc::new(Mutex::new(EventLog::new(DEFAULT_BUFFER_SIZE))),
+        }
+    }
+}
+
+impl Default for DebugDevice {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TargetInputDevice for DebugDevice {
+    fn start_dbus_interface(
+        &mut self,
+        dbus: Connection,
+        path: String,
+        _client: TargetDeviceClient,
+    ) {
+        log::debug!("Starting dbus interface: {path}");
+        let events = self.events.clone();
+        tokio::task::spawn(async move {
+            let iface = TargetDebugInterface::new(events);
+            if let Err(e) = dbus.object_server().at(path.clone(), iface).await {
+                log::debug!("Failed to start dbus interface {path}: {e:?}");
+            } else {
+                log::debug!("Started dbus interface on {path}");
+            };
+        });
+    }
+
+    fn write_event(&mut self, event: NativeEvent) -> Result<(), InputError> {
+        log::trace!("Recording event: {event:?}");
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_millis() as u64)
+            .unwrap_or_default();
+        let recorded = RecordedEvent {
+            timestamp_ms,
+            capability: event.as_capability().to_string(),
+            value: format!("{:?}", event.get_value()),
+        };
+        self.events.lock().unwrap().push(recorded);
+
+        Ok(())
+    }
+
+    /// The debug device does not handle any capabilities directly. The
+    /// composite device mirrors all emitted events to it instead.
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(vec![])
+    }
+
+    fn stop_dbus_interface(&mut self, dbus: Connection, path: String) {
+        log::debug!("Stopping dbus interface for {path}");
+        tokio::task::spawn(async move {
+            let result = dbus
+                .object_server()
+                .remove::<TargetDebugInterface, String>(path.clone())
+                .await;
+            if let Err(e) = result {
+                log::error!("Failed to stop dbus interface {path}: {e:?}");
+            } else {
+                log::debug!("Stopped dbus interface for {path}");
+            };
+        });
+    }
+}
+
+impl TargetOutputDevice for DebugDevice {}
@@ -0,0 +1,39 @@
+use super::debug::{EventLog, RecordedEvent};
+
+fn event(timestamp_ms: u64) -> RecordedEvent {
+    RecordedEvent {
+        timestamp_ms,
+        capability: "Gamepad:Button:South".to_string(),
+        value: "Bool(true)".to_string(),
+    }
+}
+
+#[test]
+fn test_event_log_ring_buffer() {
+    let mut log = EventLog::new(3);
+    for time in 0..5 {
+        log.push(event(time));
+    }
+
+    assert_eq!(log.len(), 3);
+    let times: Vec<u64> = log.latest(10).iter().map(|e| e.timestamp_ms).collect();
+    assert_eq!(times, vec![2, 3, 4]);
+    let times: Vec<u64> = log.latest(2).iter().map(|e| e.timestamp_ms).collect();
+    assert_eq!(times, vec![3, 4]);
+}
+
+#[test]
+fn test_event_log_set_capacity() {
+    let mut log = EventLog::new(4);
+    for time in 0..4 {
+        log.push(event(time));
+    }
+
+    log.set_capacity(2);
+    let times: Vec<u64> = log.latest(10).iter().map(|e| e.timestamp_ms).collect();
+    assert_eq!(times, vec![2, 3]);
+
+    log.set_capacity(0);
+    log.push(event(5));
+    assert!(log.is_empty());
+}
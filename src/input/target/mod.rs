@@ -0,0 +1,1207 @@
+use std::{
+    error::Error,
+    io,
+    os::fd::RawFd,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+    thread,
+    time::{Duration, Instant},
+};
+
+use horipad_steam::HoripadSteamDevice;
+use thiserror::Error;
+use tokio::sync::mpsc::{self, error::TryRecvError};
+
+use crate::config;
+use crate::dbus::interface::performance::PerformanceInterface;
+use crate::dbus::interface::target::gamepad::TargetGamepadInterface;
+use crate::udev::{permissions, seat};
+
+use super::{
+    capability::Capability,
+    composite_device::client::{ClientError, CompositeDeviceClient},
+    event::native::{NativeEvent, ScheduledNativeEvent},
+    latency,
+    output_capability::OutputCapability,
+    output_event::OutputEvent,
+    readiness::{self, Wake},
+};
+
+use std::convert::TryFrom;
+use std::fmt::Display;
+
+use zbus::Connection;
+
+use self::client::TargetDeviceClient;
+use self::command::TargetCommand;
+use self::dbus::DBusDevice;
+use self::debug::DebugDevice;
+use self::dualsense::{DualSenseDevice, DualSenseHardware};
+use self::keyboard::KeyboardDevice;
+use self::mouse::MouseDevice;
+use self::network::NetworkDevice;
+use self::performance::TargetPerformance;
+use self::steam_controller::SteamControllerDevice;
+use self::steam_deck::SteamDeckDevice;
+use self::touchpad::TouchpadDevice;
+use self::touchscreen::TouchscreenDevice;
+use self::watchdog::{LatencyWatchdog, RecentEvent, StateSnapshot};
+use self::xb360::XBox360Controller;
+use self::xbox_elite::XboxEliteController;
+use self::xbox_series::XboxSeriesController;
+
+pub mod activity;
+#[cfg(test)]
+pub mod activity_test;
+pub mod client;
+pub mod command;
+pub mod dbus;
+pub mod debug;
+#[cfg(test)]
+pub mod debug_test;
+pub mod dualsense;
+pub mod horipad_steam;
+pub mod keyboard;
+pub mod mouse;
+#[cfg(test)]
+pub mod mouse_test;
+pub mod network;
+pub mod performance;
+#[cfg(test)]
+pub mod performance_test;
+pub mod preflight;
+#[cfg(test)]
+pub mod preflight_test;
+pub mod steam_controller;
+pub mod steam_deck;
+pub mod touchpad;
+pub mod touchscreen;
+pub mod watchdog;
+#[cfg(test)]
+pub mod watchdog_test;
+pub mod wayland;
+#[cfg(test)]
+pub mod wayland_test;
+pub mod xb360;
+pub mod xbox_elite;
+pub mod xbox_series;
+
+/// Possible errors for a target device client
+#[derive(Error, Debug)]
+pub enum InputError {
+    #[error("error occurred running device")]
+    DeviceError(String),
+}
+
+impl From<&str> for InputError {
+    fn from(value: &str) -> Self {
+        InputError::DeviceError(value.to_string())
+    }
+}
+
+impl From<String> for InputError {
+    fn from(value: String) -> Self {
+        InputError::DeviceError(value)
+    }
+}
+
+impl From<Box<dyn Error>> for InputError {
+    fn from(value: Box<dyn Error>) -> Self {
+        InputError::DeviceError(value.to_string())
+    }
+}
+
+impl From<Box<dyn Error + Send + Sync>> for InputError {
+    fn from(value: Box<dyn Error + Send + Sync>) -> Self {
+        InputError::DeviceError(value.to_string())
+    }
+}
+
+impl From<io::Error> for InputError {
+    fn from(value: io::Error) -> Self {
+        InputError::DeviceError(value.to_string())
+    }
+}
+
+impl From<ClientError> for InputError {
+    fn from(value: ClientError) -> Self {
+        InputError::DeviceError(value.to_string())
+    }
+}
+
+/// Possible errors for a target device client
+#[derive(Error, Debug)]
+pub enum OutputError {
+    #[allow(dead_code)]
+    #[error("behavior is not implemented")]
+    NotImplemented,
+    #[error("error occurred running device")]
+    DeviceError(String),
+}
+
+impl From<&str> for OutputError {
+    fn from(value: &str) -> Self {
+        OutputError::DeviceError(value.to_string())
+    }
+}
+
+impl From<String> for OutputError {
+    fn from(value: String) -> Self {
+        OutputError::DeviceError(value)
+    }
+}
+
+impl From<Box<dyn Error>> for OutputError {
+    fn from(value: Box<dyn Error>) -> Self {
+        OutputError::DeviceError(value.to_string())
+    }
+}
+
+impl From<Box<dyn Error + Send + Sync>> for OutputError {
+    fn from(value: Box<dyn Error + Send + Sync>) -> Self {
+        OutputError::DeviceError(value.to_string())
+    }
+}
+
+impl From<io::Error> for OutputError {
+    fn from(value: io::Error) -> Self {
+        OutputError::DeviceError(value.to_string())
+    }
+}
+
+/// TargetDeviceTypeId is a string representation of a supported TargetDevice.
+/// When a new target device is added, an entry should be added to the list of
+/// supported types.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TargetDeviceTypeId {
+    id: &'static str,
+    name: &'static str,
+}
+
+impl TargetDeviceTypeId {
+    /// Returns a list of all supported target device types
+    pub fn supported_types() -> Vec<TargetDeviceTypeId> {
+        vec![
+            TargetDeviceTypeId {
+                id: "null",
+                name: "Null Device",
+            },
+            TargetDeviceTypeId {
+                id: "dbus",
+                name: "DBus Device",
+            },
+            TargetDeviceTypeId {
+                id: "deck",
+                name: "Valve Steam Deck Controller",
+            },
+            TargetDeviceTypeId {
+                id: "debug",
+                name: "InputPlumber Debug Device",
+            },
+            TargetDeviceTypeId {
+                id: "ds5",
+                name: "Sony Interactive Entertainment DualSense Wireless Controller",
+            },
+            TargetDeviceTypeId {
+                id: "ds5-edge",
+                name: "Sony Interactive Entertainment DualSense Edge Wireless Controller",
+            },
+            TargetDeviceTypeId {
+                id: "hori-steam",
+                name: "HORI CO.,LTD. HORIPAD STEAM",
+            },
+            TargetDeviceTypeId {
+                id: "keyboard",
+                name: "InputPlumber Keyboard",
+            },
+            TargetDeviceTypeId {
+                id: "mouse",
+                name: "InputPlumber Mouse",
+            },
+            TargetDeviceTypeId {
+                id: "network",
+                name: "InputPlumber Network Gamepad",
+            },
+            TargetDeviceTypeId {
+                id: "gamepad",
+                name: "InputPlumber Gamepad",
+            },
+            TargetDeviceTypeId {
+                id: "steam-controller",
+                name: "Valve Steam Controller",
+            },
+            TargetDeviceTypeId {
+                id: "touchpad",
+                name: "InputPlumber Touchpad",
+            },
+            TargetDeviceTypeId {
+                id: "touchscreen",
+                name: "InputPlumber Touchscreen",
+            },
+            TargetDeviceTypeId {
+                id: "xb360",
+                name: "Microsoft X-Box 360 pad",
+            },
+            TargetDeviceTypeId {
+                id: "xbox-elite",
+                name: "Microsoft X-Box One Elite pad",
+            },
+            TargetDeviceTypeId {
+                id: "xbox-series",
+                name: "Microsoft Xbox Series S|X Controller",
+            },
+        ]
+    }
+
+    /// Return the identifier as a string
+    pub fn as_str(&self) -> &str {
+        self.id
+    }
+
+    /// Return the name associated with the identifier
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    /// Returns true if this type of target device is created using uinput
+    pub fn requires_uinput(&self) -> bool {
+        matches!(
+            self.id,
+            "keyboard"
+                | "mouse"
+                | "gamepad"
+                | "touchpad"
+                | "touchscreen"
+                | "xb360"
+                | "xbox-elite"
+                | "xbox-series"
+        )
+    }
+
+    /// Returns true if a target device of this type created with the given
+    /// options uses uinput. Keyboard and mouse target devices do not use
+    /// uinput when the Wayland backend is selected.
+    pub fn uses_uinput(&self, options: Option<&config::TargetDeviceOptions>) -> bool {
+        if !self.requires_uinput() {
+            return false;
+        }
+        match self.id {
+            "keyboard" | "mouse" => {
+                wayland::Backend::from_options(options).unwrap_or_default()
+                    != wayland::Backend::Wayland
+            }
+            _ => true,
+        }
+    }
+}
+
+impl Display for TargetDeviceTypeId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.id)
+    }
+}
+
+impl TryFrom<&str> for TargetDeviceTypeId {
+    type Error = bool;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let supported_types = TargetDeviceTypeId::supported_types();
+        for supported_type in supported_types {
+            if supported_type.id == value {
+                return Ok(supported_type);
+            }
+        }
+
+        Err(false)
+    }
+}
+
+/// A [TargetInputDevice] is a device implementation that is capable of emitting
+/// input events. Input events originate from source devices, are processed by
+/// a composite device, and are sent to a target device to be emitted.
+pub trait TargetInputDevice {
+    /// Start the DBus interface for this target device
+    fn start_dbus_interface(&mut self, dbus: Connection, path: String, client: TargetDeviceClient) {
+        log::debug!("Starting dbus interface: {path}");
+        log::trace!("Using device client: {client:?}");
+        tokio::task::spawn(async move {
+            let name = "Gamepad".to_string();
+            let iface = TargetGamepadInterface::new(name);
+            if let Err(e) = dbus.object_server().at(path.clone(), iface).await {
+                log::debug!("Failed to start dbus interface {path}: {e:?}");
+            } else {
+                log::debug!("Started dbus interface on {path}");
+            };
+        });
+    }
+
+    /// Write the given input event to the virtual device
+    fn write_event(&mut self, event: NativeEvent) -> Result<(), InputError> {
+        log::trace!("Discarding event: {event:?}");
+        Ok(())
+    }
+
+    /// Returns the target device input capabilities that the device can handle
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(vec![])
+    }
+
+    /// Returns scheduled events that should be written later. This function will
+    /// be called every poll iteration by the [TargetDriver] and schedule the
+    /// events to be written at the specified time.
+    fn scheduled_events(&mut self) -> Option<Vec<ScheduledNativeEvent>> {
+        None
+    }
+
+    /// Returns the sysfs path of the input device created for this target
+    /// device, if any. This is used to assign the device to the active seat.
+    fn get_syspath(&mut self) -> Option<PathBuf> {
+        None
+    }
+
+    /// Stop the DBus interface for this target device
+    fn stop_dbus_interface(&mut self, dbus: Connection, path: String) {
+        log::debug!("Stopping dbus interface for {path}");
+        tokio::task::spawn(async move {
+            let result = dbus
+                .object_server()
+                .remove::<TargetGamepadInterface, String>(path.clone())
+                .await;
+            if let Err(e) = result {
+                log::error!("Failed to stop dbus interface {path}: {e:?}");
+            } else {
+                log::debug!("Stopped dbus interface for {path}");
+            };
+        });
+    }
+
+    /// Clear any local state on the target device. This is typically called
+    /// whenever the composite device has entered intercept mode to indicate
+    /// that the target device should stop sending input.
+    fn clear_state(&mut self) {}
+
+    /// Called when the target device has been attached to a composite device.
+    fn on_composite_device_attached(
+        &mut self,
+        _device: CompositeDeviceClient,
+    ) -> Result<(), InputError> {
+        Ok(())
+    }
+
+    /// Stop the target device
+    fn stop(&mut self) -> Result<(), InputError> {
+        Ok(())
+    }
+}
+
+/// A [TargetOutputDevice] is a device implementation that is capable of emitting
+/// output events such as force feedback, etc. These output events will be routed
+/// to physical source devices that can handle them.
+pub trait TargetOutputDevice {
+    /// Poll the given device for output events. This method will be called by
+    /// the target driver every polling iteration. Any output events returned by
+    /// this method will be sent to the composite device to be processed. Optionally,
+    /// output events can be sent directly using the provided composite device.
+    fn poll(
+        &mut self,
+        _composite_device: &Option<CompositeDeviceClient>,
+    ) -> Result<Vec<OutputEvent>, OutputError> {
+        //log::trace!("Polling with composite device: {composite_device:?}");
+        Ok(vec![])
+    }
+
+    /// Returns the possible output events this device is capable of emitting
+    #[allow(dead_code)]
+    fn get_output_capabilities(&self) -> Result<Vec<OutputCapability>, OutputError> {
+        Ok(vec![])
+    }
+
+    /// Returns a non-blocking file descriptor that becomes readable when the
+    /// device has output events. If implemented, the device is only polled
+    /// when the descriptor is readable, a command is received, or a scheduled
+    /// event is ready instead of at a fixed poll rate. Devices that must be
+    /// polled periodically (e.g. to emit timestamps) should not implement
+    /// this.
+    fn poll_fd(&self) -> Option<RawFd> {
+        None
+    }
+}
+
+/// Options for running a target device
+#[derive(Debug)]
+pub struct TargetDriverOptions {
+    pub poll_rate: Duration,
+    pub buffer_size: usize,
+    /// If set, the device stops polling at [TargetDriverOptions::poll_rate]
+    /// once no events have been written for this duration. Polling resumes
+    /// as soon as the next event is received.
+    pub idle_timeout: Option<Duration>,
+    /// If set, a snapshot of the device state is captured when events take
+    /// too long to reach the device.
+    pub latency_watchdog: Option<config::LatencyWatchdog>,
+}
+
+impl Default for TargetDriverOptions {
+    fn default() -> Self {
+        Self {
+            poll_rate: Duration::from_millis(8),
+            buffer_size: 2048,
+            idle_timeout: None,
+            latency_watchdog: None,
+        }
+    }
+}
+
+/// A [TargetDriver] is any virtual input device that can emit input events
+#[derive(Debug)]
+pub struct TargetDriver<T: TargetInputDevice + TargetOutputDevice> {
+    type_id: TargetDeviceTypeId,
+    options: TargetDriverOptions,
+    dbus: Connection,
+    implementation: Arc<Mutex<T>>,
+    composite_device: Option<CompositeDeviceClient>,
+    scheduled_events: Vec<ScheduledNativeEvent>,
+    performance: Arc<TargetPerformance>,
+    tx: mpsc::Sender<TargetCommand>,
+    rx: mpsc::Receiver<TargetCommand>,
+}
+
+impl<T: TargetInputDevice + TargetOutputDevice + Send + 'static> TargetDriver<T> {
+    /// Create a new target device with the given implementation
+    pub fn new(type_id: TargetDeviceTypeId, device: T, dbus: Connection) -> Self {
+        let options = TargetDriverOptions::default();
+        TargetDriver::new_with_options(type_id, device, dbus, options)
+    }
+
+    /// Create a new target device with the given implementation and options
+    pub fn new_with_options(
+        type_id: TargetDeviceTypeId,
+        device: T,
+        dbus: Connection,
+        options: TargetDriverOptions,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel(options.buffer_size);
+        let performance = Arc::new(TargetPerformance::new(options.buffer_size));
+        Self {
+            type_id,
+            options,
+            dbus,
+            implementation: Arc::new(Mutex::new(device)),
+            composite_device: None,
+            scheduled_events: Vec::new(),
+            performance,
+            rx,
+            tx,
+        }
+    }
+
+    /// Returns a transmitter channel that can be used to send events to this device
+    pub fn client(&self) -> TargetDeviceClient {
+        self.tx.clone().into()
+    }
+
+    /// Run the target device, consuming the device.
+    pub async fn run(mut self, dbus_path: String) -> Result<(), Box<dyn Error>> {
+        log::debug!("Started running target device: {dbus_path}");
+
+        // Spawn a blocking task to run the target device. The '?' operator should
+        // be avoided in this task so cleanup tasks can run to remove the DBus
+        // interface and stop the device if an error occurs.
+        let client = self.client();
+        let task =
+            tokio::task::spawn_blocking(move || -> Result<(), Box<dyn Error + Send + Sync>> {
+                let mut latency_mode = latency::ModeWatcher::new();
+                let mut composite_device = self.composite_device;
+                let mut rx = self.rx;
+                let mut implementation = self.implementation.lock().unwrap();
+                let runtime = tokio::runtime::Handle::current();
+                let mut last_write = Instant::now();
+                let mut suspended = false;
+                let mut watchdog = self
+                    .options
+                    .latency_watchdog
+                    .as_ref()
+                    .map(LatencyWatchdog::from_config);
+                let mut snapshot_pending = false;
+
+                // Wait for the device to become readable instead of polling
+                // at a fixed rate if the implementation supports it.
+                let fd = implementation.poll_fd().and_then(|fd| {
+                    readiness::register(fd)
+                        .inspect_err(|e| log::debug!("Failed to register {dbus_path}: {e:?}"))
+                        .ok()
+                });
+
+                // Start the DBus interface for the device
+                implementation.start_dbus_interface(self.dbus.clone(), dbus_path.clone(), client);
+                TargetDriver::<T>::start_performance_interface(
+                    self.dbus.clone(),
+                    dbus_path.clone(),
+                    self.performance.clone(),
+                );
+
+                // Assign the input device to the active seat
+                let sysname = implementation.get_syspath().and_then(|path| {
+                    path.file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                });
+                if let Some(sysname) = sysname.clone() {
+                    tokio::task::spawn(async move {
+                        if let Err(e) = seat::assign_device(sysname.as_str()).await {
+                            log::error!("Failed to assign {sysname} to seat: {e:?}");
+                        }
+                    });
+                }
+
+                // Apply the permissions from the global config to the input device
+                if let Some(sysname) = sysname.clone() {
+                    let kind = self.type_id.as_str().to_string();
+                    tokio::task::spawn(async move {
+                        if let Err(e) = permissions::apply_device(&kind, &sysname).await {
+                            log::error!("Failed to apply permissions to {sysname}: {e}");
+                        }
+                    });
+                }
+
+                log::debug!("Target device running: {dbus_path}");
+                loop {
+                    latency_mode.update();
+
+                    // Find any scheduled events that are ready to be sent
+                    let mut ready_events = vec![];
+                    let mut i = 0;
+                    while i < self.scheduled_events.len() {
+                        if self.scheduled_events[i].is_ready() {
+                            let event = self.scheduled_events.remove(i);
+                            ready_events.push(event);
+                            continue;
+                        }
+                        i += 1;
+                    }
+                    for event in ready_events.drain(..) {
+                        let start = Instant::now();
+                        if let Err(e) = implementation.write_event(event.into()) {
+                            log::error!("Error writing event: {e:?}");
+                            break;
+                        }
+                        self.performance.record_write(start.elapsed());
+                        last_write = Instant::now();
+                        activity::record_at(last_write);
+                    }
+
+                    // Keep track of how many commands are waiting to be processed
+                    let queue_depth = self.tx.max_capacity() - self.tx.capacity();
+                    self.performance.record_queue_depth(queue_depth);
+
+                    // Receive commands/input events
+                    if let Err(e) = TargetDriver::receive_commands(
+                        self.type_id.as_str(),
+                        &mut composite_device,
+                        &mut rx,
+                        &mut implementation,
+                        &self.performance,
+                        &mut last_write,
+                        &mut watchdog,
+                        &mut snapshot_pending,
+                    ) {
+                        log::debug!("Error receiving commands: {e:?}");
+                        break;
+                    }
+
+                    // Capture the state of the device if events have been
+                    // late for too long
+                    if let Some(watchdog) = watchdog.as_ref().filter(|_| snapshot_pending) {
+                        snapshot_pending = false;
+                        let mut snapshot = StateSnapshot::new(
+                            self.type_id.as_str(),
+                            dbus_path.as_str(),
+                            &self.performance,
+                        );
+                        snapshot.scheduled_events = self.scheduled_events.len();
+                        snapshot.suspended = suspended;
+                        snapshot.composite_device_attached = composite_device.is_some();
+                        snapshot.recent_events = watchdog.recent_events();
+                        TargetDriver::<T>::dump_snapshot(
+                            self.dbus.clone(),
+                            snapshot,
+                            watchdog.dump_path(),
+                        );
+                    }
+
+                    // Poll the implementation for scheduled input events
+                    if let Some(mut scheduled_events) = implementation.scheduled_events() {
+                        self.scheduled_events.append(&mut scheduled_events);
+                    }
+
+                    // Poll the implementation for output events
+                    let events = match implementation.poll(&composite_device) {
+                        Ok(events) => events,
+                        Err(e) => {
+                            log::error!("Error polling target device: {e:?}");
+                            break;
+                        }
+                    };
+                    for event in events.into_iter() {
+                        let Some(ref client) = composite_device else {
+                            break;
+                        };
+
+                        // Send the output event to source devices
+                        let result = client.blocking_process_output_event(event);
+                        if let Err(e) = result {
+                            return Err(e.to_string().into());
+                        }
+                    }
+
+                    // Suspend polling if no events have been written for
+                    // longer than the idle timeout, unless in low latency
+                    // mode.
+                    let idle = fd.is_none()
+                        && self.scheduled_events.is_empty()
+                        && !latency::is_enabled()
+                        && self
+                            .options
+                            .idle_timeout
+                            .is_some_and(|timeout| last_write.elapsed() >= timeout);
+                    if idle != suspended {
+                        suspended = idle;
+                        if suspended {
+                            log::debug!("Target device is idle, suspending polling: {dbus_path}");
+                        } else {
+                            log::debug!("Target device resumed polling: {dbus_path}");
+                        }
+                    }
+
+                    // Sleep for the configured duration
+                    if fd.is_none() && !suspended {
+                        thread::sleep(latency::poll_rate(self.options.poll_rate));
+                        continue;
+                    }
+
+                    // Otherwise wait for the device to become readable, the
+                    // next command, or the next scheduled event. Suspended
+                    // devices are only polled for output events at a reduced
+                    // rate.
+                    let timeout = self
+                        .scheduled_events
+                        .iter()
+                        .map(|event| event.time_until_ready())
+                        .min()
+                        .unwrap_or(readiness::MAX_WAIT)
+                        .min(readiness::MAX_WAIT);
+                    let command = match readiness::wait(&runtime, fd.as_ref(), &mut rx, timeout) {
+                        Wake::Command(Some(command)) => command,
+                        Wake::Command(None) => {
+                            log::debug!("Receive channel disconnected");
+                            break;
+                        }
+                        Wake::Readable | Wake::Timeout => continue,
+                    };
+                    if let Err(e) = TargetDriver::handle_command(
+                        self.type_id.as_str(),
+                        command,
+                        &mut composite_device,
+                        &mut implementation,
+                        &self.performance,
+                        &mut last_write,
+                        &mut watchdog,
+                        &mut snapshot_pending,
+                    ) {
+                        log::debug!("Error handling command: {e:?}");
+                        break;
+                    }
+                }
+
+                // Stop the device
+                log::debug!("Target device stopping: {dbus_path}");
+                TargetDriver::<T>::stop_performance_interface(self.dbus.clone(), dbus_path.clone());
+                implementation.stop_dbus_interface(self.dbus, dbus_path.clone());
+                if let Some(sysname) = sysname {
+                    tokio::task::spawn(async move {
+                        if let Err(e) = seat::unassign_device(sysname.as_str()).await {
+                            log::error!("Failed to remove seat rule for {sysname}: {e:?}");
+                        }
+                        if let Err(e) = permissions::remove_device(sysname.as_str()).await {
+                            log::error!("Failed to remove permissions rule for {sysname}: {e:?}");
+                        }
+                    });
+                }
+                implementation.stop()?;
+                log::debug!("Target device stopped: {dbus_path}");
+
+                Ok(())
+            });
+
+        // Wait for the device to finish running.
+        if let Err(e) = task.await? {
+            return Err(e.to_string().into());
+        }
+
+        Ok(())
+    }
+
+    /// Start the DBus interface exposing the performance counters of the
+    /// device
+    fn start_performance_interface(
+        dbus: Connection,
+        path: String,
+        performance: Arc<TargetPerformance>,
+    ) {
+        tokio::task::spawn(async move {
+            let iface = PerformanceInterface::new(performance);
+            if let Err(e) = dbus.object_server().at(path.clone(), iface).await {
+                log::debug!("Failed to start performance dbus interface {path}: {e:?}");
+            }
+        });
+    }
+
+    /// Stop the DBus interface exposing the performance counters of the
+    /// device
+    fn stop_performance_interface(dbus: Connection, path: String) {
+        tokio::task::spawn(async move {
+            let result = dbus
+                .object_server()
+                .remove::<PerformanceInterface, String>(path.clone())
+                .await;
+            if let Err(e) = result {
+                log::debug!("Failed to stop performance dbus interface {path}: {e:?}");
+            }
+        });
+    }
+
+    /// Log the given state snapshot to the journal, write it to the given
+    /// directory if set, and emit it as a DBus signal.
+    fn dump_snapshot(dbus: Connection, snapshot: StateSnapshot, dump_path: Option<&Path>) {
+        let path = snapshot.dbus_path.clone();
+        let latency_us = snapshot.event_latency_us;
+        let json = snapshot.to_json();
+        log::warn!("Event latency of {path} is {latency_us}us, device state: {json}");
+        if let Some(dir) = dump_path {
+            match snapshot.write_to(dir) {
+                Ok(file) => log::warn!("Wrote device state of {path} to {file:?}"),
+                Err(e) => log::error!("Failed to write device state of {path}: {e:?}"),
+            }
+        }
+
+        tokio::task::spawn(async move {
+            let iface_ref = match dbus
+                .object_server()
+                .interface::<_, PerformanceInterface>(path.as_str())
+                .await
+            {
+                Ok(iface_ref) => iface_ref,
+                Err(e) => {
+                    log::debug!("Failed to get performance dbus interface {path}: {e:?}");
+                    return;
+                }
+            };
+            let result =
+                PerformanceInterface::high_latency(iface_ref.signal_context(), latency_us, json)
+                    .await;
+            if let Err(e) = result {
+                log::error!("Failed to send high latency signal for {path}: {e:?}");
+            }
+        });
+    }
+
+    /// Read commands sent to this device from the channel until it is
+    /// empty.
+    fn receive_commands(
+        type_id: &str,
+        composite_device: &mut Option<CompositeDeviceClient>,
+        rx: &mut mpsc::Receiver<TargetCommand>,
+        implementation: &mut MutexGuard<'_, T>,
+        performance: &TargetPerformance,
+        last_write: &mut Instant,
+        watchdog: &mut Option<LatencyWatchdog>,
+        snapshot_pending: &mut bool,
+    ) -> Result<(), Box<dyn Error>> {
+        const MAX_COMMANDS: u8 = 64;
+        let mut commands_processed = 0;
+        loop {
+            match rx.try_recv() {
+                Ok(cmd) => TargetDriver::handle_command(
+                    type_id,
+                    cmd,
+                    composite_device,
+                    implementation,
+                    performance,
+                    last_write,
+                    watchdog,
+                    snapshot_pending,
+                )?,
+                Err(e) => match e {
+                    TryRecvError::Empty => return Ok(()),
+                    TryRecvError::Disconnected => {
+                        log::debug!("Receive channel disconnected");
+                        return Err("Receive channel disconnected".into());
+                    }
+                },
+            };
+
+            // Only process MAX_COMMANDS messages at a time
+            commands_processed += 1;
+            if commands_processed >= MAX_COMMANDS {
+                return Ok(());
+            }
+        }
+    }
+    /// Handle a single command sent to this device
+    fn handle_command(
+        type_id: &str,
+        cmd: TargetCommand,
+        composite_device: &mut Option<CompositeDeviceClient>,
+        implementation: &mut MutexGuard<'_, T>,
+        performance: &TargetPerformance,
+        last_write: &mut Instant,
+        watchdog: &mut Option<LatencyWatchdog>,
+        snapshot_pending: &mut bool,
+    ) -> Result<(), Box<dyn Error>> {
+        match cmd {
+            TargetCommand::WriteEvent(event) => {
+                let start = Instant::now();
+                let latency = start.saturating_duration_since(event.timestamp());
+                performance.record_latency(latency);
+                if let Some(watchdog) = watchdog.as_mut() {
+                    let recent = RecentEvent {
+                        capability: event.as_capability().to_string(),
+                        value: format!("{:?}", event.get_value()),
+                        latency_us: latency.as_micros() as u64,
+                    };
+                    if watchdog.record(recent, latency, start) {
+                        *snapshot_pending = true;
+                    }
+                }
+                implementation.write_event(event)?;
+                performance.record_write(start.elapsed());
+                *last_write = Instant::now();
+                activity::record_at(*last_write);
+            }
+            TargetCommand::SetCompositeDevice(device) => {
+                *composite_device = Some(device.clone());
+                implementation.on_composite_device_attached(device)?;
+            }
+            TargetCommand::GetCapabilities(sender) => {
+                let capabilities = implementation.get_capabilities().unwrap_or_default();
+                sender.blocking_send(capabilities)?;
+            }
+            TargetCommand::GetType(sender) => {
+                sender.blocking_send(type_id.to_string())?;
+            }
+            TargetCommand::ClearState => {
+                implementation.clear_state();
+            }
+            TargetCommand::Stop => {
+                implementation.stop()?;
+                return Err("Target device stopped".into());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A [TargetDevice] is any virtual input device that emits input events
+#[derive(Debug)]
+pub enum TargetDevice {
+    Null,
+    DBus(TargetDriver<DBusDevice>),
+    Debug(TargetDriver<DebugDevice>),
+    DualSense(TargetDriver<DualSenseDevice>),
+    HoripadSteam(TargetDriver<HoripadSteamDevice>),
+    Keyboard(TargetDriver<KeyboardDevice>),
+    Mouse(TargetDriver<MouseDevice>),
+    Network(TargetDriver<NetworkDevice>),
+    SteamController(TargetDriver<SteamControllerDevice>),
+    SteamDeck(TargetDriver<SteamDeckDevice>),
+    Touchpad(TargetDriver<TouchpadDevice>),
+    Touchscreen(TargetDriver<TouchscreenDevice>),
+    XBox360(TargetDriver<XBox360Controller>),
+    XBoxElite(TargetDriver<XboxEliteController>),
+    XBoxSeries(TargetDriver<XboxSeriesController>),
+}
+
+impl TargetDevice {
+    /// Create a new target device from the given target device type id
+    pub fn from_type_id(
+        id: TargetDeviceTypeId,
+        dbus: Connection,
+        options: Option<&config::TargetDeviceOptions>,
+    ) -> Result<Self, Box<dyn Error>> {
+        // Stop polling the target device while it is idle if configured
+        let idle_timeout = options
+            .and_then(|o| o.idle_timeout_minutes)
+            .filter(|minutes| *minutes > 0)
+            .map(|minutes| Duration::from_secs(minutes * 60));
+        let latency_watchdog = options.and_then(|o| o.latency_watchdog.clone());
+
+        // Only UHID and USB/IP target devices can set a unique id
+        if options.is_some_and(|o| o.uniq.is_some()) {
+            match id.as_str() {
+                "deck" | "steam-controller" | "hori-steam" | "ds5" | "ds5-usb" | "ds5-bt"
+                | "ds5-edge" | "ds5-edge-usb" | "ds5-edge-bt" => (),
+                _ => log::warn!("Target device '{id}' does not support setting 'uniq'"),
+            }
+        }
+
+        match id.as_str() {
+            "dbus" => {
+                let device = DBusDevice::new(dbus.clone());
+                let driver = TargetDriver::new(id, device, dbus);
+                Ok(Self::DBus(driver))
+            }
+            "debug" => {
+                let device = DebugDevice::new();
+                let driver = TargetDriver::new(id, device, dbus);
+                Ok(Self::Debug(driver))
+            }
+            "deck" => {
+                let device = SteamDeckDevice::new(options)?;
+                let options = TargetDriverOptions {
+                    poll_rate: Duration::from_millis(4),
+                    buffer_size: 2048,
+                    idle_timeout,
+                    latency_watchdog,
+                };
+                let driver = TargetDriver::new_with_options(id, device, dbus, options);
+                Ok(Self::SteamDeck(driver))
+            }
+            "steam-controller" => {
+                let device = SteamControllerDevice::new(options)?;
+                let options = TargetDriverOptions {
+                    poll_rate: Duration::from_millis(4),
+                    buffer_size: 2048,
+                    idle_timeout,
+                    latency_watchdog,
+                };
+                let driver = TargetDriver::new_with_options(id, device, dbus, options);
+                Ok(Self::SteamController(driver))
+            }
+            "ds5" | "ds5-usb" | "ds5-bt" | "ds5-edge" | "ds5-edge-usb" | "ds5-edge-bt" => {
+                let hw = match id.as_str() {
+                    "ds5" | "ds5-usb" => DualSenseHardware::new(
+                        dualsense::ModelType::Normal,
+                        dualsense::BusType::Usb,
+                    ),
+                    "ds5-bt" => DualSenseHardware::new(
+                        dualsense::ModelType::Normal,
+                        dualsense::BusType::Bluetooth,
+                    ),
+                    "ds5-edge" | "ds5-edge-usb" => {
+                        DualSenseHardware::new(dualsense::ModelType::Edge, dualsense::BusType::Usb)
+                    }
+                    "ds5-edge-bt" => DualSenseHardware::new(
+                        dualsense::ModelType::Edge,
+                        dualsense::BusType::Bluetooth,
+                    ),
+                    _ => DualSenseHardware::default(),
+                };
+                let device = DualSenseDevice::new(hw, options)?;
+                let options = TargetDriverOptions {
+                    poll_rate: Duration::from_millis(1),
+                    buffer_size: 2048,
+                    idle_timeout,
+                    latency_watchdog,
+                };
+                let driver = TargetDriver::new_with_options(id, device, dbus, options);
+                Ok(Self::DualSense(driver))
+            }
+            "hori-steam" => {
+                let device = HoripadSteamDevice::new(options)?;
+                let options = TargetDriverOptions {
+                    poll_rate: Duration::from_millis(1),
+                    buffer_size: 2048,
+                    idle_timeout,
+                    latency_watchdog,
+                };
+                let driver = TargetDriver::new_with_options(id, device, dbus, options);
+                Ok(Self::HoripadSteam(driver))
+            }
+            "keyboard" => {
+                let device = KeyboardDevice::new(options)?;
+                let options = TargetDriverOptions {
+                    idle_timeout,
+                    latency_watchdog,
+                    ..Default::default()
+                };
+                let driver = TargetDriver::new_with_options(id, device, dbus, options);
+                Ok(Self::Keyboard(driver))
+            }
+            "mouse" => {
+                let device = MouseDevice::new(options)?;
+                let options = TargetDriverOptions {
+                    poll_rate: Duration::from_millis(16),
+                    buffer_size: 2048,
+                    idle_timeout,
+                    latency_watchdog,
+                };
+                let driver = TargetDriver::new_with_options(id, device, dbus, options);
+                Ok(Self::Mouse(driver))
+            }
+            "network" => {
+                let device = NetworkDevice::new(options)?;
+                // The device must keep polling to answer keepalive requests
+                // from the remote InputPlumber, so it cannot be idle.
+                let options = TargetDriverOptions {
+                    poll_rate: Duration::from_millis(16),
+                    buffer_size: 2048,
+                    idle_timeout: None,
+                    latency_watchdog,
+                };
+                let driver = TargetDriver::new_with_options(id, device, dbus, options);
+                Ok(Self::Network(driver))
+            }
+            "touchpad" => {
+                let device = TouchpadDevice::new(options)?;
+                let options = TargetDriverOptions {
+                    poll_rate: Duration::from_micros(13605),
+                    buffer_size: 2048,
+                    idle_timeout,
+                    latency_watchdog,
+                };
+                let driver = TargetDriver::new_with_options(id, device, dbus, options);
+                Ok(Self::Touchpad(driver))
+            }
+            "touchscreen" => {
+                let device = TouchscreenDevice::new(options)?;
+                let options = TargetDriverOptions {
+                    poll_rate: Duration::from_micros(13605),
+                    buffer_size: 2048,
+                    idle_timeout,
+                    latency_watchdog,
+                };
+                let driver = TargetDriver::new_with_options(id, device, dbus, options);
+                Ok(Self::Touchscreen(driver))
+            }
+            "xb360" | "gamepad" => {
+                let device = XBox360Controller::new(options)?;
+                let options = TargetDriverOptions {
+                    idle_timeout,
+                    latency_watchdog,
+                    ..Default::default()
+                };
+                let driver = TargetDriver::new_with_options(id, device, dbus, options);
+                Ok(Self::XBox360(driver))
+            }
+            "xbox-elite" => {
+                let device = XboxEliteController::new(options)?;
+                let options = TargetDriverOptions {
+                    idle_timeout,
+                    latency_watchdog,
+                    ..Default::default()
+                };
+                let driver = TargetDriver::new_with_options(id, device, dbus, options);
+                Ok(Self::XBoxElite(driver))
+            }
+            "xbox-series" => {
+                let device = XboxSeriesController::new(options)?;
+                let options = TargetDriverOptions {
+                    idle_timeout,
+                    latency_watchdog,
+                    ..Default::default()
+                };
+                let driver = TargetDriver::new_with_options(id, device, dbus, options);
+                Ok(Self::XBoxSeries(driver))
+            }
+            "null" => Ok(Self::Null),
+            _ => Ok(Self::Null),
+        }
+    }
+
+    /// Returns string identifiers of the target device. This string is used
+    /// in some interfaces that want to specify a type of input device to use
+    /// such as an input profile. E.g. "xb360", "xbox-elite", "ds5-edge"
+    pub fn _type_identifiers(&self) -> Vec<TargetDeviceTypeId> {
+        match self {
+            TargetDevice::Null => vec!["null".try_into().unwrap()],
+            TargetDevice::DBus(_) => vec!["dbus".try_into().unwrap()],
+            TargetDevice::Debug(_) => vec!["debug".try_into().unwrap()],
+            TargetDevice::DualSense(_) => vec![
+                "ds5".try_into().unwrap(),
+                "ds5-usb".try_into().unwrap(),
+                "ds5-bt".try_into().unwrap(),
+                "ds5-edge".try_into().unwrap(),
+                "ds5-edge-usb".try_into().unwrap(),
+                "ds5-edge-bt".try_into().unwrap(),
+            ],
+            TargetDevice::HoripadSteam(_) => vec!["hori-steam".try_into().unwrap()],
+            TargetDevice::Keyboard(_) => vec!["keyboard".try_into().unwrap()],
+            TargetDevice::Mouse(_) => vec!["mouse".try_into().unwrap()],
+            TargetDevice::Network(_) => vec!["network".try_into().unwrap()],
+            TargetDevice::SteamController(_) => vec!["steam-controller".try_into().unwrap()],
+            TargetDevice::SteamDeck(_) => vec!["deck".try_into().unwrap()],
+            TargetDevice::Touchpad(_) => vec!["touchpad".try_into().unwrap()],
+            TargetDevice::Touchscreen(_) => vec!["touchscreen".try_into().unwrap()],
+            TargetDevice::XBox360(_) => {
+                vec!["xb360".try_into().unwrap(), "gamepad".try_into().unwrap()]
+            }
+            TargetDevice::XBoxElite(_) => vec!["xbox-elite".try_into().unwrap()],
+            TargetDevice::XBoxSeries(_) => vec!["xbox-series".try_into().unwrap()],
+        }
+    }
+
+    /// Returns a string of the base name that should be used for this kind
+    /// of device. E.g. a gamepad will return "gamepad" so it can be named
+    /// "gamepad0", "gamepad1", etc. when requesting a DBus path.
+    pub fn dbus_device_class(&self) -> &str {
+        match self {
+            TargetDevice::Null => "null",
+            TargetDevice::DBus(_) => "dbus",
+            TargetDevice::Debug(_) => "debug",
+            TargetDevice::DualSense(_) => "gamepad",
+            TargetDevice::HoripadSteam(_) => "gamepad",
+            TargetDevice::Keyboard(_) => "keyboard",
+            TargetDevice::Mouse(_) => "mouse",
+            TargetDevice::Network(_) => "gamepad",
+            TargetDevice::SteamController(_) => "gamepad",
+            TargetDevice::SteamDeck(_) => "gamepad",
+            TargetDevice::Touchpad(_) => "touchpad",
+            TargetDevice::Touchscreen(_) => "touchscreen",
+            TargetDevice::XBox360(_) => "gamepad",
+            TargetDevice::XBoxElite(_) => "gamepad",
+            TargetDevice::XBoxSeries(_) => "gamepad",
+        }
+    }
+
+    /// Returns a client channel that can be used to send events to this device
+    pub fn client(&self) -> Option<TargetDeviceClient> {
+        match self {
+            TargetDevice::Null => None,
+            TargetDevice::DBus(device) => Some(device.client()),
+            TargetDevice::Debug(device) => Some(device.client()),
+            TargetDevice::DualSense(device) => Some(device.client()),
+            TargetDevice::HoripadSteam(device) => Some(device.client()),
+            TargetDevice::Keyboard(device) => Some(device.client()),
+            TargetDevice::Mouse(device) => Some(device.client()),
+            TargetDevice::Network(device) => Some(device.client()),
+            TargetDevice::SteamController(device) => Some(device.client()),
+            TargetDevice::SteamDeck(device) => Some(device.client()),
+            TargetDevice::Touchpad(device) => Some(device.client()),
+            TargetDevice::Touchscreen(device) => Some(device.client()),
+            TargetDevice::XBox360(device) => Some(device.client()),
+            TargetDevice::XBoxElite(device) => Some(device.client()),
+            TargetDevice::XBoxSeries(device) => Some(device.client()),
+        }
+    }
+
+    /// Run the target device
+    pub async fn run(self, dbus_path: String) -> Result<(), Box<dyn Error>> {
+        match self {
+            TargetDevice::Null => Ok(()),
+            TargetDevice::DBus(device) => device.run(dbus_path).await,
+            TargetDevice::Debug(device) => device.run(dbus_path).await,
+            TargetDevice::DualSense(device) => device.run(dbus_path).await,
+            TargetDevice::HoripadSteam(device) => device.run(dbus_path).await,
+            TargetDevice::Keyboard(device) => device.run(dbus_path).await,
+            TargetDevice::Mouse(device) => device.run(dbus_path).await,
+            TargetDevice::Network(device) => device.run(dbus_path).await,
+            TargetDevice::SteamController(device) => device.run(dbus_path).await,
+            TargetDevice::SteamDeck(device) => device.run(dbus_path).await,
+            TargetDevice::Touchpad(device) => device.run(dbus_path).await,
+            TargetDevice::Touchscreen(device) => device.run(dbus_path).await,
+            TargetDevice::XBox360(device) => device.run(dbus_path).await,
+            TargetDevice::XBoxElite(device) => device.run(dbus_path).await,
+            TargetDevice::XBoxSeries(device) => device.run(dbus_path).await,
+        }
+    }
+}
@@ -0,0 +1,689 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    path::PathBuf,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use evdev::{
+    uinput::{VirtualDevice, VirtualDeviceBuilder},
+    AbsInfo, AbsoluteAxisCode, AttributeSet, BusType, EventType, InputEvent, InputId, KeyCode,
+    RelativeAxisCode,
+};
+use zbus::Connection;
+
+use crate::{
+    config,
+    dbus::interface::target::{mouse::TargetMouseInterface, pointer::TargetPointerInterface},
+    input::{
+        capability::{Capability, Mouse, MouseButton},
+        composite_device::client::CompositeDeviceClient,
+        event::{evdev::EvdevEvent, native::NativeEvent, value::InputValue},
+        output_event::OutputEvent,
+    },
+    udev::display::{self, DEFAULT_SCREEN_SIZE},
+};
+
+use super::{
+    client::TargetDeviceClient,
+    wayland::{Backend, VirtualInputDevice, WaylandDevice},
+    InputError, OutputError, TargetInputDevice, TargetOutputDevice,
+};
+
+/// Mouse speed in pixels per second at which all acceleration profiles move
+/// the mouse at the same speed. This matches the default mouse motion speed of
+/// device profiles.
+const REFERENCE_SPEED_PPS: f64 = 800.0;
+
+/// Time the mouse has to rest at the edge of its bounds before it is moved
+/// back to the center of the bounds when motion starts again. Shorter pauses,
+/// like changing the direction of a joystick, keep the mouse where it is.
+pub const RECENTER_IDLE_TIME: Duration = Duration::from_millis(250);
+
+/// Smoothed mouse speeds in pixels per second below this are treated as no
+/// motion, so smoothing does not keep the mouse drifting forever.
+const MIN_SMOOTHED_SPEED_PPS: f64 = 1.0;
+
+/// Acceleration curve applied to translated mouse motion (e.g. from sticks or
+/// gyro). Speeds below [REFERENCE_SPEED_PPS] are slowed down and speeds above
+/// it are sped up by the non-flat curves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AccelerationProfile {
+    /// The mouse speed is proportional to the input
+    #[default]
+    Flat,
+    /// The mouse speed grows with the square of the input
+    Quadratic,
+    /// The mouse speed grows with the cube of the input
+    Cubic,
+}
+
+impl AccelerationProfile {
+    /// All available acceleration profiles
+    pub const ALL: [AccelerationProfile; 3] = [
+        AccelerationProfile::Flat,
+        AccelerationProfile::Quadratic,
+        AccelerationProfile::Cubic,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccelerationProfile::Flat => "flat",
+            AccelerationProfile::Quadratic => "quadratic",
+            AccelerationProfile::Cubic => "cubic",
+        }
+    }
+
+    /// Apply the acceleration curve to the given mouse velocity in pixels per
+    /// second. The direction of the velocity is kept.
+    pub fn apply(&self, velocity: (f64, f64)) -> (f64, f64) {
+        let speed = velocity.0.hypot(velocity.1);
+        if speed == 0.0 {
+            return velocity;
+        }
+        let normal_speed = speed / REFERENCE_SPEED_PPS;
+        let curved_speed = match self {
+            AccelerationProfile::Flat => return velocity,
+            AccelerationProfile::Quadratic => normal_speed.powi(2),
+            AccelerationProfile::Cubic => normal_speed.powi(3),
+        };
+        let factor = curved_speed * REFERENCE_SPEED_PPS / speed;
+        (velocity.0 * factor, velocity.1 * factor)
+    }
+}
+
+impl FromStr for AccelerationProfile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "flat" => Ok(AccelerationProfile::Flat),
+            "quadratic" => Ok(AccelerationProfile::Quadratic),
+            "cubic" => Ok(AccelerationProfile::Cubic),
+            _ => Err(format!("Invalid acceleration profile: {s}")),
+        }
+    }
+}
+
+/// Acceleration and smoothing of translated mouse motion that can be changed
+/// at runtime over DBus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PointerSettings {
+    pub acceleration: AccelerationProfile,
+    /// Time in milliseconds over which changes in mouse speed are smoothed.
+    /// A value of 0 disables smoothing.
+    pub smoothing_ms: u32,
+}
+
+/// Configuration of the target touchpad device.
+#[derive(Debug, Clone)]
+pub struct MouseConfig {
+    pub name: String,
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub version: u16,
+    pub pointer: PointerSettings,
+    /// Backend used to emit mouse input
+    pub backend: Backend,
+    /// Wayland display to connect to when using the Wayland backend
+    pub wayland_display: Option<String>,
+}
+
+impl Default for MouseConfig {
+    fn default() -> Self {
+        Self {
+            name: "InputPlumber Mouse".to_string(),
+            vendor_id: 0x0000,
+            product_id: 0xffff,
+            version: 0x001,
+            pointer: PointerSettings::default(),
+            backend: Backend::default(),
+            wayland_display: None,
+        }
+    }
+}
+
+/// The [MouseMotionState] keeps track of the mouse velocity from translated
+/// input events (like a joystick), and sends mouse motion events to the
+/// [MouseDevice] based on the current velocity.
+#[derive(Debug, Default)]
+pub struct MouseMotionState {
+    mouse_remainder: (f64, f64),
+    mouse_velocity: (f64, f64),
+    /// Mouse velocity after smoothing is applied
+    smoothed_velocity: (f64, f64),
+    /// Width and height in pixels of the box that mouse motion is constrained
+    /// to
+    bounds: Option<(f64, f64)>,
+    /// Position of the mouse relative to the center of the bounds
+    position: (f64, f64),
+    /// Relative motion required to move the mouse back to the center of the
+    /// bounds
+    recenter: (i32, i32),
+    /// Time at which mouse motion last stopped
+    idle_since: Option<Instant>,
+}
+
+impl MouseMotionState {
+    /// Set the box that mouse motion is constrained to, with its width and
+    /// height given as a fraction of the given screen size in pixels. The
+    /// current pointer position becomes the center of the box when the
+    /// bounds are activated.
+    pub fn set_bounds(&mut self, bounds: Option<(f64, f64)>, screen_size: (f64, f64)) {
+        let Some((width, height)) = bounds else {
+            self.bounds = None;
+            self.position = (0.0, 0.0);
+            return;
+        };
+        if self.bounds.is_none() {
+            self.position = (0.0, 0.0);
+        }
+        self.bounds = Some((
+            width.clamp(0.0, 1.0) * screen_size.0,
+            height.clamp(0.0, 1.0) * screen_size.1,
+        ));
+    }
+
+    /// Returns true if mouse motion is constrained to a box
+    pub fn has_bounds(&self) -> bool {
+        self.bounds.is_some()
+    }
+
+    /// Returns the position of the mouse relative to the center of the bounds
+    pub fn position(&self) -> (f64, f64) {
+        self.position
+    }
+
+    /// Update the mouse velocity at the given time. If motion starts after
+    /// the mouse rested at the edge of the bounds for at least
+    /// [RECENTER_IDLE_TIME], the mouse is moved back to the center of the
+    /// bounds.
+    pub fn set_velocity(&mut self, x: Option<f64>, y: Option<f64>, now: Instant) {
+        let was_idle = self.mouse_velocity == (0.0, 0.0);
+        if let Some(x) = x {
+            self.mouse_velocity.0 = x;
+        }
+        if let Some(y) = y {
+            self.mouse_velocity.1 = y;
+        }
+        let is_idle = self.mouse_velocity == (0.0, 0.0);
+
+        if !was_idle && is_idle {
+            self.idle_since = Some(now);
+            return;
+        }
+        if !was_idle || is_idle {
+            return;
+        }
+        let Some(idle_since) = self.idle_since.take() else {
+            return;
+        };
+        let rested = now.duration_since(idle_since) >= RECENTER_IDLE_TIME;
+        if rested && self.at_edge() {
+            let (x, y) = self.position;
+            self.recenter = (-x as i32, -y as i32);
+            self.position = (0.0, 0.0);
+            log::trace!("Recentering mouse in bounds: {:?}", self.recenter);
+        }
+    }
+
+    /// Returns and clears the relative motion required to move the mouse back
+    /// to the center of the bounds
+    pub fn take_recenter(&mut self) -> (i32, i32) {
+        std::mem::take(&mut self.recenter)
+    }
+
+    /// Returns true if the mouse position is at the edge of the bounds
+    fn at_edge(&self) -> bool {
+        let Some((width, height)) = self.bounds else {
+            return false;
+        };
+        let half = ((width / 2.0).floor(), (height / 2.0).floor());
+        self.position.0.abs() >= half.0 || self.position.1.abs() >= half.1
+    }
+
+    /// Constrain the given relative motion so the mouse position stays within
+    /// the bounds, if any are set. Returns the constrained motion.
+    pub fn constrain(&mut self, x: i32, y: i32) -> (i32, i32) {
+        let Some((width, height)) = self.bounds else {
+            return (x, y);
+        };
+        let half = ((width / 2.0).floor(), (height / 2.0).floor());
+        let new_x = (self.position.0 + x as f64).clamp(-half.0, half.0);
+        let new_y = (self.position.1 + y as f64).clamp(-half.1, half.1);
+        let motion = (
+            (new_x - self.position.0) as i32,
+            (new_y - self.position.1) as i32,
+        );
+        self.position = (new_x, new_y);
+        motion
+    }
+
+    /// Move the smoothed velocity towards the current mouse velocity based on
+    /// the given elapsed time in seconds and smoothing time in milliseconds.
+    /// Returns the smoothed velocity.
+    fn smooth(&mut self, elapsed: f64, smoothing_ms: u32) -> (f64, f64) {
+        if smoothing_ms == 0 {
+            self.smoothed_velocity = self.mouse_velocity;
+            return self.smoothed_velocity;
+        }
+        let alpha = 1.0 - (-elapsed * 1000.0 / smoothing_ms as f64).exp();
+        let (x, y) = self.smoothed_velocity;
+        let x = x + (self.mouse_velocity.0 - x) * alpha;
+        let y = y + (self.mouse_velocity.1 - y) * alpha;
+        self.smoothed_velocity =
+            if self.mouse_velocity == (0.0, 0.0) && x.hypot(y) < MIN_SMOOTHED_SPEED_PPS {
+                (0.0, 0.0)
+            } else {
+                (x, y)
+            };
+        self.smoothed_velocity
+    }
+}
+
+/// The [MouseWheelState] keeps track of translated mouse wheel input (like a
+/// dial or a joystick) and sends discrete wheel detents to the [MouseDevice].
+#[derive(Debug, Default)]
+pub struct MouseWheelState {
+    /// Fractional detents left over from relative wheel input, like a dial
+    remainder: HashMap<MouseButton, f64>,
+    /// Wheel buttons that are currently held with their repeat rate in
+    /// detents per second. A repeat rate of 0.0 means the button does not
+    /// repeat.
+    held: HashMap<MouseButton, f64>,
+    /// Fractional detents accumulated while repeating held wheel buttons
+    repeat_remainder: HashMap<MouseButton, f64>,
+}
+
+impl MouseWheelState {
+    /// Update the wheel state with the given wheel button value. Returns the
+    /// number of detents that should be scrolled right away.
+    fn update(
+        &mut self,
+        button: &MouseButton,
+        value: &InputValue,
+        repeat_rate: Option<f64>,
+    ) -> i32 {
+        match value {
+            // Relative input accumulates until at least one detent is reached
+            InputValue::Float(detents) => {
+                let remainder = self.remainder.entry(button.clone()).or_default();
+                *remainder += detents;
+                let detents = remainder.trunc();
+                *remainder -= detents;
+                detents as i32
+            }
+            // Pressed buttons scroll one detent and keep scrolling at the
+            // repeat rate until released. Input like a joystick can report
+            // the button as pressed many times while it is held.
+            InputValue::Bool(true) => {
+                if self.held.contains_key(button) {
+                    return 0;
+                }
+                let rate = repeat_rate.unwrap_or_default().max(0.0);
+                self.held.insert(button.clone(), rate);
+                self.repeat_remainder.insert(button.clone(), 0.0);
+                1
+            }
+            _ => {
+                self.held.remove(button);
+                self.repeat_remainder.remove(button);
+                0
+            }
+        }
+    }
+
+    /// Returns the number of detents each held wheel button should scroll
+    /// after the given number of seconds has elapsed
+    fn repeat(&mut self, elapsed: f64) -> Vec<(MouseButton, i32)> {
+        let mut detents = Vec::new();
+        for (button, rate) in self.held.iter() {
+            if *rate <= 0.0 {
+                continue;
+            }
+            let remainder = self.repeat_remainder.entry(button.clone()).or_default();
+            *remainder += elapsed * rate;
+            let count = remainder.trunc();
+            *remainder -= count;
+            if count >= 1.0 {
+                detents.push((button.clone(), count as i32));
+            }
+        }
+        detents
+    }
+}
+
+/// [MouseDevice] is a target virtual mouse that can be used to send mouse input
+#[derive(Debug)]
+pub struct MouseDevice {
+    device: VirtualInputDevice,
+    pointer: Arc<Mutex<PointerSettings>>,
+    state: MouseMotionState,
+    wheel: MouseWheelState,
+    axis_map: HashMap<AbsoluteAxisCode, AbsInfo>,
+    last_poll: Instant,
+    /// Width and height in pixels of the screen that mouse bounds are
+    /// relative to
+    screen_size: (f64, f64),
+}
+
+impl MouseDevice {
+    /// Create a new emulated mouse device with the default configuration and
+    /// the given identity overrides.
+    pub fn new(options: Option<&config::TargetDeviceOptions>) -> Result<Self, Box<dyn Error>> {
+        let mut config = MouseConfig::default();
+        if let Some(options) = options {
+            if let Some(name) = options.name.as_ref() {
+                config.name = name.clone();
+            }
+            if let Some(vendor_id) = options.vendor_id {
+                config.vendor_id = vendor_id;
+            }
+            if let Some(product_id) = options.product_id {
+                config.product_id = product_id;
+            }
+            if let Some(acceleration) = options.pointer_acceleration.as_ref() {
+                config.pointer.acceleration = AccelerationProfile::from_str(acceleration)?;
+            }
+            if let Some(smoothing_ms) = options.pointer_smoothing_ms {
+                config.pointer.smoothing_ms = smoothing_ms;
+            }
+            config.backend = Backend::from_options(Some(options))?;
+            config.wayland_display = options.wayland_display.clone();
+        }
+        MouseDevice::new_with_config(config)
+    }
+
+    /// Create a new emulated touchpad device with the given configuration.
+    pub fn new_with_config(config: MouseConfig) -> Result<Self, Box<dyn Error>> {
+        let device = match config.backend {
+            Backend::Uinput => {
+                VirtualInputDevice::Uinput(MouseDevice::create_virtual_device(&config)?)
+            }
+            Backend::Wayland => VirtualInputDevice::Wayland(WaylandDevice::pointer(
+                config.wayland_display.as_deref(),
+            )?),
+        };
+        Ok(Self {
+            device,
+            pointer: Arc::new(Mutex::new(config.pointer)),
+            state: MouseMotionState::default(),
+            wheel: MouseWheelState::default(),
+            axis_map: HashMap::new(),
+            last_poll: Instant::now(),
+            screen_size: (DEFAULT_SCREEN_SIZE.0 as f64, DEFAULT_SCREEN_SIZE.1 as f64),
+        })
+    }
+
+    /// Translate the given native event into an evdev event
+    fn translate_event(&self, event: NativeEvent) -> Vec<InputEvent> {
+        EvdevEvent::from_native_event(event, self.axis_map.clone())
+            .into_iter()
+            .map(|event| event.as_input_event())
+            .collect()
+    }
+
+    /// Create the virtual device to emulate
+    fn create_virtual_device(config: &MouseConfig) -> Result<VirtualDevice, Box<dyn Error>> {
+        let mut buttons = AttributeSet::<KeyCode>::new();
+        buttons.insert(KeyCode::BTN_LEFT);
+        buttons.insert(KeyCode::BTN_RIGHT);
+        buttons.insert(KeyCode::BTN_MIDDLE);
+        buttons.insert(KeyCode::BTN_SIDE);
+        buttons.insert(KeyCode::BTN_EXTRA);
+
+        // Identify to the kernel as a touchpad
+        let name = config.name.as_str();
+        let vendor = config.vendor_id;
+        let product = config.product_id;
+        let version = config.version;
+        let id = InputId::new(BusType(3), vendor, product, version);
+
+        let device = VirtualDeviceBuilder::new()?
+            .name(name)
+            .input_id(id)
+            .with_keys(&buttons)?
+            .with_relative_axes(&AttributeSet::from_iter([
+                RelativeAxisCode::REL_X,
+                RelativeAxisCode::REL_Y,
+                RelativeAxisCode::REL_WHEEL,
+                RelativeAxisCode::REL_HWHEEL,
+            ]))?
+            .build()?;
+
+        Ok(device)
+    }
+
+    /// Processes the given mouse motion or button input event.
+    fn update_state(&mut self, event: NativeEvent) {
+        // Get the mouse position from the event value
+        let value = event.get_value();
+        let (x, y) = match value {
+            InputValue::Vector2 { x, y } => (x, y),
+            InputValue::Vector3 { x, y, z: _ } => (x, y),
+            _ => (None, None),
+        };
+
+        // Update the bounds the mouse should be constrained to, reading the
+        // screen size each time the bounds are activated.
+        let bounds = event.get_motion_bounds();
+        if bounds.is_some() && !self.state.has_bounds() {
+            let (width, height) = display::screen_size().unwrap_or(DEFAULT_SCREEN_SIZE);
+            self.screen_size = (width as f64, height as f64);
+        }
+        self.state.set_bounds(bounds, self.screen_size);
+
+        // Update the mouse velocity
+        self.state.set_velocity(x, y, Instant::now());
+        log::trace!("Updating mouse state: {:?}", self.state.mouse_velocity);
+    }
+
+    /// Scroll the given mouse wheel button by the given number of detents
+    fn scroll(&mut self, button: &MouseButton, detents: i32) -> Result<(), InputError> {
+        let (axis, value) = match button {
+            MouseButton::WheelUp => (RelativeAxisCode::REL_WHEEL, detents),
+            MouseButton::WheelDown => (RelativeAxisCode::REL_WHEEL, -detents),
+            MouseButton::WheelRight => (RelativeAxisCode::REL_HWHEEL, detents),
+            MouseButton::WheelLeft => (RelativeAxisCode::REL_HWHEEL, -detents),
+            _ => return Ok(()),
+        };
+        if value == 0 {
+            return Ok(());
+        }
+        let event = InputEvent::new(EventType::RELATIVE.0, axis.0, value);
+        if let Err(e) = self.device.emit(&[event]) {
+            return Err(e.to_string().into());
+        }
+
+        Ok(())
+    }
+}
+
+impl TargetInputDevice for MouseDevice {
+    fn start_dbus_interface(&mut self, dbus: Connection, path: String, client: TargetDeviceClient) {
+        log::debug!("Starting dbus interface: {path}");
+        let pointer = self.pointer.clone();
+        tokio::task::spawn(async move {
+            let iface = TargetMouseInterface::new(client);
+            if let Err(e) = dbus.object_server().at(path.clone(), iface).await {
+                log::debug!("Failed to start dbus interface {path}: {e:?}");
+            } else {
+                log::debug!("Started dbus interface on {path}");
+            };
+            let iface = TargetPointerInterface::new(pointer);
+            if let Err(e) = dbus.object_server().at(path.clone(), iface).await {
+                log::debug!("Failed to start pointer dbus interface {path}: {e:?}");
+            }
+        });
+    }
+
+    fn get_syspath(&mut self) -> Option<PathBuf> {
+        self.device.get_syspath()
+    }
+
+    fn write_event(&mut self, event: NativeEvent) -> Result<(), InputError> {
+        log::trace!("Received event: {event:?}");
+
+        // Check if this event needs to be processed by the
+        // mouse state.
+        if event.is_translated()
+            && matches!(event.as_capability(), Capability::Mouse(Mouse::Motion))
+        {
+            log::trace!("Got translated mouse motion event: {:?}", event);
+            self.update_state(event);
+            return Ok(());
+        }
+
+        // Mouse wheel buttons are emitted as discrete wheel detents
+        if let Capability::Mouse(Mouse::Button(
+            button @ (MouseButton::WheelUp
+            | MouseButton::WheelDown
+            | MouseButton::WheelLeft
+            | MouseButton::WheelRight),
+        )) = event.as_capability()
+        {
+            let repeat_rate = event.get_wheel_repeat_rate();
+            let detents = self.wheel.update(&button, &event.get_value(), repeat_rate);
+            return self.scroll(&button, detents);
+        }
+
+        // Translate and emit the event(s)
+        let evdev_events = self.translate_event(event);
+        if let Err(e) = self.device.emit(evdev_events.as_slice()) {
+            return Err(e.to_string().into());
+        }
+
+        Ok(())
+    }
+
+    fn get_capabilities(&self) -> Result<Vec<crate::input::capability::Capability>, InputError> {
+        Ok(vec![
+            Capability::Mouse(Mouse::Button(MouseButton::Left)),
+            Capability::Mouse(Mouse::Button(MouseButton::Right)),
+            Capability::Mouse(Mouse::Button(MouseButton::Middle)),
+            Capability::Mouse(Mouse::Button(MouseButton::Side)),
+            Capability::Mouse(Mouse::Button(MouseButton::Extra)),
+            Capability::Mouse(Mouse::Button(MouseButton::WheelUp)),
+            Capability::Mouse(Mouse::Button(MouseButton::WheelDown)),
+            Capability::Mouse(Mouse::Button(MouseButton::WheelLeft)),
+            Capability::Mouse(Mouse::Button(MouseButton::WheelRight)),
+            Capability::Mouse(Mouse::Motion),
+        ])
+    }
+
+    fn stop_dbus_interface(&mut self, dbus: Connection, path: String) {
+        log::debug!("Stopping dbus interface for {path}");
+        tokio::task::spawn(async move {
+            let result = dbus
+                .object_server()
+                .remove::<TargetMouseInterface, String>(path.clone())
+                .await;
+            if let Err(e) = result {
+                log::error!("Failed to stop dbus interface {path}: {e:?}");
+            } else {
+                log::debug!("Stopped dbus interface for {path}");
+            };
+            let result = dbus
+                .object_server()
+                .remove::<TargetPointerInterface, String>(path.clone())
+                .await;
+            if let Err(e) = result {
+                log::error!("Failed to stop pointer dbus interface {path}: {e:?}");
+            }
+        });
+    }
+
+    fn clear_state(&mut self) {
+        self.state.mouse_velocity = (0.0, 0.0);
+        self.state.smoothed_velocity = (0.0, 0.0);
+        self.state.mouse_remainder = (0.0, 0.0);
+        self.state.position = (0.0, 0.0);
+        self.state.recenter = (0, 0);
+        self.state.idle_since = None;
+        self.wheel = MouseWheelState::default();
+    }
+}
+
+impl TargetOutputDevice for MouseDevice {
+    /// Move the mouse based on the given input event translation
+    fn poll(&mut self, _: &Option<CompositeDeviceClient>) -> Result<Vec<OutputEvent>, OutputError> {
+        // Calculate the delta between the last poll
+        let delta = self.last_poll.elapsed();
+        self.last_poll = Instant::now();
+
+        // Keep scrolling any mouse wheel buttons that are held
+        for (button, detents) in self.wheel.repeat(delta.as_secs_f64()) {
+            if let Err(e) = self.scroll(&button, detents) {
+                return Err(e.to_string().into());
+            }
+        }
+
+        // Smooth and accelerate the current mouse velocity
+        let pointer = *self.pointer.lock().unwrap();
+        let velocity = self.state.smooth(delta.as_secs_f64(), pointer.smoothing_ms);
+        let velocity = pointer.acceleration.apply(velocity);
+
+        // Calculate how much the mouse should move based on the current mouse velocity
+        let mut pixels_to_move = (0.0, 0.0);
+        pixels_to_move.0 = delta.as_secs_f64() * velocity.0;
+        pixels_to_move.1 = delta.as_secs_f64() * velocity.1;
+
+        // Get the fractional value of the position so we can accumulate them
+        // in between invocations
+        let mut x = pixels_to_move.0 as i32; // E.g. 3.14 -> 3
+        let mut y = pixels_to_move.1 as i32;
+        self.state.mouse_remainder.0 += pixels_to_move.0 - x as f64;
+        self.state.mouse_remainder.1 += pixels_to_move.1 - y as f64;
+
+        // Keep track of relative mouse movements to keep around fractional values
+        if self.state.mouse_remainder.0 >= 1.0 {
+            x += 1;
+            self.state.mouse_remainder.0 -= 1.0;
+        }
+        if self.state.mouse_remainder.0 <= -1.0 {
+            x -= 1;
+            self.state.mouse_remainder.0 += 1.0;
+        }
+        if self.state.mouse_remainder.1 >= 1.0 {
+            y += 1;
+            self.state.mouse_remainder.1 -= 1.0;
+        }
+        if self.state.mouse_remainder.1 <= -1.0 {
+            y -= 1;
+            self.state.mouse_remainder.1 += 1.0;
+        }
+
+        // Keep the mouse within the configured bounds and move it back to the
+        // center of the bounds if it rested at the edge before motion started.
+        let (x, y) = self.state.constrain(x, y);
+        let recenter = self.state.take_recenter();
+        let x = x + recenter.0;
+        let y = y + recenter.1;
+
+        // Send events to the device if the mouse state has changed
+        if x != 0 {
+            let value = InputValue::Vector2 {
+                x: Some(x as f64),
+                y: None,
+            };
+            let event = NativeEvent::new(Capability::Mouse(Mouse::Motion), value);
+            if let Err(e) = self.write_event(event) {
+                return Err(e.to_string().into());
+            }
+        }
+        if y != 0 {
+            let value = InputValue::Vector2 {
+                x: None,
+                y: Some(y as f64),
+            };
+            let event = NativeEvent::new(Capability::Mouse(Mouse::Motion), value);
+            if let Err(e) = self.write_event(event) {
+                return Err(e.to_string().into());
+            }
+        }
+
+        Ok(vec![])
+    }
+}
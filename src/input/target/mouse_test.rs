@@ -0,0 +1,104 @@
+use std::{
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use super::mouse::{AccelerationProfile, MouseMotionState, RECENTER_IDLE_TIME};
+
+#[test]
+fn test_acceleration_profile_from_str() {
+    for profile in AccelerationProfile::ALL {
+        assert_eq!(AccelerationProfile::from_str(profile.as_str()), Ok(profile));
+    }
+    assert!(AccelerationProfile::from_str("linear").is_err());
+}
+
+#[test]
+fn test_acceleration_profile_apply() {
+    // Flat profile does not change the velocity
+    let velocity = (300.0, -400.0);
+    assert_eq!(AccelerationProfile::Flat.apply(velocity), velocity);
+
+    // All profiles agree at the reference speed
+    let reference = (800.0, 0.0);
+    for profile in AccelerationProfile::ALL {
+        let (x, y) = profile.apply(reference);
+        assert!((x - 800.0).abs() < 1e-9);
+        assert_eq!(y, 0.0);
+    }
+
+    // Slow motion is slowed down and fast motion is sped up, keeping direction
+    let (x, y) = AccelerationProfile::Quadratic.apply((240.0, -320.0));
+    assert!((x - 60.0).abs() < 1e-9);
+    assert!((y + 80.0).abs() < 1e-9);
+    let (x, y) = AccelerationProfile::Cubic.apply((0.0, 1600.0));
+    assert_eq!(x, 0.0);
+    assert!((y - 6400.0).abs() < 1e-9);
+
+    // No motion stays still
+    assert_eq!(AccelerationProfile::Cubic.apply((0.0, 0.0)), (0.0, 0.0));
+}
+
+#[test]
+fn test_mouse_bounds_clamping() {
+    let mut state = MouseMotionState::default();
+
+    // Motion is not constrained without bounds
+    assert_eq!(state.constrain(5000, -5000), (5000, -5000));
+
+    // Bounds are relative to the screen size
+    state.set_bounds(Some((0.5, 0.25)), (1920.0, 1080.0));
+    assert!(state.has_bounds());
+    assert_eq!(state.position(), (0.0, 0.0));
+    assert_eq!(state.constrain(100, -100), (100, -100));
+    assert_eq!(state.constrain(1000, -1000), (380, -35));
+    assert_eq!(state.position(), (480.0, -135.0));
+    assert_eq!(state.constrain(10, -10), (0, 0));
+    assert_eq!(state.constrain(-10, 10), (-10, 10));
+
+    // Bounds larger than the screen are limited to the screen size
+    let mut state = MouseMotionState::default();
+    state.set_bounds(Some((2.0, 1.0)), (1920.0, 1080.0));
+    assert_eq!(state.constrain(5000, 5000), (960, 540));
+
+    // Removing the bounds resets the position
+    state.set_bounds(None, (1920.0, 1080.0));
+    assert!(!state.has_bounds());
+    assert_eq!(state.position(), (0.0, 0.0));
+}
+
+#[test]
+fn test_mouse_bounds_recenter() {
+    let mut state = MouseMotionState::default();
+    state.set_bounds(Some((0.1, 0.1)), (1000.0, 1000.0));
+    let start = Instant::now();
+
+    // Move the mouse to the edge of the bounds
+    state.set_velocity(Some(800.0), Some(0.0), start);
+    assert_eq!(state.constrain(200, 0), (50, 0));
+    assert_eq!(state.take_recenter(), (0, 0));
+
+    // Changing direction does not recenter the mouse
+    state.set_velocity(Some(0.0), None, start);
+    let now = start + Duration::from_millis(10);
+    state.set_velocity(Some(-800.0), None, now);
+    assert_eq!(state.take_recenter(), (0, 0));
+    assert_eq!(state.constrain(-20, 0), (-20, 0));
+    assert_eq!(state.position(), (30.0, 0.0));
+
+    // Resting away from the edge does not recenter the mouse
+    state.set_velocity(Some(0.0), None, now);
+    let now = now + RECENTER_IDLE_TIME * 2;
+    state.set_velocity(Some(800.0), None, now);
+    assert_eq!(state.take_recenter(), (0, 0));
+    assert_eq!(state.position(), (30.0, 0.0));
+
+    // Resting at the edge recenters the mouse when motion starts again
+    assert_eq!(state.constrain(100, -100), (20, -50));
+    state.set_velocity(Some(0.0), None, now);
+    let now = now + RECENTER_IDLE_TIME;
+    state.set_velocity(None, Some(800.0), now);
+    assert_eq!(state.take_recenter(), (-50, 50));
+    assert_eq!(state.take_recenter(), (0, 0));
+    assert_eq!(state.position(), (0.0, 0.0));
+}
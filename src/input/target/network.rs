@@ -0,0 +1,329 @@
+use std::{
+    error::Error,
+    io::{self, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::{Duration, Instant},
+};
+
+use vsock::{VsockAddr, VsockStream};
+
+use crate::{
+    config,
+    input::{
+        capability::{Capability, Gamepad, GamepadAxis, GamepadButton, GamepadTrigger},
+        composite_device::client::CompositeDeviceClient,
+        event::native::NativeEvent,
+        output_event::OutputEvent,
+        source::{
+            external::protocol::{self, DriverMessage, ServerMessage, PROTOCOL_VERSION},
+            network::NetworkAddress,
+        },
+    },
+};
+
+use super::{InputError, OutputError, TargetInputDevice, TargetOutputDevice};
+
+/// Default name the device is registered with on the remote InputPlumber
+const DEFAULT_NAME: &str = "InputPlumber Network Gamepad";
+/// Time to wait for a connection to the remote InputPlumber
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+/// Time to wait before trying to reconnect after the connection was lost
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(2);
+/// Time to wait for a message to be written before the connection is
+/// considered lost
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Connection to the remote InputPlumber
+#[derive(Debug)]
+enum NetworkStream {
+    Tcp(TcpStream),
+    Vsock(VsockStream),
+}
+
+impl NetworkStream {
+    /// Connect to the given address
+    fn connect(address: &NetworkAddress) -> io::Result<Self> {
+        let stream = match address {
+            NetworkAddress::Tcp(addr) => {
+                let mut last_error = None;
+                let mut stream = None;
+                for addr in addr.to_socket_addrs()? {
+                    match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+                        Ok(s) => {
+                            stream = Some(s);
+                            break;
+                        }
+                        Err(e) => last_error = Some(e),
+                    }
+                }
+                let Some(stream) = stream else {
+                    return Err(last_error.unwrap_or(io::ErrorKind::NotFound.into()));
+                };
+                stream.set_nodelay(true)?;
+                stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+                NetworkStream::Tcp(stream)
+            }
+            NetworkAddress::Vsock { cid, port } => {
+                let stream = VsockStream::connect(&VsockAddr::new(*cid, *port))?;
+                stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+                NetworkStream::Vsock(stream)
+            }
+        };
+        Ok(stream)
+    }
+
+    /// Write the given message as a single line
+    fn send(&mut self, message: &DriverMessage) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let line = protocol::encode_driver(message)?;
+        match self {
+            NetworkStream::Tcp(stream) => stream.write_all(line.as_bytes())?,
+            NetworkStream::Vsock(stream) => stream.write_all(line.as_bytes())?,
+        }
+        Ok(())
+    }
+
+    /// Read any data that is available without blocking into the given
+    /// buffer. Returns false if the connection was closed.
+    fn read_available(&mut self, buffer: &mut Vec<u8>) -> io::Result<bool> {
+        let mut chunk = [0; 1024];
+        self.set_nonblocking(true)?;
+        let result = loop {
+            let result = match self {
+                NetworkStream::Tcp(stream) => stream.read(&mut chunk),
+                NetworkStream::Vsock(stream) => stream.read(&mut chunk),
+            };
+            match result {
+                Ok(0) => break Ok(false),
+                Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break Ok(true),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => break Err(e),
+            }
+        };
+        self.set_nonblocking(false)?;
+
+        result
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        match self {
+            NetworkStream::Tcp(stream) => stream.set_nonblocking(nonblocking),
+            NetworkStream::Vsock(stream) => stream.set_nonblocking(nonblocking),
+        }
+    }
+}
+
+/// The [NetworkDevice] is a target device that sends translated input to a
+/// remote InputPlumber instance, e.g. on another machine or inside a virtual
+/// machine. The remote InputPlumber must listen for network input, where the
+/// device appears as an external source device with gamepad capabilities.
+#[derive(Debug)]
+pub struct NetworkDevice {
+    address: NetworkAddress,
+    register: DriverMessage,
+    stream: Option<NetworkStream>,
+    /// Data received from the remote InputPlumber that is not a complete
+    /// line yet
+    buffer: Vec<u8>,
+    last_connect: Option<Instant>,
+}
+
+impl NetworkDevice {
+    /// Create a new network device that connects to the address from the
+    /// given options
+    pub fn new(options: Option<&config::TargetDeviceOptions>) -> Result<Self, Box<dyn Error>> {
+        let Some(address) = options.and_then(|options| options.address.as_ref()) else {
+            return Err("The 'network' target device requires an 'address' option".into());
+        };
+        let address: NetworkAddress = address.parse()?;
+        let name = options
+            .and_then(|options| options.name.clone())
+            .unwrap_or(DEFAULT_NAME.to_string());
+        let register = DriverMessage::Register {
+            version: PROTOCOL_VERSION,
+            name,
+            vendor_id: options.and_then(|options| options.vendor_id),
+            product_id: options.and_then(|options| options.product_id),
+            capabilities: capabilities().iter().map(|cap| cap.to_string()).collect(),
+            output_capabilities: vec![],
+        };
+
+        let mut device = Self {
+            address,
+            register,
+            stream: None,
+            buffer: Vec::new(),
+            last_connect: None,
+        };
+        // The device keeps trying to connect if the remote InputPlumber is
+        // not reachable yet.
+        device.connect();
+
+        Ok(device)
+    }
+
+    /// Connect and register with the remote InputPlumber if not connected.
+    /// Connection attempts are limited to one every [RECONNECT_INTERVAL].
+    fn connect(&mut self) -> bool {
+        if self.stream.is_some() {
+            return true;
+        }
+        if self
+            .last_connect
+            .is_some_and(|last| last.elapsed() < RECONNECT_INTERVAL)
+        {
+            return false;
+        }
+        self.last_connect = Some(Instant::now());
+        self.buffer.clear();
+
+        let mut stream = match NetworkStream::connect(&self.address) {
+            Ok(stream) => stream,
+            Err(e) => {
+                log::debug!("Failed to connect to {}: {e}", self.address);
+                return false;
+            }
+        };
+        if let Err(e) = stream.send(&self.register) {
+            log::debug!("Failed to register with {}: {e}", self.address);
+            return false;
+        }
+        log::info!("Connected to network input at {}", self.address);
+        self.stream = Some(stream);
+
+        true
+    }
+
+    /// Close the connection to the remote InputPlumber
+    fn disconnect(&mut self, reason: &str) {
+        if self.stream.take().is_some() {
+            log::warn!("Disconnected from {}: {reason}", self.address);
+        }
+    }
+
+    /// Send the given message, closing the connection if it fails
+    fn send(&mut self, message: &DriverMessage) {
+        let Some(stream) = self.stream.as_mut() else {
+            return;
+        };
+        if let Err(e) = stream.send(message) {
+            self.disconnect(e.to_string().as_str());
+        }
+    }
+
+    /// Handle the given message from the remote InputPlumber
+    fn handle_message(&mut self, message: ServerMessage) {
+        match message {
+            ServerMessage::Registered { id } => {
+                log::info!("Registered with {} as {id}", self.address);
+            }
+            ServerMessage::Error { message } => {
+                log::warn!("Error from {}: {message}", self.address);
+            }
+            ServerMessage::Ping => self.send(&DriverMessage::Pong),
+            // Output events are not supported
+            ServerMessage::Rumble { .. } => (),
+            ServerMessage::Stop => self.disconnect("the remote device was stopped"),
+        }
+    }
+}
+
+impl TargetInputDevice for NetworkDevice {
+    fn write_event(&mut self, event: NativeEvent) -> Result<(), InputError> {
+        log::trace!("Sending event: {event:?}");
+        // Events are dropped while the remote InputPlumber is unreachable
+        if !self.connect() {
+            return Ok(());
+        }
+        self.send(&protocol::from_native_event(&event));
+        Ok(())
+    }
+
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(capabilities())
+    }
+
+    fn stop(&mut self) -> Result<(), InputError> {
+        self.stream = None;
+        Ok(())
+    }
+}
+
+impl TargetOutputDevice for NetworkDevice {
+    /// Read messages from the remote InputPlumber and respond to keepalive
+    /// requests
+    fn poll(&mut self, _: &Option<CompositeDeviceClient>) -> Result<Vec<OutputEvent>, OutputError> {
+        if !self.connect() {
+            return Ok(vec![]);
+        }
+        let Some(stream) = self.stream.as_mut() else {
+            return Ok(vec![]);
+        };
+        match stream.read_available(&mut self.buffer) {
+            Ok(true) => (),
+            Ok(false) => {
+                self.disconnect("connection closed");
+                return Ok(vec![]);
+            }
+            Err(e) => {
+                self.disconnect(e.to_string().as_str());
+                return Ok(vec![]);
+            }
+        }
+
+        while let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            match protocol::decode_server(&line) {
+                Ok(message) => self.handle_message(message),
+                Err(e) => log::debug!("Invalid message from {}: {e}", self.address),
+            }
+        }
+
+        Ok(vec![])
+    }
+}
+
+/// Returns the gamepad capabilities sent to the remote InputPlumber
+pub fn capabilities() -> Vec<Capability> {
+    let buttons = [
+        GamepadButton::South,
+        GamepadButton::East,
+        GamepadButton::North,
+        GamepadButton::West,
+        GamepadButton::Start,
+        GamepadButton::Select,
+        GamepadButton::Guide,
+        GamepadButton::QuickAccess,
+        GamepadButton::QuickAccess2,
+        GamepadButton::DPadUp,
+        GamepadButton::DPadDown,
+        GamepadButton::DPadLeft,
+        GamepadButton::DPadRight,
+        GamepadButton::LeftBumper,
+        GamepadButton::LeftTrigger,
+        GamepadButton::LeftPaddle1,
+        GamepadButton::LeftPaddle2,
+        GamepadButton::LeftStick,
+        GamepadButton::LeftStickTouch,
+        GamepadButton::RightBumper,
+        GamepadButton::RightTrigger,
+        GamepadButton::RightPaddle1,
+        GamepadButton::RightPaddle2,
+        GamepadButton::RightStick,
+        GamepadButton::RightStickTouch,
+    ];
+    let mut capabilities: Vec<Capability> = buttons
+        .into_iter()
+        .map(|button| Capability::Gamepad(Gamepad::Button(button)))
+        .collect();
+    capabilities.extend([
+        Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+        Capability::Gamepad(Gamepad::Axis(GamepadAxis::RightStick)),
+        Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTrigger)),
+        Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::RightTrigger)),
+        Capability::Gamepad(Gamepad::Accelerometer),
+        Capability::Gamepad(Gamepad::Gyro),
+    ]);
+    capabilities
+}
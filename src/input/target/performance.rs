@@ -0,0 +1,119 @@
+//! Performance counters for target devices
+use std::{
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+    time::Duration,
+};
+
+/// Counters describing how quickly a target device is writing events and how
+/// full its command queue is. Counters are updated from the target device
+/// thread and can be read from any thread.
+#[derive(Debug, Default)]
+pub struct TargetPerformance {
+    queue_capacity: usize,
+    events_written: AtomicU64,
+    write_time_total_us: AtomicU64,
+    write_latency_last_us: AtomicU64,
+    write_latency_max_us: AtomicU64,
+    event_latency_last_us: AtomicU64,
+    event_latency_max_us: AtomicU64,
+    queue_depth: AtomicUsize,
+    queue_depth_max: AtomicUsize,
+}
+
+impl TargetPerformance {
+    /// Create new performance counters for a target device with the given
+    /// command queue capacity
+    pub fn new(queue_capacity: usize) -> Self {
+        Self {
+            queue_capacity,
+            ..Default::default()
+        }
+    }
+
+    /// Record that an event was written to the device in the given time
+    pub fn record_write(&self, elapsed: Duration) {
+        let us = elapsed.as_micros().min(u64::MAX as u128) as u64;
+        self.events_written.fetch_add(1, Ordering::Relaxed);
+        self.write_time_total_us.fetch_add(us, Ordering::Relaxed);
+        self.write_latency_last_us.store(us, Ordering::Relaxed);
+        self.write_latency_max_us.fetch_max(us, Ordering::Relaxed);
+    }
+
+    /// Record that an event reached the device the given time after it was
+    /// read from its source device
+    pub fn record_latency(&self, latency: Duration) {
+        let us = latency.as_micros().min(u64::MAX as u128) as u64;
+        self.event_latency_last_us.store(us, Ordering::Relaxed);
+        self.event_latency_max_us.fetch_max(us, Ordering::Relaxed);
+    }
+
+    /// Record the number of commands currently waiting in the queue
+    pub fn record_queue_depth(&self, depth: usize) {
+        self.queue_depth.store(depth, Ordering::Relaxed);
+        self.queue_depth_max.fetch_max(depth, Ordering::Relaxed);
+    }
+
+    /// Reset all counters
+    pub fn reset(&self) {
+        self.events_written.store(0, Ordering::Relaxed);
+        self.write_time_total_us.store(0, Ordering::Relaxed);
+        self.write_latency_last_us.store(0, Ordering::Relaxed);
+        self.write_latency_max_us.store(0, Ordering::Relaxed);
+        self.event_latency_last_us.store(0, Ordering::Relaxed);
+        self.event_latency_max_us.store(0, Ordering::Relaxed);
+        self.queue_depth.store(0, Ordering::Relaxed);
+        self.queue_depth_max.store(0, Ordering::Relaxed);
+    }
+
+    /// Total number of events written to the device
+    pub fn events_written(&self) -> u64 {
+        self.events_written.load(Ordering::Relaxed)
+    }
+
+    /// Time in microseconds it took to write the last event
+    pub fn write_latency_last_us(&self) -> u64 {
+        self.write_latency_last_us.load(Ordering::Relaxed)
+    }
+
+    /// Longest time in microseconds it took to write a single event
+    pub fn write_latency_max_us(&self) -> u64 {
+        self.write_latency_max_us.load(Ordering::Relaxed)
+    }
+
+    /// Average time in microseconds it took to write an event
+    pub fn write_latency_avg_us(&self) -> u64 {
+        let count = self.events_written();
+        if count == 0 {
+            return 0;
+        }
+        self.write_time_total_us.load(Ordering::Relaxed) / count
+    }
+
+    /// Time in microseconds between reading the last event from its source
+    /// device and it reaching the device
+    pub fn event_latency_last_us(&self) -> u64 {
+        self.event_latency_last_us.load(Ordering::Relaxed)
+    }
+
+    /// Longest time in microseconds between reading an event from its source
+    /// device and it reaching the device
+    pub fn event_latency_max_us(&self) -> u64 {
+        self.event_latency_max_us.load(Ordering::Relaxed)
+    }
+
+    /// Number of commands waiting in the queue
+    pub fn queue_depth(&self) -> usize {
+        self.queue_depth.load(Ordering::Relaxed)
+    }
+
+    /// Largest number of commands that were waiting in the queue
+    pub fn queue_depth_max(&self) -> usize {
+        self.queue_depth_max.load(Ordering::Relaxed)
+    }
+
+    /// Maximum number of commands the queue can hold before senders have to
+    /// wait
+    pub fn queue_capacity(&self) -> usize {
+        self.queue_capacity
+    }
+}
@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+use super::performance::TargetPerformance;
+
+#[test]
+fn test_record_write() {
+    let performance = TargetPerformance::new(2048);
+    assert_eq!(performance.write_latency_avg_us(), 0);
+
+    performance.record_write(Duration::from_micros(100));
+    performance.record_write(Duration::from_micros(300));
+    performance.record_write(Duration::from_micros(200));
+
+    assert_eq!(performance.events_written(), 3);
+    assert_eq!(performance.write_latency_last_us(), 200);
+    assert_eq!(performance.write_latency_max_us(), 300);
+    assert_eq!(performance.write_latency_avg_us(), 200);
+}
+
+#[test]
+fn test_record_latency() {
+    let performance = TargetPerformance::new(2048);
+    performance.record_latency(Duration::from_micros(900));
+    performance.record_latency(Duration::from_micros(400));
+
+    assert_eq!(performance.event_latency_last_us(), 400);
+    assert_eq!(performance.event_latency_max_us(), 900);
+}
+
+#[test]
+fn test_record_queue_depth() {
+    let performance = TargetPerformance::new(2048);
+    performance.record_queue_depth(10);
+    performance.record_queue_depth(1500);
+    performance.record_queue_depth(3);
+
+    assert_eq!(performance.queue_depth(), 3);
+    assert_eq!(performance.queue_depth_max(), 1500);
+    assert_eq!(performance.queue_capacity(), 2048);
+}
+
+#[test]
+fn test_reset() {
+    let performance = TargetPerformance::new(2048);
+    performance.record_write(Duration::from_micros(100));
+    performance.record_latency(Duration::from_micros(900));
+    performance.record_queue_depth(10);
+    performance.reset();
+
+    assert_eq!(performance.events_written(), 0);
+    assert_eq!(performance.write_latency_max_us(), 0);
+    assert_eq!(performance.event_latency_max_us(), 0);
+    assert_eq!(performance.queue_depth_max(), 0);
+    assert_eq!(performance.queue_capacity(), 2048);
+}
@@ -0,0 +1,221 @@
+//! Preflight checks that run before a target device is created. Creating a
+//! target device can fail for reasons outside of InputPlumber, such as a
+//! kernel module that is not loaded or missing permissions on a device node.
+//! The checks turn these into diagnostics with hints on how to fix them.
+use std::{collections::HashMap, error::Error, fmt, fs::OpenOptions, io};
+
+use crate::{config::TargetDeviceOptions, input::uinput};
+
+use super::TargetDeviceTypeId;
+
+/// Path to the UHID device node
+pub const UHID_PATH: &str = "/dev/uhid";
+
+/// A reason why a target device cannot be created
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreflightFailure {
+    /// The target device type is not supported
+    UnsupportedType(String),
+    /// The device node used to create the target device does not exist
+    MissingDeviceNode(String),
+    /// The device node exists but cannot be opened for reading and writing
+    PermissionDenied(String),
+    /// The device node cannot be opened for another reason
+    DeviceNodeError { path: String, error: String },
+    /// Another target device already uses the same unique identifier
+    DuplicateIdentity { uniq: String, path: String },
+}
+
+impl PreflightFailure {
+    /// Create a failure from the error returned when opening the given
+    /// device node
+    pub fn from_io_error(path: &str, error: &io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::NotFound => Self::MissingDeviceNode(path.to_string()),
+            io::ErrorKind::PermissionDenied => Self::PermissionDenied(path.to_string()),
+            _ => Self::DeviceNodeError {
+                path: path.to_string(),
+                error: error.to_string(),
+            },
+        }
+    }
+
+    /// Short identifier of the failure reason, e.g. "missing_device_node"
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Self::UnsupportedType(_) => "unsupported_type",
+            Self::MissingDeviceNode(_) => "missing_device_node",
+            Self::PermissionDenied(_) => "permission_denied",
+            Self::DeviceNodeError { .. } => "device_node_error",
+            Self::DuplicateIdentity { .. } => "duplicate_identity",
+        }
+    }
+
+    /// Hint on how the failure can be fixed
+    pub fn remediation(&self) -> String {
+        match self {
+            Self::UnsupportedType(_) => {
+                "use one of the supported target device types".to_string()
+            }
+            Self::MissingDeviceNode(path) => match module_name(path) {
+                Some(module) => format!("load the '{module}' kernel module with 'modprobe {module}'"),
+                None => format!("make sure {path} is created by the kernel"),
+            },
+            Self::PermissionDenied(path) => format!(
+                "run InputPlumber as root or grant it read and write access to {path} with a udev rule"
+            ),
+            Self::DeviceNodeError { path, .. } => {
+                format!("check the kernel log for errors related to {path}")
+            }
+            Self::DuplicateIdentity { path, .. } => {
+                format!("set a different 'uniq' or stop the target device at {path}")
+            }
+        }
+    }
+
+    /// Returns true if the failure is caused by uinput being unavailable
+    pub fn is_uinput_unavailable(&self) -> bool {
+        match self {
+            Self::MissingDeviceNode(path) | Self::PermissionDenied(path) => {
+                path == uinput::UINPUT_PATH
+            }
+            Self::DeviceNodeError { path, .. } => path == uinput::UINPUT_PATH,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for PreflightFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedType(kind) => write!(f, "unsupported target device type '{kind}'"),
+            Self::MissingDeviceNode(path) => write!(f, "{path} does not exist"),
+            Self::PermissionDenied(path) => write!(f, "permission denied opening {path}"),
+            Self::DeviceNodeError { path, error } => write!(f, "unable to open {path}: {error}"),
+            Self::DuplicateIdentity { uniq, path } => {
+                write!(f, "uniq '{uniq}' is already used by {path}")
+            }
+        }
+    }
+}
+
+/// Diagnostics of the preflight checks for a target device
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreflightReport {
+    /// Target device type that was checked
+    pub kind: String,
+    /// Reasons why the target device cannot be created
+    pub failures: Vec<PreflightFailure>,
+}
+
+impl PreflightReport {
+    /// Returns true if the target device can be created
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Log each failure along with a hint on how to fix it
+    pub fn log(&self) {
+        for failure in self.failures.iter() {
+            log::error!(
+                "Unable to create target device '{}': {failure} (hint: {})",
+                self.kind,
+                failure.remediation()
+            );
+        }
+    }
+}
+
+impl fmt::Display for PreflightReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "target device '{}' failed preflight checks: ", self.kind)?;
+        for (i, failure) in self.failures.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(
+                f,
+                "[{}] {failure} (hint: {})",
+                failure.reason(),
+                failure.remediation()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for PreflightReport {}
+
+/// Run the preflight checks for the given type of target device. The given
+/// identities map the DBus paths of running target devices to their unique
+/// identifiers.
+pub fn check(
+    kind: &str,
+    options: Option<&TargetDeviceOptions>,
+    identities: &HashMap<String, String>,
+) -> PreflightReport {
+    let mut report = PreflightReport {
+        kind: kind.to_string(),
+        failures: Vec::new(),
+    };
+    let Ok(id) = TargetDeviceTypeId::try_from(kind) else {
+        report
+            .failures
+            .push(PreflightFailure::UnsupportedType(kind.to_string()));
+        return report;
+    };
+
+    let node =
+        device_node(&id).filter(|node| *node != uinput::UINPUT_PATH || id.uses_uinput(options));
+    if let Some(failure) = node.and_then(check_device_node) {
+        report.failures.push(failure);
+    }
+    if let Some(failure) = check_identity(options, identities) {
+        report.failures.push(failure);
+    }
+
+    report
+}
+
+/// Returns the device node the given type of target device is created with
+pub fn device_node(id: &TargetDeviceTypeId) -> Option<&'static str> {
+    if id.requires_uinput() {
+        return Some(uinput::UINPUT_PATH);
+    }
+    match id.as_str() {
+        "ds5" | "ds5-usb" | "ds5-bt" | "ds5-edge" | "ds5-edge-usb" | "ds5-edge-bt"
+        | "hori-steam" => Some(UHID_PATH),
+        _ => None,
+    }
+}
+
+/// Check that the given device node can be opened for reading and writing
+pub fn check_device_node(path: &str) -> Option<PreflightFailure> {
+    match OpenOptions::new().read(true).write(true).open(path) {
+        Ok(_) => None,
+        Err(e) => Some(PreflightFailure::from_io_error(path, &e)),
+    }
+}
+
+/// Check that no running target device uses the unique identifier from the
+/// given options
+pub fn check_identity(
+    options: Option<&TargetDeviceOptions>,
+    identities: &HashMap<String, String>,
+) -> Option<PreflightFailure> {
+    let uniq = options?.uniq.as_ref()?;
+    let (path, _) = identities.iter().find(|(_, other)| *other == uniq)?;
+    Some(PreflightFailure::DuplicateIdentity {
+        uniq: uniq.clone(),
+        path: path.clone(),
+    })
+}
+
+/// Returns the kernel module that provides the given device node
+fn module_name(path: &str) -> Option<&'static str> {
+    match path {
+        uinput::UINPUT_PATH => Some("uinput"),
+        UHID_PATH => Some("uhid"),
+        _ => None,
+    }
+}
@@ -0,0 +1,76 @@
+use std::{collections::HashMap, io};
+
+use crate::{
+    config::TargetDeviceOptions,
+    input::{
+        target::{
+            preflight::{check, check_identity, device_node, PreflightFailure, UHID_PATH},
+            TargetDeviceTypeId,
+        },
+        uinput::UINPUT_PATH,
+    },
+};
+
+fn options(uniq: &str) -> TargetDeviceOptions {
+    let yaml = format!("type: ds5\nuniq: {uniq}\n");
+    serde_yaml::from_str(yaml.as_str()).unwrap()
+}
+
+#[test]
+fn test_unsupported_type() {
+    let report = check("xbox-original", None, &HashMap::new());
+    assert!(!report.is_ok());
+    assert_eq!(
+        report.failures,
+        vec![PreflightFailure::UnsupportedType(
+            "xbox-original".to_string()
+        )]
+    );
+    assert!(report.to_string().contains("[unsupported_type]"));
+}
+
+#[test]
+fn test_device_node() {
+    let node = |kind: &str| device_node(&TargetDeviceTypeId::try_from(kind).unwrap());
+    assert_eq!(node("xb360"), Some(UINPUT_PATH));
+    assert_eq!(node("ds5-edge"), Some(UHID_PATH));
+    assert_eq!(node("dbus"), None);
+}
+
+#[test]
+fn test_failure_from_io_error() {
+    let error = io::Error::from(io::ErrorKind::NotFound);
+    let failure = PreflightFailure::from_io_error(UINPUT_PATH, &error);
+    assert_eq!(failure.reason(), "missing_device_node");
+    assert!(failure.remediation().contains("modprobe uinput"));
+    assert!(failure.is_uinput_unavailable());
+
+    let error = io::Error::from(io::ErrorKind::PermissionDenied);
+    let failure = PreflightFailure::from_io_error(UHID_PATH, &error);
+    assert_eq!(
+        failure,
+        PreflightFailure::PermissionDenied(UHID_PATH.to_string())
+    );
+    assert!(!failure.is_uinput_unavailable());
+
+    let error = io::Error::from(io::ErrorKind::Other);
+    let failure = PreflightFailure::from_io_error(UHID_PATH, &error);
+    assert_eq!(failure.reason(), "device_node_error");
+}
+
+#[test]
+fn test_duplicate_identity() {
+    let path = "/org/shadowblip/InputPlumber/devices/target/gamepad0";
+    let identities = HashMap::from([(path.to_string(), "00:11:22:33:44:55".to_string())]);
+
+    let failure = check_identity(Some(&options("00:11:22:33:44:55")), &identities);
+    assert_eq!(
+        failure,
+        Some(PreflightFailure::DuplicateIdentity {
+            uniq: "00:11:22:33:44:55".to_string(),
+            path: path.to_string(),
+        })
+    );
+    assert!(check_identity(Some(&options("66:77:88:99:aa:bb")), &identities).is_none());
+    assert!(check_identity(None, &identities).is_none());
+}
@@ -0,0 +1,164 @@
+//! Watchdog that captures the state of a target device when events take too
+//! long to travel from source devices to the target device
+use std::{
+    collections::VecDeque,
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+
+use crate::config;
+
+use super::performance::TargetPerformance;
+
+/// Default latency in milliseconds above which events are considered late
+pub const DEFAULT_THRESHOLD_MS: u64 = 50;
+/// Default number of seconds the latency must stay above the threshold
+/// before a snapshot is captured
+pub const DEFAULT_DURATION_SECS: u64 = 5;
+/// Number of recent events included in a snapshot
+pub const RECENT_EVENTS: usize = 32;
+
+/// An event that recently reached the target device
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RecentEvent {
+    pub capability: String,
+    pub value: String,
+    pub latency_us: u64,
+}
+
+/// The [LatencyWatchdog] keeps track of the event latency of a target device
+/// and decides when a state snapshot should be captured.
+#[derive(Debug)]
+pub struct LatencyWatchdog {
+    threshold: Duration,
+    duration: Duration,
+    dump_path: Option<PathBuf>,
+    /// Time the latency first exceeded the threshold
+    high_since: Option<Instant>,
+    /// Whether a snapshot was already captured for the current spike
+    triggered: bool,
+    recent_events: VecDeque<RecentEvent>,
+}
+
+impl LatencyWatchdog {
+    /// Create a new watchdog with the given threshold and duration
+    pub fn new(threshold: Duration, duration: Duration, dump_path: Option<PathBuf>) -> Self {
+        Self {
+            threshold,
+            duration,
+            dump_path,
+            high_since: None,
+            triggered: false,
+            recent_events: VecDeque::with_capacity(RECENT_EVENTS),
+        }
+    }
+
+    /// Create a new watchdog from the given configuration
+    pub fn from_config(config: &config::LatencyWatchdog) -> Self {
+        let threshold = config.threshold_ms.unwrap_or(DEFAULT_THRESHOLD_MS);
+        let duration = config.duration_secs.unwrap_or(DEFAULT_DURATION_SECS);
+        Self::new(
+            Duration::from_millis(threshold),
+            Duration::from_secs(duration),
+            config.dump_path.as_ref().map(PathBuf::from),
+        )
+    }
+
+    /// Directory snapshots should be written to in addition to the journal
+    pub fn dump_path(&self) -> Option<&Path> {
+        self.dump_path.as_deref()
+    }
+
+    /// Returns the most recent events that reached the device, oldest first
+    pub fn recent_events(&self) -> Vec<RecentEvent> {
+        self.recent_events.iter().cloned().collect()
+    }
+
+    /// Record that the given event reached the device with the given latency
+    /// at the given time. Returns true if the latency stayed above the
+    /// threshold for long enough that a snapshot should be captured. Only one
+    /// snapshot is requested until the latency drops below the threshold
+    /// again.
+    pub fn record(&mut self, event: RecentEvent, latency: Duration, now: Instant) -> bool {
+        while self.recent_events.len() >= RECENT_EVENTS {
+            self.recent_events.pop_front();
+        }
+        self.recent_events.push_back(event);
+
+        if latency <= self.threshold {
+            self.high_since = None;
+            self.triggered = false;
+            return false;
+        }
+        let high_since = *self.high_since.get_or_insert(now);
+        if self.triggered || now.duration_since(high_since) < self.duration {
+            return false;
+        }
+        self.triggered = true;
+        true
+    }
+}
+
+/// State of a target device captured by the [LatencyWatchdog]
+#[derive(Debug, Clone, Serialize)]
+pub struct StateSnapshot {
+    /// Time the snapshot was captured in milliseconds since the UNIX epoch
+    pub timestamp_ms: u64,
+    pub device_type: String,
+    pub dbus_path: String,
+    pub event_latency_us: u64,
+    pub event_latency_max_us: u64,
+    pub write_latency_avg_us: u64,
+    pub write_latency_max_us: u64,
+    pub queue_depth: usize,
+    pub queue_depth_max: usize,
+    pub queue_capacity: usize,
+    pub scheduled_events: usize,
+    pub suspended: bool,
+    pub composite_device_attached: bool,
+    pub recent_events: Vec<RecentEvent>,
+}
+
+impl StateSnapshot {
+    /// Create a new snapshot from the performance counters of a target device
+    pub fn new(device_type: &str, dbus_path: &str, performance: &TargetPerformance) -> Self {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_millis() as u64)
+            .unwrap_or_default();
+        Self {
+            timestamp_ms,
+            device_type: device_type.to_string(),
+            dbus_path: dbus_path.to_string(),
+            event_latency_us: performance.event_latency_last_us(),
+            event_latency_max_us: performance.event_latency_max_us(),
+            write_latency_avg_us: performance.write_latency_avg_us(),
+            write_latency_max_us: performance.write_latency_max_us(),
+            queue_depth: performance.queue_depth(),
+            queue_depth_max: performance.queue_depth_max(),
+            queue_capacity: performance.queue_capacity(),
+            scheduled_events: 0,
+            suspended: false,
+            composite_device_attached: false,
+            recent_events: vec![],
+        }
+    }
+
+    /// Returns the snapshot as a JSON string
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Write the snapshot as a JSON file into the given directory and return
+    /// the path of the written file.
+    pub fn write_to(&self, dir: &Path) -> io::Result<PathBuf> {
+        let name = self.dbus_path.rsplit('/').next().unwrap_or("target");
+        let path = dir.join(format!("{name}-{}.json", self.timestamp_ms));
+        fs::create_dir_all(dir)?;
+        fs::write(&path, self.to_json())?;
+        Ok(path)
+    }
+}
@@ -0,0 +1,90 @@
+use std::time::{Duration, Instant};
+
+use super::{
+    performance::TargetPerformance,
+    watchdog::{LatencyWatchdog, RecentEvent, StateSnapshot, RECENT_EVENTS},
+};
+
+fn event(latency_us: u64) -> RecentEvent {
+    RecentEvent {
+        capability: "Gamepad:Button:South".to_string(),
+        value: "Bool(true)".to_string(),
+        latency_us,
+    }
+}
+
+/// Record an event with the given latency in milliseconds at the given
+/// number of seconds after start
+fn record(watchdog: &mut LatencyWatchdog, start: Instant, secs: u64, latency_ms: u64) -> bool {
+    let latency = Duration::from_millis(latency_ms);
+    let now = start + Duration::from_secs(secs);
+    watchdog.record(event(latency.as_micros() as u64), latency, now)
+}
+
+#[test]
+fn test_watchdog_triggers_once_per_spike() {
+    let mut watchdog =
+        LatencyWatchdog::new(Duration::from_millis(50), Duration::from_secs(5), None);
+    let start = Instant::now();
+
+    assert!(!record(&mut watchdog, start, 0, 80));
+    assert!(!record(&mut watchdog, start, 4, 80));
+    assert!(record(&mut watchdog, start, 5, 80));
+    assert!(!record(&mut watchdog, start, 6, 80));
+
+    // The watchdog re-arms once the latency drops below the threshold
+    assert!(!record(&mut watchdog, start, 7, 10));
+    assert!(!record(&mut watchdog, start, 8, 80));
+    assert!(record(&mut watchdog, start, 13, 80));
+}
+
+#[test]
+fn test_watchdog_resets_on_low_latency() {
+    let mut watchdog =
+        LatencyWatchdog::new(Duration::from_millis(50), Duration::from_secs(5), None);
+    let start = Instant::now();
+
+    assert!(!record(&mut watchdog, start, 0, 80));
+    assert!(!record(&mut watchdog, start, 3, 10));
+    assert!(!record(&mut watchdog, start, 5, 80));
+    assert!(!record(&mut watchdog, start, 9, 80));
+    assert!(record(&mut watchdog, start, 10, 80));
+}
+
+#[test]
+fn test_watchdog_recent_events() {
+    let mut watchdog =
+        LatencyWatchdog::new(Duration::from_millis(50), Duration::from_secs(5), None);
+    let start = Instant::now();
+    for latency in 0..RECENT_EVENTS as u64 + 3 {
+        record(&mut watchdog, start, 0, latency);
+    }
+
+    let events = watchdog.recent_events();
+    assert_eq!(events.len(), RECENT_EVENTS);
+    assert_eq!(events[0].latency_us, 3000);
+}
+
+#[test]
+fn test_snapshot_to_json() {
+    let performance = TargetPerformance::new(2048);
+    performance.record_latency(Duration::from_millis(80));
+    performance.record_queue_depth(1500);
+
+    let mut snapshot = StateSnapshot::new(
+        "xb360",
+        "/org/shadowblip/InputPlumber/devices/target/gamepad0",
+        &performance,
+    );
+    snapshot.recent_events = vec![event(80000)];
+    let json: serde_json::Value = serde_json::from_str(&snapshot.to_json()).unwrap();
+
+    assert_eq!(json["device_type"], "xb360");
+    assert_eq!(json["event_latency_us"], 80000);
+    assert_eq!(json["queue_depth"], 1500);
+    assert_eq!(json["queue_capacity"], 2048);
+    assert_eq!(
+        json["recent_events"][0]["capability"],
+        "Gamepad:Button:South"
+    );
+}
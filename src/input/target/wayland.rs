@@ -0,0 +1,434 @@
+//! Backend that emits the input of keyboard and mouse target devices using
+//! the Wayland virtual keyboard (zwp_virtual_keyboard_v1) and virtual pointer
+//! (zwlr_virtual_pointer_v1) protocols instead of uinput. This allows these
+//! target devices to work in environments where uinput cannot be accessed,
+//! such as containers or Flatpak. Input is emitted to the compositor only and
+//! is not visible to other applications reading input devices.
+use std::{
+    error::Error,
+    ffi::CStr,
+    fmt,
+    fs::File,
+    io::{self, Write},
+    os::{
+        fd::{AsFd, AsRawFd, RawFd},
+        unix::net::UnixStream,
+    },
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Instant,
+};
+
+use evdev::{uinput::VirtualDevice, EventType, InputEvent, KeyCode, RelativeAxisCode};
+use nix::sys::memfd::{memfd_create, MemFdCreateFlag};
+use wayland_client::{
+    delegate_noop,
+    globals::{registry_queue_init, GlobalList, GlobalListContents},
+    protocol::{wl_pointer, wl_registry, wl_seat::WlSeat},
+    Connection, Dispatch, EventQueue, QueueHandle,
+};
+use wayland_protocols_misc::zwp_virtual_keyboard_v1::client::{
+    zwp_virtual_keyboard_manager_v1::ZwpVirtualKeyboardManagerV1,
+    zwp_virtual_keyboard_v1::ZwpVirtualKeyboardV1,
+};
+use wayland_protocols_wlr::virtual_pointer::v1::client::{
+    zwlr_virtual_pointer_manager_v1::ZwlrVirtualPointerManagerV1,
+    zwlr_virtual_pointer_v1::ZwlrVirtualPointerV1,
+};
+
+use crate::{config::TargetDeviceOptions, input::keymap};
+
+/// Keymap format of XKB keymaps (wl_keyboard.keymap_format.xkb_v1)
+const KEYMAP_FORMAT_XKB_V1: u32 = 1;
+
+/// Scroll distance of a single mouse wheel detent, matching libinput
+const WHEEL_DETENT_DISTANCE: f64 = 15.0;
+
+/// XKB modifier masks of the modifiers used by the default keymap
+pub const MOD_SHIFT: u32 = 1 << 0;
+pub const MOD_LOCK: u32 = 1 << 1;
+pub const MOD_CONTROL: u32 = 1 << 2;
+pub const MOD_ALT: u32 = 1 << 3;
+pub const MOD_SUPER: u32 = 1 << 6;
+pub const MOD_ALTGR: u32 = 1 << 7;
+
+/// Backend used to emit the input of a target device
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// A virtual input device is created using uinput
+    #[default]
+    Uinput,
+    /// Input is sent to the Wayland compositor using the virtual keyboard and
+    /// virtual pointer protocols
+    Wayland,
+}
+
+impl Backend {
+    /// All available backends
+    pub const ALL: [Backend; 2] = [Backend::Uinput, Backend::Wayland];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Backend::Uinput => "uinput",
+            Backend::Wayland => "wayland",
+        }
+    }
+
+    /// Returns the backend selected in the given target device options
+    pub fn from_options(options: Option<&TargetDeviceOptions>) -> Result<Self, String> {
+        match options.and_then(|options| options.backend.as_deref()) {
+            Some(backend) => backend.parse(),
+            None => Ok(Backend::default()),
+        }
+    }
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "uinput" => Ok(Backend::Uinput),
+            "wayland" => Ok(Backend::Wayland),
+            _ => Err(format!("Invalid target device backend: {s}")),
+        }
+    }
+}
+
+/// Returns the path to the socket of the given Wayland display. Relative
+/// display names (e.g. "wayland-0") are looked up in the given runtime
+/// directory.
+pub fn socket_path(display: &str, runtime_dir: Option<&Path>) -> Option<PathBuf> {
+    let display = Path::new(display);
+    if display.is_absolute() {
+        return Some(display.to_path_buf());
+    }
+    runtime_dir.map(|dir| dir.join(display))
+}
+
+/// Returns an XKB keymap that includes the given keyboard layout
+pub fn xkb_keymap(layout: &str, variant: Option<&str>) -> String {
+    let symbols = match variant {
+        Some(variant) => format!("pc+{layout}({variant})+inet(evdev)"),
+        None => format!("pc+{layout}+inet(evdev)"),
+    };
+    format!(
+        "xkb_keymap {{\n\
+         \txkb_keycodes {{ include \"evdev+aliases(qwerty)\" }};\n\
+         \txkb_types {{ include \"complete\" }};\n\
+         \txkb_compat {{ include \"complete\" }};\n\
+         \txkb_symbols {{ include \"{symbols}\" }};\n\
+         }};\n"
+    )
+}
+
+/// Returns the XKB modifier mask of the given modifier key
+pub fn modifier_mask(key: KeyCode) -> u32 {
+    match key {
+        KeyCode::KEY_LEFTSHIFT | KeyCode::KEY_RIGHTSHIFT => MOD_SHIFT,
+        KeyCode::KEY_LEFTCTRL | KeyCode::KEY_RIGHTCTRL => MOD_CONTROL,
+        KeyCode::KEY_LEFTALT => MOD_ALT,
+        KeyCode::KEY_RIGHTALT => MOD_ALTGR,
+        KeyCode::KEY_LEFTMETA | KeyCode::KEY_RIGHTMETA => MOD_SUPER,
+        _ => 0,
+    }
+}
+
+/// Modifier state of the virtual keyboard. Unlike physical keyboards, the
+/// compositor is told about the active modifiers by the client.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    /// Modifiers of keys that are currently held
+    pub depressed: u32,
+    /// Modifiers that are toggled, e.g. by CapsLock
+    pub locked: u32,
+}
+
+impl Modifiers {
+    /// Update the modifier state with the press or release of the given key.
+    /// Returns true if the modifier state changed.
+    pub fn update(&mut self, key: KeyCode, pressed: bool) -> bool {
+        let old = *self;
+        if key == KeyCode::KEY_CAPSLOCK {
+            if pressed {
+                self.locked ^= MOD_LOCK;
+            }
+            return *self != old;
+        }
+        let mask = modifier_mask(key);
+        if pressed {
+            self.depressed |= mask;
+        } else {
+            self.depressed &= !mask;
+        }
+        *self != old
+    }
+}
+
+/// State of the Wayland event queue. The virtual input objects do not
+/// receive any events.
+#[derive(Debug, Default)]
+struct WaylandState;
+
+impl Dispatch<wl_registry::WlRegistry, GlobalListContents> for WaylandState {
+    fn event(
+        _: &mut Self,
+        _: &wl_registry::WlRegistry,
+        _: wl_registry::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+delegate_noop!(WaylandState: ignore WlSeat);
+delegate_noop!(WaylandState: ZwpVirtualKeyboardManagerV1);
+delegate_noop!(WaylandState: ZwpVirtualKeyboardV1);
+delegate_noop!(WaylandState: ZwlrVirtualPointerManagerV1);
+delegate_noop!(WaylandState: ZwlrVirtualPointerV1);
+
+/// Virtual input object created with the Wayland compositor
+#[derive(Debug)]
+enum VirtualObject {
+    Keyboard {
+        keyboard: ZwpVirtualKeyboardV1,
+        modifiers: Modifiers,
+        /// The keymap is kept open while the virtual keyboard exists
+        _keymap: File,
+    },
+    Pointer(ZwlrVirtualPointerV1),
+}
+
+/// Virtual keyboard or pointer that emits evdev input events through the
+/// Wayland compositor
+pub struct WaylandDevice {
+    connection: Connection,
+    queue: EventQueue<WaylandState>,
+    state: WaylandState,
+    object: VirtualObject,
+    /// Time the device was created. Event timestamps are relative to it.
+    started: Instant,
+}
+
+impl WaylandDevice {
+    /// Create a virtual keyboard that uses the keyboard layout of the system
+    pub fn keyboard(display: Option<&str>) -> Result<Self, Box<dyn Error>> {
+        let (connection, globals, queue) = WaylandDevice::connect(display)?;
+        let qh = queue.handle();
+        let seat: WlSeat = globals.bind(&qh, 1..=1, ())?;
+        let manager: ZwpVirtualKeyboardManagerV1 = globals.bind(&qh, 1..=1, ())?;
+        let keyboard = manager.create_virtual_keyboard(&seat, &qh, ());
+
+        // A keymap must be set before any keys can be sent
+        let (layout, variant) = keymap::active_layout();
+        let content = xkb_keymap(layout.as_str(), variant.as_deref());
+        let (keymap, size) = WaylandDevice::create_keymap_file(content.as_str())?;
+        keyboard.keymap(KEYMAP_FORMAT_XKB_V1, keymap.as_fd(), size);
+        log::debug!("Created Wayland virtual keyboard with layout: {layout}");
+
+        let object = VirtualObject::Keyboard {
+            keyboard,
+            modifiers: Modifiers::default(),
+            _keymap: keymap,
+        };
+        WaylandDevice::new(connection, queue, object)
+    }
+
+    /// Create a virtual pointer
+    pub fn pointer(display: Option<&str>) -> Result<Self, Box<dyn Error>> {
+        let (connection, globals, queue) = WaylandDevice::connect(display)?;
+        let qh = queue.handle();
+        let seat: Option<WlSeat> = globals.bind(&qh, 1..=1, ()).ok();
+        let manager: ZwlrVirtualPointerManagerV1 = globals.bind(&qh, 1..=2, ())?;
+        let pointer = manager.create_virtual_pointer(seat.as_ref(), &qh, ());
+        log::debug!("Created Wayland virtual pointer");
+
+        WaylandDevice::new(connection, queue, VirtualObject::Pointer(pointer))
+    }
+
+    fn new(
+        connection: Connection,
+        mut queue: EventQueue<WaylandState>,
+        object: VirtualObject,
+    ) -> Result<Self, Box<dyn Error>> {
+        // Wait for the compositor to process the requests so protocol errors
+        // are reported when the device is created.
+        let mut state = WaylandState;
+        queue.roundtrip(&mut state)?;
+        Ok(Self {
+            connection,
+            queue,
+            state,
+            object,
+            started: Instant::now(),
+        })
+    }
+
+    /// Connect to the given Wayland display, or the display from the
+    /// environment (WAYLAND_DISPLAY) if none is given.
+    fn connect(
+        display: Option<&str>,
+    ) -> Result<(Connection, GlobalList, EventQueue<WaylandState>), Box<dyn Error>> {
+        let connection = match display {
+            Some(display) => {
+                let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from);
+                let Some(path) = socket_path(display, runtime_dir.as_deref()) else {
+                    return Err(format!(
+                        "Unable to find Wayland display {display}: XDG_RUNTIME_DIR is not set"
+                    )
+                    .into());
+                };
+                log::debug!("Connecting to Wayland display: {path:?}");
+                Connection::from_socket(UnixStream::connect(path)?)?
+            }
+            None => Connection::connect_to_env()?,
+        };
+        let (globals, queue) = registry_queue_init::<WaylandState>(&connection)?;
+        Ok((connection, globals, queue))
+    }
+
+    /// Write the given keymap into an anonymous file that can be shared with
+    /// the compositor. Returns the file and the size of the keymap.
+    fn create_keymap_file(keymap: &str) -> Result<(File, u32), Box<dyn Error>> {
+        let name = CStr::from_bytes_with_nul(b"inputplumber-keymap\0")?;
+        let fd = memfd_create(name, MemFdCreateFlag::MFD_CLOEXEC)?;
+        let mut file = File::from(fd);
+        // The keymap must be terminated by a null byte
+        file.write_all(keymap.as_bytes())?;
+        file.write_all(&[0])?;
+        let size = keymap.len() as u32 + 1;
+        Ok((file, size))
+    }
+
+    /// Emit the given evdev input events. Keyboards handle key events, while
+    /// pointers handle button, relative motion, and wheel events.
+    pub fn emit(&mut self, events: &[InputEvent]) -> Result<(), Box<dyn Error>> {
+        let time = self.started.elapsed().as_millis() as u32;
+        match &mut self.object {
+            VirtualObject::Keyboard {
+                keyboard,
+                modifiers,
+                ..
+            } => {
+                for event in events {
+                    // Key repeat is handled by the compositor
+                    if event.event_type() != EventType::KEY || event.value() > 1 {
+                        continue;
+                    }
+                    let pressed = event.value() == 1;
+                    keyboard.key(time, event.code() as u32, pressed as u32);
+                    if modifiers.update(KeyCode(event.code()), pressed) {
+                        keyboard.modifiers(modifiers.depressed, 0, modifiers.locked, 0);
+                    }
+                }
+            }
+            VirtualObject::Pointer(pointer) => {
+                let mut motion = (0.0, 0.0);
+                let mut changed = false;
+                for event in events {
+                    match event.event_type() {
+                        EventType::KEY => {
+                            let state = if event.value() == 0 {
+                                wl_pointer::ButtonState::Released
+                            } else {
+                                wl_pointer::ButtonState::Pressed
+                            };
+                            pointer.button(time, event.code() as u32, state);
+                            changed = true;
+                        }
+                        EventType::RELATIVE => {
+                            let value = event.value();
+                            let (axis, detents) = match RelativeAxisCode(event.code()) {
+                                RelativeAxisCode::REL_X => {
+                                    motion.0 += value as f64;
+                                    continue;
+                                }
+                                RelativeAxisCode::REL_Y => {
+                                    motion.1 += value as f64;
+                                    continue;
+                                }
+                                // Wayland scrolls down for positive values
+                                RelativeAxisCode::REL_WHEEL => {
+                                    (wl_pointer::Axis::VerticalScroll, -value)
+                                }
+                                RelativeAxisCode::REL_HWHEEL => {
+                                    (wl_pointer::Axis::HorizontalScroll, value)
+                                }
+                                _ => continue,
+                            };
+                            let distance = detents as f64 * WHEEL_DETENT_DISTANCE;
+                            pointer.axis_source(wl_pointer::AxisSource::Wheel);
+                            pointer.axis_discrete(time, axis, distance, detents);
+                            changed = true;
+                        }
+                        _ => (),
+                    }
+                }
+                if motion != (0.0, 0.0) {
+                    pointer.motion(time, motion.0, motion.1);
+                    changed = true;
+                }
+                if changed {
+                    pointer.frame();
+                }
+            }
+        }
+
+        self.queue.dispatch_pending(&mut self.state)?;
+        self.connection.flush()?;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for WaylandDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WaylandDevice")
+            .field("object", &self.object)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Device that the input of a target device is emitted through, depending on
+/// the selected [Backend]
+#[derive(Debug)]
+pub enum VirtualInputDevice {
+    Uinput(VirtualDevice),
+    Wayland(WaylandDevice),
+}
+
+impl VirtualInputDevice {
+    /// Emit the given evdev input events
+    pub fn emit(&mut self, events: &[InputEvent]) -> Result<(), Box<dyn Error>> {
+        match self {
+            VirtualInputDevice::Uinput(device) => device.emit(events)?,
+            VirtualInputDevice::Wayland(device) => device.emit(events)?,
+        }
+        Ok(())
+    }
+
+    /// Returns the sysfs path of the uinput device, if any
+    pub fn get_syspath(&mut self) -> Option<PathBuf> {
+        match self {
+            VirtualInputDevice::Uinput(device) => device.get_syspath().ok(),
+            VirtualInputDevice::Wayland(_) => None,
+        }
+    }
+
+    /// Returns events written to the uinput device by the kernel, such as LED
+    /// events
+    pub fn fetch_events(&mut self) -> io::Result<Vec<InputEvent>> {
+        match self {
+            VirtualInputDevice::Uinput(device) => Ok(device.fetch_events()?.collect()),
+            VirtualInputDevice::Wayland(_) => Ok(vec![]),
+        }
+    }
+
+    /// Returns the file descriptor of the uinput device, if any
+    pub fn as_raw_fd(&self) -> Option<RawFd> {
+        match self {
+            VirtualInputDevice::Uinput(device) => Some(device.as_raw_fd()),
+            VirtualInputDevice::Wayland(_) => None,
+        }
+    }
+}
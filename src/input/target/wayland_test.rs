@@ -0,0 +1,85 @@
+use std::path::{Path, PathBuf};
+
+use evdev::KeyCode;
+
+use crate::{
+    config::TargetDeviceOptions,
+    input::target::{
+        wayland::{
+            socket_path, xkb_keymap, Backend, Modifiers, MOD_ALTGR, MOD_CONTROL, MOD_LOCK,
+            MOD_SHIFT,
+        },
+        TargetDeviceTypeId,
+    },
+};
+
+fn options(kind: &str, backend: &str) -> TargetDeviceOptions {
+    let yaml = format!("type: {kind}\nbackend: {backend}\n");
+    serde_yaml::from_str(yaml.as_str()).unwrap()
+}
+
+#[test]
+fn test_backend_from_str() {
+    for backend in Backend::ALL {
+        assert_eq!(backend.as_str().parse::<Backend>(), Ok(backend));
+    }
+    assert!("x11".parse::<Backend>().is_err());
+    assert_eq!(Backend::from_options(None), Ok(Backend::Uinput));
+    assert_eq!(
+        Backend::from_options(Some(&options("keyboard", "wayland"))),
+        Ok(Backend::Wayland)
+    );
+}
+
+#[test]
+fn test_uses_uinput() {
+    let id = |kind: &str| TargetDeviceTypeId::try_from(kind).unwrap();
+    assert!(id("keyboard").uses_uinput(None));
+    assert!(!id("keyboard").uses_uinput(Some(&options("keyboard", "wayland"))));
+    assert!(!id("mouse").uses_uinput(Some(&options("mouse", "wayland"))));
+    assert!(id("mouse").uses_uinput(Some(&options("mouse", "uinput"))));
+    // Only keyboards and mice support the Wayland backend
+    assert!(id("xb360").uses_uinput(Some(&options("xb360", "wayland"))));
+    assert!(!id("dbus").uses_uinput(None));
+}
+
+#[test]
+fn test_socket_path() {
+    let runtime_dir = Path::new("/run/user/1000");
+    assert_eq!(
+        socket_path("wayland-0", Some(runtime_dir)),
+        Some(PathBuf::from("/run/user/1000/wayland-0"))
+    );
+    assert_eq!(
+        socket_path("/tmp/wayland-1", None),
+        Some(PathBuf::from("/tmp/wayland-1"))
+    );
+    assert_eq!(socket_path("wayland-0", None), None);
+}
+
+#[test]
+fn test_xkb_keymap() {
+    let keymap = xkb_keymap("de", Some("nodeadkeys"));
+    assert!(keymap.contains("include \"pc+de(nodeadkeys)+inet(evdev)\""));
+    assert!(keymap.contains("include \"evdev+aliases(qwerty)\""));
+    assert!(xkb_keymap("us", None).contains("include \"pc+us+inet(evdev)\""));
+}
+
+#[test]
+fn test_modifiers() {
+    let mut modifiers = Modifiers::default();
+    assert!(modifiers.update(KeyCode::KEY_LEFTSHIFT, true));
+    assert!(modifiers.update(KeyCode::KEY_RIGHTCTRL, true));
+    assert_eq!(modifiers.depressed, MOD_SHIFT | MOD_CONTROL);
+    assert!(!modifiers.update(KeyCode::KEY_A, true));
+    assert!(modifiers.update(KeyCode::KEY_LEFTSHIFT, false));
+    assert!(modifiers.update(KeyCode::KEY_RIGHTALT, true));
+    assert_eq!(modifiers.depressed, MOD_CONTROL | MOD_ALTGR);
+
+    // CapsLock toggles on press and ignores release
+    assert!(modifiers.update(KeyCode::KEY_CAPSLOCK, true));
+    assert!(!modifiers.update(KeyCode::KEY_CAPSLOCK, false));
+    assert_eq!(modifiers.locked, MOD_LOCK);
+    assert!(modifiers.update(KeyCode::KEY_CAPSLOCK, true));
+    assert_eq!(modifiers.locked, 0);
+}
@@ -0,0 +1,120 @@
+//! Recovery of the uinput subsystem used to create most target devices. At
+//! boot the `uinput` kernel module may not be loaded yet, or permissions on
+//! `/dev/uinput` may not have been applied. Recovery runs a hook to load the
+//! module and retries with exponential backoff until the device is usable.
+//! The hook and maximum backoff are set with the `uinput_hook` and
+//! `uinput_max_backoff` options of the global config.
+use std::{fs::OpenOptions, time::Duration};
+
+use tokio::process::Command;
+
+use crate::config::GlobalConfig;
+
+/// Path to the uinput device node
+pub const UINPUT_PATH: &str = "/dev/uinput";
+
+/// Command used to load the uinput module if no hook is configured
+const DEFAULT_HOOK: &str = "modprobe uinput";
+
+/// Delay before the first retry
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Default maximum delay between retries
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Options for recovering uinput
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveryOptions {
+    /// Program and arguments to run to make uinput available
+    pub hook: Option<Vec<String>>,
+    /// Maximum delay between recovery attempts
+    pub max_backoff: Duration,
+}
+
+impl RecoveryOptions {
+    /// Read the recovery options from the given global config. Invalid
+    /// values are logged and ignored.
+    pub fn from_config(config: &GlobalConfig) -> Self {
+        let hook = parse_hook(config.uinput_hook.as_deref().unwrap_or(DEFAULT_HOOK));
+        let max_backoff = match config.uinput_max_backoff {
+            Some(0) => {
+                log::warn!("Invalid uinput_max_backoff '0' in global config");
+                DEFAULT_MAX_BACKOFF
+            }
+            Some(secs) => Duration::from_secs(secs),
+            None => DEFAULT_MAX_BACKOFF,
+        };
+
+        Self { hook, max_backoff }
+    }
+}
+
+/// Parse the given hook command into a program and its arguments. Returns
+/// `None` if the command is empty.
+pub fn parse_hook(value: &str) -> Option<Vec<String>> {
+    let args: Vec<String> = value.split_whitespace().map(String::from).collect();
+    if args.is_empty() {
+        return None;
+    }
+    Some(args)
+}
+
+/// Returns the delay to wait before the given retry attempt (starting at 0).
+/// The delay doubles with each attempt up to the given maximum.
+pub fn backoff_delay(attempt: u32, max: Duration) -> Duration {
+    let factor = 1u32.checked_shl(attempt).unwrap_or(u32::MAX);
+    INITIAL_BACKOFF.saturating_mul(factor).min(max)
+}
+
+/// Returns true if the uinput device can be opened to create virtual devices
+pub fn is_available() -> bool {
+    match OpenOptions::new().read(true).write(true).open(UINPUT_PATH) {
+        Ok(_) => true,
+        Err(e) => {
+            log::trace!("Unable to open {UINPUT_PATH}: {e:?}");
+            false
+        }
+    }
+}
+
+/// Run the configured hook to try and make uinput available
+pub async fn run_hook(options: &RecoveryOptions) {
+    let Some(hook) = options.hook.as_ref() else {
+        return;
+    };
+    let Some((program, args)) = hook.split_first() else {
+        return;
+    };
+
+    log::debug!("Running uinput hook: {}", hook.join(" "));
+    match Command::new(program).args(args).output().await {
+        Ok(output) if output.status.success() => (),
+        Ok(output) => log::warn!(
+            "uinput hook exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(e) => log::warn!("Failed to run uinput hook: {e:?}"),
+    }
+}
+
+/// Wait until uinput becomes available, running the configured hook and
+/// backing off exponentially between attempts.
+pub async fn wait_until_available(options: &RecoveryOptions) {
+    let mut attempt = 0;
+    loop {
+        run_hook(options).await;
+        if is_available() {
+            log::info!(
+                "{UINPUT_PATH} is available after {} attempt(s)",
+                attempt + 1
+            );
+            return;
+        }
+
+        let delay = backoff_delay(attempt, options.max_backoff);
+        log::warn!("{UINPUT_PATH} is unavailable, retrying in {delay:?}");
+        tokio::time::sleep(delay).await;
+        attempt = attempt.saturating_add(1);
+    }
+}
@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+use crate::{
+    config::GlobalConfig,
+    input::uinput::{backoff_delay, parse_hook, RecoveryOptions},
+};
+
+#[test]
+fn test_backoff_delay() {
+    let max = Duration::from_secs(60);
+    assert_eq!(backoff_delay(0, max), Duration::from_secs(1));
+    assert_eq!(backoff_delay(1, max), Duration::from_secs(2));
+    assert_eq!(backoff_delay(5, max), Duration::from_secs(32));
+    assert_eq!(backoff_delay(6, max), max);
+    assert_eq!(backoff_delay(64, max), max);
+}
+
+#[test]
+fn test_parse_hook() {
+    assert_eq!(
+        parse_hook("modprobe uinput"),
+        Some(vec!["modprobe".to_string(), "uinput".to_string()])
+    );
+    assert_eq!(
+        parse_hook("  /usr/bin/true "),
+        Some(vec!["/usr/bin/true".to_string()])
+    );
+    assert_eq!(parse_hook(""), None);
+    assert_eq!(parse_hook("   "), None);
+}
+
+#[test]
+fn test_recovery_options_from_config() {
+    // Defaults are used if nothing is configured
+    let options = RecoveryOptions::from_config(&GlobalConfig::default());
+    assert_eq!(
+        options.hook,
+        Some(vec!["modprobe".to_string(), "uinput".to_string()])
+    );
+    assert_eq!(options.max_backoff, Duration::from_secs(60));
+
+    // Options are read from the global config file format
+    let config: GlobalConfig = serde_yaml::from_str(
+        "version: 1\nkind: GlobalConfig\nuinput_hook: /usr/local/bin/load-uinput --quiet\nuinput_max_backoff: 10\n",
+    )
+    .expect("valid global config");
+    let options = RecoveryOptions::from_config(&config);
+    assert_eq!(
+        options.hook,
+        Some(vec![
+            "/usr/local/bin/load-uinput".to_string(),
+            "--quiet".to_string()
+        ])
+    );
+    assert_eq!(options.max_backoff, Duration::from_secs(10));
+
+    // An empty hook disables it and an invalid backoff uses the default
+    let config = GlobalConfig {
+        uinput_hook: Some("".to_string()),
+        uinput_max_backoff: Some(0),
+        ..Default::default()
+    };
+    let options = RecoveryOptions::from_config(&config);
+    assert_eq!(options.hook, None);
+    assert_eq!(options.max_backoff, Duration::from_secs(60));
+}
@@ -6,5 +6,6 @@ pub mod dmi;
 pub mod drivers;
 pub mod iio;
 pub mod input;
+pub mod logging;
 pub mod udev;
 pub mod watcher;
@@ -0,0 +1,402 @@
+//! Logging setup with support for changing log levels at runtime. Options are
+//! read from the environment on startup:
+//!
+//! - `LOG_LEVEL`: comma separated list of log directives in the form of
+//!   `level` or `module=level` (default: "info"). E.g.
+//!   "info,input::composite_device=trace". Modules may omit the crate name.
+//! - `LOG_FORMAT`: "json" to write each log record as a JSON object for
+//!   journald ingestion, "journal" to send records with structured fields
+//!   directly to the systemd journal, or "text" for human readable output.
+//!   Defaults to "journal" when running as a systemd service with a journal
+//!   available, and "text" otherwise.
+//!
+//! Structured fields attached to log records (e.g. `device_id`, `dbus_path`,
+//! `config_name`) are written as journal fields, so events can be filtered
+//! with e.g. `journalctl -u inputplumber DEVICE_ID=evdev://event3`.
+//!
+//! The log directives can be changed at runtime using the `SetLogLevel` method
+//! on the Manager DBus interface.
+use std::{
+    env, fmt,
+    io::Write,
+    os::unix::net::UnixDatagram,
+    path::Path,
+    str::FromStr,
+    sync::{OnceLock, RwLock},
+};
+
+use log::{
+    kv::{Key, Value, VisitSource},
+    Level, LevelFilter, Log, Metadata, Record,
+};
+
+/// Name of this crate used as the prefix of all module paths
+const CRATE_NAME: &str = "inputplumber";
+
+/// Top-level modules of this crate. Directives for these modules do not need
+/// to include the crate name.
+const MODULES: &[&str] = &[
+    "bluetooth",
+    "config",
+    "constants",
+    "dbus",
+    "dmi",
+    "drivers",
+    "iio",
+    "input",
+    "logging",
+    "udev",
+    "watcher",
+];
+
+/// Path to the native protocol socket of the systemd journal
+const JOURNAL_SOCKET: &str = "/run/systemd/journal/socket";
+
+/// The global logger instance
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+/// Output format of log records
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+    Journal,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" | "" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            "journal" => Ok(LogFormat::Journal),
+            _ => Err(format!("Invalid log format: {s}")),
+        }
+    }
+}
+
+/// Log level filter made up of a default level and per-module levels
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFilter {
+    default: LevelFilter,
+    directives: Vec<(String, LevelFilter)>,
+}
+
+impl LogFilter {
+    /// Returns the log level for the given log target (module path). The most
+    /// specific matching module directive takes precedence.
+    pub fn level_for(&self, target: &str) -> LevelFilter {
+        self.directives
+            .iter()
+            .filter(|(module, _)| {
+                target == module
+                    || target
+                        .strip_prefix(module.as_str())
+                        .is_some_and(|rest| rest.starts_with("::"))
+            })
+            .max_by_key(|(module, _)| module.len())
+            .map(|(_, level)| *level)
+            .unwrap_or(self.default)
+    }
+
+    /// Returns the most verbose level enabled by this filter
+    pub fn max_level(&self) -> LevelFilter {
+        self.directives
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, |max, level| max.max(level))
+    }
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self {
+            default: LevelFilter::Info,
+            directives: Vec::new(),
+        }
+    }
+}
+
+impl FromStr for LogFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut filter = LogFilter::default();
+        for directive in s.split(',').map(str::trim) {
+            if directive.is_empty() {
+                continue;
+            }
+            let Some((module, level)) = directive.split_once('=') else {
+                // A directive without a module can either be a level or a
+                // module that should log everything.
+                match LevelFilter::from_str(directive) {
+                    Ok(level) => filter.default = level,
+                    Err(_) => filter
+                        .directives
+                        .push((normalize_module(directive), LevelFilter::Trace)),
+                }
+                continue;
+            };
+            let module = module.trim();
+            if module.is_empty() {
+                return Err(format!("Missing module in log directive: {directive}"));
+            }
+            let Ok(level) = LevelFilter::from_str(level.trim()) else {
+                return Err(format!("Invalid log level in log directive: {directive}"));
+            };
+            filter.directives.push((normalize_module(module), level));
+        }
+
+        Ok(filter)
+    }
+}
+
+impl fmt::Display for LogFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let default = self.default.as_str().to_lowercase();
+        let directives = self
+            .directives
+            .iter()
+            .map(|(module, level)| format!("{module}={}", level.as_str().to_lowercase()));
+        let parts: Vec<String> = std::iter::once(default).chain(directives).collect();
+        write!(f, "{}", parts.join(","))
+    }
+}
+
+/// Prefix the given module path with the crate name if it refers to one of
+/// the modules in this crate.
+pub fn normalize_module(module: &str) -> String {
+    let top = module.split("::").next().unwrap_or_default();
+    if MODULES.contains(&top) {
+        return format!("{CRATE_NAME}::{module}");
+    }
+    module.to_string()
+}
+
+/// Returns the structured key-value fields attached to the given record
+pub fn record_fields(record: &Record) -> Vec<(String, String)> {
+    struct Collect(Vec<(String, String)>);
+    impl<'kvs> VisitSource<'kvs> for Collect {
+        fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), log::kv::Error> {
+            self.0.push((key.to_string(), value.to_string()));
+            Ok(())
+        }
+    }
+
+    let mut fields = Collect(Vec::new());
+    if let Err(e) = record.key_values().visit(&mut fields) {
+        eprintln!("Failed to read log record fields: {e:?}");
+    }
+    fields.0
+}
+
+/// Converts the given field name into a valid journal field name. Journal
+/// field names may only contain uppercase letters, digits, and underscores
+/// and must not start with an underscore or digit.
+pub fn journal_field_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' => c.to_ascii_uppercase(),
+            _ => '_',
+        })
+        .collect();
+    name.trim_start_matches(|c: char| c == '_' || c.is_ascii_digit())
+        .to_string()
+}
+
+/// Returns the syslog priority of the given log level
+fn journal_priority(level: Level) -> u8 {
+    match level {
+        Level::Error => 3,
+        Level::Warn => 4,
+        Level::Info => 6,
+        Level::Debug | Level::Trace => 7,
+    }
+}
+
+/// Serialize the given record into a journal native protocol entry
+pub fn journal_entry(record: &Record) -> Vec<u8> {
+    let mut entry = Vec::new();
+    let mut add_field = |name: &str, value: &str| {
+        // Values containing newlines must be length prefixed
+        if value.contains('\n') {
+            entry.extend_from_slice(name.as_bytes());
+            entry.push(b'\n');
+            entry.extend_from_slice(&(value.len() as u64).to_le_bytes());
+            entry.extend_from_slice(value.as_bytes());
+            entry.push(b'\n');
+        } else {
+            entry.extend_from_slice(format!("{name}={value}\n").as_bytes());
+        }
+    };
+
+    add_field("MESSAGE", record.args().to_string().as_str());
+    add_field(
+        "PRIORITY",
+        journal_priority(record.level()).to_string().as_str(),
+    );
+    add_field("SYSLOG_IDENTIFIER", CRATE_NAME);
+    add_field("TARGET", record.target());
+    if let Some(file) = record.file() {
+        add_field("CODE_FILE", file);
+    }
+    if let Some(line) = record.line() {
+        add_field("CODE_LINE", line.to_string().as_str());
+    }
+    if let Some(module) = record.module_path() {
+        add_field("CODE_MODULE", module);
+    }
+    for (key, value) in record_fields(record) {
+        let name = journal_field_name(key.as_str());
+        if name.is_empty() {
+            continue;
+        }
+        add_field(name.as_str(), value.as_str());
+    }
+
+    entry
+}
+
+/// Returns true if InputPlumber is running as a systemd service and can send
+/// log records directly to the journal.
+fn journal_available() -> bool {
+    env::var_os("JOURNAL_STREAM").is_some() && Path::new(JOURNAL_SOCKET).exists()
+}
+
+/// Logger that filters records using a [LogFilter] that can be changed at
+/// runtime.
+struct Logger {
+    filter: RwLock<LogFilter>,
+    inner: env_logger::Logger,
+    /// Socket used to send records to the journal if the journal format is
+    /// used
+    journal: Option<UnixDatagram>,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        let Ok(filter) = self.filter.read() else {
+            return false;
+        };
+        metadata.level() <= filter.level_for(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        // Fall back to the inner logger if the record could not be sent to
+        // the journal.
+        if let Some(journal) = self.journal.as_ref() {
+            let entry = journal_entry(record);
+            if journal.send_to(entry.as_slice(), JOURNAL_SOCKET).is_ok() {
+                return;
+            }
+        }
+        self.inner.log(record);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Initialize the global logger using options from the environment
+pub fn init() {
+    let filter = match env::var("LOG_LEVEL") {
+        Ok(value) => LogFilter::from_str(value.as_str()).unwrap_or_else(|e| {
+            eprintln!("Invalid LOG_LEVEL '{value}': {e}");
+            LogFilter::default()
+        }),
+        Err(_) => LogFilter::default(),
+    };
+    let default_format = if journal_available() {
+        LogFormat::Journal
+    } else {
+        LogFormat::Text
+    };
+    let format = match env::var("LOG_FORMAT") {
+        Ok(value) => LogFormat::from_str(value.as_str()).unwrap_or_else(|e| {
+            eprintln!("Invalid LOG_FORMAT '{value}': {e}");
+            default_format
+        }),
+        Err(_) => default_format,
+    };
+    let journal = match format {
+        LogFormat::Journal => match UnixDatagram::unbound() {
+            Ok(socket) => Some(socket),
+            Err(e) => {
+                eprintln!("Unable to open journal socket: {e:?}");
+                None
+            }
+        },
+        _ => None,
+    };
+
+    // Filtering is done by the wrapping logger, so the inner logger should
+    // format every record it receives.
+    let mut builder = env_logger::Builder::new();
+    builder.filter_level(LevelFilter::Trace);
+    if format == LogFormat::Json {
+        builder.format(|buf, record| {
+            let mut entry = serde_json::json!({
+                "timestamp": buf.timestamp_micros().to_string(),
+                "level": record.level().as_str(),
+                "target": record.target(),
+                "file": record.file(),
+                "line": record.line(),
+                "message": record.args().to_string(),
+            });
+            for (key, value) in record_fields(record) {
+                entry[key] = serde_json::Value::String(value);
+            }
+            writeln!(buf, "{entry}")
+        });
+    }
+
+    let max_level = filter.max_level();
+    let logger = LOGGER.get_or_init(|| Logger {
+        filter: RwLock::new(filter),
+        inner: builder.build(),
+        journal,
+    });
+    if let Err(e) = log::set_logger(logger) {
+        eprintln!("Failed to set logger: {e:?}");
+        return;
+    }
+    log::set_max_level(max_level);
+}
+
+/// Returns the current log filter directives
+pub fn get_filter() -> String {
+    let Some(logger) = LOGGER.get() else {
+        return String::default();
+    };
+    let Ok(filter) = logger.filter.read() else {
+        return String::default();
+    };
+    filter.to_string()
+}
+
+/// Replace the current log filter with the given directives. E.g.
+/// "info,input::composite_device=trace"
+pub fn set_filter(directives: &str) -> Result<(), String> {
+    let filter = LogFilter::from_str(directives)?;
+    let Some(logger) = LOGGER.get() else {
+        return Err("Logger is not initialized".to_string());
+    };
+    let directives = filter.to_string();
+    let max_level = filter.max_level();
+    {
+        let Ok(mut current) = logger.filter.write() else {
+            return Err("Failed to lock log filter".to_string());
+        };
+        *current = filter;
+    }
+    log::set_max_level(max_level);
+    log::info!("Set log level to: {directives}");
+
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use std::str::FromStr;
+
+use log::{Level, LevelFilter, Record};
+
+use crate::logging::{
+    journal_entry, journal_field_name, normalize_module, record_fields, LogFilter, LogFormat,
+};
+
+#[test]
+fn test_normalize_module() {
+    assert_eq!(
+        normalize_module("input::composite_device"),
+        "inputplumber::input::composite_device"
+    );
+    assert_eq!(normalize_module("dbus"), "inputplumber::dbus");
+    assert_eq!(
+        normalize_module("inputplumber::input"),
+        "inputplumber::input"
+    );
+    assert_eq!(normalize_module("zbus::connection"), "zbus::connection");
+}
+
+#[test]
+fn test_parse_log_filter() {
+    let filter = LogFilter::from_str("debug").unwrap();
+    assert_eq!(filter.level_for("inputplumber::input"), LevelFilter::Debug);
+    assert_eq!(filter.max_level(), LevelFilter::Debug);
+
+    let filter = LogFilter::from_str("warn,input::composite_device=trace,zbus=off").unwrap();
+    assert_eq!(filter.level_for("inputplumber::config"), LevelFilter::Warn);
+    assert_eq!(
+        filter.level_for("inputplumber::input::composite_device"),
+        LevelFilter::Trace
+    );
+    assert_eq!(
+        filter.level_for("inputplumber::input::composite_device::client"),
+        LevelFilter::Trace
+    );
+    assert_eq!(
+        filter.level_for("inputplumber::input::composite_devices"),
+        LevelFilter::Warn
+    );
+    assert_eq!(filter.level_for("zbus::connection"), LevelFilter::Off);
+    assert_eq!(filter.max_level(), LevelFilter::Trace);
+    assert_eq!(
+        filter.to_string(),
+        "warn,inputplumber::input::composite_device=trace,zbus=off"
+    );
+
+    assert!(LogFilter::from_str("input=loud").is_err());
+    assert!(LogFilter::from_str("=debug").is_err());
+}
+
+#[test]
+fn test_log_filter_most_specific() {
+    let filter = LogFilter::from_str("input::source=debug,input=error").unwrap();
+    assert_eq!(
+        filter.level_for("inputplumber::input::source::evdev"),
+        LevelFilter::Debug
+    );
+    assert_eq!(
+        filter.level_for("inputplumber::input::manager"),
+        LevelFilter::Error
+    );
+    assert_eq!(filter.level_for("inputplumber::dbus"), LevelFilter::Info);
+}
+
+#[test]
+fn test_parse_log_format() {
+    assert_eq!(LogFormat::from_str("json").unwrap(), LogFormat::Json);
+    assert_eq!(LogFormat::from_str("Text").unwrap(), LogFormat::Text);
+    assert_eq!(LogFormat::from_str("journal").unwrap(), LogFormat::Journal);
+    assert!(LogFormat::from_str("xml").is_err());
+}
+
+#[test]
+fn test_journal_field_name() {
+    assert_eq!(journal_field_name("device_id"), "DEVICE_ID");
+    assert_eq!(journal_field_name("dbus.path"), "DBUS_PATH");
+    assert_eq!(journal_field_name("_private"), "PRIVATE");
+    assert_eq!(journal_field_name("1st"), "ST");
+}
+
+#[test]
+fn test_journal_entry() {
+    let fields: &[(&str, &str)] = &[
+        ("device_id", "evdev://event3"),
+        ("config_name", "Steam Deck"),
+    ];
+    let check = |record: &Record| {
+        assert_eq!(
+            record_fields(record),
+            vec![
+                ("device_id".to_string(), "evdev://event3".to_string()),
+                ("config_name".to_string(), "Steam Deck".to_string()),
+            ]
+        );
+
+        let entry = String::from_utf8(journal_entry(record)).unwrap();
+        let lines: Vec<&str> = entry.lines().collect();
+        assert!(lines.contains(&"MESSAGE=Device added: event3"));
+        assert!(lines.contains(&"PRIORITY=6"));
+        assert!(lines.contains(&"TARGET=inputplumber::input::manager"));
+        assert!(lines.contains(&"DEVICE_ID=evdev://event3"));
+        assert!(lines.contains(&"CONFIG_NAME=Steam Deck"));
+    };
+    check(
+        &Record::builder()
+            .args(format_args!("Device added: {}", "event3"))
+            .level(Level::Info)
+            .target("inputplumber::input::manager")
+            .key_values(&fields)
+            .build(),
+    );
+}
+
+#[test]
+fn test_journal_entry_multiline() {
+    let entry = journal_entry(
+        &Record::builder()
+            .args(format_args!("first\nsecond"))
+            .level(Level::Error)
+            .build(),
+    );
+
+    // Multiline values are length prefixed
+    let mut expected = b"MESSAGE\n".to_vec();
+    expected.extend_from_slice(&12u64.to_le_bytes());
+    expected.extend_from_slice(b"first\nsecond\nPRIORITY=3\n");
+    assert!(entry.starts_with(expected.as_slice()));
+}
@@ -1,12 +1,36 @@
 use std::env;
 use std::error::Error;
+use std::fs;
+use std::io;
+use std::io::BufRead;
 use std::process;
+use std::str::FromStr;
+use std::time::Duration;
+use std::time::Instant;
+use tokio::signal::unix::{signal, SignalKind};
 use zbus::fdo::ObjectManager;
 use zbus::Connection;
 
+use crate::config::hid_capture;
+use crate::config::hid_capture::HidCapture;
+use crate::config::lint::lint_device_configs;
+use crate::config::CapabilityMap;
+use crate::config::GlobalConfig;
 use crate::constants::BUS_NAME;
 use crate::constants::BUS_PREFIX;
+use crate::dbus::client::CompositeDeviceProxy;
+use crate::dbus::client::InputManagerProxy;
+use crate::dmi::get_cpu_info;
+use crate::dmi::get_dmi_data;
+use crate::input::capability::Capability;
+use crate::input::composite_device::simulator::CapabilityMapSimulator;
+use crate::input::composite_device::simulator::SimulatedEvent;
+use crate::input::composite_device::simulator::SimulatedInput;
+use crate::input::composite_device::InterceptMode;
 use crate::input::manager::Manager;
+use crate::udev::authorize;
+use crate::udev::permissions;
+use crate::udev::seat::unassign_all;
 use crate::udev::unhide_all;
 
 mod bluetooth;
@@ -17,20 +41,30 @@ mod dmi;
 mod drivers;
 mod iio;
 mod input;
+mod logging;
 mod udev;
 mod watcher;
 
+#[cfg(test)]
+mod logging_test;
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    let log_level = match env::var("LOG_LEVEL") {
-        Ok(value) => value,
-        Err(_) => "info".to_string(),
-    };
-    env::set_var("RUST_LOG", log_level);
-    env_logger::init();
+    logging::init();
+
+    // Run the given command instead of starting the service if one was passed
+    let args: Vec<String> = env::args().skip(1).collect();
+    if !args.is_empty() {
+        return run_command(args).await;
+    }
+
     const VERSION: &str = env!("CARGO_PKG_VERSION");
     log::info!("Starting InputPlumber v{}", VERSION);
 
+    // Load the service-wide configuration
+    let global_config = GlobalConfig::load();
+    permissions::set_permissions(global_config.target_permissions.unwrap_or_default());
+
     // Configure the DBus connection
     let connection = Connection::system().await?;
 
@@ -46,13 +80,10 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let mut input_manager = Manager::new(connection.clone());
 
     let (ctrl_c_result, input_man_result, request_name_result) = tokio::join!(
-        // Setup CTRL+C handler
+        // Clean up on CTRL+C or SIGTERM
         tokio::spawn(async move {
-            tokio::signal::ctrl_c().await.unwrap();
-            log::info!("Un-hiding all devices");
-            if let Err(e) = unhide_all().await {
-                log::error!("Unable to un-hide devices: {:?}", e);
-            }
+            wait_for_shutdown_signal().await;
+            cleanup().await;
             log::info!("Shutting down");
             process::exit(0);
         }),
@@ -96,3 +127,234 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+/// Run the given command line arguments as a one-off command
+async fn run_command(args: Vec<String>) -> Result<(), Box<dyn Error>> {
+    let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+    match args.as_slice() {
+        ["devices", "lint"] => {
+            let dmi_data = get_dmi_data();
+            let cpu_info = get_cpu_info()?;
+            let overlaps = lint_device_configs(&dmi_data, &cpu_info);
+            if overlaps.is_empty() {
+                println!("No overlapping device configs found");
+                return Ok(());
+            }
+            for overlap in overlaps.iter() {
+                println!("{overlap}");
+            }
+            Err(format!("Found {} overlapping device config(s)", overlaps.len()).into())
+        }
+        ["capability-maps", "test", path] => test_capability_map(path, None),
+        ["capability-maps", "test", path, fixture] => test_capability_map(path, Some(fixture)),
+        ["capability-maps", "from-capture", path] => capability_map_from_capture(path, None),
+        ["capability-maps", "from-capture", path, output] => {
+            capability_map_from_capture(path, Some(output))
+        }
+        ["device", id, command @ ..] => run_device_command(id, command).await,
+        ["cleanup"] => {
+            cleanup().await;
+            Ok(())
+        }
+        _ => Err(format!("Unknown command: {}", args.join(" ")).into()),
+    }
+}
+
+/// Wait until the service is asked to stop with CTRL+C or SIGTERM
+async fn wait_for_shutdown_signal() {
+    let mut terminate = match signal(SignalKind::terminate()) {
+        Ok(terminate) => terminate,
+        Err(e) => {
+            log::error!("Unable to listen for SIGTERM: {e:?}");
+            tokio::signal::ctrl_c().await.unwrap();
+            return;
+        }
+    };
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => result.unwrap(),
+        _ = terminate.recv() => (),
+    }
+}
+
+/// Remove all udev rules installed by InputPlumber and authorize any USB
+/// devices that were left de-authorized. Run with "inputplumber cleanup"
+/// after the service stopped to clean up after a crash.
+async fn cleanup() {
+    log::info!("Removing USB authorization rules");
+    if let Err(e) = authorize::cleanup().await {
+        log::error!("Unable to remove USB authorization rules: {:?}", e);
+    }
+    log::info!("Removing seat assignment rules");
+    if let Err(e) = unassign_all().await {
+        log::error!("Unable to remove seat assignment rules: {:?}", e);
+    }
+    log::info!("Removing target device permissions rules");
+    if let Err(e) = permissions::remove_all().await {
+        log::error!("Unable to remove target device permissions rules: {:?}", e);
+    }
+    log::info!("Un-hiding all devices");
+    if let Err(e) = unhide_all().await {
+        log::error!("Unable to un-hide devices: {:?}", e);
+    }
+}
+
+/// Simulate the given capability map file by reading synthetic source inputs
+/// from the given fixture file or stdin and printing the emitted events.
+/// Each line is in the form "[<time_ms>] <press|release> <capability>" or
+/// "[<time_ms>] intercept <mode>". Lines without a time use the time elapsed
+/// since the simulation started.
+/// E.g. "inputplumber capability-maps test ./my_map.yaml"
+fn test_capability_map(path: &str, fixture: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let map = CapabilityMap::from_yaml_file(path.to_string())?;
+    println!("Loaded capability map: {} ({})", map.name, map.id);
+    let mut simulator = CapabilityMapSimulator::new(map);
+
+    let lines: Box<dyn Iterator<Item = io::Result<String>>> = match fixture {
+        Some(fixture) => Box::new(io::BufReader::new(fs::File::open(fixture)?).lines()),
+        None => {
+            println!("Enter inputs, e.g. 'press Gamepad:Button:South'. Press CTRL+D to exit.");
+            Box::new(io::stdin().lock().lines())
+        }
+    };
+
+    let start = Instant::now();
+    for line in lines {
+        let line = line?;
+        let (time, input) = match SimulatedInput::parse(line.as_str()) {
+            Ok(Some(input)) => input,
+            Ok(None) => continue,
+            Err(e) => {
+                eprintln!("{e}");
+                continue;
+            }
+        };
+        let time = time.unwrap_or_else(|| start.elapsed());
+        simulator
+            .process(time, input)
+            .iter()
+            .for_each(print_simulated_event);
+    }
+
+    // Emit any pending partial chord inputs
+    simulator
+        .advance(simulator.time() + Duration::from_secs(60))
+        .iter()
+        .for_each(print_simulated_event);
+
+    Ok(())
+}
+
+/// Build a capability map skeleton from the given hid-recorder capture. The
+/// user is prompted for the capability of each field that changed in the
+/// capture, and the skeleton is written to the given output file or stdout.
+/// E.g. "inputplumber capability-maps from-capture ./gamepad.hid ./map.yaml"
+fn capability_map_from_capture(path: &str, output: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let capture = HidCapture::from_file(path)?;
+    let fields = capture.fields();
+    eprintln!(
+        "Loaded capture of {} ({:04x}:{:04x}) with {} reports",
+        capture.name,
+        capture.vendor_id,
+        capture.product_id,
+        capture.reports.len()
+    );
+    eprintln!("Found {} changing field(s).", fields.len());
+    eprintln!("Enter the capability each field should emit (e.g. 'Gamepad:Button:South'), or leave empty to skip.");
+
+    let mut lines = io::stdin().lock().lines();
+    let mut labeled = Vec::new();
+    'fields: for (i, field) in fields.into_iter().enumerate() {
+        loop {
+            eprint!("[{}] {field}: {}\n> ", i + 1, field.describe());
+            let Some(line) = lines.next() else {
+                break 'fields;
+            };
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() {
+                break;
+            }
+            let Ok(capability) = Capability::from_str(line) else {
+                eprintln!("Invalid capability: {line}");
+                continue;
+            };
+            if hid_capture::capability_yaml(&capability).is_none() {
+                eprintln!("{capability} cannot be used in a capability map");
+                continue;
+            }
+            labeled.push((field, capability));
+            break;
+        }
+    }
+
+    let id = hid_capture::capability_map_id(capture.name.as_str());
+    let skeleton = hid_capture::capability_map_skeleton(&capture, id.as_str(), &labeled)?;
+    match output {
+        Some(output) => {
+            fs::write(output, skeleton)?;
+            eprintln!("Wrote capability map skeleton to {output}");
+        }
+        None => print!("{skeleton}"),
+    }
+
+    Ok(())
+}
+
+/// Print the given event emitted by the capability map simulator
+fn print_simulated_event(event: &SimulatedEvent) {
+    let state = if event.pressed { "pressed" } else { "released" };
+    let kind = if event.translated {
+        "translated"
+    } else {
+        "passthrough"
+    };
+    println!(
+        "{:>8}ms {} {state} ({kind})",
+        event.time.as_millis(),
+        event.capability
+    );
+}
+
+/// Run the given command on the composite device with the given stable
+/// identifier (UUID) or DBus path using the running InputPlumber service.
+/// E.g. "inputplumber device 3f2b8e1c-5d0a-8c4e-9b71-0a6d2e4f8c13 info"
+async fn run_device_command(id: &str, args: &[&str]) -> Result<(), Box<dyn Error>> {
+    let conn = Connection::system().await?;
+    let manager = InputManagerProxy::new(&conn).await?;
+    let path = manager.get_composite_device(id).await?;
+    let device = CompositeDeviceProxy::builder(&conn)
+        .path(path.clone())?
+        .build()
+        .await?;
+
+    match args {
+        [] | ["info"] => {
+            let mode = InterceptMode::try_from(device.intercept_mode().await?)?;
+            println!("Path: {path}");
+            println!("UUID: {}", device.uuid().await?);
+            println!("Name: {}", device.name().await?);
+            println!("Profile: {}", device.profile_name().await?);
+            println!("Intercept mode: {}", mode.as_str());
+        }
+        ["profile", "load", profile_path] => {
+            device.load_profile_path(profile_path).await?;
+        }
+        ["intercept", mode] => {
+            let Some(mode) = InterceptMode::ALL
+                .iter()
+                .find(|m| m.as_str().eq_ignore_ascii_case(mode))
+            else {
+                return Err(format!("Invalid intercept mode: {mode}").into());
+            };
+            device.set_intercept_mode(u32::from(*mode)).await?;
+        }
+        ["stop"] => {
+            device.stop().await?;
+        }
+        _ => {
+            return Err(format!("Unknown device command: {}", args.join(" ")).into());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+//! Detection of sound cards that belong to input devices, such as the speaker
+//! and headset jack of a DualSense controller connected over USB. Sound cards
+//! are matched to input devices by sharing the same parent USB device.
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Path to the sound devices in sysfs
+const SOUND_PATH: &str = "/sys/class/sound";
+
+/// An ALSA sound card
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SoundCard {
+    /// Number of the card (e.g. 1 for "hw:1")
+    pub number: u32,
+    /// ALSA identifier of the card (e.g. "Controller")
+    pub id: String,
+}
+
+/// Returns the sound cards that are part of the same USB device as the device
+/// with the given sysfs path (e.g. "/sys/devices/.../hidraw/hidraw0")
+pub fn find_sound_cards(syspath: &str) -> Result<Vec<SoundCard>, Box<dyn Error>> {
+    let syspath = fs::canonicalize(syspath)?;
+    let mut cards = Vec::new();
+    for entry in fs::read_dir(SOUND_PATH)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(number) = card_number(&name) else {
+            continue;
+        };
+        let Ok(device) = fs::canonicalize(entry.path().join("device")) else {
+            continue;
+        };
+        if !shares_usb_device(&device, &syspath) {
+            continue;
+        }
+        let id = fs::read_to_string(entry.path().join("id"))
+            .map(|id| id.trim().to_string())
+            .unwrap_or_default();
+        cards.push(SoundCard { number, id });
+    }
+    cards.sort_by_key(|card| card.number);
+    Ok(cards)
+}
+
+/// Returns the card number of the given sound device name (e.g. 1 for
+/// "card1"). Returns [None] if the name is not a sound card.
+pub fn card_number(name: &str) -> Option<u32> {
+    name.strip_prefix("card")?.parse().ok()
+}
+
+/// Returns true if the given sound card device, which is a USB interface (e.g.
+/// ".../usb1/1-2/1-2:1.0"), belongs to the same USB device as the device with
+/// the given sysfs path
+pub fn shares_usb_device(card_device: &Path, syspath: &Path) -> bool {
+    let Some(usb_device) = usb_device_path(card_device) else {
+        return false;
+    };
+    syspath.starts_with(&usb_device) && syspath != usb_device
+}
+
+/// Returns the path of the USB device of the given USB interface path
+fn usb_device_path(interface: &Path) -> Option<PathBuf> {
+    // USB interfaces are named "<bus>-<port>:<config>.<interface>"
+    let name = interface.file_name()?.to_str()?;
+    let (device_name, interface_name) = name.split_once(':')?;
+    if !interface_name.contains('.') || !device_name.contains('-') {
+        return None;
+    }
+    interface.parent().map(Path::to_path_buf)
+}
@@ -0,0 +1,34 @@
+use std::path::Path;
+
+use crate::udev::audio::{card_number, shares_usb_device};
+
+#[test]
+fn test_card_number() {
+    assert_eq!(card_number("card0"), Some(0));
+    assert_eq!(card_number("card12"), Some(12));
+    assert_eq!(card_number("controlC0"), None);
+    assert_eq!(card_number("pcmC0D0p"), None);
+    assert_eq!(card_number("card"), None);
+}
+
+#[test]
+fn test_shares_usb_device() {
+    let card = Path::new("/sys/devices/pci0000:00/0000:00:14.0/usb1/1-2/1-2:1.0");
+    let hidraw = Path::new(
+        "/sys/devices/pci0000:00/0000:00:14.0/usb1/1-2/1-2:1.3/0003:054C:0CE6.0001/hidraw/hidraw0",
+    );
+    assert!(shares_usb_device(card, hidraw));
+
+    // Devices on another USB port
+    let other = Path::new(
+        "/sys/devices/pci0000:00/0000:00:14.0/usb1/1-3/1-3:1.3/0003:054C:0CE6.0002/hidraw/hidraw1",
+    );
+    assert!(!shares_usb_device(card, other));
+    let other = Path::new("/sys/devices/pci0000:00/0000:00:14.0/usb1/1-22/1-22:1.0");
+    assert!(!shares_usb_device(card, other));
+
+    // Sound cards that are not USB devices
+    let card = Path::new("/sys/devices/pci0000:00/0000:00:1f.3");
+    let hidraw = Path::new("/sys/devices/pci0000:00/0000:00:1f.3/hidraw/hidraw0");
+    assert!(!shares_usb_device(card, hidraw));
+}
@@ -0,0 +1,237 @@
+//! Deferred USB device authorization. Input events from a newly plugged in
+//! device can reach other processes before InputPlumber hides and grabs it.
+//! To prevent this, an early udev rule de-authorizes matching USB devices when
+//! they are added. InputPlumber then installs a rule that hides all input
+//! devices stemming from the USB device and only authorizes it afterwards, so
+//! its input devices are never readable by regular users.
+//!
+//! The de-authorization rule only applies while the [RUNNING_MARKER] file
+//! exists. It lives in the runtime directory of the service, which systemd
+//! removes whenever the service stops, so devices are never left
+//! de-authorized if InputPlumber is killed or crashes.
+use std::{
+    error::Error,
+    fs, io,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    process,
+};
+
+use tokio::process::Command;
+
+use super::RULES_PREFIX;
+
+/// Udev property set on USB devices whose authorization was deferred
+pub const DEFERRED_PROPERTY: &str = "INPUTPLUMBER_DEFERRED";
+
+/// File that exists while InputPlumber is running
+pub const RUNNING_MARKER: &str = "/run/inputplumber/running";
+
+/// Prefix of the rules that hide the input devices of USB devices
+const HIDE_RULE_PREFIX: &str = "96-inputplumber-hide-usb-";
+
+/// Paths of the files used to defer USB device authorization
+#[derive(Debug, Clone)]
+pub struct AuthorizationPaths {
+    /// Directory the udev rules are written to
+    pub rules_dir: PathBuf,
+    /// File that exists while InputPlumber is running
+    pub marker: PathBuf,
+}
+
+impl Default for AuthorizationPaths {
+    fn default() -> Self {
+        Self {
+            rules_dir: PathBuf::from(RULES_PREFIX),
+            marker: PathBuf::from(RUNNING_MARKER),
+        }
+    }
+}
+
+impl AuthorizationPaths {
+    /// Path to the rule that de-authorizes managed USB devices
+    pub fn authorization_rule(&self) -> PathBuf {
+        self.rules_dir.join("40-inputplumber-usb-authorize.rules")
+    }
+
+    /// Path to the rule that hides the input devices of the given USB device
+    pub fn hide_rule(&self, sysname: &str) -> PathBuf {
+        self.rules_dir
+            .join(format!("{HIDE_RULE_PREFIX}{sysname}.rules"))
+    }
+
+    /// Create the running marker and write the rule to de-authorize USB
+    /// devices with the given vendor and product ids. If no ids are given,
+    /// any existing rule is removed instead. Returns true if the rules
+    /// changed.
+    pub fn install(&self, ids: &[(u16, u16)]) -> io::Result<bool> {
+        if ids.is_empty() {
+            return self.remove();
+        }
+        if let Some(parent) = self.marker.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.marker, process::id().to_string())?;
+        fs::create_dir_all(&self.rules_dir)?;
+        let marker = self.marker.to_string_lossy();
+        fs::write(self.authorization_rule(), authorization_rule(ids, &marker))?;
+
+        Ok(true)
+    }
+
+    /// Remove the rule that de-authorizes USB devices and the running
+    /// marker. Returns true if the rule existed.
+    pub fn remove(&self) -> io::Result<bool> {
+        remove_if_exists(&self.marker)?;
+        remove_if_exists(&self.authorization_rule())
+    }
+
+    /// Write the rule that hides all input devices of the given USB device
+    pub fn hide(&self, sysname: &str) -> io::Result<()> {
+        fs::create_dir_all(&self.rules_dir)?;
+        fs::write(self.hide_rule(sysname), hide_rule(sysname))
+    }
+
+    /// Remove the hide rule of the given USB device. Returns true if the rule
+    /// existed.
+    pub fn unhide(&self, sysname: &str) -> io::Result<bool> {
+        remove_if_exists(&self.hide_rule(sysname))
+    }
+
+    /// Remove the authorization rule, the running marker and the hide rules
+    /// of all USB devices. Returns true if any rule existed.
+    pub fn remove_all(&self) -> io::Result<bool> {
+        let mut removed = self.remove()?;
+        let entries = match fs::read_dir(&self.rules_dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(removed),
+            Err(e) => return Err(e),
+        };
+        for entry in entries {
+            let entry = entry?;
+            let filename = entry.file_name().to_string_lossy().to_string();
+            if filename.starts_with(HIDE_RULE_PREFIX) {
+                removed |= remove_if_exists(&entry.path())?;
+            }
+        }
+
+        Ok(removed)
+    }
+}
+
+/// Remove the given file. Returns false if it did not exist.
+fn remove_if_exists(path: &Path) -> io::Result<bool> {
+    match fs::remove_file(path) {
+        Ok(_) => Ok(true),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Returns a udev rule that de-authorizes USB devices with the given vendor
+/// and product ids when they are added, as long as the given marker file
+/// exists.
+pub fn authorization_rule(ids: &[(u16, u16)], marker: &str) -> String {
+    let mut rule = String::from(
+        "# De-authorizes USB devices until they are claimed by InputPlumber\n\
+         # Managed by InputPlumber, this file will be autoremoved when InputPlumber stops.\n",
+    );
+    for (vid, pid) in ids {
+        rule.push_str(&format!(
+            r#"ACTION=="add", SUBSYSTEM=="usb", ENV{{DEVTYPE}}=="usb_device", TEST=="{marker}", ATTR{{idVendor}}=="{vid:04x}", ATTR{{idProduct}}=="{pid:04x}", ATTR{{authorized}}="0", ENV{{{DEFERRED_PROPERTY}}}="1""#
+        ));
+        rule.push('\n');
+    }
+    rule
+}
+
+/// Returns a udev rule that hides all input devices stemming from the USB
+/// device with the given sysname (e.g. "1-2").
+pub fn hide_rule(sysname: &str) -> String {
+    format!(
+        r#"# Hides input devices stemming from USB device {sysname}
+# Managed by InputPlumber, this file will be autoremoved when the device is removed.
+KERNELS=="{sysname}", SUBSYSTEMS=="usb", KERNEL=="hidraw[0-9]*|js[0-9]*|event[0-9]*", MODE="000", GROUP="root", TAG-="uaccess"
+"#
+    )
+}
+
+/// Install the rule to de-authorize USB devices with the given vendor and
+/// product ids. If no ids are given, any existing rule is removed.
+pub async fn install_authorization_rule(ids: &[(u16, u16)]) -> Result<(), Box<dyn Error>> {
+    if AuthorizationPaths::default().install(ids)? {
+        reload_rules().await?;
+    }
+
+    Ok(())
+}
+
+/// Hide all input devices of the given de-authorized USB device, then
+/// authorize it.
+pub async fn authorize_usb_device(sysname: &str, syspath: &str) -> Result<(), Box<dyn Error>> {
+    AuthorizationPaths::default().hide(sysname)?;
+    reload_rules().await?;
+    set_authorized(syspath)?;
+
+    Ok(())
+}
+
+/// Remove the hide rule for the given USB device after it was removed
+pub async fn remove_usb_device(sysname: &str) -> Result<(), Box<dyn Error>> {
+    if AuthorizationPaths::default().unhide(sysname)? {
+        reload_rules().await?;
+    }
+
+    Ok(())
+}
+
+/// Remove the authorization rule and the hide rules of all USB devices, then
+/// authorize any USB devices that were left de-authorized. This is run when
+/// InputPlumber stops, including from the service's 'ExecStopPost' after a
+/// crash.
+pub async fn cleanup() -> Result<(), Box<dyn Error>> {
+    if AuthorizationPaths::default().remove_all()? {
+        reload_rules().await?;
+    }
+    for device in super::discover_devices("usb")? {
+        if !is_deferred(&device) {
+            continue;
+        }
+        let sysname = device.sysname().to_string_lossy().to_string();
+        log::info!("Authorizing deferred USB device: {sysname}");
+        if let Err(e) = set_authorized(&device.syspath().to_string_lossy()) {
+            log::error!("Failed to authorize USB device {sysname}: {e:?}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns true if the given USB device was de-authorized by the USB
+/// authorization udev rule and is waiting to be authorized
+pub fn is_deferred(device: &::udev::Device) -> bool {
+    let deferred = device
+        .property_value(DEFERRED_PROPERTY)
+        .map(|value| value == "1")
+        .unwrap_or(false);
+    let authorized = device
+        .attribute_value("authorized")
+        .map(|value| value == "1")
+        .unwrap_or(true);
+    deferred && !authorized
+}
+
+/// Authorize the USB device with the given sysfs path
+pub fn set_authorized(syspath: &str) -> io::Result<()> {
+    fs::write(format!("{syspath}/authorized"), "1")
+}
+
+/// Reload udev rules without triggering any devices
+async fn reload_rules() -> Result<(), Box<dyn Error>> {
+    let _ = Command::new("udevadm")
+        .args(["control", "--reload-rules"])
+        .output()
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use std::{env, fs, path::PathBuf};
+
+use crate::udev::authorize::{authorization_rule, hide_rule, set_authorized, AuthorizationPaths};
+
+/// Returns authorization paths in a fresh temporary directory
+fn temp_paths(name: &str) -> (PathBuf, AuthorizationPaths) {
+    let root = env::temp_dir().join(format!("inputplumber-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    let paths = AuthorizationPaths {
+        rules_dir: root.join("rules.d"),
+        marker: root.join("run").join("running"),
+    };
+    (root, paths)
+}
+
+#[test]
+fn test_authorization_rule() {
+    let rule = authorization_rule(&[(0x045e, 0x028e), (0x17ef, 0x6182)], "/run/ip/running");
+    assert!(rule.contains(
+        r#"ATTR{idVendor}=="045e", ATTR{idProduct}=="028e", ATTR{authorized}="0", ENV{INPUTPLUMBER_DEFERRED}="1""#
+    ));
+    assert!(rule.contains(r#"ATTR{idVendor}=="17ef", ATTR{idProduct}=="6182""#));
+    assert_eq!(
+        rule.lines()
+            .filter(|line| line.starts_with("ACTION"))
+            .count(),
+        2
+    );
+
+    // Devices are only de-authorized while InputPlumber is running
+    assert!(rule
+        .lines()
+        .filter(|line| line.starts_with("ACTION"))
+        .all(|line| line.contains(r#"TEST=="/run/ip/running""#)));
+}
+
+#[test]
+fn test_hide_rule() {
+    let rule = hide_rule("1-2");
+    assert!(rule.contains(r#"KERNELS=="1-2", SUBSYSTEMS=="usb""#));
+    assert!(rule.contains(r#"MODE="000""#));
+}
+
+#[test]
+fn test_authorization_flow() {
+    let (root, paths) = temp_paths("authorize-flow");
+
+    // Startup installs the rule and marks InputPlumber as running
+    assert!(paths.install(&[(0x045e, 0x028e)]).unwrap());
+    assert!(paths.marker.exists());
+    let rule = fs::read_to_string(paths.authorization_rule()).unwrap();
+    assert!(rule.contains(&format!(r#"TEST=="{}""#, paths.marker.display())));
+
+    // A deferred device is hidden, then authorized
+    let syspath = root.join("devices").join("1-2");
+    fs::create_dir_all(&syspath).unwrap();
+    fs::write(syspath.join("authorized"), "0").unwrap();
+    paths.hide("1-2").unwrap();
+    set_authorized(&syspath.to_string_lossy()).unwrap();
+    assert!(paths.hide_rule("1-2").exists());
+    assert_eq!(fs::read_to_string(syspath.join("authorized")).unwrap(), "1");
+
+    // Removing the device removes its hide rule
+    assert!(paths.unhide("1-2").unwrap());
+    assert!(!paths.unhide("1-2").unwrap());
+    assert!(!paths.hide_rule("1-2").exists());
+
+    // Installing without any managed devices removes the rule and marker
+    assert!(paths.install(&[]).unwrap());
+    assert!(!paths.authorization_rule().exists());
+    assert!(!paths.marker.exists());
+    assert!(!paths.install(&[]).unwrap());
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_authorization_cleanup() {
+    let (root, paths) = temp_paths("authorize-cleanup");
+
+    // Simulate a crash that left the rules and marker behind
+    paths.install(&[(0x045e, 0x028e)]).unwrap();
+    paths.hide("1-2").unwrap();
+    paths.hide("3-1.4").unwrap();
+    let other_rule = paths.rules_dir.join("96-inputplumber-hide-event5.rules");
+    fs::write(&other_rule, "").unwrap();
+
+    // Cleanup removes everything related to USB authorization
+    assert!(paths.remove_all().unwrap());
+    assert!(!paths.marker.exists());
+    assert!(!paths.authorization_rule().exists());
+    assert!(!paths.hide_rule("1-2").exists());
+    assert!(!paths.hide_rule("3-1.4").exists());
+    assert!(other_rule.exists());
+    assert!(!paths.remove_all().unwrap());
+
+    fs::remove_dir_all(root).unwrap();
+}
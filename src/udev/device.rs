@@ -384,6 +384,27 @@ impl UdevDevice {
         }
     }
 
+    /// Returns a UdevDevice object for a source device provided by an external
+    /// driver process. External devices have no devnode or syspath.
+    /// e.g. UdevDevice::from_external("external0", "My Gamepad", None, None);
+    pub fn from_external(
+        sysname: &str,
+        name: &str,
+        vendor_id: Option<u16>,
+        product_id: Option<u16>,
+    ) -> Self {
+        Self {
+            devnode: "".to_string(),
+            subsystem: "external".to_string(),
+            syspath: "".to_string(),
+            sysname: sysname.to_string(),
+            name: Some(name.to_string()),
+            vendor_id: Some(vendor_id.unwrap_or_default()),
+            product_id: Some(product_id.unwrap_or_default()),
+            bus_type: Some(0),
+        }
+    }
+
     /// Returns a udev::Device from the stored syspath.
     pub fn get_device(&self) -> Result<::udev::Device, Box<dyn Error + Send + Sync>> {
         match ::udev::Device::from_syspath(Path::new(self.syspath.as_str())) {
@@ -403,6 +424,34 @@ impl UdevDevice {
         self.devnode.clone()
     }
 
+    /// Returns the syspath of the HID device this device was created from, if
+    /// any. Input devices created by the same kernel HID driver share the same
+    /// HID parent device.
+    pub fn hid_parent_syspath(&self) -> Option<String> {
+        let device = self.get_device().ok()?;
+        let parent = device.parent_with_subsystem("hid").ok()??;
+        Some(parent.syspath().to_string_lossy().to_string())
+    }
+
+    /// Returns true if this device is a legacy joystick node. E.g. "/dev/input/js0"
+    pub fn is_joydev(&self) -> bool {
+        self.subsystem == "input" && self.sysname.starts_with("js")
+    }
+
+    /// Returns true if the input device this device node belongs to also has
+    /// an event node. E.g. "js0" and "event5" created for the same joystick.
+    pub fn has_event_sibling(&self) -> bool {
+        let Some(parent) = Path::new(self.syspath.as_str()).parent() else {
+            return false;
+        };
+        let Ok(entries) = fs::read_dir(parent) else {
+            return false;
+        };
+        entries
+            .flatten()
+            .any(|entry| entry.file_name().to_string_lossy().starts_with("event"))
+    }
+
     pub fn devpath(&self) -> String {
         let Ok(device) = self.get_device() else {
             return "".to_string();
@@ -538,7 +587,7 @@ impl UdevDevice {
     }
 
     /// Return a unique identifier for the device based on the subsystem and
-    /// sysname. E.g. "evdev://event3", "hidraw://hidraw0"
+    /// sysname. E.g. "evdev://event3", "hidraw://hidraw0", "external://external0"
     pub fn get_id(&self) -> String {
         match self.subsystem().as_str() {
             "input" => {
@@ -550,6 +599,9 @@ impl UdevDevice {
             "iio" => {
                 format!("iio://{}", self.sysname)
             }
+            "external" => {
+                format!("external://{}", self.sysname)
+            }
             _ => "".to_string(),
         }
     }
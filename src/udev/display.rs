@@ -0,0 +1,65 @@
+//! Detection of the size of connected displays from the DRM connectors in
+//! sysfs.
+use std::{error::Error, fs};
+
+/// Path to the DRM connectors in sysfs
+pub const DRM_PATH: &str = "/sys/class/drm";
+
+/// Screen size in pixels to use if no connected display can be found
+pub const DEFAULT_SCREEN_SIZE: (u32, u32) = (1920, 1080);
+
+/// Returns the width and height in pixels of the preferred mode of the first
+/// connected display, if any.
+pub fn screen_size() -> Option<(u32, u32)> {
+    match connected_screen_size() {
+        Ok(size) => size,
+        Err(e) => {
+            log::warn!("Failed to read DRM connectors: {e:?}");
+            None
+        }
+    }
+}
+
+/// Returns the preferred mode of the first connected DRM connector, ordered
+/// by connector name
+fn connected_screen_size() -> Result<Option<(u32, u32)>, Box<dyn Error>> {
+    let mut connectors = Vec::new();
+    for entry in fs::read_dir(DRM_PATH)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with("card") && name.contains('-') {
+            connectors.push((name, entry.path()));
+        }
+    }
+    connectors.sort();
+
+    for (_, path) in connectors {
+        let Ok(status) = fs::read_to_string(path.join("status")) else {
+            continue;
+        };
+        if status.trim() != "connected" {
+            continue;
+        }
+        let Ok(modes) = fs::read_to_string(path.join("modes")) else {
+            continue;
+        };
+        // The preferred mode is listed first
+        if let Some(size) = modes.lines().next().and_then(parse_mode) {
+            return Ok(Some(size));
+        }
+    }
+    Ok(None)
+}
+
+/// Parse a DRM connector mode (e.g. "1920x1080" or "1920x1080i") into its
+/// width and height in pixels
+pub fn parse_mode(mode: &str) -> Option<(u32, u32)> {
+    let (width, height) = mode.trim().split_once('x')?;
+    let height = height.trim_end_matches(|c: char| !c.is_ascii_digit());
+    let width = width.parse().ok()?;
+    let height = height.parse().ok()?;
+    if width == 0 || height == 0 {
+        return None;
+    }
+    Some((width, height))
+}
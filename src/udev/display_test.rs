@@ -0,0 +1,11 @@
+use crate::udev::display::parse_mode;
+
+#[test]
+fn test_parse_mode() {
+    assert_eq!(parse_mode("1920x1080"), Some((1920, 1080)));
+    assert_eq!(parse_mode("1280x800\n"), Some((1280, 800)));
+    assert_eq!(parse_mode("1920x1080i"), Some((1920, 1080)));
+    assert_eq!(parse_mode("0x1080"), None);
+    assert_eq!(parse_mode("1920"), None);
+    assert_eq!(parse_mode(""), None);
+}
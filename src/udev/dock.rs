@@ -0,0 +1,89 @@
+//! Detection of docks. A device is considered docked if any of the USB
+//! devices of a dock (e.g. its USB hub) are connected, or if an external
+//! display is connected over DisplayPort, which is how USB-C docks using
+//! DisplayPort alt-mode appear.
+use std::{error::Error, fs};
+
+use crate::config::{DockDetection, DockUsbDevice};
+
+use super::display::DRM_PATH;
+
+/// Returns true if the device is docked based on the given dock detection
+/// config
+pub fn is_docked(config: &DockDetection) -> bool {
+    if let Some(usb_devices) = config.usb.as_ref() {
+        match has_usb_device(usb_devices) {
+            Ok(true) => return true,
+            Ok(false) => (),
+            Err(e) => log::warn!("Failed to discover dock USB devices: {e:?}"),
+        }
+    }
+    if config.displayport.unwrap_or(false) {
+        match has_displayport_connection() {
+            Ok(true) => return true,
+            Ok(false) => (),
+            Err(e) => log::warn!("Failed to read DisplayPort connectors: {e:?}"),
+        }
+    }
+    false
+}
+
+/// Returns true if any of the given USB devices are connected
+fn has_usb_device(usb_devices: &[DockUsbDevice]) -> Result<bool, Box<dyn Error>> {
+    let devices = super::discover_devices("usb")?;
+    let found = devices.iter().any(|device| {
+        let vendor_id = device.attribute_value("idVendor");
+        let product_id = device.attribute_value("idProduct");
+        let (Some(vendor_id), Some(product_id)) = (vendor_id, product_id) else {
+            return false;
+        };
+        usb_id_matches(
+            usb_devices,
+            &vendor_id.to_string_lossy(),
+            &product_id.to_string_lossy(),
+        )
+    });
+    Ok(found)
+}
+
+/// Returns true if the given hexadecimal USB vendor and product id from
+/// sysfs (e.g. "28de") matches any of the given USB devices
+pub fn usb_id_matches(usb_devices: &[DockUsbDevice], vendor_id: &str, product_id: &str) -> bool {
+    let vendor_id = u16::from_str_radix(vendor_id.trim(), 16);
+    let product_id = u16::from_str_radix(product_id.trim(), 16);
+    let (Ok(vendor_id), Ok(product_id)) = (vendor_id, product_id) else {
+        return false;
+    };
+    usb_devices
+        .iter()
+        .any(|device| device.vendor_id == vendor_id && device.product_id == product_id)
+}
+
+/// Returns true if an external display is connected to any DisplayPort
+/// connector
+fn has_displayport_connection() -> Result<bool, Box<dyn Error>> {
+    for entry in fs::read_dir(DRM_PATH)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !is_displayport_connector(&name) {
+            continue;
+        }
+        let Ok(status) = fs::read_to_string(entry.path().join("status")) else {
+            continue;
+        };
+        if status.trim() == "connected" {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Returns true if the given DRM connector name (e.g. "card0-DP-1") is an
+/// external DisplayPort connector. Embedded DisplayPort panels (e.g.
+/// "card0-eDP-1") are not external displays.
+pub fn is_displayport_connector(name: &str) -> bool {
+    let Some((card, connector)) = name.split_once('-') else {
+        return false;
+    };
+    card.starts_with("card") && connector.starts_with("DP-")
+}
@@ -0,0 +1,26 @@
+use crate::{
+    config::DockUsbDevice,
+    udev::dock::{is_displayport_connector, usb_id_matches},
+};
+
+#[test]
+fn test_usb_id_matches() {
+    let devices = vec![DockUsbDevice {
+        vendor_id: 0x28de,
+        product_id: 0x2001,
+    }];
+    assert!(usb_id_matches(&devices, "28de", "2001"));
+    assert!(usb_id_matches(&devices, "28DE", "2001\n"));
+    assert!(!usb_id_matches(&devices, "28de", "2002"));
+    assert!(!usb_id_matches(&devices, "invalid", "2001"));
+}
+
+#[test]
+fn test_is_displayport_connector() {
+    assert!(is_displayport_connector("card0-DP-1"));
+    assert!(is_displayport_connector("card1-DP-3"));
+    assert!(!is_displayport_connector("card0-eDP-1"));
+    assert!(!is_displayport_connector("card0-HDMI-A-1"));
+    assert!(!is_displayport_connector("card0"));
+    assert!(!is_displayport_connector("renderD128"));
+}
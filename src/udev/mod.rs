@@ -1,10 +1,27 @@
 //! Based on the pattern developed by the hhd project:
 //! https://github.com/hhd-dev/hhd/blob/master/src/hhd/controller/lib/hide.py
 
+pub mod audio;
 #[cfg(test)]
-pub mod device_test;
-
+pub mod audio_test;
+pub mod authorize;
+#[cfg(test)]
+pub mod authorize_test;
 pub mod device;
+#[cfg(test)]
+pub mod device_test;
+pub mod display;
+#[cfg(test)]
+pub mod display_test;
+pub mod dock;
+#[cfg(test)]
+pub mod dock_test;
+pub mod permissions;
+#[cfg(test)]
+pub mod permissions_test;
+pub mod seat;
+#[cfg(test)]
+pub mod seat_test;
 
 use std::{error::Error, fs, path::Path};
 
@@ -0,0 +1,254 @@
+//! Permission tagging of target devices. On some distributions, virtual input
+//! devices created by InputPlumber only get default permissions and cannot be
+//! read by the gaming session. Udev tags (e.g. 'uaccess'), properties (e.g.
+//! ID_INPUT_JOYSTICK), seat assignments, and permissions can be configured in
+//! the global config for each type of target device. They are applied with a
+//! udev rule when the target device is created, after which the device is
+//! checked to verify that udev applied them.
+use std::{collections::HashMap, error::Error, fs, io::ErrorKind, sync::RwLock};
+
+use tokio::process::Command;
+
+use crate::config::TargetPermissions;
+
+use super::{device::AttributeGetter, seat, RULES_PREFIX};
+
+/// Prefix of the rule files used to apply permissions to target devices. The
+/// rules are ordered after the seat rules so they take precedence.
+const PERMISSIONS_RULE_PREFIX: &str = "73-inputplumber-permissions-";
+
+/// Maximum time in seconds to wait for udev to process the device after the
+/// rule is applied
+const SETTLE_TIMEOUT_SECS: u32 = 5;
+
+/// Permissions from the global config applied to target devices
+static PERMISSIONS: RwLock<Vec<TargetPermissions>> = RwLock::new(Vec::new());
+
+/// Set the permissions to apply to target devices that are created
+pub fn set_permissions(permissions: Vec<TargetPermissions>) {
+    let Ok(mut current) = PERMISSIONS.write() else {
+        log::error!("Failed to lock target device permissions");
+        return;
+    };
+    *current = permissions;
+}
+
+/// Path to the permissions rule for the input device with the given sysname
+fn permissions_rule_path(sysname: &str) -> String {
+    format!("{RULES_PREFIX}/{PERMISSIONS_RULE_PREFIX}{sysname}.rules")
+}
+
+/// Combine all permissions that apply to the given type of target device.
+/// Tags and properties are merged, while later entries override the seat,
+/// group, mode, and other settings of earlier entries. Returns None if no
+/// permissions apply.
+pub fn resolve(permissions: &[TargetPermissions], kind: &str) -> Option<TargetPermissions> {
+    let mut resolved: Option<TargetPermissions> = None;
+    for entry in permissions {
+        if let Some(types) = entry.target_types.as_ref() {
+            if !types.iter().any(|t| t == kind) {
+                continue;
+            }
+        }
+        let combined = resolved.get_or_insert_with(TargetPermissions::default);
+        if entry.uaccess.is_some() {
+            combined.uaccess = entry.uaccess;
+        }
+        if entry.seat.is_some() {
+            combined.seat = entry.seat.clone();
+        }
+        if entry.group.is_some() {
+            combined.group = entry.group.clone();
+        }
+        if entry.mode.is_some() {
+            combined.mode = entry.mode.clone();
+        }
+        if entry.verify.is_some() {
+            combined.verify = entry.verify;
+        }
+        if let Some(tags) = entry.tags.as_ref() {
+            let combined_tags = combined.tags.get_or_insert_with(Vec::new);
+            for tag in tags {
+                if !combined_tags.contains(tag) {
+                    combined_tags.push(tag.clone());
+                }
+            }
+        }
+        if let Some(properties) = entry.properties.as_ref() {
+            let combined_properties = combined.properties.get_or_insert_with(HashMap::new);
+            combined_properties.extend(properties.clone());
+        }
+    }
+
+    resolved
+}
+
+/// Returns the udev tags that are added with the given permissions
+fn tags(permissions: &TargetPermissions) -> Vec<String> {
+    let mut tags = Vec::new();
+    if permissions.seat.is_some() {
+        tags.push("seat".to_string());
+    }
+    if permissions.uaccess.unwrap_or(false) {
+        tags.push("uaccess".to_string());
+    }
+    for tag in permissions.tags.iter().flatten() {
+        if !tags.contains(tag) {
+            tags.push(tag.clone());
+        }
+    }
+    tags
+}
+
+/// Returns a udev rule that applies the given permissions to the input device
+/// with the given sysname (e.g. "input42") and all of its device nodes.
+pub fn permissions_rule(sysname: &str, permissions: &TargetPermissions) -> String {
+    let mut assignments = Vec::new();
+    for tag in tags(permissions) {
+        assignments.push(format!(r#"TAG+="{tag}""#));
+    }
+    if let Some(seat) = permissions.seat.as_ref() {
+        assignments.push(format!(r#"ENV{{ID_SEAT}}="{seat}""#));
+    }
+    if let Some(properties) = permissions.properties.as_ref() {
+        let mut properties: Vec<_> = properties.iter().collect();
+        properties.sort();
+        for (name, value) in properties {
+            assignments.push(format!(r#"ENV{{{name}}}="{value}""#));
+        }
+    }
+    if let Some(group) = permissions.group.as_ref() {
+        assignments.push(format!(r#"GROUP="{group}""#));
+    }
+    if let Some(mode) = permissions.mode.as_ref() {
+        assignments.push(format!(r#"MODE="{mode}""#));
+    }
+    let assignments = assignments.join(", ");
+
+    format!(
+        r#"# Applies permissions to InputPlumber target device {sysname}
+# Managed by InputPlumber, this file will be autoremoved when the device is removed.
+KERNELS=="{sysname}", SUBSYSTEM=="input", {assignments}
+"#
+    )
+}
+
+/// Returns the tags and properties from the given permissions that are
+/// missing from the given udev properties of a device. Udev lists the tags
+/// of a device in the "TAGS" property, e.g. ":seat:uaccess:".
+pub fn missing_entries(
+    permissions: &TargetPermissions,
+    properties: &HashMap<String, String>,
+) -> Vec<String> {
+    let mut missing = Vec::new();
+    let device_tags = properties.get("TAGS").cloned().unwrap_or_default();
+    for tag in tags(permissions) {
+        if !device_tags.split(':').any(|t| t == tag) {
+            missing.push(format!("tag {tag}"));
+        }
+    }
+
+    let mut expected = permissions.properties.clone().unwrap_or_default();
+    if let Some(seat) = permissions.seat.as_ref() {
+        expected.insert("ID_SEAT".to_string(), seat.clone());
+    }
+    let mut expected: Vec<_> = expected.into_iter().collect();
+    expected.sort();
+    for (name, value) in expected {
+        if properties.get(&name) != Some(&value) {
+            missing.push(format!("property {name}={value}"));
+        }
+    }
+
+    missing
+}
+
+/// Apply the configured permissions to the input device with the given
+/// sysname of the given type of target device. Does nothing if no permissions
+/// are configured for the target device type.
+pub async fn apply_device(kind: &str, sysname: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let permissions = {
+        let Ok(permissions) = PERMISSIONS.read() else {
+            return Err("Failed to lock target device permissions".into());
+        };
+        resolve(permissions.as_slice(), kind)
+    };
+    let Some(permissions) = permissions else {
+        return Ok(());
+    };
+    log::debug!("Applying permissions to target device {sysname}: {permissions:?}");
+    fs::create_dir_all(RULES_PREFIX)?;
+    fs::write(
+        permissions_rule_path(sysname),
+        permissions_rule(sysname, &permissions),
+    )?;
+    seat::reload_rules().await?;
+    seat::trigger_device(sysname).await?;
+
+    if !permissions.verify.unwrap_or(true) {
+        return Ok(());
+    }
+    verify_device(sysname, &permissions).await
+}
+
+/// Verify that udev applied the given permissions to the input device with
+/// the given sysname
+async fn verify_device(
+    sysname: &str,
+    permissions: &TargetPermissions,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let timeout = format!("--timeout={SETTLE_TIMEOUT_SECS}");
+    let _ = Command::new("udevadm")
+        .args(["settle", timeout.as_str()])
+        .output()
+        .await?;
+
+    let device = ::udev::Device::from_subsystem_sysname("input".into(), sysname.into())?;
+    let properties = device.get_properties();
+    let missing = missing_entries(permissions, &properties);
+    if !missing.is_empty() {
+        return Err(format!(
+            "Permissions were not applied to target device {sysname}, missing: {}",
+            missing.join(", ")
+        )
+        .into());
+    }
+    log::debug!("Verified permissions of target device {sysname}");
+
+    Ok(())
+}
+
+/// Remove the permissions rule for the given target input device after it
+/// was removed
+pub async fn remove_device(sysname: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+    match fs::remove_file(permissions_rule_path(sysname)) {
+        Ok(_) => (),
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    }
+    seat::reload_rules().await?;
+
+    Ok(())
+}
+
+/// Remove the permissions rules of all target devices
+pub async fn remove_all() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let entries = match fs::read_dir(RULES_PREFIX) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    for entry in entries {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let filename = entry.file_name().to_string_lossy().to_string();
+        if !filename.starts_with(PERMISSIONS_RULE_PREFIX) {
+            continue;
+        }
+        fs::remove_file(entry.path())?;
+    }
+    seat::reload_rules().await?;
+
+    Ok(())
+}
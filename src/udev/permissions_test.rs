@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use crate::{
+    config::TargetPermissions,
+    udev::permissions::{missing_entries, permissions_rule, resolve},
+};
+
+fn permissions(yaml: &str) -> Vec<TargetPermissions> {
+    serde_yaml::from_str(yaml).unwrap()
+}
+
+#[test]
+fn test_resolve() {
+    let config = permissions(
+        r#"
+- uaccess: true
+  mode: "0660"
+- target_types: [xb360, xbox-elite]
+  seat: seat1
+  tags: [gaming]
+  properties:
+    ID_INPUT_JOYSTICK: "1"
+"#,
+    );
+    let resolved = resolve(&config, "xb360").unwrap();
+    assert_eq!(resolved.uaccess, Some(true));
+    assert_eq!(resolved.mode, Some("0660".to_string()));
+    assert_eq!(resolved.seat, Some("seat1".to_string()));
+    assert_eq!(resolved.tags, Some(vec!["gaming".to_string()]));
+
+    let resolved = resolve(&config, "keyboard").unwrap();
+    assert_eq!(resolved.seat, None);
+    assert_eq!(resolved.properties, None);
+
+    assert!(resolve(&config[1..], "keyboard").is_none());
+}
+
+#[test]
+fn test_permissions_rule() {
+    let config = permissions(
+        r#"
+- uaccess: true
+  seat: seat1
+  group: input
+  properties:
+    ID_INPUT_JOYSTICK: "1"
+"#,
+    );
+    let rule = permissions_rule("input42", &config[0]);
+    assert!(rule.contains(
+        r#"KERNELS=="input42", SUBSYSTEM=="input", TAG+="seat", TAG+="uaccess", ENV{ID_SEAT}="seat1", ENV{ID_INPUT_JOYSTICK}="1", GROUP="input""#
+    ));
+}
+
+#[test]
+fn test_missing_entries() {
+    let config = permissions(
+        r#"
+- uaccess: true
+  properties:
+    ID_INPUT_JOYSTICK: "1"
+"#,
+    );
+    let mut properties = HashMap::from([
+        ("TAGS".to_string(), ":seat:uaccess:".to_string()),
+        ("ID_INPUT_JOYSTICK".to_string(), "1".to_string()),
+    ]);
+    assert!(missing_entries(&config[0], &properties).is_empty());
+
+    properties.insert("TAGS".to_string(), ":seat:".to_string());
+    properties.remove("ID_INPUT_JOYSTICK");
+    assert_eq!(
+        missing_entries(&config[0], &properties),
+        vec![
+            "tag uaccess".to_string(),
+            "property ID_INPUT_JOYSTICK=1".to_string()
+        ]
+    );
+}
@@ -0,0 +1,159 @@
+//! Seat assignment of target devices. On multi-user systems, virtual input
+//! devices created by InputPlumber should only be readable by the user of the
+//! active session. Each target device is tagged for the active seat using a
+//! udev rule, so logind grants access to the active session of that seat and
+//! revokes it when the session changes. When logind reports a new active
+//! session, all target devices are re-tagged and re-permissioned. When the
+//! session is switched, composite devices also re-create their target devices
+//! so processes of the previous session lose any open handles to them.
+use std::{error::Error, fs, io::ErrorKind, sync::RwLock};
+
+use tokio::process::Command;
+
+use super::RULES_PREFIX;
+
+/// Prefix of the rule files used to assign target devices to a seat
+const SEAT_RULE_PREFIX: &str = "72-inputplumber-seat-";
+
+/// The seat target devices are assigned to. This is only set once logind
+/// reports an active session, so seat assignment is disabled on systems
+/// without logind.
+static ACTIVE_SEAT: RwLock<Option<String>> = RwLock::new(None);
+
+/// Path to the seat rule for the input device with the given sysname
+fn seat_rule_path(sysname: &str) -> String {
+    format!("{RULES_PREFIX}/{SEAT_RULE_PREFIX}{sysname}.rules")
+}
+
+/// Returns a udev rule that assigns the input device with the given sysname
+/// (e.g. "input42") and all of its device nodes to the given seat. Access is
+/// limited to the active session of the seat using the 'uaccess' tag.
+pub fn seat_rule(sysname: &str, seat: &str) -> String {
+    format!(
+        r#"# Assigns InputPlumber target device {sysname} to {seat}
+# Managed by InputPlumber, this file will be autoremoved when the device is removed.
+KERNELS=="{sysname}", SUBSYSTEM=="input", TAG+="seat", TAG+="uaccess", ENV{{ID_SEAT}}="{seat}", MODE="0600", GROUP="root"
+"#
+    )
+}
+
+/// Returns the seat target devices are currently assigned to
+pub fn get_active_seat() -> Option<String> {
+    ACTIVE_SEAT.read().ok().and_then(|seat| seat.clone())
+}
+
+/// Assign the target input device with the given sysname to the active seat.
+/// Does nothing if no seat is active.
+pub async fn assign_device(sysname: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let Some(seat) = get_active_seat() else {
+        return Ok(());
+    };
+    log::debug!("Assigning target device {sysname} to {seat}");
+    fs::create_dir_all(RULES_PREFIX)?;
+    fs::write(seat_rule_path(sysname), seat_rule(sysname, seat.as_str()))?;
+    reload_rules().await?;
+    trigger_device(sysname).await?;
+
+    Ok(())
+}
+
+/// Remove the seat rule for the given target input device after it was
+/// removed
+pub async fn unassign_device(sysname: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+    match fs::remove_file(seat_rule_path(sysname)) {
+        Ok(_) => (),
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    }
+    reload_rules().await?;
+
+    Ok(())
+}
+
+/// Remove the seat rules of all target devices
+pub async fn unassign_all() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let entries = match fs::read_dir(RULES_PREFIX) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    for entry in entries {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let filename = entry.file_name().to_string_lossy().to_string();
+        if !filename.starts_with(SEAT_RULE_PREFIX) {
+            continue;
+        }
+        fs::remove_file(entry.path())?;
+    }
+    reload_rules().await?;
+
+    Ok(())
+}
+
+/// Set the active seat and re-assign all target devices to it. This should be
+/// called whenever the active session changes so access to the target devices
+/// is re-evaluated for the new session.
+pub async fn set_active_seat(seat: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+    {
+        let Ok(mut active_seat) = ACTIVE_SEAT.write() else {
+            return Err("Failed to lock active seat".into());
+        };
+        *active_seat = Some(seat.to_string());
+    }
+
+    let entries = match fs::read_dir(RULES_PREFIX) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut sysnames = Vec::new();
+    for entry in entries {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let filename = entry.file_name().to_string_lossy().to_string();
+        let Some(sysname) = filename
+            .strip_prefix(SEAT_RULE_PREFIX)
+            .and_then(|name| name.strip_suffix(".rules"))
+        else {
+            continue;
+        };
+        fs::write(entry.path(), seat_rule(sysname, seat))?;
+        sysnames.push(sysname.to_string());
+    }
+    if sysnames.is_empty() {
+        return Ok(());
+    }
+
+    reload_rules().await?;
+    for sysname in sysnames {
+        log::debug!("Re-assigning target device {sysname} to {seat}");
+        trigger_device(sysname.as_str()).await?;
+    }
+
+    Ok(())
+}
+
+/// Reload udev rules without triggering any devices
+pub(super) async fn reload_rules() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let _ = Command::new("udevadm")
+        .args(["control", "--reload-rules"])
+        .output()
+        .await?;
+
+    Ok(())
+}
+
+/// Trigger udev to re-evaluate rules for the given input device and its
+/// device nodes
+pub(super) async fn trigger_device(sysname: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let parent = format!("/sys/class/input/{sysname}");
+    let _ = Command::new("udevadm")
+        .args(["trigger", "--action", "change", "-b", parent.as_str()])
+        .output()
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,9 @@
+use crate::udev::seat::seat_rule;
+
+#[test]
+fn test_seat_rule() {
+    let rule = seat_rule("input42", "seat1");
+    assert!(rule.contains(r#"KERNELS=="input42", SUBSYSTEM=="input""#));
+    assert!(rule.contains(r#"TAG+="uaccess""#));
+    assert!(rule.contains(r#"ENV{ID_SEAT}="seat1""#));
+}